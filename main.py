@@ -4,7 +4,9 @@ BlueBubbles GTK4 Client
 A modern GTK4 application for connecting to BlueBubbles servers
 """
 
+import getpass
 import gi
+import json
 import sys
 import os
 from pathlib import Path
@@ -20,6 +22,7 @@ gi.require_version('Gtk', '4.0')
 gi.require_version('Adw', '1')
 
 from gi.repository import Gtk, Adw, Gio, GLib
+from src import __version__
 from src.application import BlueBubblesApplication
 
 def main():
@@ -28,18 +31,52 @@ def main():
     if len(sys.argv) > 1:
         if sys.argv[1] in ['--help', '-h']:
             print("BlueBubbles GTK4 Client")
-            print("Usage: bluebubbles [options]")
+            print("Usage: bluebubbles [options] [link]")
             print("")
             print("Options:")
             print("  -h, --help     Show this help message")
             print("  --version      Show version information")
+            print("  --healthcheck  Check the server, its socket and the local database,")
+            print("                 print a JSON report and exit non-zero on failure")
+            print("  --decrypt-export <file> <output.zip>")
+            print("                 Decrypt a passphrase-protected chat export")
+            print("  --background   Start without showing a window (used at login)")
+            print("")
+            print("Links:")
+            print("  bluebubbles://chat/<guid>                       Open a conversation")
+            print("  bluebubbles://send?address=<address>&body=<text>  Open a draft to an address")
+            print("  sms:<address>?body=<text>, imessage:<address>   Same, on that service")
             return 0
         elif sys.argv[1] == '--version':
-            print("BlueBubbles Client 1.0.0")
+            print(f"BlueBubbles Client {__version__}")
+            return 0
+        elif sys.argv[1] == '--healthcheck':
+            from src.services.healthcheck import run_healthcheck
+            report, exit_code = run_healthcheck()
+            print(json.dumps(report, indent=2))
+            return exit_code
+        elif sys.argv[1] == '--decrypt-export':
+            if len(sys.argv) != 4:
+                print("Usage: bluebubbles --decrypt-export <file> <output.zip>", file=sys.stderr)
+                return 2
+            from src.utils.export_crypto import decrypt_export_file
+            try:
+                decrypt_export_file(sys.argv[2], sys.argv[3], getpass.getpass("Passphrase: "))
+            except (OSError, ValueError) as e:
+                print(f"Couldn't decrypt {sys.argv[2]}: {e}", file=sys.stderr)
+                return 1
+            print(f"Saved {sys.argv[3]}")
             return 0
     
     app = BlueBubblesApplication()
-    return app.run(sys.argv)
+    
+    # GApplication doesn't know our own options, so take them out first
+    argv = list(sys.argv)
+    if '--background' in argv:
+        argv.remove('--background')
+        app.start_in_background = True
+    
+    return app.run(argv)
 
 if __name__ == '__main__':
     sys.exit(main())
@@ -24,6 +24,11 @@ setup(
     packages=find_packages(),
     include_package_data=True,
     install_requires=requirements,
+    # Optional Python dependencies; GStreamer and poppler-glib come from the system.
+    # See src/utils/features.py for turning features off in a minimal build
+    extras_require={
+        'encryption': ['cryptography'],
+    },
     python_requires='>=3.8',
     entry_points={
         'console_scripts': [
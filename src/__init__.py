@@ -1 +1,3 @@
 # Main source package
+
+__version__ = '1.0.0'
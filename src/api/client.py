@@ -5,22 +5,90 @@ Handles all communication with the BlueBubbles server
 
 import aiohttp
 import asyncio
-from typing import Dict, List, Optional, Any
+from email.utils import parsedate_to_datetime
+from datetime import datetime, timezone
+from typing import Callable, Dict, List, Optional, Tuple, Any
 from urllib.parse import urljoin, urlparse
+import io
 import json
+import math
+import os
+import time
+
+from .events import (
+    CONNECT_FRAME, ENGINE_CLOSE, ENGINE_MESSAGE, ENGINE_OPEN, ENGINE_PING, ENGINE_PONG, SOCKET_CONNECT,
+    SOCKET_CONNECT_ERROR, SOCKET_DISCONNECT, ServerEvent, decode_event_frame, parse_open_packet,
+    parse_server_event
+)
+
+# Seconds to back off after a 429 response without a usable Retry-After header
+DEFAULT_RETRY_AFTER = 30
+
+# Longest back off a Retry-After header can ask for, in seconds
+MAX_RETRY_AFTER = 60 * 60
+
+# Bytes per request when sending an attachment in chunks
+UPLOAD_CHUNK_SIZE = 8 * 1024 * 1024
+
+# Bytes read at a time when streaming an attachment download to a file
+DOWNLOAD_CHUNK_SIZE = 256 * 1024
+
+# Default seconds before giving up on quick calls (ping, send) and long calls
+# (history pages, attachment transfers), set from Advanced settings
+DEFAULT_REQUEST_TIMEOUT = 15
+DEFAULT_LONG_REQUEST_TIMEOUT = 300
+
+_request_timeout = DEFAULT_REQUEST_TIMEOUT
+_long_request_timeout = DEFAULT_LONG_REQUEST_TIMEOUT
+
+
+class ProgressReader(io.BufferedReader):
+    """File reader calling on_progress with (bytes read, file size) as a request body streams it."""
+
+    def __init__(self, raw, size: int, on_progress: Callable[[int, int], None]):
+        super().__init__(raw)
+        self.size = size
+        self.on_progress = on_progress
+        self.sent = 0
+
+    def read(self, size: int = -1) -> bytes:
+        data = super().read(size)
+        self.sent += len(data)
+        self.on_progress(self.sent, self.size)
+        return data
+
+
+def set_request_timeouts(timeout: int, long_timeout: int):
+    """Set the timeouts in seconds used by clients created from now on."""
+    global _request_timeout, _long_request_timeout
+    _request_timeout = timeout
+    _long_request_timeout = long_timeout
+
+
+def get_request_timeouts() -> Tuple[int, int]:
+    """Get the (quick, long) request timeouts in seconds."""
+    return _request_timeout, _long_request_timeout
+
 
 class BlueBubblesClient:
     """Async client for the BlueBubbles API."""
     
-    def __init__(self, server_url: str, password: str, api_method: str = 'applescript'):
+    def __init__(self, server_url: str, password: str, api_method: str = 'applescript',
+                 on_request_failed: Optional[Callable[[str, str, str], None]] = None):
         self.server_url = server_url.rstrip('/')
         self.password = password
         self.api_method = api_method  # 'applescript' or 'private'
+        # Called with (method, endpoint, error message) when a request fails
+        self.on_request_failed = on_request_failed
         self.session = None
+        # Headers of the most recent response, e.g. for the server's Date
+        self.last_response_headers = {}
+        self.timeout, self.long_timeout = get_request_timeouts()
     
     async def __aenter__(self):
         """Async context manager entry."""
-        self.session = aiohttp.ClientSession()
+        # Quick calls use the session's timeout; long ones pass long_call to _make_request
+        self.session = aiohttp.ClientSession(timeout=aiohttp.ClientTimeout(total=self.timeout))
         return self
     
     async def __aexit__(self, exc_type, exc_val, exc_tb):
@@ -41,15 +109,23 @@ class BlueBubblesClient:
             payload['method'] = 'private-api'
         return payload
     
-    async def _make_request(self, method: str, endpoint: str, **kwargs) -> Dict[str, Any]:
-        """Make an HTTP request to the BlueBubbles server."""
+    def _long_call_timeout(self) -> aiohttp.ClientTimeout:
+        """Get the timeout for calls that move a lot of data."""
+        return aiohttp.ClientTimeout(total=self.long_timeout)
+    
+    async def _make_request(self, method: str, endpoint: str, long_call: bool = False,
+                            **kwargs) -> Dict[str, Any]:
+        """Make an HTTP request to the BlueBubbles server, with the long timeout if long_call."""
         if not self.session:
             raise RuntimeError("Client not initialized. Use async context manager.")
         
         url = self._build_url(endpoint)
+        if long_call:
+            kwargs['timeout'] = self._long_call_timeout()
         
         try:
             async with self.session.request(method, url, **kwargs) as response:
+                self.last_response_headers = response.headers
                 if response.content_type == 'application/json':
                     data = await response.json()
                 else:
@@ -58,13 +134,36 @@ class BlueBubblesClient:
                 
                 if response.status == 200:
                     return data
+                elif response.status == 429:
+                    retry_after = parse_retry_after(response.headers.get('Retry-After'))
+                    error_msg = f"HTTP 429: Server busy, retry after {retry_after:.0f}s"
+                    self._report_failure(method, endpoint, error_msg)
+                    raise BlueBubblesRateLimitError(error_msg, retry_after)
                 else:
                     error_msg = f"HTTP {response.status}: {data.get('message', 'Unknown error')}"
-                    raise BlueBubblesAPIError(error_msg)
+                    # The reason, e.g. why a send failed, is under error when the server gives one
+                    error = data.get('error')
+                    if isinstance(error, dict) and error.get('message'):
+                        error_msg += f" ({error['message']})"
+                    self._report_failure(method, endpoint, error_msg)
+                    raise BlueBubblesAPIError(error_msg, response.status)
         
         except aiohttp.ClientError as e:
             error_msg = f"Network error: {str(e)}"
+            self._report_failure(method, endpoint, error_msg)
             raise BlueBubblesAPIError(error_msg)
+        except asyncio.TimeoutError:
+            timeout = self.long_timeout if long_call else self.timeout
+            error_msg = f"Timed out after {timeout}s"
+            self._report_failure(method, endpoint, error_msg)
+            raise BlueBubblesTimeoutError(error_msg)
+    
+    def _report_failure(self, method: str, endpoint: str, error_msg: str):
+        """Pass a failed request to the failure callback, without the password."""
+        if self.on_request_failed:
+            if self.password:
+                error_msg = error_msg.replace(self.password, '***')
+            self.on_request_failed(method, endpoint, error_msg)
     
     async def test_connection(self) -> bool:
         """Test if we can connect to the BlueBubbles server."""
@@ -74,11 +173,90 @@ class BlueBubblesClient:
         except BlueBubblesAPIError:
             return False
     
+    async def ping(self) -> Optional[datetime]:
+        """Ping the server. Returns the server's clock time from the Date header, if sent."""
+        await self._make_request('GET', '/api/v1/ping')
+        try:
+            return parsedate_to_datetime(self.last_response_headers.get('Date'))
+        except (TypeError, ValueError):
+            return None
+    
+    async def check_socket(self) -> bool:
+        """Check that the server's socket.io endpoint accepts a handshake."""
+        response = await self._make_request('GET', '/socket.io/?EIO=4&transport=polling')
+        # Engine.IO answers a polling handshake with an "open" packet, type 0
+        return str(response.get('data', '')).startswith('0')
+    
+    async def listen_events(self, on_event: Callable[[ServerEvent], None],
+                            should_stop: Callable[[], bool], on_connected: Callable[[], None] = None):
+        """
+        Stay connected to the server's socket.io endpoint, passing each event to on_event.
+        
+        on_connected is called once the server accepts the connection. Returns when
+        the server closes it or should_stop says so, checked every second; raises
+        BlueBubblesAPIError if the connection can't be made or is refused, or goes
+        quiet: the server pings every pingInterval, so no ping within pingInterval
+        plus pingTimeout means the link is gone (e.g. after suspend), even if the
+        socket still looks open.
+        """
+        url = self._build_url('/socket.io/?EIO=4&transport=websocket')
+        url = 'ws' + url[len('http'):] if url.startswith('http') else url
+        
+        try:
+            async with self.session.ws_connect(url) as ws:
+                # Monotonic time by which the next ping is due, once the server is open
+                ping_deadline = None
+                heartbeat = 0.0
+                while not should_stop():
+                    if ping_deadline is not None and time.monotonic() > ping_deadline:
+                        error_msg = f"Realtime connection timed out: no ping for {heartbeat:g}s"
+                        self._report_failure('GET', '/socket.io/', error_msg)
+                        raise BlueBubblesAPIError(error_msg)
+                    try:
+                        message = await ws.receive(timeout=1.0)
+                    except asyncio.TimeoutError:
+                        continue
+                    if message.type != aiohttp.WSMsgType.TEXT:
+                        break  # Closed or failed
+                    
+                    frame = message.data
+                    if frame == ENGINE_PING:
+                        ping_deadline = time.monotonic() + heartbeat
+                        await ws.send_str(ENGINE_PONG)
+                    elif frame.startswith(ENGINE_OPEN):
+                        ping_interval, ping_timeout = parse_open_packet(frame)
+                        heartbeat = ping_interval + ping_timeout
+                        ping_deadline = time.monotonic() + heartbeat
+                        # Engine.IO is open; join the default namespace
+                        await ws.send_str(CONNECT_FRAME)
+                    elif frame.startswith(ENGINE_MESSAGE + SOCKET_CONNECT):
+                        if on_connected:
+                            on_connected()
+                    elif frame.startswith(ENGINE_MESSAGE + SOCKET_CONNECT_ERROR):
+                        error_msg = f"Realtime connection refused: {frame[2:] or 'Unknown error'}"
+                        self._report_failure('GET', '/socket.io/', error_msg)
+                        raise BlueBubblesAPIError(error_msg)
+                    elif frame in (ENGINE_CLOSE, ENGINE_MESSAGE + SOCKET_DISCONNECT):
+                        break
+                    else:
+                        event = decode_event_frame(frame)
+                        if event:
+                            on_event(parse_server_event(*event))
+        except aiohttp.ClientError as e:
+            error_msg = f"Network error: {str(e)}"
+            self._report_failure('GET', '/socket.io/', error_msg)
+            raise BlueBubblesAPIError(error_msg)
+    
     async def get_server_info(self) -> Dict[str, Any]:
         """Get server information."""
         response = await self._make_request('GET', '/api/v1/server/info')
         return response.get('data', {})
     
+    async def check_for_update(self) -> Dict[str, Any]:
+        """Ask the server whether a newer server release is available."""
+        response = await self._make_request('GET', '/api/v1/server/update/check')
+        return response.get('data', {})
+    
     async def get_icloud_account_info(self) -> Dict[str, Any]:
         """Get iCloud account information."""
         response = await self._make_request('GET', '/api/v1/icloud/account')
@@ -107,21 +285,85 @@ class BlueBubblesClient:
         )
         return response.get('data', [])
     
+    async def get_chats_page(self, limit: int, offset: int,
+                             with_data: Optional[List[str]] = None) -> Tuple[List[Dict[str, Any]], Optional[int]]:
+        """Get a page of chats, with the total number of chats if the server reports it."""
+        payload = {
+            'limit': limit,
+            'offset': offset
+        }
+        
+        if with_data:
+            payload['with'] = with_data
+        
+        response = await self._make_request(
+            'POST',
+            '/api/v1/chat/query',
+            json=payload,
+            headers={'Content-Type': 'application/json'}
+        )
+        return response.get('data', []), response.get('metadata', {}).get('total')
+    
+    async def chat_exists(self, chat_guid: str) -> bool:
+        """Check whether the server still has a chat, e.g. before dropping it from the cache."""
+        try:
+            await self._make_request('GET', f'/api/v1/chat/{chat_guid}')
+            return True
+        except BlueBubblesAPIError as e:
+            if e.status == 404:
+                return False
+            raise
+    
     async def get_chat_messages(self, chat_guid: str, limit: int = 100, offset: int = 0) -> List[Dict[str, Any]]:
         """Get messages for a specific chat."""
         endpoint = f'/api/v1/chat/{chat_guid}/message'
         # Include attachment data in the response
         params = f'?limit={limit}&offset={offset}&with=handle,attachment&sort=DESC'
         
-        response = await self._make_request('GET', endpoint + params)
+        response = await self._make_request('GET', endpoint + params, long_call=True)
+        return response.get('data', [])
+    
+    async def get_chat_messages_page(self, chat_guid: str, limit: int,
+                                     offset: int) -> Tuple[List[Dict[str, Any]], Optional[int]]:
+        """Get a page of a chat's messages, newest first, with the chat's total if the server reports it."""
+        endpoint = f'/api/v1/chat/{chat_guid}/message'
+        params = f'?limit={limit}&offset={offset}&with=handle,attachment&sort=DESC'
+        
+        response = await self._make_request('GET', endpoint + params, long_call=True)
+        return response.get('data', []), response.get('metadata', {}).get('total')
+    
+    async def get_messages_since(self, after_ms: int, limit: int = 500, offset: int = 0) -> List[Dict[str, Any]]:
+        """Get messages from every chat created after a time in Unix milliseconds, oldest first."""
+        payload = {
+            'after': after_ms,
+            'limit': limit,
+            'offset': offset,
+            'sort': 'ASC',
+            'with': ['chat', 'handle', 'attachment']
+        }
+        
+        response = await self._make_request(
+            'POST',
+            '/api/v1/message/query',
+            long_call=True,
+            json=payload,
+            headers={'Content-Type': 'application/json'}
+        )
         return response.get('data', [])
     
-    async def send_message(self, chat_guid: str, message: str) -> Dict[str, Any]:
-        """Send a text message to a chat."""
+    async def send_message(self, chat_guid: str, message: str,
+                           selected_message_guid: Optional[str] = None) -> Dict[str, Any]:
+        """Send a text message to a chat, optionally as a reply to another message."""
         payload = {
             'chatGuid': chat_guid,
             'message': message
         }
+        
+        # Replies are only supported through the private API
+        if selected_message_guid:
+            payload['selectedMessageGuid'] = selected_message_guid
+            payload['partIndex'] = 0
+        
         payload = self._add_api_method_to_payload(payload)
         
         response = await self._make_request(
@@ -156,6 +398,20 @@ class BlueBubblesClient:
         except Exception as e:
             raise
     
+    async def update_chat(self, chat_guid: str, display_name: str) -> Dict[str, Any]:
+        """Update a chat's properties (currently only the group name)."""
+        payload = {
+            'displayName': display_name
+        }
+        
+        response = await self._make_request(
+            'PUT',
+            f'/api/v1/chat/{chat_guid}',
+            json=payload,
+            headers={'Content-Type': 'application/json'}
+        )
+        return response.get('data', {})
+    
     async def mark_chat_read(self, chat_guid: str) -> bool:
         """Mark a chat as read."""
         try:
@@ -164,9 +420,14 @@ class BlueBubblesClient:
         except BlueBubblesAPIError:
             return False
     
-    async def send_attachment(self, chat_guid: str, file_path: str, message: str = "") -> Dict[str, Any]:
-        """Send an attachment to a chat."""
-        import os
+    async def send_attachment(self, chat_guid: str, file_path: str, message: str = "",
+                              is_audio_message: bool = False,
+                              on_progress: Optional[Callable[[int, int], None]] = None) -> Dict[str, Any]:
+        """
+        Send an attachment to a chat as a multipart upload, optionally as a voice memo.
+        
+        on_progress is called with (bytes sent, file size) as the file is streamed.
+        """
         from aiohttp import FormData
         
         if not os.path.exists(file_path):
@@ -177,23 +438,84 @@ class BlueBubblesClient:
         data.add_field('chatGuid', chat_guid)
         if message:
             data.add_field('message', message)
+        if is_audio_message:
+            data.add_field('isAudioMessage', 'true')
         
         # Add API method for private API
         if self.api_method == 'private':
             data.add_field('method', 'private-api')
         
         # Add the file
-        with open(file_path, 'rb') as f:
+        f = open(file_path, 'rb')
+        if on_progress:
+            f = ProgressReader(f.detach(), os.path.getsize(file_path), on_progress)
+        with f:
             data.add_field('attachment', f, filename=os.path.basename(file_path))
             
             response = await self._make_request(
                 'POST',
                 '/api/v1/message/attachment',
+                long_call=True,
                 data=data
             )
         
         return response.get('data', {})
     
+    async def send_attachment_chunked(self, chat_guid: str, file_path: str, upload_guid: str,
+                                      start: int = 0, message: str = "", is_audio_message: bool = False,
+                                      on_progress: Optional[Callable[[int, int], None]] = None) -> Dict[str, Any]:
+        """
+        Send a large attachment in UPLOAD_CHUNK_SIZE pieces.
+        
+        The server assembles the chunks sharing upload_guid and sends the attachment
+        once the last arrives. Starting at a later byte resumes an interrupted upload.
+        on_progress is called with (bytes sent, file size) after each chunk. Servers
+        without chunked uploads answer the first request with HTTP 404.
+        """
+        import os
+        from aiohttp import FormData
+        
+        if not os.path.exists(file_path):
+            raise BlueBubblesAPIError(f"File not found: {file_path}")
+        
+        file_name = os.path.basename(file_path)
+        file_size = os.path.getsize(file_path)
+        offset = start
+        
+        with open(file_path, 'rb') as f:
+            f.seek(offset)
+            while True:
+                chunk = f.read(UPLOAD_CHUNK_SIZE)
+                has_more = offset + len(chunk) < file_size
+                
+                data = FormData()
+                data.add_field('chatGuid', chat_guid)
+                data.add_field('tempGuid', upload_guid)
+                data.add_field('attachmentGuid', upload_guid)
+                data.add_field('attachmentName', file_name)
+                data.add_field('attachmentChunkStart', str(offset))
+                data.add_field('hasMore', 'true' if has_more else 'false')
+                if message and not has_more:
+                    data.add_field('message', message)
+                if is_audio_message:
+                    data.add_field('isAudioMessage', 'true')
+                if self.api_method == 'private':
+                    data.add_field('method', 'private-api')
+                data.add_field('attachmentChunk', chunk, filename=file_name)
+                
+                response = await self._make_request(
+                    'POST',
+                    '/api/v1/message/attachment/chunk',
+                    long_call=True,
+                    data=data
+                )
+                
+                offset += len(chunk)
+                if on_progress:
+                    on_progress(offset, file_size)
+                if not has_more:
+                    return response.get('data', {})
+    
     async def send_reaction(self, message_guid: str, reaction_type: str, chat_guid: str = None) -> Dict[str, Any]:
         """Send a reaction to a message."""
         payload = {
@@ -296,6 +618,23 @@ class BlueBubblesClient:
         )
         return response.get('data', {})
     
+    async def check_imessage_availability(self, address: str) -> bool:
+        """Ask whether a phone number or email address can receive iMessages."""
+        response = await self._make_request(
+            'GET', '/api/v1/handle/availability/imessage', params={'address': address}
+        )
+        return bool(response.get('data', {}).get('available'))
+    
+    async def create_facetime_link(self) -> Optional[str]:
+        """Create a FaceTime link anyone can join. Needs the Private API on the server."""
+        response = await self._make_request('POST', '/api/v1/facetime/session')
+        return (response.get('data') or {}).get('link')
+    
+    async def get_contacts(self) -> List[Dict[str, Any]]:
+        """Get the contacts known to the server's Mac."""
+        response = await self._make_request('GET', '/api/v1/contact')
+        return response.get('data', [])
+    
     async def get_contact_avatar(self, address: str) -> bytes:
         """Get contact avatar/profile picture."""
         response = await self._make_request(
@@ -318,7 +657,7 @@ class BlueBubblesClient:
         try:
             # This endpoint returns the raw image data
             async with self.session.get(
-                f"{self.base_url}/api/v1/chat/{chat_guid}/icon",
+                f"{self.server_url}/api/v1/chat/{chat_guid}/icon",
                 params={'password': self.password}
             ) as response:
                 if response.status == 200:
@@ -327,6 +666,26 @@ class BlueBubblesClient:
         except Exception:
             return None
     
+    async def set_chat_icon(self, chat_guid: str, file_path: str) -> Dict[str, Any]:
+        """Upload a new group chat icon. Requires the private API."""
+        import os
+        from aiohttp import FormData
+        
+        if not os.path.exists(file_path):
+            raise BlueBubblesAPIError(f"File not found: {file_path}")
+        
+        data = FormData()
+        with open(file_path, 'rb') as f:
+            data.add_field('icon', f, filename=os.path.basename(file_path))
+            
+            response = await self._make_request(
+                'POST',
+                f'/api/v1/chat/{chat_guid}/icon',
+                data=data
+            )
+        
+        return response.get('data', {})
+    
     async def mark_chat_read(self, chat_guid: str) -> bool:
         """Mark a chat as read."""
         try:
@@ -345,7 +704,8 @@ class BlueBubblesClient:
             # This endpoint returns the raw attachment data
             async with self.session.get(
                 f"{self.server_url}/api/v1/attachment/{attachment_guid}/download",
-                params={'password': self.password}
+                params={'password': self.password},
+                timeout=self._long_call_timeout()
             ) as response:
                 if response.status == 200:
                     return await response.read()
@@ -353,6 +713,37 @@ class BlueBubblesClient:
         except Exception:
             return None
 
+    async def download_attachment(self, attachment_guid: str, file_path: str) -> Optional[int]:
+        """
+        Stream an attachment to a file without holding it in memory; returns its size in bytes.
+        
+        The data goes to a hidden temporary file next to file_path, renamed into
+        place once complete, so a failed download never leaves a partial file.
+        Returns None if the download fails.
+        """
+        directory, name = os.path.split(file_path)
+        temp_path = os.path.join(directory, f".{name}.part")
+        try:
+            async with self.session.get(
+                f"{self.server_url}/api/v1/attachment/{attachment_guid}/download",
+                params={'password': self.password},
+                timeout=self._long_call_timeout()
+            ) as response:
+                if response.status != 200:
+                    return None
+                size = 0
+                with open(temp_path, 'wb') as f:
+                    async for chunk in response.content.iter_chunked(DOWNLOAD_CHUNK_SIZE):
+                        f.write(chunk)
+                        size += len(chunk)
+            os.replace(temp_path, file_path)
+            return size
+        except Exception:
+            return None
+        finally:
+            if os.path.exists(temp_path):
+                os.remove(temp_path)
+
     async def get_attachment_info(self, attachment_guid: str) -> Dict[str, Any]:
         """Get attachment metadata."""
         try:
@@ -367,4 +758,49 @@ class BlueBubblesClient:
 
 class BlueBubblesAPIError(Exception):
     """Exception raised for BlueBubbles API errors."""
-    pass
+    
+    def __init__(self, message: str, status: Optional[int] = None):
+        super().__init__(message)
+        # HTTP status of the failed response, None for network errors
+        self.status = status
+
+
+class BlueBubblesRateLimitError(BlueBubblesAPIError):
+    """Exception raised when the server asks us to slow down (HTTP 429)."""
+    
+    def __init__(self, message: str, retry_after: float):
+        super().__init__(message, 429)
+        self.retry_after = retry_after
+
+
+class BlueBubblesTimeoutError(BlueBubblesAPIError):
+    """Exception raised when the server doesn't answer in time; the request may still have gone through."""
+
+
+class BlueBubblesUploadTooLargeError(BlueBubblesAPIError):
+    """Exception raised when an attachment is bigger than the server accepts."""
+    
+    def __init__(self, message: str, size: int, limit: Optional[int]):
+        super().__init__(message, 413)
+        self.size = size
+        # The server's upload limit in bytes, None if it doesn't report one
+        self.limit = limit
+
+
+def parse_retry_after(value: Optional[str]) -> float:
+    """Parse a Retry-After header (seconds or an HTTP date) into seconds, at most MAX_RETRY_AFTER."""
+    if not value:
+        return DEFAULT_RETRY_AFTER
+    
+    try:
+        delay = float(value)
+    except ValueError:
+        try:
+            retry_at = parsedate_to_datetime(value)
+            delay = (retry_at - datetime.now(timezone.utc)).total_seconds()
+        except (TypeError, ValueError):
+            return DEFAULT_RETRY_AFTER
+    
+    if not math.isfinite(delay):
+        return DEFAULT_RETRY_AFTER
+    return min(max(0.0, delay), MAX_RETRY_AFTER)
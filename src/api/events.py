@@ -0,0 +1,193 @@
+"""
+Server Events
+Typed events pushed by the server over socket.io, and decoding of the frames
+that carry them
+"""
+
+import json
+import random
+from dataclasses import dataclass
+from typing import Any, Callable, Dict, Optional, Tuple, Union
+
+# Engine.IO packet types, the first character of every frame
+ENGINE_OPEN = '0'
+ENGINE_CLOSE = '1'
+ENGINE_PING = '2'
+ENGINE_PONG = '3'
+ENGINE_MESSAGE = '4'
+
+# Socket.IO packet types, the character after ENGINE_MESSAGE
+SOCKET_CONNECT = '0'
+SOCKET_DISCONNECT = '1'
+SOCKET_EVENT = '2'
+SOCKET_CONNECT_ERROR = '4'
+
+# Frame asking to join the default namespace, sent after the server's open packet
+CONNECT_FRAME = ENGINE_MESSAGE + SOCKET_CONNECT
+
+# Engine.IO heartbeat defaults in seconds, for open packets that don't give them
+DEFAULT_PING_INTERVAL = 25
+DEFAULT_PING_TIMEOUT = 20
+
+# Seconds before reconnecting a dropped socket, doubling with each failed attempt up to the
+# maximum; up to the jitter fraction is taken off at random so clients don't reconnect in step
+RECONNECT_BASE_DELAY = 2
+RECONNECT_MAX_DELAY = 60
+RECONNECT_JITTER = 0.5
+
+# Events whose payload is a message to add to its chat; group changes arrive as system messages
+NEW_MESSAGE_EVENTS = {
+    'new-message', 'group-name-change', 'participant-added', 'participant-removed', 'participant-left',
+}
+
+
+def message_chat_guid(message: Dict[str, Any]) -> Optional[str]:
+    """Get the GUID of the chat a pushed message belongs to."""
+    chats = message.get('chats') or []
+    if chats and isinstance(chats[0], dict):
+        return chats[0].get('guid')
+    return None
+
+
+@dataclass
+class NewMessage:
+    """A message arrived or was sent from another device."""
+    message: Dict[str, Any]
+
+    @property
+    def chat_guid(self) -> Optional[str]:
+        return message_chat_guid(self.message)
+
+
+@dataclass
+class MessageUpdated:
+    """A message was delivered, read, edited or unsent."""
+    message: Dict[str, Any]
+
+    @property
+    def chat_guid(self) -> Optional[str]:
+        return message_chat_guid(self.message)
+
+
+@dataclass
+class MessageSendError:
+    """A message sent from here failed after the server accepted it."""
+    message: Dict[str, Any]
+
+    @property
+    def chat_guid(self) -> Optional[str]:
+        return message_chat_guid(self.message)
+
+
+@dataclass
+class TypingIndicator:
+    """Someone started or stopped typing in a chat."""
+    chat_guid: str
+    typing: bool
+
+
+@dataclass
+class ChatRead:
+    """A chat was read, or marked unread, on another device."""
+    chat_guid: str
+    read: bool
+
+
+@dataclass
+class ServerUpdate:
+    """A new version of the server is available."""
+    version: Optional[str]
+
+
+@dataclass
+class Connected:
+    """The socket connected; after a reconnect, missed messages are fetched."""
+    reconnected: bool
+
+
+@dataclass
+class Disconnected:
+    """The socket dropped or couldn't connect, and will be retried."""
+    reason: Optional[str]
+
+
+@dataclass
+class UnknownEvent:
+    """An event this client doesn't handle, kept for logging."""
+    name: str
+    data: Any
+
+
+ServerEvent = Union[
+    NewMessage, MessageUpdated, MessageSendError, TypingIndicator, ChatRead, ServerUpdate,
+    Connected, Disconnected, UnknownEvent
+]
+
+
+def reconnect_delay(attempt: int, rand: Callable[[], float] = random.random) -> float:
+    """Get the seconds to wait before a reconnect attempt, counting from 0 after the last success."""
+    delay = min(RECONNECT_BASE_DELAY * 2 ** attempt, RECONNECT_MAX_DELAY)
+    return delay * (1 - RECONNECT_JITTER * rand())
+
+
+def parse_server_event(name: str, data: Any) -> ServerEvent:
+    """Turn an event's name and payload into a typed event; malformed payloads become UnknownEvent."""
+    if not isinstance(data, dict):
+        return UnknownEvent(name, data)
+
+    if name in NEW_MESSAGE_EVENTS:
+        return NewMessage(data)
+    if name == 'updated-message':
+        return MessageUpdated(data)
+    if name == 'message-send-error':
+        return MessageSendError(data)
+    if name == 'typing-indicator' and data.get('guid'):
+        return TypingIndicator(data['guid'], bool(data.get('display')))
+    if name == 'chat-read-status-changed' and data.get('chatGuid'):
+        return ChatRead(data['chatGuid'], bool(data.get('read', True)))
+    if name == 'server-update':
+        return ServerUpdate(data.get('version'))
+    return UnknownEvent(name, data)
+
+
+def parse_open_packet(frame: str) -> Tuple[float, float]:
+    """
+    Get the (ping interval, ping timeout) in seconds from an Engine.IO open packet.
+
+    The server pings every interval and drops clients that don't answer within
+    the timeout; a client not pinged within both can assume the link is gone.
+    """
+    try:
+        data = json.loads(frame[len(ENGINE_OPEN):])
+    except ValueError:
+        data = None
+    if not isinstance(data, dict):
+        data = {}
+
+    def seconds(key: str, default: float) -> float:
+        value = data.get(key)
+        if isinstance(value, (int, float)) and not isinstance(value, bool) and value > 0:
+            return value / 1000
+        return default
+
+    return seconds('pingInterval', DEFAULT_PING_INTERVAL), seconds('pingTimeout', DEFAULT_PING_TIMEOUT)
+
+
+def decode_event_frame(frame: str) -> Optional[Tuple[str, Any]]:
+    """
+    Get the (name, data) of a socket.io event frame, e.g. '42["new-message",{...}]'.
+
+    Returns None for frames that aren't events, or don't parse.
+    """
+    if not frame.startswith(ENGINE_MESSAGE + SOCKET_EVENT):
+        return None
+
+    # An acknowledgement id may sit between the packet type and the payload
+    payload = frame[2:].lstrip('0123456789')
+    try:
+        packet = json.loads(payload)
+    except json.JSONDecodeError:
+        return None
+    if not isinstance(packet, list) or not packet or not isinstance(packet[0], str):
+        return None
+    return packet[0], packet[1] if len(packet) > 1 else None
@@ -7,16 +7,44 @@ import gi
 gi.require_version('Gtk', '4.0')
 gi.require_version('Adw', '1')
 
-from gi.repository import Gtk, Adw, Gio, GLib
-from pathlib import Path
-from pathlib import Path
+from gi.repository import Gtk, Adw, Gdk, Gio, GLib
+import math
+import os
+import sys
+import time
+from typing import List
 
+from . import __version__
 from .config.manager import ConfigManager
 from .ui.login_window import LoginWindow
 from .ui.main_window import MainWindow
+from .ui.chat_window import ChatWindow, CompactChatWindow
 from .ui.preferences_dialog import PreferencesDialog
+from .ui.theme import ThemeStyles
+from .ui.capability_checklist_dialog import CapabilityChecklistDialog
+from .ui.onboarding_dialog import OnboardingDialog, has_whats_new
+from .api.client import set_request_timeouts
 from .db.manager import DatabaseManager
-from .services.chat_service import ChatService
+from .models.app_model import CHATS
+from .services.chat_service import SHUTDOWN_TIMEOUT, ChatService
+from .services.attachment_cache import MEMORY_CACHE_BYTES, REDUCED_MEMORY_CACHE_BYTES
+from .services.debug_bundle import format_activity, get_secrets
+from .services.feedback import FeedbackPlayer, is_do_not_disturb
+from .services.launcher import LauncherEntry
+from .services.power import MeteredMonitor, PowerMonitor, POWER_PROFILES
+from .services.notifications import format_notification, format_reminder
+from .utils.crash_reports import LOG_TAIL_LINES, CrashReporter
+from .utils.links import parse_message_link
+from .utils.otp import find_chat_code
+from .utils.outbox import Outbox
+from .utils.pending_actions import PendingActionQueue
+
+# Config keys that only take effect after a restart
+RESTART_CONFIG_KEYS = {'server.url', 'server.password'}
+
+# Longest wait in seconds before checking reminders again. GLib timers stop while
+# the computer sleeps, so long waits would make reminders late after a resume.
+REMINDER_CHECK_INTERVAL = 60
 
 class BlueBubblesApplication(Adw.Application):
     """Main application class that manages the entire application lifecycle."""
@@ -24,7 +52,7 @@ class BlueBubblesApplication(Adw.Application):
     def __init__(self):
         super().__init__(
             application_id='com.github.bluebubbles.client',
-            flags=Gio.ApplicationFlags.DEFAULT_FLAGS
+            flags=Gio.ApplicationFlags.HANDLES_OPEN
         )
         
         self.config_manager = ConfigManager()
@@ -32,35 +60,77 @@ class BlueBubblesApplication(Adw.Application):
         # Initialize database and services
         self.db_manager = DatabaseManager()
         self.chat_service = ChatService(self.db_manager, self.config_manager)
+        self.launcher = LauncherEntry(self.get_application_id())
+        # Deletions waiting out their undo toast
+        self.pending_actions = PendingActionQueue(GLib.timeout_add_seconds, GLib.source_remove)
+        # Message sends, kept in order per chat
+        self.outbox = Outbox()
+        # The launcher badge follows the unread total of the chat list
+        self.chat_service.model.connect(CHATS, self.on_chats_changed)
+        self.feedback = FeedbackPlayer(self.get_application_id())
+        self.power_monitor = None
+        self.metered_monitor = None
+        # Desktop animation setting, restored when leaving battery saver
+        self.system_animations = None
         
         self.main_window = None
         self.login_window = None
+        # Conversations opened in their own windows, by chat GUID
+        self.chat_windows = {}
+        self.compact_window = None
+        # Started at login: create the main window without showing it
+        self.start_in_background = False
+        # Timer for the next due reminder
+        self.reminder_timeout_id = None
+        self.theme_styles = None
+        self.crash_reporter = CrashReporter(
+            __version__, self.get_recent_log_lines,
+            in_main_loop=lambda: GLib.main_depth() > 0,
+            on_error=lambda summary: self.chat_service.log_activity('error', summary)
+        )
+        # Report of a crash in the last run, offered once a window is up
+        self.crash_report = None
         
         self.connect('activate', self.on_activate)
+        self.connect('open', self.on_open)
         self.connect('startup', self.on_startup)
+        self.connect('shutdown', self.on_shutdown)
     
     def on_startup(self, app):
         """Called when the application starts up."""
+        self.crash_report = self.crash_reporter.install()
         self.setup_actions()
         self.apply_theme_preference()
+        self.load_styles()
+        self.apply_request_timeouts()
+        self.power_monitor = PowerMonitor(self.apply_power_profile)
+        self.metered_monitor = MeteredMonitor(self.apply_power_profile)
+        self.apply_power_profile()
+        self.apply_reduced_effects()
+        self.watch_config_file()
+        # Reminders due while the app wasn't running are shown now
+        self.arm_reminders()
+    
+    def on_shutdown(self, app):
+        """
+        Finish or save pending work before quitting, within SHUTDOWN_TIMEOUT seconds.
+        
+        Deletions waiting on their undo toast are carried out, drafts and the
+        window size saved, and queued sends given the time left to go out.
+        """
+        deadline = time.monotonic() + SHUTDOWN_TIMEOUT
+        self.pending_actions.flush()
+        for window in [self.main_window, self.compact_window] + list(self.chat_windows.values()):
+            if window:
+                window.save_state()
+        self.outbox.wait_until_empty(max(deadline - time.monotonic(), 0))
+        self.chat_service.shutdown(max(deadline - time.monotonic(), 0))
     
     def load_styles(self):
-        """Load custom CSS styles."""
-        try:
-            css_provider = Gtk.CssProvider()
-            css_path = Path(__file__).parent / 'ui' / 'styles.css'
-            css_provider.load_from_path(str(css_path))
-            
-            # Apply to default display
-            if self.main_window:
-                display = self.main_window.get_display()
-                Gtk.StyleContext.add_provider_for_display(
-                    display,
-                    css_provider,
-                    Gtk.STYLE_PROVIDER_PRIORITY_APPLICATION
-                )
-        except Exception as e:
-            pass  # Silently handle CSS loading errors
+        """Load custom CSS styles for every window, following dark mode, high contrast and the accent."""
+        display = Gdk.Display.get_default()
+        if display:
+            self.theme_styles = ThemeStyles(display, self.config_manager)
         
     def on_activate(self, app):
         """Called when the application is activated."""
@@ -68,6 +138,28 @@ class BlueBubblesApplication(Adw.Application):
             self.show_main_window()
         else:
             self.show_login_window()
+        
+        if self.config_manager.errors:
+            GLib.idle_add(self.show_config_errors)
+        if self.crash_report:
+            GLib.idle_add(self.show_crash_report)
+    
+    def on_open(self, app, files, n_files, hint):
+        """Open bluebubbles://, sms: and imessage: links passed on the command line or by other apps."""
+        links = [parse_message_link(file.get_uri()) for file in files]
+        
+        if not self.config_manager.has_valid_config():
+            self.show_login_window()
+            return
+        
+        # A link is a request to see the conversation, even when started at login
+        self.start_in_background = False
+        self.show_main_window()
+        for link in links:
+            if link:
+                self.main_window.open_message_link(link)
+            else:
+                self.main_window.show_toast("Unsupported link")
     
     def setup_actions(self):
         """Set up application-wide actions."""
@@ -84,6 +176,31 @@ class BlueBubblesApplication(Adw.Application):
         about_action = Gio.SimpleAction.new('about', None)
         about_action.connect('activate', self.on_about_action)
         self.add_action(about_action)
+        
+        tour_action = Gio.SimpleAction.new('show-tour', None)
+        tour_action.connect('activate', self.on_show_tour_action)
+        self.add_action(tour_action)
+        
+        checklist_action = Gio.SimpleAction.new('server-checklist', None)
+        checklist_action.connect('activate', lambda action, param: self.show_capability_checklist())
+        self.add_action(checklist_action)
+        
+        # Activated from notifications with the chat GUID
+        open_chat_action = Gio.SimpleAction.new('open-chat', GLib.VariantType.new('s'))
+        open_chat_action.connect('activate', self.on_open_chat_action)
+        self.add_action(open_chat_action)
+        
+        # Copies a one-time code from a notification
+        copy_code_action = Gio.SimpleAction.new('copy-code', GLib.VariantType.new('s'))
+        copy_code_action.connect('activate', self.on_copy_code_action)
+        self.add_action(copy_code_action)
+        
+        # Window-level actions
+        self.set_accels_for_action('win.search', ['<primary>f'])
+        self.set_accels_for_action('win.find-in-chat', ['<primary><shift>f'])
+        self.set_accels_for_action('win.quick-switch', ['<primary>k'])
+        for number in range(1, 10):
+            self.set_accels_for_action(f'win.open-pinned({number})', [f'<primary>{number}'])
     
     def show_login_window(self):
         """Show the login window."""
@@ -94,9 +211,213 @@ class BlueBubblesApplication(Adw.Application):
     def show_main_window(self):
         """Show the main application window."""
         if self.main_window is None:
+            self.chat_service.log_activity('session', f"Started BlueBubbles Client {__version__}")
             self.main_window = MainWindow(application=self)
-            self.load_styles()  # Load styles after window is created
+            
+            if self.db_manager.repaired_backup:
+                self.chat_service.log_activity(
+                    'database_repaired',
+                    "The local cache was damaged and has been rebuilt; "
+                    f"the old copy is at {self.db_manager.repaired_backup}"
+                )
+                self.main_window.show_toast("The local cache was damaged and has been rebuilt", timeout=5)
+        
+        if self.start_in_background:
+            self.start_in_background = False
+            return
         self.main_window.present()
+        
+        # Show the tour or release notes once the window is on screen
+        GLib.idle_add(self.show_onboarding_if_needed)
+    
+    def on_chats_changed(self, chat_guid):
+        """Publish the total unread count when the chat list changes."""
+        self.launcher.set_count(self.chat_service.model.unread_total)
+    
+    def open_chat_window(self, chat):
+        """Open a chat in its own window, or raise it if it is already open."""
+        if chat.guid not in self.chat_windows:
+            self.chat_windows[chat.guid] = ChatWindow(self, chat)
+        self.chat_windows[chat.guid].present()
+    
+    def open_compact_window(self, chat):
+        """Show a chat in the compact mini window, replacing any other chat there."""
+        if self.compact_window and self.compact_window.chat.guid != chat.guid:
+            self.compact_window.close()
+        if self.compact_window is None:
+            self.compact_window = CompactChatWindow(self, chat)
+        self.compact_window.present()
+        if self.main_window:
+            self.main_window.set_compact_mode_state(True)
+    
+    def close_compact_window(self):
+        """Close the compact mini window."""
+        if self.compact_window:
+            self.compact_window.close()
+    
+    def forget_chat_window(self, window):
+        """Forget a chat window once it is closed."""
+        if window is self.compact_window:
+            self.compact_window = None
+            if self.main_window:
+                self.main_window.set_compact_mode_state(False)
+        elif self.chat_windows.get(window.chat.guid) is window:
+            del self.chat_windows[window.chat.guid]
+    
+    def watch_config_file(self):
+        """Apply edits to bb.toml made while the app is running."""
+        config_file = Gio.File.new_for_path(str(self.config_manager.config_file))
+        self.config_monitor = config_file.monitor_file(Gio.FileMonitorFlags.NONE, None)
+        self.config_monitor.connect('changed', self.on_config_file_changed)
+    
+    def on_config_file_changed(self, monitor, file, other_file, event_type):
+        """Reload the config once an outside edit is finished."""
+        # Editors that save by replacing the file only report it as created
+        if event_type not in (Gio.FileMonitorEvent.CHANGES_DONE_HINT,
+                              Gio.FileMonitorEvent.CREATED):
+            return
+        
+        changed_keys = self.config_manager.reload()
+        if self.config_manager.errors:
+            self.show_config_errors()
+        if not changed_keys:
+            return
+        
+        if 'appearance.dark_mode' in changed_keys:
+            self.apply_theme_preference()
+        
+        if 'appearance.accent_color' in changed_keys and self.theme_styles:
+            self.theme_styles.update()
+        
+        if any(key.startswith('power.') or key == 'app.message_check_interval' for key in changed_keys):
+            self.apply_power_profile()
+        
+        if 'appearance.reduced_effects' in changed_keys:
+            self.apply_reduced_effects()
+        
+        if any(key in ('advanced.request_timeout', 'advanced.long_request_timeout') for key in changed_keys):
+            self.apply_request_timeouts()
+        
+        if any(key in ('appearance.clock_format', 'appearance.date_format',
+                       'appearance.relative_timestamps') for key in changed_keys):
+            for window in self.get_windows():
+                if hasattr(window, 'refresh_timestamps'):
+                    window.refresh_timestamps()
+        
+        if any(key in ('appearance.avatar_size', 'appearance.preview_lines',
+                       'appearance.show_chat_timestamps', 'appearance.compact_chat_list')
+               for key in changed_keys):
+            for window in self.get_windows():
+                if hasattr(window, 'refresh_chat_list_style'):
+                    window.refresh_chat_list_style()
+        
+        # Notification settings need nothing: they're read for each notification
+        if RESTART_CONFIG_KEYS.intersection(changed_keys):
+            self.show_restart_prompt()
+    
+    def show_restart_prompt(self):
+        """Offer to restart after the server credentials changed."""
+        parent = self.get_active_window()
+        if not parent:
+            return
+        
+        dialog = Adw.AlertDialog()
+        dialog.set_heading("Restart Required")
+        dialog.set_body("The server settings in bb.toml changed. Restart BlueBubbles to connect with them.")
+        dialog.add_response("later", "Later")
+        dialog.add_response("restart", "Restart Now")
+        dialog.set_response_appearance("restart", Adw.ResponseAppearance.SUGGESTED)
+        dialog.set_default_response("restart")
+        dialog.set_close_response("later")
+        dialog.connect("response", self.on_restart_response)
+        dialog.present(parent)
+    
+    def on_restart_response(self, dialog, response):
+        """Restart the app in place."""
+        if response == "restart":
+            self.on_shutdown(self)
+            os.execv(sys.executable, [sys.executable] + sys.argv)
+    
+    def show_config_errors(self):
+        """Tell the user about problems found in bb.toml."""
+        parent = self.get_active_window()
+        if not parent:
+            return False
+        
+        dialog = Adw.AlertDialog()
+        dialog.set_heading("Configuration Problems")
+        dialog.set_body(
+            "Some settings in bb.toml couldn't be used, so their defaults apply:\n\n"
+            + "\n".join(f"• {error}" for error in self.config_manager.errors)
+        )
+        dialog.add_response("ok", "OK")
+        dialog.present(parent)
+        
+        # Only report them once per run
+        self.config_manager.errors = []
+        return False
+    
+    def get_recent_log_lines(self) -> List[str]:
+        """Get the last activity log lines for a crash report, with credentials removed."""
+        activity = self.chat_service.get_activity_log(LOG_TAIL_LINES)
+        return format_activity(activity, get_secrets(self.config_manager.get_all()))
+    
+    def show_crash_report(self):
+        """Tell the user the last run crashed, and offer to open its report."""
+        parent = self.get_active_window()
+        if not parent:
+            return False
+        
+        report_path = str(self.crash_report)
+        dialog = Adw.AlertDialog()
+        dialog.set_heading("BlueBubbles Quit Unexpectedly")
+        dialog.set_body(
+            "A crash report was saved. It has no messages or credentials, "
+            "and can be attached to a bug report.\n\n" + report_path
+        )
+        dialog.add_response("close", "Close")
+        dialog.add_response("open", "Open Report")
+        dialog.set_response_appearance("open", Adw.ResponseAppearance.SUGGESTED)
+        dialog.set_default_response("open")
+        dialog.set_close_response("close")
+        dialog.connect("response", self.on_crash_report_response, parent, report_path)
+        dialog.present(parent)
+        
+        # Only offered once
+        self.crash_report = None
+        return False
+    
+    def on_crash_report_response(self, dialog, response, parent, report_path: str):
+        """Open the crash report in the default text viewer."""
+        if response == "open":
+            Gtk.FileLauncher.new(Gio.File.new_for_path(report_path)).launch(parent, None, None, None)
+    
+    def show_onboarding_if_needed(self):
+        """Show the first-run tour, or the "What's new" notes after an upgrade."""
+        if not self.main_window:
+            return False
+        
+        if not self.config_manager.has_seen_tour():
+            self.show_onboarding('tour')
+        elif (self.config_manager.get_last_seen_version() != __version__
+                and has_whats_new(__version__)):
+            self.show_onboarding('whats_new')
+        else:
+            self.config_manager.set_last_seen_version(__version__)
+        
+        return False
+    
+    def show_onboarding(self, mode: str):
+        """Present the onboarding dialog in the given mode ('tour' or 'whats_new')."""
+        dialog = OnboardingDialog(mode=mode, version=__version__)
+        
+        def on_closed(dialog):
+            # Dismissing counts as seen so we never nag twice
+            self.config_manager.mark_tour_seen()
+            self.config_manager.set_last_seen_version(__version__)
+        
+        dialog.connect('closed', on_closed)
+        dialog.present(self.main_window)
     
     def get_chat_service(self) -> ChatService:
         """Get the chat service instance."""
@@ -107,6 +428,14 @@ class BlueBubblesApplication(Adw.Application):
         if self.login_window:
             self.login_window.close()
         self.show_main_window()
+        # Queued after the tour, so it's on top and seen first
+        GLib.idle_add(self.show_capability_checklist)
+    
+    def show_capability_checklist(self):
+        """Check which server features work and show how to fix the rest."""
+        if self.main_window:
+            CapabilityChecklistDialog(self).present(self.main_window)
+        return False
     
     def on_quit_action(self, action, param):
         """Handle quit action."""
@@ -126,6 +455,143 @@ class BlueBubblesApplication(Adw.Application):
         if self.main_window:
             self.main_window.show_about_dialog()
     
+    def on_show_tour_action(self, action, param):
+        """Handle show tour action."""
+        if self.main_window:
+            self.show_onboarding('tour')
+    
+    def on_open_chat_action(self, action, param):
+        """Handle open chat action from a notification."""
+        if self.main_window:
+            self.main_window.present()
+            self.main_window.select_chat(param.get_string())
+    
+    def on_copy_code_action(self, action, param):
+        """Copy a one-time code from a notification to the clipboard."""
+        Gdk.Display.get_default().get_clipboard().set(param.get_string())
+    
+    def notify_new_message(self, chat, quiet: bool = False):
+        """Send a desktop notification for the latest message in a chat, at low priority if quiet."""
+        sender = None
+        if chat.is_group_chat and chat.last_message_address:
+            address = chat.last_message_address
+            sender = self.chat_service.get_contact_name(address) or address.split('@')[0]
+        
+        title, body = format_notification(
+            self.config_manager.get_notification_preview(),
+            chat.display_title, chat.last_message_text, sender
+        )
+        
+        notification = Gio.Notification.new(title)
+        if body:
+            notification.set_body(body)
+        notification.set_default_action_and_target('app.open-chat', GLib.Variant('s', chat.guid))
+        code = find_chat_code(chat)
+        if code:
+            # The code only shows on the button when the preview setting allows message text
+            label = f"Copy Code {code}" if self.config_manager.get_notification_preview() == 'full' else "Copy Code"
+            notification.add_button_with_target(label, 'app.copy-code', GLib.Variant('s', code))
+        if quiet:
+            # Low priority notifications go straight to the tray, without a banner or sound
+            notification.set_priority(Gio.NotificationPriority.LOW)
+        
+        # One notification per chat, replaced as new messages arrive
+        self.send_notification(f"chat-{chat.guid}", notification)
+    
+    def arm_reminders(self):
+        """Set a timer for the soonest pending reminder, replacing any set before."""
+        if self.reminder_timeout_id:
+            GLib.source_remove(self.reminder_timeout_id)
+            self.reminder_timeout_id = None
+        
+        next_time = self.chat_service.get_next_reminder_time()
+        if next_time is None:
+            return
+        
+        delay = min(max(next_time / 1000 - time.time(), 0), REMINDER_CHECK_INTERVAL)
+        self.reminder_timeout_id = GLib.timeout_add_seconds(math.ceil(delay), self.on_reminder_timeout)
+    
+    def on_reminder_timeout(self):
+        """Show the reminders that are due and wait for the next."""
+        self.reminder_timeout_id = None
+        for reminder in self.chat_service.take_due_reminders():
+            self.notify_reminder(reminder)
+        self.arm_reminders()
+        return False
+    
+    def notify_reminder(self, reminder):
+        """Send a desktop notification for a due message reminder."""
+        chat = self.chat_service.get_chat_by_guid(reminder['chat_guid'])
+        chat_title = chat.display_title if chat else reminder['chat_guid']
+        title, body = format_reminder(
+            self.config_manager.get_notification_preview(), chat_title, reminder['message_text']
+        )
+        
+        notification = Gio.Notification.new(title)
+        if body:
+            notification.set_body(body)
+        notification.set_default_action_and_target(
+            'app.open-chat', GLib.Variant('s', chat.guid if chat else reminder['chat_guid'])
+        )
+        # Asked for by the user, so shown even when a label quietens the chat
+        notification.set_priority(Gio.NotificationPriority.HIGH)
+        self.send_notification(f"reminder-{reminder['id']}", notification)
+    
+    def play_feedback(self, event: str):
+        """Play a message sound and vibrate, unless turned off or Do Not Disturb is on."""
+        if not self.config_manager.get_message_sounds() or is_do_not_disturb():
+            return
+        self.feedback.play(event)
+    
+    def get_active_power_profile(self) -> str:
+        """Get the power profile in effect, accounting for automatic battery saver and metered networks."""
+        if (self.config_manager.get_auto_battery_saver()
+                and self.power_monitor and self.power_monitor.is_battery_low()):
+            return 'battery_saver'
+        profile = self.config_manager.get_power_profile()
+        # Battery saver already skips downloads, so it wins over low bandwidth
+        if (profile != 'battery_saver' and self.config_manager.get_auto_low_bandwidth()
+                and self.metered_monitor and self.metered_monitor.is_metered()):
+            return 'low_bandwidth'
+        return profile
+    
+    def get_power_setting(self, name: str):
+        """Get a behaviour setting of the active power profile."""
+        return POWER_PROFILES[self.get_active_power_profile()][name]
+    
+    def get_message_check_interval(self) -> int:
+        """Get the message check interval for the active power profile."""
+        interval = self.get_power_setting('check_interval')
+        if interval is None:
+            interval = self.config_manager.get_message_check_interval()
+        return interval
+    
+    def apply_power_profile(self):
+        """Apply the active power profile to polling and animations."""
+        settings = Gtk.Settings.get_default()
+        if settings:
+            if self.system_animations is None:
+                self.system_animations = settings.get_property('gtk-enable-animations')
+            animations = (self.system_animations and self.get_power_setting('animations')
+                          and not self.config_manager.get_reduced_effects())
+            settings.set_property('gtk-enable-animations', animations)
+        
+        self.chat_service.check_interval = self.get_message_check_interval()
+    
+    def apply_reduced_effects(self):
+        """Turn reduced effects on or off: shadows, animations and the in-memory image cache."""
+        if self.theme_styles:
+            self.theme_styles.update()
+        self.apply_power_profile()
+        reduced = self.config_manager.get_reduced_effects()
+        self.chat_service.attachment_cache.set_memory_limit(
+            REDUCED_MEMORY_CACHE_BYTES if reduced else MEMORY_CACHE_BYTES
+        )
+    
+    def apply_request_timeouts(self):
+        """Apply the configured server request timeouts to new connections."""
+        set_request_timeouts(*self.config_manager.get_request_timeouts())
+    
     def apply_theme_preference(self):
         """Apply the saved theme preference."""
         dark_mode = self.config_manager.get('appearance.dark_mode', False)
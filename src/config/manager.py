@@ -3,10 +3,15 @@ Configuration Manager
 Handles loading and saving configuration from/to bb.toml in user's config directory
 """
 
+import copy
 import os
+import shutil
 import toml
 from pathlib import Path
-from typing import Dict, Optional, Any
+from typing import Dict, List, Optional, Any, Tuple
+from ..utils.chat_list_style import ChatListStyle
+from ..utils.time_format import TimeFormat
+from .schema import ENV_OVERRIDES, validate_config
 
 class ConfigManager:
     """Manages application configuration stored in bb.toml."""
@@ -15,6 +20,12 @@ class ConfigManager:
         self.config_dir = self._get_config_dir()
         self.config_file = self.config_dir / "bb.toml"
         self._config_data = {}
+        # Problems found while loading bb.toml, shown to the user on startup
+        self.errors: List[str] = []
+        # Invalid values from bb.toml, not used but written back on save until replaced
+        self._rejected: Dict[str, Any] = {}
+        # Contents of bb.toml as last read or written, to tell our own saves apart
+        self._file_text = None
         self._load_config()
     
     def _get_config_dir(self) -> Path:
@@ -28,12 +39,27 @@ class ConfigManager:
     
     def _load_config(self):
         """Load configuration from bb.toml file."""
+        self.errors = []
+        self._rejected = {}
         if self.config_file.exists():
             try:
-                with open(self.config_file, 'r', encoding='utf-8') as f:
-                    self._config_data = toml.load(f)
-            except (toml.TomlDecodeError, IOError) as e:
-                # print(f"Error loading config: {e}")
+                self._file_text = self.config_file.read_text(encoding='utf-8')
+                self._config_data, self.errors, self._rejected = validate_config(toml.loads(self._file_text))
+            except toml.TomlDecodeError as e:
+                # Keep the broken file around, since the next save replaces it
+                backup_file = self.config_file.with_suffix('.toml.invalid')
+                try:
+                    shutil.copyfile(self.config_file, backup_file)
+                    backup_note = f"A copy was saved as {backup_file.name}"
+                except OSError as backup_error:
+                    backup_note = f"A copy couldn't be saved ({backup_error})"
+                self.errors = [
+                    f"bb.toml is not valid TOML (line {e.lineno}, column {e.colno}): {e.msg}. "
+                    f"{backup_note}, and defaults are used."
+                ]
+                self._config_data = {}
+            except IOError as e:
+                self.errors = [f"Couldn't read bb.toml: {e}"]
                 self._config_data = {}
         else:
             self._config_data = {}
@@ -44,13 +70,52 @@ class ConfigManager:
         self.config_dir.mkdir(parents=True, exist_ok=True)
         
         try:
-            with open(self.config_file, 'w', encoding='utf-8') as f:
-                toml.dump(self._config_data, f)
+            self._file_text = toml.dumps(_merge_missing(self._config_data, self._rejected))
+            self.config_file.write_text(self._file_text, encoding='utf-8')
         except IOError as e:
             pass  # Silently handle config save errors
     
+    def reload(self) -> List[str]:
+        """
+        Re-read bb.toml after it was changed outside the app.
+        
+        Returns the dotted keys whose values changed. A file that can't be parsed
+        leaves the current settings in place and is reported through errors.
+        """
+        try:
+            text = self.config_file.read_text(encoding='utf-8')
+        except IOError:
+            return []
+        
+        if text == self._file_text:
+            return []  # Our own save
+        
+        try:
+            data, self.errors, rejected = validate_config(toml.loads(text))
+        except toml.TomlDecodeError as e:
+            self.errors = [
+                f"bb.toml is not valid TOML (line {e.lineno}, column {e.colno}): {e.msg}. "
+                f"The previous settings stay in use until it is fixed."
+            ]
+            return []
+        
+        old_values = _flatten(self._config_data)
+        new_values = _flatten(data)
+        self._config_data = data
+        self._rejected = rejected
+        self._file_text = text
+        
+        return sorted(
+            key for key in old_values.keys() | new_values.keys()
+            if old_values.get(key) != new_values.get(key)
+        )
+    
     def get(self, key: str, default: Any = None) -> Any:
-        """Get a configuration value."""
+        """Get a configuration value. Environment overrides take precedence."""
+        env_var = ENV_OVERRIDES.get(key)
+        if env_var and os.environ.get(env_var):
+            return os.environ[env_var]
+        
         keys = key.split('.')
         value = self._config_data
         
@@ -75,8 +140,19 @@ class ConfigManager:
         
         # Set the final value
         config[keys[-1]] = value
+        
+        # A valid value replaces an invalid one kept from the file
+        rejected = self._rejected
+        for k in keys[:-1]:
+            rejected = rejected.get(k) if isinstance(rejected, dict) else None
+        if isinstance(rejected, dict):
+            rejected.pop(keys[-1], None)
         self._save_config()
     
+    def get_all(self) -> Dict[str, Any]:
+        """Get a copy of the whole configuration."""
+        return copy.deepcopy(self._config_data)
+    
     def has_valid_config(self) -> bool:
         """Check if we have a valid configuration for connecting to BlueBubbles."""
         server_url = self.get('server.url')
@@ -98,8 +174,9 @@ class ConfigManager:
     
     def clear_server_config(self):
         """Clear server configuration."""
-        if 'server' in self._config_data:
-            del self._config_data['server']
+        if 'server' in self._config_data or 'server' in self._rejected:
+            self._config_data.pop('server', None)
+            self._rejected.pop('server', None)
             self._save_config()
     
     def get_appearance_config(self) -> Dict[str, Any]:
@@ -120,6 +197,10 @@ class ConfigManager:
         """Get the text width preference."""
         return self.get('appearance.text_width', 80)
     
+    def get_reduced_effects(self) -> bool:
+        """Get whether animations, shadows and cached images are cut down for slow graphics."""
+        return self.get('appearance.reduced_effects', False)
+    
     def get_api_method(self) -> str:
         """Get the API method preference (applescript or private)."""
         return self.get('advanced.api_method', 'applescript')
@@ -129,3 +210,147 @@ class ConfigManager:
         if method not in ['applescript', 'private']:
             raise ValueError("API method must be 'applescript' or 'private'")
         self.set('advanced.api_method', method)
+    
+    def get_request_timeouts(self) -> Tuple[int, int]:
+        """Get the (quick, long) server request timeouts in seconds."""
+        return (
+            self.get('advanced.request_timeout', 15),
+            self.get('advanced.long_request_timeout', 300),
+        )
+    
+    def get_message_check_interval(self) -> int:
+        """Get the message check interval in seconds used by the balanced profile."""
+        return self.get('app.message_check_interval', 3)
+    
+    def get_run_in_background(self) -> bool:
+        """Get whether closing the main window keeps the app running for notifications."""
+        return self.get('app.run_in_background', False)
+    
+    def get_autostart(self) -> bool:
+        """Get whether the app starts in the background at login."""
+        return self.get('app.autostart', False)
+    
+    def get_power_profile(self) -> str:
+        """Get the power profile (performance, balanced or battery_saver)."""
+        return self.get('power.profile', 'balanced')
+    
+    def set_power_profile(self, profile: str):
+        """Set the power profile."""
+        if profile not in ['performance', 'balanced', 'battery_saver']:
+            raise ValueError("Power profile must be 'performance', 'balanced' or 'battery_saver'")
+        self.set('power.profile', profile)
+    
+    def get_auto_battery_saver(self) -> bool:
+        """Check whether battery saver turns on automatically on low battery."""
+        return self.get('power.auto_battery_saver', True)
+    
+    def get_auto_low_bandwidth(self) -> bool:
+        """Check whether metered connections switch to the low-bandwidth profile."""
+        return self.get('power.auto_low_bandwidth', True)
+    
+    def get_time_format(self) -> TimeFormat:
+        """Get timestamp preferences."""
+        return TimeFormat(
+            clock=self.get('appearance.clock_format', 'locale'),
+            relative=self.get('appearance.relative_timestamps', True),
+            date_format=self.get('appearance.date_format', 'locale')
+        )
+    
+    def get_accent_color(self) -> str:
+        """Get the accent color ID, or 'system' for the desktop's accent."""
+        return self.get('appearance.accent_color', 'system')
+    
+    def get_chat_list_style(self) -> ChatListStyle:
+        """Get chat list appearance preferences."""
+        return ChatListStyle(
+            avatar_size=self.get('appearance.avatar_size', 40),
+            preview_lines=self.get('appearance.preview_lines', 1),
+            show_timestamps=self.get('appearance.show_chat_timestamps', True),
+            compact=self.get('appearance.compact_chat_list', False)
+        )
+    
+    def get_notification_preview(self) -> str:
+        """Get how much of a message notifications show: 'full', 'sender' or 'hidden'."""
+        return self.get('notifications.preview', 'full')
+    
+    def set_notification_preview(self, preview: str):
+        """Set how much of a message notifications show."""
+        if preview not in ['full', 'sender', 'hidden']:
+            raise ValueError("Notification preview must be 'full', 'sender' or 'hidden'")
+        self.set('notifications.preview', preview)
+    
+    def get_message_sounds(self) -> bool:
+        """Get whether sending and receiving messages plays a sound and vibrates."""
+        return self.get('notifications.sounds', True)
+    
+    def get_auto_copy_codes(self) -> bool:
+        """Get whether one-time codes arriving in the open chat are copied to the clipboard."""
+        return self.get('notifications.auto_copy_codes', False)
+    
+    def get_send_typing_indicators(self) -> bool:
+        """Get whether typing shows an indicator to the other side, unless a chat overrides it."""
+        return self.get('privacy.send_typing_indicators', True)
+    
+    def get_send_read_receipts(self) -> bool:
+        """Get whether opening a chat marks it read on the server, unless a chat overrides it."""
+        return self.get('privacy.send_read_receipts', True)
+    
+    def get_attachment_cache_limit(self) -> int:
+        """Get the most disk space cached attachments may take, in megabytes."""
+        return self.get('storage.attachment_cache_mb', 2048)
+    
+    def set_attachment_cache_limit(self, megabytes: int):
+        """Set the most disk space cached attachments may take, in megabytes."""
+        self.set('storage.attachment_cache_mb', megabytes)
+    
+    def get_window_state(self) -> Tuple[int, int, bool]:
+        """Get the main window's (width, height, maximized) from when it was last closed."""
+        return (
+            self.get('window.width', 1200),
+            self.get('window.height', 800),
+            self.get('window.maximized', False),
+        )
+    
+    def set_window_state(self, width: int, height: int, maximized: bool):
+        """Remember the main window's size for next time."""
+        self.set('window.width', width)
+        self.set('window.height', height)
+        self.set('window.maximized', maximized)
+    
+    def has_seen_tour(self) -> bool:
+        """Check whether the onboarding tour has already been shown."""
+        return self.get('onboarding.tour_seen', False)
+    
+    def mark_tour_seen(self):
+        """Remember that the onboarding tour has been shown."""
+        self.set('onboarding.tour_seen', True)
+    
+    def get_last_seen_version(self) -> Optional[str]:
+        """Get the app version the user last saw the "What's new" notes for."""
+        return self.get('onboarding.last_seen_version')
+    
+    def set_last_seen_version(self, version: str):
+        """Set the app version the user last saw the "What's new" notes for."""
+        self.set('onboarding.last_seen_version', version)
+
+
+def _flatten(data: Dict[str, Any], prefix: str = '') -> Dict[str, Any]:
+    """Flatten nested tables into dotted keys."""
+    values = {}
+    for name, value in data.items():
+        if isinstance(value, dict):
+            values.update(_flatten(value, f"{prefix}{name}."))
+        else:
+            values[f"{prefix}{name}"] = value
+    return values
+
+
+def _merge_missing(data: Dict[str, Any], extra: Dict[str, Any]) -> Dict[str, Any]:
+    """Copy data, adding the tables and keys of extra that it doesn't have."""
+    merged = copy.deepcopy(data)
+    for name, value in extra.items():
+        if name not in merged:
+            merged[name] = copy.deepcopy(value)
+        elif isinstance(merged[name], dict) and isinstance(value, dict):
+            merged[name] = _merge_missing(merged[name], value)
+    return merged
@@ -0,0 +1,144 @@
+"""
+Configuration Schema
+Describes the sections and keys of bb.toml and validates loaded values
+"""
+
+from dataclasses import dataclass
+from typing import Any, Dict, List, Optional, Sequence, Tuple
+
+from ..utils.accent_colors import ACCENT_COLOR_IDS
+from ..utils.chat_list_style import MAX_AVATAR_SIZE, MIN_AVATAR_SIZE, PREVIEW_LINE_CHOICES
+from ..utils.time_format import CLOCK_FORMATS, DATE_FORMATS
+
+
+@dataclass
+class Setting:
+    """A single configuration key."""
+    type: type
+    choices: Optional[Sequence[Any]] = None
+    minimum: Optional[int] = None
+    maximum: Optional[int] = None
+
+
+# section -> key -> setting
+CONFIG_SCHEMA = {
+    'server': {
+        'url': Setting(str),
+        'password': Setting(str),
+    },
+    'appearance': {
+        'dark_mode': Setting(bool),
+        'accent_color': Setting(str, choices=ACCENT_COLOR_IDS),
+        'text_width': Setting(int, minimum=60, maximum=150),
+        'clock_format': Setting(str, choices=CLOCK_FORMATS),
+        'date_format': Setting(str, choices=list(DATE_FORMATS)),
+        'relative_timestamps': Setting(bool),
+        'avatar_size': Setting(int, minimum=MIN_AVATAR_SIZE, maximum=MAX_AVATAR_SIZE),
+        'preview_lines': Setting(int, choices=PREVIEW_LINE_CHOICES),
+        'show_chat_timestamps': Setting(bool),
+        'compact_chat_list': Setting(bool),
+        'reduced_effects': Setting(bool),
+    },
+    'notifications': {
+        'preview': Setting(str, choices=['full', 'sender', 'hidden']),
+        'sounds': Setting(bool),
+        'auto_copy_codes': Setting(bool),
+    },
+    'privacy': {
+        'send_typing_indicators': Setting(bool),
+        'send_read_receipts': Setting(bool),
+    },
+    'app': {
+        'message_check_interval': Setting(int, minimum=1, maximum=60),
+        'run_in_background': Setting(bool),
+        'autostart': Setting(bool),
+    },
+    'power': {
+        'profile': Setting(str, choices=['performance', 'balanced', 'battery_saver']),
+        'auto_battery_saver': Setting(bool),
+        'auto_low_bandwidth': Setting(bool),
+    },
+    'audio': {
+        'input_device': Setting(str),
+    },
+    'advanced': {
+        'api_method': Setting(str, choices=['applescript', 'private']),
+        'request_timeout': Setting(int, minimum=5, maximum=120),
+        'long_request_timeout': Setting(int, minimum=30, maximum=3600),
+    },
+    'storage': {
+        'attachment_cache_mb': Setting(int, minimum=100, maximum=1024 * 1024),
+    },
+    'window': {
+        'width': Setting(int, minimum=360),
+        'height': Setting(int, minimum=294),
+        'maximized': Setting(bool),
+    },
+    'onboarding': {
+        'tour_seen': Setting(bool),
+        'last_seen_version': Setting(str),
+    },
+}
+
+# Environment variables that override config keys, for containers and headless use
+ENV_OVERRIDES = {
+    'server.url': 'BB_SERVER_URL',
+    'server.password': 'BB_PASSWORD',
+}
+
+TYPE_NAMES = {str: "a string", int: "an integer", bool: "true or false"}
+
+
+def check_value(name: str, setting: Setting, value: Any) -> Optional[str]:
+    """Check a value against its setting. Returns an error message, or None if valid."""
+    # bool is a subclass of int, so compare types exactly
+    if type(value) is not setting.type:
+        return f"{name}: expected {TYPE_NAMES[setting.type]}, got {value!r}"
+
+    if setting.choices is not None and value not in setting.choices:
+        options = ", ".join(f"'{choice}'" for choice in setting.choices)
+        return f"{name}: must be one of {options}, got {value!r}"
+
+    if setting.minimum is not None and value < setting.minimum:
+        return f"{name}: must be at least {setting.minimum}, got {value}"
+
+    if setting.maximum is not None and value > setting.maximum:
+        return f"{name}: must be at most {setting.maximum}, got {value}"
+
+    return None
+
+
+def validate_config(data: Dict[str, Any]) -> Tuple[Dict[str, Any], List[str], Dict[str, Any]]:
+    """
+    Validate loaded configuration.
+
+    Returns the configuration with invalid values removed (so defaults apply),
+    a list of error messages, and the removed values, in the same layout, so they
+    can be written back rather than lost. Unknown sections and keys are kept as-is.
+    """
+    cleaned = {}
+    errors = []
+    rejected = {}
+
+    for section_name, section in data.items():
+        schema_section = CONFIG_SCHEMA.get(section_name)
+        if schema_section is None:
+            cleaned[section_name] = section
+            continue
+
+        if not isinstance(section, dict):
+            errors.append(f"[{section_name}] must be a table, got {section!r}")
+            rejected[section_name] = section
+            continue
+
+        cleaned[section_name] = {}
+        for key, value in section.items():
+            setting = schema_section.get(key)
+            error = check_value(f"{section_name}.{key}", setting, value) if setting else None
+            if error:
+                errors.append(error)
+                rejected.setdefault(section_name, {})[key] = value
+            else:
+                cleaned[section_name][key] = value
+
+    return cleaned, errors, rejected
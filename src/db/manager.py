@@ -3,22 +3,44 @@ Database Manager
 Handles all SQLite database operations for caching
 """
 
+import os
 import sqlite3
 import json
 import asyncio
 from pathlib import Path
-from typing import List, Optional, Dict, Any, Tuple
+from typing import List, Optional, Dict, Any, Set, Tuple
 from datetime import datetime
 import threading
+import time
 
 from ..models.data import Chat, Message, Handle
-from .models import ChatRecord, MessageRecord, HandleRecord
+from .models import MEDIA_MIME_PREFIXES, ChatRecord, MediaItem, MessageRecord, HandleRecord
+from ..utils.payloads import as_bool, as_dict, as_int, as_list, as_text
+from ..utils.search_query import SENDER_ME, AttachmentHit, SearchHit, SearchQuery, search_words
+from ..utils.timestamps import to_unix_ms
+
+# Number of activity log entries kept; older entries are dropped
+ACTIVITY_LOG_LIMIT = 500
+
+# Local-only tables copied out of a damaged database; everything else comes back from the server
+SALVAGED_TABLES = ['chat_settings', 'pins', 'hidden_messages', 'labels', 'chat_labels', 'snoozes', 'reminders']
+
+# Rough bytes per cached message beyond its text, for the fixed columns and index entries
+MESSAGE_ROW_OVERHEAD = 200
+
+# Seconds a connection waits for another thread's write lock before failing with "database is locked"
+BUSY_TIMEOUT = 10
 
 class DatabaseManager:
     """Manages SQLite database operations for BlueBubbles data caching."""
     
-    def __init__(self, db_path: str = None):
-        """Initialize the database manager."""
+    def __init__(self, db_path: str = None, repair: bool = True):
+        """
+        Initialize the database manager.
+        
+        With repair=False a damaged database is left where it is, for callers
+        that only inspect the cache while the app may be using it.
+        """
         if db_path is None:
             # Use XDG config directory or fallback to home
             try:
@@ -32,17 +54,84 @@ class DatabaseManager:
         
         self.db_path = db_path
         self._local = threading.local()
+        # Where a damaged database was moved on startup, None if it was healthy
+        self.repaired_backup: Optional[str] = None
+        if repair:
+            self._check_and_repair()
         self._init_db()
+        if self.repaired_backup:
+            self._salvage_local_data()
     
     def _get_connection(self) -> sqlite3.Connection:
         """Get a thread-local database connection."""
         if not hasattr(self._local, 'connection'):
-            self._local.connection = sqlite3.connect(self.db_path)
+            # file: URIs allow shared in-memory databases
+            self._local.connection = sqlite3.connect(
+                self.db_path, timeout=BUSY_TIMEOUT, uri=self.db_path.startswith('file:')
+            )
             self._local.connection.row_factory = sqlite3.Row
             # Enable foreign keys
             self._local.connection.execute("PRAGMA foreign_keys = ON")
+            # WAL lets the UI read while sync threads write; NORMAL only syncs at
+            # checkpoints, which can lose the last commits on power loss but never corrupts
+            self._local.connection.execute("PRAGMA journal_mode = WAL")
+            self._local.connection.execute("PRAGMA synchronous = NORMAL")
+            self._local.connection.execute(f"PRAGMA busy_timeout = {BUSY_TIMEOUT * 1000}")
         return self._local.connection
     
+    def _close_connection(self):
+        """Close this thread's connection, if it has one."""
+        if hasattr(self._local, 'connection'):
+            self._local.connection.close()
+            del self._local.connection
+    
+    def _check_and_repair(self):
+        """
+        Run a quick integrity check and move a damaged database aside.
+        
+        A fresh database is then created in its place, so the app starts with an
+        empty cache and syncs again instead of failing on every query.
+        """
+        if self.db_path.startswith('file:') or not os.path.exists(self.db_path):
+            return
+        
+        try:
+            # Connecting can already fail, when setting WAL mode on something that isn't a database
+            result = self._get_connection().execute("PRAGMA quick_check").fetchone()[0]
+        except sqlite3.OperationalError:
+            # Locked by another process or unreadable for now; that isn't damage
+            self._close_connection()
+            return
+        except sqlite3.DatabaseError as e:
+            result = str(e)  # e.g. "file is not a database"
+        if result == 'ok':
+            return
+        
+        self._close_connection()
+        backup_path = f"{self.db_path}.corrupt-{datetime.now():%Y%m%d-%H%M%S}"
+        os.replace(self.db_path, backup_path)
+        # Journal files belong to the damaged database, not the new one
+        for suffix in ('-wal', '-shm', '-journal'):
+            if os.path.exists(self.db_path + suffix):
+                os.replace(self.db_path + suffix, backup_path + suffix)
+        self.repaired_backup = backup_path
+    
+    def _salvage_local_data(self):
+        """Copy what can still be read of the local-only tables from the damaged database."""
+        conn = self._get_connection()
+        try:
+            conn.execute("ATTACH DATABASE ? AS damaged", (self.repaired_backup,))
+        except sqlite3.DatabaseError:
+            return
+        
+        for table in SALVAGED_TABLES:
+            try:
+                conn.execute(f"INSERT OR IGNORE INTO main.{table} SELECT * FROM damaged.{table}")
+                conn.commit()
+            except sqlite3.DatabaseError:
+                conn.rollback()  # Unreadable table, start it empty
+        conn.execute("DETACH DATABASE damaged")
+    
     def _init_db(self):
         """Initialize the database schema."""
         conn = self._get_connection()
@@ -118,42 +207,196 @@ class DatabaseManager:
             FOREIGN KEY (handle_id) REFERENCES handles (original_rowid)
         );
         
+        CREATE TABLE IF NOT EXISTS hidden_messages (
+            guid TEXT PRIMARY KEY,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );
+        
+        CREATE TABLE IF NOT EXISTS retracted_messages (
+            guid TEXT PRIMARY KEY,
+            date_retracted INTEGER NOT NULL
+        );
+        
+        CREATE TABLE IF NOT EXISTS message_replies (
+            guid TEXT PRIMARY KEY,
+            thread_originator_guid TEXT NOT NULL
+        );
+        
+        CREATE TABLE IF NOT EXISTS chat_settings (
+            chat_guid TEXT NOT NULL,
+            key TEXT NOT NULL,
+            value TEXT,
+            PRIMARY KEY (chat_guid, key)
+        );
+        
+        CREATE TABLE IF NOT EXISTS activity_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            message TEXT NOT NULL
+        );
+        
+        CREATE TABLE IF NOT EXISTS contacts (
+            address TEXT PRIMARY KEY,
+            display_name TEXT NOT NULL,
+            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );
+        
+        CREATE TABLE IF NOT EXISTS sync_state (
+            chat_guid TEXT PRIMARY KEY,
+            last_synced_at INTEGER,
+            last_error TEXT,
+            failed_at INTEGER,
+            history_offset INTEGER NOT NULL DEFAULT 0,
+            history_complete BOOLEAN NOT NULL DEFAULT FALSE
+        );
+        
+        CREATE TABLE IF NOT EXISTS pins (
+            chat_guid TEXT PRIMARY KEY,
+            position INTEGER NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );
+        
+        CREATE TABLE IF NOT EXISTS labels (
+            name TEXT PRIMARY KEY,
+            position INTEGER NOT NULL,
+            notifications TEXT NOT NULL DEFAULT 'all',
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );
+        
+        CREATE TABLE IF NOT EXISTS chat_labels (
+            chat_guid TEXT NOT NULL,
+            label TEXT NOT NULL,
+            PRIMARY KEY (chat_guid, label)
+        );
+        
+        CREATE TABLE IF NOT EXISTS snoozes (
+            chat_guid TEXT PRIMARY KEY,
+            until INTEGER NOT NULL
+        );
+        
+        CREATE TABLE IF NOT EXISTS storage (
+            guid TEXT PRIMARY KEY,
+            file_name TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            mime_type TEXT,
+            last_used INTEGER NOT NULL
+        );
+        
+        CREATE TABLE IF NOT EXISTS message_attachments (
+            guid TEXT PRIMARY KEY,
+            message_guid TEXT NOT NULL,
+            file_name TEXT,
+            mime_type TEXT
+        );
+        
+        CREATE TABLE IF NOT EXISTS reminders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            chat_guid TEXT NOT NULL,
+            message_guid TEXT NOT NULL,
+            message_text TEXT,
+            remind_at INTEGER NOT NULL
+        );
+        
         -- Indexes for better performance
         CREATE INDEX IF NOT EXISTS idx_messages_chat_guid ON messages (chat_guid);
         CREATE INDEX IF NOT EXISTS idx_messages_date_created ON messages (date_created);
         CREATE INDEX IF NOT EXISTS idx_messages_handle_id ON messages (handle_id);
         CREATE INDEX IF NOT EXISTS idx_chats_last_message_date ON chats (last_message_date);
         CREATE INDEX IF NOT EXISTS idx_handles_address ON handles (address);
+        CREATE INDEX IF NOT EXISTS idx_message_replies_originator ON message_replies (thread_originator_guid);
+        CREATE INDEX IF NOT EXISTS idx_storage_last_used ON storage (last_used);
+        CREATE INDEX IF NOT EXISTS idx_message_attachments_message ON message_attachments (message_guid);
         """)
         
         conn.commit()
+        
+        # Caches from before attachment names and types were indexed
+        if not conn.execute("SELECT 1 FROM message_attachments LIMIT 1").fetchone():
+            self._index_cached_attachments()
     
-    def save_handle(self, handle_data: Dict[str, Any]) -> int:
-        """Save a handle to the database."""
+    def _index_cached_attachments(self):
+        """Index the attachments of every cached message."""
         conn = self._get_connection()
+        cursor = conn.execute("SELECT guid, attachments_json FROM messages WHERE attachments_json IS NOT NULL")
+        for row in cursor.fetchall():
+            try:
+                attachments = json.loads(row['attachments_json'])
+            except json.JSONDecodeError:
+                continue
+            self._index_attachments(conn, row['guid'], attachments)
+        conn.commit()
+    
+    @staticmethod
+    def _index_attachments(conn: sqlite3.Connection, message_guid: str, attachments: List[Any]):
+        """Record the names and types of a message's attachments, for searching."""
+        conn.execute("DELETE FROM message_attachments WHERE message_guid = ?", (message_guid,))
+        conn.executemany("""
+        INSERT OR REPLACE INTO message_attachments (guid, message_guid, file_name, mime_type) VALUES (?, ?, ?, ?)
+        """, [
+            (as_text(attachment.get('guid')), message_guid,
+             as_text(attachment.get('transferName')), as_text(attachment.get('mimeType')))
+            for attachment in attachments
+            if isinstance(attachment, dict) and as_text(attachment.get('guid'))
+        ])
+    
+    def save_handle(self, handle_data: Dict[str, Any], commit: bool = True) -> Optional[int]:
+        """
+        Save a handle to the database. With commit off, it joins the open transaction.
         
-        cursor = conn.execute("""
+        Returns the handle's ROWID, or None if the payload lacks a ROWID or address
+        and was skipped.
+        """
+        conn = self._get_connection()
+        
+        original_rowid = as_int(handle_data.get('originalROWID'), None)
+        address = as_text(handle_data.get('address'))
+        if original_rowid is None or not address:
+            return None
+        
+        conn.execute("""
         INSERT OR REPLACE INTO handles 
         (original_rowid, address, country, uncanonicalizedId, updated_at)
         VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)
         """, (
-            handle_data.get('originalROWID'),
-            handle_data.get('address'),
-            handle_data.get('country'),
-            handle_data.get('uncanonicalizedId')
+            original_rowid,
+            address,
+            as_text(handle_data.get('country')),
+            as_text(handle_data.get('uncanonicalizedId'))
         ))
         
-        conn.commit()
-        return cursor.lastrowid
+        if commit:
+            conn.commit()
+        return original_rowid
+    
+    def _get_original_rowid(self, conn, table: str, guid: str, value: Any) -> int:
+        """
+        Get the server ROWID of a chat or message, or a placeholder if the payload lacks one.
+        
+        Placeholders are negative so they never clash with real ROWIDs, and a row
+        saved again keeps its placeholder. The real ROWID replaces it on the next sync.
+        """
+        original_rowid = as_int(value, None)
+        if original_rowid is not None:
+            return original_rowid
+        
+        row = conn.execute(f"SELECT original_rowid FROM {table} WHERE guid = ?", (guid,)).fetchone()
+        if row:
+            return row['original_rowid']
+        row = conn.execute(f"SELECT MIN(original_rowid) FROM {table}").fetchone()
+        return min(row[0] or 0, 0) - 1
     
     def save_chat(self, chat_data: Dict[str, Any]) -> str:
         """Save a chat to the database."""
         conn = self._get_connection()
         
+        guid = chat_data.get('guid')
+        
         # Extract last message date for sorting
         last_message_date = None
-        if chat_data.get('lastMessage') and chat_data['lastMessage'].get('dateCreated'):
-            last_message_date = chat_data['lastMessage']['dateCreated']
+        last_message = as_dict(chat_data.get('lastMessage'))
+        if last_message:
+            last_message_date = to_unix_ms(last_message.get('dateCreated'))
         
         cursor = conn.execute("""
         INSERT OR REPLACE INTO chats 
@@ -161,48 +404,100 @@ class DatabaseManager:
          display_name, group_id, last_message_date, updated_at)
         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
         """, (
-            chat_data.get('originalROWID'),
-            chat_data.get('guid'),
-            chat_data.get('chatIdentifier'),
-            chat_data.get('style', 0),
-            chat_data.get('isArchived', False),
-            chat_data.get('isFiltered', False),
-            chat_data.get('displayName'),
-            chat_data.get('groupId'),
+            self._get_original_rowid(conn, 'chats', guid, chat_data.get('originalROWID')),
+            guid,
+            # The identifier is the GUID's last part, e.g. "chat123" or "+15551234567"
+            as_text(chat_data.get('chatIdentifier')) or str(guid).split(';')[-1],
+            as_int(chat_data.get('style')),
+            as_bool(chat_data.get('isArchived')),
+            as_bool(chat_data.get('isFiltered')),
+            as_text(chat_data.get('displayName')),
+            as_text(chat_data.get('groupId')),
             last_message_date
         ))
         
         # Save participants
-        if chat_data.get('participants'):
+        participants = as_list(chat_data.get('participants'))
+        if participants:
             # Clear existing participants
             conn.execute("DELETE FROM chat_participants WHERE chat_guid = ?", 
-                        (chat_data.get('guid'),))
+                        (guid,))
             
             # Add new participants
-            for participant in chat_data['participants']:
-                self.save_handle(participant)
+            for participant in participants:
+                handle_id = self.save_handle(participant)
+                if handle_id is None:
+                    continue
                 conn.execute("""
                 INSERT OR IGNORE INTO chat_participants (chat_guid, handle_id)
                 VALUES (?, ?)
-                """, (chat_data.get('guid'), participant.get('originalROWID')))
+                """, (guid, handle_id))
         
         conn.commit()
-        return chat_data.get('guid')
+        return guid
+    
+    def _get_fallback_date(self, conn, guid: str, chat_guid: str, original_rowid: Optional[int]) -> int:
+        """
+        Get a creation date for a message the server sent without one.
+        
+        Keeps the date already cached for the message, otherwise borrows the date
+        of the message before it by ROWID, so it sorts right after that message.
+        """
+        row = conn.execute(
+            "SELECT date_created FROM messages WHERE guid = ?", (guid,)
+        ).fetchone()
+        if row and row['date_created']:
+            return row['date_created']
+        
+        if original_rowid is not None:
+            row = conn.execute("""
+            SELECT date_created FROM messages
+            WHERE chat_guid = ? AND original_rowid < ?
+            ORDER BY original_rowid DESC LIMIT 1
+            """, (chat_guid, original_rowid)).fetchone()
+            if row:
+                return row['date_created']
+        return 0
     
-    def save_message(self, message_data: Dict[str, Any], chat_guid: str) -> str:
-        """Save a message to the database."""
+    def save_message(self, message_data: Dict[str, Any], chat_guid: str, commit: bool = True) -> str:
+        """Save a message to the database. With commit off, it joins the open transaction."""
         conn = self._get_connection()
+        guid = message_data.get('guid')
+        original_rowid = self._get_original_rowid(conn, 'messages', guid, message_data.get('originalROWID'))
         
         # Save handle if present
         handle_id = None
-        if message_data.get('handle'):
-            self.save_handle(message_data['handle'])
-            handle_id = message_data['handle'].get('originalROWID')
+        handle = as_dict(message_data.get('handle'))
+        if handle:
+            handle_id = self.save_handle(handle, commit=False)
         
         # Serialize attachments
         attachments_json = None
-        if message_data.get('attachments'):
-            attachments_json = json.dumps(message_data['attachments'])
+        attachments = as_list(message_data.get('attachments'))
+        if attachments:
+            attachments_json = json.dumps(attachments)
+        
+        # Unsent messages are kept as a tombstone, without their content
+        text = as_text(message_data.get('text'))
+        date_retracted = to_unix_ms(message_data.get('dateRetracted'))
+        if date_retracted:
+            text = None
+            attachments_json = None
+            conn.execute(
+                "INSERT OR REPLACE INTO retracted_messages (guid, date_retracted) VALUES (?, ?)",
+                (guid, date_retracted)
+            )
+        
+        thread_originator_guid = as_text(message_data.get('threadOriginatorGuid'))
+        if thread_originator_guid:
+            conn.execute(
+                "INSERT OR REPLACE INTO message_replies (guid, thread_originator_guid) VALUES (?, ?)",
+                (guid, thread_originator_guid)
+            )
+        
+        date_created = to_unix_ms(message_data.get('dateCreated'))
+        if date_created is None:
+            date_created = self._get_fallback_date(conn, guid, chat_guid, original_rowid)
         
         cursor = conn.execute("""
         INSERT OR REPLACE INTO messages 
@@ -214,40 +509,58 @@ class DatabaseManager:
          time_expressive_send_style_id, attachments_json, updated_at)
         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
         """, (
-            message_data.get('originalROWID'),
-            message_data.get('guid'),
-            message_data.get('text'),
+            original_rowid,
+            guid,
+            text,
             handle_id,
             chat_guid,
-            message_data.get('dateCreated'),
-            message_data.get('dateRead'),
-            message_data.get('dateDelivered'),
-            message_data.get('isFromMe', False),
-            message_data.get('isDelayed', False),
-            message_data.get('isAutoReply', False),
-            message_data.get('isSystemMessage', False),
-            message_data.get('isServiceMessage', False),
-            message_data.get('isForward', False),
-            message_data.get('isArchived', False),
-            message_data.get('isAudioMessage', False),
-            message_data.get('hasDdResults', False),
-            message_data.get('itemType', 0),
-            message_data.get('groupTitle'),
-            message_data.get('groupActionType', 0),
-            message_data.get('isExpired', False),
-            message_data.get('balloonBundleId'),
-            message_data.get('associatedMessageGuid'),
-            message_data.get('associatedMessageType'),
-            message_data.get('expressiveSendStyleId'),
-            message_data.get('timeExpressiveSendStyleId'),
+            date_created,
+            to_unix_ms(message_data.get('dateRead')),
+            to_unix_ms(message_data.get('dateDelivered')),
+            as_bool(message_data.get('isFromMe')),
+            as_bool(message_data.get('isDelayed')),
+            as_bool(message_data.get('isAutoReply')),
+            as_bool(message_data.get('isSystemMessage')),
+            as_bool(message_data.get('isServiceMessage')),
+            as_bool(message_data.get('isForward')),
+            as_bool(message_data.get('isArchived')),
+            as_bool(message_data.get('isAudioMessage')),
+            as_bool(message_data.get('hasDdResults')),
+            as_int(message_data.get('itemType')),
+            as_text(message_data.get('groupTitle')),
+            as_int(message_data.get('groupActionType')),
+            as_bool(message_data.get('isExpired')),
+            as_text(message_data.get('balloonBundleId')),
+            as_text(message_data.get('associatedMessageGuid')),
+            as_text(message_data.get('associatedMessageType')),
+            as_text(message_data.get('expressiveSendStyleId')),
+            as_text(message_data.get('timeExpressiveSendStyleId')),
             attachments_json
         ))
+        self._index_attachments(conn, guid, attachments if attachments_json else [])
         
-        conn.commit()
-        return message_data.get('guid')
+        if commit:
+            conn.commit()
+        return guid
     
-    def get_chats(self, limit: int = 100, offset: int = 0) -> List[ChatRecord]:
-        """Get chats from the database, ordered by last message date."""
+    def save_messages(self, messages_data: List[Dict[str, Any]], chat_guid: str):
+        """
+        Save a batch of messages in a single transaction.
+        
+        A page of synced messages then costs one disk sync instead of one per
+        message, and holds the write lock once rather than fighting UI reads for it.
+        """
+        conn = self._get_connection()
+        try:
+            for message_data in messages_data:
+                self.save_message(message_data, chat_guid, commit=False)
+            conn.commit()
+        except Exception:
+            conn.rollback()
+            raise
+    
+    def get_chats(self, limit: int = 100, offset: int = 0, archived: bool = False) -> List[ChatRecord]:
+        """Get unarchived chats, or archived ones, from the database, ordered by last message date."""
         conn = self._get_connection()
         
         cursor = conn.execute("""
@@ -256,21 +569,30 @@ class DatabaseManager:
                h_last.address as last_message_address,
                m_last.text as last_message_text,
                m_last.date_created as last_message_date,
-               m_last.is_from_me as last_message_from_me
+               m_last.is_from_me as last_message_from_me,
+               nick.value as nickname,
+               badge.value as hide_badge,
+               (SELECT COUNT(*) FROM messages mu
+                WHERE mu.chat_guid = c.guid AND mu.is_from_me = FALSE
+                AND mu.date_read IS NULL AND mu.associated_message_guid IS NULL) as unread_count
         FROM chats c
         LEFT JOIN chat_participants cp ON c.guid = cp.chat_guid
         LEFT JOIN messages m_last ON c.guid = m_last.chat_guid 
-            AND m_last.date_created = (
-                SELECT MAX(date_created) 
+            AND m_last.original_rowid = (
+                SELECT original_rowid
                 FROM messages 
                 WHERE chat_guid = c.guid
+                ORDER BY date_created DESC, original_rowid DESC
+                LIMIT 1
             )
         LEFT JOIN handles h_last ON m_last.handle_id = h_last.original_rowid
-        WHERE c.is_archived = FALSE
+        LEFT JOIN chat_settings nick ON c.guid = nick.chat_guid AND nick.key = 'nickname'
+        LEFT JOIN chat_settings badge ON c.guid = badge.chat_guid AND badge.key = 'hide_badge'
+        WHERE c.is_archived = ?
         GROUP BY c.id
         ORDER BY COALESCE(c.last_message_date, 0) DESC
         LIMIT ? OFFSET ?
-        """, (limit, offset))
+        """, (archived, limit, offset))
         
         chats = []
         for row in cursor.fetchall():
@@ -290,7 +612,10 @@ class DatabaseManager:
                 last_message_text=row['last_message_text'],
                 last_message_date=row['last_message_date'],
                 last_message_from_me=row['last_message_from_me'],
-                last_message_address=row['last_message_address']
+                last_message_address=row['last_message_address'],
+                nickname=row['nickname'],
+                unread_count=row['unread_count'],
+                hide_badge=row['hide_badge'] == '1'
             )
             chats.append(chat_record)
         
@@ -319,65 +644,136 @@ class DatabaseManager:
         
         return participants
     
-    def get_chat_messages(self, chat_guid: str, limit: int = 50, offset: int = 0) -> List[MessageRecord]:
-        """Get messages for a specific chat."""
+    def get_chat_messages(self, chat_guid: str, limit: int = 50, offset: int = 0,
+                          before: Optional[int] = None) -> List[MessageRecord]:
+        """Get the newest messages of a chat, or the newest sent before a time in Unix milliseconds."""
         conn = self._get_connection()
         
         cursor = conn.execute("""
-        SELECT m.*, h.address as handle_address
+        SELECT m.*, h.address as handle_address, r.date_retracted, rp.thread_originator_guid
         FROM messages m
         LEFT JOIN handles h ON m.handle_id = h.original_rowid
+        LEFT JOIN retracted_messages r ON r.guid = m.guid
+        LEFT JOIN message_replies rp ON rp.guid = m.guid
         WHERE m.chat_guid = ?
-        ORDER BY m.date_created DESC
+        AND m.guid NOT IN (SELECT guid FROM hidden_messages)
+        AND (? IS NULL OR m.date_created < ?)
+        ORDER BY m.date_created DESC, m.original_rowid DESC
         LIMIT ? OFFSET ?
-        """, (chat_guid, limit, offset))
+        """, (chat_guid, before, before, limit, offset))
         
-        messages = []
-        for row in cursor.fetchall():
-            # Parse attachments
-            attachments = []
-            if row['attachments_json']:
-                try:
-                    attachments = json.loads(row['attachments_json'])
-                except json.JSONDecodeError:
-                    attachments = []
-            
-            message_record = MessageRecord(
-                original_rowid=row['original_rowid'],
-                guid=row['guid'],
-                text=row['text'],
-                handle_id=row['handle_id'],
-                handle_address=row['handle_address'],
-                chat_guid=row['chat_guid'],
-                date_created=row['date_created'],
-                date_read=row['date_read'],
-                date_delivered=row['date_delivered'],
-                is_from_me=row['is_from_me'],
-                is_delayed=row['is_delayed'],
-                is_auto_reply=row['is_auto_reply'],
-                is_system_message=row['is_system_message'],
-                is_service_message=row['is_service_message'],
-                is_forward=row['is_forward'],
-                is_archived=row['is_archived'],
-                is_audio_message=row['is_audio_message'],
-                has_dd_results=row['has_dd_results'],
-                item_type=row['item_type'],
-                group_title=row['group_title'],
-                group_action_type=row['group_action_type'],
-                is_expired=row['is_expired'],
-                balloon_bundle_id=row['balloon_bundle_id'],
-                associated_message_guid=row['associated_message_guid'],
-                associated_message_type=row['associated_message_type'],
-                expressive_send_style_id=row['expressive_send_style_id'],
-                time_expressive_send_style_id=row['time_expressive_send_style_id'],
-                attachments=attachments
-            )
-            messages.append(message_record)
+        messages = [self._message_from_row(row) for row in cursor.fetchall()]
         
         # Reverse the messages so they're in chronological order (oldest first)
         # Database query gets newest messages first (DESC), but UI expects oldest first
         return list(reversed(messages))
     
+    def get_message(self, message_guid: str) -> Optional[MessageRecord]:
+        """Get a cached message by GUID, or None if it isn't cached or is hidden."""
+        conn = self._get_connection()
+        row = conn.execute("""
+        SELECT m.*, h.address as handle_address, r.date_retracted, rp.thread_originator_guid
+        FROM messages m
+        LEFT JOIN handles h ON m.handle_id = h.original_rowid
+        LEFT JOIN retracted_messages r ON r.guid = m.guid
+        LEFT JOIN message_replies rp ON rp.guid = m.guid
+        WHERE m.guid = ?
+        AND m.guid NOT IN (SELECT guid FROM hidden_messages)
+        """, (message_guid,)).fetchone()
+        return self._message_from_row(row) if row else None
+    
+    def get_thread_messages(self, originator_guid: str) -> List[MessageRecord]:
+        """Get a thread's originator message and the inline replies to it, oldest first."""
+        conn = self._get_connection()
+        cursor = conn.execute("""
+        SELECT m.*, h.address as handle_address, r.date_retracted, rp.thread_originator_guid
+        FROM messages m
+        LEFT JOIN handles h ON m.handle_id = h.original_rowid
+        LEFT JOIN retracted_messages r ON r.guid = m.guid
+        LEFT JOIN message_replies rp ON rp.guid = m.guid
+        WHERE (m.guid = ? OR rp.thread_originator_guid = ?)
+        AND m.associated_message_guid IS NULL
+        AND m.guid NOT IN (SELECT guid FROM hidden_messages)
+        ORDER BY m.date_created ASC, m.original_rowid ASC
+        """, (originator_guid, originator_guid))
+        return [self._message_from_row(row) for row in cursor.fetchall()]
+    
+    def get_chat_media(self, chat_guid: str) -> List[MediaItem]:
+        """Get the cached image and video attachments of a chat, oldest first."""
+        conn = self._get_connection()
+        cursor = conn.execute("""
+        SELECT m.guid, m.chat_guid, m.original_rowid, m.date_created, m.is_from_me,
+               m.attachments_json, h.address as handle_address
+        FROM messages m
+        LEFT JOIN handles h ON m.handle_id = h.original_rowid
+        WHERE m.chat_guid = ? AND m.attachments_json IS NOT NULL
+        AND m.guid NOT IN (SELECT guid FROM hidden_messages)
+        ORDER BY m.date_created ASC, m.original_rowid ASC
+        """, (chat_guid,))
+        
+        media = []
+        for row in cursor.fetchall():
+            try:
+                attachments = json.loads(row['attachments_json'])
+            except json.JSONDecodeError:
+                continue
+            for attachment in attachments:
+                item = MediaItem(
+                    attachment=attachment,
+                    message_guid=row['guid'],
+                    chat_guid=row['chat_guid'],
+                    original_rowid=row['original_rowid'],
+                    date_created=row['date_created'],
+                    is_from_me=bool(row['is_from_me']),
+                    handle_address=row['handle_address']
+                )
+                if attachment.get('guid') and item.mime_type.startswith(MEDIA_MIME_PREFIXES):
+                    media.append(item)
+        return media
+    
+    @staticmethod
+    def _message_from_row(row) -> MessageRecord:
+        """Build a message from a row of the messages query joined with its handle, retraction and reply."""
+        attachments = []
+        if row['attachments_json']:
+            try:
+                attachments = json.loads(row['attachments_json'])
+            except json.JSONDecodeError:
+                attachments = []
+        
+        return MessageRecord(
+            original_rowid=row['original_rowid'],
+            guid=row['guid'],
+            text=row['text'],
+            handle_id=row['handle_id'],
+            handle_address=row['handle_address'],
+            chat_guid=row['chat_guid'],
+            date_created=row['date_created'],
+            date_read=row['date_read'],
+            date_delivered=row['date_delivered'],
+            is_from_me=row['is_from_me'],
+            is_delayed=row['is_delayed'],
+            is_auto_reply=row['is_auto_reply'],
+            is_system_message=row['is_system_message'],
+            is_service_message=row['is_service_message'],
+            is_forward=row['is_forward'],
+            is_archived=row['is_archived'],
+            is_audio_message=row['is_audio_message'],
+            has_dd_results=row['has_dd_results'],
+            item_type=row['item_type'],
+            group_title=row['group_title'],
+            group_action_type=row['group_action_type'],
+            is_expired=row['is_expired'],
+            balloon_bundle_id=row['balloon_bundle_id'],
+            associated_message_guid=row['associated_message_guid'],
+            associated_message_type=row['associated_message_type'],
+            expressive_send_style_id=row['expressive_send_style_id'],
+            time_expressive_send_style_id=row['time_expressive_send_style_id'],
+            attachments=attachments,
+            date_retracted=row['date_retracted'],
+            thread_originator_guid=row['thread_originator_guid']
+        )
+    
     def get_message_reactions(self, message_guid: str) -> List[MessageRecord]:
         """Get reactions for a specific message."""
         conn = self._get_connection()
@@ -394,7 +790,7 @@ class DatabaseManager:
             OR m.associated_message_guid = ('bp:0/' || ?)
         )
         AND m.associated_message_type IS NOT NULL
-        ORDER BY m.date_created ASC
+        ORDER BY m.date_created ASC, m.original_rowid ASC
         """, (message_guid, message_guid, message_guid))
         
         reactions = []
@@ -450,14 +846,22 @@ class DatabaseManager:
                h_last.address as last_message_address,
                m_last.text as last_message_text,
                m_last.date_created as last_message_date,
-               m_last.is_from_me as last_message_from_me
+               m_last.is_from_me as last_message_from_me,
+               nick.value as nickname,
+               badge.value as hide_badge,
+               (SELECT COUNT(*) FROM messages mu
+                WHERE mu.chat_guid = c.guid AND mu.is_from_me = FALSE
+                AND mu.date_read IS NULL AND mu.associated_message_guid IS NULL) as unread_count
         FROM chats c
         LEFT JOIN (
             SELECT m.chat_guid, m.text, m.date_created, m.is_from_me, m.handle_id,
-                   ROW_NUMBER() OVER (PARTITION BY m.chat_guid ORDER BY m.date_created DESC) as rn
+                   ROW_NUMBER() OVER (PARTITION BY m.chat_guid
+                                      ORDER BY m.date_created DESC, m.original_rowid DESC) as rn
             FROM messages m
         ) m_last ON c.guid = m_last.chat_guid AND m_last.rn = 1
         LEFT JOIN handles h_last ON m_last.handle_id = h_last.original_rowid
+        LEFT JOIN chat_settings nick ON c.guid = nick.chat_guid AND nick.key = 'nickname'
+        LEFT JOIN chat_settings badge ON c.guid = badge.chat_guid AND badge.key = 'hide_badge'
         WHERE c.guid = ?
         """, (chat_guid,))
         
@@ -479,10 +883,512 @@ class DatabaseManager:
                 last_message_text=row['last_message_text'],
                 last_message_date=row['last_message_date'],
                 last_message_from_me=row['last_message_from_me'],
-                last_message_address=row['last_message_address']
+                last_message_address=row['last_message_address'],
+                nickname=row['nickname'],
+                unread_count=row['unread_count'],
+                hide_badge=row['hide_badge'] == '1'
             )
         return None
     
+    def update_chat_display_name(self, chat_guid: str, display_name: Optional[str]):
+        """Update the cached display name of a chat."""
+        conn = self._get_connection()
+        conn.execute("""
+        UPDATE chats SET display_name = ?, updated_at = CURRENT_TIMESTAMP WHERE guid = ?
+        """, (display_name, chat_guid))
+        conn.commit()
+    
+    def delete_chat_messages(self, chat_guid: str) -> List[str]:
+        """Delete a chat's cached messages. Returns the GUIDs of their attachments."""
+        conn = self._get_connection()
+        cursor = conn.execute(
+            "SELECT attachments_json FROM messages WHERE chat_guid = ? AND attachments_json IS NOT NULL",
+            (chat_guid,)
+        )
+        attachment_guids = [
+            guid for row in cursor.fetchall() for guid in self._attachment_guids(row['attachments_json'])
+        ]
+        
+        for table in ('retracted_messages', 'message_replies'):
+            conn.execute(
+                f"DELETE FROM {table} WHERE guid IN (SELECT guid FROM messages WHERE chat_guid = ?)",
+                (chat_guid,)
+            )
+        conn.execute(
+            "DELETE FROM message_attachments WHERE message_guid IN (SELECT guid FROM messages WHERE chat_guid = ?)",
+            (chat_guid,)
+        )
+        conn.execute("DELETE FROM messages WHERE chat_guid = ?", (chat_guid,))
+        conn.execute("DELETE FROM sync_state WHERE chat_guid = ?", (chat_guid,))
+        conn.commit()
+        return attachment_guids
+    
+    def get_retracted_message_guids(self, message_guids: List[str]) -> set:
+        """Get which of some messages are cached as unsent."""
+        conn = self._get_connection()
+        placeholders = ','.join('?' * len(message_guids))
+        cursor = conn.execute(
+            f"SELECT guid FROM retracted_messages WHERE guid IN ({placeholders})", message_guids
+        )
+        return {row['guid'] for row in cursor.fetchall()}
+    
+    def delete_missing_messages(self, chat_guid: str, messages_data: List[Dict[str, Any]]) -> Tuple[int, List[str]]:
+        """
+        Delete cached messages that were deleted on the server.
+        
+        messages_data is a run of a chat's messages as fetched from the server, so
+        any cached message dated between its oldest and newest that it lacks is gone.
+        Returns how many messages were deleted and the GUIDs of their attachments.
+        """
+        keys = [
+            (to_unix_ms(message_data.get('dateCreated')), message_data.get('originalROWID'))
+            for message_data in messages_data
+        ]
+        keys = [key for key in keys if key[0] and key[1]]
+        if not keys:
+            return 0, []
+        
+        conn = self._get_connection()
+        (oldest_date, oldest_rowid), (newest_date, newest_rowid) = min(keys), max(keys)
+        cursor = conn.execute("""
+        SELECT guid, attachments_json FROM messages
+        WHERE chat_guid = ? AND (date_created, original_rowid) BETWEEN (?, ?) AND (?, ?)
+        """, (chat_guid, oldest_date, oldest_rowid, newest_date, newest_rowid))
+        
+        fetched_guids = {message_data.get('guid') for message_data in messages_data}
+        missing = [row for row in cursor.fetchall() if row['guid'] not in fetched_guids]
+        if not missing:
+            return 0, []
+        
+        conn.executemany("DELETE FROM messages WHERE guid = ?", [(row['guid'],) for row in missing])
+        conn.executemany("DELETE FROM message_attachments WHERE message_guid = ?", [(row['guid'],) for row in missing])
+        conn.commit()
+        return len(missing), [
+            guid for row in missing if row['attachments_json']
+            for guid in self._attachment_guids(row['attachments_json'])
+        ]
+    
+    def delete_chat(self, chat_guid: str) -> List[str]:
+        """
+        Remove a chat deleted on the server, with its cached messages. Returns its attachment GUIDs.
+        
+        Pins, settings, labels and snoozes are the user's own and are kept, so they
+        apply again if the chat comes back.
+        """
+        attachment_guids = self.delete_chat_messages(chat_guid)
+        conn = self._get_connection()
+        conn.execute("DELETE FROM chat_participants WHERE chat_guid = ?", (chat_guid,))
+        conn.execute("DELETE FROM chats WHERE guid = ?", (chat_guid,))
+        conn.commit()
+        return attachment_guids
+    
+    def get_chat_guids(self) -> List[str]:
+        """Get the GUIDs of every cached chat."""
+        conn = self._get_connection()
+        return [row['guid'] for row in conn.execute("SELECT guid FROM chats").fetchall()]
+    
+    def has_attachment(self, attachment_guid: str) -> bool:
+        """Check whether a cached message has an attachment."""
+        conn = self._get_connection()
+        cursor = conn.execute(
+            "SELECT 1 FROM messages WHERE attachments_json LIKE ? LIMIT 1",
+            (f'%"{attachment_guid}"%',)
+        )
+        return cursor.fetchone() is not None
+    
+    def mark_chat_read_locally(self, chat_guid: str):
+        """Mark all incoming messages of a chat as read in the cache."""
+        conn = self._get_connection()
+        now_ms = int(datetime.now().timestamp() * 1000)
+        conn.execute("""
+        UPDATE messages SET date_read = ?
+        WHERE chat_guid = ? AND is_from_me = FALSE AND date_read IS NULL
+        """, (now_ms, chat_guid))
+        conn.commit()
+    
+    def mark_chat_read_until(self, chat_guid: str, date_created: int, date_read: int) -> int:
+        """
+        Mark a chat's incoming messages up to a point as read, e.g. after it was read on another device.
+        Returns how many messages were unread.
+        """
+        conn = self._get_connection()
+        cursor = conn.execute("""
+        UPDATE messages SET date_read = ?
+        WHERE chat_guid = ? AND is_from_me = FALSE AND date_read IS NULL AND date_created <= ?
+        """, (date_read, chat_guid, date_created))
+        conn.commit()
+        return cursor.rowcount
+    
+    def get_chat_setting(self, chat_guid: str, key: str, default: Optional[str] = None) -> Optional[str]:
+        """Get a local per-chat setting."""
+        conn = self._get_connection()
+        cursor = conn.execute(
+            "SELECT value FROM chat_settings WHERE chat_guid = ? AND key = ?",
+            (chat_guid, key)
+        )
+        row = cursor.fetchone()
+        return row['value'] if row else default
+    
+    def set_chat_setting(self, chat_guid: str, key: str, value: Optional[str]):
+        """Set a local per-chat setting. A value of None removes it."""
+        conn = self._get_connection()
+        if value is None:
+            conn.execute(
+                "DELETE FROM chat_settings WHERE chat_guid = ? AND key = ?",
+                (chat_guid, key)
+            )
+        else:
+            conn.execute(
+                "INSERT OR REPLACE INTO chat_settings (chat_guid, key, value) VALUES (?, ?, ?)",
+                (chat_guid, key, value)
+            )
+        conn.commit()
+    
+    def get_chats_with_setting(self, key: str) -> Dict[str, str]:
+        """Get a mapping of chat GUID to value for every chat with a setting."""
+        conn = self._get_connection()
+        cursor = conn.execute("SELECT chat_guid, value FROM chat_settings WHERE key = ?", (key,))
+        return {row['chat_guid']: row['value'] for row in cursor.fetchall()}
+    
+    def hide_message(self, message_guid: str):
+        """Hide a message locally. Hidden messages stay hidden across syncs."""
+        conn = self._get_connection()
+        conn.execute("INSERT OR IGNORE INTO hidden_messages (guid) VALUES (?)", (message_guid,))
+        conn.commit()
+    
+    def log_activity(self, kind: str, message: str):
+        """Add an activity log entry, dropping the oldest beyond ACTIVITY_LOG_LIMIT."""
+        conn = self._get_connection()
+        cursor = conn.execute(
+            "INSERT INTO activity_log (timestamp, kind, message) VALUES (?, ?, ?)",
+            (int(time.time() * 1000), kind, message)
+        )
+        conn.execute(
+            "DELETE FROM activity_log WHERE id <= ?",
+            (cursor.lastrowid - ACTIVITY_LOG_LIMIT,)
+        )
+        conn.commit()
+    
+    def get_activity_log(self, limit: int = 200) -> List[Dict[str, Any]]:
+        """Get the most recent activity log entries, newest first."""
+        conn = self._get_connection()
+        cursor = conn.execute(
+            "SELECT timestamp, kind, message FROM activity_log ORDER BY id DESC LIMIT ?",
+            (limit,)
+        )
+        return [dict(row) for row in cursor.fetchall()]
+    
+    def clear_activity_log(self):
+        """Remove all activity log entries."""
+        conn = self._get_connection()
+        conn.execute("DELETE FROM activity_log")
+        conn.commit()
+    
+    def save_contact_names(self, names: Dict[str, str]):
+        """Replace the cached contact names, keyed by normalized address."""
+        conn = self._get_connection()
+        conn.execute("DELETE FROM contacts")
+        conn.executemany(
+            "INSERT INTO contacts (address, display_name) VALUES (?, ?)",
+            names.items()
+        )
+        conn.commit()
+    
+    def get_contact_names(self) -> Dict[str, str]:
+        """Get the cached contact names, keyed by normalized address."""
+        conn = self._get_connection()
+        cursor = conn.execute("SELECT address, display_name FROM contacts")
+        return {row['address']: row['display_name'] for row in cursor.fetchall()}
+    
+    def record_chat_sync(self, chat_guid: str, error: Optional[str] = None):
+        """Record the outcome of fetching a chat's messages; an error of None means it succeeded."""
+        conn = self._get_connection()
+        now_ms = int(time.time() * 1000)
+        
+        if error is None:
+            conn.execute("""
+            INSERT INTO sync_state (chat_guid, last_synced_at) VALUES (?, ?)
+            ON CONFLICT (chat_guid) DO UPDATE SET
+                last_synced_at = excluded.last_synced_at, last_error = NULL, failed_at = NULL
+            """, (chat_guid, now_ms))
+        else:
+            conn.execute("""
+            INSERT INTO sync_state (chat_guid, last_error, failed_at) VALUES (?, ?, ?)
+            ON CONFLICT (chat_guid) DO UPDATE SET
+                last_error = excluded.last_error, failed_at = excluded.failed_at
+            """, (chat_guid, error, now_ms))
+        
+        conn.commit()
+    
+    def get_chat_sync_errors(self) -> Dict[str, str]:
+        """Get the last sync error of each chat whose most recent message fetch failed."""
+        conn = self._get_connection()
+        cursor = conn.execute("SELECT chat_guid, last_error FROM sync_state WHERE last_error IS NOT NULL")
+        return {row['chat_guid']: row['last_error'] for row in cursor.fetchall()}
+    
+    def get_history_import_state(self, chat_guid: str) -> Tuple[int, bool]:
+        """Get how many messages of a chat's full history were imported, and whether it's done."""
+        conn = self._get_connection()
+        row = conn.execute(
+            "SELECT history_offset, history_complete FROM sync_state WHERE chat_guid = ?",
+            (chat_guid,)
+        ).fetchone()
+        if not row:
+            return 0, False
+        return row['history_offset'], bool(row['history_complete'])
+    
+    def save_history_import_state(self, chat_guid: str, offset: int, complete: bool = False):
+        """Record progress through a chat's full history, so an import can resume."""
+        conn = self._get_connection()
+        conn.execute("""
+        INSERT INTO sync_state (chat_guid, history_offset, history_complete) VALUES (?, ?, ?)
+        ON CONFLICT (chat_guid) DO UPDATE SET
+            history_offset = excluded.history_offset, history_complete = excluded.history_complete
+        """, (chat_guid, offset, complete))
+        conn.commit()
+    
+    def search_chat_messages(self, chat_guid: str, query: str, limit: int = 500) -> List[Tuple[str, int]]:
+        """Find messages of a chat whose text contains each word of a query, newest first, as (GUID, date) pairs."""
+        matches = self.search_messages(SearchQuery(text=query), [chat_guid], limit=limit)
+        return [(guid, date_created) for chat_guid, guid, date_created in matches]
+    
+    def search_messages(self, query: SearchQuery, chat_guids: Optional[List[str]] = None,
+                        handle_ids: Optional[List[int]] = None, limit: int = 500) -> List[Tuple[str, str, int]]:
+        """
+        Find messages matching a search's text and filters, newest first, as (chat GUID, GUID, date).
+        
+        The in: filter is left to the caller, which knows conversation names; a
+        from: filter other than SENDER_ME needs the matching handle_ids.
+        """
+        conditions, params = self._message_search_conditions(query, chat_guids, handle_ids)
+        conn = self._get_connection()
+        cursor = conn.execute(f"""
+        SELECT chat_guid, guid, date_created FROM messages
+        WHERE {conditions}
+        ORDER BY date_created DESC
+        LIMIT ?
+        """, params + [limit])
+        return [(row['chat_guid'], row['guid'], row['date_created']) for row in cursor.fetchall()]
+    
+    def search_message_hits(self, query: SearchQuery, handle_ids: Optional[List[int]] = None,
+                            limit: int = 500) -> List[SearchHit]:
+        """Find messages across chats matching a search, like search_messages, with their text."""
+        conditions, params = self._message_search_conditions(query, None, handle_ids)
+        conn = self._get_connection()
+        cursor = conn.execute(f"""
+        SELECT chat_guid, guid, date_created, text FROM messages
+        WHERE {conditions}
+        ORDER BY date_created DESC
+        LIMIT ?
+        """, params + [limit])
+        return [
+            SearchHit(row['chat_guid'], row['guid'], row['date_created'], row['text'])
+            for row in cursor.fetchall()
+        ]
+    
+    def search_attachments(self, query: SearchQuery, handle_ids: Optional[List[int]] = None,
+                           limit: int = 500) -> List[AttachmentHit]:
+        """Find the attachments of messages matching a search, newest first; has: picks their kind."""
+        conditions, params = self._message_search_conditions(query, None, handle_ids)
+        kinds = " OR ".join("a.mime_type LIKE ?" for prefix in query.attachment_mime_prefixes)
+        conn = self._get_connection()
+        cursor = conn.execute(f"""
+        SELECT m.chat_guid, m.guid AS message_guid, m.date_created, a.guid, a.file_name, a.mime_type
+        FROM message_attachments a
+        JOIN messages m ON m.guid = a.message_guid
+        WHERE a.message_guid IN (SELECT guid FROM messages WHERE {conditions})
+        {f'AND ({kinds})' if kinds else ''}
+        ORDER BY m.date_created DESC
+        LIMIT ?
+        """, params + [prefix + '%' for prefix in query.attachment_mime_prefixes] + [limit])
+        return [
+            AttachmentHit(row['chat_guid'], row['message_guid'], row['guid'], row['file_name'],
+                          row['mime_type'], row['date_created'])
+            for row in cursor.fetchall()
+        ]
+    
+    def search_message_chats(self, query: SearchQuery, handle_ids: Optional[List[int]] = None) -> Set[str]:
+        """Get the GUIDs of chats with messages matching a search, like search_messages."""
+        conditions, params = self._message_search_conditions(query, None, handle_ids)
+        conn = self._get_connection()
+        cursor = conn.execute(f"SELECT DISTINCT chat_guid FROM messages WHERE {conditions}", params)
+        return {row['chat_guid'] for row in cursor.fetchall()}
+    
+    def _message_search_conditions(self, query: SearchQuery, chat_guids: Optional[List[str]],
+                                   handle_ids: Optional[List[int]]) -> Tuple[str, List[Any]]:
+        """Build the WHERE clause, and its parameters, of a message search."""
+        conditions = [
+            "associated_message_guid IS NULL",
+            "guid NOT IN (SELECT guid FROM hidden_messages)",
+        ]
+        params: List[Any] = []
+        # Each word has to appear, in any order, in the text or an attachment's file name
+        for word in search_words(query.text):
+            conditions.append("""(text LIKE ? ESCAPE '\\'
+            OR guid IN (SELECT message_guid FROM message_attachments WHERE file_name LIKE ? ESCAPE '\\'))""")
+            pattern = '%' + word.replace('\\', '\\\\').replace('%', '\\%').replace('_', '\\_') + '%'
+            params.extend([pattern, pattern])
+        if chat_guids is not None:
+            conditions.append(f"chat_guid IN ({', '.join('?' * len(chat_guids))})")
+            params.extend(chat_guids)
+        if query.sender == SENDER_ME:
+            conditions.append("is_from_me")
+        elif query.sender:
+            handle_ids = handle_ids or []
+            conditions.append(f"NOT is_from_me AND handle_id IN ({', '.join('?' * len(handle_ids))})")
+            params.extend(handle_ids)
+        if query.has_attachment:
+            prefixes = query.attachment_mime_prefixes
+            kinds = " OR ".join("mime_type LIKE ?" for prefix in prefixes)
+            conditions.append(
+                f"guid IN (SELECT message_guid FROM message_attachments{f' WHERE {kinds}' if kinds else ''})"
+            )
+            params.extend(prefix + '%' for prefix in prefixes)
+        if query.before:
+            conditions.append("date_created < ?")
+            params.append(query.before_ms)
+        if query.after:
+            conditions.append("date_created >= ?")
+            params.append(query.after_ms)
+        return " AND ".join(conditions), params
+    
+    def get_handle_addresses(self) -> Dict[int, str]:
+        """Get the address of every cached handle, keyed by its original row ID."""
+        conn = self._get_connection()
+        cursor = conn.execute("SELECT original_rowid, address FROM handles")
+        return {row['original_rowid']: row['address'] for row in cursor.fetchall()}
+    
+    def pin_chat(self, chat_guid: str):
+        """Pin a chat, placing it after the existing pins."""
+        conn = self._get_connection()
+        
+        conn.execute("""
+        INSERT OR IGNORE INTO pins (chat_guid, position)
+        VALUES (?, (SELECT COALESCE(MAX(position), -1) + 1 FROM pins))
+        """, (chat_guid,))
+        
+        conn.commit()
+    
+    def unpin_chat(self, chat_guid: str):
+        """Unpin a chat."""
+        conn = self._get_connection()
+        conn.execute("DELETE FROM pins WHERE chat_guid = ?", (chat_guid,))
+        conn.commit()
+    
+    def get_pinned_chat_guids(self) -> List[str]:
+        """Get the GUIDs of pinned chats in pin order."""
+        conn = self._get_connection()
+        cursor = conn.execute("SELECT chat_guid FROM pins ORDER BY position")
+        return [row['chat_guid'] for row in cursor.fetchall()]
+    
+    def move_pin(self, chat_guid: str, index: int):
+        """Move a pinned chat to a place in the pin order, shifting the others along."""
+        chat_guids = self.get_pinned_chat_guids()
+        if chat_guid not in chat_guids:
+            return
+        chat_guids.remove(chat_guid)
+        chat_guids.insert(index, chat_guid)
+        
+        conn = self._get_connection()
+        conn.executemany("UPDATE pins SET position = ? WHERE chat_guid = ?",
+                         [(position, guid) for position, guid in enumerate(chat_guids)])
+        conn.commit()
+    
+    def create_label(self, name: str):
+        """Create a label, placing it after the existing ones."""
+        conn = self._get_connection()
+        
+        conn.execute("""
+        INSERT OR IGNORE INTO labels (name, position)
+        VALUES (?, (SELECT COALESCE(MAX(position), -1) + 1 FROM labels))
+        """, (name,))
+        
+        conn.commit()
+    
+    def delete_label(self, name: str):
+        """Delete a label and take it off every chat."""
+        conn = self._get_connection()
+        conn.execute("DELETE FROM chat_labels WHERE label = ?", (name,))
+        conn.execute("DELETE FROM labels WHERE name = ?", (name,))
+        conn.commit()
+    
+    def get_labels(self) -> List[Tuple[str, str]]:
+        """Get (name, notification policy) of every label in creation order."""
+        conn = self._get_connection()
+        cursor = conn.execute("SELECT name, notifications FROM labels ORDER BY position")
+        return [(row['name'], row['notifications']) for row in cursor.fetchall()]
+    
+    def set_label_notifications(self, name: str, policy: str):
+        """Set the notification policy of chats with a label."""
+        conn = self._get_connection()
+        conn.execute("UPDATE labels SET notifications = ? WHERE name = ?", (policy, name))
+        conn.commit()
+    
+    def set_chat_labels(self, chat_guid: str, labels: List[str]):
+        """Replace the labels of a chat."""
+        conn = self._get_connection()
+        conn.execute("DELETE FROM chat_labels WHERE chat_guid = ?", (chat_guid,))
+        conn.executemany(
+            "INSERT OR IGNORE INTO chat_labels (chat_guid, label) VALUES (?, ?)",
+            [(chat_guid, label) for label in labels]
+        )
+        conn.commit()
+    
+    def get_chat_labels(self) -> Dict[str, List[str]]:
+        """Get the labels of every labelled chat, by chat GUID, in label order."""
+        conn = self._get_connection()
+        cursor = conn.execute("""
+        SELECT cl.chat_guid, cl.label FROM chat_labels cl
+        JOIN labels l ON l.name = cl.label
+        ORDER BY l.position
+        """)
+        chat_labels: Dict[str, List[str]] = {}
+        for row in cursor.fetchall():
+            chat_labels.setdefault(row['chat_guid'], []).append(row['label'])
+        return chat_labels
+    
+    def snooze_chat(self, chat_guid: str, until: int):
+        """Snooze a chat until a time in Unix milliseconds."""
+        conn = self._get_connection()
+        conn.execute("INSERT OR REPLACE INTO snoozes (chat_guid, until) VALUES (?, ?)", (chat_guid, until))
+        conn.commit()
+    
+    def unsnooze_chat(self, chat_guid: str):
+        """End a chat's snooze."""
+        conn = self._get_connection()
+        conn.execute("DELETE FROM snoozes WHERE chat_guid = ?", (chat_guid,))
+        conn.commit()
+    
+    def get_snoozes(self) -> Dict[str, int]:
+        """Get the snooze end time of every snoozed chat, by chat GUID."""
+        conn = self._get_connection()
+        cursor = conn.execute("SELECT chat_guid, until FROM snoozes")
+        return {row['chat_guid']: row['until'] for row in cursor.fetchall()}
+    
+    def add_reminder(self, chat_guid: str, message_guid: str, message_text: Optional[str], remind_at: int) -> int:
+        """Schedule a reminder about a message at a time in Unix milliseconds. Returns its ID."""
+        conn = self._get_connection()
+        cursor = conn.execute(
+            "INSERT INTO reminders (chat_guid, message_guid, message_text, remind_at) VALUES (?, ?, ?, ?)",
+            (chat_guid, message_guid, message_text, remind_at)
+        )
+        conn.commit()
+        return cursor.lastrowid
+    
+    def delete_reminder(self, reminder_id: int):
+        """Remove a reminder, once shown or cancelled."""
+        conn = self._get_connection()
+        conn.execute("DELETE FROM reminders WHERE id = ?", (reminder_id,))
+        conn.commit()
+    
+    def get_reminders(self) -> List[Dict[str, Any]]:
+        """Get every pending reminder, soonest first."""
+        conn = self._get_connection()
+        cursor = conn.execute(
+            "SELECT id, chat_guid, message_guid, message_text, remind_at FROM reminders ORDER BY remind_at, id"
+        )
+        return [dict(row) for row in cursor.fetchall()]
+    
     def clear_cache(self):
         """Clear all cached data."""
         conn = self._get_connection()
@@ -491,9 +1397,137 @@ class DatabaseManager:
         DELETE FROM messages;
         DELETE FROM chats;
         DELETE FROM handles;
+        DELETE FROM contacts;
+        DELETE FROM sync_state;
+        DELETE FROM retracted_messages;
+        DELETE FROM message_replies;
+        DELETE FROM message_attachments;
         """)
         conn.commit()
     
+    @staticmethod
+    def _attachment_guids(attachments_json: str) -> List[str]:
+        """Get the attachment GUIDs out of a message's attachments JSON."""
+        return [attachment['guid'] for attachment in json.loads(attachments_json) if attachment.get('guid')]
+    
+    def get_file_size(self) -> int:
+        """Get the size of the database on disk in bytes, including its journal files."""
+        if self.db_path.startswith('file:'):
+            return 0
+        paths = [self.db_path] + [self.db_path + suffix for suffix in ('-wal', '-shm', '-journal')]
+        return sum(os.path.getsize(path) for path in paths if os.path.exists(path))
+    
+    def record_stored_attachment(self, guid: str, file_name: str, size: int, mime_type: Optional[str] = None):
+        """Track an attachment saved to the attachment cache, as just used."""
+        conn = self._get_connection()
+        conn.execute(
+            "INSERT OR REPLACE INTO storage (guid, file_name, size, mime_type, last_used) VALUES (?, ?, ?, ?, ?)",
+            (guid, file_name, size, mime_type, int(time.time() * 1000))
+        )
+        conn.commit()
+    
+    def get_stored_attachment(self, guid: str) -> Optional[Dict[str, Any]]:
+        """Get the tracked file of a cached attachment: its file_name, size, mime_type and last_used."""
+        conn = self._get_connection()
+        cursor = conn.execute("SELECT * FROM storage WHERE guid = ?", (guid,))
+        row = cursor.fetchone()
+        return dict(row) if row else None
+    
+    def touch_stored_attachment(self, guid: str):
+        """Mark a cached attachment as just used, so it's evicted last."""
+        conn = self._get_connection()
+        conn.execute("UPDATE storage SET last_used = ? WHERE guid = ?", (int(time.time() * 1000), guid))
+        conn.commit()
+    
+    def remove_stored_attachment(self, guid: str):
+        """Stop tracking an attachment removed from the cache."""
+        conn = self._get_connection()
+        conn.execute("DELETE FROM storage WHERE guid = ?", (guid,))
+        conn.commit()
+    
+    def clear_storage(self):
+        """Stop tracking every cached attachment."""
+        conn = self._get_connection()
+        conn.execute("DELETE FROM storage")
+        conn.commit()
+    
+    def get_storage_evictions(self, limit: int, keep_guid: Optional[str] = None) -> List[Dict[str, Any]]:
+        """
+        Get the cached attachments to remove to bring the cache down to limit bytes.
+        
+        Least recently used go first. keep_guid, e.g. the attachment just
+        downloaded, is never picked, even if it alone is over the limit.
+        """
+        conn = self._get_connection()
+        total = conn.execute("SELECT COALESCE(SUM(size), 0) FROM storage").fetchone()[0]
+        if total <= limit:
+            return []
+        
+        evictions = []
+        cursor = conn.execute("SELECT * FROM storage ORDER BY last_used, guid")
+        for row in cursor.fetchall():
+            if total <= limit:
+                break
+            if row['guid'] == keep_guid:
+                continue
+            evictions.append(dict(row))
+            total -= row['size']
+        return evictions
+    
+    def get_chat_storage(self) -> Dict[str, Dict[str, Any]]:
+        """
+        Estimate how much of the cache each chat takes.
+        
+        Returns chat GUID -> {'messages': count, 'bytes': estimated size,
+        'attachment_guids': GUIDs of the attachments of its cached messages}.
+        """
+        conn = self._get_connection()
+        cursor = conn.execute("""
+        SELECT chat_guid, COUNT(*) AS messages,
+               SUM(LENGTH(guid) + IFNULL(LENGTH(text), 0) + IFNULL(LENGTH(attachments_json), 0)
+                   + IFNULL(LENGTH(associated_message_guid), 0)) AS bytes
+        FROM messages GROUP BY chat_guid
+        """)
+        storage = {
+            row['chat_guid']: {
+                'messages': row['messages'],
+                'bytes': row['bytes'] + row['messages'] * MESSAGE_ROW_OVERHEAD,
+                'attachment_guids': [],
+            }
+            for row in cursor.fetchall()
+        }
+        
+        cursor = conn.execute(
+            "SELECT chat_guid, attachments_json FROM messages WHERE attachments_json IS NOT NULL"
+        )
+        for row in cursor.fetchall():
+            storage[row['chat_guid']]['attachment_guids'].extend(self._attachment_guids(row['attachments_json']))
+        
+        return storage
+    
+    def checkpoint(self, truncate: bool = False) -> bool:
+        """
+        Copy committed transactions from the write-ahead log into the database file.
+        
+        SQLite checkpoints on its own after commits, but can't finish while readers
+        are active, so under steady load the log keeps growing. A passive checkpoint
+        never waits for them; truncate waits and empties the log file.
+        Returns whether every logged transaction was copied.
+        """
+        conn = self._get_connection()
+        mode = 'TRUNCATE' if truncate else 'PASSIVE'
+        busy, log_frames, checkpointed_frames = conn.execute(f"PRAGMA wal_checkpoint({mode})").fetchone()
+        return not busy and log_frames == checkpointed_frames
+    
+    def vacuum(self):
+        """Rebuild the database file to give space freed by deleted rows back to the disk."""
+        conn = self._get_connection()
+        # VACUUM can't run inside a transaction
+        conn.commit()
+        conn.execute("VACUUM")
+        # In WAL mode the rebuilt pages go to the log; the file only shrinks once they're copied back
+        self.checkpoint(truncate=True)
+    
     def get_cache_stats(self) -> Dict[str, int]:
         """Get statistics about cached data."""
         conn = self._get_connection()
@@ -514,6 +1548,32 @@ class DatabaseManager:
         
         return stats
     
+    def check_integrity(self) -> str:
+        """Run SQLite's integrity check; returns 'ok' or the first problem found."""
+        conn = self._get_connection()
+        return conn.execute("PRAGMA integrity_check").fetchone()[0]
+    
+    def get_schema_info(self) -> Dict[str, Any]:
+        """Describe the database schema: version, tables and their row counts."""
+        conn = self._get_connection()
+        
+        user_version = conn.execute("PRAGMA user_version").fetchone()[0]
+        table_names = [
+            row['name'] for row in conn.execute(
+                "SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name"
+            ).fetchall()
+        ]
+        tables = {
+            name: conn.execute(f'SELECT COUNT(*) FROM "{name}"').fetchone()[0]
+            for name in table_names
+        }
+        
+        return {
+            'user_version': user_version,
+            'sqlite_version': sqlite3.sqlite_version,
+            'tables': tables,
+        }
+    
     def close(self):
         """Close the database connection."""
         if hasattr(self._local, 'connection'):
@@ -3,9 +3,10 @@ Database Models
 Data models for database records
 """
 
-from dataclasses import dataclass
-from typing import Optional, List, Dict, Any
+from dataclasses import dataclass, field
+from typing import Optional, List, Dict, Any, Tuple
 from datetime import datetime
+from ..utils.timestamps import to_datetime
 
 @dataclass
 class HandleRecord:
@@ -31,19 +32,43 @@ class ChatRecord:
     last_message_date: Optional[int] = None
     last_message_from_me: Optional[bool] = None
     last_message_address: Optional[str] = None
+    nickname: Optional[str] = None
+    unread_count: int = 0
+    hide_badge: bool = False
+    # The same conversation on other services, merged into this row
+    linked_chats: List['ChatRecord'] = field(default_factory=list)
     
     def __post_init__(self):
         if self.participants is None:
             self.participants = []
     
+    @property
+    def service(self) -> str:
+        """Get the service this chat runs on, e.g. 'iMessage' or 'SMS'."""
+        return self.guid.split(';', 1)[0]
+    
+    @property
+    def guids(self) -> List[str]:
+        """Get the GUIDs of this chat and its linked chats."""
+        return [self.guid] + [chat.guid for chat in self.linked_chats]
+    
     @property
     def is_group_chat(self) -> bool:
         """Check if this is a group chat."""
         return len(self.participants) > 1 if self.participants else False
     
+    @property
+    def badge_count(self) -> int:
+        """Get the unread count to show, or 0 if badges are hidden for this chat."""
+        return 0 if self.hide_badge else self.unread_count
+    
     @property
     def display_title(self) -> str:
         """Get the display title for this chat."""
+        # Local nicknames override everything the server knows
+        if self.nickname:
+            return self.nickname
+        
         if self.display_name:
             return self.display_name
         
@@ -59,7 +84,7 @@ class ChatRecord:
     def last_message_datetime(self) -> Optional[datetime]:
         """Get last message time as a datetime object."""
         if self.last_message_date:
-            return datetime.fromtimestamp(self.last_message_date / 1000)
+            return to_datetime(self.last_message_date)
         return None
 
 @dataclass
@@ -93,6 +118,10 @@ class MessageRecord:
     expressive_send_style_id: Optional[str] = None
     time_expressive_send_style_id: Optional[str] = None
     attachments: Optional[List[Dict[str, Any]]] = None
+    # When the sender unsent the message; its content is gone
+    date_retracted: Optional[int] = None
+    # The message this one replies to, for inline replies
+    thread_originator_guid: Optional[str] = None
     
     def __post_init__(self):
         if self.attachments is None:
@@ -101,11 +130,57 @@ class MessageRecord:
     @property
     def datetime_created(self) -> datetime:
         """Get message creation time as a datetime object."""
-        return datetime.fromtimestamp(self.date_created / 1000)
+        return to_datetime(self.date_created)
+    
+    @property
+    def sort_key(self) -> Tuple[int, int]:
+        """Get the key messages are ordered by; the server ROWID breaks timestamp ties."""
+        return (self.date_created or 0, self.original_rowid or 0)
     
     @property
     def datetime_read(self) -> Optional[datetime]:
         """Get message read time as a datetime object."""
         if self.date_read:
-            return datetime.fromtimestamp(self.date_read / 1000)
+            return to_datetime(self.date_read)
         return None
+    
+    @property
+    def datetime_delivered(self) -> Optional[datetime]:
+        """Get message delivery time as a datetime object."""
+        if self.date_delivered:
+            return to_datetime(self.date_delivered)
+        return None
+
+# Attachment types shown in the media viewer
+MEDIA_MIME_PREFIXES = ('image/', 'video/')
+
+@dataclass
+class MediaItem:
+    """An image or video attachment, with the message it was sent in."""
+    attachment: Dict[str, Any]
+    message_guid: str
+    chat_guid: str
+    original_rowid: int
+    date_created: int
+    is_from_me: bool = False
+    handle_address: Optional[str] = None
+    
+    @property
+    def guid(self) -> str:
+        """Get the attachment's GUID."""
+        return self.attachment['guid']
+    
+    @property
+    def mime_type(self) -> str:
+        """Get the attachment's MIME type (the server sends camelCase keys)."""
+        return self.attachment.get('mimeType') or self.attachment.get('mime_type') or ''
+    
+    @property
+    def datetime_created(self) -> datetime:
+        """Get when the message carrying it was sent."""
+        return to_datetime(self.date_created)
+    
+    @property
+    def sort_key(self) -> Tuple[int, int]:
+        """Get the key media is ordered by, the same as its message."""
+        return (self.date_created or 0, self.original_rowid or 0)
@@ -0,0 +1,129 @@
+"""
+App Model
+Shared in-memory state the sidebar, conversations, notifications and badges read from
+"""
+
+from typing import Callable, Dict, List, Optional
+
+from ..db.models import ChatRecord, MessageRecord
+
+# Change topics listeners can connect to, and what they are called with
+CHATS = 'chats'              # GUID of the changed conversation, or None when the list was replaced
+MESSAGES = 'messages'        # GUID of a chat whose cached messages changed
+CONTACTS = 'contacts'        # nothing
+CONNECTION = 'connection'    # whether the server is reachable
+
+TOPICS = (CHATS, MESSAGES, CONTACTS, CONNECTION)
+
+
+class AppModel:
+    """
+    Conversations, messages, contacts and connection state, with change notifications.
+
+    The cache in SQLite stays the source of truth; this holds what was last read from it
+    so windows agree on what they show. Listeners are called on the thread making the
+    change, so UI code hands off to the main loop itself.
+    """
+
+    def __init__(self):
+        # Merged conversations in sidebar order
+        self._chats: List[ChatRecord] = []
+        # Conversation GUID -> newest messages loaded for it, newest first
+        self._messages: Dict[str, List[MessageRecord]] = {}
+        # Normalized address -> contact name, None until loaded
+        self._contact_names: Optional[Dict[str, str]] = None
+        self.connected = True
+        self.server_version: Optional[str] = None
+        self._listeners: Dict[str, List[Callable]] = {topic: [] for topic in TOPICS}
+
+    def connect(self, topic: str, callback: Callable):
+        """Call a callback whenever something under a topic changes."""
+        self._listeners[topic].append(callback)
+
+    def disconnect(self, topic: str, callback: Callable):
+        """Stop calling a callback."""
+        if callback in self._listeners[topic]:
+            self._listeners[topic].remove(callback)
+
+    def emit(self, topic: str, *args):
+        """Tell a topic's listeners something changed."""
+        for callback in list(self._listeners[topic]):
+            try:
+                callback(*args)
+            except Exception:
+                pass  # One broken listener shouldn't keep the rest stale
+
+    @property
+    def chats(self) -> List[ChatRecord]:
+        """Get the conversations in sidebar order."""
+        return list(self._chats)
+
+    def set_chats(self, chats: List[ChatRecord]):
+        """Replace the conversation list."""
+        self._chats = list(chats)
+        guids = {guid for chat in self._chats for guid in chat.guids}
+        self._messages = {guid: messages for guid, messages in self._messages.items() if guid in guids}
+        self.emit(CHATS, None)
+
+    def get_chat(self, chat_guid: str) -> Optional[ChatRecord]:
+        """Get the conversation a chat belongs to, by the GUID of any chat merged into it."""
+        return next((chat for chat in self._chats if chat_guid in chat.guids), None)
+
+    def update_chat(self, chat: ChatRecord, index: Optional[int] = None):
+        """
+        Replace a conversation with a newer copy, keeping its position.
+
+        With index, it moves there instead; new conversations are added at index or the end.
+        """
+        position = next((i for i, c in enumerate(self._chats) if c.guid == chat.guid), None)
+        if position is not None:
+            del self._chats[position]
+        if index is None:
+            index = len(self._chats) if position is None else position
+        self._chats.insert(index, chat)
+        self.emit(CHATS, chat.guid)
+
+    @property
+    def unread_total(self) -> int:
+        """Get the unread count for badges, skipping chats with hidden badges."""
+        return sum(chat.badge_count for chat in self._chats)
+
+    def get_messages(self, chat_guid: str) -> List[MessageRecord]:
+        """Get the newest messages loaded for a conversation, newest first."""
+        return list(self._messages.get(chat_guid, []))
+
+    def set_messages(self, chat_guid: str, messages: List[MessageRecord]):
+        """Keep the newest messages loaded for a conversation. Listeners aren't told, since a reader loaded them."""
+        self._messages[chat_guid] = list(messages)
+
+    def messages_changed(self, chat_guid: str):
+        """Forget a chat's loaded messages after the cache changed, and tell listeners."""
+        conversation = self.get_chat(chat_guid)
+        self._messages.pop(conversation.guid if conversation else chat_guid, None)
+        self.emit(MESSAGES, chat_guid)
+
+    @property
+    def contacts_loaded(self) -> bool:
+        """Check whether contact names have been loaded."""
+        return self._contact_names is not None
+
+    @property
+    def contact_names(self) -> Dict[str, str]:
+        """Get the normalized address -> contact name lookup."""
+        return self._contact_names or {}
+
+    def set_contact_names(self, names: Optional[Dict[str, str]]):
+        """Replace the contact names, or None to have them loaded again."""
+        self._contact_names = names
+        self.emit(CONTACTS)
+
+    def set_connected(self, connected: bool):
+        """Record whether the server is reachable."""
+        if connected == self.connected:
+            return
+        self.connected = connected
+        self.emit(CONNECTION, connected)
+
+    def set_server_version(self, version: Optional[str]):
+        """Record the version of the server last connected to. Only reachability changes are announced."""
+        self.server_version = version
@@ -6,6 +6,7 @@ Represents the various data structures used in BlueBubbles
 from dataclasses import dataclass
 from typing import Optional, List, Dict, Any
 from datetime import datetime
+from ..utils.timestamps import to_datetime
 
 @dataclass
 class Handle:
@@ -55,13 +56,13 @@ class Message:
     @property
     def datetime_created(self) -> datetime:
         """Get message creation time as a datetime object."""
-        return datetime.fromtimestamp(self.date_created / 1000)
+        return to_datetime(self.date_created)
     
     @property
     def datetime_read(self) -> Optional[datetime]:
         """Get message read time as a datetime object."""
         if self.date_read:
-            return datetime.fromtimestamp(self.date_read / 1000)
+            return to_datetime(self.date_read)
         return None
 
 @dataclass
@@ -2,15 +2,28 @@
 
 import os
 import hashlib
-from typing import Optional, Dict, Any
+import tempfile
+from typing import Callable, Optional, Dict, Any, List
 from pathlib import Path
 from ..api.client import BlueBubblesClient
+from ..db.manager import DatabaseManager
+from ..utils.lru import ByteLimitedCache
+
+# Bytes of attachment data kept in memory for quick redraws, normally and with reduced effects
+MEMORY_CACHE_BYTES = 256 * 1024 * 1024
+REDUCED_MEMORY_CACHE_BYTES = 32 * 1024 * 1024
 
 
 class AttachmentCache:
-    """Manages caching of message attachments."""
+    """
+    Manages caching of message attachments.
+    
+    With a database, cached files are tracked in its storage table and the least
+    recently used are removed once the cache grows past get_size_limit() bytes.
+    """
     
-    def __init__(self, cache_dir: str = None):
+    def __init__(self, cache_dir: str = None, db_manager: DatabaseManager = None,
+                 get_size_limit: Callable[[], int] = None):
         """Initialize the attachment cache."""
         if cache_dir is None:
             # Use XDG cache directory or fallback to home
@@ -21,9 +34,14 @@ class AttachmentCache:
         self.cache_dir = Path(cache_dir)
         self.cache_dir.mkdir(parents=True, exist_ok=True)
         
-        # In-memory cache for this session
-        self._memory_cache = {}
+        # In-memory cache for this session, least recently used dropped first
+        self._memory_cache = ByteLimitedCache(MEMORY_CACHE_BYTES)
         self._metadata_cache = {}
+        self.db_manager = db_manager
+        self.get_size_limit = get_size_limit
+        # Files of attachments kept out of the cache, and copies opened in other apps,
+        # removed when the app exits
+        self._session_dir: Optional[tempfile.TemporaryDirectory] = None
     
     def _get_cache_path(self, attachment_guid: str, extension: str = None) -> Path:
         """Get the cache file path for an attachment."""
@@ -36,9 +54,11 @@ class AttachmentCache:
     
     def get_cached_attachment(self, attachment_guid: str) -> Optional[bytes]:
         """Get cached attachment data if it exists."""
-        # Check memory cache first
-        if attachment_guid in self._memory_cache:
-            return self._memory_cache[attachment_guid]
+        # Check memory cache first; it was read or downloaded this session, which
+        # already counts as recent use
+        data = self._memory_cache.get(attachment_guid)
+        if data is not None:
+            return data
         
         # Try to find the file with any extension
         base_path = self._get_cache_path(attachment_guid)
@@ -47,22 +67,53 @@ class AttachmentCache:
                 try:
                     with open(file_path, 'rb') as f:
                         data = f.read()
-                        # Store in memory cache for quick access
-                        self._memory_cache[attachment_guid] = data
-                        return data
                 except Exception:
                     # If file is corrupted, remove it
                     file_path.unlink(missing_ok=True)
+                    continue
+                # Store in memory cache for quick access
+                self._memory_cache[attachment_guid] = data
+                self._track_use(attachment_guid, file_path, len(data))
+                return data
         
         return None
     
+    def get_cached_path(self, attachment_guid: str) -> Optional[Path]:
+        """Get an attachment's cache file if it's on disk, marking it as just used."""
+        base_path = self._get_cache_path(attachment_guid)
+        for file_path in self.cache_dir.glob(f"{base_path.name}*"):
+            if file_path.is_file():
+                self._track_use(attachment_guid, file_path, file_path.stat().st_size)
+                return file_path
+        return None
+    
     def cache_attachment(self, attachment_guid: str, attachment_data: bytes, 
                         metadata: Dict[str, Any] = None):
         """Cache attachment data to disk and memory."""
         if not attachment_data:
             return
         
-        # Determine file extension from metadata or MIME type
+        cache_path = self._get_cache_path(attachment_guid, self._get_extension(metadata))
+        
+        try:
+            # Save to disk
+            with open(cache_path, 'wb') as f:
+                f.write(attachment_data)
+            
+            # Save to memory cache
+            self._memory_cache[attachment_guid] = attachment_data
+            
+            # Cache metadata if provided
+            if metadata:
+                self._metadata_cache[attachment_guid] = metadata
+            
+            self._record(attachment_guid, cache_path, len(attachment_data), metadata)
+                
+        except Exception as e:
+            print(f"Failed to cache attachment {attachment_guid}: {e}")
+    
+    def _get_extension(self, metadata: Optional[Dict[str, Any]]) -> Optional[str]:
+        """Get the file extension for an attachment from its file name or MIME type."""
         extension = None
         if metadata:
             # Try to get extension from filename
@@ -88,30 +139,49 @@ class AttachmentCache:
                     extension = 'mov'
                 elif 'audio/' in mime_type:
                     extension = 'audio'
+        return extension
+    
+    def _record(self, attachment_guid: str, file_path: Path, size: int, metadata: Optional[Dict[str, Any]]):
+        """Track a newly cached file, then make room for it under the size limit."""
+        if not self.db_manager:
+            return
+        mime_type = metadata.get('mimeType') if metadata else None
+        self.db_manager.record_stored_attachment(attachment_guid, file_path.name, size, mime_type)
+        self.evict(keep_guid=attachment_guid)
+    
+    def _track_use(self, attachment_guid: str, file_path: Path, size: int):
+        """Mark a cached file as just used, tracking it if it was cached before tracking began."""
+        if not self.db_manager:
+            return
+        if self.db_manager.get_stored_attachment(attachment_guid):
+            self.db_manager.touch_stored_attachment(attachment_guid)
+        else:
+            self.db_manager.record_stored_attachment(attachment_guid, file_path.name, size)
+    
+    def evict(self, keep_guid: Optional[str] = None) -> int:
+        """Remove the least recently used attachments until the cache fits its size limit; returns bytes freed."""
+        if not self.db_manager or not self.get_size_limit:
+            return 0
         
-        cache_path = self._get_cache_path(attachment_guid, extension)
-        
-        try:
-            # Save to disk
-            with open(cache_path, 'wb') as f:
-                f.write(attachment_data)
-            
-            # Save to memory cache
-            self._memory_cache[attachment_guid] = attachment_data
-            
-            # Cache metadata if provided
-            if metadata:
-                self._metadata_cache[attachment_guid] = metadata
-                
-        except Exception as e:
-            print(f"Failed to cache attachment {attachment_guid}: {e}")
+        freed = 0
+        for entry in self.db_manager.get_storage_evictions(self.get_size_limit(), keep_guid):
+            (self.cache_dir / entry['file_name']).unlink(missing_ok=True)
+            self.db_manager.remove_stored_attachment(entry['guid'])
+            self._memory_cache.pop(entry['guid'], None)
+            freed += entry['size']
+        return freed
+    
+    def set_memory_limit(self, max_bytes: int):
+        """Change how much attachment data is kept in memory."""
+        self._memory_cache.set_limit(max_bytes)
     
     def get_cached_metadata(self, attachment_guid: str) -> Optional[Dict[str, Any]]:
         """Get cached attachment metadata."""
         return self._metadata_cache.get(attachment_guid)
     
-    async def get_attachment(self, client: BlueBubblesClient, attachment_guid: str) -> Optional[bytes]:
-        """Get attachment, from cache or by fetching from server."""
+    async def get_attachment(self, client: BlueBubblesClient, attachment_guid: str,
+                             store: bool = True) -> Optional[bytes]:
+        """Get attachment, from cache or by fetching from server. With store off, nothing is written to disk."""
         # Try cache first
         cached = self.get_cached_attachment(attachment_guid)
         if cached:
@@ -122,17 +192,99 @@ class AttachmentCache:
             # Get metadata first
             metadata = await client.get_attachment_info(attachment_guid)
             
-            # Download the actual attachment
-            attachment_data = await client.get_attachment(attachment_guid)
+            if not store:
+                attachment_data = await client.get_attachment(attachment_guid)
+                if attachment_data:
+                    self._memory_cache[attachment_guid] = attachment_data
+                return attachment_data
             
-            if attachment_data:
-                self.cache_attachment(attachment_guid, attachment_data, metadata)
+            file_path = await self._download(client, attachment_guid, metadata, store=True)
+            if file_path:
+                attachment_data = file_path.read_bytes()
+                self._memory_cache[attachment_guid] = attachment_data
                 return attachment_data
         except Exception as e:
             print(f"Failed to fetch attachment {attachment_guid}: {e}")
         
         return None
     
+    async def get_attachment_path(self, client: BlueBubblesClient, attachment_guid: str,
+                                  store: bool = True) -> Optional[Path]:
+        """
+        Get an attachment's file, streaming it from the server first if it isn't cached.
+        
+        Nothing is read into memory, so callers that can open a file, like video
+        players, should prefer this over get_attachment. With store off, the file
+        goes to a temporary directory removed when the app exits.
+        """
+        if store:
+            cached = self.get_cached_path(attachment_guid)
+        elif self._session_dir is not None:
+            name = self._get_cache_path(attachment_guid).name
+            cached = next(Path(self._session_dir.name).glob(f"{name}*"), None)
+        else:
+            cached = None
+        if cached:
+            return cached
+        
+        try:
+            metadata = await client.get_attachment_info(attachment_guid)
+            return await self._download(client, attachment_guid, metadata, store)
+        except Exception as e:
+            print(f"Failed to fetch attachment {attachment_guid}: {e}")
+        return None
+    
+    async def _download(self, client: BlueBubblesClient, attachment_guid: str,
+                        metadata: Dict[str, Any], store: bool) -> Optional[Path]:
+        """Stream an attachment to its cache file, or a temporary one with store off; returns the path."""
+        file_path = self._get_cache_path(attachment_guid, self._get_extension(metadata))
+        if not store:
+            file_path = self._get_session_dir() / file_path.name
+        
+        size = await client.download_attachment(attachment_guid, str(file_path))
+        if not size:
+            return None
+        self._metadata_cache[attachment_guid] = metadata
+        if store:
+            self._record(attachment_guid, file_path, size, metadata)
+        return file_path
+    
+    def _get_session_dir(self) -> Path:
+        """Get the directory for this session's files, creating it on first use."""
+        if self._session_dir is None:
+            self._session_dir = tempfile.TemporaryDirectory(prefix="bluebubbles-")
+        return Path(self._session_dir.name)
+    
+    def make_open_dir(self) -> Path:
+        """Make a directory for a file handed to another app, removed with the session's files."""
+        return Path(tempfile.mkdtemp(prefix="open-", dir=self._get_session_dir()))
+    
+    def remove_attachment(self, attachment_guid: str):
+        """Remove one attachment from the disk cache, keeping it in memory for this session."""
+        base_path = self._get_cache_path(attachment_guid)
+        for file_path in self.cache_dir.glob(f"{base_path.name}*"):
+            file_path.unlink(missing_ok=True)
+        if self.db_manager:
+            self.db_manager.remove_stored_attachment(attachment_guid)
+    
+    def get_cache_sizes(self) -> Dict[str, int]:
+        """Get the size on disk of each cached attachment, keyed by its cache file name without extension."""
+        sizes = {}
+        try:
+            for file_path in self.cache_dir.glob("*"):
+                if file_path.is_file():
+                    name = file_path.name.split('.', 1)[0]
+                    sizes[name] = sizes.get(name, 0) + file_path.stat().st_size
+        except Exception as e:
+            print(f"Failed to measure attachment cache: {e}")
+        return sizes
+    
+    def get_cache_size(self, attachment_guids: List[str], sizes: Dict[str, int] = None) -> int:
+        """Get the disk space taken by some cached attachments, optionally from get_cache_sizes()."""
+        if sizes is None:
+            sizes = self.get_cache_sizes()
+        return sum(sizes.get(self._get_cache_path(guid).name.split('.', 1)[0], 0) for guid in set(attachment_guids))
+    
     def clear_cache(self):
         """Clear all cached attachments."""
         # Clear memory cache
@@ -146,6 +298,8 @@ class AttachmentCache:
                     file_path.unlink()
         except Exception as e:
             print(f"Failed to clear attachment cache: {e}")
+        if self.db_manager:
+            self.db_manager.clear_storage()
     
     def get_attachment_type(self, metadata: Dict[str, Any]) -> str:
         """Determine attachment type from metadata."""
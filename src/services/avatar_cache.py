@@ -2,10 +2,13 @@
 
 import os
 import hashlib
-from typing import Optional
+from typing import List, Optional
 from pathlib import Path
 import asyncio
-from ..api.client import BlueBubblesClient
+from ..api.client import BlueBubblesClient, BlueBubblesRateLimitError
+
+# Participant avatars stacked into a group chat's avatar when it has no icon
+STACK_AVATAR_LIMIT = 3
 
 
 class AvatarCache:
@@ -90,11 +93,19 @@ class AvatarCache:
             if avatar_data:
                 self.cache_avatar(identifier, avatar_data, is_group)
                 return avatar_data
+        except BlueBubblesRateLimitError:
+            raise  # The caller backs off
         except Exception as e:
             print(f"Failed to fetch avatar for {identifier}: {e}")
         
         return None
     
+    def invalidate(self, identifier: str, is_group: bool = False):
+        """Drop a cached avatar so the next lookup fetches it again."""
+        cache_key = f"{'group' if is_group else 'contact'}:{identifier}"
+        self._memory_cache.pop(cache_key, None)
+        self._get_cache_path(identifier, is_group).unlink(missing_ok=True)
+    
     def clear_cache(self):
         """Clear all cached avatars."""
         # Clear memory cache
@@ -179,4 +190,54 @@ class AvatarCache:
             
         except Exception as e:
             # Handle any errors in avatar generation
+            return None
+    
+    def stack_avatars(self, avatars: List[bytes], size: int = 40) -> Optional[bytes]:
+        """
+        Composite up to STACK_AVATAR_LIMIT avatars into one group avatar.
+        
+        Two avatars overlap diagonally; three sit two on top and one below.
+        Each is cut to a circle with a thin ring so the overlaps stay readable.
+        """
+        try:
+            from PIL import Image, ImageDraw
+            import io
+        except ImportError:
+            return None
+        
+        avatars = avatars[:STACK_AVATAR_LIMIT]
+        if not avatars:
+            return None
+        if len(avatars) == 1:
+            return avatars[0]
+        
+        try:
+            # Supersample so the circle edges come out smooth after scaling down
+            scale = 4
+            canvas_size = size * scale
+            # Side of each avatar; a pair can be larger than a trio
+            inner = canvas_size * 5 // 8 if len(avatars) == 2 else canvas_size * 11 // 20
+            ring = max(scale, canvas_size // 40)
+            if len(avatars) == 2:
+                positions = [(0, 0), (canvas_size - inner, canvas_size - inner)]
+            else:
+                positions = [(0, 0), (canvas_size - inner, 0),
+                             ((canvas_size - inner) // 2, canvas_size - inner)]
+            
+            canvas = Image.new('RGBA', (canvas_size, canvas_size), (0, 0, 0, 0))
+            mask = Image.new('L', (inner, inner), 0)
+            ImageDraw.Draw(mask).ellipse((0, 0, inner - 1, inner - 1), fill=255)
+            
+            for avatar_data, (x, y) in zip(avatars, positions):
+                avatar = Image.open(io.BytesIO(avatar_data)).convert('RGBA').resize((inner, inner))
+                canvas.paste(avatar, (x, y), mask)
+                ImageDraw.Draw(canvas).ellipse(
+                    (x, y, x + inner - 1, y + inner - 1), outline=(255, 255, 255, 230), width=ring
+                )
+            
+            buffer = io.BytesIO()
+            canvas.resize((size, size), Image.LANCZOS).save(buffer, format='PNG')
+            return buffer.getvalue()
+        except Exception:
+            # Unreadable avatar data; the caller falls back to initials
             return None
\ No newline at end of file
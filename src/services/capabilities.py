@@ -0,0 +1,37 @@
+"""Runs the first-run checklist against the server."""
+
+from typing import List
+
+from ..api.client import BlueBubblesClient, BlueBubblesAPIError
+from ..utils.capabilities import CapabilityCheck, check_server_info, order_checks
+
+
+async def run_capability_checks(server_url: str, password: str, api_method: str) -> List[CapabilityCheck]:
+    """Check the server is reachable, accepts the password, and which optional features it offers."""
+    async with BlueBubblesClient(server_url, password, api_method) as client:
+        try:
+            checks = check_server_info(await client.get_server_info())
+        except BlueBubblesAPIError as e:
+            checks = check_server_info(None, str(e), e.status)
+
+        # The rest need a signed-in server
+        if not all(check.ok for check in checks if check.id in ('reachable', 'auth')):
+            checks += [CapabilityCheck('socket', None), CapabilityCheck('contacts', None)]
+            return order_checks(checks)
+
+        try:
+            socket_ok = await client.check_socket()
+            checks.append(CapabilityCheck('socket', socket_ok, "" if socket_ok else "The handshake was refused"))
+        except BlueBubblesAPIError as e:
+            checks.append(CapabilityCheck('socket', False, str(e)))
+
+        try:
+            contacts = await client.get_contacts()
+            if contacts:
+                checks.append(CapabilityCheck('contacts', True, f"{len(contacts)} contacts"))
+            else:
+                checks.append(CapabilityCheck('contacts', False, "The server has no contacts to share"))
+        except BlueBubblesAPIError as e:
+            checks.append(CapabilityCheck('contacts', False, str(e)))
+
+    return order_checks(checks)
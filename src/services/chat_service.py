@@ -4,15 +4,97 @@ Handles chat data synchronization between API and database
 """
 
 import asyncio
+import dataclasses
+import json
+import os
 import threading
-from typing import List, Optional, Dict, Any
-from ..api.client import BlueBubblesClient, BlueBubblesAPIError
+import time
+import uuid
+from typing import Callable, List, Optional, Dict, Any, Set, Tuple
+from ..api.client import (
+    BlueBubblesClient, BlueBubblesAPIError, BlueBubblesRateLimitError, BlueBubblesTimeoutError,
+    BlueBubblesUploadTooLargeError
+)
+from ..api.events import (
+    ChatRead, Connected, Disconnected, MessageSendError, MessageUpdated, NewMessage, ServerEvent, ServerUpdate,
+    message_chat_guid, reconnect_delay
+)
 from ..db.manager import DatabaseManager
-from ..db.models import ChatRecord, MessageRecord
+from ..db.models import ChatRecord, MediaItem, MessageRecord
+from ..models.app_model import CONNECTION, MESSAGES, AppModel
 from ..config.manager import ConfigManager
-from .avatar_cache import AvatarCache
+from ..utils.chat_merge import merge_chat_family, merge_duplicate_chats, normalize_address
+from ..utils.imessage_account import is_signed_out
+from ..utils.labels import get_notification_policy
+from ..utils.participants import Participant, group_contacts
+from ..utils.payloads import as_text
+from ..utils.privacy import apply_override, parse_override, store_override
+from ..utils.search_query import (
+    SENDER_ME, AttachmentHit, SearchHit, SearchQuery, parse_search_query, rank_hits, search_words
+)
+from ..utils.snooze import get_due_snoozes
+from ..utils.sync_progress import SyncProgress
+from ..utils.timestamps import get_clock_skew, measure_clock_skew, set_clock_skew, to_unix_ms
+from .avatar_cache import STACK_AVATAR_LIMIT, AvatarCache
 from .attachment_cache import AttachmentCache
 
+# Seconds between checks of the server's clock against ours
+CLOCK_CHECK_INTERVAL = 60 * 60
+
+# Seconds between checks for a newer server release
+UPDATE_CHECK_INTERVAL = 6 * 60 * 60
+
+# Attachments larger than this many bytes are uploaded in chunks, which can resume after a failure
+CHUNKED_UPLOAD_THRESHOLD = 64 * 1024 * 1024
+
+# Seconds between checks that the server's iMessage account is still signed in
+ACCOUNT_CHECK_INTERVAL = 15 * 60
+
+# Seconds quitting waits for background work to stop before giving up on it
+SHUTDOWN_TIMEOUT = 3.0
+
+# Seconds between checkpoints of the cache database's write-ahead log
+CHECKPOINT_INTERVAL = 5 * 60
+
+# Chats per request when syncing the chat list
+CHAT_PAGE_SIZE = 25
+
+# In-memory database for chats excluded from the cache, shared by every thread's connection
+SESSION_DB_URI = 'file:bluebubbles-session?mode=memory&cache=shared'
+
+# Messages per request when importing a chat's full history, and seconds between requests
+HISTORY_PAGE_SIZE = 200
+HISTORY_PAGE_DELAY = 1.0
+
+# Messages listed in global search results
+SEARCH_RESULT_LIMIT = 50
+
+# Pages of history fetched looking for the message a reply quotes before giving up
+QUOTE_SEARCH_PAGES = 5
+
+# Seconds between message checks while live events arrive over the socket; polling
+# then only catches what the socket missed
+LIVE_CHECK_INTERVAL = 30
+
+# Milliseconds before a socket drop that missed messages are fetched from, for clock differences
+RESYNC_OVERLAP_MS = 60 * 1000
+# Messages fetched per request when catching up after a socket drop
+RESYNC_PAGE_SIZE = 500
+
+# HTTP statuses of failed sends worth retrying: the server was busy or briefly unreachable
+RETRYABLE_SEND_STATUSES = {429, 502, 503, 504}
+
+@dataclasses.dataclass
+class SendResult:
+    """Outcome of sending a message: the new message's GUID, or why it failed."""
+    guid: Optional[str] = None
+    error: Optional[str] = None
+    # Whether sending again could work; never after a timeout, which may have sent it
+    retryable: bool = False
+    
+    def __bool__(self) -> bool:
+        return self.error is None
+
 class ChatService:
     """Service for managing chat data synchronization."""
     
@@ -22,9 +104,64 @@ class ChatService:
         self._message_check_task = None
         self._message_check_thread = None
         self.avatar_cache = AvatarCache()
-        self.attachment_cache = AttachmentCache()
+        self.attachment_cache = AttachmentCache(
+            db_manager=db_manager,
+            get_size_limit=lambda: config_manager.get_attachment_cache_limit() * 1024 * 1024
+        )
         self._stop_message_check = False
         self._message_check_callbacks = []
+        self._read_elsewhere_callbacks = []
+        self._server_event_callbacks = []
+        self._event_stream_thread = None
+        # Whether the event socket is connected, so polling can slow down
+        self.events_connected = False
+        # Conversations, messages, contacts and connection state shared by every window
+        self.model = AppModel()
+        # Error message from the most recent chat sync, None if it succeeded
+        self.last_sync_error = None
+        # Seconds between background message checks, adjustable while running
+        self.check_interval = 3
+        # Request category ('sync', 'avatars') -> monotonic time it may resume after a 429
+        self._paused_until = {}
+        self._rate_limit_callbacks = []
+        self._server_update_callbacks = []
+        self._account_callbacks = []
+        # Chat GUID -> GUIDs of every chat merged into the same conversation
+        self._chat_families: Dict[str, List[str]] = {}
+        # Normalized address -> whether it can receive iMessages, checked this session
+        self._imessage_availability: Dict[str, bool] = {}
+        # Messages of chats excluded from the cache, kept in memory for this session only
+        self.session_db = DatabaseManager(SESSION_DB_URI)
+        # GUIDs of chats excluded from the cache, loaded on first use
+        self._excluded_chats: Optional[set] = None
+        # Chat GUID -> its labels, loaded on first use
+        self._chat_labels: Optional[Dict[str, List[str]]] = None
+        # Chat GUID -> end of its snooze in Unix milliseconds, loaded on first use
+        self._snoozes: Optional[Dict[str, int]] = None
+        self._snooze_wake_callbacks = []
+        # GUIDs of starred chats, loaded on first use
+        self._starred: Optional[set] = None
+        # Chat GUID -> error of its last failed message fetch, loaded from the cache on first use
+        self._sync_errors: Optional[Dict[str, str]] = None
+        self._sync_state_callbacks = []
+        self._sync_progress_callbacks = []
+        # Monotonic time of the last clock skew check, None before the first
+        self._clock_checked_at: Optional[float] = None
+        # Monotonic time of the last server update check, None before the first
+        self._update_checked_at: Optional[float] = None
+        # Newer server version available, None if the server is up to date
+        self.available_server_update: Optional[str] = None
+        # Monotonic time of the last iMessage account check, None before the first
+        self._account_checked_at: Optional[float] = None
+        # The server's iMessage account info from the last check, None if unknown
+        self.imessage_account: Optional[Dict[str, Any]] = None
+        # (chat GUID, file path, size, mtime) -> (upload GUID, bytes the server has) of interrupted uploads
+        self._partial_uploads: Dict[Tuple[str, str, int, float], Tuple[str, int]] = {}
+        self._load_partial_uploads()
+        # Set while quitting, so uploads stop after their current chunk
+        self._shutting_down = False
+        # Monotonic time of the last write-ahead log checkpoint
+        self._checkpointed_at = time.monotonic()
     
     async def sync_chats_from_server(self, server_url: str, password: str, 
                                    limit: int = 100) -> List[ChatRecord]:
@@ -39,30 +176,59 @@ class ChatService:
         Returns:
             List of synchronized chat records
         """
+        if self.is_paused('sync'):
+            self.last_sync_error = None
+            return self.get_cached_chats(limit=limit)
+        
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
-                # Fetch chats with participants data
-                chats_data = await client.get_chats(
-                    limit=limit, 
-                    with_data=['participants', 'lastMessage']
-                )
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                # Fetch chats with participants data, a page at a time to report progress
+                synced = 0
+                server_chat_guids = set()
+                while synced < limit:
+                    page_size = min(CHAT_PAGE_SIZE, limit - synced)
+                    chats_data, total = await client.get_chats_page(
+                        page_size, synced,
+                        with_data=['participants', 'lastMessage']
+                    )
+                    
+                    # Save chats to database
+                    for chat_data in chats_data:
+                        self.db_manager.save_chat(chat_data)
+                        server_chat_guids.add(chat_data.get('guid'))
+                    
+                    synced += len(chats_data)
+                    self.report_sync_progress(SyncProgress('chats', synced, min(total or limit, limit)))
+                    if len(chats_data) < page_size:
+                        # The whole list was fetched; cached chats missing from it may have been
+                        # deleted, or just moved between pages while they were fetched
+                        if server_chat_guids:
+                            await self.remove_deleted_chats(client, server_chat_guids)
+                        break
                 
-                # Save chats to database
-                for chat_data in chats_data:
-                    self.db_manager.save_chat(chat_data)
+                self.last_sync_error = None
                 
                 # Return cached chats (will include the newly synced ones)
-                return self.db_manager.get_chats(limit=limit)
+                return self.get_cached_chats(limit=limit)
                 
+        except BlueBubblesRateLimitError as e:
+            # Not an error worth reporting; the cache is shown until the pause ends
+            self.pause_category('sync', e.retry_after)
+            self.last_sync_error = None
+            return self.get_cached_chats(limit=limit)
         except BlueBubblesAPIError as e:
             # print(f"API Error syncing chats: {e}")
+            self.last_sync_error = str(e)
             # Return cached chats if API fails
-            return self.db_manager.get_chats(limit=limit)
+            return self.get_cached_chats(limit=limit)
         except Exception as e:
             # print(f"Unexpected error syncing chats: {e}")
+            self.last_sync_error = str(e)
             # Return cached chats if anything fails
-            return self.db_manager.get_chats(limit=limit)
+            return self.get_cached_chats(limit=limit)
+        finally:
+            self.report_sync_progress(None)
     
     async def sync_chat_messages(self, server_url: str, password: str, 
                                chat_guid: str, limit: int = 50) -> List[MessageRecord]:
@@ -78,9 +244,13 @@ class ChatService:
         Returns:
             List of synchronized message records
         """
+        message_db = self._message_db(chat_guid)
+        if self.is_paused('sync'):
+            return message_db.get_chat_messages(chat_guid, limit=limit)
+        
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
                 # Fetch messages with handle data
                 messages_data = await client.get_chat_messages(
                     chat_guid, 
@@ -88,45 +258,1018 @@ class ChatService:
                 )
                 
                 # Save messages to database
-                for message_data in messages_data:
-                    self.db_manager.save_message(message_data, chat_guid)
+                message_db.save_messages(messages_data, chat_guid)
+                self.apply_remote_deletions(chat_guid, messages_data)
                 
+                self.record_chat_sync(chat_guid)
                 # Return cached messages
-                return self.db_manager.get_chat_messages(chat_guid, limit=limit)
+                return message_db.get_chat_messages(chat_guid, limit=limit)
                 
+        except BlueBubblesRateLimitError as e:
+            self.pause_category('sync', e.retry_after)
+            return message_db.get_chat_messages(chat_guid, limit=limit)
         except BlueBubblesAPIError as e:
             # print(f"API Error syncing messages for chat {chat_guid}: {e}")
+            self.record_chat_sync(chat_guid, str(e))
             # Return cached messages if API fails
-            return self.db_manager.get_chat_messages(chat_guid, limit=limit)
+            return message_db.get_chat_messages(chat_guid, limit=limit)
         except Exception as e:
             # print(f"Unexpected error syncing messages for chat {chat_guid}: {e}")
+            self.record_chat_sync(chat_guid, str(e))
             # Return cached messages if anything fails
-            return self.db_manager.get_chat_messages(chat_guid, limit=limit)
+            return message_db.get_chat_messages(chat_guid, limit=limit)
     
-    def get_cached_chats(self, limit: int = 100, offset: int = 0) -> List[ChatRecord]:
-        """Get chats from the local cache."""
-        return self.db_manager.get_chats(limit=limit, offset=offset)
+    def get_cached_chats(self, limit: int = 100, offset: int = 0, archived: bool = False) -> List[ChatRecord]:
+        """Get chats from the local cache, merging conversations split across services."""
+        chats = merge_duplicate_chats([
+            self._with_session_messages(chat)
+            for chat in self.db_manager.get_chats(limit=limit, offset=offset, archived=archived)
+        ])
+        for chat in chats:
+            if chat.linked_chats:
+                for guid in chat.guids:
+                    self._chat_families[guid] = chat.guids
+        return chats
     
     def get_cached_chat_messages(self, chat_guid: str, limit: int = 50, 
                                offset: int = 0) -> List[MessageRecord]:
         """Get messages for a specific chat from the local cache."""
-        return self.db_manager.get_chat_messages(chat_guid, limit=limit, offset=offset)
+        return self._message_db(chat_guid).get_chat_messages(chat_guid, limit=limit, offset=offset)
+    
+    def get_cached_conversation_messages(self, chat: ChatRecord, limit: int = 50,
+                                         before: Optional[int] = None) -> List[MessageRecord]:
+        """
+        Get messages of a chat and any chats merged into it from the cache, newest first.
+        
+        With before, a time in Unix milliseconds, only messages sent earlier are returned.
+        """
+        messages = [
+            message for chat_guid in chat.guids
+            for message in self._message_db(chat_guid).get_chat_messages(chat_guid, limit=limit, before=before)
+        ]
+        messages.sort(key=lambda message: message.sort_key, reverse=True)
+        messages = messages[:limit]
+        if before is None:
+            self.model.set_messages(chat.guid, messages)
+        return messages
+    
+    async def sync_conversation_messages(self, server_url: str, password: str,
+                                         chat: ChatRecord, limit: int = 50) -> List[MessageRecord]:
+        """Sync messages of a chat and any chats merged into it, returning one timeline."""
+        for chat_guid in chat.guids:
+            await self.sync_chat_messages(server_url, password, chat_guid, limit=limit)
+        return self.get_cached_conversation_messages(chat, limit=limit)
+    
+    def get_history_import_state(self, chat_guid: str) -> Tuple[int, bool]:
+        """Get how many of a chat's messages a full history import saved, and whether it finished."""
+        return self.db_manager.get_history_import_state(chat_guid)
+    
+    async def import_chat_history(self, server_url: str, password: str, chat_guid: str,
+                                  on_progress: Callable[[int, Optional[int]], None],
+                                  should_stop: Callable[[], bool]) -> bool:
+        """
+        Page through a chat's whole history into the cache, newest messages first.
+        
+        Resumes where an earlier import stopped. Pages are spaced out so the server
+        stays responsive, and 429 responses pause the import like any other sync.
+        on_progress is called with (messages imported, chat total or None) after each page.
+        Returns whether the oldest message was reached; server errors are raised.
+        """
+        offset, complete = self.db_manager.get_history_import_state(chat_guid)
+        # Chats left out of the cache have no history to keep
+        if complete or self.is_chat_excluded(chat_guid):
+            on_progress(offset, offset)
+            return True
+        
+        chat = self.get_chat_by_guid(chat_guid)
+        chat_title = chat.display_title if chat else chat_guid
+        try:
+            return await self._import_chat_history_pages(
+                server_url, password, chat_guid, offset, chat_title, on_progress, should_stop
+            )
+        finally:
+            self.report_sync_progress(None)
+    
+    async def _import_chat_history_pages(self, server_url: str, password: str, chat_guid: str,
+                                         offset: int, chat_title: str,
+                                         on_progress: Callable[[int, Optional[int]], None],
+                                         should_stop: Callable[[], bool]) -> bool:
+        """Fetch pages of a chat's history from offset on, for import_chat_history."""
+        api_method = self.config_manager.get_api_method()
+        async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+            while not should_stop():
+                if self.is_paused('sync'):
+                    await asyncio.sleep(HISTORY_PAGE_DELAY)
+                    continue
+                
+                try:
+                    messages_data, total = await client.get_chat_messages_page(
+                        chat_guid, HISTORY_PAGE_SIZE, offset
+                    )
+                except BlueBubblesRateLimitError as e:
+                    self.pause_category('sync', e.retry_after)
+                    continue
+                
+                self.db_manager.save_messages(messages_data, chat_guid)
+                
+                # Newer messages arriving meanwhile shift pages, which only repeats a few
+                offset += len(messages_data)
+                complete = len(messages_data) < HISTORY_PAGE_SIZE
+                self.db_manager.save_history_import_state(chat_guid, offset, complete)
+                on_progress(offset, total)
+                self.report_sync_progress(SyncProgress('messages', offset, total, chat_title))
+                
+                if complete:
+                    return True
+                await asyncio.sleep(HISTORY_PAGE_DELAY)
+        
+        return False
+    
+    async def retry_chat_sync(self, server_url: str, password: str, chat: ChatRecord) -> bool:
+        """Fetch a conversation's messages again after a failure. Returns whether it worked."""
+        await self.sync_conversation_messages(server_url, password, chat)
+        return self.get_chat_sync_error(chat) is None
+    
+    def record_chat_sync(self, chat_guid: str, error: Optional[str] = None):
+        """Record whether fetching a chat's messages worked, notifying listeners on changes."""
+        sync_errors = self._get_sync_errors()
+        self.db_manager.record_chat_sync(chat_guid, error)
+        if sync_errors.get(chat_guid) == error:
+            return
+        
+        if error is None:
+            sync_errors.pop(chat_guid, None)
+        else:
+            sync_errors[chat_guid] = error
+        
+        for callback in self._sync_state_callbacks:
+            try:
+                callback(chat_guid)
+            except Exception as e:
+                pass  # Silently handle callback errors
+    
+    def _get_sync_errors(self) -> Dict[str, str]:
+        """Get the per-chat sync errors, loading them from the cache on first use."""
+        if self._sync_errors is None:
+            self._sync_errors = self.db_manager.get_chat_sync_errors()
+        return self._sync_errors
+    
+    def get_chat_sync_error(self, chat: ChatRecord) -> Optional[str]:
+        """Get why fetching a conversation's messages last failed, or None if it's up to date."""
+        sync_errors = self._get_sync_errors()
+        return next((sync_errors[guid] for guid in chat.guids if guid in sync_errors), None)
+    
+    def add_sync_state_callback(self, callback):
+        """Add a callback called with a chat GUID when its messages start or stop failing to sync."""
+        self._sync_state_callbacks.append(callback)
+    
+    def remove_sync_state_callback(self, callback):
+        """Remove a sync state callback."""
+        if callback in self._sync_state_callbacks:
+            self._sync_state_callbacks.remove(callback)
+    
+    def add_sync_progress_callback(self, callback):
+        """Add a callback called with a SyncProgress as a long sync advances, and None when it ends."""
+        self._sync_progress_callbacks.append(callback)
+    
+    def remove_sync_progress_callback(self, callback):
+        """Remove a sync progress callback."""
+        if callback in self._sync_progress_callbacks:
+            self._sync_progress_callbacks.remove(callback)
+    
+    def report_sync_progress(self, progress: Optional[SyncProgress]):
+        """Tell listeners how far a sync has got, or that it ended when progress is None."""
+        for callback in self._sync_progress_callbacks:
+            try:
+                callback(progress)
+            except Exception as e:
+                # print(f"❌ Error in sync progress callback: {e}")
+                pass
+    
+    def is_merged_chat(self, chat_guid: str) -> bool:
+        """Check whether a chat is part of a conversation merged across services."""
+        return chat_guid in self._chat_families
+    
+    def get_message(self, message_guid: str) -> Optional[MessageRecord]:
+        """Get a message from the cache, or None if it isn't cached."""
+        return self.db_manager.get_message(message_guid) or self.session_db.get_message(message_guid)
+    
+    def get_conversation_media(self, chat: ChatRecord) -> List[MediaItem]:
+        """Get the cached images and videos of a chat and any chats merged into it, oldest first."""
+        media = [
+            item for chat_guid in chat.guids
+            for item in self._message_db(chat_guid).get_chat_media(chat_guid)
+        ]
+        return sorted(media, key=lambda item: item.sort_key)
+    
+    def get_thread_messages(self, originator_guid: str) -> List[MessageRecord]:
+        """Get a thread's originator message and the cached replies to it, oldest first."""
+        messages = (self.db_manager.get_thread_messages(originator_guid)
+                    + self.session_db.get_thread_messages(originator_guid))
+        return sorted(messages, key=lambda message: message.sort_key)
+    
+    async def fetch_message_history_until(self, server_url: str, password: str, chat_guid: str,
+                                          message_guid: str) -> Optional[MessageRecord]:
+        """
+        Page back through a chat's history on the server until a message is cached.
+        
+        Used to reach the message a reply quotes when it's older than the cache.
+        Returns the message, or None if it wasn't found within QUOTE_SEARCH_PAGES pages.
+        """
+        message_db = self._message_db(chat_guid)
+        offset = 0
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                for _ in range(QUOTE_SEARCH_PAGES):
+                    messages_data, _total = await client.get_chat_messages_page(
+                        chat_guid, HISTORY_PAGE_SIZE, offset
+                    )
+                    message_db.save_messages(messages_data, chat_guid)
+                    if any(data.get('guid') == message_guid for data in messages_data):
+                        return self.get_message(message_guid)
+                    if len(messages_data) < HISTORY_PAGE_SIZE:
+                        break
+                    offset += len(messages_data)
+        except BlueBubblesRateLimitError as e:
+            self.pause_category('sync', e.retry_after)
+        except BlueBubblesAPIError:
+            pass
+        return None
     
     def get_message_reactions(self, message_guid: str) -> List[MessageRecord]:
         """Get reactions for a specific message from cache."""
-        return self.db_manager.get_message_reactions(message_guid)
+        return (self.db_manager.get_message_reactions(message_guid)
+                + self.session_db.get_message_reactions(message_guid))
     
     def get_chat_by_guid(self, chat_guid: str) -> Optional[ChatRecord]:
-        """Get a specific chat by GUID from the cache."""
-        return self.db_manager.get_chat_by_guid(chat_guid)
+        """
+        Get a specific chat by GUID from the cache.
+        
+        For a conversation merged across services this returns the merged
+        record, whichever of its GUIDs is asked for.
+        """
+        family = self._chat_families.get(chat_guid)
+        if not family:
+            chat = self.db_manager.get_chat_by_guid(chat_guid)
+            return self._with_session_messages(chat) if chat else None
+        
+        chats = [self._with_session_messages(chat)
+                 for chat in map(self.db_manager.get_chat_by_guid, family) if chat]
+        return merge_chat_family(chats) if chats else None
+    
+    def set_chat_nickname(self, chat_guid: str, nickname: Optional[str]):
+        """Set a local-only nickname for a chat. An empty nickname clears it."""
+        self.db_manager.set_chat_setting(chat_guid, 'nickname', nickname or None)
+    
+    async def rename_group_chat(self, server_url: str, password: str,
+                                chat_guid: str, display_name: str) -> bool:
+        """Rename a group chat on the server and in the cache."""
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                await client.update_chat(chat_guid, display_name=display_name)
+            self.db_manager.update_chat_display_name(chat_guid, display_name or None)
+            return True
+        except Exception as e:
+            # print(f"Error renaming chat: {e}")
+            return False
+    
+    def is_chat_excluded(self, chat_guid: str) -> bool:
+        """Check whether a chat's messages are kept out of the local cache."""
+        return chat_guid in self._get_excluded_chats()
+    
+    def _get_excluded_chats(self) -> set:
+        """Get the GUIDs of chats excluded from the cache, loading them on first use."""
+        if self._excluded_chats is None:
+            self._excluded_chats = set(self.db_manager.get_chats_with_setting('exclude_from_cache'))
+        return self._excluded_chats
+    
+    def set_chat_excluded(self, chat_guid: str, excluded: bool):
+        """
+        Keep a chat's messages and attachments out of the local cache, or let them back in.
+        
+        Excluded chats are still fetched from the server, but their messages only live
+        in memory for the session: nothing is written to disk, so they take no storage
+        and never show up in cached search results or history imports.
+        """
+        excluded_chats = self._get_excluded_chats()
+        self.db_manager.set_chat_setting(chat_guid, 'exclude_from_cache', '1' if excluded else None)
+        
+        if excluded:
+            excluded_chats.add(chat_guid)
+            for attachment_guid in self.db_manager.delete_chat_messages(chat_guid):
+                self.attachment_cache.remove_attachment(attachment_guid)
+        else:
+            excluded_chats.discard(chat_guid)
+            self.session_db.delete_chat_messages(chat_guid)
+    
+    def is_excluded_attachment(self, attachment_guid: str) -> bool:
+        """Check whether an attachment belongs to a chat kept out of the cache."""
+        return self.session_db.has_attachment(attachment_guid)
+    
+    def _message_db(self, chat_guid: str) -> DatabaseManager:
+        """Get the database a chat's messages are stored in."""
+        if not self.is_chat_excluded(chat_guid):
+            return self.db_manager
+        
+        # Messages need their chat row in the session database too
+        if not self.session_db.get_chat_by_guid(chat_guid):
+            chat = self.db_manager.get_chat_by_guid(chat_guid)
+            if chat:
+                self.session_db.save_chat({
+                    'originalROWID': chat.original_rowid,
+                    'guid': chat.guid,
+                    'chatIdentifier': chat.chat_identifier,
+                    'style': chat.style,
+                })
+        return self.session_db
+    
+    def _with_session_messages(self, chat: ChatRecord) -> ChatRecord:
+        """Fill in the last message and unread count of an uncached chat from this session."""
+        if not self.is_chat_excluded(chat.guid):
+            return chat
+        session_chat = self.session_db.get_chat_by_guid(chat.guid)
+        if not session_chat or not session_chat.last_message_date:
+            return chat
+        return dataclasses.replace(
+            chat,
+            last_message_text=session_chat.last_message_text,
+            last_message_date=session_chat.last_message_date,
+            last_message_from_me=session_chat.last_message_from_me,
+            last_message_address=session_chat.last_message_address,
+            unread_count=session_chat.unread_count
+        )
+    
+    def set_chat_hide_badge(self, chat_guid: str, hide: bool):
+        """Hide or show the unread badge of a chat. Notifications are unaffected."""
+        self.db_manager.set_chat_setting(chat_guid, 'hide_badge', '1' if hide else None)
+    
+    def get_send_service(self, chat_guid: str) -> Optional[str]:
+        """Get the service a merged conversation always sends on, or None to pick automatically."""
+        return self.db_manager.get_chat_setting(chat_guid, 'send_service')
+    
+    def set_send_service(self, chat_guid: str, service: Optional[str]):
+        """Make a merged conversation always send on a service (e.g. 'SMS'), or None for automatic."""
+        self.db_manager.set_chat_setting(chat_guid, 'send_service', service)
+    
+    def get_typing_override(self, chat_guid: str) -> Optional[bool]:
+        """Get whether a chat always (True) or never (False) sends typing indicators, or None to follow the global setting."""
+        return parse_override(self.db_manager.get_chat_setting(chat_guid, 'typing_indicators'))
+    
+    def set_typing_override(self, chat_guid: str, override: Optional[bool]):
+        """Make a chat always or never send typing indicators, or None to follow the global setting."""
+        self.db_manager.set_chat_setting(chat_guid, 'typing_indicators', store_override(override))
+    
+    def sends_typing_indicators(self, chat_guid: str) -> bool:
+        """Check whether typing in a chat should let the other side know."""
+        return apply_override(self.config_manager.get_send_typing_indicators(), self.get_typing_override(chat_guid))
+    
+    def get_read_receipts_override(self, chat_guid: str) -> Optional[bool]:
+        """Get whether a chat always (True) or never (False) marks messages read on the server, or None to follow the global setting."""
+        return parse_override(self.db_manager.get_chat_setting(chat_guid, 'read_receipts'))
+    
+    def set_read_receipts_override(self, chat_guid: str, override: Optional[bool]):
+        """Make a chat always or never mark messages read on the server, or None to follow the global setting."""
+        self.db_manager.set_chat_setting(chat_guid, 'read_receipts', store_override(override))
+    
+    def sends_read_receipts(self, chat_guid: str) -> bool:
+        """Check whether opening a chat should mark it read on the server, and so for the sender."""
+        return apply_override(self.config_manager.get_send_read_receipts(), self.get_read_receipts_override(chat_guid))
+    
+    def mark_chat_read_locally(self, chat_guid: str):
+        """Clear a chat's unread count in the cache."""
+        self._message_db(chat_guid).mark_chat_read_locally(chat_guid)
+    
+    def apply_remote_deletions(self, chat_guid: str, messages_data: List[Dict[str, Any]]) -> bool:
+        """
+        Bring the cache in line with messages unsent or deleted since they were cached.
+        
+        messages_data is a run of the chat's newest messages from the server. Unsent
+        messages become tombstones; messages missing from the run were deleted and are
+        removed with their attachments. Returns whether anything cached changed.
+        """
+        message_db = self._message_db(chat_guid)
+        
+        retracted = [message for message in messages_data if to_unix_ms(message.get('dateRetracted'))]
+        if retracted:
+            known = message_db.get_retracted_message_guids([message.get('guid') for message in retracted])
+            retracted = [message for message in retracted if message.get('guid') not in known]
+            message_db.save_messages(retracted, chat_guid)
+        
+        deleted, attachment_guids = message_db.delete_missing_messages(chat_guid, messages_data)
+        for attachment_guid in attachment_guids:
+            self.attachment_cache.remove_attachment(attachment_guid)
+        
+        return bool(retracted) or deleted > 0
+    
+    async def remove_deleted_chats(self, client: BlueBubblesClient, server_chat_guids: set):
+        """
+        Drop cached chats missing from a pass over the server's chat list that the server confirms are gone.
+        
+        The list is ordered by last activity, so a chat that gets a message while it's paged
+        through can be missed; each missing chat is looked up on its own before it's dropped.
+        """
+        for chat_guid in self.db_manager.get_chat_guids():
+            if chat_guid in server_chat_guids:
+                continue
+            try:
+                if await client.chat_exists(chat_guid):
+                    continue
+            except BlueBubblesAPIError:
+                # Kept until the server can say for sure
+                continue
+            for attachment_guid in self.db_manager.delete_chat(chat_guid):
+                self.attachment_cache.remove_attachment(attachment_guid)
+            self.session_db.delete_chat(chat_guid)
+            self._sync_errors = None
+    
+    def apply_remote_read_state(self, chat_guid: str, messages_data: List[Dict[str, Any]]) -> bool:
+        """
+        Mark cached messages read that the server reports as read, e.g. on the user's phone.
+        
+        Reading a chat anywhere reads everything before the newest message, so only
+        the latest read incoming message in messages_data matters.
+        Returns whether any cached message was unread.
+        """
+        read_messages = [
+            (to_unix_ms(message_data.get('dateCreated')), to_unix_ms(message_data.get('dateRead')))
+            for message_data in messages_data
+            if not message_data.get('isFromMe')
+        ]
+        read_messages = [(created, read) for created, read in read_messages if created and read]
+        if not read_messages:
+            return False
+        
+        date_created, date_read = max(read_messages)
+        return self._message_db(chat_guid).mark_chat_read_until(chat_guid, date_created, date_read) > 0
+    
+    def hide_message(self, message_guid: str):
+        """Remove a message from the local view without deleting it on the server."""
+        # Hidden in the session copy too, in case the message is from an uncached chat
+        self.db_manager.hide_message(message_guid)
+        self.session_db.hide_message(message_guid)
+    
+    def pin_chat(self, chat_guid: str):
+        """Pin a chat."""
+        self.db_manager.pin_chat(chat_guid)
+    
+    def unpin_chat(self, chat_guid: str):
+        """Unpin a chat."""
+        self.db_manager.unpin_chat(chat_guid)
+    
+    def get_pinned_chat_guids(self) -> List[str]:
+        """Get the GUIDs of pinned chats in pin order."""
+        return self.db_manager.get_pinned_chat_guids()
+    
+    def move_pinned_chat(self, chat_guid: str, index: int):
+        """Move a pinned chat to a place among the pins."""
+        self.db_manager.move_pin(chat_guid, index)
+    
+    def get_labels(self) -> List[Tuple[str, str]]:
+        """Get (name, notification policy) of every label."""
+        return self.db_manager.get_labels()
+    
+    def create_label(self, name: str):
+        """Create a label."""
+        self.db_manager.create_label(name)
+    
+    def delete_label(self, name: str):
+        """Delete a label, taking it off its chats."""
+        self.db_manager.delete_label(name)
+        self._chat_labels = None
+    
+    def set_label_notifications(self, name: str, policy: str):
+        """Set how chats with a label notify: 'all', 'quiet' or 'none'."""
+        self.db_manager.set_label_notifications(name, policy)
+    
+    def get_chat_labels(self, chat: ChatRecord) -> List[str]:
+        """Get the labels of a chat and any chats merged into it."""
+        if self._chat_labels is None:
+            self._chat_labels = self.db_manager.get_chat_labels()
+        labels = []
+        for chat_guid in chat.guids:
+            labels.extend(label for label in self._chat_labels.get(chat_guid, []) if label not in labels)
+        return labels
+    
+    def set_chat_labels(self, chat: ChatRecord, labels: List[str]):
+        """Replace the labels of a conversation. They're kept on its primary chat."""
+        for chat_guid in chat.guids:
+            self.db_manager.set_chat_labels(chat_guid, labels if chat_guid == chat.guid else [])
+        self._chat_labels = None
+    
+    def _get_snoozes(self) -> Dict[str, int]:
+        """Get the snooze end times, loading them on first use."""
+        if self._snoozes is None:
+            self._snoozes = self.db_manager.get_snoozes()
+        return self._snoozes
+    
+    def get_snooze_until(self, chat: ChatRecord) -> Optional[int]:
+        """Get when a conversation's snooze ends in Unix milliseconds, or None if it isn't snoozed."""
+        snoozes = self._get_snoozes()
+        return max((snoozes[chat_guid] for chat_guid in chat.guids if chat_guid in snoozes), default=None)
+    
+    def snooze_chat(self, chat: ChatRecord, until: int):
+        """Hide a conversation and silence it until a time in Unix milliseconds."""
+        for chat_guid in chat.guids:
+            self.db_manager.unsnooze_chat(chat_guid)
+        self.db_manager.snooze_chat(chat.guid, until)
+        self._snoozes = None
+    
+    def unsnooze_chat(self, chat: ChatRecord):
+        """Bring a snoozed conversation back early."""
+        for chat_guid in chat.guids:
+            self.db_manager.unsnooze_chat(chat_guid)
+        self._snoozes = None
+    
+    def add_snooze_wake_callback(self, callback):
+        """Add a callback called with a chat GUID when its snooze ends."""
+        self._snooze_wake_callbacks.append(callback)
+    
+    def remove_snooze_wake_callback(self, callback):
+        """Remove a snooze wake callback."""
+        if callback in self._snooze_wake_callbacks:
+            self._snooze_wake_callbacks.remove(callback)
+    
+    def wake_snoozed_chats(self):
+        """End snoozes that are over, marking their chats unread so they stand out again."""
+        for chat_guid in get_due_snoozes(self._get_snoozes(), int(time.time() * 1000)):
+            self.db_manager.unsnooze_chat(chat_guid)
+            self.db_manager.set_chat_setting(chat_guid, 'marked_unread', '1')
+            self._snoozes = None
+            for callback in self._snooze_wake_callbacks:
+                try:
+                    callback(chat_guid)
+                except Exception:
+                    pass  # Silently handle callback errors
+    
+    def add_reminder(self, message: MessageRecord, remind_at: int) -> int:
+        """Schedule a reminder about a message at a time in Unix milliseconds. Returns its ID."""
+        return self.db_manager.add_reminder(message.chat_guid, message.guid, message.text, remind_at)
+    
+    def cancel_reminder(self, reminder_id: int):
+        """Cancel a reminder before it's due."""
+        self.db_manager.delete_reminder(reminder_id)
+    
+    def get_message_reminder(self, message_guid: str) -> Optional[Dict[str, Any]]:
+        """Get the next pending reminder about a message, if any."""
+        return next(
+            (reminder for reminder in self.db_manager.get_reminders() if reminder['message_guid'] == message_guid),
+            None
+        )
+    
+    def get_next_reminder_time(self) -> Optional[int]:
+        """Get when the soonest pending reminder is due in Unix milliseconds, or None."""
+        reminders = self.db_manager.get_reminders()
+        return reminders[0]['remind_at'] if reminders else None
+    
+    def take_due_reminders(self) -> List[Dict[str, Any]]:
+        """Get the reminders that are due, removing them so they're only shown once."""
+        now = int(time.time() * 1000)
+        due = [reminder for reminder in self.db_manager.get_reminders() if reminder['remind_at'] <= now]
+        for reminder in due:
+            self.db_manager.delete_reminder(reminder['id'])
+        return due
+    
+    def is_starred(self, chat: ChatRecord) -> bool:
+        """Check whether a conversation is starred."""
+        if self._starred is None:
+            self._starred = set(self.db_manager.get_chats_with_setting('starred'))
+        return any(chat_guid in self._starred for chat_guid in chat.guids)
+    
+    def set_starred(self, chat: ChatRecord, starred: bool):
+        """Star or unstar a conversation. The star is kept on its primary chat."""
+        for chat_guid in chat.guids:
+            self.db_manager.set_chat_setting(chat_guid, 'starred', '1' if starred and chat_guid == chat.guid else None)
+        self._starred = None
+    
+    def is_marked_unread(self, chat: ChatRecord) -> bool:
+        """Check whether a conversation was marked unread, e.g. when its snooze ended."""
+        return any(self.db_manager.get_chat_setting(chat_guid, 'marked_unread') for chat_guid in chat.guids)
+    
+    def clear_unread_marker(self, chat: ChatRecord):
+        """Clear a conversation's unread marker once it's opened."""
+        for chat_guid in chat.guids:
+            self.db_manager.set_chat_setting(chat_guid, 'marked_unread', None)
+    
+    def get_chat_notification_policy(self, chat: ChatRecord) -> str:
+        """Get how new messages in a chat notify, from its labels. Snoozed chats don't notify."""
+        if self.get_snooze_until(chat) is not None:
+            return 'none'
+        policies = dict(self.get_labels())
+        return get_notification_policy(self.get_chat_labels(chat), policies)
+    
+    def log_activity(self, kind: str, message: str):
+        """Record a significant client action in the activity log."""
+        self.db_manager.log_activity(kind, message)
+    
+    def log_failed_request(self, method: str, endpoint: str, error: str):
+        """Record a failed server request in the activity log."""
+        self.log_activity('request_failed', f"{method} {endpoint.split('?')[0]}: {error}")
+    
+    def get_activity_log(self, limit: int = 200) -> List[Dict[str, Any]]:
+        """Get the most recent activity log entries."""
+        return self.db_manager.get_activity_log(limit)
+    
+    def clear_activity_log(self):
+        """Remove all activity log entries."""
+        self.db_manager.clear_activity_log()
+    
+    def add_rate_limit_callback(self, callback):
+        """Add a callback called with (category, seconds) when requests are paused."""
+        self._rate_limit_callbacks.append(callback)
+    
+    def remove_rate_limit_callback(self, callback):
+        """Remove a rate limit callback."""
+        if callback in self._rate_limit_callbacks:
+            self._rate_limit_callbacks.remove(callback)
+    
+    def is_paused(self, category: str) -> bool:
+        """Check whether a request category is backing off after a 429."""
+        return time.monotonic() < self._paused_until.get(category, 0)
+    
+    def pause_category(self, category: str, seconds: float):
+        """Hold off a request category for as long as the server asked."""
+        already_paused = self.is_paused(category)
+        self._paused_until[category] = max(self._paused_until.get(category, 0),
+                                           time.monotonic() + seconds)
+        if already_paused:
+            return
+        
+        self.log_activity('rate_limited', f"Server busy, pausing {category} for {seconds:.0f}s")
+        for callback in self._rate_limit_callbacks:
+            try:
+                callback(category, seconds)
+            except Exception as e:
+                pass  # Silently handle callback errors
+    
+    def set_server_reachable(self, reachable: bool, error: str = None):
+        """Log losing and regaining the connection to the server."""
+        if reachable == self.model.connected:
+            return
+        if reachable:
+            self.log_activity('reconnected', "Reconnected to the server")
+            # The server may have come back from installing an update, or signed in again
+            self._update_checked_at = None
+            self._account_checked_at = None
+        else:
+            self.log_activity('connection_lost', f"Lost connection to the server: {error}")
+        
+        self.model.set_connected(reachable)
+    
+    def add_connection_callback(self, callback):
+        """Add a callback called with True or False when the server becomes reachable or unreachable."""
+        self.model.connect(CONNECTION, callback)
+    
+    def remove_connection_callback(self, callback):
+        """Remove a connection callback."""
+        self.model.disconnect(CONNECTION, callback)
+    
+    def add_server_update_callback(self, callback):
+        """Add a callback called with the newer server version when one is found, or None once installed."""
+        self._server_update_callbacks.append(callback)
+    
+    def remove_server_update_callback(self, callback):
+        """Remove a server update callback."""
+        if callback in self._server_update_callbacks:
+            self._server_update_callbacks.remove(callback)
+    
+    def add_account_callback(self, callback):
+        """Add a callback called with True when the server's iMessage account signs out, False when back."""
+        self._account_callbacks.append(callback)
+    
+    def remove_account_callback(self, callback):
+        """Remove an account callback."""
+        if callback in self._account_callbacks:
+            self._account_callbacks.remove(callback)
+    
+    def is_imessage_signed_out(self) -> bool:
+        """Check whether the last account check found the server's iMessage account signed out."""
+        return self.imessage_account is not None and is_signed_out(self.imessage_account)
+    
+    async def check_imessage_account(self, server_url: str, password: str) -> Optional[Dict[str, Any]]:
+        """
+        Fetch which iMessage account and aliases the server uses, notifying listeners when
+        it signs out or back in. Returns the account info, or None if it couldn't be fetched.
+        """
+        self._account_checked_at = time.monotonic()
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method) as client:
+                account = await client.get_icloud_account_info()
+        except BlueBubblesAPIError:
+            return None
+        
+        was_signed_out = self.is_imessage_signed_out()
+        self.imessage_account = account
+        signed_out = self.is_imessage_signed_out()
+        if signed_out == was_signed_out:
+            return account
+        
+        if signed_out:
+            self.log_activity('imessage_account', "The server's iMessage account is signed out")
+        else:
+            self.log_activity('imessage_account', f"The server is signed in to iMessage as {account.get('apple_id')}")
+        for callback in self._account_callbacks:
+            try:
+                callback(signed_out)
+            except Exception as e:
+                # print(f"❌ Error in account callback: {e}")
+                pass
+        return account
+    
+    async def check_for_server_update(self, server_url: str, password: str):
+        """Check whether a newer server release is available, notifying listeners when that changes."""
+        self._update_checked_at = time.monotonic()
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method) as client:
+                update = await client.check_for_update()
+        except BlueBubblesAPIError:
+            return
+        
+        version = None
+        if update.get('available'):
+            version = (update.get('metadata') or {}).get('version')
+        if version == self.available_server_update:
+            return
+        
+        self.available_server_update = version
+        if version:
+            self.log_activity('server', f"Server version {version} is available")
+        for callback in self._server_update_callbacks:
+            try:
+                callback(version)
+            except Exception as e:
+                # print(f"❌ Error in server update callback: {e}")
+                pass
+    
+    def get_schema_info(self) -> Dict[str, Any]:
+        """Describe the local database schema."""
+        return self.db_manager.get_schema_info()
+    
+    async def check_clock_skew(self, server_url: str, password: str):
+        """Compare the server's clock with ours, so timestamps read right if either is off."""
+        self._clock_checked_at = time.monotonic()
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method) as client:
+                sent_at = time.time()
+                server_time = await client.ping()
+                received_at = time.time()
+        except BlueBubblesAPIError:
+            return
+        if server_time is None:
+            return
+        
+        skew_ms = measure_clock_skew(server_time, sent_at, received_at)
+        if skew_ms != get_clock_skew():
+            set_clock_skew(skew_ms)
+            if skew_ms:
+                direction = "ahead of" if skew_ms > 0 else "behind"
+                self.log_activity('clock_skew', f"Server clock is {abs(skew_ms) // 1000}s "
+                                                f"{direction} this computer; adjusting timestamps")
+    
+    async def get_server_info(self, server_url: str, password: str) -> Optional[Dict[str, Any]]:
+        """Get the server's version and capabilities, or None if it can't be reached."""
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                return await client.get_server_info()
+        except Exception as e:
+            return None
+    
+    async def sync_contacts(self, server_url: str, password: str) -> bool:
+        """
+        Fetch contact names from the server and cache them.
+        
+        Returns True if any address got a different name than before.
+        """
+        if self.is_paused('sync'):
+            return False
+        
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                contacts = await client.get_contacts()
+        except BlueBubblesRateLimitError as e:
+            self.pause_category('sync', e.retry_after)
+            return False
+        except Exception as e:
+            return False
+        
+        names = {}
+        for contact in contacts:
+            name = contact.get('displayName') or ' '.join(
+                part for part in (contact.get('firstName'), contact.get('lastName')) if part
+            )
+            if not name:
+                continue
+            for entry in (contact.get('phoneNumbers') or []) + (contact.get('emails') or []):
+                address = entry.get('address') if isinstance(entry, dict) else entry
+                if address:
+                    names[normalize_address(address)] = name
+        
+        changed = names != self._get_contact_names()
+        if changed:
+            self.db_manager.save_contact_names(names)
+            self.model.set_contact_names(names)
+        return changed
+    
+    async def check_imessage_availability(self, server_url: str, password: str, address: str) -> Optional[bool]:
+        """Check whether an address can receive iMessages. Returns None if the server couldn't say."""
+        key = normalize_address(address)
+        if key in self._imessage_availability:
+            return self._imessage_availability[key]
+        
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                available = await client.check_imessage_availability(address)
+        except Exception:
+            return None
+        
+        self._imessage_availability[key] = available
+        return available
+    
+    async def create_facetime_link(self, server_url: str, password: str) -> Optional[str]:
+        """Create a FaceTime link to share in a conversation. Returns None if the server couldn't."""
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                return await client.create_facetime_link()
+        except Exception:
+            return None
+    
+    def search_conversation_messages(self, chat: ChatRecord, query: str) -> List[Tuple[str, int]]:
+        """
+        Find cached messages of a chat and any chats merged into it matching a search, newest first.
+        
+        The search may use filters (see parse_search_query); in: doesn't apply within a chat.
+        """
+        search = parse_search_query(query)
+        if not search.text and not search.filters_messages:
+            return []
+        
+        matches = []
+        for chat_guid in chat.guids:
+            message_db = self._message_db(chat_guid)
+            handle_ids = self._get_sender_handle_ids(message_db, search)
+            matches.extend(
+                (guid, date_created)
+                for _, guid, date_created in message_db.search_messages(search, [chat_guid], handle_ids)
+            )
+        matches.sort(key=lambda match: match[1], reverse=True)
+        return matches
+    
+    def search_message_chats(self, search: SearchQuery) -> Set[str]:
+        """Get the GUIDs of chats with cached messages matching a search's text and filters."""
+        chat_guids = set()
+        for message_db in (self.db_manager, self.session_db):
+            handle_ids = self._get_sender_handle_ids(message_db, search)
+            chat_guids |= message_db.search_message_chats(search, handle_ids)
+        return chat_guids
+    
+    def search_all_messages(self, search: SearchQuery, limit: int = SEARCH_RESULT_LIMIT) -> List[SearchHit]:
+        """Find cached messages in every chat matching a search, best first (see rank_hits)."""
+        hits = []
+        for message_db in (self.db_manager, self.session_db):
+            handle_ids = self._get_sender_handle_ids(message_db, search)
+            hits.extend(message_db.search_message_hits(search, handle_ids))
+        return rank_hits(hits, search_words(search.text), int(time.time() * 1000))[:limit]
+    
+    def search_all_attachments(self, search: SearchQuery, limit: int = SEARCH_RESULT_LIMIT) -> List[AttachmentHit]:
+        """Find the cached attachments of messages in every chat matching a search, newest first."""
+        hits = []
+        for message_db in (self.db_manager, self.session_db):
+            handle_ids = self._get_sender_handle_ids(message_db, search)
+            hits.extend(message_db.search_attachments(search, handle_ids, limit))
+        hits.sort(key=lambda hit: hit.date_created, reverse=True)
+        return hits[:limit]
+    
+    def _get_sender_handle_ids(self, message_db: DatabaseManager, search: SearchQuery) -> Optional[List[int]]:
+        """Get the handles a from: filter matches by address or contact name."""
+        if not search.sender or search.sender == SENDER_ME:
+            return None
+        names = self._get_contact_names()
+        return [
+            rowid for rowid, address in message_db.get_handle_addresses().items()
+            if search.sender in address.lower()
+            or search.sender in names.get(normalize_address(address), '').lower()
+        ]
+    
+    def _get_contact_names(self) -> Dict[str, str]:
+        """Get the contact name lookup, loading it from the cache if needed."""
+        if not self.model.contacts_loaded:
+            self.model.set_contact_names(self.db_manager.get_contact_names())
+        return self.model.contact_names
+    
+    def resolve_address(self, address: str) -> str:
+        """
+        Match an address from outside the app against the cached contacts.
+        
+        Accepts a contact name, or a phone number written without the country
+        code, and returns the contact's address; anything else comes back as is.
+        """
+        names = self._get_contact_names()
+        normalized = normalize_address(address)
+        if normalized in names:
+            return address
+        
+        if '@' not in address and not any(char.isdigit() for char in address):
+            return next((contact_address for contact_address, name in names.items()
+                         if name.lower() == address.strip().lower()), address)
+        
+        # Local numbers: match the trailing digits of a known international one
+        digits = normalized.lstrip('+')
+        if '@' not in normalized and len(digits) >= 7:
+            return next((contact_address for contact_address in names
+                         if contact_address.lstrip('+').endswith(digits)), address)
+        
+        return address
+    
+    def get_contacts(self) -> List[Participant]:
+        """Get the cached contacts, one per name with all of their addresses."""
+        return group_contacts(self._get_contact_names())
+    
+    def get_contact_name(self, address: str) -> Optional[str]:
+        """Get the contact name for a handle address, or None if it isn't a known contact."""
+        return self._get_contact_names().get(normalize_address(address))
     
     def clear_cache(self):
         """Clear all cached data."""
         self.db_manager.clear_cache()
+        self.session_db.clear_cache()
+        self.model.set_contact_names(None)
+        self._sync_errors = None
     
     def get_cache_stats(self) -> Dict[str, int]:
-        """Get cache statistics."""
-        return self.db_manager.get_cache_stats()
+        """Get cache statistics. Chats excluded from the cache add nothing but their count."""
+        stats = self.db_manager.get_cache_stats()
+        stats['excluded_chats'] = len(self._get_excluded_chats())
+        return stats
+    
+    def get_storage_usage(self) -> Dict[str, Any]:
+        """
+        Measure the disk space taken by the cache.
+        
+        Returns {'database': bytes, 'attachments': bytes, 'chats': [(chat, messages, bytes), ...]},
+        with conversations largest first. A conversation's bytes are an estimate of its
+        share of the database plus its cached attachments.
+        """
+        attachment_sizes = self.attachment_cache.get_cache_sizes()
+        
+        # Merged conversation GUID -> [chat, message count, bytes]
+        conversations = {}
+        for chat_guid, usage in self.db_manager.get_chat_storage().items():
+            chat = self.get_chat_by_guid(chat_guid)
+            if not chat:
+                continue
+            entry = conversations.setdefault(chat.guid, [chat, 0, 0])
+            entry[1] += usage['messages']
+            entry[2] += usage['bytes'] + self.attachment_cache.get_cache_size(
+                usage['attachment_guids'], attachment_sizes
+            )
+        
+        return {
+            'database': self.db_manager.get_file_size(),
+            'attachments': sum(attachment_sizes.values()),
+            'chats': sorted((tuple(entry) for entry in conversations.values()),
+                            key=lambda entry: entry[2], reverse=True),
+        }
+    
+    def clear_attachment_cache(self):
+        """Delete every cached attachment; they're downloaded again when next shown."""
+        self.attachment_cache.clear_cache()
+    
+    def set_attachment_cache_limit(self, megabytes: int) -> int:
+        """Change how much space cached attachments may take, removing the least recently used over it; returns bytes freed."""
+        self.config_manager.set_attachment_cache_limit(megabytes)
+        return self.attachment_cache.evict()
+    
+    def clear_chat_cache(self, chat: ChatRecord):
+        """
+        Delete a conversation's cached messages and attachments.
+        
+        The chat itself stays in the list; its recent messages are fetched again
+        the next time it's opened or synced.
+        """
+        for chat_guid in chat.guids:
+            for attachment_guid in self.db_manager.delete_chat_messages(chat_guid):
+                self.attachment_cache.remove_attachment(attachment_guid)
+        self._sync_errors = None
+    
+    def checkpoint_database(self):
+        """Fold the write-ahead log back into the cache database, so it doesn't grow under load."""
+        self._checkpointed_at = time.monotonic()
+        try:
+            self.db_manager.checkpoint()
+        except Exception:
+            pass  # Tried again next interval
+    
+    def vacuum_database(self):
+        """Shrink the database file after messages have been deleted."""
+        self.db_manager.vacuum()
     
     async def refresh_chat_data(self, server_url: str, password: str, 
                               chat_guid: str) -> Optional[ChatRecord]:
@@ -153,40 +1296,151 @@ class ChatService:
             return None
     
     async def send_message(self, server_url: str, password: str, 
-                          chat_guid: str, message: str) -> bool:
-        """Send a text message to a chat."""
+                          chat_guid: str, message: str, reply_to_guid: str = None) -> SendResult:
+        """Send a text message to a chat. The result is falsy, with the server's error, if it failed."""
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
-                await client.send_message(chat_guid, message)
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                data = await client.send_message(chat_guid, message, selected_message_guid=reply_to_guid)
                 # Refresh messages after sending
                 await self.sync_chat_messages(server_url, password, chat_guid, limit=10)
-                return True
+                return SendResult(guid=as_text(data.get('guid')) if isinstance(data, dict) else None)
+        except BlueBubblesTimeoutError as e:
+            return SendResult(error=str(e))
+        except BlueBubblesAPIError as e:
+            # No status means the request never reached the server
+            retryable = e.status is None or e.status in RETRYABLE_SEND_STATUSES
+            return SendResult(error=str(e), retryable=retryable)
         except Exception as e:
-            # print(f"Error sending message: {e}")
-            return False
+            return SendResult(error=str(e) or "Unknown error")
     
     async def send_attachment(self, server_url: str, password: str, 
-                            chat_guid: str, file_path: str, message: str = "") -> bool:
-        """Send an attachment to a chat."""
+                            chat_guid: str, file_path: str, message: str = "",
+                            is_audio_message: bool = False,
+                            on_progress: Optional[Callable[[int, int], None]] = None) -> bool:
+        """
+        Send an attachment to a chat.
+        
+        Progress is reported to on_progress as (bytes sent, file size). Files over
+        CHUNKED_UPLOAD_THRESHOLD are uploaded in chunks; sending the same file again
+        after a failure resumes where it stopped. Raises BlueBubblesUploadTooLargeError
+        when the server can't accept the file, so the limit can be shown.
+        """
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
-                await client.send_attachment(chat_guid, file_path, message)
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                if os.path.getsize(file_path) > CHUNKED_UPLOAD_THRESHOLD:
+                    await self._send_attachment_in_chunks(
+                        client, chat_guid, file_path, message, is_audio_message, on_progress
+                    )
+                else:
+                    await self._send_attachment_whole(
+                        client, chat_guid, file_path, message, is_audio_message, on_progress
+                    )
                 # Refresh messages after sending
                 await self.sync_chat_messages(server_url, password, chat_guid, limit=10)
                 return True
+        except BlueBubblesUploadTooLargeError:
+            raise
         except Exception as e:
             # print(f"Error sending attachment: {e}")
             return False
     
+    async def _send_attachment_whole(self, client: BlueBubblesClient, chat_guid: str, file_path: str,
+                                     message: str, is_audio_message: bool,
+                                     on_progress: Optional[Callable[[int, int], None]]):
+        """Upload an attachment in a single multipart request."""
+        try:
+            await client.send_attachment(chat_guid, file_path, message, is_audio_message, on_progress)
+        except BlueBubblesAPIError as e:
+            if e.status == 413:
+                raise await self._upload_too_large_error(client, file_path)
+            raise
+    
+    async def _send_attachment_in_chunks(self, client: BlueBubblesClient, chat_guid: str, file_path: str,
+                                         message: str, is_audio_message: bool,
+                                         on_progress: Optional[Callable[[int, int], None]]):
+        """Upload a large attachment in chunks, resuming an earlier attempt at the same file."""
+        stat = os.stat(file_path)
+        key = (chat_guid, file_path, stat.st_size, stat.st_mtime)
+        upload_guid, start = self._partial_uploads.get(key, (f"temp-{uuid.uuid4()}", 0))
+        
+        def on_chunk_sent(sent: int, size: int):
+            self._partial_uploads[key] = (upload_guid, sent)
+            if self._shutting_down:
+                # Kept as a partial upload, so sending the file again resumes it
+                raise BlueBubblesAPIError("Upload stopped because the app is quitting")
+            if on_progress:
+                on_progress(sent, size)
+        
+        try:
+            await client.send_attachment_chunked(
+                chat_guid, file_path, upload_guid, start, message, is_audio_message, on_chunk_sent
+            )
+        except BlueBubblesAPIError as e:
+            if e.status == 404 and key not in self._partial_uploads:
+                # The server doesn't take chunks; try in one go if it can take the size
+                limit = await self._get_upload_limit(client)
+                if limit and stat.st_size > limit:
+                    raise await self._upload_too_large_error(client, file_path, limit)
+                await self._send_attachment_whole(client, chat_guid, file_path, message, is_audio_message, on_progress)
+                return
+            if e.status and 400 <= e.status < 500 and e.status != 429:
+                # The server rejected the upload; a retry starts over rather than resuming
+                self._partial_uploads.pop(key, None)
+            if e.status == 413:
+                raise await self._upload_too_large_error(client, file_path)
+            raise
+        
+        self._partial_uploads.pop(key, None)
+    
+    def _load_partial_uploads(self):
+        """Load the interrupted uploads saved when the app last quit."""
+        for chat_guid, value in self.db_manager.get_chats_with_setting('partial_uploads').items():
+            try:
+                for file_path, size, mtime, upload_guid, sent in json.loads(value):
+                    self._partial_uploads[(chat_guid, file_path, size, mtime)] = (upload_guid, sent)
+            except (TypeError, ValueError):
+                pass  # Unreadable; those uploads start over
+    
+    def _save_partial_uploads(self):
+        """Save interrupted uploads, so sending the same file after a restart resumes it."""
+        by_chat = {chat_guid: [] for chat_guid in self.db_manager.get_chats_with_setting('partial_uploads')}
+        for (chat_guid, file_path, size, mtime), (upload_guid, sent) in list(self._partial_uploads.items()):
+            by_chat.setdefault(chat_guid, []).append([file_path, size, mtime, upload_guid, sent])
+        for chat_guid, uploads in by_chat.items():
+            self.db_manager.set_chat_setting(chat_guid, 'partial_uploads', json.dumps(uploads) if uploads else None)
+    
+    async def _get_upload_limit(self, client: BlueBubblesClient) -> Optional[int]:
+        """Get the largest upload the server accepts in bytes, if its server info says."""
+        try:
+            server_info = await client.get_server_info()
+        except BlueBubblesAPIError:
+            return None
+        return server_info.get('max_upload_size') or None
+    
+    async def _upload_too_large_error(self, client: BlueBubblesClient, file_path: str,
+                                      limit: Optional[int] = None) -> BlueBubblesUploadTooLargeError:
+        """Build the error for a file the server refused for its size, naming the limit."""
+        if limit is None:
+            limit = await self._get_upload_limit(client)
+        size = os.path.getsize(file_path)
+        file_name = os.path.basename(file_path)
+        size_text = self.attachment_cache.get_file_size_string(size)
+        if limit:
+            limit_text = self.attachment_cache.get_file_size_string(limit)
+            message = f"{file_name} is {size_text}, larger than the server's {limit_text} upload limit"
+        else:
+            message = f"{file_name} ({size_text}) is too large for the server to accept"
+        return BlueBubblesUploadTooLargeError(message, size, limit)
+    
     async def send_reaction(self, server_url: str, password: str, 
                            message_guid: str, reaction_type: str, chat_guid: str = None) -> bool:
         """Send a reaction to a message."""
         try:
             api_method = self.config_manager.get_api_method()
             # print(f"🎭 Sending reaction: message_guid={message_guid}, reaction_type={reaction_type}, chat_guid={chat_guid}, api_method={api_method}")
-            async with BlueBubblesClient(server_url, password, api_method) as client:
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
                 result = await client.send_reaction(message_guid, reaction_type, chat_guid)
                 # print(f"🎭 Reaction API response: {result}")
             # Proactively sync messages so UI can immediately reflect the reaction badge
@@ -208,7 +1462,7 @@ class ChatService:
         try:
             api_method = self.config_manager.get_api_method()
             # print(f"🎭 Removing reaction: message_guid={message_guid}, chat_guid={chat_guid}, api_method={api_method}")
-            async with BlueBubblesClient(server_url, password, api_method) as client:
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
                 result = await client.remove_reaction(message_guid, chat_guid)
                 # print(f"🎭 Remove reaction API response: {result}")
             # Proactively sync messages so UI can immediately reflect the removed badge
@@ -228,7 +1482,7 @@ class ChatService:
         """Send typing indicator to a chat."""
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
                 return await client.send_typing_indicator(chat_guid, typing)
         except Exception as e:
             # print(f"Error sending typing indicator: {e}")
@@ -239,7 +1493,7 @@ class ChatService:
         """Unsend a message."""
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
                 await client.unsend_message(message_guid)
                 # Refresh messages after unsending
                 await self.sync_chat_messages(server_url, password, chat_guid, limit=10)
@@ -253,7 +1507,7 @@ class ChatService:
         """Edit a message."""
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
                 await client.edit_message(message_guid, new_text)
                 # Refresh messages after editing
                 await self.sync_chat_messages(server_url, password, chat_guid, limit=10)
@@ -271,6 +1525,159 @@ class ChatService:
         if callback in self._message_check_callbacks:
             self._message_check_callbacks.remove(callback)
     
+    def add_messages_changed_callback(self, callback):
+        """Add a callback called with a chat GUID when cached messages were unsent or deleted remotely."""
+        self.model.connect(MESSAGES, callback)
+    
+    def remove_messages_changed_callback(self, callback):
+        """Remove a messages changed callback."""
+        self.model.disconnect(MESSAGES, callback)
+    
+    def add_read_elsewhere_callback(self, callback):
+        """Add a callback called with a chat GUID when its messages were read on another device."""
+        self._read_elsewhere_callbacks.append(callback)
+    
+    def remove_read_elsewhere_callback(self, callback):
+        """Remove a read elsewhere callback."""
+        if callback in self._read_elsewhere_callbacks:
+            self._read_elsewhere_callbacks.remove(callback)
+    
+    def add_server_event_callback(self, callback):
+        """Add a callback called with each live ServerEvent, on the event stream's thread."""
+        self._server_event_callbacks.append(callback)
+    
+    def remove_server_event_callback(self, callback):
+        """Remove a server event callback."""
+        if callback in self._server_event_callbacks:
+            self._server_event_callbacks.remove(callback)
+    
+    def handle_server_event(self, event: ServerEvent):
+        """Apply a live event to the cache, tell the usual callbacks, then pass it on."""
+        if isinstance(event, (NewMessage, MessageUpdated, MessageSendError)) and event.chat_guid:
+            chat_guid = event.chat_guid
+            self._message_db(chat_guid).save_message(event.message, chat_guid)
+            callbacks = self._message_check_callbacks if isinstance(event, NewMessage) else [self.model.messages_changed]
+            for callback in callbacks:
+                try:
+                    callback(chat_guid)
+                except Exception:
+                    pass
+        elif isinstance(event, ChatRead) and event.read:
+            self._message_db(event.chat_guid).mark_chat_read_locally(event.chat_guid)
+            for callback in self._read_elsewhere_callbacks:
+                try:
+                    callback(event.chat_guid)
+                except Exception:
+                    pass
+        elif isinstance(event, ServerUpdate):
+            self._update_checked_at = None  # Checked on the next round
+        
+        for callback in self._server_event_callbacks:
+            try:
+                callback(event)
+            except Exception:
+                pass
+    
+    async def resync_missed_messages(self, server_url: str, password: str, since_ms: int) -> int:
+        """Fetch messages sent since a time, e.g. while the event socket was down; returns how many were new."""
+        # Chat GUID -> its messages not cached yet
+        missed = {}
+        api_method = self.config_manager.get_api_method()
+        async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+            # Oldest first, so messages arriving meanwhile only add pages at the end
+            offset = 0
+            while True:
+                messages = await client.get_messages_since(
+                    since_ms - RESYNC_OVERLAP_MS, limit=RESYNC_PAGE_SIZE, offset=offset
+                )
+                for message in messages:
+                    chat_guid = message_chat_guid(message)
+                    if chat_guid and message.get('guid') and not self.get_message(message['guid']):
+                        missed.setdefault(chat_guid, []).append(message)
+                if len(messages) < RESYNC_PAGE_SIZE:
+                    break
+                offset += len(messages)
+        
+        for chat_guid, chat_messages in missed.items():
+            self._message_db(chat_guid).save_messages(chat_messages, chat_guid)
+            for callback in self._message_check_callbacks:
+                try:
+                    callback(chat_guid)
+                except Exception:
+                    pass
+        return sum(len(chat_messages) for chat_messages in missed.values())
+    
+    def _start_event_stream(self, server_url: str, password: str):
+        """Keep a socket to the server open for live events, reconnecting with backoff when it drops."""
+        # Unix milliseconds the socket last dropped, None until it has been connected
+        disconnected_at = None
+        resync_tasks = set()
+        
+        async def resync(since_ms: int):
+            try:
+                count = await self.resync_missed_messages(server_url, password, since_ms)
+                if count:
+                    self.log_activity('live_events', f"Fetched {count} messages missed while disconnected")
+            except Exception:
+                pass  # Polling picks them up instead
+        
+        def on_connected():
+            self.events_connected = True
+            self.log_activity('live_events', "Receiving live events from the server")
+            self.handle_server_event(Connected(reconnected=disconnected_at is not None))
+            if disconnected_at is not None:
+                task = asyncio.get_running_loop().create_task(resync(disconnected_at))
+                resync_tasks.add(task)
+                task.add_done_callback(resync_tasks.discard)
+        
+        async def event_loop():
+            nonlocal disconnected_at
+            # Failed attempts since the socket was last connected
+            attempt = 0
+            while not self._stop_message_check:
+                reason = None
+                try:
+                    api_method = self.config_manager.get_api_method()
+                    async with BlueBubblesClient(server_url, password, api_method) as client:
+                        await client.listen_events(
+                            self.handle_server_event, lambda: self._stop_message_check, on_connected
+                        )
+                except Exception as e:
+                    reason = str(e)  # Polling carries on; try again after the delay
+                
+                if self.events_connected:
+                    self.events_connected = False
+                    disconnected_at = int(time.time() * 1000)
+                    attempt = 0
+                    if not self._stop_message_check:
+                        self.log_activity('live_events', "Live events stopped; checking for messages instead")
+                        self.handle_server_event(Disconnected(reason))
+                else:
+                    attempt += 1
+                
+                delay = reconnect_delay(attempt)
+                while delay > 0 and not self._stop_message_check:
+                    await asyncio.sleep(min(delay, 1))
+                    delay -= 1
+        
+        def run_async_loop():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            try:
+                loop.run_until_complete(event_loop())
+            finally:
+                # A resync started just before stopping gets a moment to finish, then is cancelled
+                pending = list(resync_tasks)
+                if pending:
+                    loop.run_until_complete(asyncio.wait(pending, timeout=SHUTDOWN_TIMEOUT))
+                    for task in pending:
+                        task.cancel()
+                    loop.run_until_complete(asyncio.gather(*pending, return_exceptions=True))
+                loop.close()
+        
+        self._event_stream_thread = threading.Thread(target=run_async_loop, daemon=True)
+        self._event_stream_thread.start()
+    
     def start_message_checking(self, server_url: str, password: str, check_interval: int = 3):
         """Start the background message checking task."""
         if self._message_check_task is not None:
@@ -278,14 +1685,43 @@ class ChatService:
             return
         
         self._stop_message_check = False
+        self.check_interval = check_interval
         # print(f"🔄 Starting message checking with {check_interval}s interval")
         
         async def message_check_loop():
             """Background task to periodically check for new messages."""
             while not self._stop_message_check:
                 try:
-                    # Get all cached chats
-                    cached_chats = self.get_cached_chats(limit=50)
+                    if self.is_paused('sync'):
+                        await asyncio.sleep(self.check_interval)
+                        continue
+                    
+                    if (self._clock_checked_at is None
+                            or time.monotonic() - self._clock_checked_at > CLOCK_CHECK_INTERVAL):
+                        await self.check_clock_skew(server_url, password)
+                    
+                    if (self._update_checked_at is None
+                            or time.monotonic() - self._update_checked_at > UPDATE_CHECK_INTERVAL):
+                        await self.check_for_server_update(server_url, password)
+                    
+                    if (self._account_checked_at is None
+                            or time.monotonic() - self._account_checked_at > ACCOUNT_CHECK_INTERVAL):
+                        await self.check_imessage_account(server_url, password)
+                    
+                    if time.monotonic() - self._checkpointed_at > CHECKPOINT_INTERVAL:
+                        self.checkpoint_database()
+                    
+                    self.wake_snoozed_chats()
+                    
+                    # Get all cached chats, including every service of merged conversations
+                    cached_chats = [
+                        service_chat for merged_chat in self.get_cached_chats(limit=50)
+                        for service_chat in [merged_chat] + merged_chat.linked_chats
+                    ]
+                    
+                    # Chat GUID -> error, for chats that failed while others synced this round
+                    failed_chats = {}
+                    any_synced = False
                     
                     for chat in cached_chats:
                         if self._stop_message_check:
@@ -300,42 +1736,77 @@ class ChatService:
                             api_method = self.config_manager.get_api_method()
                             async with BlueBubblesClient(server_url, password, api_method) as client:
                                 new_messages = await client.get_chat_messages(chat.guid, limit=5)
+                                self.set_server_reachable(True)
+                                any_synced = True
+                                if chat.guid in self._get_sync_errors():
+                                    self.record_chat_sync(chat.guid)
                                 
                                 if len(new_messages) > 0:
-                                    # Get the latest message timestamp from our cache
-                                    latest_cached_timestamp = 0
+                                    # Get the position of the latest message in our cache
+                                    latest_cached_key = (0, 0)
                                     if current_messages:
-                                        latest_cached_timestamp = current_messages[0].date_created
+                                        latest_cached_key = current_messages[0].sort_key
                                     
-                                    # Check if any new messages are newer than our latest cached message
-                                    new_message_found = False
+                                    # Check if any new messages are newer than our latest cached message.
+                                    # Messages with the same or no timestamp are told apart by ROWID.
+                                    unseen_messages = []
                                     for msg_data in new_messages:
-                                        msg_timestamp = msg_data.get('dateCreated', 0)
-                                        if msg_timestamp > latest_cached_timestamp:
-                                            # Save new message to database
-                                            self.db_manager.save_message(msg_data, chat.guid)
-                                            new_message_found = True
+                                        msg_date = to_unix_ms(msg_data.get('dateCreated'))
+                                        if msg_date is None:
+                                            msg_date = latest_cached_key[0]
+                                        msg_key = (msg_date, msg_data.get('originalROWID') or 0)
+                                        if msg_key > latest_cached_key:
+                                            unseen_messages.append(msg_data)
                                             # print(f"📨 New message detected in chat {chat.display_name or chat.guid[:8]}")
                                     
-                                    # Notify callbacks if new messages were found
-                                    if new_message_found:
+                                    # Save new messages to database in one transaction and notify callbacks
+                                    if unseen_messages:
+                                        self._message_db(chat.guid).save_messages(unseen_messages, chat.guid)
                                         for callback in self._message_check_callbacks:
                                             try:
                                                 callback(chat.guid)
                                             except Exception as e:
                                                 # print(f"❌ Error in message callback: {e}")
                                                 pass
+                                    
+                                    # Messages already cached may have been read on another device since
+                                    if self.apply_remote_read_state(chat.guid, new_messages):
+                                        for callback in self._read_elsewhere_callbacks:
+                                            try:
+                                                callback(chat.guid)
+                                            except Exception as e:
+                                                # print(f"❌ Error in read elsewhere callback: {e}")
+                                                pass
+                                    
+                                    # Or unsent or deleted
+                                    if self.apply_remote_deletions(chat.guid, new_messages):
+                                        self.model.messages_changed(chat.guid)
                         
+                        except BlueBubblesRateLimitError as e:
+                            # Stop this round; the remaining chats wait for the pause
+                            self.pause_category('sync', e.retry_after)
+                            break
+                        except BlueBubblesAPIError as e:
+                            self.set_server_reachable(False, str(e))
+                            failed_chats[chat.guid] = str(e)
                         except Exception as e:
                             # Don't # print errors for individual chats as it can be spammy
-                            pass
+                            failed_chats[chat.guid] = str(e)
+                    
+                    # If nothing synced the server is unreachable, which isn't any one chat's problem
+                    if any_synced:
+                        for chat_guid, error in failed_chats.items():
+                            self.record_chat_sync(chat_guid, error)
                     
-                    # Wait before next check
-                    await asyncio.sleep(check_interval)
+                    # Wait before next check; live events make frequent checks unnecessary
+                    if self.events_connected:
+                        await asyncio.sleep(max(self.check_interval, LIVE_CHECK_INTERVAL))
+                    else:
+                        await asyncio.sleep(self.check_interval)
                 
                 except Exception as e:
                     # print(f"❌ Error in message checking loop: {e}")
-                    await asyncio.sleep(check_interval)
+                    await asyncio.sleep(self.check_interval)
             
             # print("🛑 Message checking stopped")
         
@@ -356,9 +1827,30 @@ class ChatService:
         
         self._message_check_thread = threading.Thread(target=run_async_loop, daemon=True)
         self._message_check_thread.start()
+        self._start_event_stream(server_url, password)
+    
+    def get_draft(self, chat_guid: str) -> str:
+        """Get the unsent text left in a chat's composer when the app last quit."""
+        return self.db_manager.get_chat_setting(chat_guid, 'draft', '')
+    
+    def set_draft(self, chat_guid: str, text: str):
+        """Save the unsent text in a chat's composer; empty text removes it."""
+        self.db_manager.set_chat_setting(chat_guid, 'draft', text or None)
+    
+    def shutdown(self, timeout: float = SHUTDOWN_TIMEOUT):
+        """
+        Stop background work before quitting, waiting at most timeout seconds.
+        
+        The event socket closes, uploads stop after their current chunk with
+        their progress saved, and the database's write-ahead log is folded in.
+        """
+        self._shutting_down = True
+        self.stop_message_checking(timeout)
+        self._save_partial_uploads()
+        self.checkpoint_database()
     
-    def stop_message_checking(self):
-        """Stop the background message checking task."""
+    def stop_message_checking(self, timeout: float = 2.0):
+        """Stop the background message checking task, waiting up to timeout seconds for its threads."""
         if self._message_check_task is None and self._message_check_thread is None:
             return
         
@@ -368,33 +1860,81 @@ class ChatService:
         if self._message_check_task and not self._message_check_task.done():
             self._message_check_task.cancel()
         
-        # Wait a bit for the thread to finish
-        if self._message_check_thread and self._message_check_thread.is_alive():
-            self._message_check_thread.join(timeout=2.0)
+        # Wait a bit for the threads to finish; the event socket closes as its thread exits
+        deadline = time.monotonic() + timeout
+        for thread in (self._event_stream_thread, self._message_check_thread):
+            if thread and thread.is_alive():
+                thread.join(timeout=max(deadline - time.monotonic(), 0))
         
         self._message_check_task = None
         self._message_check_thread = None
+        self._event_stream_thread = None
+        self.events_connected = False
     
     async def get_contact_avatar(self, server_url: str, password: str, address: str) -> Optional[bytes]:
         """Get contact avatar from server or cache."""
+        if self.is_paused('avatars'):
+            return self.avatar_cache.get_cached_avatar(address, is_group=False)
+        
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
                 return await self.avatar_cache.get_avatar(client, address, is_group=False)
+        except BlueBubblesRateLimitError as e:
+            self.pause_category('avatars', e.retry_after)
+            return None
         except Exception as e:
             # Silently handle avatar fetch errors
             return None
     
     async def get_chat_icon(self, server_url: str, password: str, chat_guid: str) -> Optional[bytes]:
         """Get group chat icon from server or cache."""
+        if self.is_paused('avatars'):
+            return self.avatar_cache.get_cached_avatar(chat_guid, is_group=True)
+        
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
                 return await self.avatar_cache.get_avatar(client, chat_guid, is_group=True)
+        except BlueBubblesRateLimitError as e:
+            self.pause_category('avatars', e.retry_after)
+            return None
         except Exception as e:
             # Silently handle avatar fetch errors
             return None
     
+    async def set_group_icon(self, server_url: str, password: str,
+                             chat_guid: str, file_path: str) -> bool:
+        """Upload a new group chat icon and drop the cached one."""
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                await client.set_chat_icon(chat_guid, file_path)
+            self.avatar_cache.invalidate(chat_guid, is_group=True)
+            return True
+        except Exception as e:
+            # print(f"Error setting group icon: {e}")
+            return False
+    
+    async def get_group_avatar(self, server_url: str, password: str, chat: ChatRecord,
+                               size: int = 40) -> Optional[bytes]:
+        """
+        Build an avatar for a group chat without an icon from its first participants.
+        
+        Participants are taken in address order so the stack is the same every time;
+        those without a photo are drawn as initials.
+        """
+        participants = sorted(chat.participants, key=lambda p: normalize_address(p.address))
+        avatars = []
+        for participant in participants[:STACK_AVATAR_LIMIT]:
+            avatar_data = await self.get_contact_avatar(server_url, password, participant.address)
+            if not avatar_data:
+                name = self.get_contact_name(participant.address) or participant.address
+                avatar_data = self.generate_fallback_avatar(name, size)
+            if avatar_data:
+                avatars.append(avatar_data)
+        return self.avatar_cache.stack_avatars(avatars, size)
+    
     def generate_fallback_avatar(self, name: str, size: int = 40) -> Optional[bytes]:
         """Generate a fallback avatar with initials."""
         return self.avatar_cache.generate_initials_avatar(name, size)
@@ -403,7 +1943,7 @@ class ChatService:
         """Mark a chat as read."""
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
                 return await client.mark_chat_read(chat_guid)
         except Exception as e:
             # Silently handle mark read errors
@@ -413,8 +1953,23 @@ class ChatService:
         """Get attachment data from server or cache."""
         try:
             api_method = self.config_manager.get_api_method()
-            async with BlueBubblesClient(server_url, password, api_method) as client:
-                return await self.attachment_cache.get_attachment(client, attachment_guid)
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                return await self.attachment_cache.get_attachment(
+                    client, attachment_guid, store=not self.is_excluded_attachment(attachment_guid)
+                )
+        except Exception as e:
+            # Silently handle attachment fetch errors
+            return None
+    
+    async def get_attachment_path(self, server_url: str, password: str, attachment_guid: str) -> Optional[str]:
+        """Get the path of an attachment's file, from the cache or streamed from the server."""
+        try:
+            api_method = self.config_manager.get_api_method()
+            async with BlueBubblesClient(server_url, password, api_method, self.log_failed_request) as client:
+                path = await self.attachment_cache.get_attachment_path(
+                    client, attachment_guid, store=not self.is_excluded_attachment(attachment_guid)
+                )
+                return str(path) if path else None
         except Exception as e:
             # Silently handle attachment fetch errors
             return None
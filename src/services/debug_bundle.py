@@ -0,0 +1,104 @@
+"""Debug bundle for bug reports, with credentials and message contents left out."""
+
+import copy
+import json
+import platform
+import zipfile
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+from urllib.parse import urlparse
+
+REDACTED = "<redacted>"
+
+# Config keys whose values are never included, matched anywhere in the key name
+SECRET_KEY_PARTS = ('password', 'token', 'secret', 'key')
+
+
+def redact_url(url: str) -> str:
+    """Keep the scheme and port of a server URL, hiding the host."""
+    parsed = urlparse(url)
+    port = f":{parsed.port}" if parsed.port else ""
+    return f"{parsed.scheme}://{REDACTED}{port}" if parsed.scheme else REDACTED
+
+
+def redact_config(config_data: Dict[str, Any]) -> Dict[str, Any]:
+    """Copy the configuration with credentials and the server address removed."""
+    def redact(section: Dict[str, Any]) -> Dict[str, Any]:
+        for name, value in section.items():
+            if isinstance(value, dict):
+                redact(value)
+            elif any(part in name.lower() for part in SECRET_KEY_PARTS):
+                section[name] = REDACTED
+        return section
+
+    config = redact(copy.deepcopy(config_data))
+    server_url = config.get('server', {}).get('url')
+    if server_url:
+        config['server']['url'] = redact_url(server_url)
+    return config
+
+
+def scrub_text(text: str, secrets: List[str]) -> str:
+    """Replace every secret in a piece of text."""
+    for secret in secrets:
+        if secret:
+            text = text.replace(secret, REDACTED)
+    return text
+
+
+def get_secrets(config_data: Dict[str, Any]) -> List[str]:
+    """Get the values that must never appear in a report: the password and server address."""
+    server = config_data.get('server', {})
+    secrets = [server.get('password'), server.get('url')]
+    if server.get('url'):
+        secrets.append(urlparse(server['url']).hostname)
+    return secrets
+
+
+def format_activity(activity: List[Dict[str, Any]], secrets: List[str]) -> List[str]:
+    """Format activity log entries, newest first as stored, into log lines oldest first."""
+    return [
+        f"{datetime.fromtimestamp(entry['timestamp'] / 1000).isoformat(timespec='seconds')} "
+        f"[{entry['kind']}] {scrub_text(entry['message'], secrets)}"
+        for entry in reversed(activity)
+    ]
+
+
+def build_debug_bundle(file_path: str, app_version: str, config_data: Dict[str, Any],
+                       activity: List[Dict[str, Any]], schema: Dict[str, Any],
+                       server_info: Optional[Dict[str, Any]], features: Optional[Dict[str, str]] = None):
+    """
+    Write a zip with the redacted config, activity log, database schema summary,
+    server capabilities, version details and the optional features this build has.
+    Messages are never included.
+    """
+    secrets = get_secrets(config_data)
+
+    versions = {
+        'app_version': app_version,
+        'python': platform.python_version(),
+        'platform': platform.platform(),
+        'created': datetime.now().isoformat(timespec='seconds'),
+        'features': features or {},
+    }
+
+    log_lines = format_activity(activity, secrets)
+
+    if server_info is None:
+        server_report = {'reachable': False}
+    else:
+        server_report = {
+            'reachable': True,
+            'server_version': server_info.get('server_version'),
+            'os_version': server_info.get('os_version'),
+            'private_api': server_info.get('private_api'),
+            'helper_connected': server_info.get('helper_connected'),
+            'proxy_service': server_info.get('proxy_service'),
+        }
+
+    with zipfile.ZipFile(file_path, 'w', zipfile.ZIP_DEFLATED) as bundle:
+        bundle.writestr("versions.json", json.dumps(versions, indent=2))
+        bundle.writestr("config.json", json.dumps(redact_config(config_data), indent=2))
+        bundle.writestr("activity.log", "\n".join(log_lines) + "\n")
+        bundle.writestr("schema.json", json.dumps(schema, indent=2))
+        bundle.writestr("server.json", json.dumps(server_report, indent=2))
@@ -0,0 +1,117 @@
+"""Sound and haptic feedback for sending and receiving messages."""
+
+import os
+from pathlib import Path
+from typing import List, Optional
+from gi.repository import Gio, GLib, Gtk
+from ..utils.features import is_feature_enabled
+
+# Events from the freedesktop sound naming spec, with fallbacks for themes that lack them
+MESSAGE_SENT = 'message-sent-instant'
+MESSAGE_RECEIVED = 'message-new-instant'
+SOUND_FALLBACKS = {
+    MESSAGE_SENT: ['message-sent-instant', 'message-sent-email', 'message'],
+    MESSAGE_RECEIVED: ['message-new-instant', 'message'],
+}
+SOUND_EXTENSIONS = ['oga', 'ogg', 'wav']
+
+# Kept quiet, since these play on every message
+SOUND_VOLUME = 0.5
+
+# feedbackd runs the vibration motor on Linux phones; there's no portal for haptics yet.
+# Its "quiet" profile vibrates without playing the theme's sound over ours.
+FEEDBACKD_BUS_NAME = 'org.sigxcpu.Feedback'
+FEEDBACKD_OBJECT_PATH = '/org/sigxcpu/Feedback'
+FEEDBACKD_INTERFACE = 'org.sigxcpu.Feedback'
+FEEDBACKD_PROFILE = 'quiet'
+
+
+def get_sound_dirs() -> List[Path]:
+    """Get the directories sound themes are installed in, most specific first."""
+    data_home = os.environ.get('XDG_DATA_HOME') or os.path.expanduser('~/.local/share')
+    data_dirs = os.environ.get('XDG_DATA_DIRS') or '/usr/local/share:/usr/share'
+    return [Path(data_home) / 'sounds'] + [Path(d) / 'sounds' for d in data_dirs.split(':') if d]
+
+
+def find_sound(event: str, theme: str = 'freedesktop') -> Optional[Path]:
+    """Find the sound file for an event in a theme, falling back to the freedesktop theme."""
+    themes = [theme] if theme == 'freedesktop' else [theme, 'freedesktop']
+    for theme_name in themes:
+        for name in SOUND_FALLBACKS.get(event, [event]):
+            for sound_dir in get_sound_dirs():
+                for extension in SOUND_EXTENSIONS:
+                    path = sound_dir / theme_name / 'stereo' / f"{name}.{extension}"
+                    if path.exists():
+                        return path
+    return None
+
+
+def is_do_not_disturb() -> bool:
+    """Check whether the desktop's Do Not Disturb mode is on (GNOME only)."""
+    source = Gio.SettingsSchemaSource.get_default()
+    if source is None or source.lookup('org.gnome.desktop.notifications', True) is None:
+        return False
+    return not Gio.Settings.new('org.gnome.desktop.notifications').get_boolean('show-banners')
+
+
+class FeedbackPlayer:
+    """Plays message sounds and triggers haptic feedback where a motor is available."""
+
+    def __init__(self, app_id: str):
+        self.app_id = app_id
+        # Sounds being played, kept alive until they finish
+        self.playing = []
+        try:
+            self.connection = Gio.bus_get_sync(Gio.BusType.SESSION, None)
+        except GLib.Error:
+            self.connection = None
+
+    def get_sound_theme(self) -> str:
+        """Get the desktop's sound theme name."""
+        source = Gio.SettingsSchemaSource.get_default()
+        if source is None or source.lookup('org.gnome.desktop.sound', True) is None:
+            return 'freedesktop'
+        return Gio.Settings.new('org.gnome.desktop.sound').get_string('theme-name') or 'freedesktop'
+
+    def play(self, event: str):
+        """Play the sound for an event and vibrate on devices that can."""
+        self.play_sound(event)
+        self.vibrate(event)
+
+    def play_sound(self, event: str):
+        """Play the sound theme's sound for an event, if it has one."""
+        if not is_feature_enabled('media'):
+            return
+        path = find_sound(event, self.get_sound_theme())
+        if path is None:
+            return
+
+        media = Gtk.MediaFile.new_for_filename(str(path))
+        media.set_volume(SOUND_VOLUME)
+        media.connect("notify::ended", self.on_sound_ended)
+        self.playing.append(media)
+        media.play()
+
+    def on_sound_ended(self, media, pspec):
+        """Let go of a sound once it has played."""
+        if media.get_ended() and media in self.playing:
+            self.playing.remove(media)
+
+    def vibrate(self, event: str):
+        """Ask feedbackd for haptic feedback. Does nothing where it isn't running."""
+        if self.connection is None:
+            return
+
+        hints = {'profile': GLib.Variant('s', FEEDBACKD_PROFILE)}
+        self.connection.call(
+            FEEDBACKD_BUS_NAME, FEEDBACKD_OBJECT_PATH, FEEDBACKD_INTERFACE, 'TriggerFeedback',
+            GLib.Variant('(ssa{sv}i)', (self.app_id, event, hints, -1)),
+            None, Gio.DBusCallFlags.NO_AUTO_START, -1, None, self.on_vibrate_finished
+        )
+
+    def on_vibrate_finished(self, connection, result):
+        """Ignore devices without feedbackd."""
+        try:
+            connection.call_finish(result)
+        except GLib.Error:
+            pass
@@ -0,0 +1,75 @@
+"""Headless health check for monitoring, used by the --healthcheck command line option."""
+
+import asyncio
+import time
+from typing import Any, Dict, Tuple
+
+from ..api.client import BlueBubblesClient, BlueBubblesAPIError
+from ..config.manager import ConfigManager
+from ..db.manager import DatabaseManager
+
+# Exit codes
+EXIT_HEALTHY = 0
+EXIT_UNHEALTHY = 1
+EXIT_NOT_CONFIGURED = 2
+
+
+async def check_server(server_url: str, password: str, api_method: str) -> Dict[str, Any]:
+    """Ping the server's REST API and its realtime (socket.io) endpoint."""
+    results = {}
+
+    async with BlueBubblesClient(server_url, password, api_method) as client:
+        started = time.monotonic()
+        try:
+            server_info = await client.get_server_info()
+            results['server'] = {
+                'ok': True,
+                'latency_ms': round((time.monotonic() - started) * 1000),
+                'server_version': server_info.get('server_version'),
+                'private_api': server_info.get('private_api'),
+            }
+        except BlueBubblesAPIError as e:
+            results['server'] = {'ok': False, 'error': str(e)}
+
+        try:
+            results['socket'] = {'ok': await client.check_socket()}
+        except BlueBubblesAPIError as e:
+            results['socket'] = {'ok': False, 'error': str(e)}
+
+    return results
+
+
+def check_database(db_path: str = None) -> Dict[str, Any]:
+    """
+    Run SQLite's integrity check on the local cache.
+    
+    The cache is opened without repairing it: the app may be running, and a
+    damaged cache should be reported, not moved out from under it.
+    """
+    try:
+        result = DatabaseManager(db_path, repair=False).check_integrity()
+        return {'ok': result == 'ok', 'result': result}
+    except Exception as e:
+        return {'ok': False, 'error': str(e)}
+
+
+def run_healthcheck() -> Tuple[Dict[str, Any], int]:
+    """Run every check. Returns the JSON-serialisable report and the exit code."""
+    config_manager = ConfigManager()
+    report = {'checks': {}}
+
+    report['checks']['config'] = {'ok': not config_manager.errors, 'errors': config_manager.errors}
+
+    if not config_manager.has_valid_config():
+        report['healthy'] = False
+        report['error'] = "No server configured"
+        return report, EXIT_NOT_CONFIGURED
+
+    config = config_manager.get_server_config()
+    report['checks'].update(asyncio.run(
+        check_server(config['url'], config['password'], config_manager.get_api_method())
+    ))
+    report['checks']['database'] = check_database()
+
+    report['healthy'] = all(check['ok'] for check in report['checks'].values())
+    return report, EXIT_HEALTHY if report['healthy'] else EXIT_UNHEALTHY
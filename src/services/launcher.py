@@ -0,0 +1,45 @@
+"""Launcher badge service using the Unity LauncherEntry D-Bus API."""
+
+import hashlib
+from gi.repository import Gio, GLib
+
+
+class LauncherEntry:
+    """Publishes the unread count to docks and launchers that support it."""
+
+    def __init__(self, application_id: str):
+        """Initialize the launcher entry for a desktop file ID."""
+        self.app_uri = f"application://{application_id}.desktop"
+        self.object_path = (
+            "/com/canonical/unity/launcherentry/"
+            + hashlib.md5(self.app_uri.encode()).hexdigest()
+        )
+        self.count = 0
+
+        try:
+            self.connection = Gio.bus_get_sync(Gio.BusType.SESSION, None)
+        except GLib.Error:
+            # No session bus, badges are simply unavailable
+            self.connection = None
+
+    def set_count(self, count: int):
+        """Show a count on the launcher icon; 0 hides the badge."""
+        if count == self.count or self.connection is None:
+            return
+        self.count = count
+
+        properties = {
+            'count': GLib.Variant('x', count),
+            'count-visible': GLib.Variant('b', count > 0),
+        }
+
+        try:
+            self.connection.emit_signal(
+                None,
+                self.object_path,
+                'com.canonical.Unity.LauncherEntry',
+                'Update',
+                GLib.Variant('(sa{sv})', (self.app_uri, properties))
+            )
+        except GLib.Error:
+            pass  # Silently handle launchers going away
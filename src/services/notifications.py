@@ -0,0 +1,41 @@
+"""Desktop notification content, honouring the notification preview setting."""
+
+from typing import Optional, Tuple
+
+# Preview levels in the order shown in preferences:
+# show sender and text, sender only, or a generic "New message"
+NOTIFICATION_PREVIEWS = ['full', 'sender', 'hidden']
+
+GENERIC_TITLE = "New message"
+REMINDER_TITLE = "Reminder"
+
+
+def format_notification(preview: str, chat_title: str, text: Optional[str],
+                        sender: Optional[str] = None) -> Tuple[str, Optional[str]]:
+    """
+    Get the (summary, body) of a new message notification.
+
+    The summary is the line desktops show on the lock screen, so it never
+    carries more than the preview level allows.
+    """
+    if preview == 'hidden':
+        return GENERIC_TITLE, None
+
+    if preview == 'sender':
+        return chat_title, GENERIC_TITLE
+
+    body = text or "Attachment"
+    if sender:
+        body = f"{sender}: {body}"
+    return chat_title, body
+
+
+def format_reminder(preview: str, chat_title: str, text: Optional[str]) -> Tuple[str, Optional[str]]:
+    """Get the (summary, body) of a message reminder notification."""
+    if preview == 'hidden':
+        return REMINDER_TITLE, None
+
+    summary = f"{REMINDER_TITLE}: {chat_title}"
+    if preview == 'sender':
+        return summary, None
+    return summary, text or "Attachment"
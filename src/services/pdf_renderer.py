@@ -0,0 +1,49 @@
+"""PDF page rendering using poppler-glib, when it is installed."""
+
+from typing import Optional
+
+from ..utils.features import require_feature
+
+try:
+    require_feature('pdf')
+    import gi
+    gi.require_version('Poppler', '0.18')
+    from gi.repository import Poppler
+    import cairo
+    POPPLER_AVAILABLE = True
+except (ImportError, ValueError):
+    Poppler = None
+    POPPLER_AVAILABLE = False
+
+from gi.repository import Gdk, GLib
+
+
+def load_pdf(pdf_data: bytes):
+    """Load a Poppler document from PDF bytes."""
+    return Poppler.Document.new_from_bytes(GLib.Bytes.new(pdf_data), None)
+
+
+def render_page(document, index: int, width: int) -> Optional[Gdk.Texture]:
+    """Render a page of a document to a texture of the given pixel width."""
+    if index < 0 or index >= document.get_n_pages():
+        return None
+
+    page = document.get_page(index)
+    page_width, page_height = page.get_size()
+    scale = width / page_width
+    height = max(1, int(page_height * scale))
+
+    surface = cairo.ImageSurface(cairo.FORMAT_ARGB32, width, height)
+    context = cairo.Context(surface)
+
+    # PDFs assume a white page underneath
+    context.set_source_rgb(1, 1, 1)
+    context.paint()
+    context.scale(scale, scale)
+    page.render(context)
+    surface.flush()
+
+    return Gdk.MemoryTexture.new(
+        width, height, Gdk.MemoryFormat.B8G8R8A8_PREMULTIPLIED,
+        GLib.Bytes.new(bytes(surface.get_data())), surface.get_stride()
+    )
@@ -0,0 +1,112 @@
+"""XDG desktop portal helpers for sandbox-friendly access to desktop features."""
+
+import uuid
+from typing import Callable, Optional
+from gi.repository import Gio, GLib
+
+PORTAL_BUS_NAME = 'org.freedesktop.portal.Desktop'
+PORTAL_OBJECT_PATH = '/org/freedesktop/portal/desktop'
+PORTAL_REQUEST_INTERFACE = 'org.freedesktop.portal.Request'
+
+
+class PortalRequest:
+    """Calls a portal method and waits for its Request::Response signal."""
+
+    def __init__(self, interface: str, method: str,
+                 callback: Callable[[int, dict], None]):
+        """Prepare a request. callback receives (response code, results)."""
+        self.interface = interface
+        self.method = method
+        self.callback = callback
+        self.subscription_id = None
+
+        try:
+            self.connection = Gio.bus_get_sync(Gio.BusType.SESSION, None)
+        except GLib.Error:
+            self.connection = None
+
+    def get_request_path(self, token: str) -> str:
+        """Get the object path the portal will use for a request token."""
+        sender = self.connection.get_unique_name()[1:].replace('.', '_')
+        return f"/org/freedesktop/portal/desktop/request/{sender}/{token}"
+
+    def call(self, args_format: str, args: tuple, options: dict) -> bool:
+        """Start the request. options is an a{sv} dict without the handle token."""
+        if self.connection is None:
+            return False
+
+        # Subscribe before calling so the response can't be missed
+        token = f"bluebubbles_{uuid.uuid4().hex}"
+        self.subscription_id = self.connection.signal_subscribe(
+            PORTAL_BUS_NAME, PORTAL_REQUEST_INTERFACE, 'Response',
+            self.get_request_path(token), None,
+            Gio.DBusSignalFlags.NO_MATCH_RULE, self.on_response
+        )
+
+        options = dict(options)
+        options['handle_token'] = GLib.Variant('s', token)
+
+        self.connection.call(
+            PORTAL_BUS_NAME, PORTAL_OBJECT_PATH, self.interface, self.method,
+            GLib.Variant(f"({args_format}a{{sv}})", args + (options,)),
+            None, Gio.DBusCallFlags.NONE, -1, None, self.on_call_finished
+        )
+        return True
+
+    def on_call_finished(self, connection, result):
+        """Report portals that fail before they can respond."""
+        try:
+            connection.call_finish(result)
+        except GLib.Error:
+            self.finish(2, {})
+
+    def on_response(self, connection, sender, path, interface, signal, parameters):
+        """Handle the Request::Response signal."""
+        response, results = parameters.unpack()
+        self.finish(response, results)
+
+    def finish(self, response: int, results: dict):
+        """Unsubscribe and hand the result to the callback."""
+        if self.subscription_id is not None:
+            self.connection.signal_unsubscribe(self.subscription_id)
+            self.subscription_id = None
+        self.callback(response, results)
+
+
+def request_background(autostart: bool, reason: str,
+                       callback: Callable[[bool, bool], None]) -> bool:
+    """
+    Ask the background portal to let the app keep running after its windows close,
+    and optionally to start it at login.
+
+    Calls callback with (background allowed, autostart enabled).
+    Returns False if the portal isn't reachable at all.
+    """
+    def on_response(response, results):
+        if response != 0:
+            callback(False, False)
+            return
+        callback(results.get('background', False), results.get('autostart', False))
+
+    options = {
+        'reason': GLib.Variant('s', reason),
+        'autostart': GLib.Variant('b', autostart),
+        'commandline': GLib.Variant('as', ['bluebubbles', '--background']),
+    }
+    request = PortalRequest('org.freedesktop.portal.Background', 'RequestBackground', on_response)
+    return request.call('s', ('',), options)
+
+
+def take_screenshot(callback: Callable[[Optional[str]], None]) -> bool:
+    """
+    Ask the screenshot portal for an interactive screenshot (region or window).
+
+    Calls callback with the screenshot file path, or None if cancelled or failed.
+    Returns False if the portal isn't reachable at all.
+    """
+    def on_response(response, results):
+        uri = results.get('uri') if response == 0 else None
+        callback(Gio.File.new_for_uri(uri).get_path() if uri else None)
+
+    request = PortalRequest('org.freedesktop.portal.Screenshot', 'Screenshot', on_response)
+    return request.call('s', ('',), {'interactive': GLib.Variant('b', True)})
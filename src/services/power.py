@@ -0,0 +1,111 @@
+"""Power profile service: sync and download behaviour tuned to the power source and network."""
+
+from typing import Callable, Optional
+from gi.repository import Gio, GLib
+
+# Per-profile behaviour. A check interval of None means "use the configured interval",
+# and attachments larger than the download limit (in bytes) wait for the user to confirm.
+POWER_PROFILES = {
+    'performance': {
+        'check_interval': 2,
+        'animations': True,
+        'auto_download': True,
+        'download_limit': None,
+    },
+    'balanced': {
+        'check_interval': None,
+        'animations': True,
+        'auto_download': True,
+        'download_limit': None,
+    },
+    'battery_saver': {
+        'check_interval': 30,
+        'animations': False,
+        'auto_download': False,
+        'download_limit': None,
+    },
+    # Applied automatically on metered connections, never chosen directly
+    'low_bandwidth': {
+        'check_interval': 10,
+        'animations': True,
+        'auto_download': True,
+        'download_limit': 1024 * 1024,
+    },
+}
+
+# Battery percentage at or below which battery saver kicks in automatically
+LOW_BATTERY_PERCENTAGE = 20
+
+
+class PowerMonitor:
+    """Watches UPower for a low battery and reports changes."""
+
+    def __init__(self, on_changed: Callable[[], None]):
+        """Start watching UPower. on_changed is called on the main loop."""
+        self.on_changed = on_changed
+        self.on_battery = False
+        self.percentage = 100.0
+        self.proxy: Optional[Gio.DBusProxy] = None
+        self.device_proxy: Optional[Gio.DBusProxy] = None
+
+        try:
+            self.proxy = Gio.DBusProxy.new_for_bus_sync(
+                Gio.BusType.SYSTEM, Gio.DBusProxyFlags.NONE, None,
+                'org.freedesktop.UPower', '/org/freedesktop/UPower',
+                'org.freedesktop.UPower', None
+            )
+            self.device_proxy = Gio.DBusProxy.new_for_bus_sync(
+                Gio.BusType.SYSTEM, Gio.DBusProxyFlags.NONE, None,
+                'org.freedesktop.UPower', '/org/freedesktop/UPower/devices/DisplayDevice',
+                'org.freedesktop.UPower.Device', None
+            )
+        except GLib.Error:
+            # No UPower (e.g. desktops or sandboxes without system bus access)
+            self.proxy = None
+            self.device_proxy = None
+            return
+
+        self.proxy.connect('g-properties-changed', self.on_properties_changed)
+        self.device_proxy.connect('g-properties-changed', self.on_properties_changed)
+        self.read_properties()
+
+    def read_properties(self):
+        """Read the current battery state from the proxies."""
+        on_battery = self.proxy.get_cached_property('OnBattery') if self.proxy else None
+        percentage = self.device_proxy.get_cached_property('Percentage') if self.device_proxy else None
+
+        self.on_battery = on_battery.unpack() if on_battery is not None else False
+        self.percentage = percentage.unpack() if percentage is not None else 100.0
+
+    def on_properties_changed(self, proxy, changed, invalidated):
+        """Handle UPower property changes."""
+        was_low = self.is_battery_low()
+        self.read_properties()
+        if self.is_battery_low() != was_low:
+            self.on_changed()
+
+    def is_battery_low(self) -> bool:
+        """Check whether we are running on a low battery."""
+        return self.on_battery and self.percentage <= LOW_BATTERY_PERCENTAGE
+
+
+class MeteredMonitor:
+    """Watches the network for metered connections (mobile data, tethering) and reports changes."""
+
+    def __init__(self, on_changed: Callable[[], None]):
+        """Start watching the default network monitor. on_changed is called on the main loop."""
+        self.on_changed = on_changed
+        self.monitor = Gio.NetworkMonitor.get_default()
+        self.metered = self.monitor.get_network_metered()
+        self.monitor.connect('notify::network-metered', self.on_metered_changed)
+
+    def on_metered_changed(self, monitor, pspec):
+        """Handle the network switching between metered and unmetered."""
+        metered = monitor.get_network_metered()
+        if metered != self.metered:
+            self.metered = metered
+            self.on_changed()
+
+    def is_metered(self) -> bool:
+        """Check whether the current connection is metered."""
+        return self.metered
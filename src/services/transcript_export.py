@@ -0,0 +1,51 @@
+"""Chat transcript export, optionally encrypted with a passphrase."""
+
+import io
+import zipfile
+from typing import List, Optional
+
+from ..db.models import ChatRecord, MessageRecord
+from ..utils.export_crypto import encrypt_archive
+from ..utils.time_format import TimeFormat, format_time
+
+
+def format_transcript(chat: ChatRecord, messages: List[MessageRecord],
+                      time_format: TimeFormat) -> str:
+    """Format messages (oldest first) as a plain text transcript."""
+    lines = [chat.display_title, ""]
+
+    for message in messages:
+        # Reactions belong to other messages and aren't part of the transcript
+        if message.associated_message_guid:
+            continue
+
+        sender = "Me" if message.is_from_me else (message.handle_address or "Unknown")
+        text = message.text or ""
+        if message.attachments:
+            names = [attachment.get('transferName') or attachment.get('transfer_name') or 'attachment'
+                     for attachment in message.attachments]
+            text = " ".join(filter(None, [text, *(f"[{name}]" for name in names)]))
+
+        timestamp = format_time(message.datetime_created, time_format, full=True)
+        lines.append(f"[{timestamp}] {sender}: {text}")
+
+    return "\n".join(lines) + "\n"
+
+
+def build_archive(transcript: str) -> bytes:
+    """Pack a transcript into a zip archive."""
+    buffer = io.BytesIO()
+    with zipfile.ZipFile(buffer, 'w', zipfile.ZIP_DEFLATED) as archive:
+        archive.writestr("transcript.txt", transcript)
+    return buffer.getvalue()
+
+
+def export_chat(file_path: str, chat: ChatRecord, messages: List[MessageRecord],
+                time_format: TimeFormat, passphrase: Optional[str] = None):
+    """Write a chat export archive, encrypted when a passphrase is given."""
+    data = build_archive(format_transcript(chat, messages, time_format))
+    if passphrase:
+        data = encrypt_archive(data, passphrase)
+
+    with open(file_path, 'wb') as f:
+        f.write(data)
@@ -0,0 +1,138 @@
+"""Voice memo recording using GStreamer, when it is installed."""
+
+import wave
+from typing import Callable, List, Optional, Tuple
+
+from ..utils.features import require_feature
+
+try:
+    require_feature('media')
+    import gi
+    gi.require_version('Gst', '1.0')
+    from gi.repository import Gst
+    Gst.init(None)
+    GST_AVAILABLE = True
+except (ImportError, ValueError):
+    Gst = None
+    GST_AVAILABLE = False
+
+# Level reported for silence, in dB
+SILENCE_DB = -60.0
+
+
+def list_input_devices() -> List[Tuple[str, object]]:
+    """List audio input devices as (display name, Gst.Device)."""
+    if not GST_AVAILABLE:
+        return []
+
+    monitor = Gst.DeviceMonitor()
+    monitor.add_filter("Audio/Source", None)
+    monitor.start()
+    devices = [(device.get_display_name(), device) for device in monitor.get_devices()]
+    monitor.stop()
+    return devices
+
+
+def get_wav_duration(file_path: str) -> float:
+    """Get the length of a WAV file in seconds."""
+    with wave.open(file_path, 'rb') as wav_file:
+        return wav_file.getnframes() / wav_file.getframerate()
+
+
+def trim_wav(source_path: str, target_path: str, start: float, end: float):
+    """Copy the part of a WAV file between start and end (in seconds)."""
+    with wave.open(source_path, 'rb') as source:
+        rate = source.getframerate()
+        start_frame = max(0, int(start * rate))
+        end_frame = min(source.getnframes(), int(end * rate))
+
+        source.setpos(start_frame)
+        frames = source.readframes(max(0, end_frame - start_frame))
+
+        with wave.open(target_path, 'wb') as target:
+            target.setparams(source.getparams())
+            target.writeframes(frames)
+
+
+class VoiceRecorder:
+    """Records an audio input device to a WAV file, reporting the input level."""
+
+    def __init__(self, device=None, on_level: Callable[[float], None] = None,
+                 on_finished: Callable[[Optional[str]], None] = None):
+        """
+        Create a recorder for a Gst.Device (None uses the default input).
+
+        on_level receives the input level from 0.0 to 1.0 while recording.
+        on_finished receives the file path once the file is complete, or None on error.
+        """
+        self.device = device
+        self.on_level = on_level
+        self.on_finished = on_finished
+        self.pipeline = None
+        self.file_path = None
+
+    def start(self, file_path: str):
+        """Start recording to a WAV file."""
+        self.file_path = file_path
+        self.pipeline = Gst.Pipeline.new("voice-memo")
+
+        if self.device is not None:
+            source = self.device.create_element("source")
+        else:
+            source = Gst.ElementFactory.make("autoaudiosrc", "source")
+
+        convert = Gst.ElementFactory.make("audioconvert", None)
+        resample = Gst.ElementFactory.make("audioresample", None)
+        level = Gst.ElementFactory.make("level", None)
+        level.set_property("interval", 50 * Gst.MSECOND)
+        level.set_property("post-messages", True)
+        encoder = Gst.ElementFactory.make("wavenc", None)
+        sink = Gst.ElementFactory.make("filesink", None)
+        sink.set_property("location", file_path)
+
+        elements = [source, convert, resample, level, encoder, sink]
+        for element in elements:
+            self.pipeline.add(element)
+        for upstream, downstream in zip(elements, elements[1:]):
+            upstream.link(downstream)
+
+        bus = self.pipeline.get_bus()
+        bus.add_signal_watch()
+        bus.connect("message", self.on_bus_message)
+
+        self.pipeline.set_state(Gst.State.PLAYING)
+
+    def stop(self):
+        """Stop recording; on_finished is called once the file is written."""
+        if self.pipeline:
+            # EOS lets wavenc finalize the header before we tear down
+            self.pipeline.send_event(Gst.Event.new_eos())
+
+    def cancel(self):
+        """Stop recording and discard the result."""
+        self.on_finished = None
+        self.teardown()
+
+    def teardown(self):
+        """Release the pipeline."""
+        if self.pipeline:
+            self.pipeline.get_bus().remove_signal_watch()
+            self.pipeline.set_state(Gst.State.NULL)
+            self.pipeline = None
+
+    def on_bus_message(self, bus, message):
+        """Handle level, end-of-stream and error messages."""
+        if message.type == Gst.MessageType.ELEMENT:
+            structure = message.get_structure()
+            if structure and structure.get_name() == "level" and self.on_level:
+                rms = structure.get_value("rms")
+                peak_db = max(rms) if rms else SILENCE_DB
+                self.on_level(min(1.0, max(0.0, 1.0 - peak_db / SILENCE_DB)))
+        elif message.type == Gst.MessageType.EOS:
+            self.teardown()
+            if self.on_finished:
+                self.on_finished(self.file_path)
+        elif message.type == Gst.MessageType.ERROR:
+            self.teardown()
+            if self.on_finished:
+                self.on_finished(None)
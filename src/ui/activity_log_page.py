@@ -0,0 +1,99 @@
+"""
+Activity Log Page
+Preferences subpage listing recent client activity for troubleshooting
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw
+from datetime import datetime
+from ..utils.time_format import format_time
+
+# Activity kind -> (icon name, label)
+ACTIVITY_KINDS = {
+    'session': ("system-run-symbolic", "Session"),
+    'login': ("system-users-symbolic", "Login"),
+    'logout': ("system-log-out-symbolic", "Logout"),
+    'server': ("network-server-symbolic", "Server"),
+    'request_failed': ("dialog-error-symbolic", "Request Failed"),
+    'connection_lost': ("network-offline-symbolic", "Connection Lost"),
+    'reconnected': ("network-transmit-receive-symbolic", "Reconnected"),
+    'live_events': ("emblem-synchronizing-symbolic", "Live Events"),
+    'rate_limited': ("content-loading-symbolic", "Rate Limited"),
+    'clock_skew': ("preferences-system-time-symbolic", "Clock Skew"),
+    'database_repaired': ("drive-harddisk-symbolic", "Cache Rebuilt"),
+    'imessage_account': ("avatar-default-symbolic", "iMessage Account"),
+    'error': ("dialog-warning-symbolic", "Error"),
+}
+
+
+class ActivityLogPage(Adw.NavigationPage):
+    """Lists recent activity log entries, newest first."""
+
+    def __init__(self, application):
+        super().__init__()
+        self.application = application
+        self.chat_service = application.get_chat_service()
+
+        self.set_title("Activity Log")
+
+        self.setup_ui()
+        self.load_entries()
+
+    def setup_ui(self):
+        """Set up the page UI."""
+        toolbar_view = Adw.ToolbarView()
+
+        header_bar = Adw.HeaderBar()
+        self.clear_button = Gtk.Button()
+        self.clear_button.set_label("Clear")
+        self.clear_button.connect("clicked", self.on_clear_clicked)
+        header_bar.pack_end(self.clear_button)
+        toolbar_view.add_top_bar(header_bar)
+
+        self.stack = Gtk.Stack()
+
+        empty_page = Adw.StatusPage()
+        empty_page.set_icon_name("document-open-recent-symbolic")
+        empty_page.set_title("No Activity")
+        empty_page.set_description("Logins, connection problems and failed requests appear here")
+        self.stack.add_named(empty_page, "empty")
+
+        page = Adw.PreferencesPage()
+        self.entries_group = Adw.PreferencesGroup()
+        self.entries_group.set_description(
+            "Timestamps can be matched against your server's logs. Passwords are never recorded."
+        )
+        page.add(self.entries_group)
+        self.stack.add_named(page, "list")
+
+        toolbar_view.set_content(self.stack)
+        self.set_child(toolbar_view)
+
+    def load_entries(self):
+        """Show the logged entries."""
+        entries = self.chat_service.get_activity_log()
+        time_format = self.application.config_manager.get_time_format()
+
+        for entry in entries:
+            icon_name, label = ACTIVITY_KINDS.get(entry['kind'], ("dialog-information-symbolic", entry['kind']))
+            timestamp = format_time(datetime.fromtimestamp(entry['timestamp'] / 1000), time_format, full=True)
+
+            row = Adw.ActionRow()
+            row.set_title(entry['message'])
+            row.set_title_lines(3)
+            row.set_subtitle(f"{label} · {timestamp}")
+            row.set_use_markup(False)
+            row.add_prefix(Gtk.Image.new_from_icon_name(icon_name))
+            self.entries_group.add(row)
+
+        self.clear_button.set_sensitive(bool(entries))
+        self.stack.set_visible_child_name("list" if entries else "empty")
+
+    def on_clear_clicked(self, button):
+        """Clear the activity log."""
+        self.chat_service.clear_activity_log()
+        self.clear_button.set_sensitive(False)
+        self.stack.set_visible_child_name("empty")
@@ -0,0 +1,188 @@
+"""
+Attachment Preview Dialog
+Shows a pasted, dropped or picked image before it is sent, with an optional
+caption and basic crop/rotate
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+gi.require_version('GdkPixbuf', '2.0')
+
+from gi.repository import Gtk, Adw, Gdk, GdkPixbuf
+import os
+import tempfile
+from typing import Callable, Optional
+
+# Crop presets: (label, width:height ratio or None for the original image)
+CROP_PRESETS = [
+    ("Original", None),
+    ("Square", 1.0),
+    ("4:3", 4 / 3),
+    ("16:9", 16 / 9),
+]
+
+
+def center_crop_rect(width: int, height: int, ratio: Optional[float]):
+    """Get the (x, y, width, height) of the largest centered crop with the given ratio."""
+    if ratio is None:
+        return 0, 0, width, height
+
+    if width / height > ratio:
+        crop_width = max(1, round(height * ratio))
+        return (width - crop_width) // 2, 0, crop_width, height
+
+    crop_height = max(1, round(width / ratio))
+    return 0, (height - crop_height) // 2, width, crop_height
+
+
+def texture_to_pixbuf(texture: Gdk.Texture) -> GdkPixbuf.Pixbuf:
+    """Convert a texture (e.g. from the clipboard) into a pixbuf."""
+    loader = GdkPixbuf.PixbufLoader.new_with_type('png')
+    loader.write_bytes(texture.save_to_png_bytes())
+    loader.close()
+    return loader.get_pixbuf()
+
+
+class AttachmentPreviewDialog(Adw.Dialog):
+    """Preview an image and confirm sending it."""
+
+    def __init__(self, pixbuf: GdkPixbuf.Pixbuf,
+                 on_send: Callable[[str, str, Optional[tempfile.TemporaryDirectory]], None],
+                 file_path: str = None):
+        super().__init__()
+
+        # Original image, before rotation and cropping
+        self.original_pixbuf = pixbuf
+        self.rotation = 0
+        self.crop_ratio = None
+        # Called with (file_path, caption, temp_dir) once the user confirms; temp_dir
+        # holds an edited image and is the sender's to clean up after the upload
+        self.on_send = on_send
+        # Source file, sent as-is when the image isn't edited
+        self.file_path = file_path
+
+        self.set_title("Send Image")
+        self.set_content_width(520)
+        self.set_content_height(560)
+
+        self.setup_ui()
+        self.update_preview()
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        toolbar_view = Adw.ToolbarView()
+
+        header_bar = Adw.HeaderBar()
+        header_bar.set_show_end_title_buttons(False)
+        header_bar.set_show_start_title_buttons(False)
+
+        cancel_button = Gtk.Button()
+        cancel_button.set_label("Cancel")
+        cancel_button.connect("clicked", lambda b: self.close())
+        header_bar.pack_start(cancel_button)
+
+        send_button = Gtk.Button()
+        send_button.set_label("Send")
+        send_button.add_css_class("suggested-action")
+        send_button.connect("clicked", self.on_send_clicked)
+        header_bar.pack_end(send_button)
+
+        toolbar_view.add_top_bar(header_bar)
+
+        content_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=12)
+        content_box.set_margin_top(12)
+        content_box.set_margin_bottom(12)
+        content_box.set_margin_start(12)
+        content_box.set_margin_end(12)
+
+        # Image preview
+        self.picture = Gtk.Picture()
+        self.picture.set_vexpand(True)
+        self.picture.set_content_fit(Gtk.ContentFit.CONTAIN)
+        self.picture.add_css_class("attachment-preview")
+        content_box.append(self.picture)
+
+        # Edit controls
+        edit_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=6)
+        edit_box.set_halign(Gtk.Align.CENTER)
+
+        rotate_left_button = Gtk.Button()
+        rotate_left_button.set_icon_name("object-rotate-left-symbolic")
+        rotate_left_button.set_tooltip_text("Rotate left")
+        rotate_left_button.connect("clicked", self.on_rotate_clicked, 90)
+        edit_box.append(rotate_left_button)
+
+        rotate_right_button = Gtk.Button()
+        rotate_right_button.set_icon_name("object-rotate-right-symbolic")
+        rotate_right_button.set_tooltip_text("Rotate right")
+        rotate_right_button.connect("clicked", self.on_rotate_clicked, 270)
+        edit_box.append(rotate_right_button)
+
+        self.crop_dropdown = Gtk.DropDown.new_from_strings([label for label, ratio in CROP_PRESETS])
+        self.crop_dropdown.set_tooltip_text("Crop")
+        self.crop_dropdown.connect("notify::selected", self.on_crop_changed)
+        edit_box.append(self.crop_dropdown)
+
+        content_box.append(edit_box)
+
+        # Caption
+        self.caption_entry = Gtk.Entry()
+        self.caption_entry.set_placeholder_text("Add a caption (optional)")
+        self.caption_entry.connect("activate", self.on_send_clicked)
+        content_box.append(self.caption_entry)
+
+        toolbar_view.set_content(content_box)
+        self.set_child(toolbar_view)
+
+        self.set_focus(self.caption_entry)
+
+    def get_edited_pixbuf(self) -> GdkPixbuf.Pixbuf:
+        """Apply the rotation and crop to the original image."""
+        pixbuf = self.original_pixbuf
+        if self.rotation:
+            pixbuf = pixbuf.rotate_simple(GdkPixbuf.PixbufRotation(self.rotation))
+
+        x, y, width, height = center_crop_rect(pixbuf.get_width(), pixbuf.get_height(), self.crop_ratio)
+        if (width, height) != (pixbuf.get_width(), pixbuf.get_height()):
+            pixbuf = pixbuf.new_subpixbuf(x, y, width, height)
+        return pixbuf
+
+    def update_preview(self):
+        """Show the edited image."""
+        self.picture.set_paintable(Gdk.Texture.new_for_pixbuf(self.get_edited_pixbuf()))
+
+    def on_rotate_clicked(self, button, degrees: int):
+        """Rotate the image by 90 degrees (counter-clockwise angles, as GdkPixbuf uses)."""
+        self.rotation = (self.rotation + degrees) % 360
+        self.update_preview()
+
+    def on_crop_changed(self, dropdown, pspec):
+        """Apply a crop preset."""
+        self.crop_ratio = CROP_PRESETS[dropdown.get_selected()][1]
+        self.update_preview()
+
+    def on_send_clicked(self, widget):
+        """Save the edited image and hand it to the sender."""
+        caption = self.caption_entry.get_text().strip()
+
+        # Unedited files go out untouched, keeping their original format
+        if self.file_path and not self.rotation and self.crop_ratio is None:
+            self.on_send(self.file_path, caption, None)
+            self.close()
+            return
+
+        base_name = "image"
+        if self.file_path:
+            base_name = os.path.splitext(os.path.basename(self.file_path))[0]
+        temp_dir = tempfile.TemporaryDirectory(prefix="bluebubbles-")
+        file_path = os.path.join(temp_dir.name, f"{base_name}.png")
+
+        try:
+            self.get_edited_pixbuf().savev(file_path, "png", [], [])
+        except Exception:
+            temp_dir.cleanup()
+            return  # Saving failed; keep the dialog open
+
+        self.on_send(file_path, caption, temp_dir)
+        self.close()
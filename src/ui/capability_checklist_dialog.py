@@ -0,0 +1,122 @@
+"""
+Capability Checklist Dialog
+Shows which server features work after signing in, and how to fix the ones that don't
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw, GLib
+import asyncio
+import threading
+from typing import List
+
+from ..services.capabilities import run_capability_checks
+from ..utils.capabilities import CHECK_TITLES, CapabilityCheck
+
+
+class CapabilityChecklistDialog(Adw.Dialog):
+    """Checks the server's features one by one so missing ones come with a reason."""
+
+    def __init__(self, application):
+        super().__init__()
+
+        self.config_manager = application.config_manager
+        # Check ID -> (row, status icon)
+        self.rows = {}
+
+        self.set_title("Server Checklist")
+        self.set_content_width(460)
+
+        self.setup_ui()
+        self.run_checks()
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        toolbar_view = Adw.ToolbarView()
+
+        header_bar = Adw.HeaderBar()
+        self.recheck_button = Gtk.Button()
+        self.recheck_button.set_label("Check Again")
+        self.recheck_button.connect("clicked", lambda b: self.run_checks())
+        header_bar.pack_start(self.recheck_button)
+        toolbar_view.add_top_bar(header_bar)
+
+        page = Adw.PreferencesPage()
+        group = Adw.PreferencesGroup()
+        group.set_description("What works with your server. Features that are missing show how to turn them on.")
+
+        for check_id, title in CHECK_TITLES.items():
+            row = Adw.ActionRow()
+            row.set_title(title)
+            row.set_subtitle_lines(0)
+            icon = Gtk.Image()
+            icon.set_valign(Gtk.Align.CENTER)
+            row.add_prefix(icon)
+            group.add(row)
+            self.rows[check_id] = (row, icon)
+
+        page.add(group)
+
+        continue_button = Gtk.Button()
+        continue_button.set_label("Continue")
+        continue_button.set_halign(Gtk.Align.CENTER)
+        continue_button.add_css_class("pill")
+        continue_button.add_css_class("suggested-action")
+        continue_button.connect("clicked", lambda b: self.close())
+        button_group = Adw.PreferencesGroup()
+        button_group.add(continue_button)
+        page.add(button_group)
+
+        toolbar_view.set_content(page)
+        self.set_child(toolbar_view)
+
+    def run_checks(self):
+        """Run every check in the background, showing the rows as in progress."""
+        config = self.config_manager.get_server_config()
+        api_method = self.config_manager.get_api_method()
+        self.recheck_button.set_sensitive(False)
+        for row, icon in self.rows.values():
+            row.set_subtitle("Checking…")
+            icon.set_from_icon_name("content-loading-symbolic")
+            icon.set_css_classes(["dim-label"])
+
+        def run_async():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            try:
+                checks = loop.run_until_complete(
+                    run_capability_checks(config['url'], config['password'], api_method)
+                )
+            except Exception as e:
+                # Couldn't even start the requests, e.g. a malformed URL
+                checks = [CapabilityCheck('reachable', False, str(e))] + [
+                    CapabilityCheck(check_id, None) for check_id in list(CHECK_TITLES)[1:]
+                ]
+            finally:
+                loop.close()
+            GLib.idle_add(self.show_results, checks)
+
+        threading.Thread(target=run_async, daemon=True).start()
+
+    def show_results(self, checks: List[CapabilityCheck]):
+        """Mark each row passed, failed with its fix, or skipped."""
+        for check in checks:
+            row, icon = self.rows[check.id]
+            if check.ok:
+                icon.set_from_icon_name("emblem-ok-symbolic")
+                icon.set_css_classes(["success"])
+                row.set_subtitle(GLib.markup_escape_text(check.detail or "Working"))
+            elif check.ok is False:
+                icon.set_from_icon_name("dialog-warning-symbolic")
+                icon.set_css_classes(["error"])
+                lines = [check.detail, check.hint] if check.detail else [check.hint]
+                row.set_subtitle(GLib.markup_escape_text("\n".join(lines)))
+            else:
+                icon.set_from_icon_name("action-unavailable-symbolic")
+                icon.set_css_classes(["dim-label"])
+                row.set_subtitle(GLib.markup_escape_text(check.detail or "Not checked until the items above work"))
+
+        self.recheck_button.set_sensitive(True)
+        return False
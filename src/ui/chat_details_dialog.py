@@ -0,0 +1,384 @@
+"""
+Chat Details Dialog
+Shows information about a conversation and per-chat options
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw, Gio, GLib
+import asyncio
+import os
+import threading
+from ..db.models import ChatRecord
+from ..utils.file_names import safe_file_name
+from ..utils.links import MessageLink
+from ..utils.participant_colors import participant_css_class
+from ..utils.participants import ParticipantHandle, Participant, build_contact_card, group_participants
+from ..utils.phone_format import format_phone_number
+from ..utils.privacy import OVERRIDE_CHOICES, override_labels
+
+
+class ChatDetailsDialog(Adw.PreferencesDialog):
+    """Details and options for a single chat."""
+
+    def __init__(self, window, chat: ChatRecord):
+        super().__init__()
+
+        # The main window performs the actual updates so it can refresh its views
+        self.window = window
+        self.chat = chat
+
+        self.set_title("Chat Details")
+
+        self.setup_ui()
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        page = Adw.PreferencesPage()
+        page.set_title("Details")
+        page.set_icon_name("user-info-symbolic")
+
+        # Name group
+        name_group = Adw.PreferencesGroup()
+
+        if self.chat.is_group_chat:
+            name_group.set_title("Group Name")
+            name_group.set_description("Renaming a group changes its name for everyone")
+
+            self.name_row = Adw.EntryRow()
+            self.name_row.set_title("Name")
+            self.name_row.set_text(self.chat.display_name or "")
+            self.name_row.set_show_apply_button(True)
+            self.name_row.connect("apply", self.on_group_name_applied)
+            name_group.add(self.name_row)
+        else:
+            name_group.set_title("Nickname")
+            name_group.set_description("Only shown on this device, in place of the contact name")
+
+            self.name_row = Adw.EntryRow()
+            self.name_row.set_title("Nickname")
+            self.name_row.set_text(self.chat.nickname or "")
+            self.name_row.set_show_apply_button(True)
+            self.name_row.connect("apply", self.on_nickname_applied)
+            name_group.add(self.name_row)
+
+        page.add(name_group)
+
+        # Group photo, changeable through the private API only
+        if self.chat.is_group_chat:
+            photo_group = Adw.PreferencesGroup()
+            photo_group.set_title("Group Photo")
+
+            photo_row = Adw.ActionRow()
+            photo_row.set_title("Set Group Photo")
+            photo_row.set_activatable(True)
+            photo_row.add_suffix(Gtk.Image.new_from_icon_name("go-next-symbolic"))
+
+            if self.window.config_manager.get_api_method() == 'private':
+                photo_row.set_subtitle("Choose an image for everyone in the group")
+                photo_row.connect("activated", self.on_set_photo_activated)
+            else:
+                photo_row.set_subtitle("Requires the Private API")
+                photo_row.set_sensitive(False)
+
+            photo_group.add(photo_row)
+            page.add(photo_group)
+
+        # Unread badge, separate from notifications
+        badge_group = Adw.PreferencesGroup()
+        badge_group.set_title("Unread Badge")
+
+        self.hide_badge_row = Adw.SwitchRow()
+        self.hide_badge_row.set_title("Hide Unread Badge")
+        self.hide_badge_row.set_subtitle("Leave this chat out of unread counts. Notifications still arrive.")
+        self.hide_badge_row.set_active(self.chat.hide_badge)
+        self.hide_badge_row.connect("notify::active", self.on_hide_badge_changed)
+        badge_group.add(self.hide_badge_row)
+
+        page.add(badge_group)
+
+        # Privacy, overriding the global preferences for this chat
+        privacy_group = Adw.PreferencesGroup()
+        privacy_group.set_title("Privacy")
+
+        config_manager = self.window.get_application().config_manager
+        self.typing_indicators_row = Adw.ComboRow()
+        self.typing_indicators_row.set_title("Typing Indicators")
+        self.typing_indicators_row.set_subtitle("Whether others see when you're typing here")
+        self.typing_indicators_row.set_model(Gtk.StringList.new(
+            override_labels(config_manager.get_send_typing_indicators())
+        ))
+        override = self.window.chat_service.get_typing_override(self.chat.guid)
+        self.typing_indicators_row.set_selected(OVERRIDE_CHOICES.index(override))
+        self.typing_indicators_row.connect("notify::selected", self.on_typing_indicators_changed)
+        privacy_group.add(self.typing_indicators_row)
+
+        self.read_receipts_row = Adw.ComboRow()
+        self.read_receipts_row.set_title("Mark Read on Server")
+        self.read_receipts_row.set_subtitle("Whether opening this chat marks it read on the server")
+        self.read_receipts_row.set_model(Gtk.StringList.new(
+            override_labels(config_manager.get_send_read_receipts())
+        ))
+        override = self.window.chat_service.get_read_receipts_override(self.chat.guid)
+        self.read_receipts_row.set_selected(OVERRIDE_CHOICES.index(override))
+        self.read_receipts_row.connect("notify::selected", self.on_read_receipts_changed)
+        privacy_group.add(self.read_receipts_row)
+
+        page.add(privacy_group)
+
+        # Local labels, for filtering the chat list and quietening notifications
+        self.labels_group = Adw.PreferencesGroup()
+        self.labels_group.set_title("Labels")
+        self.labels_group.set_description("Only shown on this device")
+        self.label_rows = []
+        self.setup_label_rows()
+
+        self.new_label_row = Adw.EntryRow()
+        self.new_label_row.set_title("New Label")
+        self.new_label_row.set_show_apply_button(True)
+        self.new_label_row.connect("apply", self.on_new_label_applied)
+        self.labels_group.add(self.new_label_row)
+
+        page.add(self.labels_group)
+
+        # Local storage, for keeping huge chats off the disk
+        storage_group = Adw.PreferencesGroup()
+        storage_group.set_title("Storage")
+
+        self.exclude_row = Adw.SwitchRow()
+        self.exclude_row.set_title("Don't Keep Offline")
+        self.exclude_row.set_subtitle("Fetch messages and attachments from the server each time instead of "
+                                      "storing them on this computer. Cached messages are removed.")
+        self.exclude_row.set_active(self.window.chat_service.is_chat_excluded(self.chat.guid))
+        self.exclude_row.connect("notify::active", self.on_exclude_changed)
+        storage_group.add(self.exclude_row)
+
+        page.add(storage_group)
+
+        # Participants group, one row per person with a row per handle of theirs
+        participants_group = Adw.PreferencesGroup()
+        participants_group.set_title("Participants")
+
+        # Handle address -> row showing its iMessage availability
+        self.handle_rows = {}
+        participants = group_participants(self.chat, self.window.chat_service.get_contact_name)
+        for participant in participants:
+            if len(participant.handles) == 1:
+                row = self.create_handle_row(participant, participant.handles[0], participant.name)
+            else:
+                row = Adw.ExpanderRow()
+                row.set_title(participant.name)
+                row.set_subtitle(f"{len(participant.handles)} addresses")
+                for handle in participant.handles:
+                    row.add_row(self.create_handle_row(participant, handle))
+
+            if self.chat.is_group_chat:
+                # Same color as the participant's messages in the conversation
+                address = participant.handles[0].address
+                avatar = Adw.Avatar.new(32, participant.name or address.split('@')[0], True)
+                avatar.add_css_class("participant-avatar")
+                avatar.add_css_class(participant_css_class(address))
+                row.add_prefix(avatar)
+            participants_group.add(row)
+
+        page.add(participants_group)
+
+        self.check_imessage_availability([handle.address for p in participants for handle in p.handles])
+
+        self.add(page)
+
+    def create_handle_row(self, participant: Participant, handle: ParticipantHandle,
+                          name: str = None) -> Adw.ActionRow:
+        """Create a row for one handle with its services and quick actions."""
+        formatted = format_phone_number(handle.address, handle.country)
+        row = Adw.ActionRow()
+        row.set_title(name or formatted)
+        row.set_title_selectable(True)
+        row.handle_subtitle = formatted if name else ""
+        row.set_subtitle(row.handle_subtitle)
+
+        # Services the conversation reaches this handle on
+        for service in handle.services:
+            badge = Gtk.Label(label=service)
+            badge.set_valign(Gtk.Align.CENTER)
+            badge.add_css_class("service-badge")
+            badge.add_css_class("caption")
+            row.add_suffix(badge)
+
+        actions_button = Gtk.MenuButton()
+        actions_button.set_icon_name("view-more-symbolic")
+        actions_button.set_tooltip_text("Actions")
+        actions_button.set_valign(Gtk.Align.CENTER)
+        actions_button.add_css_class("flat")
+        actions_button.set_popover(self.create_handle_menu(participant, handle))
+        row.add_suffix(actions_button)
+
+        self.handle_rows[handle.address] = row
+        return row
+
+    def create_handle_menu(self, participant: Participant, handle: ParticipantHandle) -> Gtk.Popover:
+        """Create the quick actions popover of a handle."""
+        popover = Gtk.Popover()
+        menu_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=4)
+        menu_box.set_margin_start(8)
+        menu_box.set_margin_end(8)
+        menu_box.set_margin_top(8)
+        menu_box.set_margin_bottom(8)
+
+        for label, callback in (("Copy Address", self.on_copy_handle),
+                                ("New Chat", self.on_new_chat_with_handle),
+                                ("Open Contact Card", self.on_open_contact_card)):
+            button = Gtk.Button()
+            button.set_label(label)
+            button.add_css_class("flat")
+            button.connect("clicked", callback, participant, handle, popover)
+            menu_box.append(button)
+
+        popover.set_child(menu_box)
+        return popover
+
+    def on_copy_handle(self, button, participant: Participant, handle: ParticipantHandle, popover):
+        """Copy a handle's address to the clipboard."""
+        popover.popdown()
+        self.get_clipboard().set(handle.address)
+        self.add_toast(Adw.Toast.new("Address copied to clipboard"))
+
+    def on_new_chat_with_handle(self, button, participant: Participant, handle: ParticipantHandle, popover):
+        """Open the one-to-one conversation with a handle, starting it if there isn't one."""
+        popover.popdown()
+        self.close()
+        # Chat windows have no chat list, so the main window opens it
+        main_window = self.window.get_application().main_window
+        if main_window:
+            main_window.present()
+            main_window.open_message_link(MessageLink(addresses=[handle.address]))
+
+    def on_open_contact_card(self, button, participant: Participant, handle: ParticipantHandle, popover):
+        """Open a participant's contact card in the contacts app, to view or save it."""
+        popover.popdown()
+        name = participant.name or handle.address
+        # The contacts app reads the card after launch, so it stays until the app exits
+        file_path = os.path.join(self.window.chat_service.attachment_cache.make_open_dir(),
+                                 safe_file_name(name, 'vcf', 'Contact'))
+        with open(file_path, 'w', encoding='utf-8') as f:
+            f.write(build_contact_card(participant))
+        Gtk.FileLauncher.new(Gio.File.new_for_path(file_path)).launch(self.window, None, None, None)
+
+    def check_imessage_availability(self, addresses):
+        """Look up which handles can receive iMessages and show it under each."""
+        config = self.window.config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            return
+
+        def run_async():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            for address in addresses:
+                available = loop.run_until_complete(
+                    self.window.chat_service.check_imessage_availability(config['url'], config['password'], address)
+                )
+                if available is not None:
+                    GLib.idle_add(self.show_imessage_availability, address, available)
+            loop.close()
+
+        threading.Thread(target=run_async, daemon=True).start()
+
+    def show_imessage_availability(self, address: str, available: bool):
+        """Show whether a handle can receive iMessages."""
+        row = self.handle_rows.get(address)
+        if row is None:
+            return False
+        availability = "Reachable on iMessage" if available else "Not on iMessage"
+        row.set_subtitle(" · ".join(part for part in (row.handle_subtitle, availability) if part))
+        return False
+
+    def on_group_name_applied(self, row):
+        """Rename the group chat on the server."""
+        self.window.rename_group_chat_async(self.chat, row.get_text().strip())
+
+    def on_set_photo_activated(self, row):
+        """Pick an image and upload it as the group photo."""
+        file_dialog = Gtk.FileDialog()
+        file_dialog.set_title("Select Group Photo")
+
+        filter_images = Gtk.FileFilter()
+        filter_images.set_name("Images")
+        filter_images.add_mime_type("image/jpeg")
+        filter_images.add_mime_type("image/png")
+
+        filter_list = Gio.ListStore.new(Gtk.FileFilter)
+        filter_list.append(filter_images)
+        file_dialog.set_filters(filter_list)
+        file_dialog.set_default_filter(filter_images)
+
+        def on_file_selected(dialog, result):
+            try:
+                file = dialog.open_finish(result)
+                if file and file.get_path():
+                    self.window.set_group_icon_async(self.chat, file.get_path())
+            except Exception:
+                pass  # Selection cancelled
+
+        file_dialog.open(self.window, None, on_file_selected)
+
+    def on_hide_badge_changed(self, row, param):
+        """Toggle badge suppression for the chat."""
+        self.window.set_chat_hide_badge(self.chat, row.get_active())
+
+    def on_typing_indicators_changed(self, row, param):
+        """Save whether the chat sends typing indicators."""
+        override = OVERRIDE_CHOICES[row.get_selected()]
+        self.window.chat_service.set_typing_override(self.chat.guid, override)
+
+    def on_read_receipts_changed(self, row, param):
+        """Save whether opening the chat marks it read on the server."""
+        override = OVERRIDE_CHOICES[row.get_selected()]
+        self.window.chat_service.set_read_receipts_override(self.chat.guid, override)
+
+    def setup_label_rows(self):
+        """Add a switch for each label, replacing any shown before."""
+        for row in self.label_rows:
+            self.labels_group.remove(row)
+        self.label_rows = []
+
+        chat_labels = self.window.chat_service.get_chat_labels(self.chat)
+        for name, policy in self.window.chat_service.get_labels():
+            row = Adw.SwitchRow()
+            row.set_title(name)
+            row.set_active(name in chat_labels)
+            row.label_name = name
+            row.connect("notify::active", self.on_label_toggled)
+            self.labels_group.add(row)
+            self.label_rows.append(row)
+
+    def on_label_toggled(self, row, param):
+        """Add the chat to a label or take it out."""
+        labels = [label_row.label_name for label_row in self.label_rows if label_row.get_active()]
+        self.window.set_chat_labels(self.chat, labels)
+
+    def on_new_label_applied(self, row):
+        """Create a label and give it to the chat."""
+        name = row.get_text().strip()
+        if not name:
+            return
+        if not self.window.create_label(name):
+            self.add_toast(Adw.Toast.new(f"A label named “{name}” already exists"))
+            return
+
+        labels = self.window.chat_service.get_chat_labels(self.chat) + [name]
+        self.window.set_chat_labels(self.chat, labels)
+        row.set_text("")
+        # Keep the entry last, below the new label's switch
+        self.labels_group.remove(self.new_label_row)
+        self.setup_label_rows()
+        self.labels_group.add(self.new_label_row)
+
+    def on_exclude_changed(self, row, param):
+        """Keep the chat out of the local cache, or let it back in."""
+        self.window.set_chat_excluded(self.chat, row.get_active())
+
+    def on_nickname_applied(self, row):
+        """Save the local nickname."""
+        self.window.set_chat_nickname(self.chat, row.get_text().strip())
@@ -0,0 +1,206 @@
+"""
+Chat Window
+A single conversation in its own window, sharing the application's services
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw, GLib, Gio
+from ..db.models import ChatRecord
+from .main_window import MainWindow
+
+
+class ChatWindow(MainWindow):
+    """
+    Shows one conversation without the chat list.
+
+    Reuses the main window's conversation view; anything that touches the chat
+    list is forwarded to the main window instead.
+    """
+
+    # There's no content header here, so conversations keep their title area
+    SHOW_CHAT_TITLE_AREA = True
+
+    def __init__(self, application, chat: ChatRecord):
+        # Needed by the overridden setup methods that MainWindow.__init__ calls
+        self.chat = chat
+        super().__init__(application)
+
+        self.set_title(chat.display_title)
+        self.set_default_size(480, 720)
+        self.set_maximized(False)
+
+    def save_state(self):
+        """Save the conversation's draft; only the main window's size is remembered."""
+        self.save_drafts()
+
+    def setup_ui(self):
+        """Set up the window with just the conversation."""
+        content = Adw.ToastOverlay()
+
+        toolbar_view = Adw.ToolbarView()
+        toolbar_view.add_top_bar(self.create_header_bar())
+
+        # Shown while an attachment uploads
+        self.sync_progress_bar = Gtk.ProgressBar()
+        self.sync_progress_bar.add_css_class("osd")
+        self.sync_progress_bar.set_visible(False)
+        toolbar_view.add_top_bar(self.sync_progress_bar)
+
+        self.content_stack = Gtk.Stack()
+        toolbar_view.set_content(self.content_stack)
+
+        content.set_child(toolbar_view)
+        self.set_content(content)
+        self.toast_overlay = content
+
+        # Composer actions
+        screenshot_action = Gio.SimpleAction.new('capture-screenshot', None)
+        screenshot_action.connect('activate', self.on_capture_screenshot_action)
+        self.add_action(screenshot_action)
+
+        voice_memo_action = Gio.SimpleAction.new('record-voice-memo', None)
+        voice_memo_action.connect('activate', self.on_record_voice_memo_action)
+        self.add_action(voice_memo_action)
+
+        share_contact_action = Gio.SimpleAction.new('share-contact', None)
+        share_contact_action.connect('activate', self.on_share_contact_action)
+        self.add_action(share_contact_action)
+
+        find_in_chat_action = Gio.SimpleAction.new('find-in-chat', None)
+        find_in_chat_action.connect('activate', self.on_find_in_chat_action)
+        self.add_action(find_in_chat_action)
+
+    def create_header_bar(self) -> Adw.HeaderBar:
+        """Create the window's header bar."""
+        return Adw.HeaderBar()
+
+    def get_main_window(self):
+        """Get the application's main window, if it is open."""
+        return self.get_application().main_window
+
+    def load_server_info(self):
+        """Keep the chat name as the window title."""
+
+    def load_chats(self, force_refresh: bool = False):
+        """Open the conversation."""
+        self.current_chat = self.chat
+        self.load_chat_view(self.chat)
+        if self.chat_service.sends_read_receipts(self.chat.guid):
+            for chat_guid in self.chat.guids:
+                self.mark_chat_read_async(chat_guid)
+        if self.chat.unread_count:
+            for chat_guid in self.chat.guids:
+                self.chat_service.mark_chat_read_locally(chat_guid)
+            GLib.idle_add(self.update_chat_row, self.chat.guid)
+
+    def start_message_monitoring(self):
+        """Listen for new messages; the main window runs the actual checking."""
+        self.chat_service.add_new_message_callback(self.on_new_message_detected)
+
+    def on_new_message_detected(self, chat_guid: str):
+        """Refresh the conversation when a message arrives in it."""
+        if chat_guid != self.chat.guid:
+            return
+
+        def update_ui():
+            self.chat_service.mark_chat_read_locally(chat_guid)
+            self.refresh_current_chat_messages()
+            self.update_chat_row(chat_guid)
+
+        GLib.idle_add(update_ui)
+
+    def populate_chat_list(self):
+        """There is no chat list in this window."""
+
+    def select_chat(self, chat_guid: str) -> bool:
+        """There is no chat list in this window."""
+        return chat_guid == self.chat.guid
+
+    def update_chat_row(self, chat_guid: str):
+        """Update the chat's row in the main window."""
+        main_window = self.get_main_window()
+        if main_window:
+            main_window.update_chat_row(chat_guid)
+
+    def reload_chat(self, chat_guid: str):
+        """Reload the chat here and in the main window."""
+        updated_chat = self.chat_service.get_chat_by_guid(chat_guid)
+        if not updated_chat:
+            return
+
+        self.chat = self.current_chat = updated_chat
+        self.set_title(updated_chat.display_title)
+
+        chat_view = self.content_stack.get_child_by_name(f"chat_{chat_guid}")
+        if chat_view and hasattr(chat_view, 'title_label'):
+            chat_view.title_label.set_text(updated_chat.display_title)
+            self.load_chat_avatar_async(chat_view.title_avatar, updated_chat)
+
+        main_window = self.get_main_window()
+        if main_window:
+            main_window.reload_chat(chat_guid)
+
+    def refresh_timestamps(self):
+        """Re-render the conversation after a time format change."""
+        chat_view = self.content_stack.get_child_by_name(f"chat_{self.chat.guid}")
+        if chat_view and hasattr(chat_view, 'messages_box'):
+            self.load_chat_messages(self.chat, chat_view.messages_box,
+                                    getattr(chat_view, 'messages_area', None))
+
+    def on_close_request(self, window):
+        """Conversation windows always close."""
+        return False
+
+    def on_window_destroy(self, window):
+        """Stop listening for messages; checking continues for the main window."""
+        self.chat_service.remove_new_message_callback(self.on_new_message_detected)
+        self.chat_service.remove_sync_progress_callback(self.on_sync_progress)
+        self.get_application().forget_chat_window(self)
+
+
+class CompactChatWindow(ChatWindow):
+    """
+    A minimal mini window with just the messages and the composer.
+
+    GTK 4 leaves window stacking to the compositor, so keeping it on top is
+    done through the window manager (e.g. "Always on Top" in the window menu).
+    """
+
+    # The slim header already names the chat
+    SHOW_CHAT_TITLE_AREA = False
+
+    def __init__(self, application, chat: ChatRecord):
+        super().__init__(application, chat)
+
+        self.set_default_size(360, 520)
+        self.add_css_class("compact-chat-window")
+
+    def create_header_bar(self) -> Adw.HeaderBar:
+        """Create a slim header with the chat name and a way back."""
+        header_bar = Adw.HeaderBar()
+        header_bar.add_css_class("flat")
+
+        title_label = Gtk.Label()
+        title_label.set_text(self.chat.display_title)
+        title_label.set_ellipsize(3)  # ELLIPSIZE_END
+        title_label.add_css_class("heading")
+        header_bar.set_title_widget(title_label)
+
+        expand_button = Gtk.Button()
+        expand_button.set_icon_name("view-fullscreen-symbolic")
+        expand_button.set_tooltip_text("Back to Main Window")
+        expand_button.connect("clicked", self.on_expand_clicked)
+        header_bar.pack_start(expand_button)
+
+        return header_bar
+
+    def on_expand_clicked(self, button):
+        """Return to the conversation in the main window."""
+        main_window = self.get_main_window()
+        if main_window:
+            main_window.present()
+            main_window.select_chat(self.chat.guid)
+        self.close()
@@ -0,0 +1,129 @@
+"""
+Export Chat Dialog
+Exports a conversation transcript, optionally encrypted with a passphrase
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw
+from ..db.models import ChatRecord
+from ..utils.export_crypto import ENCRYPTION_AVAILABLE
+from ..utils.file_names import safe_file_name
+from ..utils.features import get_missing_reason
+
+
+class ExportChatDialog(Adw.Dialog):
+    """Choose export options and a destination for a chat transcript."""
+
+    def __init__(self, window, chat: ChatRecord):
+        super().__init__()
+
+        # The main window runs the export in the background
+        self.window = window
+        self.chat = chat
+
+        self.set_title("Export Chat")
+        self.set_content_width(420)
+
+        self.setup_ui()
+        self.update_export_button()
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        toolbar_view = Adw.ToolbarView()
+
+        header_bar = Adw.HeaderBar()
+        header_bar.set_show_end_title_buttons(False)
+        header_bar.set_show_start_title_buttons(False)
+
+        cancel_button = Gtk.Button()
+        cancel_button.set_label("Cancel")
+        cancel_button.connect("clicked", lambda b: self.close())
+        header_bar.pack_start(cancel_button)
+
+        self.export_button = Gtk.Button()
+        self.export_button.set_label("Export")
+        self.export_button.add_css_class("suggested-action")
+        self.export_button.connect("clicked", self.on_export_clicked)
+        header_bar.pack_end(self.export_button)
+
+        toolbar_view.add_top_bar(header_bar)
+
+        page = Adw.PreferencesPage()
+
+        encryption_group = Adw.PreferencesGroup()
+        encryption_group.set_title("Encryption")
+        if ENCRYPTION_AVAILABLE:
+            encryption_group.set_description(
+                "Protect the archive with AES-256-GCM. Open it again with "
+                "\"bluebubbles --decrypt-export\". The passphrase can't be recovered if it's lost."
+            )
+        else:
+            encryption_group.set_description(f"Exports can't be encrypted: {get_missing_reason('encryption')}")
+
+        self.encrypt_row = Adw.SwitchRow()
+        self.encrypt_row.set_title("Encrypt with Passphrase")
+        self.encrypt_row.set_sensitive(ENCRYPTION_AVAILABLE)
+        self.encrypt_row.connect("notify::active", lambda row, pspec: self.update_export_button())
+        encryption_group.add(self.encrypt_row)
+
+        self.passphrase_row = Adw.PasswordEntryRow()
+        self.passphrase_row.set_title("Passphrase")
+        self.passphrase_row.connect("changed", lambda row: self.update_export_button())
+        encryption_group.add(self.passphrase_row)
+
+        self.confirm_row = Adw.PasswordEntryRow()
+        self.confirm_row.set_title("Confirm Passphrase")
+        self.confirm_row.connect("changed", lambda row: self.update_export_button())
+        encryption_group.add(self.confirm_row)
+
+        page.add(encryption_group)
+
+        toolbar_view.set_content(page)
+        self.set_child(toolbar_view)
+
+    def get_passphrase(self):
+        """Get the entered passphrase, or None when not encrypting."""
+        if not self.encrypt_row.get_active():
+            return None
+        return self.passphrase_row.get_text()
+
+    def update_export_button(self):
+        """Only allow exporting with matching, non-empty passphrases."""
+        encrypt = self.encrypt_row.get_active()
+        self.passphrase_row.set_visible(encrypt)
+        self.confirm_row.set_visible(encrypt)
+
+        passphrase = self.passphrase_row.get_text()
+        mismatch = bool(self.confirm_row.get_text()) and self.confirm_row.get_text() != passphrase
+        if mismatch:
+            self.confirm_row.add_css_class("error")
+        else:
+            self.confirm_row.remove_css_class("error")
+
+        self.export_button.set_sensitive(
+            not encrypt or (bool(passphrase) and self.confirm_row.get_text() == passphrase)
+        )
+
+    def on_export_clicked(self, button):
+        """Pick a destination and start the export."""
+        passphrase = self.get_passphrase()
+        extension = "bbexport" if passphrase else "zip"
+
+        file_dialog = Gtk.FileDialog()
+        file_dialog.set_title("Export Chat")
+        file_dialog.set_initial_name(safe_file_name(self.chat.display_title, extension, "Chat"))
+
+        def on_file_selected(dialog, result):
+            try:
+                file = dialog.save_finish(result)
+            except Exception:
+                return  # Selection cancelled
+
+            if file and file.get_path():
+                self.window.export_chat_async(self.chat, file.get_path(), passphrase)
+                self.close()
+
+        file_dialog.save(self.window, None, on_file_selected)
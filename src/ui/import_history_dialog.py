@@ -0,0 +1,250 @@
+"""
+Import History Dialog
+Downloads the full message history of chosen chats into the local cache
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw, GLib
+import asyncio
+import threading
+from typing import List, Optional
+
+from ..db.models import ChatRecord
+
+
+class ImportHistoryDialog(Adw.Dialog):
+    """Pick chats and import their complete history, with progress and resume."""
+
+    def __init__(self, window, chats: List[ChatRecord]):
+        super().__init__()
+
+        self.window = window
+        self.chat_service = window.chat_service
+        self.config_manager = window.config_manager
+        # Chats kept out of the cache have nothing to import into
+        self.chats = [chat for chat in chats if not window.chat_service.is_chat_excluded(chat.guid)]
+        # Chat GUID -> check button, for the chats offered
+        self.check_buttons = {}
+        # Set when the dialog closes or Stop is clicked; the import resumes next time
+        self.stop_requested = False
+        self.importing = False
+
+        self.set_title("Import Full History")
+        self.set_content_width(460)
+        self.set_content_height(560)
+
+        self.setup_ui()
+        self.connect("closed", self.on_closed)
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        toolbar_view = Adw.ToolbarView()
+
+        header_bar = Adw.HeaderBar()
+        header_bar.set_show_end_title_buttons(False)
+        header_bar.set_show_start_title_buttons(False)
+
+        self.cancel_button = Gtk.Button()
+        self.cancel_button.set_label("Cancel")
+        self.cancel_button.connect("clicked", lambda b: self.close())
+        header_bar.pack_start(self.cancel_button)
+
+        self.import_button = Gtk.Button()
+        self.import_button.set_label("Import")
+        self.import_button.add_css_class("suggested-action")
+        self.import_button.connect("clicked", self.on_import_clicked)
+        header_bar.pack_end(self.import_button)
+
+        toolbar_view.add_top_bar(header_bar)
+
+        self.stack = Gtk.Stack()
+        self.stack.set_transition_type(Gtk.StackTransitionType.CROSSFADE)
+        self.stack.add_named(self.create_selection_page(), "select")
+        self.stack.add_named(self.create_progress_page(), "progress")
+
+        toolbar_view.set_content(self.stack)
+        self.set_child(toolbar_view)
+        self.update_import_button()
+
+    def create_selection_page(self) -> Gtk.Widget:
+        """Create the list of chats to import."""
+        page = Adw.PreferencesPage()
+
+        group = Adw.PreferencesGroup()
+        group.set_title("Conversations")
+        group.set_description(
+            "Download every message of the chosen conversations so they can be read and "
+            "searched offline. Large chats can take a while; a stopped import resumes where it left off."
+        )
+
+        select_all_button = Gtk.Button()
+        select_all_button.set_label("Select All")
+        select_all_button.add_css_class("flat")
+        select_all_button.set_valign(Gtk.Align.CENTER)
+        select_all_button.connect("clicked", self.on_select_all_clicked)
+        group.set_header_suffix(select_all_button)
+
+        for chat in self.chats:
+            row = Adw.ActionRow()
+            row.set_use_markup(False)
+            row.set_title(chat.display_title)
+            row.set_subtitle(self.describe_import_state(chat))
+
+            check_button = Gtk.CheckButton()
+            check_button.set_valign(Gtk.Align.CENTER)
+            check_button.connect("toggled", lambda button: self.update_import_button())
+            row.add_prefix(check_button)
+            row.set_activatable_widget(check_button)
+
+            self.check_buttons[chat.guid] = check_button
+            group.add(row)
+
+        page.add(group)
+        return page
+
+    def create_progress_page(self) -> Gtk.Widget:
+        """Create the page shown while importing."""
+        self.status_page = Adw.StatusPage()
+        self.status_page.set_icon_name("folder-download-symbolic")
+        self.status_page.set_title("Importing History")
+
+        progress_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=12)
+        progress_box.set_halign(Gtk.Align.CENTER)
+
+        self.progress_bar = Gtk.ProgressBar()
+        self.progress_bar.set_size_request(300, -1)
+        progress_box.append(self.progress_bar)
+
+        self.progress_label = Gtk.Label()
+        self.progress_label.add_css_class("dim-label")
+        self.progress_label.set_wrap(True)
+        self.progress_label.set_justify(Gtk.Justification.CENTER)
+        progress_box.append(self.progress_label)
+
+        self.stop_button = Gtk.Button()
+        self.stop_button.set_label("Stop")
+        self.stop_button.add_css_class("pill")
+        self.stop_button.set_halign(Gtk.Align.CENTER)
+        self.stop_button.connect("clicked", self.on_stop_clicked)
+        progress_box.append(self.stop_button)
+
+        self.status_page.set_child(progress_box)
+        return self.status_page
+
+    def describe_import_state(self, chat: ChatRecord) -> str:
+        """Describe how much of a chat's history was imported before."""
+        imported = 0
+        complete = True
+        for chat_guid in chat.guids:
+            offset, chat_complete = self.chat_service.get_history_import_state(chat_guid)
+            imported += offset
+            complete = complete and chat_complete
+
+        if complete:
+            return f"Imported ({imported} messages)"
+        if imported:
+            return f"Partly imported ({imported} messages), will resume"
+        return "Not imported"
+
+    def get_selected_chats(self) -> List[ChatRecord]:
+        """Get the chats whose boxes are ticked."""
+        return [chat for chat in self.chats if self.check_buttons[chat.guid].get_active()]
+
+    def update_import_button(self):
+        """Only allow importing once a chat is chosen."""
+        self.import_button.set_sensitive(bool(self.get_selected_chats()))
+
+    def on_select_all_clicked(self, button):
+        """Tick every chat, or untick them all if they already are."""
+        select = not all(check.get_active() for check in self.check_buttons.values())
+        for check_button in self.check_buttons.values():
+            check_button.set_active(select)
+
+    def on_import_clicked(self, button):
+        """Start importing the chosen chats."""
+        config = self.config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            self.window.show_toast("No server configuration found")
+            return
+
+        chats = self.get_selected_chats()
+        self.importing = True
+        self.stop_requested = False
+        self.import_button.set_visible(False)
+        self.cancel_button.set_label("Close")
+        self.stack.set_visible_child_name("progress")
+
+        def run_async():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            error = None
+            try:
+                loop.run_until_complete(self.import_chats(config['url'], config['password'], chats))
+            except Exception as e:
+                error = str(e)
+            loop.close()
+            GLib.idle_add(self.on_import_finished, error)
+
+        threading.Thread(target=run_async, daemon=True).start()
+
+    async def import_chats(self, server_url: str, password: str, chats: List[ChatRecord]):
+        """Import each chosen chat in turn, reporting progress on the main loop."""
+        chat_guids = [(chat, chat_guid) for chat in chats for chat_guid in chat.guids]
+
+        for index, (chat, chat_guid) in enumerate(chat_guids):
+            def on_progress(imported: int, total: Optional[int], index=index, chat=chat):
+                GLib.idle_add(self.update_progress, chat, index, len(chat_guids), imported, total)
+
+            await self.chat_service.import_chat_history(
+                server_url, password, chat_guid, on_progress, lambda: self.stop_requested
+            )
+            if self.stop_requested:
+                return
+
+    def update_progress(self, chat: ChatRecord, index: int, count: int,
+                        imported: int, total: Optional[int]):
+        """Show progress through the current chat and the whole import."""
+        chat_fraction = min(imported / total, 1.0) if total else 0.0
+        self.progress_bar.set_fraction((index + chat_fraction) / count)
+
+        if total:
+            self.progress_label.set_text(f"{chat.display_title}\n{imported} of {total} messages")
+        else:
+            self.progress_label.set_text(f"{chat.display_title}\n{imported} messages")
+        return False
+
+    def on_stop_clicked(self, button):
+        """Stop after the current page; the import resumes next time."""
+        self.stop_requested = True
+        self.stop_button.set_sensitive(False)
+        self.progress_label.set_text("Stopping…")
+
+    def on_import_finished(self, error: Optional[str]):
+        """Show how the import ended."""
+        self.importing = False
+        self.stop_button.set_visible(False)
+
+        if error:
+            self.status_page.set_icon_name("dialog-error-symbolic")
+            self.status_page.set_title("Import Interrupted")
+            self.progress_label.set_text(f"{error}\nImport again to resume.")
+        elif self.stop_requested:
+            self.status_page.set_title("Import Paused")
+            self.progress_label.set_text("Import again to resume where it stopped.")
+        else:
+            self.progress_bar.set_fraction(1.0)
+            self.status_page.set_icon_name("emblem-ok-symbolic")
+            self.status_page.set_title("Import Complete")
+            self.progress_label.set_text("The full history of these conversations is available offline.")
+
+        if self.window.current_chat:
+            self.window.refresh_current_chat_messages()
+        return False
+
+    def on_closed(self, dialog):
+        """Stop a running import when the dialog closes."""
+        if self.importing:
+            self.stop_requested = True
@@ -195,6 +195,7 @@ class LoginWindow(Adw.ApplicationWindow):
         
         # Save configuration and connect
         self.get_application().config_manager.set_server_config(url, password)
+        self.get_application().get_chat_service().log_activity('login', f"Logged in to {url}")
         self.show_toast("Configuration saved successfully!")
         
         # Notify application of successful login
@@ -6,33 +6,144 @@ The primary application window that displays chats and messages
 import gi
 gi.require_version('Gtk', '4.0')
 gi.require_version('Adw', '1')
+gi.require_version('GdkPixbuf', '2.0')
 
-from gi.repository import Gtk, Adw, GLib, Gio, GObject, Gdk
+from gi.repository import Gtk, Adw, GLib, Gio, GObject, Gdk, GdkPixbuf
 import asyncio
 import threading
+import math
 import os
-from datetime import datetime
-from pathlib import Path
-from ..api.client import BlueBubblesClient, BlueBubblesAPIError
-from ..db.models import ChatRecord
+import shutil
+import tempfile
+import time
+from datetime import date, datetime, timedelta
+from typing import Callable, List, Optional, Tuple
+from urllib.parse import urlparse
+from ..api.client import BlueBubblesClient, BlueBubblesAPIError, BlueBubblesUploadTooLargeError
+from ..api.events import Disconnected, MessageSendError, NewMessage, TypingIndicator
+from ..db.models import MEDIA_MIME_PREFIXES, ChatRecord, MediaItem
+from ..services.chat_service import SendResult
+from ..services.feedback import MESSAGE_RECEIVED, MESSAGE_SENT
+from ..services.portals import take_screenshot
+from ..services.voice_recorder import GST_AVAILABLE
+from ..services.pdf_renderer import POPPLER_AVAILABLE, load_pdf, render_page
+from ..services.transcript_export import export_chat
+from ..utils.attachment_safety import describe_file_type, is_risky, needs_open_confirmation
+from ..utils.balloons import describe_balloon, is_drawing_balloon
+from ..utils.chat_merge import normalize_address, participant_key, pick_send_chat, service_order
+from ..utils.features import get_missing_reason, is_feature_enabled
+from ..utils.file_names import safe_file_name
+from ..utils.links import URL_PATTERN, MessageLink, linkify
+from ..utils.message_recall import get_recallable_messages
+from ..utils.message_split import get_length, get_length_limit, preview_part, split_message
+from ..utils.otp import find_chat_code, find_incoming_code
+from ..utils.outbox import send_with_retries
+from ..utils.participant_colors import is_group_chat_guid, participant_css_class
+from ..utils.participants import Participant, build_contact_card, summarize_chat
+from ..utils.paste import is_long_paste, paste_file_name
+from ..utils.search_query import (
+    FILTER_KEYS, AttachmentHit, SearchHit, SearchQuery, has_filter, make_snippet, parse_search_query,
+    remove_filter, search_words
+)
+from ..utils.sms_segments import count_segments, describe_count
+from ..utils.snooze import describe_snooze, describe_time_ahead, get_snooze_options
+from ..utils.sync_progress import SyncProgress
+from ..utils.time_format import format_day, format_time
 from .new_chat_dialog import NewChatDialog
+from .start_page import StartPage
+from .quick_switcher import QuickSwitcherDialog
+from .chat_details_dialog import ChatDetailsDialog
+from .attachment_preview_dialog import AttachmentPreviewDialog, texture_to_pixbuf
+from .voice_memo_dialog import VoiceMemoDialog
+from .media_viewer_dialog import MediaViewerDialog
+from .pdf_viewer_dialog import PdfViewerDialog
+from .export_chat_dialog import ExportChatDialog
+from .import_history_dialog import ImportHistoryDialog
+from .share_contact_dialog import ShareContactDialog
+from .undo_toast import create_undo_toast
+
+# Horizontal speed (pixels per second) a bubble swipe needs to start a reply
+SWIPE_REPLY_VELOCITY = 400
+
+# Milliseconds a message stays highlighted after jumping to it from a reply's quote
+QUOTE_HIGHLIGHT_MS = 2000
+
+# Milliseconds after the last key press before type-to-search starts over
+TYPE_AHEAD_TIMEOUT = 1000
+
+# Milliseconds after the last change to the chat list search before cached messages are searched
+MESSAGE_SEARCH_DELAY = 300
+
+# Chat list views in the workspace rail: (id, name, icon)
+CHAT_VIEWS = [
+    ('all', "All Chats", "view-list-symbolic"),
+    ('unread', "Unread", "mail-unread-symbolic"),
+    ('starred', "Starred", "starred-symbolic"),
+    ('archived', "Archived", "package-x-generic-symbolic"),
+]
+
+# Chat list search filters: key -> (chip label, tooltip)
+SEARCH_CHIPS = {
+    'from': ("From", "Messages from a person, or from:me"),
+    'in': ("In", "Conversations with a name containing this"),
+    'has': ("Attachment", "Messages with attachments, or has:image, video, audio or pdf"),
+    'before': ("Before", "Messages sent before a date (YYYY-MM-DD)"),
+    'after': ("After", "Messages sent on or after a date (YYYY-MM-DD)"),
+}
+
+# Tabs of the chat list search results: (name, title)
+SEARCH_RESULT_TABS = [
+    ('messages', "Messages"),
+    ('media', "Media"),
+    ('files', "Files"),
+]
+
+# Release notes of a BlueBubbles server version
+SERVER_RELEASE_NOTES_URL = "https://github.com/BlueBubblesApp/bluebubbles-server/releases/tag/v{version}"
 
 class MainWindow(Adw.ApplicationWindow):
     """Main application window."""
     
+    # The content header names the open chat, so conversations skip their own title area
+    SHOW_CHAT_TITLE_AREA = False
+    
     def __init__(self, application):
         super().__init__(application=application)
         
         self.set_title("BlueBubbles")
-        self.set_default_size(1200, 800)
+        width, height, maximized = application.config_manager.get_window_state()
+        self.set_default_size(width, height)
+        self.set_maximized(maximized)
         
         # Store reference to config manager and chat service
         self.config_manager = application.config_manager
         self.chat_service = application.get_chat_service()
         
-        # Store chat data
-        self.chats = []
+        # Store chat data; the chat list itself lives in the shared model
         self.current_chat = None
+        self.pinned_guids = []
+        # Link opened before the chat list finished loading
+        self.pending_link = None
+        # Merged conversation GUID -> GUID of the chat new messages are sent to
+        self.send_services = {}
+        # GUIDs of chats where someone is typing, from live events
+        self.typing_chats = set()
+        # Participant set -> stacked avatar texture of group chats without an icon
+        self.group_avatar_textures = {}
+        # Label the chat list is filtered to, or None for all chats
+        self.label_filter = None
+        # Chat list view picked in the workspace rail, and the chats the archived view lists
+        self.chat_view = 'all'
+        self.archived_chats = []
+        # Parsed chat list search, and the chats with messages matching its filters
+        self.search_query = SearchQuery()
+        self.search_chat_guids = set()
+        # Timer for the debounced message search, and the number of the latest one
+        # so results of searches overtaken while running are dropped
+        self.message_search_timeout_id = None
+        self.message_search_generation = 0
+        # Set while the filter chips are updated to match the search text
+        self.syncing_search_chips = False
         
         # Typing indicator state
         self.typing_timeout_id = None
@@ -40,10 +151,10 @@ class MainWindow(Adw.ApplicationWindow):
         
         # Connect to window destroy signal for cleanup
         self.connect("destroy", self.on_window_destroy)
+        self.connect("close-request", self.on_close_request)
         
         # Build UI
         self.setup_ui()
-        self.load_styles()
         
         # Load data
         self.load_server_info()
@@ -52,24 +163,6 @@ class MainWindow(Adw.ApplicationWindow):
         # Start background message checking
         self.start_message_monitoring()
     
-    def load_styles(self):
-        """Load custom CSS styles for this window."""
-        try:
-            css_provider = Gtk.CssProvider()
-            css_path = Path(__file__).parent / 'styles.css'
-            css_provider.load_from_path(str(css_path))
-            
-            # Apply to this window's display
-            display = self.get_display()
-            if display:
-                Gtk.StyleContext.add_provider_for_display(
-                    display,
-                    css_provider,
-                    Gtk.STYLE_PROVIDER_PRIORITY_APPLICATION
-                )
-        except Exception as e:
-            pass  # Silently handle CSS loading errors
-    
     def load_image_from_data(self, image_data: bytes, size: int = 40) -> Gtk.Image:
         """Load image data into a Gtk.Image widget."""
         try:
@@ -98,21 +191,107 @@ class MainWindow(Adw.ApplicationWindow):
         # Create main content area with toast overlay
         content = Adw.ToastOverlay()
         
-        # Create a toolbar view to properly handle the header bar
-        toolbar_view = Adw.ToolbarView()
+        # Each pane has its own toolbar view and header, so the split view
+        # gives both panes a drag region and puts window controls where they belong
+        sidebar_toolbar_view = Adw.ToolbarView()
+        sidebar_header = Adw.HeaderBar()
         
-        # Create header bar
-        header_bar = Adw.HeaderBar()
+        new_chat_button = Gtk.Button()
+        new_chat_button.set_icon_name("list-add-symbolic")
+        new_chat_button.set_tooltip_text("New Chat")
+        new_chat_button.connect("clicked", self.on_new_chat_clicked)
+        sidebar_header.pack_start(new_chat_button)
+        
+        # Search field for filtering the chat list
+        self.search_entry = Gtk.SearchEntry()
+        self.search_entry.set_placeholder_text("Search chats")
+        self.search_entry.set_hexpand(True)
+        self.search_entry.connect("search-changed", self.on_search_changed)
+        sidebar_header.set_title_widget(self.search_entry)
+        self.search_focus = Gtk.EventControllerFocus()
+        self.search_focus.connect("enter", lambda c: self.update_search_chips_revealed())
+        self.search_focus.connect("leave", lambda c: self.update_search_chips_revealed())
+        self.search_entry.add_controller(self.search_focus)
         
         # Add menu button
         menu_button = Gtk.MenuButton()
         menu_button.set_child(Gtk.Image.new_from_icon_name("open-menu-symbolic"))
         menu_button.set_menu_model(self.create_menu())
-        header_bar.pack_end(menu_button)
-        
-        toolbar_view.add_top_bar(header_bar)
-        content.set_child(toolbar_view)
-        self.set_content(content)
+        sidebar_header.pack_end(menu_button)
+        
+        sidebar_toolbar_view.add_top_bar(sidebar_header)
+        
+        content_toolbar_view = Adw.ToolbarView()
+        content_header = Adw.HeaderBar()
+        
+        # Open chat's avatar and title, with who's in it (or the connection status) under it
+        title_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=8)
+        self.content_avatar = Gtk.Image()
+        self.content_avatar.set_pixel_size(28)
+        self.content_avatar.add_css_class("circular")
+        title_box.append(self.content_avatar)
+        self.content_title = Adw.WindowTitle()
+        title_box.append(self.content_title)
+        content_header.set_title_widget(title_box)
+        self.content_avatar_guid = None
+        
+        # Conversation actions, shown while a chat is open
+        self.details_button = Gtk.Button()
+        self.details_button.set_icon_name("view-more-symbolic")
+        self.details_button.set_tooltip_text("Chat details")
+        self.details_button.connect("clicked", lambda button: self.show_chat_details(self.current_chat.guid))
+        content_header.pack_end(self.details_button)
+        
+        self.call_link_button = Gtk.Button()
+        self.call_link_button.set_icon_name("camera-video-symbolic")
+        self.call_link_button.set_tooltip_text("Share a FaceTime Link")
+        self.call_link_button.connect("clicked", self.on_call_link_clicked)
+        content_header.pack_end(self.call_link_button)
+        
+        self.find_in_chat_button = Gtk.Button()
+        self.find_in_chat_button.set_icon_name("system-search-symbolic")
+        self.find_in_chat_button.set_tooltip_text("Search in Conversation")
+        self.find_in_chat_button.set_action_name("win.find-in-chat")
+        content_header.pack_end(self.find_in_chat_button)
+        
+        # Compact window toggle for the open conversation
+        compact_button = Gtk.ToggleButton()
+        compact_button.set_icon_name("view-restore-symbolic")
+        compact_button.set_tooltip_text("Compact Window")
+        compact_button.set_action_name("win.compact-mode")
+        content_header.pack_end(compact_button)
+        
+        content_toolbar_view.add_top_bar(content_header)
+        
+        # Shown while the server is rate limiting us, instead of error toasts
+        self.busy_banner = Adw.Banner()
+        self.busy_banner.set_title("Server busy, retrying…")
+        content_toolbar_view.add_top_bar(self.busy_banner)
+        self.busy_banner_timeout_id = None
+        
+        # Shown while the server can't be reached, e.g. during a restart, counting down to the next try
+        self.connection_banner = Adw.Banner()
+        content_toolbar_view.add_top_bar(self.connection_banner)
+        self.reconnect_at = None
+        self.reconnect_countdown_id = None
+        
+        # Shown while the server's iMessage account is signed out, since sends fail
+        self.account_banner = Adw.Banner()
+        self.account_banner.set_title("iMessage is signed out on your server, so messages can't be sent")
+        self.account_banner.add_css_class("error")
+        content_toolbar_view.add_top_bar(self.account_banner)
+        
+        # Shown when a newer server release is out
+        self.update_banner = Adw.Banner()
+        self.update_banner.set_button_label("Release Notes")
+        self.update_banner.connect("button-clicked", self.on_release_notes_clicked)
+        content_toolbar_view.add_top_bar(self.update_banner)
+        
+        # Shown during long syncs, so a first sync doesn't look like a frozen app
+        self.sync_progress_bar = Gtk.ProgressBar()
+        self.sync_progress_bar.add_css_class("osd")
+        self.sync_progress_bar.set_visible(False)
+        content_toolbar_view.add_top_bar(self.sync_progress_bar)
         
         # Create split view for chat list and messages
         self.split_view = Adw.NavigationSplitView()
@@ -125,53 +304,475 @@ class MainWindow(Adw.ApplicationWindow):
         # Create main sidebar container
         sidebar_container = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=0)
         
+        # Search filter chips, shown while searching
+        self.search_chips_revealer = Gtk.Revealer()
+        search_chips_bar = Gtk.ScrolledWindow()
+        search_chips_bar.set_policy(Gtk.PolicyType.AUTOMATIC, Gtk.PolicyType.NEVER)
+        search_chips_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=6)
+        search_chips_box.set_margin_start(12)
+        search_chips_box.set_margin_end(12)
+        search_chips_box.set_margin_top(6)
+        search_chips_box.set_margin_bottom(6)
+        self.search_chips = {}
+        for key in FILTER_KEYS:
+            label, tooltip = SEARCH_CHIPS[key]
+            chip = Gtk.ToggleButton(label=label)
+            chip.set_tooltip_text(tooltip)
+            chip.add_css_class("pill")
+            # Leaves the focus in the search field, so the chips stay up
+            chip.set_focus_on_click(False)
+            chip.connect("toggled", self.on_search_chip_toggled, key)
+            search_chips_box.append(chip)
+            self.search_chips[key] = chip
+        search_chips_bar.set_child(search_chips_box)
+        self.search_chips_revealer.set_child(search_chips_bar)
+        sidebar_container.append(self.search_chips_revealer)
+        
+        # Label filters, hidden until a label exists
+        self.label_filter_bar = Gtk.ScrolledWindow()
+        self.label_filter_bar.set_policy(Gtk.PolicyType.AUTOMATIC, Gtk.PolicyType.NEVER)
+        self.label_filter_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=6)
+        self.label_filter_box.set_margin_start(12)
+        self.label_filter_box.set_margin_end(12)
+        self.label_filter_box.set_margin_top(6)
+        self.label_filter_box.set_margin_bottom(6)
+        self.label_filter_bar.set_child(self.label_filter_box)
+        sidebar_container.append(self.label_filter_bar)
+        self.refresh_label_filters()
+        
         # Chat list in scrolled window
         sidebar_content = Gtk.ScrolledWindow()
         sidebar_content.set_vexpand(True)
         self.chat_list = Gtk.ListBox()
         self.chat_list.add_css_class("navigation-sidebar")
+        # Arrow keys move the selection, which loads the chat
         self.chat_list.connect("row-selected", self.on_chat_selected)
-        sidebar_content.set_child(self.chat_list)
-        sidebar_container.append(sidebar_content)
+        self.chat_list.connect("row-activated", self.on_chat_activated)
+        
+        # Typing with the list focused jumps to the first matching chat
+        self.type_ahead_text = ""
+        self.type_ahead_timeout_id = None
+        type_ahead_controller = Gtk.EventControllerKey()
+        type_ahead_controller.connect("key-pressed", self.on_chat_list_key_pressed)
+        self.chat_list.add_controller(type_ahead_controller)
+        self.chat_list.set_filter_func(self.filter_chat_row)
+        
+        # Shown when the view, label filter or search leaves nothing to list
+        no_chats_label = Gtk.Label(label="No conversations")
+        no_chats_label.set_margin_top(24)
+        no_chats_label.set_margin_bottom(24)
+        no_chats_label.add_css_class("dim-label")
+        self.chat_list.set_placeholder(no_chats_label)
+        
+        # Messages, media and files found by a search, listed under the chats
+        self.search_results_stack = Gtk.Stack()
+        self.search_results_stack.set_vhomogeneous(False)
+        self.search_results_lists = {}
+        for name, title in SEARCH_RESULT_TABS:
+            results_list = Gtk.ListBox()
+            results_list.add_css_class("navigation-sidebar")
+            results_list.connect("row-activated", self.on_search_result_activated)
+            self.search_results_stack.add_titled(results_list, name, title)
+            self.search_results_lists[name] = results_list
+        
+        search_results_switcher = Gtk.StackSwitcher()
+        search_results_switcher.set_stack(self.search_results_stack)
+        search_results_switcher.set_margin_start(12)
+        search_results_switcher.set_margin_end(12)
+        search_results_switcher.set_margin_top(12)
+        
+        self.search_results_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=6)
+        self.search_results_box.append(search_results_switcher)
+        self.search_results_box.append(self.search_results_stack)
+        self.search_results_box.set_visible(False)
+        
+        sidebar_lists = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=0)
+        sidebar_lists.append(self.chat_list)
+        sidebar_lists.append(self.search_results_box)
+        sidebar_content.set_child(sidebar_lists)
+        
+        # Stack switching between the chat list and its empty/error states
+        self.sidebar_stack = Gtk.Stack()
+        self.sidebar_stack.set_vexpand(True)
+        self.sidebar_stack.add_named(sidebar_content, "list")
+        
+        self.sidebar_loading_page = self.create_status_page(
+            "emblem-synchronizing-symbolic", "Syncing Chats",
+            "Fetching your conversations from the server…"
+        )
+        spinner = Gtk.Spinner()
+        spinner.set_size_request(32, 32)
+        spinner.start()
+        self.sidebar_loading_page.set_child(spinner)
+        self.sidebar_stack.add_named(self.sidebar_loading_page, "loading")
+        
+        empty_page = self.create_status_page(
+            "mail-message-new-symbolic", "No Chats Yet",
+            "Start a new conversation to see it here",
+            "New Chat", self.on_new_chat_clicked
+        )
+        self.sidebar_stack.add_named(empty_page, "empty")
         
-        # New chat button at the bottom
-        new_chat_button = Gtk.Button()
-        new_chat_button.set_label("New Chat")
-        new_chat_button.set_margin_start(12)
-        new_chat_button.set_margin_end(12)
-        new_chat_button.set_margin_top(6)
-        new_chat_button.set_margin_bottom(12)
-        new_chat_button.add_css_class("suggested-action")
-        new_chat_button.set_icon_name("list-add-symbolic")
-        new_chat_button.connect("clicked", self.on_new_chat_clicked)
-        sidebar_container.append(new_chat_button)
+        self.sidebar_error_page = self.create_status_page(
+            "network-offline-symbolic", "Server Unreachable",
+            "Couldn't connect to your BlueBubbles server",
+            "Retry", lambda button: self.refresh_chat_list()
+        )
+        self.sidebar_stack.add_named(self.sidebar_error_page, "error")
         
-        sidebar_page.set_child(sidebar_container)
+        sidebar_container.append(self.sidebar_stack)
+        
+        # Workspace rail beside the chat list: server, views and settings
+        sidebar_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=0)
+        sidebar_box.append(self.create_workspace_rail())
+        sidebar_box.append(Gtk.Separator(orientation=Gtk.Orientation.VERTICAL))
+        sidebar_container.set_hexpand(True)
+        sidebar_box.append(sidebar_container)
+        
+        sidebar_toolbar_view.set_content(sidebar_box)
+        sidebar_page.set_child(sidebar_toolbar_view)
         self.split_view.set_sidebar(sidebar_page)
         
         # Content area (messages)
-        content_page = Adw.NavigationPage()
-        content_page.set_title("Messages")
+        self.content_page = Adw.NavigationPage()
+        self.content_page.set_title("Messages")
         
         # Placeholder content
         self.content_stack = Gtk.Stack()
         
-        # Placeholder when no chat is selected
-        placeholder = Adw.StatusPage()
-        placeholder.set_icon_name("mail-send-symbolic")
-        placeholder.set_title("Welcome to BlueBubbles")
-        placeholder.set_description("Select a chat to start messaging")
-        self.content_stack.add_named(placeholder, "placeholder")
+        # Start page when no chat is selected
+        self.start_page = StartPage(self)
+        self.content_stack.add_named(self.start_page, "placeholder")
         
         # Chat view (will be created when a chat is selected)
         self.content_stack.set_visible_child_name("placeholder")
         
-        content_page.set_child(self.content_stack)
-        self.split_view.set_content(content_page)
+        content_toolbar_view.set_content(self.content_stack)
+        self.content_page.set_child(content_toolbar_view)
+        self.split_view.set_content(self.content_page)
         
-        # Set the split view as the content of the toolbar view
-        toolbar_view.set_content(self.split_view)
+        content.set_child(self.split_view)
+        self.set_content(content)
         self.toast_overlay = content
+        self.update_content_title()
+        
+        # Narrow windows show one pane at a time; the collapsed split view
+        # handles swiping back from the conversation to the chat list
+        narrow_breakpoint = Adw.Breakpoint.new(Adw.BreakpointCondition.parse("max-width: 600sp"))
+        narrow_breakpoint.add_setter(self.split_view, "collapsed", True)
+        self.add_breakpoint(narrow_breakpoint)
+        
+        # Window actions
+        search_action = Gio.SimpleAction.new('search', None)
+        search_action.connect('activate', self.on_search_action)
+        self.add_action(search_action)
+        
+        find_in_chat_action = Gio.SimpleAction.new('find-in-chat', None)
+        find_in_chat_action.connect('activate', self.on_find_in_chat_action)
+        self.add_action(find_in_chat_action)
+        
+        quick_switch_action = Gio.SimpleAction.new('quick-switch', None)
+        quick_switch_action.connect('activate', self.on_quick_switch_action)
+        self.add_action(quick_switch_action)
+        
+        # Ctrl+1..9 open the pinned chats in pin order
+        open_pinned_action = Gio.SimpleAction.new('open-pinned', GLib.VariantType.new('i'))
+        open_pinned_action.connect('activate', self.on_open_pinned_action)
+        self.add_action(open_pinned_action)
+        
+        screenshot_action = Gio.SimpleAction.new('capture-screenshot', None)
+        screenshot_action.connect('activate', self.on_capture_screenshot_action)
+        self.add_action(screenshot_action)
+        
+        voice_memo_action = Gio.SimpleAction.new('record-voice-memo', None)
+        voice_memo_action.connect('activate', self.on_record_voice_memo_action)
+        self.add_action(voice_memo_action)
+        
+        share_contact_action = Gio.SimpleAction.new('share-contact', None)
+        share_contact_action.connect('activate', self.on_share_contact_action)
+        self.add_action(share_contact_action)
+        
+        import_history_action = Gio.SimpleAction.new('import-history', None)
+        import_history_action.connect('activate', self.on_import_history_action)
+        self.add_action(import_history_action)
+        
+        self.compact_mode_action = Gio.SimpleAction.new_stateful(
+            'compact-mode', None, GLib.Variant('b', False)
+        )
+        self.compact_mode_action.set_enabled(False)  # Enabled once a chat is open
+        self.compact_mode_action.connect('change-state', self.on_compact_mode_changed)
+        self.add_action(self.compact_mode_action)
+        
+        # Sync progress is shown on the progress bar and chat list loading page built above
+        self.chat_service.add_sync_progress_callback(self.on_sync_progress)
+    
+    def on_compact_mode_changed(self, action, value):
+        """Open or close the compact window for the open conversation."""
+        if value.get_boolean() and self.current_chat:
+            self.get_application().open_compact_window(self.current_chat)
+        else:
+            self.get_application().close_compact_window()
+    
+    def update_content_title(self):
+        """Show the open chat and the connection status in the content header."""
+        chat = self.current_chat
+        title = chat.display_title if chat else "BlueBubbles"
+        self.content_title.set_title(title)
+        self.content_page.set_title(title if chat else "Messages")
+        
+        model = self.chat_service.model
+        if not model.connected:
+            self.content_title.set_subtitle("Reconnecting…")
+        elif chat and self.typing_chats.intersection(chat.guids):
+            self.content_title.set_subtitle("Typing…")
+        elif chat:
+            self.content_title.set_subtitle(summarize_chat(chat, self.chat_service.get_contact_name))
+        elif model.server_version:
+            self.content_title.set_subtitle(f"Connected · Server v{model.server_version}")
+        else:
+            self.content_title.set_subtitle("Connecting…")
+        
+        for button in (self.details_button, self.call_link_button, self.find_in_chat_button):
+            button.set_visible(chat is not None)
+        self.content_avatar.set_visible(chat is not None)
+        if chat and chat.guid != self.content_avatar_guid:
+            self.content_avatar.set_from_icon_name("group-symbolic" if chat.is_group_chat else "person-symbolic")
+            self.load_chat_avatar_async(self.content_avatar, chat)
+        self.content_avatar_guid = chat.guid if chat else None
+    
+    def on_call_link_clicked(self, button):
+        """Create a FaceTime link and put it in the composer, ready to send."""
+        config = self.get_application().config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            return
+        button.set_sensitive(False)
+        
+        def on_link(link: Optional[str]):
+            button.set_sensitive(True)
+            if not link:
+                self.show_toast("Couldn't create a FaceTime link. It needs the Private API on your server.")
+                return False
+            
+            chat_view = self.content_stack.get_visible_child()
+            input_area = getattr(chat_view, 'input_area', None)
+            current_text = input_area.message_entry.get_text() if input_area else ""
+            self.set_composer_text(f"{current_text} {link}".strip())
+            return False
+        
+        def run_async():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            try:
+                link = loop.run_until_complete(
+                    self.chat_service.create_facetime_link(config['url'], config['password'])
+                )
+            finally:
+                loop.close()
+            GLib.idle_add(on_link, link)
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def on_find_in_chat_action(self, action, param):
+        """Open the search bar of the visible conversation."""
+        chat_view = self.content_stack.get_visible_child()
+        search_bar = getattr(chat_view, 'search_bar', None)
+        if search_bar:
+            search_bar.set_search_mode(True)
+            chat_view.search_entry.grab_focus()
+    
+    def show_chat_search(self, query: str):
+        """Open the visible conversation's search bar with a search filled in."""
+        chat_view = self.content_stack.get_visible_child()
+        search_bar = getattr(chat_view, 'search_bar', None)
+        if search_bar:
+            search_bar.set_search_mode(True)
+            if chat_view.search_entry.get_text() == query:
+                # Already searched; look again for the message being opened
+                self.on_chat_search_changed(chat_view.search_entry, chat_view, self.current_chat)
+            else:
+                chat_view.search_entry.set_text(query)
+    
+    def on_chat_search_changed(self, entry, chat_view, chat: ChatRecord):
+        """Find the messages of a conversation matching the search, starting at the newest."""
+        query = entry.get_text().strip()
+        chat_view.search_matches = self.chat_service.search_conversation_messages(chat, query) if query else []
+        # Start at the message opened from a search result, if it's among the matches
+        target = chat_view.search_target
+        chat_view.search_target = None
+        chat_view.search_index = next(
+            (index for index, (guid, date_created) in enumerate(chat_view.search_matches) if guid == target), 0
+        )
+        self.show_chat_search_match(chat_view, chat)
+    
+    def step_chat_search(self, chat_view, chat: ChatRecord, step: int):
+        """Move to an older (1) or newer (-1) search match, wrapping around."""
+        if not chat_view.search_matches:
+            return
+        chat_view.search_index = (chat_view.search_index + step) % len(chat_view.search_matches)
+        self.show_chat_search_match(chat_view, chat)
+    
+    def show_chat_search_match(self, chat_view, chat: ChatRecord):
+        """Scroll to and highlight the current search match, loading older messages to reach it."""
+        if chat_view.search_highlight:
+            chat_view.search_highlight.remove_css_class("search-match")
+            chat_view.search_highlight = None
+        
+        matches = chat_view.search_matches
+        if not matches:
+            has_query = bool(chat_view.search_entry.get_text().strip())
+            chat_view.search_count.set_text("No matches" if has_query else "")
+            return
+        chat_view.search_count.set_text(f"{chat_view.search_index + 1} of {len(matches)}")
+        
+        message_guid, date_created = matches[chat_view.search_index]
+        widget = self.find_message_widget(chat_view.messages_box, message_guid)
+        if widget is None:
+            # Older than what's shown: load the messages leading up to it
+            messages = self.chat_service.get_cached_conversation_messages(chat, limit=50, before=date_created + 1)
+            self.display_messages(messages, chat_view.messages_box)
+            widget = self.find_message_widget(chat_view.messages_box, message_guid)
+        
+        if widget:
+            widget.add_css_class("search-match")
+            chat_view.search_highlight = widget
+            GLib.idle_add(self.scroll_to_widget, chat_view.messages_area, widget)
+    
+    def find_message_widget(self, messages_box: Gtk.Box, message_guid: str) -> Optional[Gtk.Widget]:
+        """Find the widget showing a message in a messages box."""
+        child = messages_box.get_first_child()
+        while child:
+            if getattr(child, 'message_guid', None) == message_guid:
+                return child
+            child = child.get_next_sibling()
+        return None
+    
+    def set_compact_mode_state(self, active: bool):
+        """Reflect whether the compact window is open in the header toggle."""
+        self.compact_mode_action.set_state(GLib.Variant('b', active))
+    
+    def create_status_page(self, icon_name: str, title: str, description: str = None,
+                           button_label: str = None, callback=None) -> Adw.StatusPage:
+        """Create a status page for empty, loading and error states."""
+        page = Adw.StatusPage()
+        page.set_icon_name(icon_name)
+        page.set_title(title)
+        if description:
+            page.set_description(description)
+        page.set_vexpand(True)
+        
+        if button_label and callback:
+            button = Gtk.Button()
+            button.set_label(button_label)
+            button.set_halign(Gtk.Align.CENTER)
+            button.add_css_class("pill")
+            button.add_css_class("suggested-action")
+            button.connect("clicked", callback)
+            page.set_child(button)
+        
+        return page
+    
+    def set_sidebar_state(self, state: str, error_message: str = None):
+        """Switch the sidebar between 'list', 'loading', 'empty' and 'error' states."""
+        if state == "error" and error_message:
+            self.sidebar_error_page.set_description(error_message)
+        self.sidebar_stack.set_visible_child_name(state)
+    
+    def create_workspace_rail(self) -> Gtk.Widget:
+        """Create the slim icon rail with the server switcher, chat list views and settings."""
+        rail = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=6)
+        rail.set_margin_start(6)
+        rail.set_margin_end(6)
+        rail.set_margin_top(6)
+        rail.set_margin_bottom(12)
+        rail.add_css_class("workspace-rail")
+        
+        self.server_button = Gtk.MenuButton()
+        self.server_button.set_icon_name("network-server-symbolic")
+        self.server_button.add_css_class("flat")
+        self.server_button.add_css_class("circular")
+        self.server_button.set_create_popup_func(self.on_server_menu_opened)
+        rail.append(self.server_button)
+        rail.append(Gtk.Separator(orientation=Gtk.Orientation.HORIZONTAL))
+        
+        group = None
+        self.chat_view_buttons = {}
+        for view, name, icon in CHAT_VIEWS:
+            button = Gtk.ToggleButton()
+            button.set_icon_name(icon)
+            button.set_tooltip_text(name)
+            button.add_css_class("flat")
+            if group:
+                button.set_group(group)
+            else:
+                group = button
+            button.set_active(view == self.chat_view)
+            button.connect("toggled", self.on_chat_view_toggled, view)
+            self.chat_view_buttons[view] = button
+            rail.append(button)
+        
+        spacer = Gtk.Box()
+        spacer.set_vexpand(True)
+        rail.append(spacer)
+        
+        settings_button = Gtk.Button.new_from_icon_name("emblem-system-symbolic")
+        settings_button.set_tooltip_text("Preferences")
+        settings_button.add_css_class("flat")
+        settings_button.set_action_name("app.preferences")
+        rail.append(settings_button)
+        
+        self.update_server_button()
+        return rail
+    
+    def update_server_button(self):
+        """Name the connected server in the rail's server button."""
+        url = self.config_manager.get_server_config()['url']
+        self.server_button.set_tooltip_text((urlparse(url).hostname if url else None) or "No server")
+    
+    def on_server_menu_opened(self, menu_button):
+        """List the configured server profiles, with a way to change the server."""
+        popover = Gtk.Popover()
+        menu_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=4)
+        menu_box.set_margin_start(8)
+        menu_box.set_margin_end(8)
+        menu_box.set_margin_top(8)
+        menu_box.set_margin_bottom(8)
+        
+        # Only one server is configured at a time, so the list is just the current one
+        profile_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=8)
+        profile_box.set_margin_start(8)
+        profile_box.set_margin_end(8)
+        profile_label = Gtk.Label(label=self.server_button.get_tooltip_text())
+        profile_label.set_hexpand(True)
+        profile_label.set_halign(Gtk.Align.START)
+        profile_box.append(profile_label)
+        profile_box.append(Gtk.Image.new_from_icon_name("object-select-symbolic"))
+        menu_box.append(profile_box)
+        
+        settings_button = Gtk.Button(label="Server Settings…")
+        settings_button.add_css_class("flat")
+        settings_button.set_action_name("app.preferences")
+        settings_button.connect("clicked", lambda button: popover.popdown())
+        menu_box.append(settings_button)
+        
+        popover.set_child(menu_box)
+        menu_button.set_popover(popover)
+    
+    def on_chat_view_toggled(self, button: Gtk.ToggleButton, view: str):
+        """Switch the chat list to another view."""
+        if not button.get_active() or view == self.chat_view:
+            return
+        
+        rebuild = 'archived' in (view, self.chat_view)
+        self.chat_view = view
+        if view == 'archived':
+            self.archived_chats = self.chat_service.get_cached_chats(archived=True)
+        
+        if rebuild:
+            self.populate_chat_list()
+            if self.current_chat:
+                self.select_chat(self.current_chat.guid)
+        else:
+            self.chat_list.invalidate_filter()
     
     def create_menu(self):
         """Create the application menu."""
@@ -179,6 +780,9 @@ class MainWindow(Adw.ApplicationWindow):
         
         # Preferences item
         menu.append("Preferences", "app.preferences")
+        menu.append("Welcome Tour", "app.show-tour")
+        menu.append("Server Checklist", "app.server-checklist")
+        menu.append("Import Full History…", "win.import-history")
         
         # Separator
         menu.append_section(None, Gio.Menu())
@@ -207,6 +811,362 @@ class MainWindow(Adw.ApplicationWindow):
         dialog = NewChatDialog(self, self.config_manager)
         dialog.present(self)
     
+    def on_search_action(self, action, param):
+        """Focus the chat search field."""
+        self.search_entry.grab_focus()
+    
+    def on_quick_switch_action(self, action, param):
+        """Open the quick switcher."""
+        dialog = QuickSwitcherDialog(self)
+        dialog.present(self)
+    
+    def on_open_pinned_action(self, action, param):
+        """Open the nth pinned chat, counting from 1."""
+        listed = {chat.guid for chat in self.chats}
+        pinned = [guid for guid in self.pinned_guids if guid in listed]
+        number = param.get_int32()
+        if 1 <= number <= len(pinned):
+            self.select_chat(pinned[number - 1])
+    
+    def send_quick_message(self, chat: ChatRecord, message_text: str):
+        """Send a message to a chat without opening it."""
+        config = self.get_application().config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            self.show_toast("No server configuration")
+            return
+        
+        def on_sent(sent: int, result: SendResult):
+            if sent:
+                self.show_toast(f"Sent to {chat.display_title}")
+            else:
+                self.show_toast(f"Couldn't send to {chat.display_title}: {result.error}")
+            return False
+        
+        self.queue_send(chat.guid, [message_text], None, on_sent)
+    
+    def on_search_changed(self, entry):
+        """Re-filter the chat list when the search text changes."""
+        text = entry.get_text()
+        self.search_query = parse_search_query(text)
+        
+        self.syncing_search_chips = True
+        for key, chip in self.search_chips.items():
+            chip.set_active(has_filter(text, key))
+        self.syncing_search_chips = False
+        self.update_search_chips_revealed()
+        self.chat_list.invalidate_filter()
+        
+        # Searching the cached messages scans them all, so it waits for typing to pause
+        if self.message_search_timeout_id:
+            GLib.source_remove(self.message_search_timeout_id)
+        self.message_search_timeout_id = GLib.timeout_add(MESSAGE_SEARCH_DELAY, self.start_message_search)
+    
+    def start_message_search(self):
+        """Search the cached messages for the chat list search in the background."""
+        self.message_search_timeout_id = None
+        self.message_search_generation += 1
+        generation = self.message_search_generation
+        query = self.search_query
+        
+        def search():
+            chat_guids, hits, attachment_hits = set(), [], []
+            try:
+                if query.filters_messages:
+                    chat_guids = self.chat_service.search_message_chats(query)
+                if query.text or query.filters_messages:
+                    hits = self.chat_service.search_all_messages(query)
+                    attachment_hits = self.chat_service.search_all_attachments(query)
+            except Exception:
+                pass  # Nothing is listed if the cache can't be searched
+            GLib.idle_add(self.show_message_search_results, generation, query, chat_guids, hits, attachment_hits)
+        
+        threading.Thread(target=search, daemon=True).start()
+        return False
+    
+    def show_message_search_results(self, generation: int, query: SearchQuery, chat_guids: set,
+                                    hits: List[SearchHit], attachment_hits: List[AttachmentHit]):
+        """Filter the chat list by a finished message search and list its results, unless a newer one started."""
+        if generation != self.message_search_generation:
+            return False
+        
+        self.search_chat_guids = chat_guids
+        if query.filters_messages:
+            self.chat_list.invalidate_filter()
+        self.update_search_results(query, hits, attachment_hits)
+        return False
+    
+    def update_search_results(self, query: SearchQuery, hits: List[SearchHit], attachment_hits: List[AttachmentHit]):
+        """List the messages matching the chat list search, best first, and their media and files."""
+        for results_list in self.search_results_lists.values():
+            while True:
+                row = results_list.get_first_child()
+                if row is None:
+                    break
+                results_list.remove(row)
+        
+        # Only conversations that are listed can be opened
+        chats = {chat_guid: chat for chat in self.chats for chat_guid in chat.guids}
+        
+        def get_listed_chat(chat_guid: str) -> Optional[ChatRecord]:
+            chat = chats.get(chat_guid)
+            if chat is None or (query.chat and query.chat not in chat.display_title.lower()):
+                return None
+            return chat
+        
+        words = search_words(query.text)
+        for hit in hits:
+            chat = get_listed_chat(hit.chat_guid)
+            if chat:
+                self.search_results_lists['messages'].append(self.create_search_result_row(hit, chat, words))
+        for hit in attachment_hits:
+            chat = get_listed_chat(hit.chat_guid)
+            if chat:
+                tab = 'media' if (hit.mime_type or '').startswith(MEDIA_MIME_PREFIXES) else 'files'
+                self.search_results_lists[tab].append(self.create_attachment_result_row(hit, chat))
+        
+        # Tabs with nothing found are hidden
+        has_results = False
+        for results_list in self.search_results_lists.values():
+            has_rows = results_list.get_first_child() is not None
+            self.search_results_stack.get_page(results_list).set_visible(has_rows)
+            has_results = has_results or has_rows
+        self.search_results_box.set_visible(has_results)
+    
+    def create_search_result_row(self, hit: SearchHit, chat: ChatRecord, words: List[str]) -> Gtk.ListBoxRow:
+        """Create a search result row: the conversation, when, and the message with the words highlighted."""
+        row = Gtk.ListBoxRow()
+        row.chat_guid = hit.chat_guid
+        row.message_guid = hit.guid
+        
+        box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=2)
+        box.set_margin_start(12)
+        box.set_margin_end(12)
+        box.set_margin_top(6)
+        box.set_margin_bottom(6)
+        
+        title_row = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=8)
+        title_label = Gtk.Label(label=chat.display_title)
+        title_label.set_halign(Gtk.Align.START)
+        title_label.set_hexpand(True)
+        title_label.set_ellipsize(3)  # ELLIPSIZE_END
+        title_label.add_css_class("heading")
+        title_row.append(title_label)
+        
+        time_label = Gtk.Label(label=self.format_message_time(datetime.fromtimestamp(hit.date_created / 1000)))
+        time_label.add_css_class("dim-label")
+        time_label.add_css_class("caption")
+        title_row.append(time_label)
+        box.append(title_row)
+        
+        snippet_label = Gtk.Label()
+        if hit.text:
+            snippet_label.set_markup("".join(
+                f"<b>{GLib.markup_escape_text(part)}</b>" if is_match else GLib.markup_escape_text(part)
+                for part, is_match in make_snippet(hit.text, words)
+            ))
+        else:
+            snippet_label.set_text("Attachment")
+        snippet_label.set_halign(Gtk.Align.START)
+        snippet_label.set_xalign(0)
+        snippet_label.set_wrap(True)
+        snippet_label.set_wrap_mode(2)  # WORD_CHAR
+        snippet_label.set_lines(2)
+        snippet_label.set_ellipsize(3)  # ELLIPSIZE_END
+        snippet_label.add_css_class("search-snippet")
+        box.append(snippet_label)
+        
+        row.set_child(box)
+        return row
+    
+    def create_attachment_result_row(self, hit: AttachmentHit, chat: ChatRecord) -> Gtk.ListBoxRow:
+        """Create a media or file search result row: the file's name and type, its conversation and when."""
+        row = Gtk.ListBoxRow()
+        row.chat_guid = hit.chat_guid
+        row.message_guid = hit.message_guid
+        
+        box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=12)
+        box.set_margin_start(12)
+        box.set_margin_end(12)
+        box.set_margin_top(6)
+        box.set_margin_bottom(6)
+        
+        content_type = Gio.content_type_from_mime_type(hit.mime_type or '') or 'application/octet-stream'
+        icon = Gtk.Image.new_from_gicon(Gio.content_type_get_symbolic_icon(content_type))
+        icon.set_icon_size(Gtk.IconSize.LARGE)
+        box.append(icon)
+        
+        text_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=2)
+        text_box.set_hexpand(True)
+        name_label = Gtk.Label(label=hit.file_name or "Attachment")
+        name_label.set_halign(Gtk.Align.START)
+        name_label.set_ellipsize(2)  # ELLIPSIZE_MIDDLE, keeping the extension
+        name_label.set_tooltip_text(hit.file_name)
+        text_box.append(name_label)
+        
+        time_text = self.format_message_time(datetime.fromtimestamp(hit.date_created / 1000))
+        details_label = Gtk.Label(label=f"{chat.display_title} · {time_text}")
+        details_label.set_halign(Gtk.Align.START)
+        details_label.set_ellipsize(3)  # ELLIPSIZE_END
+        details_label.add_css_class("dim-label")
+        details_label.add_css_class("caption")
+        text_box.append(details_label)
+        box.append(text_box)
+        
+        row.set_child(box)
+        return row
+    
+    def on_search_result_activated(self, list_box, row):
+        """Open the conversation of a search result at the message found."""
+        if not self.select_chat(row.chat_guid):
+            return
+        chat_view = self.content_stack.get_visible_child()
+        if hasattr(chat_view, 'search_target'):
+            chat_view.search_target = row.message_guid
+            self.show_chat_search(remove_filter(self.search_entry.get_text(), 'in'))
+    
+    def update_search_chips_revealed(self):
+        """Show the filter chips while the search field is focused or has text."""
+        focused = self.search_focus.contains_focus()
+        self.search_chips_revealer.set_reveal_child(focused or bool(self.search_entry.get_text()))
+    
+    def on_search_chip_toggled(self, chip: Gtk.ToggleButton, key: str):
+        """Add a filter to the search, ready for its value, or take it out again."""
+        if self.syncing_search_chips:
+            return
+        
+        text = self.search_entry.get_text()
+        if not chip.get_active():
+            self.search_entry.set_text(remove_filter(text, key))
+            return
+        
+        prefix = text.rstrip() + ' ' if text.strip() else ''
+        value = {'has': 'attachment', 'before': date.today().isoformat(),
+                 'after': date.today().isoformat()}.get(key, '')
+        self.search_entry.set_text(f"{prefix}{key}:{value}")
+        self.search_entry.grab_focus()
+        if key in ('before', 'after'):
+            # Today's date, selected so typing replaces it
+            end = len(self.search_entry.get_text())
+            self.search_entry.select_region(end - len(value), end)
+        else:
+            self.search_entry.set_position(-1)
+    
+    def filter_chat_row(self, row) -> bool:
+        """Return True if a chat row matches the current search text and label filter."""
+        chat = getattr(row, 'chat', None)
+        if chat is None:
+            return True
+        
+        if self.label_filter and self.label_filter not in self.chat_service.get_chat_labels(chat):
+            return False
+        
+        # The open chat stays listed after reading it in the unread view
+        is_open = self.current_chat is not None and chat.guid == self.current_chat.guid
+        if self.chat_view == 'unread' and not is_open:
+            if not chat.unread_count and not self.chat_service.is_marked_unread(chat):
+                return False
+        if self.chat_view == 'starred' and not self.chat_service.is_starred(chat):
+            return False
+        
+        # Snoozed chats only turn up in searches
+        query = self.search_query
+        if not query:
+            return self.chat_service.get_snooze_until(chat) is None
+        
+        if query.chat and query.chat not in chat.display_title.lower():
+            return False
+        # Filters look through the cached messages, text included
+        if query.filters_messages:
+            return any(chat_guid in self.search_chat_guids for chat_guid in chat.guids)
+        
+        text = query.text.lower()
+        if not text or text in chat.display_title.lower():
+            return True
+        return bool(chat.last_message_text and text in chat.last_message_text.lower())
+    
+    def refresh_label_filters(self):
+        """Rebuild the label filter buttons above the chat list."""
+        child = self.label_filter_box.get_first_child()
+        while child:
+            next_child = child.get_next_sibling()
+            self.label_filter_box.remove(child)
+            child = next_child
+        
+        labels = [name for name, policy in self.chat_service.get_labels()]
+        if self.label_filter not in labels:
+            self.label_filter = None
+        self.label_filter_bar.set_visible(bool(labels))
+        
+        all_button = Gtk.ToggleButton(label="All")
+        all_button.add_css_class("pill")
+        all_button.set_active(self.label_filter is None)
+        all_button.connect("toggled", self.on_label_filter_toggled, None)
+        self.label_filter_box.append(all_button)
+        
+        for label in labels:
+            button = Gtk.ToggleButton(label=label)
+            button.add_css_class("pill")
+            button.set_group(all_button)
+            button.set_active(label == self.label_filter)
+            button.connect("toggled", self.on_label_filter_toggled, label)
+            self.label_filter_box.append(button)
+    
+    def on_label_filter_toggled(self, button: Gtk.ToggleButton, label):
+        """Show only chats with the chosen label."""
+        if not button.get_active():
+            return
+        self.label_filter = label
+        self.chat_list.invalidate_filter()
+    
+    def on_chat_activated(self, list_box, row):
+        """Enter or a click on a chat moves on to writing in it."""
+        # Also brings the open chat back on narrow layouts
+        self.split_view.set_show_content(True)
+        self.focus_composer()
+    
+    def on_chat_list_key_pressed(self, controller, keyval, keycode, state):
+        """Collect typed characters and select the first chat whose title matches."""
+        if state & (Gdk.ModifierType.CONTROL_MASK | Gdk.ModifierType.ALT_MASK):
+            return False
+        
+        if keyval == Gdk.KEY_BackSpace and self.type_ahead_text:
+            self.type_ahead_text = self.type_ahead_text[:-1]
+        else:
+            char = chr(Gdk.keyval_to_unicode(keyval))
+            # Space only counts inside a search, so it can still activate rows
+            if not char.isprintable() or char == '\0' or (char == ' ' and not self.type_ahead_text):
+                return False
+            self.type_ahead_text += char.lower()
+        
+        if self.type_ahead_timeout_id:
+            GLib.source_remove(self.type_ahead_timeout_id)
+        self.type_ahead_timeout_id = GLib.timeout_add(TYPE_AHEAD_TIMEOUT, self.reset_type_ahead)
+        
+        if not self.type_ahead_text:
+            return True
+        
+        # Prefer titles starting with the text, then titles containing it
+        rows = []
+        child = self.chat_list.get_first_child()
+        while child:
+            if hasattr(child, 'chat') and child.get_child_visible():
+                rows.append(child)
+            child = child.get_next_sibling()
+        
+        titles = [(row, row.chat.display_title.lower()) for row in rows]
+        match = (next((row for row, title in titles if title.startswith(self.type_ahead_text)), None)
+                 or next((row for row, title in titles if self.type_ahead_text in title), None))
+        if match:
+            self.chat_list.select_row(match)
+            match.grab_focus()
+        return True
+    
+    def reset_type_ahead(self):
+        """Forget the typed search text."""
+        self.type_ahead_text = ""
+        self.type_ahead_timeout_id = None
+        return False
+    
     def refresh_chat_list(self):
         """Refresh the chat list."""
         self.load_chats(force_refresh=True)
@@ -233,15 +1193,37 @@ class MainWindow(Adw.ApplicationWindow):
                         self.load_chats_async(config['url'], config['password'])
                     )
                 
+                # Contacts can resolve senders that are still shown as raw numbers
+                if loop.run_until_complete(
+                    self.chat_service.sync_contacts(config['url'], config['password'])
+                ):
+                    def update_ui():
+                        for window in self.get_application().get_windows():
+                            if hasattr(window, 'refresh_sender_names'):
+                                window.refresh_sender_names()
+                    
+                    GLib.idle_add(update_ui)
+                
                 loop.close()
             except Exception as e:
+                error_msg = str(e)
                 def show_error():
-                    self.show_toast(f"Failed to load chats: {str(e)}")
+                    self.show_chat_list_error(error_msg)
                 GLib.idle_add(show_error)
         
+        if not self.chats:
+            self.set_sidebar_state("loading")
+        
         thread = threading.Thread(target=run_async, daemon=True)
         thread.start()
     
+    def show_chat_list_error(self, error_message: str):
+        """Show a chat loading error, keeping any chats we already display."""
+        if self.chats:
+            self.show_toast(f"Failed to load chats: {error_message}")
+        else:
+            self.set_sidebar_state("error", error_message)
+    
     async def load_chats_async(self, server_url: str, password: str):
         """Load chats, preferring cache but falling back to server."""
         try:
@@ -277,8 +1259,9 @@ class MainWindow(Adw.ApplicationWindow):
                 await self.load_chats_from_server_async(server_url, password)
                 
         except Exception as e:
+            error_msg = str(e)
             def show_error():
-                self.show_toast(f"Failed to load chats: {str(e)}")
+                self.show_chat_list_error(error_msg)
             
             GLib.idle_add(show_error)
     
@@ -289,22 +1272,34 @@ class MainWindow(Adw.ApplicationWindow):
                 server_url, password, limit=100
             )
             
+            sync_error = self.chat_service.last_sync_error
+            
             def update_ui():
                 self.chats = chats
                 self.populate_chat_list()
-                if chats:
-                    self.show_toast(f"Loaded {len(chats)} chats")
-                else:
-                    self.show_toast("No chats found")
+                if sync_error and not chats:
+                    self.set_sidebar_state("error", sync_error)
+                elif sync_error:
+                    self.show_toast(f"Failed to sync chats: {sync_error}")
             
             GLib.idle_add(update_ui)
             
         except Exception as e:
+            error_msg = str(e)
             def show_error():
-                self.show_toast(f"Failed to load chats from server: {str(e)}")
+                self.show_chat_list_error(error_msg)
             
             GLib.idle_add(show_error)
     
+    @property
+    def chats(self) -> List[ChatRecord]:
+        """Get the conversations in the chat list, from the model shared by every window."""
+        return self.chat_service.model.chats
+    
+    @chats.setter
+    def chats(self, chats: List[ChatRecord]):
+        self.chat_service.model.set_chats(chats)
+    
     def populate_chat_list(self):
         """Populate the chat list with chat data."""
         # Clear existing items
@@ -314,23 +1309,416 @@ class MainWindow(Adw.ApplicationWindow):
                 break
             self.chat_list.remove(row)
         
+        # Pinned chats go first, in pin order
+        self.pinned_guids = self.chat_service.get_pinned_chat_guids()
+        pinned = [chat for guid in self.pinned_guids
+                  for chat in self.chats if chat.guid == guid]
+        others = [chat for chat in self.chats if chat.guid not in self.pinned_guids]
+        self.chats = pinned + others
+        
         # Add chat items
-        for chat in self.chats:
+        for chat in self.archived_chats if self.chat_view == 'archived' else self.chats:
             chat_row = self.create_chat_row(chat)
             self.chat_list.append(chat_row)
+        
+        self.set_sidebar_state("list" if self.chats else "empty")
+        self.start_page.set_chats(pinned, others)
+        
+        if self.pending_link:
+            link, self.pending_link = self.pending_link, None
+            self.open_message_link(link)
+    
+    def select_chat(self, chat_guid: str) -> bool:
+        """Select a chat in the sidebar by GUID. Returns False if it isn't listed."""
+        child = self.chat_list.get_first_child()
+        while child:
+            if hasattr(child, 'chat') and chat_guid in child.chat.guids:
+                self.chat_list.select_row(child)
+                self.split_view.set_show_content(True)
+                return True
+            child = child.get_next_sibling()
+        return False
+    
+    def open_message_link(self, link: MessageLink):
+        """Open the conversation a deep link points at, with its draft in the composer."""
+        if self.sidebar_stack.get_visible_child_name() == "loading":
+            # Opened at startup; wait for the chat list
+            self.pending_link = link
+            return
+        
+        if link.chat_guid:
+            if not self.select_chat(link.chat_guid):
+                self.show_toast("Conversation not found")
+                return
+        else:
+            addresses = [self.chat_service.resolve_address(address) for address in link.addresses]
+            chat = self.find_chat_with_addresses(addresses)
+            if not chat:
+                NewChatDialog(self, self.config_manager, ", ".join(addresses), link.body).present(self)
+                return
+            self.select_chat(chat.guid)
+            
+            # sms: and imessage: links pick the service of a merged conversation for this session
+            service_chat = next((c for c in [chat] + chat.linked_chats if c.service == link.service), None)
+            chat_view = self.content_stack.get_visible_child()
+            if service_chat and link.service in getattr(chat_view, 'service_choices', []):
+                self.send_services[chat.guid] = service_chat.guid
+                self.show_send_service(chat_view, link.service)
+        
+        if link.body:
+            self.set_composer_text(link.body)
+        else:
+            self.focus_composer()
+    
+    def find_chat_with_addresses(self, addresses) -> Optional[ChatRecord]:
+        """Find the listed chat whose participants are exactly these addresses."""
+        wanted = frozenset(normalize_address(address) for address in addresses)
+        for chat in self.chats:
+            if any(participant_key(c) == wanted for c in [chat] + chat.linked_chats):
+                return chat
+        return None
+    
+    def select_chat_when_listed(self, chat_guid: str):
+        """Select a chat once the next chat list refresh includes it."""
+        self.pending_link = MessageLink(chat_guid=chat_guid)
+        self.refresh_chat_list()
+    
+    def set_composer_text(self, text: str):
+        """Put a draft into the composer of the visible chat."""
+        chat_view = self.content_stack.get_visible_child()
+        input_area = getattr(chat_view, 'input_area', None)
+        if input_area:
+            input_area.message_entry.set_text(text)
+            input_area.message_entry.grab_focus_without_selecting()
+            input_area.message_entry.set_position(-1)
+    
+    def on_chat_row_right_click(self, gesture, n_press, x, y, chat):
+        """Show the context menu for a chat row."""
+        self.show_chat_row_menu(gesture.get_widget(), chat)
+    
+    def show_chat_row_menu(self, row, chat: ChatRecord):
+        """Show context menu with chat list actions."""
+        popover = Gtk.Popover()
+        popover.set_parent(row)
+        popover.set_position(Gtk.PositionType.BOTTOM)
+        
+        menu_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=4)
+        menu_box.set_margin_start(8)
+        menu_box.set_margin_end(8)
+        menu_box.set_margin_top(8)
+        menu_box.set_margin_bottom(8)
+        
+        pin_button = Gtk.Button()
+        if chat.guid in self.pinned_guids:
+            pin_button.set_label("Unpin")
+        else:
+            pin_button.set_label("Pin")
+        pin_button.add_css_class("flat")
+        pin_button.connect("clicked", self.on_toggle_pin, chat, popover)
+        menu_box.append(pin_button)
+        
+        star_button = Gtk.Button()
+        star_button.set_label("Unstar" if self.chat_service.is_starred(chat) else "Star")
+        star_button.add_css_class("flat")
+        star_button.connect("clicked", self.on_toggle_star, chat, popover)
+        menu_box.append(star_button)
+        
+        snooze_button = Gtk.Button()
+        if self.chat_service.get_snooze_until(chat) is not None:
+            snooze_button.set_label("Unsnooze")
+            snooze_button.connect("clicked", self.on_unsnooze_chat, chat, popover)
+        else:
+            snooze_button.set_label("Snooze…")
+            snooze_button.connect("clicked", self.on_show_snooze_menu, row, chat, popover)
+        snooze_button.add_css_class("flat")
+        menu_box.append(snooze_button)
+        
+        new_window_button = Gtk.Button()
+        new_window_button.set_label("Open in New Window")
+        new_window_button.add_css_class("flat")
+        new_window_button.connect("clicked", self.on_open_chat_window, chat, popover)
+        menu_box.append(new_window_button)
+        
+        export_button = Gtk.Button()
+        export_button.set_label("Export Chat…")
+        export_button.add_css_class("flat")
+        export_button.connect("clicked", self.on_export_chat, chat, popover)
+        menu_box.append(export_button)
+        
+        popover.set_child(menu_box)
+        popover.popup()
+    
+    def on_toggle_pin(self, button, chat: ChatRecord, popover):
+        """Pin or unpin a chat."""
+        popover.popdown()
+        if chat.guid in self.pinned_guids:
+            self.chat_service.unpin_chat(chat.guid)
+        else:
+            self.chat_service.pin_chat(chat.guid)
+        self.populate_chat_list()
+        
+        # Keep the open conversation selected after the rebuild
+        if self.current_chat:
+            self.select_chat(self.current_chat.guid)
+    
+    def on_toggle_star(self, button, chat: ChatRecord, popover):
+        """Star or unstar a chat."""
+        popover.popdown()
+        self.chat_service.set_starred(chat, not self.chat_service.is_starred(chat))
+        self.update_chat_row(chat.guid)
+        self.chat_list.invalidate_filter()
+    
+    def on_show_snooze_menu(self, button, row, chat: ChatRecord, popover):
+        """Offer times to snooze a chat until."""
+        popover.popdown()
+        
+        snooze_popover = Gtk.Popover()
+        snooze_popover.set_parent(row)
+        snooze_popover.set_position(Gtk.PositionType.BOTTOM)
+        
+        menu_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=4)
+        menu_box.set_margin_start(8)
+        menu_box.set_margin_end(8)
+        menu_box.set_margin_top(8)
+        menu_box.set_margin_bottom(8)
+        
+        time_format = self.get_application().config_manager.get_time_format()
+        for label, until in get_snooze_options(datetime.now()):
+            option_button = Gtk.Button()
+            option_button.set_label(label)
+            option_button.set_tooltip_text(describe_snooze(until, time_format))
+            option_button.add_css_class("flat")
+            option_button.connect("clicked", self.on_snooze_chat, chat, until, snooze_popover)
+            menu_box.append(option_button)
+        
+        snooze_popover.set_child(menu_box)
+        snooze_popover.popup()
+    
+    def on_snooze_chat(self, button, chat: ChatRecord, until: datetime, popover):
+        """Hide a chat and silence it until the chosen time."""
+        popover.popdown()
+        self.chat_service.snooze_chat(chat, int(until.timestamp() * 1000))
+        self.update_chat_row(chat.guid)
+        self.chat_list.invalidate_filter()
+        time_format = self.get_application().config_manager.get_time_format()
+        self.show_toast(f"{chat.display_title}: {describe_snooze(until, time_format)}")
+    
+    def on_unsnooze_chat(self, button, chat: ChatRecord, popover):
+        """Bring a snoozed chat back to the list."""
+        popover.popdown()
+        self.chat_service.unsnooze_chat(chat)
+        self.update_chat_row(chat.guid)
+        self.chat_list.invalidate_filter()
+    
+    def on_snooze_woke(self, chat_guid: str):
+        """Called when a chat's snooze ends; brings it back marked unread."""
+        def update_ui():
+            self.update_chat_row(chat_guid)
+            self.chat_list.invalidate_filter()
+            chat = self.chat_service.get_chat_by_guid(chat_guid)
+            if chat:
+                self.show_toast(f"{chat.display_title} is back from snooze")
+            return False
+        
+        GLib.idle_add(update_ui)
+    
+    def on_open_chat_window(self, button, chat: ChatRecord, popover):
+        """Open a chat in its own window."""
+        popover.popdown()
+        self.get_application().open_chat_window(chat)
+    
+    def on_import_history_action(self, action, param):
+        """Offer to download the complete history of chosen chats."""
+        if not self.chats:
+            self.show_toast("No conversations to import yet")
+            return
+        ImportHistoryDialog(self, self.chats).present(self)
+    
+    def on_export_chat(self, button, chat: ChatRecord, popover):
+        """Open the export dialog for a chat."""
+        popover.popdown()
+        ExportChatDialog(self, chat).present(self)
+    
+    def export_chat_async(self, chat: ChatRecord, file_path: str, passphrase=None):
+        """Export the cached messages of a chat to a file."""
+        def run_export():
+            try:
+                # Cached messages come newest first
+                messages = self.chat_service.get_cached_conversation_messages(chat, limit=1000000)
+                export_chat(file_path, chat, list(reversed(messages)),
+                            self.get_application().config_manager.get_time_format(), passphrase)
+                GLib.idle_add(self.show_toast, f"Exported {chat.display_title}")
+            except Exception as e:
+                GLib.idle_add(self.show_toast, f"Failed to export chat: {str(e)}")
+        
+        # Key derivation for encrypted exports takes a moment
+        threading.Thread(target=run_export, daemon=True).start()
+    
+    def show_chat_details(self, chat_guid: str):
+        """Open the details dialog for a chat."""
+        chat = self.chat_service.get_chat_by_guid(chat_guid)
+        if chat:
+            dialog = ChatDetailsDialog(self, chat)
+            dialog.present(self)
+    
+    def set_chat_nickname(self, chat: ChatRecord, nickname: str):
+        """Set or clear the local nickname of a chat."""
+        self.chat_service.set_chat_nickname(chat.guid, nickname)
+        self.reload_chat(chat.guid)
+        self.show_toast("Nickname saved" if nickname else "Nickname cleared")
+    
+    def rename_group_chat_async(self, chat: ChatRecord, display_name: str):
+        """Rename a group chat on the server."""
+        config = self.get_application().config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            self.show_toast("No server configuration")
+            return
+        
+        def run_async():
+            try:
+                loop = asyncio.new_event_loop()
+                asyncio.set_event_loop(loop)
+                success = loop.run_until_complete(
+                    self.chat_service.rename_group_chat(
+                        config['url'], config['password'], chat.guid, display_name
+                    )
+                )
+                loop.close()
+                
+                def update_ui():
+                    if success:
+                        self.reload_chat(chat.guid)
+                        self.show_toast("Group renamed")
+                    else:
+                        self.show_toast("Failed to rename group")
+                
+                GLib.idle_add(update_ui)
+            except Exception as e:
+                GLib.idle_add(self.show_toast, f"Error renaming group: {str(e)}")
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def set_group_icon_async(self, chat: ChatRecord, file_path: str):
+        """Upload a new group photo and refresh the avatars showing it."""
+        config = self.get_application().config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            self.show_toast("No server configuration")
+            return
+        
+        def run_async():
+            try:
+                loop = asyncio.new_event_loop()
+                asyncio.set_event_loop(loop)
+                success = loop.run_until_complete(
+                    self.chat_service.set_group_icon(
+                        config['url'], config['password'], chat.guid, file_path
+                    )
+                )
+                loop.close()
+                
+                def update_ui():
+                    if success:
+                        self.reload_chat(chat.guid)
+                        self.show_toast("Group photo updated")
+                    else:
+                        self.show_toast("Failed to update group photo")
+                
+                GLib.idle_add(update_ui)
+            except Exception as e:
+                GLib.idle_add(self.show_toast, f"Error updating group photo: {str(e)}")
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def set_chat_excluded(self, chat: ChatRecord, excluded: bool):
+        """Keep every service of a conversation out of the local cache, or let it back in."""
+        for chat_guid in chat.guids:
+            self.chat_service.set_chat_excluded(chat_guid, excluded)
+        self.update_chat_row(chat.guid)
+    
+    def set_chat_labels(self, chat: ChatRecord, labels: list):
+        """Set a conversation's labels and re-filter the chat list."""
+        self.chat_service.set_chat_labels(chat, labels)
+        self.update_chat_row(chat.guid)
+        self.chat_list.invalidate_filter()
+    
+    def create_label(self, name: str) -> bool:
+        """Create a label, returning False if one with that name exists."""
+        if any(existing.lower() == name.lower() for existing, policy in self.chat_service.get_labels()):
+            return False
+        self.chat_service.create_label(name)
+        self.refresh_label_filters()
+        return True
+    
+    def delete_label(self, name: str):
+        """Delete a label, removing it from every chat."""
+        labelled = [chat for chat in self.chats if name in self.chat_service.get_chat_labels(chat)]
+        self.chat_service.delete_label(name)
+        self.refresh_label_filters()
+        self.chat_list.invalidate_filter()
+        for chat in labelled:
+            self.update_chat_row(chat.guid)
+    
+    def set_chat_hide_badge(self, chat: ChatRecord, hide: bool):
+        """Hide or show a chat's unread badge."""
+        self.chat_service.set_chat_hide_badge(chat.guid, hide)
+        self.update_chat_row(chat.guid)
+    
+    def update_chat_row(self, chat_guid: str):
+        """Rebuild a single sidebar row from the cache, keeping its position."""
+        updated_chat = self.chat_service.get_chat_by_guid(chat_guid)
+        if not updated_chat:
+            return False
+        
+        self.chats = [updated_chat if chat_guid in c.guids else c for c in self.chats]
+        
+        child = self.chat_list.get_first_child()
+        while child:
+            if hasattr(child, 'chat') and chat_guid in child.chat.guids:
+                was_selected = child.is_selected()
+                index = child.get_index()
+                self.chat_list.remove(child)
+                new_row = self.create_chat_row(updated_chat)
+                self.chat_list.insert(new_row, index)
+                if was_selected:
+                    self.chat_list.select_row(new_row)
+                break
+            child = child.get_next_sibling()
+        
+        return False  # Remove from idle queue
+    
+    def reload_chat(self, chat_guid: str):
+        """Reload a chat from the cache and refresh everywhere it is shown."""
+        updated_chat = self.chat_service.get_chat_by_guid(chat_guid)
+        if not updated_chat:
+            return
+        
+        self.chats = [updated_chat if c.guid == chat_guid else c for c in self.chats]
+        if self.current_chat and self.current_chat.guid == chat_guid:
+            self.current_chat = updated_chat
+        
+        self.populate_chat_list()
+        if self.current_chat:
+            self.select_chat(self.current_chat.guid)
+        
+        chat_view = self.content_stack.get_child_by_name(f"chat_{chat_guid}")
+        if chat_view and hasattr(chat_view, 'title_label'):
+            chat_view.title_label.set_text(updated_chat.display_title)
+            self.load_chat_avatar_async(chat_view.title_avatar, updated_chat)
+        self.update_content_title()
     
     def create_chat_row(self, chat: ChatRecord) -> Gtk.ListBoxRow:
         """Create a chat list row."""
         row = Gtk.ListBoxRow()
         # Store chat data as an attribute instead of using set_data
         row.chat = chat
+        style = self.get_application().config_manager.get_chat_list_style()
         
         # Main container
         main_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=12)
         main_box.set_margin_start(12)
         main_box.set_margin_end(12)
-        main_box.set_margin_top(8)
-        main_box.set_margin_bottom(8)
+        main_box.set_margin_top(style.row_margin)
+        main_box.set_margin_bottom(style.row_margin)
         
         # Avatar (will be loaded asynchronously)
         avatar = Gtk.Image()
@@ -339,15 +1727,16 @@ class MainWindow(Adw.ApplicationWindow):
         else:
             avatar.set_from_icon_name("person-symbolic")
         avatar.add_css_class("circular")
-        avatar.set_pixel_size(40)
+        avatar.set_pixel_size(style.avatar_size)
         main_box.append(avatar)
         
         # Load avatar asynchronously
         self.load_chat_avatar_async(avatar, chat)
         
         # Content area
-        content_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=4)
+        content_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=style.row_spacing)
         content_box.set_hexpand(True)
+        content_box.set_valign(Gtk.Align.CENTER)
         
         # Title and timestamp row
         title_row = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=8)
@@ -361,8 +1750,45 @@ class MainWindow(Adw.ApplicationWindow):
         title_label.add_css_class("heading")
         title_row.append(title_label)
         
+        # Pin indicator
+        if chat.guid in self.pinned_guids:
+            pin_icon = Gtk.Image.new_from_icon_name("view-pin-symbolic")
+            pin_icon.add_css_class("dim-label")
+            title_row.append(pin_icon)
+        
+        # Star indicator
+        if self.chat_service.is_starred(chat):
+            star_icon = Gtk.Image.new_from_icon_name("starred-symbolic")
+            star_icon.add_css_class("dim-label")
+            title_row.append(star_icon)
+        
+        # Unread badge
+        if chat.badge_count:
+            badge_label = Gtk.Label()
+            badge_label.set_text(str(chat.badge_count) if chat.badge_count < 100 else "99+")
+            badge_label.set_valign(Gtk.Align.CENTER)
+            badge_label.add_css_class("unread-badge")
+            title_row.append(badge_label)
+        elif self.chat_service.is_marked_unread(chat):
+            unread_dot = Gtk.Box()
+            unread_dot.set_valign(Gtk.Align.CENTER)
+            unread_dot.set_tooltip_text("Marked unread")
+            unread_dot.add_css_class("unread-dot")
+            title_row.append(unread_dot)
+        
+        # Warning when the last attempt to fetch this conversation's messages failed
+        sync_error = self.chat_service.get_chat_sync_error(chat)
+        if sync_error:
+            retry_button = Gtk.Button.new_from_icon_name("dialog-warning-symbolic")
+            retry_button.set_tooltip_text(f"Couldn't sync messages: {sync_error}\nClick to retry")
+            retry_button.set_valign(Gtk.Align.CENTER)
+            retry_button.add_css_class("flat")
+            retry_button.add_css_class("sync-warning")
+            retry_button.connect("clicked", lambda button: self.retry_chat_sync(button, chat))
+            title_row.append(retry_button)
+        
         # Timestamp
-        if chat.last_message_date:
+        if chat.last_message_date and style.show_timestamps:
             time_label = Gtk.Label()
             time_str = self.format_message_time(chat.last_message_datetime)
             time_label.set_text(time_str)
@@ -376,47 +1802,157 @@ class MainWindow(Adw.ApplicationWindow):
         if chat.last_message_text:
             preview_label = Gtk.Label()
             preview_text = chat.last_message_text
-            if len(preview_text) > 60:
-                preview_text = preview_text[:60] + "..."
+            if len(preview_text) > style.preview_length:
+                preview_text = preview_text[:style.preview_length] + "..."
             
             # Add sender info for group chats
             if chat.is_group_chat and chat.last_message_address and not chat.last_message_from_me:
-                sender = chat.last_message_address.split('@')[0]  # Simple name extraction
+                sender = self.get_sender_name(chat.last_message_address)
                 preview_text = f"{sender}: {preview_text}"
             elif chat.last_message_from_me:
                 preview_text = f"You: {preview_text}"
             
             preview_label.set_text(preview_text)
             preview_label.set_halign(Gtk.Align.START)
+            preview_label.set_xalign(0)
             preview_label.set_ellipsize(3)  # ELLIPSIZE_END
+            if style.preview_lines > 1:
+                preview_label.set_wrap(True)
+                preview_label.set_wrap_mode(2)  # WORD_CHAR
+                preview_label.set_lines(style.preview_lines)
             preview_label.add_css_class("dim-label")
             content_box.append(preview_label)
         
+        # When a snoozed chat found by searching comes back
+        snooze_until = self.chat_service.get_snooze_until(chat)
+        if snooze_until is not None:
+            snooze_label = Gtk.Label()
+            snooze_label.set_text(describe_snooze(
+                datetime.fromtimestamp(snooze_until / 1000),
+                self.get_application().config_manager.get_time_format()
+            ))
+            snooze_label.set_halign(Gtk.Align.START)
+            snooze_label.add_css_class("dim-label")
+            snooze_label.add_css_class("caption")
+            content_box.append(snooze_label)
+        
+        # Labels
+        labels = self.chat_service.get_chat_labels(chat)
+        if labels:
+            labels_label = Gtk.Label()
+            labels_label.set_text(" · ".join(labels))
+            labels_label.set_halign(Gtk.Align.START)
+            labels_label.set_ellipsize(3)  # ELLIPSIZE_END
+            labels_label.add_css_class("dim-label")
+            labels_label.add_css_class("caption")
+            content_box.append(labels_label)
+        
         main_box.append(content_box)
         row.set_child(main_box)
         
+        # Right-click gesture for the row context menu
+        right_click = Gtk.GestureClick()
+        right_click.set_button(3)  # Right mouse button
+        right_click.connect("pressed", self.on_chat_row_right_click, chat)
+        row.add_controller(right_click)
+        
+        # Middle-click opens the chat in its own window
+        middle_click = Gtk.GestureClick()
+        middle_click.set_button(2)  # Middle mouse button
+        middle_click.connect("pressed", lambda g, n, x, y: self.get_application().open_chat_window(chat))
+        row.add_controller(middle_click)
+        
+        # Pinned chats are reordered by dragging one onto another
+        if chat.guid in self.pinned_guids:
+            drag_source = Gtk.DragSource.new()
+            drag_source.set_actions(Gdk.DragAction.MOVE)
+            drag_source.set_content(Gdk.ContentProvider.new_for_value(chat.guid))
+            drag_source.connect("drag-begin", lambda source, drag: source.set_icon(
+                Gtk.WidgetPaintable.new(row), 0, 0))
+            row.add_controller(drag_source)
+            
+            drop_target = Gtk.DropTarget.new(GObject.TYPE_STRING, Gdk.DragAction.MOVE)
+            drop_target.connect("drop", self.on_pinned_chat_drop, chat)
+            row.add_controller(drop_target)
+        
         return row
     
+    def on_pinned_chat_drop(self, drop_target, value, x, y, chat: ChatRecord):
+        """Move the dragged pinned chat to where it was dropped."""
+        if value not in self.pinned_guids or value == chat.guid:
+            return False
+        self.chat_service.move_pinned_chat(value, self.pinned_guids.index(chat.guid))
+        self.populate_chat_list()
+        if self.current_chat:
+            self.select_chat(self.current_chat.guid)
+        return True
+    
+    def retry_chat_sync(self, button: Gtk.Button, chat: ChatRecord):
+        """Fetch a conversation's messages again after a failed sync."""
+        config = self.config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            return
+        
+        button.set_sensitive(False)
+        
+        def run_async():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            synced = loop.run_until_complete(
+                self.chat_service.retry_chat_sync(config['url'], config['password'], chat)
+            )
+            loop.close()
+            
+            def update_ui():
+                if synced:
+                    if self.current_chat and self.current_chat.guid == chat.guid:
+                        self.refresh_current_chat_messages()
+                else:
+                    button.set_sensitive(True)
+                    self.show_toast(f"Still couldn't sync {chat.display_title}")
+            
+            GLib.idle_add(update_ui)
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def on_chat_sync_state_changed(self, chat_guid: str):
+        """Show or clear a conversation's sync warning."""
+        GLib.idle_add(self.update_chat_row, chat_guid)
+    
     def format_message_time(self, dt: datetime) -> str:
         """Format message timestamp for display."""
-        if not dt:
-            return ""
-        
-        now = datetime.now()
-        diff = now - dt
-        
-        if diff.days == 0:
-            # Today - show time
-            return dt.strftime("%H:%M")
-        elif diff.days == 1:
-            # Yesterday
-            return "Yesterday"
-        elif diff.days < 7:
-            # This week - show day name
-            return dt.strftime("%A")
-        else:
-            # Older - show date
-            return dt.strftime("%m/%d/%y")
+        return format_time(dt, self.get_application().config_manager.get_time_format())
+    
+    def refresh_timestamps(self):
+        """Re-render the chat list and open conversation after a time format change."""
+        self.populate_chat_list()
+        if not self.current_chat:
+            return
+        
+        self.select_chat(self.current_chat.guid)
+        chat_view = self.content_stack.get_child_by_name(f"chat_{self.current_chat.guid}")
+        if chat_view and hasattr(chat_view, 'messages_box'):
+            self.load_chat_messages(self.current_chat, chat_view.messages_box,
+                                    getattr(chat_view, 'messages_area', None))
+    
+    def refresh_chat_list_style(self):
+        """Rebuild the chat list rows after a chat list appearance change."""
+        self.populate_chat_list()
+        if self.current_chat:
+            self.select_chat(self.current_chat.guid)
+    
+    def format_message_details(self, message) -> str:
+        """Describe when a message was sent, delivered and read, with full dates."""
+        time_format = self.get_application().config_manager.get_time_format()
+        lines = [
+            f"{'Sent' if message.is_from_me else 'Received'} "
+            f"{format_time(message.datetime_created, time_format, full=True)}"
+        ]
+        if message.is_from_me and message.date_delivered:
+            lines.append(f"Delivered {format_time(message.datetime_delivered, time_format, full=True)}")
+        if message.is_from_me and message.date_read:
+            lines.append(f"Read {format_time(message.datetime_read, time_format, full=True)}")
+        return "\n".join(lines)
     
     def get_message_receipt_status(self, message):
         """Get the read receipt status for a message. Returns (status_text, css_class)."""
@@ -441,9 +1977,66 @@ class MainWindow(Adw.ApplicationWindow):
         chat = getattr(row, 'chat', None)
         if chat:
             self.current_chat = chat
+            self.compact_mode_action.set_enabled(True)
             self.load_chat_view(chat)
-            # Mark chat as read when opened
-            self.mark_chat_read_async(chat.guid)
+            self.update_content_title()
+            self.split_view.set_show_content(True)
+            # Opened from a filtered search: step through its matches in the conversation
+            if self.search_query.filters_messages:
+                self.show_chat_search(remove_filter(self.search_entry.get_text(), 'in'))
+            # Mark chat as read when opened, on every service it is merged from.
+            # Unread counts are cleared locally either way.
+            if self.chat_service.sends_read_receipts(chat.guid):
+                for chat_guid in chat.guids:
+                    self.mark_chat_read_async(chat_guid)
+            marked_unread = self.chat_service.is_marked_unread(chat)
+            if marked_unread:
+                self.chat_service.clear_unread_marker(chat)
+            if chat.unread_count:
+                for chat_guid in chat.guids:
+                    self.chat_service.mark_chat_read_locally(chat_guid)
+            if chat.unread_count or marked_unread:
+                GLib.idle_add(self.update_chat_row, chat.guid)
+    
+    def get_send_chat_guid(self) -> str:
+        """Get the chat new messages go to; merged conversations send on the picked service."""
+        chat = self.current_chat
+        if chat.guid in self.send_services:
+            return self.send_services[chat.guid]
+        return pick_send_chat(chat, self.chat_service.get_send_service(chat.guid))
+    
+    def show_send_service(self, chat_view, service: Optional[str]):
+        """Show a service as the active choice without saving it as the chat's preference."""
+        chat_view.showing_send_service = True
+        chat_view.service_dropdown.set_selected(chat_view.service_choices.index(service))
+        chat_view.showing_send_service = False
+    
+    def on_send_service_selected(self, dropdown, pspec, chat_view, chat: ChatRecord):
+        """Save the service a merged conversation sends on: automatic, or always one service."""
+        if getattr(chat_view, 'showing_send_service', False):
+            return
+        service = chat_view.service_choices[dropdown.get_selected()]
+        self.chat_service.set_send_service(chat.guid, service)
+        self.send_services.pop(chat.guid, None)
+        self.update_sms_counter(chat_view)
+    
+    def update_sms_counter(self, chat_view):
+        """Show how many characters and texts the draft takes when it will go as SMS."""
+        text = chat_view.input_area.message_entry.get_text()
+        sending_sms = self.current_chat and self.get_send_chat_guid().split(';', 1)[0] == 'SMS'
+        if not text or not sending_sms:
+            chat_view.sms_counter.set_visible(False)
+            return
+        
+        count = count_segments(text)
+        chat_view.sms_counter.set_text(describe_count(count))
+        if count.unicode:
+            chat_view.sms_counter.set_tooltip_text(
+                f"Emoji and some characters send as Unicode, {count.per_segment} characters per text"
+            )
+        else:
+            chat_view.sms_counter.set_tooltip_text(f"{count.per_segment} characters per text")
+        chat_view.sms_counter.set_visible(True)
     
     def load_chat_view(self, chat: ChatRecord):
         """Load the chat view for the selected chat."""
@@ -454,6 +2047,7 @@ class MainWindow(Adw.ApplicationWindow):
         if existing_view:
             # print(f"🎯 Chat view already exists, switching to: {chat_view_name}")
             self.content_stack.set_visible_child_name(chat_view_name)
+            self.fetch_latest_messages_async(chat)
             return
         
         # print(f"🎯 Creating new chat view: {chat_view_name}")
@@ -469,6 +2063,12 @@ class MainWindow(Adw.ApplicationWindow):
         title_area.set_margin_bottom(8)
         title_area.add_css_class("chat-title-area")
         
+        title_avatar = Gtk.Image()
+        title_avatar.set_from_icon_name("group-symbolic" if chat.is_group_chat else "person-symbolic")
+        title_avatar.set_pixel_size(32)
+        title_avatar.add_css_class("circular")
+        title_area.append(title_avatar)
+        
         title_label = Gtk.Label()
         title_label.set_text(chat.display_title)
         title_label.set_hexpand(True)
@@ -476,8 +2076,61 @@ class MainWindow(Adw.ApplicationWindow):
         title_label.add_css_class("title-2")
         title_area.append(title_label)
         
+        details_button = Gtk.Button()
+        details_button.set_icon_name("view-more-symbolic")
+        details_button.set_tooltip_text("Chat details")
+        details_button.add_css_class("flat")
+        details_button.connect("clicked", lambda b: self.show_chat_details(chat.guid))
+        title_area.append(details_button)
+        
+        title_area.set_visible(self.SHOW_CHAT_TITLE_AREA)
+        chat_view.title_area = title_area
+        chat_view.title_label = title_label
+        chat_view.title_avatar = title_avatar
         chat_view.append(title_area)
         
+        # Search within the conversation
+        search_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=6)
+        search_entry = Gtk.SearchEntry()
+        search_entry.set_placeholder_text("Search this conversation")
+        search_entry.set_hexpand(True)
+        search_box.append(search_entry)
+        
+        search_count = Gtk.Label()
+        search_count.add_css_class("dim-label")
+        search_count.add_css_class("caption")
+        search_box.append(search_count)
+        
+        older_button = Gtk.Button.new_from_icon_name("go-up-symbolic")
+        older_button.set_tooltip_text("Older Match")
+        older_button.connect("clicked", lambda b: self.step_chat_search(chat_view, chat, 1))
+        search_box.append(older_button)
+        
+        newer_button = Gtk.Button.new_from_icon_name("go-down-symbolic")
+        newer_button.set_tooltip_text("Newer Match")
+        newer_button.connect("clicked", lambda b: self.step_chat_search(chat_view, chat, -1))
+        search_box.append(newer_button)
+        
+        search_bar = Gtk.SearchBar()
+        search_bar.set_child(search_box)
+        search_bar.connect_entry(search_entry)
+        search_bar.set_show_close_button(True)
+        search_entry.connect("search-changed", self.on_chat_search_changed, chat_view, chat)
+        # Enter steps back through older matches
+        search_entry.connect("activate", lambda e: self.step_chat_search(chat_view, chat, 1))
+        
+        chat_view.search_bar = search_bar
+        chat_view.search_entry = search_entry
+        chat_view.search_count = search_count
+        chat_view.search_matches = []
+        chat_view.search_index = 0
+        chat_view.search_highlight = None
+        # GUID of the message to show first, when opened from a search result
+        chat_view.search_target = None
+        chat_view.append(search_bar)
+        
+        self.load_chat_avatar_async(title_avatar, chat)
+        
         # Messages area (placeholder for now)
         messages_area = Gtk.ScrolledWindow()
         messages_area.set_vexpand(True)
@@ -493,11 +2146,21 @@ class MainWindow(Adw.ApplicationWindow):
         chat_view.messages_area = messages_area
         chat_view.messages_box = messages_box
         
-        # Load recent messages
-        self.load_chat_messages(chat, messages_box, messages_area)
+        # Load recent messages, then catch up on anything the cache missed
+        if self.load_chat_messages(chat, messages_box, messages_area):
+            self.fetch_latest_messages_async(chat)
         
         messages_area.set_child(messages_box)
-        chat_view.append(messages_area)
+        
+        # Side panel showing one reply thread, opened from a message's menu
+        thread_split = Adw.OverlaySplitView()
+        thread_split.set_sidebar_position(Gtk.PackType.END)
+        thread_split.set_show_sidebar(False)
+        thread_split.set_vexpand(True)
+        thread_split.set_content(messages_area)
+        thread_split.set_sidebar(self.create_thread_panel(chat_view, chat))
+        chat_view.thread_split = thread_split
+        chat_view.append(thread_split)
         
         # Message input area with attachment support
         input_container = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=8)
@@ -511,20 +2174,66 @@ class MainWindow(Adw.ApplicationWindow):
         
         # Attachment button
         attachment_button = Gtk.Button()
-        attachment_button.set_icon_name("document-edit-symbolic")
-        attachment_button.set_tooltip_text("Attach image")
+        attachment_button.set_icon_name("mail-attachment-symbolic")
+        attachment_button.set_tooltip_text("Attach File")
         attachment_button.add_css_class("flat")
         attachment_button.connect("clicked", self.on_attachment_clicked)
         input_area.append(attachment_button)
         
+        # More composer actions
+        more_menu = Gio.Menu()
+        more_menu.append("Capture Screenshot", "win.capture-screenshot")
+        more_menu.append("Record Voice Memo", "win.record-voice-memo")
+        more_menu.append("Share Contact…", "win.share-contact")
+        
+        more_button = Gtk.MenuButton()
+        more_button.set_icon_name("list-add-symbolic")
+        more_button.set_tooltip_text("More")
+        more_button.set_menu_model(more_menu)
+        more_button.add_css_class("flat")
+        input_area.append(more_button)
+        
         # Message entry
         message_entry = Gtk.Entry()
         message_entry.set_placeholder_text("Type a message...")
         message_entry.set_hexpand(True)
+        # Before the handlers, so restoring a draft doesn't count as typing
+        message_entry.set_text(self.chat_service.get_draft(chat.guid))
         message_entry.connect("changed", self.on_message_entry_changed)
         message_entry.connect("activate", self.on_send_message)
         input_area.append(message_entry)
         
+        # Character and segment count, shown while composing on SMS
+        sms_counter = Gtk.Label()
+        sms_counter.add_css_class("caption")
+        sms_counter.add_css_class("dim-label")
+        sms_counter.add_css_class("numeric")
+        sms_counter.set_visible(False)
+        chat_view.sms_counter = sms_counter
+        input_area.append(sms_counter)
+        message_entry.connect("changed", lambda entry: self.update_sms_counter(chat_view))
+        
+        # Conversations merged across services pick the one to send on,
+        # automatically (the primary chat's service) or always the same one
+        chat_view.service_choices = []
+        if chat.linked_chats:
+            services = [c.service for c in service_order([chat] + chat.linked_chats)]
+            chat_view.service_choices = [None] + services
+            service_dropdown = Gtk.DropDown.new_from_strings([f"Auto ({chat.service})"] + services)
+            service_dropdown.set_tooltip_text("Send As")
+            service_dropdown.set_valign(Gtk.Align.CENTER)
+            chat_view.service_dropdown = service_dropdown
+            
+            preferred = self.chat_service.get_send_service(chat.guid)
+            session_guid = self.send_services.get(chat.guid)
+            if session_guid:
+                preferred = next((c.service for c in [chat] + chat.linked_chats if c.guid == session_guid), None)
+            if preferred not in chat_view.service_choices:
+                preferred = None
+            self.show_send_service(chat_view, preferred)
+            service_dropdown.connect("notify::selected", self.on_send_service_selected, chat_view, chat)
+            input_area.append(service_dropdown)
+        
         # Send button
         send_button = Gtk.Button()
         send_button.set_icon_name("send-symbolic")
@@ -537,7 +2246,53 @@ class MainWindow(Adw.ApplicationWindow):
         input_area.send_button = send_button
         input_area.attachment_button = attachment_button
         chat_view.input_area = input_area
-        
+        chat_view.chat_guid = chat.guid
+        
+        # Pasting an image opens the preview instead of inserting text,
+        # and Up in an empty composer recalls sent messages
+        paste_controller = Gtk.EventControllerKey()
+        paste_controller.set_propagation_phase(Gtk.PropagationPhase.CAPTURE)
+        paste_controller.connect("key-pressed", self.on_composer_key_pressed)
+        message_entry.add_controller(paste_controller)
+        
+        # Dropping images or files on the conversation attaches them
+        drop_target = Gtk.DropTarget.new(GObject.TYPE_NONE, Gdk.DragAction.COPY)
+        drop_target.set_gtypes([Gdk.FileList, Gdk.Texture])
+        drop_target.connect("drop", self.on_chat_view_drop)
+        chat_view.add_controller(drop_target)
+        
+        # Reply bar shown above the input while replying to a message
+        reply_revealer = Gtk.Revealer()
+        reply_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=8)
+        reply_box.add_css_class("reply-bar")
+        
+        reply_icon = Gtk.Image.new_from_icon_name("mail-reply-sender-symbolic")
+        reply_box.append(reply_icon)
+        chat_view.reply_icon = reply_icon
+        
+        reply_label = Gtk.Label()
+        reply_label.set_hexpand(True)
+        reply_label.set_halign(Gtk.Align.START)
+        reply_label.set_ellipsize(3)  # ELLIPSIZE_END
+        reply_label.add_css_class("dim-label")
+        reply_box.append(reply_label)
+        
+        cancel_reply_button = Gtk.Button()
+        cancel_reply_button.set_icon_name("window-close-symbolic")
+        cancel_reply_button.set_tooltip_text("Cancel")
+        cancel_reply_button.add_css_class("flat")
+        cancel_reply_button.connect("clicked", lambda b: self.cancel_reply())
+        reply_box.append(cancel_reply_button)
+        
+        reply_revealer.set_child(reply_box)
+        chat_view.reply_revealer = reply_revealer
+        chat_view.reply_label = reply_label
+        chat_view.reply_to = None
+        # Sent messages being cycled through with Up, and the one in the composer
+        chat_view.recall_messages = []
+        chat_view.recall_index = None
+        
+        input_container.append(reply_revealer)
         input_container.append(input_area)
         chat_view.append(input_container)
         
@@ -545,16 +2300,17 @@ class MainWindow(Adw.ApplicationWindow):
         self.content_stack.add_named(chat_view, f"chat_{chat.guid}")
         self.content_stack.set_visible_child_name(f"chat_{chat.guid}")
     
-    def load_chat_messages(self, chat: ChatRecord, messages_box: Gtk.Box, messages_area: Gtk.ScrolledWindow = None):
-        """Load messages for a chat."""
+    def load_chat_messages(self, chat: ChatRecord, messages_box: Gtk.Box, messages_area: Gtk.ScrolledWindow = None) -> bool:
+        """Load messages for a chat; returns False when the cache had none and the server is asked."""
         # Get cached messages first
-        messages = self.chat_service.get_cached_chat_messages(chat.guid, limit=50)
+        messages = self.chat_service.get_cached_conversation_messages(chat, limit=50)
         
         if not messages:
             # No cached messages, show loading and fetch from server
-            loading_label = Gtk.Label()
-            loading_label.set_text("Loading messages...")
-            loading_label.add_css_class("dim-label")
+            loading_label = self.create_status_page(
+                "emblem-synchronizing-symbolic", "Loading Messages"
+            )
+            loading_label.add_css_class("compact")
             messages_box.append(loading_label)
             
             # Load from server in background
@@ -566,18 +2322,18 @@ class MainWindow(Adw.ApplicationWindow):
                         asyncio.set_event_loop(loop)
                         loop.run_until_complete(
                             self.load_messages_from_server_async(
-                                config['url'], config['password'], chat.guid, messages_box, messages_area
+                                config['url'], config['password'], chat, messages_box, messages_area
                             )
                         )
                         loop.close()
                     except Exception as e:
+                        error_msg = str(e)
                         def show_error():
                             # Remove loading label
                             messages_box.remove(loading_label)
-                            error_label = Gtk.Label()
-                            error_label.set_text(f"Failed to load messages: {str(e)}")
-                            error_label.add_css_class("error")
-                            messages_box.append(error_label)
+                            messages_box.append(
+                                self.create_messages_error_page(chat, messages_box, messages_area, error_msg)
+                            )
                         GLib.idle_add(show_error)
                 
                 thread = threading.Thread(target=run_async, daemon=True)
@@ -588,13 +2344,42 @@ class MainWindow(Adw.ApplicationWindow):
             # Auto-scroll to bottom after displaying messages
             if messages_area:
                 GLib.idle_add(self.scroll_to_bottom, messages_area)
+        return bool(messages)
+    
+    def fetch_latest_messages_async(self, chat: ChatRecord):
+        """Fetch a chat's latest page from the server, adding new messages if it's still open."""
+        config = self.get_application().config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            return
+        
+        def on_fetched():
+            if self.current_chat and self.current_chat.guid == chat.guid:
+                self.refresh_current_chat_messages()
+            return False
+        
+        def run_async():
+            try:
+                loop = asyncio.new_event_loop()
+                asyncio.set_event_loop(loop)
+                loop.run_until_complete(
+                    self.chat_service.sync_conversation_messages(
+                        config['url'], config['password'], chat, limit=50
+                    )
+                )
+                loop.close()
+                GLib.idle_add(on_fetched)
+            except Exception as e:
+                pass  # The cached messages stay up; the message check catches up later
+        
+        thread = threading.Thread(target=run_async, daemon=True)
+        thread.start()
     
     async def load_messages_from_server_async(self, server_url: str, password: str, 
-                                            chat_guid: str, messages_box: Gtk.Box, messages_area: Gtk.ScrolledWindow = None):
+                                            chat: ChatRecord, messages_box: Gtk.Box, messages_area: Gtk.ScrolledWindow = None):
         """Load messages from server."""
         try:
-            messages = await self.chat_service.sync_chat_messages(
-                server_url, password, chat_guid, limit=50
+            messages = await self.chat_service.sync_conversation_messages(
+                server_url, password, chat, limit=50
             )
             
             def update_ui():
@@ -612,6 +2397,7 @@ class MainWindow(Adw.ApplicationWindow):
             GLib.idle_add(update_ui)
             
         except Exception as e:
+            error_msg = str(e)
             def show_error():
                 # Remove any existing children
                 while True:
@@ -620,13 +2406,27 @@ class MainWindow(Adw.ApplicationWindow):
                         break
                     messages_box.remove(child)
                 
-                error_label = Gtk.Label()
-                error_label.set_text(f"Failed to load messages: {str(e)}")
-                error_label.add_css_class("error")
-                messages_box.append(error_label)
+                messages_box.append(
+                    self.create_messages_error_page(chat, messages_box, messages_area, error_msg)
+                )
             
             GLib.idle_add(show_error)
     
+    def create_messages_error_page(self, chat, messages_box: Gtk.Box,
+                                   messages_area: Gtk.ScrolledWindow, error_message: str) -> Adw.StatusPage:
+        """Create an error page for a chat whose messages failed to load."""
+        def on_retry(button):
+            messages_box.remove(page)
+            if chat:
+                self.load_chat_messages(chat, messages_box, messages_area)
+        
+        page = self.create_status_page(
+            "network-offline-symbolic", "Couldn't Load Messages",
+            error_message, "Retry", on_retry
+        )
+        page.add_css_class("compact")
+        return page
+    
     def scroll_to_bottom(self, scrolled_window: Gtk.ScrolledWindow):
         """Scroll to the bottom of a scrolled window."""
         try:
@@ -649,27 +2449,147 @@ class MainWindow(Adw.ApplicationWindow):
         filtered_messages = [m for m in messages if not self.is_reaction_event(m)]
 
         if not filtered_messages:
-            no_messages_label = Gtk.Label()
-            no_messages_label.set_text("No messages in this chat")
-            no_messages_label.add_css_class("dim-label")
-            messages_box.append(no_messages_label)
+            no_messages_page = self.create_status_page(
+                "mail-message-new-symbolic", "No Messages",
+                "Send a message to start the conversation"
+            )
+            no_messages_page.add_css_class("compact")
+            messages_box.append(no_messages_page)
+            return
+
+        # Sort messages by date (newest last for natural reading order)
+        sorted_messages = sorted(filtered_messages, key=lambda m: m.sort_key)
+
+        previous_message = None
+        for message in sorted_messages:
+            self.append_date_separator(messages_box, previous_message, message)
+            message_widget = self.create_message_widget(message, previous_message)
+            # Store the message GUID for future reference
+            message_widget.message_guid = message.guid
+            messages_box.append(message_widget)
+            previous_message = message
+    
+    def append_date_separator(self, messages_box: Gtk.Box, previous_message, message):
+        """Add a date chip above a message that starts a new day. Clicking it opens jump-to-date."""
+        created = message.datetime_created
+        if created is None:
+            return
+        previous_created = previous_message.datetime_created if previous_message else None
+        if previous_created and previous_created.date() == created.date():
+            return
+        
+        day = created.date()
+        separator = Gtk.Button()
+        separator.set_label(format_day(day, self.get_application().config_manager.get_time_format()))
+        separator.set_tooltip_text("Jump to Date")
+        separator.set_halign(Gtk.Align.CENTER)
+        separator.add_css_class("flat")
+        separator.add_css_class("date-separator")
+        separator.connect("clicked", self.on_date_separator_clicked, day)
+        separator.separator_day = day
+        messages_box.append(separator)
+    
+    def on_date_separator_clicked(self, button: Gtk.Button, day: date):
+        """Show a calendar on the clicked day, so nearby days are one click away."""
+        popover = Gtk.Popover()
+        popover.set_parent(button)
+        popover.set_position(Gtk.PositionType.BOTTOM)
+        
+        calendar = Gtk.Calendar()
+        calendar.select_day(GLib.DateTime.new_local(day.year, day.month, day.day, 0, 0, 0))
+        calendar.connect("day-selected", self.on_jump_date_selected, popover)
+        popover.set_child(calendar)
+        popover.popup()
+    
+    def on_jump_date_selected(self, calendar: Gtk.Calendar, popover: Gtk.Popover):
+        """Jump to the day picked in the calendar."""
+        popover.popdown()
+        selected = calendar.get_date()
+        self.jump_to_date(date(selected.get_year(), selected.get_month(), selected.get_day_of_month()))
+    
+    def jump_to_date(self, day: date):
+        """Show the cached messages up to the end of a day, scrolled to the start of that day."""
+        chat_view = self.content_stack.get_visible_child()
+        messages_box = getattr(chat_view, 'messages_box', None)
+        messages_area = getattr(chat_view, 'messages_area', None)
+        if not self.current_chat or not messages_box:
+            return
+        
+        if day >= date.today():
+            self.load_chat_messages(self.current_chat, messages_box, messages_area)
             return
-
-        # Sort messages by date (newest last for natural reading order)
-        sorted_messages = sorted(filtered_messages, key=lambda m: m.date_created)
-
-        for message in sorted_messages:
-            message_widget = self.create_message_widget(message)
-            # Store the message GUID for future reference
-            message_widget.message_guid = message.guid
-            messages_box.append(message_widget)
+        
+        day_end = datetime.combine(day + timedelta(days=1), datetime.min.time())
+        messages = self.chat_service.get_cached_conversation_messages(
+            self.current_chat, limit=50, before=int(day_end.timestamp() * 1000)
+        )
+        if not messages:
+            self.show_toast("No messages from that far back on this device")
+            return
+        
+        self.display_messages(messages, messages_box)
+        
+        # The day's chip, or the closest earlier one when nothing was sent that day
+        target = None
+        child = messages_box.get_first_child()
+        while child:
+            separator_day = getattr(child, 'separator_day', None)
+            if separator_day and separator_day <= day:
+                target = child
+            child = child.get_next_sibling()
+        
+        if target is None or target.separator_day != day:
+            time_format = self.get_application().config_manager.get_time_format()
+            self.show_toast(f"No messages on {format_day(day, time_format)}, showing earlier ones")
+        if target and messages_area:
+            GLib.idle_add(self.scroll_to_widget, messages_area, target)
+    
+    def scroll_to_widget(self, scrolled_window: Gtk.ScrolledWindow, widget: Gtk.Widget):
+        """Scroll a scrolled window so a widget directly in its content box is at the top."""
+        # Measured against the content box, which doesn't move as the window scrolls
+        ok, bounds = widget.compute_bounds(widget.get_parent())
+        if ok:
+            scrolled_window.get_vadjustment().set_value(bounds.get_y())
+        return False
+    
+    def copy_code(self, code: str):
+        """Copy a one-time code to the clipboard."""
+        self.get_clipboard().set(code)
+        self.show_toast(f"Copied code {code}")
+    
+    def get_sender_name(self, address: str) -> str:
+        """Get the contact name for a sender, falling back to the raw address."""
+        return self.chat_service.get_contact_name(address) or address.split('@')[0]
     
-    def create_message_widget(self, message) -> Gtk.Widget:
-        """Create a widget for a message with reaction and context menu support."""
+    def refresh_sender_names(self):
+        """Update sender names and avatars in open conversations after contacts sync."""
+        chat_view = self.content_stack.get_first_child()
+        while chat_view:
+            messages_box = getattr(chat_view, 'messages_box', None)
+            child = messages_box.get_first_child() if messages_box else None
+            while child:
+                address = getattr(child, 'sender_address', None)
+                if address:
+                    sender_name = self.get_sender_name(address)
+                    child.sender_avatar.set_text(sender_name)
+                    if child.sender_label:
+                        child.sender_label.set_text(sender_name)
+                child = child.get_next_sibling()
+            chat_view = chat_view.get_next_sibling()
+        return False
+    
+    def create_message_widget(self, message, previous_message=None) -> Gtk.Widget:
+        """
+        Create a widget for a message with reaction and context menu support.
+        
+        previous_message is the message shown just above, used to label only the
+        first bubble of each sender's run in group chats.
+        """
         # Main container
         message_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=4)
         message_box.set_margin_top(4)
         message_box.set_margin_bottom(4)
+        message_box.sender_address = None
         
         # Content row
         content_row = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=8)
@@ -681,6 +2601,33 @@ class MainWindow(Adw.ApplicationWindow):
             # Left-align for received messages
             content_row.set_halign(Gtk.Align.START)
         
+        # Group chats color each sender's avatar ring and name
+        if not message.is_from_me and message.handle_address and is_group_chat_guid(message.chat_guid):
+            participant_class = participant_css_class(message.handle_address)
+            sender_name = self.get_sender_name(message.handle_address)
+            message_box.sender_address = message.handle_address
+            
+            # Name above the first bubble of each run of messages from one sender
+            message_box.sender_label = None
+            if (previous_message is None or previous_message.is_from_me
+                    or previous_message.handle_address != message.handle_address):
+                sender_label = Gtk.Label(label=sender_name)
+                sender_label.set_halign(Gtk.Align.START)
+                sender_label.set_margin_start(40)  # Past the avatar
+                sender_label.add_css_class("caption")
+                sender_label.add_css_class("participant-name")
+                sender_label.add_css_class(participant_class)
+                message_box.append(sender_label)
+                message_box.sender_label = sender_label
+            
+            sender_avatar = Adw.Avatar.new(24, sender_name, True)
+            sender_avatar.set_valign(Gtk.Align.END)
+            sender_avatar.set_margin_bottom(4)
+            sender_avatar.add_css_class("participant-avatar")
+            sender_avatar.add_css_class(participant_class)
+            content_row.append(sender_avatar)
+            message_box.sender_avatar = sender_avatar
+        
         # Message bubble with gesture support
         bubble_event_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=2)
         bubble_event_box.set_margin_start(8)
@@ -694,6 +2641,13 @@ class MainWindow(Adw.ApplicationWindow):
         else:
             bubble_event_box.add_css_class("message-bubble-received")
         
+        # Conversations merged across services mark which one each message used
+        chat_service_name = message.chat_guid.split(';', 1)[0]
+        service = chat_service_name if self.chat_service.is_merged_chat(message.chat_guid) else None
+        # Sent texts are green in SMS chats, and off iMessage in merged conversations
+        if message.is_from_me and (chat_service_name == 'SMS' or (service and service != 'iMessage')):
+            bubble_event_box.add_css_class("message-bubble-sms")
+        
         # Add gesture controllers for reactions and context menu
         # Long press gesture for reactions (mobile-style)
         long_press = Gtk.GestureLongPress()
@@ -707,16 +2661,71 @@ class MainWindow(Adw.ApplicationWindow):
         right_click.connect("pressed", self.on_message_right_click, message)
         bubble_event_box.add_controller(right_click)
         
-        # Message text
-        if message.text:
+        # Swiping a bubble to the right quotes it in a reply
+        swipe = Gtk.GestureSwipe()
+        swipe.set_touch_only(True)  # Mouse drags select text instead
+        swipe.connect("swipe", self.on_message_swipe, message)
+        bubble_event_box.add_controller(swipe)
+        
+        # Keyboard shortcuts for the focused bubble
+        bubble_event_box.set_focusable(True)
+        key_controller = Gtk.EventControllerKey()
+        key_controller.connect("key-pressed", self.on_message_key_pressed, message)
+        bubble_event_box.add_controller(key_controller)
+        
+        # Unsent messages keep their place in the conversation, without their content
+        unsent = bool(getattr(message, 'date_retracted', None))
+        if unsent:
+            unsent_label = Gtk.Label(label="This message was unsent")
+            unsent_label.set_halign(Gtk.Align.START)
+            unsent_label.add_css_class("dim-label")
+            unsent_label.add_css_class("unsent-message")
+            bubble_event_box.append(unsent_label)
+        
+        # Inline replies quote the start of the message they answer
+        if not unsent and getattr(message, 'thread_originator_guid', None):
+            bubble_event_box.append(self.create_reply_quote(message))
+        
+        # Drawings are rendered from their image or video payload when there is one.
+        # Strokes sent only in payloadData (an archived, undocumented format) aren't
+        # decoded; those messages get the placeholder card below instead.
+        drawing_attachments = []
+        if not unsent and is_drawing_balloon(message.balloon_bundle_id):
+            drawing_attachments = [
+                attachment for attachment in (getattr(message, 'attachments', None) or [])
+                if self.get_attachment_mime_type(attachment).startswith(('image/', 'video/'))
+            ]
+            for attachment in drawing_attachments:
+                bubble_event_box.append(self.create_drawing_widget(attachment))
+        
+        # Placeholder card for iMessage app messages we can't render
+        balloon = None if unsent else describe_balloon(message.balloon_bundle_id)
+        if balloon and not drawing_attachments:
+            bubble_event_box.append(self.create_balloon_placeholder(*balloon))
+        
+        # Message text (app messages often carry only an object replacement character)
+        if message.text and (not balloon or message.text.strip('\ufffc \n')):
             text_label = Gtk.Label()
-            text_label.set_text(message.text)
+            if URL_PATTERN.search(message.text):
+                text_label.set_markup(linkify(message.text))
+            else:
+                text_label.set_text(message.text)
             text_label.set_wrap(True)
             text_label.set_wrap_mode(2)  # WORD_CHAR
             text_label.set_max_width_chars(50)
             text_label.set_halign(Gtk.Align.START)
             text_label.set_selectable(True)
             bubble_event_box.append(text_label)
+            
+            # One-time codes received by SMS get a button to copy them
+            code = find_incoming_code(message.text, [message.chat_guid.split(';', 1)[0]], message.is_from_me)
+            if code:
+                copy_code_button = Gtk.Button(label=f"Copy Code {code}")
+                copy_code_button.set_halign(Gtk.Align.START)
+                copy_code_button.set_margin_top(4)
+                copy_code_button.add_css_class("pill")
+                copy_code_button.connect("clicked", lambda button: self.copy_code(code))
+                bubble_event_box.append(copy_code_button)
         
         # Attachments
         if hasattr(message, 'attachments') and message.attachments:
@@ -726,7 +2735,9 @@ class MainWindow(Adw.ApplicationWindow):
             
             for attachment in message.attachments:
                 print(f"DEBUG: Processing attachment: {attachment}")
-                attachment_widget = self.create_attachment_widget(attachment)
+                if attachment in drawing_attachments:
+                    continue
+                attachment_widget = self.create_attachment_widget(attachment, message)
                 attachment_box.append(attachment_widget)
             
             bubble_event_box.append(attachment_box)
@@ -735,17 +2746,15 @@ class MainWindow(Adw.ApplicationWindow):
             if hasattr(message, 'attachments'):
                 print(f"DEBUG: Message attachments value: {message.attachments} (type: {type(message.attachments)})")
         
-        # Timestamp and sender info
+        # Timestamp and status info
         info_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=4)
         
-        # Sender (for received messages in group chats)
-        if not message.is_from_me and message.handle_address:
-            sender_label = Gtk.Label()
-            sender_name = message.handle_address.split('@')[0]  # Simple name extraction
-            sender_label.set_text(sender_name)
-            sender_label.add_css_class("caption")
-            sender_label.add_css_class("dim-label")
-            info_box.append(sender_label)
+        # Service badge
+        if service:
+            service_label = Gtk.Label(label=service)
+            service_label.add_css_class("caption")
+            service_label.add_css_class("service-badge")
+            info_box.append(service_label)
         
         # Timestamp
         time_label = Gtk.Label()
@@ -780,6 +2789,26 @@ class MainWindow(Adw.ApplicationWindow):
         content_row.append(bubble_event_box)
         message_box.append(content_row)
         
+        # Exact times: a tooltip on hover, and revealed below the bubble on focus
+        details_text = self.format_message_details(message)
+        bubble_event_box.set_tooltip_text(details_text)
+        
+        details_label = Gtk.Label(label=details_text)
+        details_label.add_css_class("caption")
+        details_label.add_css_class("dim-label")
+        details_label.set_justify(Gtk.Justification.RIGHT if message.is_from_me else Gtk.Justification.LEFT)
+        details_revealer = Gtk.Revealer()
+        details_revealer.set_child(details_label)
+        details_revealer.set_halign(Gtk.Align.END if message.is_from_me else Gtk.Align.START)
+        details_revealer.set_margin_start(8)
+        details_revealer.set_margin_end(8)
+        message_box.append(details_revealer)
+        
+        focus_controller = Gtk.EventControllerFocus()
+        focus_controller.connect("enter", lambda c: details_revealer.set_reveal_child(True))
+        focus_controller.connect("leave", lambda c: details_revealer.set_reveal_child(False))
+        bubble_event_box.add_controller(focus_controller)
+        
         # Get and display reactions
         reactions = self.chat_service.get_message_reactions(message.guid)
         if reactions:
@@ -797,19 +2826,219 @@ class MainWindow(Adw.ApplicationWindow):
         
         # Store message reference for gesture callbacks
         bubble_event_box.message = message
+        bubble_event_box.message_widget = message_box
+        message_box.bubble = bubble_event_box
         
         return message_box
     
-    def create_attachment_widget(self, attachment) -> Gtk.Widget:
+    def create_reply_quote(self, message) -> Gtk.Widget:
+        """Create the quote of the message a reply answers. Clicking it shows the original."""
+        original = self.chat_service.get_message(message.thread_originator_guid)
+        if original is None:
+            preview = "Earlier message"
+        else:
+            preview = f"{self.get_message_sender(original)}: {self.get_message_preview(original)}"
+        
+        quote_label = Gtk.Label(label=preview)
+        quote_label.set_halign(Gtk.Align.START)
+        quote_label.set_ellipsize(3)  # ELLIPSIZE_END
+        quote_label.set_max_width_chars(40)
+        quote_label.add_css_class("caption")
+        
+        quote_button = Gtk.Button()
+        quote_button.set_child(quote_label)
+        quote_button.set_halign(Gtk.Align.START)
+        quote_button.set_tooltip_text("Show Original Message")
+        quote_button.add_css_class("flat")
+        quote_button.add_css_class("reply-quote")
+        quote_button.connect("clicked", lambda button: self.show_quoted_message(message))
+        return quote_button
+    
+    def get_message_sender(self, message) -> str:
+        """Get who sent a message, as shown in quotes and threads."""
+        return "You" if message.is_from_me else self.get_sender_name(message.handle_address or "")
+    
+    def get_message_preview(self, message) -> str:
+        """Get a one-line summary of a message's content for quotes and threads."""
+        if getattr(message, 'date_retracted', None):
+            return "This message was unsent"
+        text = (message.text or "").strip('\ufffc \n')
+        return text or "Attachment"
+    
+    def create_thread_panel(self, chat_view, chat: ChatRecord) -> Gtk.Widget:
+        """Create the side panel listing a reply thread's messages. Clicking one shows it in the chat."""
+        panel = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=0)
+        panel.add_css_class("thread-panel")
+        
+        header = Adw.HeaderBar()
+        header.set_show_start_title_buttons(False)
+        header.set_show_end_title_buttons(False)
+        thread_title = Adw.WindowTitle(title="Thread")
+        header.set_title_widget(thread_title)
+        close_button = Gtk.Button.new_from_icon_name("window-close-symbolic")
+        close_button.set_tooltip_text("Close Thread")
+        close_button.connect("clicked", lambda b: chat_view.thread_split.set_show_sidebar(False))
+        header.pack_end(close_button)
+        panel.append(header)
+        
+        thread_list = Gtk.ListBox()
+        thread_list.set_selection_mode(Gtk.SelectionMode.NONE)
+        thread_list.add_css_class("navigation-sidebar")
+        thread_list.connect(
+            "row-activated", lambda box, row: self.reveal_message(chat_view, chat, row.message_guid)
+        )
+        
+        scrolled = Gtk.ScrolledWindow()
+        scrolled.set_vexpand(True)
+        scrolled.set_hscrollbar_policy(Gtk.PolicyType.NEVER)
+        scrolled.set_child(thread_list)
+        panel.append(scrolled)
+        
+        chat_view.thread_title = thread_title
+        chat_view.thread_list = thread_list
+        return panel
+    
+    def show_thread(self, chat_view, message):
+        """Open the thread side panel on the thread a message starts or replies in."""
+        originator_guid = getattr(message, 'thread_originator_guid', None) or message.guid
+        messages = self.chat_service.get_thread_messages(originator_guid)
+        
+        while True:
+            row = chat_view.thread_list.get_first_child()
+            if row is None:
+                break
+            chat_view.thread_list.remove(row)
+        
+        for thread_message in messages:
+            sender_label = Gtk.Label(
+                label=f"{self.get_message_sender(thread_message)} · "
+                      f"{self.format_message_time(thread_message.datetime_created)}"
+            )
+            sender_label.set_halign(Gtk.Align.START)
+            sender_label.add_css_class("caption")
+            sender_label.add_css_class("dim-label")
+            
+            text_label = Gtk.Label(label=self.get_message_preview(thread_message))
+            text_label.set_halign(Gtk.Align.START)
+            text_label.set_xalign(0)
+            text_label.set_wrap(True)
+            text_label.set_wrap_mode(2)  # WORD_CHAR
+            
+            row_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=2)
+            row_box.set_margin_top(6)
+            row_box.set_margin_bottom(6)
+            row_box.append(sender_label)
+            row_box.append(text_label)
+            
+            row = Gtk.ListBoxRow()
+            row.set_child(row_box)
+            row.set_tooltip_text("Show in Conversation")
+            row.message_guid = thread_message.guid
+            if thread_message.guid == originator_guid:
+                row.add_css_class("thread-originator")
+            chat_view.thread_list.append(row)
+        
+        replies = sum(1 for thread_message in messages if thread_message.guid != originator_guid)
+        chat_view.thread_title.set_subtitle("1 reply" if replies == 1 else f"{replies} replies")
+        chat_view.thread_split.set_show_sidebar(True)
+    
+    def show_quoted_message(self, reply):
+        """Scroll to and briefly highlight the message a reply quotes, fetching older history to reach it."""
+        chat_view = self.content_stack.get_visible_child()
+        chat = self.current_chat
+        if not chat or not hasattr(chat_view, 'messages_box'):
+            return
+        if self.reveal_message(chat_view, chat, reply.thread_originator_guid):
+            return
+        
+        # Older than the cache: page back through the server's history
+        config = self.get_application().config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            self.show_toast("The original message isn't on this device")
+            return
+        self.show_toast("Loading earlier messages…")
+        
+        def on_loaded(original):
+            if self.current_chat is not chat:
+                return False
+            if original is None or not self.reveal_message(chat_view, chat, original.guid):
+                self.show_toast("Couldn't find the original message")
+            return False
+        
+        def run_async():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            try:
+                original = loop.run_until_complete(
+                    self.chat_service.fetch_message_history_until(
+                        config['url'], config['password'], reply.chat_guid, reply.thread_originator_guid
+                    )
+                )
+            finally:
+                loop.close()
+            GLib.idle_add(on_loaded, original)
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def reveal_message(self, chat_view, chat: ChatRecord, message_guid: str) -> bool:
+        """Scroll to and briefly highlight a cached message, loading the messages leading up to it if needed."""
+        widget = self.find_message_widget(chat_view.messages_box, message_guid)
+        if widget is None:
+            original = self.chat_service.get_message(message_guid)
+            if original is None:
+                return False
+            messages = self.chat_service.get_cached_conversation_messages(
+                chat, limit=50, before=original.date_created + 1
+            )
+            self.display_messages(messages, chat_view.messages_box)
+            widget = self.find_message_widget(chat_view.messages_box, message_guid)
+            if widget is None:
+                return False
+        
+        widget.add_css_class("quoted-message")
+        GLib.timeout_add(QUOTE_HIGHLIGHT_MS, lambda: widget.remove_css_class("quoted-message") or False)
+        GLib.idle_add(self.scroll_to_widget, chat_view.messages_area, widget)
+        return True
+    
+    def create_balloon_placeholder(self, icon_name: str, description: str) -> Gtk.Widget:
+        """Create an informative card for an iMessage app message."""
+        card = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=8)
+        card.add_css_class("balloon-placeholder")
+        
+        icon = Gtk.Image.new_from_icon_name(icon_name)
+        icon.set_pixel_size(32)
+        card.append(icon)
+        
+        text_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=2)
+        text_box.set_valign(Gtk.Align.CENTER)
+        
+        title_label = Gtk.Label()
+        title_label.set_text(description)
+        title_label.set_halign(Gtk.Align.START)
+        title_label.add_css_class("heading")
+        text_box.append(title_label)
+        
+        hint_label = Gtk.Label()
+        hint_label.set_text("Open on an Apple device to view")
+        hint_label.set_halign(Gtk.Align.START)
+        hint_label.add_css_class("caption")
+        hint_label.add_css_class("dim-label")
+        text_box.append(hint_label)
+        
+        card.append(text_box)
+        return card
+    
+    def create_attachment_widget(self, attachment, message) -> Gtk.Widget:
         """Create a widget for displaying a message attachment."""
         attachment_container = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=8)
         attachment_container.set_margin_top(4)
         attachment_container.set_margin_bottom(4)
         
-        # Get file info from metadata or original name
-        file_name = attachment.get('original_roi') or attachment.get('transfer_name', 'Unknown File')
-        file_size = attachment.get('total_bytes', 0)
-        mime_type = attachment.get('mime_type', '')
+        # Get file info from metadata or original name (the server sends camelCase keys)
+        file_name = (attachment.get('transferName') or attachment.get('transfer_name')
+                     or attachment.get('original_roi') or 'Unknown File')
+        file_size = attachment.get('totalBytes') or attachment.get('total_bytes') or 0
+        mime_type = self.get_attachment_mime_type(attachment)
         
         # Icon based on file type
         icon_widget = Gtk.Image()
@@ -857,20 +3086,334 @@ class MainWindow(Adw.ApplicationWindow):
         
         attachment_container.append(info_box)
         
+        # Images and videos open in the media viewer, with the rest of the chat's media
+        is_media = mime_type.startswith(('image/', 'video/'))
+        if is_media:
+            view_button = Gtk.Button()
+            view_button.set_icon_name("view-fullscreen-symbolic")
+            view_button.set_tooltip_text("View")
+            view_button.add_css_class("flat")
+            view_button.connect("clicked", lambda button: self.confirm_metered_download(
+                file_size, lambda: self.open_media_viewer(message, attachment)))
+            attachment_container.append(view_button)
+        
+        # Open button; received files are only ever opened from here
+        sender = "you" if message.is_from_me else self.get_sender_name(message.handle_address or '')
+        open_button = Gtk.Button()
+        open_button.set_icon_name("document-open-symbolic")
+        open_button.set_tooltip_text("Open attachment")
+        open_button.add_css_class("flat")
+        open_button.connect("clicked", lambda button: self.confirm_metered_download(
+            file_size, lambda: self.open_attachment_async(attachment['guid'], file_name, mime_type, sender)))
+        
+        attachment_container.append(open_button)
+        
         # Download button
         download_button = Gtk.Button()
         download_button.set_icon_name("document-save")
         download_button.set_tooltip_text("Download attachment")
         download_button.add_css_class("flat")
-        download_button.connect("clicked", self.on_download_attachment, attachment)
+        download_button.connect("clicked", lambda button: self.confirm_metered_download(
+            file_size, lambda: self.on_download_attachment(button, attachment)))
         
         attachment_container.append(download_button)
         
         # Style the attachment container
         attachment_container.add_css_class("attachment-widget")
         
+        is_pdf = 'pdf' in mime_type and POPPLER_AVAILABLE
+        
+        # Download previews automatically unless the power profile says otherwise
+        application = self.get_application()
+        download_limit = application.get_power_setting('download_limit')
+        if ((mime_type.startswith('image/') or is_pdf)
+                and application.get_power_setting('auto_download')):
+            if ((download_limit is not None and file_size > download_limit)
+                    or self.chat_service.is_excluded_attachment(attachment['guid'])):
+                # Large previews on a metered connection, and any in chats kept
+                # out of the cache, wait for a click
+                preview_click = Gtk.GestureClick()
+                preview_click.connect("pressed", lambda g, n, x, y: self.confirm_metered_download(
+                    file_size, lambda: self.load_attachment_preview_async(
+                        icon_widget, attachment['guid'], is_pdf)))
+                icon_widget.add_controller(preview_click)
+                icon_widget.set_cursor_from_name("pointer")
+                icon_widget.set_tooltip_text("Download preview")
+            else:
+                self.load_attachment_preview_async(icon_widget, attachment['guid'], is_pdf)
+                if is_media:
+                    view_click = Gtk.GestureClick()
+                    view_click.connect("pressed", lambda g, n, x, y: self.open_media_viewer(message, attachment))
+                    icon_widget.add_controller(view_click)
+                    icon_widget.set_cursor_from_name("pointer")
+                    icon_widget.set_tooltip_text("View")
+        
+        # Clicking a PDF opens it in the in-app viewer
+        if is_pdf:
+            pdf_click = Gtk.GestureClick()
+            pdf_click.connect("pressed", lambda g, n, x, y: self.open_pdf_attachment_async(
+                attachment['guid'], file_name))
+            icon_widget.add_controller(pdf_click)
+            icon_widget.set_cursor_from_name("pointer")
+            icon_widget.set_tooltip_text("Open PDF")
+        
         return attachment_container
     
+    def get_attachment_mime_type(self, attachment) -> str:
+        """Get an attachment's MIME type (the server sends camelCase keys)."""
+        return attachment.get('mimeType') or attachment.get('mime_type') or ''
+    
+    def create_drawing_widget(self, attachment) -> Gtk.Widget:
+        """Create a static rendering of a handwriting or Digital Touch payload."""
+        picture = Gtk.Picture()
+        picture.set_size_request(240, 240)
+        picture.set_content_fit(Gtk.ContentFit.CONTAIN)
+        picture.set_halign(Gtk.Align.START)
+        picture.add_css_class("drawing-message")
+        
+        self.load_drawing_async(picture, attachment)
+        return picture
+    
+    def load_drawing_async(self, picture: Gtk.Picture, attachment):
+        """Download a drawing payload and show it in a picture."""
+        config = self.config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            return
+        
+        is_video = self.get_attachment_mime_type(attachment).startswith('video/')
+        
+        def run_async():
+            try:
+                loop = asyncio.new_event_loop()
+                asyncio.set_event_loop(loop)
+                drawing_path = loop.run_until_complete(
+                    self.chat_service.get_attachment_path(
+                        config['url'], config['password'], attachment['guid']
+                    )
+                )
+                loop.close()
+                
+                if not drawing_path:
+                    return
+                
+                def update_picture():
+                    try:
+                        if is_video and not is_feature_enabled('media'):
+                            picture.set_tooltip_text(f"Can't play: {get_missing_reason('media')}")
+                        elif is_video:
+                            # Animated Digital Touch messages arrive as videos;
+                            # the media file shows its first frame until played
+                            media = Gtk.MediaFile.new_for_filename(drawing_path)
+                            picture.set_paintable(media)
+                            
+                            click = Gtk.GestureClick()
+                            click.connect("pressed", lambda g, n, x, y: media.play())
+                            picture.add_controller(click)
+                            picture.set_tooltip_text("Play")
+                        else:
+                            picture.set_paintable(Gdk.Texture.new_from_filename(drawing_path))
+                    except Exception:
+                        pass  # Leave the picture empty for payloads we can't decode
+                    return False
+                
+                GLib.idle_add(update_picture)
+            except Exception:
+                pass  # Silently handle drawing download errors
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def open_pdf_attachment_async(self, attachment_guid: str, title: str):
+        """Download a PDF attachment and show it in the PDF viewer."""
+        config = self.config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            return
+        
+        def run_async():
+            try:
+                loop = asyncio.new_event_loop()
+                asyncio.set_event_loop(loop)
+                pdf_data = loop.run_until_complete(
+                    self.chat_service.get_attachment(
+                        config['url'], config['password'], attachment_guid
+                    )
+                )
+                loop.close()
+                
+                def show_viewer():
+                    try:
+                        PdfViewerDialog(pdf_data, title).present(self)
+                    except Exception as e:
+                        self.show_toast(f"Failed to open PDF: {e}")
+                    return False
+                
+                if pdf_data:
+                    GLib.idle_add(show_viewer)
+                else:
+                    GLib.idle_add(self.show_toast, "Failed to download PDF")
+            except Exception as e:
+                GLib.idle_add(self.show_toast, f"Failed to open PDF: {str(e)}")
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def open_media_viewer(self, message, attachment):
+        """Show an image or video in the media viewer, stepping through the chat's other media."""
+        media = self.chat_service.get_conversation_media(self.current_chat) if self.current_chat else []
+        index = next((i for i, item in enumerate(media) if item.guid == attachment.get('guid')), None)
+        if index is None:
+            # Not in the cache, e.g. a chat kept out of it: show just this one
+            media = [MediaItem(attachment, message.guid, message.chat_guid, message.original_rowid,
+                               message.date_created, message.is_from_me, message.handle_address)]
+            index = 0
+        MediaViewerDialog(media, index, self.load_attachment_file_async, self.describe_media_item).present(self)
+    
+    def describe_media_item(self, item: MediaItem) -> Tuple[str, str]:
+        """Get who sent a media item and when, for the media viewer's title."""
+        sender = "You" if item.is_from_me else self.get_sender_name(item.handle_address or "")
+        time_format = self.get_application().config_manager.get_time_format()
+        return sender, format_time(item.datetime_created, time_format, full=True)
+    
+    def load_attachment_file_async(self, attachment_guid: str, callback: Callable[[Optional[str]], None]):
+        """Fetch an attachment's file, from the cache if it's there, and hand its path to callback on the main loop."""
+        config = self.config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            callback(None)
+            return
+        
+        def run_async():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            try:
+                path = loop.run_until_complete(
+                    self.chat_service.get_attachment_path(config['url'], config['password'], attachment_guid)
+                )
+            finally:
+                loop.close()
+            GLib.idle_add(callback, path)
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def open_attachment_async(self, attachment_guid: str, file_name: str, mime_type: str, sender: str):
+        """Download an attachment to a temporary file and open it in its default app."""
+        config = self.config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            return
+        
+        def run_async():
+            try:
+                loop = asyncio.new_event_loop()
+                asyncio.set_event_loop(loop)
+                cached_path = loop.run_until_complete(
+                    self.chat_service.get_attachment_path(
+                        config['url'], config['password'], attachment_guid
+                    )
+                )
+                loop.close()
+                
+                if not cached_path:
+                    GLib.idle_add(self.show_toast, "Failed to download attachment")
+                    return
+                
+                # Opened under its own name, so the right app picks it up
+                file_path = os.path.join(self.chat_service.attachment_cache.make_open_dir(),
+                                         os.path.basename(file_name) or attachment_guid)
+                shutil.copyfile(cached_path, file_path)
+                GLib.idle_add(self.confirm_open_attachment, file_path, file_name, mime_type, sender)
+            except Exception as e:
+                GLib.idle_add(self.show_toast, f"Failed to open attachment: {str(e)}")
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def confirm_open_attachment(self, file_path: str, file_name: str, mime_type: str, sender: str):
+        """Open a downloaded attachment, asking first if it's a program or an unusual type of file."""
+        risky = is_risky(file_name, mime_type)
+        
+        def launch():
+            launcher = Gtk.FileLauncher.new(Gio.File.new_for_path(file_path))
+            # Let the user pick what opens programs and scripts, rather than running them
+            launcher.set_always_ask(risky)
+            launcher.launch(self, None, None, None)
+        
+        if not needs_open_confirmation(file_name, mime_type):
+            launch()
+            return False
+        
+        file_type = describe_file_type(file_name, mime_type)
+        dialog = Adw.AlertDialog()
+        dialog.set_heading(f"Open “{file_name}”?")
+        if risky:
+            dialog.set_body(f"This is a {file_type} sent by {sender}. Programs and scripts can harm "
+                            "your computer or steal your data. Only open it if you expected it "
+                            "and trust who sent it.")
+        else:
+            dialog.set_body(f"This is a {file_type} sent by {sender}, which isn't a common kind of "
+                            "attachment. Only open it if you expected it.")
+        dialog.add_response("cancel", "Cancel")
+        dialog.add_response("open", "Open Anyway")
+        dialog.set_response_appearance(
+            "open", Adw.ResponseAppearance.DESTRUCTIVE if risky else Adw.ResponseAppearance.DEFAULT
+        )
+        dialog.set_default_response("cancel")
+        dialog.set_close_response("cancel")
+        dialog.connect("response", lambda d, response: launch() if response == "open" else None)
+        dialog.present(self)
+        return False
+    
+    def load_attachment_preview_async(self, image_widget: Gtk.Image, attachment_guid: str,
+                                      is_pdf: bool = False):
+        """Download an image (or a PDF's first page) and show it in place of its file icon."""
+        config = self.config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            return
+        
+        def run_async():
+            try:
+                loop = asyncio.new_event_loop()
+                asyncio.set_event_loop(loop)
+                image_data = loop.run_until_complete(
+                    self.chat_service.get_attachment(
+                        config['url'], config['password'], attachment_guid
+                    )
+                )
+                loop.close()
+                
+                if image_data:
+                    def update_preview():
+                        try:
+                            if is_pdf:
+                                texture = render_page(load_pdf(image_data), 0, 320)
+                            else:
+                                texture = Gdk.Texture.new_from_bytes(GLib.Bytes.new(image_data))
+                            image_widget.set_from_paintable(texture)
+                            image_widget.set_pixel_size(160)
+                        except Exception:
+                            pass  # Keep the file icon for formats GDK can't load
+                        return False
+                    
+                    GLib.idle_add(update_preview)
+            except Exception as e:
+                pass  # Silently handle preview errors
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def confirm_metered_download(self, file_size: int, download: Callable[[], None]):
+        """Run a download, asking first if it's over the metered connection download limit."""
+        download_limit = self.get_application().get_power_setting('download_limit')
+        if download_limit is None or file_size <= download_limit:
+            download()
+            return
+        
+        dialog = Adw.AlertDialog()
+        dialog.set_heading("Download on Metered Connection?")
+        dialog.set_body(f"This attachment is {self.format_file_size(file_size)}. "
+                        "Downloading it may use your data allowance.")
+        dialog.add_response("cancel", "Cancel")
+        dialog.add_response("download", "Download")
+        dialog.set_response_appearance("download", Adw.ResponseAppearance.SUGGESTED)
+        dialog.set_default_response("download")
+        dialog.set_close_response("cancel")
+        dialog.connect("response", lambda d, response: download() if response == "download" else None)
+        dialog.present(self)
+    
     def format_file_size(self, bytes_size: int) -> str:
         """Format file size in human readable format."""
         if bytes_size < 1024:
@@ -1051,44 +3594,264 @@ class MainWindow(Adw.ApplicationWindow):
                 
                 GLib.idle_add(show_dialog)
         
-        except Exception as e:
-            error_msg = str(e)
-            def show_error():
-                self.show_toast(f"Failed to load server information: {error_msg}")
-            GLib.idle_add(show_error)
+        except Exception as e:
+            error_msg = str(e)
+            def show_error():
+                self.show_toast(f"Failed to load server information: {error_msg}")
+            GLib.idle_add(show_error)
+    
+    # New callback methods for the enhanced features
+    
+    def on_attachment_clicked(self, button):
+        """Handle attachment button click to show file picker."""
+        file_dialog = Gtk.FileDialog()
+        file_dialog.set_title("Attach File")
+        
+        # Any file can be sent; images are previewed first
+        filter_all = Gtk.FileFilter()
+        filter_all.set_name("All Files")
+        filter_all.add_pattern("*")
+        
+        filter_images = Gtk.FileFilter()
+        filter_images.set_name("Images")
+        filter_images.add_mime_type("image/jpeg")
+        filter_images.add_mime_type("image/png")
+        filter_images.add_mime_type("image/gif")
+        filter_images.add_mime_type("image/webp")
+        filter_images.add_mime_type("image/heic")
+        
+        filter_list = Gio.ListStore.new(Gtk.FileFilter)
+        filter_list.append(filter_all)
+        filter_list.append(filter_images)
+        file_dialog.set_filters(filter_list)
+        file_dialog.set_default_filter(filter_all)
+        
+        def on_file_selected(dialog, result):
+            try:
+                file = dialog.open_finish(result)
+                if file:
+                    file_path = file.get_path()
+                    if file_path and self.current_chat:
+                        self.preview_attachment_file(file_path)
+            except Exception as e:
+                self.show_toast(f"Failed to select file: {e}")
+        
+        file_dialog.open(self, None, on_file_selected)
+    
+    def on_capture_screenshot_action(self, action, param):
+        """Capture a screenshot through the portal and preview it for sending."""
+        if not self.current_chat:
+            return
+        
+        def on_screenshot(file_path):
+            if file_path:
+                self.preview_attachment_file(file_path)
+        
+        if not take_screenshot(on_screenshot):
+            self.show_toast("Screenshots are not available on this desktop")
+    
+    def on_record_voice_memo_action(self, action, param):
+        """Open the voice memo recorder."""
+        if not self.current_chat:
+            return
+        
+        if not GST_AVAILABLE:
+            self.show_toast(f"Voice memos aren't available: {get_missing_reason('media')}")
+            return
+        
+        dialog = VoiceMemoDialog(
+            self.config_manager,
+            lambda path, temp_dir: self.send_attachment_async(
+                path, is_audio_message=True, temp_dir=temp_dir
+            )
+        )
+        dialog.present(self)
+    
+    def on_share_contact_action(self, action, param):
+        """Pick a contact to send as a contact card."""
+        if not self.current_chat:
+            return
+        ShareContactDialog(self.chat_service.get_contacts(), self.share_contact).present(self)
+    
+    def share_contact(self, contact: Participant):
+        """Send a contact as a .vcf attachment."""
+        temp_dir = tempfile.TemporaryDirectory(prefix="bluebubbles-")
+        file_path = os.path.join(temp_dir.name, safe_file_name(contact.name, 'vcf', 'Contact'))
+        with open(file_path, 'w', encoding='utf-8') as f:
+            f.write(build_contact_card(contact))
+        self.send_attachment_async(file_path, temp_dir=temp_dir)
+    
+    def on_composer_key_pressed(self, controller, keyval, keycode, state):
+        """Intercept Ctrl+V for images and long text, and Up/Down for recalling sent messages."""
+        modifiers = Gdk.ModifierType.CONTROL_MASK | Gdk.ModifierType.ALT_MASK | Gdk.ModifierType.SHIFT_MASK
+        if keyval in (Gdk.KEY_Up, Gdk.KEY_Down) and not state & modifiers:
+            return self.recall_sent_message(controller.get_widget(), 1 if keyval == Gdk.KEY_Up else -1)
+        if keyval == Gdk.KEY_Escape and self.get_recalled_message() is not None:
+            controller.get_widget().set_text("")
+            self.cancel_reply()
+            return True
+        
+        if keyval not in (Gdk.KEY_v, Gdk.KEY_V) or not state & Gdk.ModifierType.CONTROL_MASK:
+            return False
+        
+        clipboard = self.get_clipboard()
+        message_entry = controller.get_widget()
+        
+        if clipboard.get_formats().contain_gtype(Gdk.Texture):
+            def on_texture_read(clipboard, result):
+                try:
+                    texture = clipboard.read_texture_finish(result)
+                    if texture:
+                        self.preview_attachment_pixbuf(texture_to_pixbuf(texture))
+                except Exception as e:
+                    self.show_toast(f"Failed to paste image: {e}")
+            
+            clipboard.read_texture_async(None, on_texture_read)
+            return True
+        
+        if not clipboard.get_formats().contain_gtype(GObject.TYPE_STRING):
+            return False
+        
+        # Text is pasted by hand, so long pastes can be offered as a file first
+        def on_text_read(clipboard, result):
+            try:
+                text = clipboard.read_text_finish(result)
+            except GLib.Error as e:
+                self.show_toast(f"Failed to paste: {e.message}")
+                return
+            if not text:
+                return
+            if is_long_paste(text):
+                self.confirm_long_paste(message_entry, text)
+            else:
+                self.paste_into_composer(message_entry, text)
+        
+        clipboard.read_text_async(None, on_text_read)
+        return True
     
-    # New callback methods for the enhanced features
+    def paste_into_composer(self, message_entry: Gtk.Entry, text: str):
+        """Insert text at the composer's cursor, replacing any selection."""
+        message_entry.delete_selection()
+        position = message_entry.insert_text(text, -1, message_entry.get_position())
+        message_entry.set_position(position)
     
-    def on_attachment_clicked(self, button):
-        """Handle attachment button click to show file picker."""
-        file_dialog = Gtk.FileDialog()
-        file_dialog.set_title("Select Image")
+    def confirm_long_paste(self, message_entry: Gtk.Entry, text: str):
+        """Offer to send long pasted text as a .txt attachment instead of one huge message."""
+        file_name = paste_file_name(text)
         
-        # Set up image filters
-        filter_images = Gtk.FileFilter()
-        filter_images.set_name("Images")
-        filter_images.add_mime_type("image/jpeg")
-        filter_images.add_mime_type("image/png")
-        filter_images.add_mime_type("image/gif")
-        filter_images.add_mime_type("image/webp")
-        filter_images.add_mime_type("image/heic")
+        def on_response(dialog, response):
+            if response == "paste":
+                self.paste_into_composer(message_entry, text)
+            elif response == "attach":
+                temp_dir = tempfile.TemporaryDirectory(prefix="bluebubbles-")
+                file_path = os.path.join(temp_dir.name, file_name)
+                with open(file_path, 'w', encoding='utf-8') as f:
+                    f.write(text)
+                self.send_attachment_async(file_path, temp_dir=temp_dir)
         
-        filter_list = Gio.ListStore.new(Gtk.FileFilter)
-        filter_list.append(filter_images)
-        file_dialog.set_filters(filter_list)
-        file_dialog.set_default_filter(filter_images)
+        dialog = Adw.AlertDialog()
+        dialog.set_heading("Send as Text File?")
+        dialog.set_body(f"The pasted text is {len(text):,} characters long. It can be sent as "
+                        f"“{file_name}” instead of one long message.")
+        dialog.add_response("cancel", "Cancel")
+        dialog.add_response("paste", "Paste as Message")
+        dialog.add_response("attach", "Send as File")
+        dialog.set_response_appearance("attach", Adw.ResponseAppearance.SUGGESTED)
+        dialog.set_default_response("attach")
+        dialog.set_close_response("cancel")
+        dialog.connect("response", on_response)
+        dialog.present(self)
+    
+    def get_recalled_message(self):
+        """Get the sent message recalled into the visible chat's composer, if any."""
+        current_page = self.content_stack.get_visible_child()
+        index = getattr(current_page, 'recall_index', None)
+        if index is None:
+            return None
+        return current_page.recall_messages[index]
+    
+    def recall_sent_message(self, message_entry: Gtk.Entry, step: int) -> bool:
+        """
+        Put an older (step 1) or newer (step -1) sent message in the composer.
+        
+        Recall starts from an empty composer and continues while the recalled
+        text is unchanged; otherwise the arrow keys are left to the entry.
+        With the Private API the message is edited on send, otherwise sent again.
+        """
+        current_page = self.content_stack.get_visible_child()
+        if not self.current_chat or not hasattr(current_page, 'recall_messages'):
+            return False
         
-        def on_file_selected(dialog, result):
-            try:
-                file = dialog.open_finish(result)
-                if file:
-                    file_path = file.get_path()
-                    if file_path and self.current_chat:
-                        self.send_attachment_async(file_path)
-            except Exception as e:
-                self.show_toast(f"Failed to select file: {e}")
+        recalled = self.get_recalled_message()
+        text = message_entry.get_text()
+        if recalled is None:
+            if step < 0 or text:
+                return False
+            messages = self.chat_service.get_cached_conversation_messages(self.current_chat, limit=100)
+            current_page.recall_messages = get_recallable_messages(messages)
+            if not current_page.recall_messages:
+                return False
+            index = 0
+        elif text != recalled.text:
+            return False
+        else:
+            index = current_page.recall_index + step
+        
+        # Down past the newest recalled message leaves recall with an empty composer
+        if index < 0:
+            message_entry.set_text("")
+            self.cancel_reply()
+            return True
+        index = min(index, len(current_page.recall_messages) - 1)
+        
+        message = current_page.recall_messages[index]
+        current_page.reply_to = None
+        current_page.recall_index = index
+        if self.config_manager.get_api_method() == 'private':
+            current_page.reply_icon.set_from_icon_name("document-edit-symbolic")
+            current_page.reply_label.set_text(f"Editing: {message.text}")
+        else:
+            current_page.reply_icon.set_from_icon_name("view-refresh-symbolic")
+            current_page.reply_label.set_text(f"Sending again: {message.text}")
+        current_page.reply_revealer.set_reveal_child(True)
         
-        file_dialog.open(self, None, on_file_selected)
+        message_entry.set_text(message.text)
+        message_entry.set_position(-1)
+        return True
+    
+    def on_chat_view_drop(self, drop_target, value, x, y):
+        """Handle images and files dropped on the conversation."""
+        if not self.current_chat:
+            return False
+        
+        if isinstance(value, Gdk.Texture):
+            self.preview_attachment_pixbuf(texture_to_pixbuf(value))
+            return True
+        
+        files = value.get_files() if isinstance(value, Gdk.FileList) else []
+        for file in files:
+            file_path = file.get_path()
+            if file_path:
+                self.preview_attachment_file(file_path)
+        return bool(files)
+    
+    def preview_attachment_file(self, file_path: str):
+        """Preview an image file before sending; other files are sent directly."""
+        try:
+            pixbuf = GdkPixbuf.Pixbuf.new_from_file(file_path)
+        except GLib.Error:
+            self.send_attachment_async(file_path)
+            return
+        self.preview_attachment_pixbuf(pixbuf, file_path)
+    
+    def preview_attachment_pixbuf(self, pixbuf, file_path: str = None):
+        """Show the attachment preview dialog for an image."""
+        dialog = AttachmentPreviewDialog(
+            pixbuf,
+            lambda path, caption, temp_dir: self.send_attachment_async(path, caption, temp_dir=temp_dir),
+            file_path
+        )
+        dialog.present(self)
     
     def on_message_entry_changed(self, entry):
         """Handle message entry text changes for typing indicators."""
@@ -1097,7 +3860,7 @@ class MainWindow(Adw.ApplicationWindow):
         
         text = entry.get_text()
         
-        if text and not self.is_typing:
+        if text and not self.is_typing and self.chat_service.sends_typing_indicators(self.current_chat.guid):
             # Start typing
             self.is_typing = True
             self.send_typing_indicator_async(True)
@@ -1142,13 +3905,73 @@ class MainWindow(Adw.ApplicationWindow):
             self.is_typing = False
             self.send_typing_indicator_async(False)
         
+        reply_to = getattr(current_page, 'reply_to', None)
+        recalled = self.get_recalled_message()
+        self.cancel_reply()
+        
+        # A recalled message is edited in place where the server supports it
+        if recalled and self.config_manager.get_api_method() == 'private':
+            if message_text != recalled.text:
+                self.edit_message_async(recalled.guid, message_text, recalled.chat_guid)
+            return
+        
+        # Text over the service's limit goes as several messages, once the split is confirmed
+        service = self.get_send_chat_guid().split(';', 1)[0]
+        limit = get_length_limit(service, message_text)
+        if limit and get_length(service, message_text) > limit:
+            self.confirm_split_message(message_entry, message_text, service, limit, reply_to)
+            return
+        
         # Send the message
-        self.send_message_async(message_text)
+        self.send_message_async(message_text, reply_to.guid if reply_to else None)
+    
+    def confirm_split_message(self, message_entry: Gtk.Entry, message_text: str, service: str,
+                              limit: int, reply_to):
+        """Show where an over-long message will be split, and send the parts if confirmed."""
+        parts = split_message(message_text, limit, lambda part: get_length(service, part))
+        
+        parts_list = Gtk.ListBox()
+        parts_list.set_selection_mode(Gtk.SelectionMode.NONE)
+        parts_list.add_css_class("boxed-list")
+        for number, part in enumerate(parts, 1):
+            row = Adw.ActionRow()
+            row.set_title(f"Part {number} · {len(part):,} characters")
+            row.set_subtitle(preview_part(part))
+            row.set_subtitle_lines(2)
+            parts_list.append(row)
+        
+        def on_response(dialog, response):
+            if response == "send":
+                self.send_split_message_async(parts, reply_to.guid if reply_to else None)
+                return
+            # Put the message back to be shortened
+            message_entry.set_text(message_text)
+            message_entry.set_position(-1)
+            if reply_to:
+                self.start_reply(reply_to)
+        
+        dialog = Adw.AlertDialog()
+        dialog.set_heading("Send as Several Messages?")
+        dialog.set_body(f"This message is {len(message_text):,} characters long, more than the "
+                        f"{limit:,} that fit in one. It will be sent as {len(parts)} messages.")
+        dialog.set_extra_child(parts_list)
+        dialog.add_response("cancel", "Cancel")
+        dialog.add_response("send", f"Send {len(parts)} Messages")
+        dialog.set_response_appearance("send", Adw.ResponseAppearance.SUGGESTED)
+        dialog.set_default_response("send")
+        dialog.set_close_response("cancel")
+        dialog.connect("response", on_response)
+        dialog.present(self)
     
     def on_message_long_press(self, gesture, x, y, message):
         """Handle long press on message for reactions."""
         self.show_reaction_popover(gesture.get_widget(), message)
     
+    def on_message_swipe(self, gesture, velocity_x, velocity_y, message):
+        """Start a reply when a bubble is swiped to the right."""
+        if velocity_x > SWIPE_REPLY_VELOCITY and abs(velocity_x) > 2 * abs(velocity_y):
+            self.start_reply(message)
+    
     def on_message_right_click(self, gesture, n_press, x, y, message):
         """Handle right click on message for context menu."""
         self.show_message_context_menu(gesture.get_widget(), message, x, y)
@@ -1210,6 +4033,22 @@ class MainWindow(Adw.ApplicationWindow):
         menu_box.set_margin_top(8)
         menu_box.set_margin_bottom(8)
         
+        # Reply
+        reply_button = Gtk.Button()
+        reply_button.set_label("Reply")
+        reply_button.add_css_class("flat")
+        reply_button.connect("clicked", self.on_reply_message, message, popover)
+        menu_box.append(reply_button)
+        
+        # Thread this message starts or replies in
+        if (getattr(message, 'thread_originator_guid', None)
+                or len(self.chat_service.get_thread_messages(message.guid)) > 1):
+            thread_button = Gtk.Button()
+            thread_button.set_label("View Thread")
+            thread_button.add_css_class("flat")
+            thread_button.connect("clicked", self.on_view_thread, message, popover)
+            menu_box.append(thread_button)
+        
         # Copy text
         if message.text:
             copy_button = Gtk.Button()
@@ -1218,6 +4057,25 @@ class MainWindow(Adw.ApplicationWindow):
             copy_button.connect("clicked", self.on_copy_message, message, popover)
             menu_box.append(copy_button)
         
+        # Follow-up reminder
+        reminder = self.chat_service.get_message_reminder(message.guid)
+        remind_button = Gtk.Button()
+        remind_button.add_css_class("flat")
+        if reminder:
+            remind_button.set_label("Cancel Reminder")
+            remind_button.connect("clicked", self.on_cancel_reminder, reminder, popover)
+        else:
+            remind_button.set_label("Remind Me…")
+            remind_button.connect("clicked", self.on_show_remind_menu, widget, message, popover)
+        menu_box.append(remind_button)
+        
+        # Remove from this device only
+        delete_button = Gtk.Button()
+        delete_button.set_label("Delete Locally")
+        delete_button.add_css_class("flat")
+        delete_button.connect("clicked", self.on_delete_message_locally, message, widget, popover)
+        menu_box.append(delete_button)
+        
         # Only show edit/unsend for own messages
         if message.is_from_me:
             if message.text:  # Only allow editing text messages
@@ -1240,21 +4098,178 @@ class MainWindow(Adw.ApplicationWindow):
     def on_reaction_selected(self, button, message, reaction_type, popover):
         """Handle reaction selection."""
         popover.popdown()
-        self.send_reaction_async(message.guid, reaction_type)
+        self.send_reaction_async(message.guid, reaction_type, message.chat_guid)
     
     def on_reaction_removed(self, button, message, popover):
         """Handle reaction removal."""
         popover.popdown()
-        self.remove_reaction_async(message.guid)
+        self.remove_reaction_async(message.guid, message.chat_guid)
     
     def on_copy_message(self, button, message, popover):
         """Handle copying message text."""
         popover.popdown()
+        self.copy_message_text(message)
+    
+    def copy_message_text(self, message):
+        """Copy a message's text to the clipboard."""
         if message.text:
             clipboard = Gdk.Display.get_default().get_clipboard()
             clipboard.set(message.text)
             self.show_toast("Message copied to clipboard")
     
+    def on_show_remind_menu(self, button, widget, message, popover):
+        """Offer times to be reminded about a message."""
+        popover.popdown()
+        
+        remind_popover = Gtk.Popover()
+        remind_popover.set_parent(widget)
+        remind_popover.set_position(Gtk.PositionType.TOP)
+        
+        menu_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=4)
+        menu_box.set_margin_start(8)
+        menu_box.set_margin_end(8)
+        menu_box.set_margin_top(8)
+        menu_box.set_margin_bottom(8)
+        
+        time_format = self.get_application().config_manager.get_time_format()
+        for label, remind_at in get_snooze_options(datetime.now()):
+            option_button = Gtk.Button()
+            option_button.set_label(label)
+            option_button.set_tooltip_text(describe_time_ahead(remind_at, time_format))
+            option_button.add_css_class("flat")
+            option_button.connect("clicked", self.on_remind_message, message, remind_at, remind_popover)
+            menu_box.append(option_button)
+        
+        remind_popover.set_child(menu_box)
+        remind_popover.popup()
+    
+    def on_remind_message(self, button, message, remind_at: datetime, popover):
+        """Schedule a reminder about a message."""
+        popover.popdown()
+        self.chat_service.add_reminder(message, int(remind_at.timestamp() * 1000))
+        self.get_application().arm_reminders()
+        time_format = self.get_application().config_manager.get_time_format()
+        self.show_toast(f"Reminder set for {describe_time_ahead(remind_at, time_format)}")
+    
+    def on_cancel_reminder(self, button, reminder, popover):
+        """Cancel a pending message reminder."""
+        popover.popdown()
+        self.chat_service.cancel_reminder(reminder['id'])
+        self.get_application().arm_reminders()
+        self.show_toast("Reminder cancelled")
+    
+    def on_reply_message(self, button, message, popover):
+        """Handle replying to a message."""
+        popover.popdown()
+        self.start_reply(message)
+    
+    def on_view_thread(self, button, message, popover):
+        """Handle opening the thread a message belongs to."""
+        popover.popdown()
+        chat_view = self.content_stack.get_visible_child()
+        if hasattr(chat_view, 'thread_split'):
+            self.show_thread(chat_view, message)
+    
+    def on_delete_message_locally(self, button, message, bubble, popover):
+        """Handle removing a message from this device."""
+        popover.popdown()
+        self.delete_message_locally(message, bubble)
+    
+    def on_message_key_pressed(self, controller, keyval, keycode, state, message):
+        """Handle keyboard shortcuts on a focused message bubble."""
+        # Leave shortcuts with modifiers (e.g. Ctrl+C on selected text) alone
+        if state & (Gdk.ModifierType.CONTROL_MASK | Gdk.ModifierType.ALT_MASK):
+            return False
+        
+        bubble = controller.get_widget()
+        
+        if keyval in (Gdk.KEY_r, Gdk.KEY_R):
+            self.start_reply(message)
+        elif keyval in (Gdk.KEY_e, Gdk.KEY_E):
+            self.show_reaction_popover(bubble, message)
+        elif keyval in (Gdk.KEY_c, Gdk.KEY_C):
+            self.copy_message_text(message)
+        elif keyval == Gdk.KEY_Delete:
+            self.delete_message_locally(message, bubble)
+        elif keyval == Gdk.KEY_Up:
+            self.focus_adjacent_message(bubble, -1)
+        elif keyval == Gdk.KEY_Down:
+            self.focus_adjacent_message(bubble, 1)
+        else:
+            return False
+        return True
+    
+    def focus_adjacent_message(self, bubble, direction: int):
+        """Move keyboard focus to the previous (-1) or next (1) message bubble."""
+        widget = getattr(bubble, 'message_widget', None)
+        while widget:
+            widget = widget.get_prev_sibling() if direction < 0 else widget.get_next_sibling()
+            if widget is not None and hasattr(widget, 'bubble'):
+                widget.bubble.grab_focus()
+                return
+        
+        # Moving down past the last message continues into the composer
+        if direction > 0:
+            self.focus_composer()
+    
+    def focus_composer(self):
+        """Focus the message entry of the visible chat."""
+        current_page = self.content_stack.get_visible_child()
+        input_area = getattr(current_page, 'input_area', None)
+        if input_area:
+            input_area.message_entry.grab_focus()
+    
+    def start_reply(self, message):
+        """Start replying to a message from the composer."""
+        if self.config_manager.get_api_method() != 'private':
+            self.show_toast("Replies require the Private API")
+            return
+        
+        current_page = self.content_stack.get_visible_child()
+        if not hasattr(current_page, 'reply_revealer'):
+            return
+        
+        preview = message.text or "Attachment"
+        current_page.recall_index = None
+        current_page.reply_to = message
+        current_page.reply_icon.set_from_icon_name("mail-reply-sender-symbolic")
+        current_page.reply_label.set_text(f"Replying to: {preview}")
+        current_page.reply_revealer.set_reveal_child(True)
+        self.focus_composer()
+    
+    def cancel_reply(self):
+        """Cancel replying, or recalling a sent message, in the visible chat."""
+        current_page = self.content_stack.get_visible_child()
+        if hasattr(current_page, 'reply_revealer'):
+            current_page.reply_to = None
+            current_page.recall_index = None
+            current_page.reply_revealer.set_reveal_child(False)
+    
+    def delete_message_locally(self, message, bubble):
+        """Hide a message's bubble, and on this device once the undo toast times out."""
+        message_widget = getattr(bubble, 'message_widget', None)
+        if message_widget and message_widget.get_parent():
+            # Keep keyboard focus in the list
+            sibling = message_widget.get_next_sibling() or message_widget.get_prev_sibling()
+            message_widget.set_visible(False)
+            if sibling is not None and hasattr(sibling, 'bubble'):
+                sibling.bubble.grab_focus()
+        
+        def commit():
+            self.chat_service.hide_message(message.guid)
+            if message_widget and message_widget.get_parent():
+                message_widget.get_parent().remove(message_widget)
+        
+        def revert():
+            if message_widget:
+                message_widget.set_visible(True)
+        
+        pending_actions = self.get_application().pending_actions
+        action_id = pending_actions.add("Delete message locally", commit, revert)
+        self.toast_overlay.add_toast(
+            create_undo_toast("Message removed from this device", pending_actions, action_id)
+        )
+    
     def on_edit_message(self, button, message, popover):
         """Handle editing message."""
         popover.popdown()
@@ -1263,7 +4278,7 @@ class MainWindow(Adw.ApplicationWindow):
     def on_unsend_message(self, button, message, popover):
         """Handle unsending message."""
         popover.popdown()
-        self.unsend_message_async(message.guid)
+        self.unsend_message_async(message.guid, message.chat_guid)
     
     def show_edit_dialog(self, message):
         """Show dialog to edit a message."""
@@ -1294,15 +4309,15 @@ class MainWindow(Adw.ApplicationWindow):
             if response == "save":
                 new_text = entry.get_text().strip()
                 if new_text and new_text != message.text:
-                    self.edit_message_async(message.guid, new_text)
+                    self.edit_message_async(message.guid, new_text, message.chat_guid)
         
         dialog.connect("response", on_response)
         dialog.present(self)
     
     # Async helper methods
     
-    def send_message_async(self, message_text: str):
-        """Send a message asynchronously."""
+    def send_split_message_async(self, parts: List[str], reply_to_guid: str = None):
+        """Send the parts of a split message one after another, in order."""
         if not self.current_chat:
             return
         
@@ -1311,91 +4326,160 @@ class MainWindow(Adw.ApplicationWindow):
             self.show_toast("No server configuration")
             return
         
-        def run_async():
-            try:
-                loop = asyncio.new_event_loop()
-                asyncio.set_event_loop(loop)
-                success = loop.run_until_complete(
-                    self.chat_service.send_message(
-                        config['url'], config['password'], 
-                        self.current_chat.guid, message_text
+        def on_sent(sent: int, result: SendResult):
+            if sent:
+                self.get_application().play_feedback(MESSAGE_SENT)
+                self.refresh_current_chat_messages()
+            if sent < len(parts):
+                self.show_toast(f"Sent {sent} of {len(parts)} parts; the rest failed: {result.error}")
+            return False
+        
+        self.queue_send(self.get_send_chat_guid(), parts, reply_to_guid, on_sent)
+    
+    def send_message_async(self, message_text: str, reply_to_guid: str = None):
+        """Send a message asynchronously, after any still queued for the chat."""
+        if not self.current_chat:
+            return
+        
+        config = self.get_application().config_manager.get_server_config()
+        if not config['url'] or not config['password']:
+            self.show_toast("No server configuration")
+            return
+        
+        def delayed_refresh():
+            # Force sync messages from server to get the latest
+            def sync_and_refresh():
+                try:
+                    loop = asyncio.new_event_loop()
+                    asyncio.set_event_loop(loop)
+                    loop.run_until_complete(
+                        self.chat_service.sync_conversation_messages(
+                            config['url'], config['password'], 
+                            self.current_chat, limit=50
+                        )
                     )
-                )
-                loop.close()
-                
-                if success:
-                    # Immediate refresh
-                    GLib.idle_add(self.refresh_current_chat_messages)
-                    
-                    # Schedule additional refresh after 1 second to catch any delayed messages
-                    def delayed_refresh():
-                        # Force sync messages from server to get the latest
-                        def sync_and_refresh():
-                            try:
-                                loop = asyncio.new_event_loop()
-                                asyncio.set_event_loop(loop)
-                                loop.run_until_complete(
-                                    self.chat_service.sync_chat_messages(
-                                        config['url'], config['password'], 
-                                        self.current_chat.guid, limit=50
-                                    )
-                                )
-                                loop.close()
-                                
-                                # Refresh the message view
-                                GLib.idle_add(self.refresh_current_chat_messages)
-                            except Exception as e:
-                                pass  # Silently handle delayed refresh errors
-                        
-                        # Run sync in thread to avoid blocking
-                        import threading
-                        thread = threading.Thread(target=sync_and_refresh, daemon=True)
-                        thread.start()
-                        return False  # Don't repeat the timeout
+                    loop.close()
                     
-                    # Schedule the delayed refresh for 1 second later
-                    GLib.timeout_add_seconds(1, delayed_refresh)
-                else:
-                    GLib.idle_add(lambda: self.show_toast("Failed to send message"))
+                    # Refresh the message view
+                    GLib.idle_add(self.refresh_current_chat_messages)
+                except Exception as e:
+                    pass  # Silently handle delayed refresh errors
+            
+            # Run sync in thread to avoid blocking
+            threading.Thread(target=sync_and_refresh, daemon=True).start()
+            return False  # Don't repeat the timeout
+        
+        def on_sent(sent: int, result: SendResult):
+            if not sent:
+                self.show_toast(f"Couldn't send message: {result.error}")
+                return False
+            
+            self.get_application().play_feedback(MESSAGE_SENT)
+            self.refresh_current_chat_messages()
+            # Refresh again a second later to catch any delayed messages
+            GLib.timeout_add_seconds(1, delayed_refresh)
+            return False
+        
+        self.queue_send(self.get_send_chat_guid(), [message_text], reply_to_guid, on_sent)
+    
+    def queue_send(self, chat_guid: str, texts: List[str], reply_to_guid: Optional[str],
+                   on_sent: Callable[[int, SendResult], None]):
+        """
+        Send texts to a chat through the outbox, after anything already queued for it.
+        
+        The texts go one after another, each retried if the server was briefly
+        unreachable, stopping at the first that fails for good. on_sent gets how
+        many went and the last result, on the main loop.
+        """
+        config = self.get_application().config_manager.get_server_config()
+        
+        def send(text: str, reply_guid: Optional[str]) -> SendResult:
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            try:
+                return loop.run_until_complete(
+                    self.chat_service.send_message(config['url'], config['password'], chat_guid, text, reply_guid)
+                )
             except Exception as e:
-                GLib.idle_add(lambda: self.show_toast(f"Error: {e}"))
+                return SendResult(error=str(e))
+            finally:
+                loop.close()
         
-        thread = threading.Thread(target=run_async, daemon=True)
-        thread.start()
+        def job():
+            sent, result = 0, SendResult()
+            for number, text in enumerate(texts):
+                # Only the first part answers the message being replied to
+                reply_guid = reply_to_guid if number == 0 else None
+                result = send_with_retries(lambda: send(text, reply_guid))
+                if not result:
+                    break
+                sent += 1
+            return sent, result
+        
+        def on_done(outcome, error: Optional[Exception]):
+            sent, result = outcome if error is None else (0, SendResult(error=str(error)))
+            GLib.idle_add(on_sent, sent, result)
+        
+        self.get_application().outbox.submit(chat_guid, job, on_done)
     
-    def send_attachment_async(self, file_path: str):
-        """Send an attachment asynchronously."""
+    def send_attachment_async(self, file_path: str, caption: str = "", is_audio_message: bool = False,
+                              temp_dir: tempfile.TemporaryDirectory = None):
+        """
+        Send an attachment asynchronously, with an optional caption.
+        
+        temp_dir, if given, holds a file made just for this send and is
+        removed once the upload is over.
+        """
         if not self.current_chat:
+            if temp_dir:
+                temp_dir.cleanup()
             return
         
         config = self.get_application().config_manager.get_server_config()
         if not config['url'] or not config['password']:
+            if temp_dir:
+                temp_dir.cleanup()
             self.show_toast("No server configuration")
             return
         
-        def run_async():
+        file_name = os.path.basename(file_path)
+        chat_guid = self.get_send_chat_guid()
+        
+        def on_progress(sent: int, size: int):
+            GLib.idle_add(self.show_upload_progress, file_name, sent, size)
+        
+        def job():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
             try:
-                loop = asyncio.new_event_loop()
-                asyncio.set_event_loop(loop)
-                success = loop.run_until_complete(
+                return loop.run_until_complete(
                     self.chat_service.send_attachment(
-                        config['url'], config['password'], 
-                        self.current_chat.guid, file_path
+                        config['url'], config['password'],
+                        chat_guid, file_path, caption, is_audio_message,
+                        on_progress
                     )
                 )
+            finally:
                 loop.close()
-                
-                if success:
-                    # Refresh the message view
-                    GLib.idle_add(self.refresh_current_chat_messages)
-                    GLib.idle_add(lambda: self.show_toast("Image sent"))
-                else:
-                    GLib.idle_add(lambda: self.show_toast("Failed to send image"))
-            except Exception as e:
-                GLib.idle_add(lambda: self.show_toast(f"Error: {e}"))
+                if temp_dir:
+                    temp_dir.cleanup()
+        
+        def on_done(success, error: Optional[Exception]):
+            GLib.idle_add(self.show_upload_progress, file_name, 0, 0)
+            if isinstance(error, BlueBubblesUploadTooLargeError):
+                GLib.idle_add(self.show_toast, str(error))
+            elif error is not None:
+                GLib.idle_add(self.show_toast, f"Error: {error}")
+            elif success:
+                GLib.idle_add(self.get_application().play_feedback, MESSAGE_SENT)
+                # Refresh the message view
+                GLib.idle_add(self.refresh_current_chat_messages)
+                GLib.idle_add(self.show_toast, "Attachment sent")
+            else:
+                GLib.idle_add(self.show_toast, f"Failed to send {file_name}")
         
-        thread = threading.Thread(target=run_async, daemon=True)
-        thread.start()
+        # Queued with the chat's text sends, so everything arrives in the order composed
+        self.get_application().outbox.submit(chat_guid, job, on_done)
     
     def send_typing_indicator_async(self, typing: bool):
         """Send typing indicator asynchronously."""
@@ -1413,7 +4497,7 @@ class MainWindow(Adw.ApplicationWindow):
                 loop.run_until_complete(
                     self.chat_service.send_typing_indicator(
                         config['url'], config['password'], 
-                        self.current_chat.guid, typing
+                        self.get_send_chat_guid(), typing
                     )
                 )
                 loop.close()
@@ -1429,6 +4513,12 @@ class MainWindow(Adw.ApplicationWindow):
         if not config['url'] or not config['password']:
             return
         
+        def set_texture(texture: Gdk.Texture):
+            # Check if the widget is still valid
+            if image_widget and not image_widget.get_parent() is None:
+                image_widget.set_from_paintable(texture)
+            return False
+        
         def run_async():
             try:
                 loop = asyncio.new_event_loop()
@@ -1443,6 +4533,32 @@ class MainWindow(Adw.ApplicationWindow):
                             config['url'], config['password'], chat.guid
                         )
                     )
+                    
+                    # Without one, stack the participants' avatars
+                    if not avatar_data:
+                        key = participant_key(chat)
+                        texture = self.group_avatar_textures.get(key)
+                        if texture:
+                            loop.close()
+                            GLib.idle_add(set_texture, texture)
+                            return
+                        
+                        stacked = loop.run_until_complete(
+                            self.chat_service.get_group_avatar(config['url'], config['password'], chat)
+                        )
+                        if stacked:
+                            loop.close()
+                            
+                            def cache_stack():
+                                try:
+                                    texture = Gdk.Texture.new_from_bytes(GLib.Bytes.new(stacked))
+                                except GLib.Error:
+                                    return False
+                                self.group_avatar_textures[key] = texture
+                                return set_texture(texture)
+                            
+                            GLib.idle_add(cache_stack)
+                            return
                 else:
                     # For individual chats, use the first participant's address
                     participants = chat.participants
@@ -1511,15 +4627,16 @@ class MainWindow(Adw.ApplicationWindow):
         thread = threading.Thread(target=run_async, daemon=True)
         thread.start()
     
-    def send_reaction_async(self, message_guid: str, reaction_type: str):
-        """Send a reaction asynchronously."""
+    def send_reaction_async(self, message_guid: str, reaction_type: str, chat_guid: str = None):
+        """Send a reaction asynchronously, to the message's chat or the current one."""
         config = self.get_application().config_manager.get_server_config()
         if not config['url'] or not config['password']:
             self.show_toast("No server configuration")
             return
         
-        # Get the current chat GUID
-        chat_guid = self.current_chat.guid if self.current_chat else None
+        # Fall back to the current chat GUID
+        if not chat_guid:
+            chat_guid = self.current_chat.guid if self.current_chat else None
         # print(f"🎭 UI: Starting send reaction - guid={message_guid}, type={reaction_type}, chat_guid={chat_guid}")
         
         def run_async():
@@ -1549,15 +4666,16 @@ class MainWindow(Adw.ApplicationWindow):
         thread = threading.Thread(target=run_async, daemon=True)
         thread.start()
     
-    def remove_reaction_async(self, message_guid: str):
-        """Remove a reaction asynchronously."""
+    def remove_reaction_async(self, message_guid: str, chat_guid: str = None):
+        """Remove a reaction asynchronously, from the message's chat or the current one."""
         config = self.get_application().config_manager.get_server_config()
         if not config['url'] or not config['password']:
             self.show_toast("No server configuration")
             return
         
-        # Get the current chat GUID
-        chat_guid = self.current_chat.guid if self.current_chat else None
+        # Fall back to the current chat GUID
+        if not chat_guid:
+            chat_guid = self.current_chat.guid if self.current_chat else None
         # print(f"🎭 UI: Starting remove reaction - guid={message_guid}, chat_guid={chat_guid}")
         
         def run_async():
@@ -1587,10 +4705,11 @@ class MainWindow(Adw.ApplicationWindow):
         thread = threading.Thread(target=run_async, daemon=True)
         thread.start()
     
-    def edit_message_async(self, message_guid: str, new_text: str):
+    def edit_message_async(self, message_guid: str, new_text: str, chat_guid: str = None):
         """Edit a message asynchronously."""
         if not self.current_chat:
             return
+        chat_guid = chat_guid or self.current_chat.guid
         
         config = self.get_application().config_manager.get_server_config()
         if not config['url'] or not config['password']:
@@ -1604,7 +4723,7 @@ class MainWindow(Adw.ApplicationWindow):
                 success = loop.run_until_complete(
                     self.chat_service.edit_message(
                         config['url'], config['password'], 
-                        message_guid, new_text, self.current_chat.guid
+                        message_guid, new_text, chat_guid
                     )
                 )
                 loop.close()
@@ -1621,10 +4740,11 @@ class MainWindow(Adw.ApplicationWindow):
         thread = threading.Thread(target=run_async, daemon=True)
         thread.start()
     
-    def unsend_message_async(self, message_guid: str):
+    def unsend_message_async(self, message_guid: str, chat_guid: str = None):
         """Unsend a message asynchronously."""
         if not self.current_chat:
             return
+        chat_guid = chat_guid or self.current_chat.guid
         
         config = self.get_application().config_manager.get_server_config()
         if not config['url'] or not config['password']:
@@ -1638,7 +4758,7 @@ class MainWindow(Adw.ApplicationWindow):
                 success = loop.run_until_complete(
                     self.chat_service.unsend_message(
                         config['url'], config['password'], 
-                        message_guid, self.current_chat.guid
+                        message_guid, chat_guid
                     )
                 )
                 loop.close()
@@ -1741,9 +4861,12 @@ class MainWindow(Adw.ApplicationWindow):
             async with BlueBubblesClient(url, password, api_method) as client:
                 server_info = await client.get_server_info()
                 version = server_info.get('server_version', 'Unknown')
+                self.chat_service.log_activity('server', f"Connected to server version {version}")
                 
                 def update_title():
                     self.set_title(f"BlueBubbles - Server v{version}")
+                    self.chat_service.model.set_server_version(version)
+                    self.update_content_title()
                 
                 GLib.idle_add(update_title)
         
@@ -1763,9 +4886,18 @@ class MainWindow(Adw.ApplicationWindow):
         
         # Add callback for new message notifications
         self.chat_service.add_new_message_callback(self.on_new_message_detected)
-        
-        # Get message check interval from config (default 3 seconds)
-        check_interval = self.config_manager.get('app.message_check_interval', 3)
+        self.chat_service.add_rate_limit_callback(self.on_rate_limited)
+        self.chat_service.add_connection_callback(self.on_connection_changed)
+        self.chat_service.add_server_update_callback(self.on_server_update_available)
+        self.chat_service.add_account_callback(self.on_imessage_account_changed)
+        self.chat_service.add_sync_state_callback(self.on_chat_sync_state_changed)
+        self.chat_service.add_read_elsewhere_callback(self.on_chat_read_elsewhere)
+        self.chat_service.add_messages_changed_callback(self.on_messages_changed)
+        self.chat_service.add_snooze_wake_callback(self.on_snooze_woke)
+        self.chat_service.add_server_event_callback(self.on_server_event)
+        
+        # Get message check interval for the active power profile
+        check_interval = self.get_application().get_message_check_interval()
         
         # Start the monitoring (ChatService handles the threading)
         self.chat_service.start_message_checking(
@@ -1776,12 +4908,137 @@ class MainWindow(Adw.ApplicationWindow):
         
         # print("🚀 Message monitoring started in background")
     
+    def on_rate_limited(self, category: str, seconds: float):
+        """Show the busy banner until the server is ready again."""
+        def update_ui():
+            self.busy_banner.set_revealed(True)
+            if self.busy_banner_timeout_id:
+                GLib.source_remove(self.busy_banner_timeout_id)
+            self.busy_banner_timeout_id = GLib.timeout_add_seconds(
+                math.ceil(seconds) + 1, self.hide_busy_banner
+            )
+        
+        GLib.idle_add(update_ui)
+    
+    def on_sync_progress(self, progress: Optional[SyncProgress]):
+        """Called from sync threads as a long sync advances."""
+        GLib.idle_add(self.show_sync_progress, progress)
+    
+    def show_sync_progress(self, progress: Optional[SyncProgress]):
+        """Update the progress bar under the header, hiding it once the sync ends."""
+        if progress is None:
+            self.sync_progress_bar.set_visible(False)
+            return False
+        
+        if progress.fraction is None:
+            self.sync_progress_bar.pulse()
+        else:
+            self.sync_progress_bar.set_fraction(progress.fraction)
+        self.sync_progress_bar.set_tooltip_text(progress.label)
+        self.sync_progress_bar.set_visible(True)
+        
+        if progress.stage == 'chats':
+            self.sidebar_loading_page.set_description(progress.label)
+        return False
+    
+    def show_upload_progress(self, file_name: str, sent: int, size: int):
+        """Show an upload's progress under the header, hiding it when size is 0."""
+        if not size:
+            self.sync_progress_bar.set_visible(False)
+            return False
+        
+        fraction = min(sent / size, 1.0)
+        self.sync_progress_bar.set_fraction(fraction)
+        self.sync_progress_bar.set_tooltip_text(f"Uploading {file_name} {int(fraction * 100)}%")
+        self.sync_progress_bar.set_visible(True)
+        return False
+    
+    def on_connection_changed(self, reachable: bool):
+        """Called from the message check thread when the server goes away or comes back."""
+        def update_ui():
+            self.update_content_title()
+            if reachable:
+                self.connection_banner.set_revealed(False)
+                if self.reconnect_countdown_id:
+                    GLib.source_remove(self.reconnect_countdown_id)
+                    self.reconnect_countdown_id = None
+                self.show_toast("Reconnected to the server")
+                # It may be running a different version after a restart
+                self.load_server_info()
+            else:
+                self.reconnect_at = time.monotonic() + self.chat_service.check_interval
+                self.update_reconnect_countdown()
+                self.connection_banner.set_revealed(True)
+                if not self.reconnect_countdown_id:
+                    self.reconnect_countdown_id = GLib.timeout_add_seconds(1, self.update_reconnect_countdown)
+            return False
+        
+        GLib.idle_add(update_ui)
+    
+    def update_reconnect_countdown(self):
+        """Count down to the next attempt to reach the server."""
+        remaining = math.ceil(self.reconnect_at - time.monotonic())
+        if remaining <= 0:
+            self.connection_banner.set_title("Server unreachable, reconnecting…")
+            # Attempts repeat every check interval until one gets through
+            self.reconnect_at = time.monotonic() + self.chat_service.check_interval
+        else:
+            self.connection_banner.set_title(f"Server unreachable, reconnecting in {remaining} s")
+        return True
+    
+    def on_server_update_available(self, version: Optional[str]):
+        """Called from the message check thread when a server update is found or installed."""
+        def update_ui():
+            if version:
+                self.update_banner.set_title(f"BlueBubbles server {version} available")
+            self.update_banner.set_revealed(bool(version))
+            return False
+        
+        GLib.idle_add(update_ui)
+    
+    def on_imessage_account_changed(self, signed_out: bool):
+        """Called from the message check thread when the server's iMessage account signs out or back in."""
+        def update_ui():
+            self.account_banner.set_revealed(signed_out)
+            application = self.get_application()
+            if signed_out:
+                notification = Gio.Notification.new("iMessage Signed Out")
+                notification.set_body("Your BlueBubbles server is signed out of iMessage. "
+                                      "Sign in again on the Mac to keep sending messages.")
+                notification.set_priority(Gio.NotificationPriority.HIGH)
+                application.send_notification("imessage-account", notification)
+            else:
+                application.withdraw_notification("imessage-account")
+            return False
+        
+        GLib.idle_add(update_ui)
+    
+    def on_release_notes_clicked(self, banner):
+        """Open the release notes of the available server version."""
+        version = self.chat_service.available_server_update
+        if version:
+            url = SERVER_RELEASE_NOTES_URL.format(version=version.lstrip('v'))
+            Gtk.UriLauncher.new(url).launch(self, None, None, None)
+    
+    def hide_busy_banner(self):
+        """Hide the busy banner once no request category is paused."""
+        self.busy_banner_timeout_id = None
+        if self.chat_service.is_paused('sync') or self.chat_service.is_paused('avatars'):
+            self.busy_banner_timeout_id = GLib.timeout_add_seconds(1, self.hide_busy_banner)
+        else:
+            self.busy_banner.set_revealed(False)
+        return False
+    
     def on_new_message_detected(self, chat_guid: str):
         """Called when a new message is detected in a chat."""
         # print(f"📨 New message detected in chat: {chat_guid}")
         
         # Update the UI on the main thread
         def update_ui():
+            # Messages arriving in the open conversation are read right away
+            if self.current_chat and chat_guid in self.current_chat.guids:
+                self.chat_service.mark_chat_read_locally(chat_guid)
+            
             # Get the updated chat with the new message
             updated_chat = self.chat_service.get_chat_by_guid(chat_guid)
             if not updated_chat:
@@ -1792,37 +5049,107 @@ class MainWindow(Adw.ApplicationWindow):
             self.move_chat_to_top(updated_chat)
             
             # If this is the currently selected chat, refresh the messages
-            if self.current_chat and self.current_chat.guid == chat_guid:
+            if self.current_chat and chat_guid in self.current_chat.guids:
                 self.refresh_current_chat_messages()
+                
+                # Codes arriving in the chat in front of the user can go straight to the clipboard
+                code = find_chat_code(updated_chat)
+                if code and self.is_active() and self.config_manager.get_auto_copy_codes():
+                    self.copy_code(code)
             
-            # Show a toast notification
-            chat_name = updated_chat.display_name if updated_chat.display_name else chat_guid[:8]
-            self.show_toast(f"New message in {chat_name}")
+            # Labels can quieten or silence a chat's notifications
+            policy = self.chat_service.get_chat_notification_policy(updated_chat)
+            if policy == 'none':
+                return
+            
+            # Notify on the desktop when none of our windows is in front,
+            # otherwise show a toast. Desktop notifications bring their own sound.
+            if not any(window.is_active() for window in self.get_application().get_windows()):
+                if not updated_chat.last_message_from_me:
+                    self.get_application().notify_new_message(updated_chat, quiet=policy == 'quiet')
+            else:
+                if not updated_chat.last_message_from_me and policy == 'all':
+                    self.get_application().play_feedback(MESSAGE_RECEIVED)
+                chat_name = updated_chat.display_name if updated_chat.display_name else chat_guid[:8]
+                self.show_toast(f"New message in {chat_name}")
+        
+        GLib.idle_add(update_ui)
+    
+    def on_chat_read_elsewhere(self, chat_guid: str):
+        """Called when a chat was read on another device; clears its badge and notification."""
+        def update_ui():
+            self.update_chat_row(chat_guid)
+            chat = self.chat_service.get_chat_by_guid(chat_guid)
+            if chat and not chat.unread_count:
+                self.get_application().withdraw_notification(f"chat-{chat.guid}")
+            return False
+        
+        GLib.idle_add(update_ui)
+    
+    def on_server_event(self, event):
+        """Called from the event stream thread with each live event from the server."""
+        if isinstance(event, TypingIndicator):
+            GLib.idle_add(self.set_chat_typing, event.chat_guid, event.typing)
+        elif isinstance(event, NewMessage) and event.chat_guid:
+            # Their message arrived, so they're done typing it
+            GLib.idle_add(self.set_chat_typing, event.chat_guid, False)
+        elif isinstance(event, MessageSendError) and event.chat_guid:
+            GLib.idle_add(self.on_message_send_failed, event.chat_guid)
+        elif isinstance(event, Disconnected):
+            # Nothing will say they stopped, so don't leave them showing
+            for chat_guid in list(self.typing_chats):
+                GLib.idle_add(self.set_chat_typing, chat_guid, False)
+    
+    def set_chat_typing(self, chat_guid: str, typing: bool):
+        """Show or hide that someone is typing, if the chat is open."""
+        if typing:
+            self.typing_chats.add(chat_guid)
+        else:
+            self.typing_chats.discard(chat_guid)
+        if self.current_chat and chat_guid in self.current_chat.guids:
+            self.update_content_title()
+        return False
+    
+    def on_message_send_failed(self, chat_guid: str):
+        """Tell the user a message the server accepted wasn't delivered."""
+        chat = self.chat_service.get_chat_by_guid(chat_guid)
+        self.show_toast(f"A message to {chat.display_title if chat else 'a chat'} wasn't delivered")
+        return False
+    
+    def on_messages_changed(self, chat_guid: str):
+        """Called when cached messages of a chat were unsent or deleted elsewhere."""
+        def update_ui():
+            self.update_chat_row(chat_guid)
+            if self.current_chat and chat_guid in self.current_chat.guids:
+                self.refresh_current_chat_messages()
+            return False
         
         GLib.idle_add(update_ui)
     
     def move_chat_to_top(self, updated_chat):
         """Move a chat to the top of the list and update its preview."""
-        # Find the existing chat in our local list
+        # Find the existing chat in the list
         chat_index = -1
         for i, chat in enumerate(self.chats):
             if chat.guid == updated_chat.guid:
                 chat_index = i
                 break
         
-        if chat_index >= 0:
-            # Remove the old chat from the list
-            old_chat = self.chats.pop(chat_index)
-            # print(f"📌 Moving chat {updated_chat.display_title} to top (was at index {chat_index})")
-        else:
-            pass  # Silently handle adding new chat to top
-        
-        # Add the updated chat to the beginning
-        self.chats.insert(0, updated_chat)
+        # Move the updated chat to the beginning, below any pinned chats
+        self.chat_service.model.update_chat(updated_chat, self.get_chat_insert_index(updated_chat))
         
         # Update the UI list efficiently
         self.update_chat_list_order(updated_chat, chat_index)
     
+    def get_chat_insert_index(self, chat) -> int:
+        """Get the list position for a chat with new activity."""
+        # Pinned chats that aren't loaded have no row to count
+        listed = {c.guid for c in self.chats}
+        pinned = [guid for guid in self.pinned_guids if guid in listed or guid == chat.guid]
+        if chat.guid in pinned:
+            return pinned.index(chat.guid)
+        return len(pinned)
+    
     def update_chat_list_order(self, updated_chat, old_index):
         """Efficiently update the chat list order without full rebuild."""
         # Find the existing row in the UI
@@ -1845,7 +5172,7 @@ class MainWindow(Adw.ApplicationWindow):
         
         # Create a new row with updated data and insert at the top
         new_row = self.create_chat_row(updated_chat)
-        self.chat_list.insert(new_row, 0)
+        self.chat_list.insert(new_row, self.get_chat_insert_index(updated_chat))
         # print(f"⬆️ Moved {updated_chat.display_title} to top of chat list")
         
         # Update the selection if this was the current chat
@@ -1862,8 +5189,12 @@ class MainWindow(Adw.ApplicationWindow):
         
         # print(f"🔄 Refreshing messages for current chat: {self.current_chat.display_title}")
         
-        # Reload messages from cache (they should already be updated by the background task)
-        messages = self.chat_service.get_cached_chat_messages(self.current_chat.guid, limit=50)
+        # Reload messages from cache (they should already be updated by the background task).
+        # Leave the view and its scroll position alone if nothing changed since it last loaded.
+        shown_messages = self.chat_service.model.get_messages(self.current_chat.guid)
+        messages = self.chat_service.get_cached_conversation_messages(self.current_chat, limit=50)
+        if messages == shown_messages:
+            return
         # print(f"📥 Retrieved {len(messages)} messages from cache")
         
         # Update the message list using the correct chat view name
@@ -1925,14 +5256,20 @@ class MainWindow(Adw.ApplicationWindow):
             # print(f"➕ Adding {len(messages_to_add)} new messages to chat")
             
             # Sort by date to maintain chronological order
-            messages_to_add.sort(key=lambda m: m.date_created)
+            messages_to_add.sort(key=lambda m: m.sort_key)
+            
+            # The last message shown decides whether the first new one needs a sender name
+            last_child = messages_box.get_last_child()
+            previous_message = getattr(getattr(last_child, 'bubble', None), 'message', None)
             
             # Add new messages at the bottom
             for message in messages_to_add:
-                message_widget = self.create_message_widget(message)
+                self.append_date_separator(messages_box, previous_message, message)
+                message_widget = self.create_message_widget(message, previous_message)
                 # Store the message GUID for future reference
                 message_widget.message_guid = message.guid
                 messages_box.append(message_widget)
+                previous_message = message
                 # print(f"➕ Added message widget for: {message.guid}")
         else:
             # Silently handle case where no new messages need to be added
@@ -1973,6 +5310,29 @@ class MainWindow(Adw.ApplicationWindow):
                 pass
             child = child.get_next_sibling()
     
+    def on_close_request(self, window):
+        """Hide instead of closing when running in the background."""
+        if self.get_application().config_manager.get_run_in_background():
+            self.set_visible(False)
+            return True
+        self.save_state()
+        return False
+    
+    def save_state(self):
+        """Save the drafts in open conversations' composers, and the window's size."""
+        self.save_drafts()
+        width, height = self.get_default_size()
+        self.config_manager.set_window_state(width, height, self.is_maximized())
+    
+    def save_drafts(self):
+        """Save the drafts in open conversations' composers, so they're back after a restart."""
+        child = self.content_stack.get_first_child()
+        while child:
+            chat_guid = getattr(child, 'chat_guid', None)
+            if chat_guid:
+                self.chat_service.set_draft(chat_guid, child.input_area.message_entry.get_text())
+            child = child.get_next_sibling()
+    
     def on_window_destroy(self, window):
         """Called when the window is being destroyed."""
         # print("🛑 Window destroying, stopping message monitoring...")
@@ -1980,5 +5340,15 @@ class MainWindow(Adw.ApplicationWindow):
         # Stop message monitoring
         self.chat_service.stop_message_checking()
         
-        # Remove callback
+        # Remove callbacks
         self.chat_service.remove_new_message_callback(self.on_new_message_detected)
+        self.chat_service.remove_rate_limit_callback(self.on_rate_limited)
+        self.chat_service.remove_connection_callback(self.on_connection_changed)
+        self.chat_service.remove_server_update_callback(self.on_server_update_available)
+        self.chat_service.remove_account_callback(self.on_imessage_account_changed)
+        self.chat_service.remove_sync_state_callback(self.on_chat_sync_state_changed)
+        self.chat_service.remove_read_elsewhere_callback(self.on_chat_read_elsewhere)
+        self.chat_service.remove_messages_changed_callback(self.on_messages_changed)
+        self.chat_service.remove_snooze_wake_callback(self.on_snooze_woke)
+        self.chat_service.remove_server_event_callback(self.on_server_event)
+        self.chat_service.remove_sync_progress_callback(self.on_sync_progress)
@@ -0,0 +1,194 @@
+"""
+Media Viewer Dialog
+Full-size viewer for a chat's images and videos, with arrow-key navigation
+and a slideshow
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw, Gdk, Gio, GLib
+from typing import Callable, List, Optional, Tuple
+
+from ..db.models import MediaItem
+from ..utils.features import get_missing_reason, is_feature_enabled
+
+# Seconds each item is shown in the slideshow
+SLIDESHOW_SECONDS = 4
+
+
+class MediaViewerDialog(Adw.Dialog):
+    """Browse the images and videos of a chat."""
+
+    def __init__(self, media: List[MediaItem], index: int,
+                 load_attachment: Callable[[str, Callable[[Optional[str]], None]], None],
+                 describe: Callable[[MediaItem], Tuple[str, str]]):
+        super().__init__()
+
+        self.media = media
+        self.index = index
+        # Fetches an attachment's file by GUID and calls back on the main loop with its path
+        self.load_attachment = load_attachment
+        # Gets the (sender, date) shown for an item
+        self.describe = describe
+        self.slideshow_source = None
+
+        self.set_content_width(900)
+        self.set_content_height(700)
+
+        self.setup_ui()
+        self.connect("closed", self.on_closed)
+        self.show_item(index)
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        toolbar_view = Adw.ToolbarView()
+
+        header_bar = Adw.HeaderBar()
+        self.title_widget = Adw.WindowTitle()
+        header_bar.set_title_widget(self.title_widget)
+
+        self.slideshow_button = Gtk.ToggleButton()
+        self.slideshow_button.set_icon_name("media-playback-start-symbolic")
+        self.slideshow_button.set_tooltip_text("Slideshow (Space)")
+        self.slideshow_button.connect("toggled", self.on_slideshow_toggled)
+        header_bar.pack_start(self.slideshow_button)
+        toolbar_view.add_top_bar(header_bar)
+
+        self.content_stack = Gtk.Stack()
+        self.content_stack.set_vexpand(True)
+
+        self.picture = Gtk.Picture()
+        self.picture.set_content_fit(Gtk.ContentFit.CONTAIN)
+        self.picture.set_can_shrink(True)
+        self.content_stack.add_named(self.picture, "picture")
+
+        self.video = Gtk.Video()
+        self.video.set_autoplay(True)
+        self.content_stack.add_named(self.video, "video")
+
+        spinner = Gtk.Spinner()
+        spinner.set_spinning(True)
+        spinner.set_halign(Gtk.Align.CENTER)
+        spinner.set_valign(Gtk.Align.CENTER)
+        self.content_stack.add_named(spinner, "loading")
+
+        error_page = Adw.StatusPage()
+        error_page.set_icon_name("image-missing-symbolic")
+        error_page.set_title("Couldn't Load This Attachment")
+        self.content_stack.add_named(error_page, "error")
+
+        no_video_page = Adw.StatusPage()
+        no_video_page.set_icon_name("video-x-generic-symbolic")
+        no_video_page.set_title("Videos Can't Play Here")
+        no_video_page.set_description(f"Video playback isn't available: {get_missing_reason('media')}")
+        self.content_stack.add_named(no_video_page, "no-video")
+
+        toolbar_view.set_content(self.content_stack)
+
+        # Navigation
+        nav_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=12)
+        nav_box.set_halign(Gtk.Align.CENTER)
+        nav_box.set_margin_top(6)
+        nav_box.set_margin_bottom(6)
+
+        self.prev_button = Gtk.Button()
+        self.prev_button.set_icon_name("go-previous-symbolic")
+        self.prev_button.set_tooltip_text("Previous (Left)")
+        self.prev_button.connect("clicked", lambda b: self.show_item(self.index - 1))
+        nav_box.append(self.prev_button)
+
+        self.position_label = Gtk.Label()
+        self.position_label.add_css_class("numeric")
+        nav_box.append(self.position_label)
+
+        self.next_button = Gtk.Button()
+        self.next_button.set_icon_name("go-next-symbolic")
+        self.next_button.set_tooltip_text("Next (Right)")
+        self.next_button.connect("clicked", lambda b: self.show_item(self.index + 1))
+        nav_box.append(self.next_button)
+
+        toolbar_view.add_bottom_bar(nav_box)
+        self.set_child(toolbar_view)
+
+        key_controller = Gtk.EventControllerKey()
+        key_controller.set_propagation_phase(Gtk.PropagationPhase.CAPTURE)
+        key_controller.connect("key-pressed", self.on_key_pressed)
+        self.add_controller(key_controller)
+
+    def show_item(self, index: int):
+        """Show the item at an index and start loading it."""
+        if index < 0 or index >= len(self.media):
+            return
+
+        self.index = index
+        item = self.media[index]
+        sender, date_text = self.describe(item)
+        self.title_widget.set_title(sender)
+        self.title_widget.set_subtitle(date_text)
+        self.position_label.set_text(f"{index + 1} of {len(self.media)}")
+        self.prev_button.set_sensitive(index > 0)
+        self.next_button.set_sensitive(index < len(self.media) - 1)
+
+        self.video.set_media_stream(None)
+        if item.mime_type.startswith('video/') and not is_feature_enabled('media'):
+            self.content_stack.set_visible_child_name("no-video")
+            return
+        self.content_stack.set_visible_child_name("loading")
+        self.load_attachment(item.guid, lambda path: self.on_item_loaded(item, path))
+
+    def on_item_loaded(self, item: MediaItem, path: Optional[str]):
+        """Show an item's file once it arrives, unless the viewer moved on."""
+        if item is not self.media[self.index]:
+            return
+        if not path:
+            self.content_stack.set_visible_child_name("error")
+            return
+
+        try:
+            if item.mime_type.startswith('video/'):
+                self.video.set_file(Gio.File.new_for_path(path))
+                self.content_stack.set_visible_child_name("video")
+            else:
+                self.picture.set_paintable(Gdk.Texture.new_from_filename(path))
+                self.content_stack.set_visible_child_name("picture")
+        except Exception:
+            self.content_stack.set_visible_child_name("error")  # A format GTK can't show
+
+    def on_key_pressed(self, controller, keyval, keycode, state):
+        """Step through the media with the arrow keys, Home and End; Space toggles the slideshow."""
+        if keyval == Gdk.KEY_Left:
+            self.show_item(self.index - 1)
+        elif keyval == Gdk.KEY_Right:
+            self.show_item(self.index + 1)
+        elif keyval == Gdk.KEY_Home:
+            self.show_item(0)
+        elif keyval == Gdk.KEY_End:
+            self.show_item(len(self.media) - 1)
+        elif keyval == Gdk.KEY_space:
+            self.slideshow_button.set_active(not self.slideshow_button.get_active())
+        else:
+            return False
+        return True
+
+    def on_slideshow_toggled(self, button):
+        """Start or stop advancing through the media on a timer."""
+        if self.slideshow_source:
+            GLib.source_remove(self.slideshow_source)
+            self.slideshow_source = None
+        if button.get_active():
+            button.set_icon_name("media-playback-pause-symbolic")
+            self.slideshow_source = GLib.timeout_add_seconds(SLIDESHOW_SECONDS, self.advance_slideshow)
+        else:
+            button.set_icon_name("media-playback-start-symbolic")
+
+    def advance_slideshow(self):
+        """Show the next item, starting over after the last."""
+        self.show_item((self.index + 1) % len(self.media))
+        return True
+
+    def on_closed(self, dialog):
+        """Stop the slideshow and the video."""
+        self.slideshow_button.set_active(False)
+        self.video.set_media_stream(None)
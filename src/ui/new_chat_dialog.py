@@ -11,23 +11,35 @@ from gi.repository import Gtk, Adw, GLib
 import asyncio
 import threading
 from ..api.client import BlueBubblesClient, BlueBubblesAPIError
+from ..utils.address_check import REACHABILITY_LABELS, get_reachability, is_valid_address, parse_addresses
+
+# Milliseconds to wait after typing stops before checking addresses with the server
+AVAILABILITY_CHECK_DELAY = 600
 
 
 class NewChatDialog(Adw.Dialog):
     """Dialog for creating new chats."""
     
-    def __init__(self, parent_window, config_manager):
+    def __init__(self, parent_window, config_manager, address: str = "", message: str = ""):
         super().__init__()
         
         self.parent_window = parent_window
         self.config_manager = config_manager
         self.contacts = []
+        # Address -> how a new chat would reach it, see get_reachability
+        self.reachability = {}
+        self.availability_timeout_id = None
+        # Bumped on each edit so checks of older text are ignored
+        self.availability_generation = 0
         
         self.set_title("New Chat")
         self.set_content_width(400)
         self.set_content_height(300)
         
         self.setup_ui()
+        # Pre-filled when opened from a link
+        self.contact_entry.set_text(address)
+        self.message_entry.set_text(message)
         self.load_contacts()
     
     def setup_ui(self):
@@ -65,6 +77,10 @@ class NewChatDialog(Adw.Dialog):
         self.contact_entry.set_hexpand(True)
         entry_box.append(self.contact_entry)
         
+        # How each typed address will be reached: iMessage, SMS, or not at all
+        self.availability_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=2)
+        entry_box.append(self.availability_box)
+        
         # Dropdown for existing contacts
         self.contacts_dropdown = Gtk.DropDown()
         self.contacts_dropdown.set_hexpand(True)
@@ -127,6 +143,7 @@ class NewChatDialog(Adw.Dialog):
         content_box.append(button_box)
         
         # Connect entry text changes to validate input
+        self.contact_entry.connect("changed", self.on_address_changed)
         self.contact_entry.connect("changed", self.on_entry_changed)
         self.message_entry.connect("changed", self.on_entry_changed)
         
@@ -149,10 +166,83 @@ class NewChatDialog(Adw.Dialog):
     
     def on_entry_changed(self, entry):
         """Handle entry text changes to validate input."""
-        contact_text = self.contact_entry.get_text().strip()
+        addresses = parse_addresses(self.contact_entry.get_text())
         message_text = self.message_entry.get_text().strip()
-        # Enable create button if both fields have text
-        self.create_button.set_sensitive(len(contact_text) > 0 and len(message_text) > 0)
+        # Enable create button once every address is known to be reachable, or couldn't be checked
+        reachable = all(self.reachability.get(address) in ('imessage', 'sms', 'unknown') for address in addresses)
+        self.create_button.set_sensitive(bool(addresses) and reachable and len(message_text) > 0)
+    
+    def on_address_changed(self, entry):
+        """Check the typed addresses once typing pauses."""
+        self.availability_generation += 1
+        if self.availability_timeout_id:
+            GLib.source_remove(self.availability_timeout_id)
+        
+        addresses = parse_addresses(entry.get_text())
+        # Malformed addresses are flagged straight away; the rest wait for the server
+        self.reachability = {
+            address: 'checking' if is_valid_address(address) else 'invalid' for address in addresses
+        }
+        self.show_reachability()
+        self.availability_timeout_id = GLib.timeout_add(AVAILABILITY_CHECK_DELAY, self.check_availability)
+    
+    def check_availability(self):
+        """Ask the server which of the typed addresses can receive iMessages."""
+        self.availability_timeout_id = None
+        generation = self.availability_generation
+        addresses = [address for address, state in self.reachability.items() if state == 'checking']
+        config = self.config_manager.get_server_config()
+        chat_service = getattr(self.parent_window, 'chat_service', None)
+        if not addresses:
+            return False
+        if chat_service is None or not config['url'] or not config['password']:
+            for address in addresses:
+                self.on_availability_checked(generation, address, None)
+            return False
+        
+        def run_async():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            for address in addresses:
+                available = loop.run_until_complete(
+                    chat_service.check_imessage_availability(config['url'], config['password'], address)
+                )
+                GLib.idle_add(self.on_availability_checked, generation, address, available)
+            loop.close()
+        
+        threading.Thread(target=run_async, daemon=True).start()
+        return False
+    
+    def on_availability_checked(self, generation: int, address: str, available):
+        """Show how an address will be reached, unless the text changed since."""
+        if generation != self.availability_generation:
+            return False
+        self.reachability[address] = get_reachability(address, available)
+        self.show_reachability()
+        self.on_entry_changed(self.contact_entry)
+        return False
+    
+    def show_reachability(self):
+        """Show an indicator per typed address: blue for iMessage, green for SMS."""
+        child = self.availability_box.get_first_child()
+        while child:
+            next_child = child.get_next_sibling()
+            self.availability_box.remove(child)
+            child = next_child
+        
+        for address, state in self.reachability.items():
+            label = Gtk.Label()
+            label.set_halign(Gtk.Align.START)
+            label.set_ellipsize(3)  # ELLIPSIZE_END
+            label.add_css_class("caption")
+            label.set_text(f"● {address}: {REACHABILITY_LABELS.get(state, 'Checking…')}")
+            if state == 'invalid':
+                label.add_css_class("error")
+            elif state in ('imessage', 'sms'):
+                label.add_css_class(f"{state}-indicator")
+            else:
+                label.add_css_class("dim-label")
+            self.availability_box.append(label)
     
     def on_cancel_clicked(self, button):
         """Handle cancel button click."""
@@ -214,7 +304,7 @@ class NewChatDialog(Adw.Dialog):
             return
         
         try:
-            api_method = self.application.config_manager.get_api_method()
+            api_method = self.config_manager.get_api_method()
             async with BlueBubblesClient(config['url'], config['password'], api_method) as client:
                 # Get chats to extract contacts
                 chats = await client.get_chats(limit=200, with_data=['participants'])
@@ -284,17 +374,20 @@ class NewChatDialog(Adw.Dialog):
             return
         
         try:
-            api_method = self.application.config_manager.get_api_method()
+            api_method = self.config_manager.get_api_method()
             async with BlueBubblesClient(config['url'], config['password'], api_method) as client:
-                # Create the chat
-                result = await client.create_chat([address], message=message)
+                # Create the chat, with every address for a group
+                addresses = parse_addresses(address)
+                result = await client.create_chat(addresses, message=message)
                 
                 def on_success():
                     if hasattr(self.parent_window, 'show_toast'):
                         self.parent_window.show_toast("Chat created successfully!")
                     
-                    # Refresh the chat list if the method exists
-                    if hasattr(self.parent_window, 'refresh_chat_list'):
+                    # Refresh the chat list and open the new chat
+                    if result.get('guid') and hasattr(self.parent_window, 'select_chat_when_listed'):
+                        self.parent_window.select_chat_when_listed(result['guid'])
+                    elif hasattr(self.parent_window, 'refresh_chat_list'):
                         self.parent_window.refresh_chat_list()
                     
                     self.close()
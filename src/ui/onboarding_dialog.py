@@ -0,0 +1,143 @@
+"""
+Onboarding Dialog
+Carousel-based tour shown after the first login, plus a "What's new" variant
+shown once per version upgrade
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw
+
+# Pages shown in the first-run tour: (icon, title, description)
+TOUR_PAGES = [
+    ("mail-send-symbolic", "Welcome to BlueBubbles",
+     "Your iMessage conversations, right on your Linux desktop. Here's a quick look around."),
+    ("view-list-symbolic", "Your Chats",
+     "The sidebar lists every conversation, most recent first. Click one to open it, "
+     "or use the + button above the list to start a conversation."),
+    ("document-edit-symbolic", "The Composer",
+     "Type at the bottom of a conversation and press Enter to send. Use the attachment "
+     "button to share images, and right-click or long-press a message to react, copy or edit it."),
+    ("system-search-symbolic", "Search",
+     "Press Ctrl+F or use the search field above the chat list to quickly filter your conversations."),
+    ("preferences-system-symbolic", "Settings",
+     "Open Preferences from the main menu or with Ctrl+, to change the theme, text width "
+     "and server options."),
+]
+
+# Highlights shown once after upgrading to a given version: version -> [(icon, title, description)]
+WHATS_NEW = {
+    '1.0.0': [
+        ("starred-symbolic", "Welcome Tour",
+         "A short tour now introduces the main parts of the app. You can replay it any time "
+         "from the main menu."),
+        ("system-search-symbolic", "Chat Search",
+         "Filter the chat list by name or message with Ctrl+F."),
+        ("go-jump-symbolic", "Quick Switcher",
+         "Press Ctrl+K to jump to any chat, or type \"name: message\" to send a quick reply "
+         "without opening the conversation."),
+    ],
+}
+
+
+def has_whats_new(version: str) -> bool:
+    """Check whether there are release highlights for a version."""
+    return bool(WHATS_NEW.get(version))
+
+
+class OnboardingDialog(Adw.Dialog):
+    """Dismissible carousel that walks the user through the app."""
+
+    def __init__(self, mode: str = 'tour', version: str = None):
+        super().__init__()
+
+        self.mode = mode
+
+        if mode == 'whats_new':
+            self.set_title(f"What's New in {version}" if version else "What's New")
+            self.pages = WHATS_NEW.get(version, [])
+        else:
+            self.set_title("Welcome")
+            self.pages = TOUR_PAGES
+
+        self.set_content_width(480)
+        self.set_content_height(420)
+
+        self.setup_ui()
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        toolbar_view = Adw.ToolbarView()
+
+        header_bar = Adw.HeaderBar()
+        toolbar_view.add_top_bar(header_bar)
+
+        content_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=12)
+        content_box.set_margin_bottom(24)
+
+        # Carousel with one status page per tour step
+        self.carousel = Adw.Carousel()
+        self.carousel.set_vexpand(True)
+        self.carousel.set_hexpand(True)
+        self.carousel.connect("page-changed", self.on_page_changed)
+
+        for icon_name, title, description in self.pages:
+            page = Adw.StatusPage()
+            page.set_icon_name(icon_name)
+            page.set_title(title)
+            page.set_description(description)
+            page.set_hexpand(True)
+            self.carousel.append(page)
+
+        content_box.append(self.carousel)
+
+        # Page indicator
+        dots = Adw.CarouselIndicatorDots()
+        dots.set_carousel(self.carousel)
+        content_box.append(dots)
+
+        # Navigation buttons
+        button_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=12)
+        button_box.set_halign(Gtk.Align.CENTER)
+
+        self.skip_button = Gtk.Button()
+        self.skip_button.set_label("Skip")
+        self.skip_button.connect("clicked", self.on_skip_clicked)
+        button_box.append(self.skip_button)
+
+        self.next_button = Gtk.Button()
+        self.next_button.add_css_class("suggested-action")
+        self.next_button.connect("clicked", self.on_next_clicked)
+        button_box.append(self.next_button)
+
+        content_box.append(button_box)
+
+        toolbar_view.set_content(content_box)
+        self.set_child(toolbar_view)
+
+        self.update_buttons(0)
+
+    def update_buttons(self, index: int):
+        """Update button labels for the current page."""
+        is_last = index >= len(self.pages) - 1
+        self.next_button.set_label("Get Started" if is_last else "Next")
+        self.skip_button.set_visible(not is_last)
+
+    def on_page_changed(self, carousel, index):
+        """Handle carousel page changes."""
+        self.update_buttons(index)
+
+    def on_next_clicked(self, button):
+        """Advance to the next page, or close on the last one."""
+        index = int(round(self.carousel.get_position()))
+        if index >= len(self.pages) - 1:
+            self.close()
+            return
+
+        self.carousel.scroll_to(self.carousel.get_nth_page(index + 1), True)
+
+    def on_skip_clicked(self, button):
+        """Dismiss the tour."""
+        self.close()
@@ -0,0 +1,84 @@
+"""
+PDF Viewer Dialog
+Simple in-app viewer for PDF attachments, one page at a time
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw
+from ..services.pdf_renderer import load_pdf, render_page
+
+# Width in pixels pages are rendered at
+PAGE_RENDER_WIDTH = 1000
+
+
+class PdfViewerDialog(Adw.Dialog):
+    """Page through a PDF attachment."""
+
+    def __init__(self, pdf_data: bytes, title: str):
+        super().__init__()
+
+        self.document = load_pdf(pdf_data)
+        self.page_count = self.document.get_n_pages()
+        self.page_index = 0
+
+        self.set_title(title)
+        self.set_content_width(640)
+        self.set_content_height(760)
+
+        self.setup_ui()
+        self.show_page(0)
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        toolbar_view = Adw.ToolbarView()
+
+        header_bar = Adw.HeaderBar()
+        toolbar_view.add_top_bar(header_bar)
+
+        scrolled = Gtk.ScrolledWindow()
+        scrolled.set_vexpand(True)
+
+        self.picture = Gtk.Picture()
+        self.picture.set_content_fit(Gtk.ContentFit.CONTAIN)
+        self.picture.set_can_shrink(True)
+        scrolled.set_child(self.picture)
+        toolbar_view.set_content(scrolled)
+
+        # Page navigation
+        nav_box = Gtk.Box(orientation=Gtk.Orientation.HORIZONTAL, spacing=12)
+        nav_box.set_halign(Gtk.Align.CENTER)
+        nav_box.set_margin_top(6)
+        nav_box.set_margin_bottom(6)
+
+        self.prev_button = Gtk.Button()
+        self.prev_button.set_icon_name("go-previous-symbolic")
+        self.prev_button.set_tooltip_text("Previous page")
+        self.prev_button.connect("clicked", lambda b: self.show_page(self.page_index - 1))
+        nav_box.append(self.prev_button)
+
+        self.page_label = Gtk.Label()
+        self.page_label.add_css_class("numeric")
+        nav_box.append(self.page_label)
+
+        self.next_button = Gtk.Button()
+        self.next_button.set_icon_name("go-next-symbolic")
+        self.next_button.set_tooltip_text("Next page")
+        self.next_button.connect("clicked", lambda b: self.show_page(self.page_index + 1))
+        nav_box.append(self.next_button)
+
+        toolbar_view.add_bottom_bar(nav_box)
+        self.set_child(toolbar_view)
+
+    def show_page(self, index: int):
+        """Render and show a page."""
+        if index < 0 or index >= self.page_count:
+            return
+
+        self.page_index = index
+        self.picture.set_paintable(render_page(self.document, index, PAGE_RENDER_WIDTH))
+        self.page_label.set_text(f"Page {index + 1} of {self.page_count}")
+        self.prev_button.set_sensitive(index > 0)
+        self.next_button.set_sensitive(index < self.page_count - 1)
@@ -7,8 +7,32 @@ import gi
 gi.require_version('Gtk', '4.0')
 gi.require_version('Adw', '1')
 
-from gi.repository import Gtk, Adw, Gio
+from gi.repository import Gtk, Adw, Gio, GLib
+import asyncio
+import threading
+from datetime import datetime
+from .. import __version__
+from ..services.debug_bundle import build_debug_bundle
+from ..services.pdf_renderer import POPPLER_AVAILABLE
+from ..utils.export_crypto import ENCRYPTION_AVAILABLE
+from ..services.voice_recorder import GST_AVAILABLE
+from ..services.portals import request_background
 
+from ..services.notifications import NOTIFICATION_PREVIEWS
+from ..utils.accent_colors import ACCENT_COLOR_IDS
+from ..utils.chat_list_style import MAX_AVATAR_SIZE, MIN_AVATAR_SIZE, PREVIEW_LINE_CHOICES
+from ..utils.features import describe_features
+from ..utils.labels import LABEL_NOTIFICATIONS
+from ..utils.imessage_account import describe_account, get_aliases, is_signed_out
+from ..utils.time_format import CLOCK_FORMATS, DATE_FORMATS
+from .activity_log_page import ActivityLogPage
+from .storage_page import StoragePage
+
+# Power profile IDs in the order shown in the power profile combo row
+POWER_PROFILE_IDS = ['performance', 'balanced', 'battery_saver']
+
+# Label notification choices, in LABEL_NOTIFICATIONS order
+LABEL_NOTIFICATION_NAMES = ["Notify", "Notify Quietly", "Don't Notify"]
 
 class PreferencesDialog(Adw.PreferencesDialog):
     """Preferences dialog for application settings."""
@@ -42,6 +66,23 @@ class PreferencesDialog(Adw.PreferencesDialog):
         self.dark_mode_row.connect("notify::active", self.on_dark_mode_changed)
         appearance_group.add(self.dark_mode_row)
         
+        # Reduced Effects Toggle
+        self.reduced_effects_row = Adw.SwitchRow()
+        self.reduced_effects_row.set_title("Reduced Effects")
+        self.reduced_effects_row.set_subtitle("No animations or shadows and fewer images kept in memory, for Raspberry Pi and older graphics")
+        self.reduced_effects_row.connect("notify::active", self.on_reduced_effects_changed)
+        appearance_group.add(self.reduced_effects_row)
+        
+        # Accent Color Selection
+        self.accent_color_row = Adw.ComboRow()
+        self.accent_color_row.set_title("Accent Color")
+        self.accent_color_row.set_subtitle("Color of sent messages, badges and highlights")
+        self.accent_color_row.set_model(Gtk.StringList.new(
+            ["System Default"] + [accent.capitalize() for accent in ACCENT_COLOR_IDS[1:]]
+        ))
+        self.accent_color_row.connect("notify::selected", self.on_accent_color_changed)
+        appearance_group.add(self.accent_color_row)
+        
         # Text Width Setting
         self.text_width_row = Adw.SpinRow()
         self.text_width_row.set_title("Text Width")
@@ -61,6 +102,188 @@ class PreferencesDialog(Adw.PreferencesDialog):
         
         main_page.add(appearance_group)
         
+        # Chat List Group
+        chat_list_group = Adw.PreferencesGroup()
+        chat_list_group.set_title("Chat List")
+        chat_list_group.set_description("How conversations appear in the sidebar")
+        
+        # Avatar Size Setting
+        self.avatar_size_row = Adw.SpinRow()
+        self.avatar_size_row.set_title("Avatar Size")
+        self.avatar_size_row.set_subtitle("Size of conversation pictures, in pixels")
+        self.avatar_size_row.set_adjustment(Gtk.Adjustment(
+            value=40, lower=MIN_AVATAR_SIZE, upper=MAX_AVATAR_SIZE, step_increment=4, page_increment=8
+        ))
+        self.avatar_size_row.connect("notify::value", self.on_avatar_size_changed)
+        chat_list_group.add(self.avatar_size_row)
+        
+        # Preview Lines Selection
+        self.preview_lines_row = Adw.ComboRow()
+        self.preview_lines_row.set_title("Message Preview")
+        self.preview_lines_row.set_model(Gtk.StringList.new(["One Line", "Two Lines"]))
+        self.preview_lines_row.connect("notify::selected", self.on_preview_lines_changed)
+        chat_list_group.add(self.preview_lines_row)
+        
+        # Timestamps Toggle
+        self.chat_timestamps_row = Adw.SwitchRow()
+        self.chat_timestamps_row.set_title("Show Timestamps")
+        self.chat_timestamps_row.set_subtitle("Show when the last message arrived")
+        self.chat_timestamps_row.connect("notify::active", self.on_chat_timestamps_changed)
+        chat_list_group.add(self.chat_timestamps_row)
+        
+        # Compact Mode Toggle
+        self.compact_chat_list_row = Adw.SwitchRow()
+        self.compact_chat_list_row.set_title("Compact Mode")
+        self.compact_chat_list_row.set_subtitle("Fit more conversations by tightening row spacing")
+        self.compact_chat_list_row.connect("notify::active", self.on_compact_chat_list_changed)
+        chat_list_group.add(self.compact_chat_list_row)
+        
+        main_page.add(chat_list_group)
+        
+        # Date and Time Group
+        time_group = Adw.PreferencesGroup()
+        time_group.set_title("Date and Time")
+        time_group.set_description("How timestamps appear in the chat list, messages and exports")
+        
+        # Clock Format Selection
+        self.clock_format_row = Adw.ComboRow()
+        self.clock_format_row.set_title("Clock Format")
+        self.clock_format_row.set_model(Gtk.StringList.new(["System Default", "12-hour", "24-hour"]))
+        self.clock_format_row.connect("notify::selected", self.on_clock_format_changed)
+        time_group.add(self.clock_format_row)
+        
+        # Date Format Selection
+        self.date_format_row = Adw.ComboRow()
+        self.date_format_row.set_title("Date Format")
+        self.date_format_row.set_model(Gtk.StringList.new(
+            ["System Default", "MM/DD/YY", "DD/MM/YY", "YYYY-MM-DD"]
+        ))
+        self.date_format_row.connect("notify::selected", self.on_date_format_changed)
+        time_group.add(self.date_format_row)
+        
+        # Relative Timestamps Toggle
+        self.relative_timestamps_row = Adw.SwitchRow()
+        self.relative_timestamps_row.set_title("Relative Timestamps")
+        self.relative_timestamps_row.set_subtitle("Show \"5 min\", \"Yesterday\" or the weekday for recent messages")
+        self.relative_timestamps_row.connect("notify::active", self.on_relative_timestamps_changed)
+        time_group.add(self.relative_timestamps_row)
+        
+        main_page.add(time_group)
+        
+        # Notifications Group
+        notifications_group = Adw.PreferencesGroup()
+        notifications_group.set_title("Notifications")
+        notifications_group.set_description("Control what new message notifications reveal")
+        
+        # Notification Preview Selection
+        self.notification_preview_row = Adw.ComboRow()
+        self.notification_preview_row.set_title("Notification Content")
+        self.notification_preview_row.set_subtitle("Also applies to notifications shown on the lock screen")
+        self.notification_preview_row.set_model(Gtk.StringList.new(
+            ["Sender and Message", "Sender Only", "Generic \"New message\""]
+        ))
+        self.notification_preview_row.connect("notify::selected", self.on_notification_preview_changed)
+        notifications_group.add(self.notification_preview_row)
+        
+        # Message Sounds Toggle
+        self.message_sounds_row = Adw.SwitchRow()
+        self.message_sounds_row.set_title("Message Sounds")
+        self.message_sounds_row.set_subtitle("Play a sound when sending and receiving messages, and vibrate on phones. Silent during Do Not Disturb.")
+        self.message_sounds_row.connect("notify::active", self.on_message_sounds_changed)
+        notifications_group.add(self.message_sounds_row)
+        
+        self.auto_copy_codes_row = Adw.SwitchRow()
+        self.auto_copy_codes_row.set_title("Copy Verification Codes")
+        self.auto_copy_codes_row.set_subtitle("Copy one-time codes received by SMS to the clipboard when their chat is open")
+        self.auto_copy_codes_row.connect("notify::active", self.on_auto_copy_codes_changed)
+        notifications_group.add(self.auto_copy_codes_row)
+        
+        main_page.add(notifications_group)
+        
+        # Privacy Group
+        privacy_group = Adw.PreferencesGroup()
+        privacy_group.set_title("Privacy")
+        privacy_group.set_description("Chats can override these in their details")
+        
+        self.typing_indicators_row = Adw.SwitchRow()
+        self.typing_indicators_row.set_title("Send Typing Indicators")
+        self.typing_indicators_row.set_subtitle("Let others see when you're typing. Requires the Private API.")
+        self.typing_indicators_row.connect("notify::active", self.on_typing_indicators_changed)
+        privacy_group.add(self.typing_indicators_row)
+        
+        self.read_receipts_row = Adw.SwitchRow()
+        self.read_receipts_row.set_title("Mark Chats Read on Server")
+        self.read_receipts_row.set_subtitle("Opening a chat marks it read on the server, which sends read receipts if they're on there. Unread badges here clear either way.")
+        self.read_receipts_row.connect("notify::active", self.on_read_receipts_changed)
+        privacy_group.add(self.read_receipts_row)
+        
+        main_page.add(privacy_group)
+        
+        # Labels Group
+        self.labels_group = Adw.PreferencesGroup()
+        self.labels_group.set_title("Labels")
+        self.labels_group.set_description("Notifications for chats with each label. Add labels from a chat's details.")
+        self.label_rows = []
+        self.setup_label_rows()
+        main_page.add(self.labels_group)
+        
+        # Background Group
+        background_group = Adw.PreferencesGroup()
+        background_group.set_title("Background")
+        background_group.set_description("Keep receiving notifications when the window is closed")
+        
+        # Run in Background Toggle
+        self.run_in_background_row = Adw.SwitchRow()
+        self.run_in_background_row.set_title("Run in Background")
+        self.run_in_background_row.set_subtitle("Closing the window keeps checking for new messages")
+        self.run_in_background_row.connect("notify::active", self.on_background_setting_changed)
+        background_group.add(self.run_in_background_row)
+        
+        # Start at Login Toggle
+        self.autostart_row = Adw.SwitchRow()
+        self.autostart_row.set_title("Start at Login")
+        self.autostart_row.set_subtitle("Start in the background when you log in")
+        self.autostart_row.connect("notify::active", self.on_background_setting_changed)
+        background_group.add(self.autostart_row)
+        
+        main_page.add(background_group)
+        
+        # Power Group
+        power_group = Adw.PreferencesGroup()
+        power_group.set_title("Power")
+        power_group.set_description("Balance responsiveness against battery life")
+        
+        # Power Profile Selection
+        self.power_profile_row = Adw.ComboRow()
+        self.power_profile_row.set_title("Power Profile")
+        self.power_profile_row.set_subtitle("Battery saver checks for messages less often, turns off animations and skips image previews")
+        self.power_profile_row.set_model(Gtk.StringList.new(["Performance", "Balanced", "Battery Saver"]))
+        self.power_profile_row.connect("notify::selected", self.on_power_profile_changed)
+        power_group.add(self.power_profile_row)
+        
+        # Automatic Battery Saver Toggle
+        self.auto_battery_saver_row = Adw.SwitchRow()
+        self.auto_battery_saver_row.set_title("Automatic Battery Saver")
+        self.auto_battery_saver_row.set_subtitle("Switch to battery saver when the battery is low")
+        self.auto_battery_saver_row.connect("notify::active", self.on_auto_battery_saver_changed)
+        power_group.add(self.auto_battery_saver_row)
+        
+        # Metered Connection Toggle
+        self.auto_low_bandwidth_row = Adw.SwitchRow()
+        self.auto_low_bandwidth_row.set_title("Save Data on Metered Connections")
+        self.auto_low_bandwidth_row.set_subtitle("Check for messages less often and ask before downloading large attachments")
+        self.auto_low_bandwidth_row.connect("notify::active", self.on_auto_low_bandwidth_changed)
+        power_group.add(self.auto_low_bandwidth_row)
+        
+        # Check Interval Setting (range: 1-60 seconds, default: 3)
+        self.check_interval_row = Adw.SpinRow.new_with_range(1, 60, 1)
+        self.check_interval_row.set_title("Message Check Interval")
+        self.check_interval_row.set_subtitle("Seconds between checks for new messages in the balanced profile")
+        self.check_interval_row.connect("notify::value", self.on_check_interval_changed)
+        power_group.add(self.check_interval_row)
+        
+        main_page.add(power_group)
+        
         # Server Group
         server_group = Adw.PreferencesGroup()
         server_group.set_title("Server")
@@ -79,6 +302,14 @@ class PreferencesDialog(Adw.PreferencesDialog):
             self.server_info_row.add_prefix(status_icon)
             
             server_group.add(self.server_info_row)
+            
+            # iMessage account the server sends with, and its addresses
+            self.account_row = Adw.ExpanderRow()
+            self.account_row.set_title("iMessage Account")
+            self.account_row.set_subtitle("Checking…")
+            self.account_row.set_enable_expansion(False)
+            server_group.add(self.account_row)
+            self.load_imessage_account()
         
         # Forget Server Button
         self.forget_server_row = Adw.ActionRow()
@@ -101,6 +332,9 @@ class PreferencesDialog(Adw.PreferencesDialog):
         # Add the main page to the dialog
         self.add(main_page)
         
+        # Storage page
+        self.add(StoragePage(self, self.application))
+        
         # Advanced page (for future use)
         advanced_page = Adw.PreferencesPage()
         advanced_page.set_title("Advanced")
@@ -160,19 +394,51 @@ We are not responsible for any damage or issues caused by enabling this feature.
         
         advanced_page.add(api_group)
         
-        # Other Advanced Settings Group (placeholder for future)
-        other_advanced_group = Adw.PreferencesGroup()
-        other_advanced_group.set_title("Other Advanced Settings")
-        other_advanced_group.set_description("Additional advanced configuration options")
-        
-        # Placeholder row
-        placeholder_row = Adw.ActionRow()
-        placeholder_row.set_title("More settings coming soon")
-        placeholder_row.set_subtitle("Additional preferences will be added in future versions")
-        placeholder_row.set_sensitive(False)
-        
-        other_advanced_group.add(placeholder_row)
-        advanced_page.add(other_advanced_group)
+        # Network Group
+        network_group = Adw.PreferencesGroup()
+        network_group.set_title("Network")
+        network_group.set_description("How long to wait for the server before giving up")
+        
+        # Request Timeout Setting (range: 5-120 seconds, default: 15)
+        self.request_timeout_row = Adw.SpinRow.new_with_range(5, 120, 1)
+        self.request_timeout_row.set_title("Request Timeout")
+        self.request_timeout_row.set_subtitle("Seconds to wait for quick requests like sending a message")
+        self.request_timeout_row.connect("notify::value", self.on_request_timeout_changed)
+        network_group.add(self.request_timeout_row)
+        
+        # Long Request Timeout Setting (range: 30-3600 seconds, default: 300)
+        self.long_request_timeout_row = Adw.SpinRow.new_with_range(30, 3600, 10)
+        self.long_request_timeout_row.set_title("Transfer Timeout")
+        self.long_request_timeout_row.set_subtitle("Seconds to wait for message history and attachment uploads or downloads")
+        self.long_request_timeout_row.connect("notify::value", self.on_long_request_timeout_changed)
+        network_group.add(self.long_request_timeout_row)
+        
+        advanced_page.add(network_group)
+        
+        # Diagnostics Group
+        diagnostics_group = Adw.PreferencesGroup()
+        diagnostics_group.set_title("Diagnostics")
+        diagnostics_group.set_description("Help track down connection and server problems")
+        
+        # Activity Log Row
+        activity_log_row = Adw.ActionRow()
+        activity_log_row.set_title("Activity Log")
+        activity_log_row.set_subtitle("Logins, failed requests and reconnects")
+        activity_log_row.set_activatable(True)
+        activity_log_row.add_suffix(Gtk.Image.new_from_icon_name("go-next-symbolic"))
+        activity_log_row.connect("activated", self.on_activity_log_activated)
+        diagnostics_group.add(activity_log_row)
+        
+        # Debug Bundle Row
+        debug_bundle_row = Adw.ActionRow()
+        debug_bundle_row.set_title("Create Debug Bundle")
+        debug_bundle_row.set_subtitle("Save logs and diagnostics for a bug report, without passwords or messages")
+        debug_bundle_row.set_activatable(True)
+        debug_bundle_row.add_suffix(Gtk.Image.new_from_icon_name("document-save-symbolic"))
+        debug_bundle_row.connect("activated", self.on_debug_bundle_activated)
+        diagnostics_group.add(debug_bundle_row)
+        
+        advanced_page.add(diagnostics_group)
         
         self.add(advanced_page)
     
@@ -182,13 +448,58 @@ We are not responsible for any damage or issues caused by enabling this feature.
         dark_mode = self.config_manager.get('appearance.dark_mode', False)
         self.dark_mode_row.set_active(dark_mode)
         
+        # Load reduced effects preference
+        self.reduced_effects_row.set_active(self.config_manager.get_reduced_effects())
+        
+        # Load accent color preference
+        self.accent_color_row.set_selected(ACCENT_COLOR_IDS.index(self.config_manager.get_accent_color()))
+        
         # Load text width preference
         text_width = self.config_manager.get('appearance.text_width', 80)
         self.text_width_row.set_value(text_width)
         
+        # Load chat list preferences
+        style = self.config_manager.get_chat_list_style()
+        self.avatar_size_row.set_value(style.avatar_size)
+        self.preview_lines_row.set_selected(PREVIEW_LINE_CHOICES.index(style.preview_lines))
+        self.chat_timestamps_row.set_active(style.show_timestamps)
+        self.compact_chat_list_row.set_active(style.compact)
+        
+        # Load timestamp preferences
+        time_format = self.config_manager.get_time_format()
+        self.clock_format_row.set_selected(CLOCK_FORMATS.index(time_format.clock))
+        self.date_format_row.set_selected(list(DATE_FORMATS).index(time_format.date_format))
+        self.relative_timestamps_row.set_active(time_format.relative)
+        
+        # Load background preferences (autostart first, it turns on running in the background)
+        self.autostart_row.set_active(self.config_manager.get_autostart())
+        self.run_in_background_row.set_active(self.config_manager.get_run_in_background())
+        
+        # Load notification preferences
+        preview = self.config_manager.get_notification_preview()
+        self.notification_preview_row.set_selected(NOTIFICATION_PREVIEWS.index(preview))
+        self.message_sounds_row.set_active(self.config_manager.get_message_sounds())
+        self.auto_copy_codes_row.set_active(self.config_manager.get_auto_copy_codes())
+        
+        # Load privacy preferences
+        self.typing_indicators_row.set_active(self.config_manager.get_send_typing_indicators())
+        self.read_receipts_row.set_active(self.config_manager.get_send_read_receipts())
+        
+        # Load power preferences
+        profile = self.config_manager.get_power_profile()
+        self.power_profile_row.set_selected(POWER_PROFILE_IDS.index(profile))
+        self.auto_battery_saver_row.set_active(self.config_manager.get_auto_battery_saver())
+        self.auto_low_bandwidth_row.set_active(self.config_manager.get_auto_low_bandwidth())
+        self.check_interval_row.set_value(self.config_manager.get_message_check_interval())
+        
         # Load API method preference
         api_method = self.config_manager.get_api_method()
         self.api_method_row.set_active(api_method == 'private')
+        
+        # Load network preferences
+        timeout, long_timeout = self.config_manager.get_request_timeouts()
+        self.request_timeout_row.set_value(timeout)
+        self.long_request_timeout_row.set_value(long_timeout)
     
     def on_dark_mode_changed(self, switch_row, pspec):
         """Handle dark mode toggle change."""
@@ -202,11 +513,210 @@ We are not responsible for any damage or issues caused by enabling this feature.
         else:
             style_manager.set_color_scheme(Adw.ColorScheme.FORCE_LIGHT)
     
+    def on_accent_color_changed(self, combo_row, pspec):
+        """Handle accent color change."""
+        self.config_manager.set('appearance.accent_color', ACCENT_COLOR_IDS[combo_row.get_selected()])
+        if self.application.theme_styles:
+            self.application.theme_styles.update()
+    
+    def on_reduced_effects_changed(self, switch_row, pspec):
+        """Handle reduced effects toggle change."""
+        self.config_manager.set('appearance.reduced_effects', switch_row.get_active())
+        self.application.apply_reduced_effects()
+    
     def on_text_width_changed(self, spin_row, pspec):
         """Handle text width change."""
         width = int(spin_row.get_value())
         self.config_manager.set('appearance.text_width', width)
     
+    def on_avatar_size_changed(self, spin_row, pspec):
+        """Handle avatar size change."""
+        self.config_manager.set('appearance.avatar_size', int(spin_row.get_value()))
+        self.refresh_chat_list_style()
+    
+    def on_preview_lines_changed(self, combo_row, pspec):
+        """Handle message preview length change."""
+        self.config_manager.set('appearance.preview_lines', PREVIEW_LINE_CHOICES[combo_row.get_selected()])
+        self.refresh_chat_list_style()
+    
+    def on_chat_timestamps_changed(self, switch_row, pspec):
+        """Handle chat list timestamps toggle change."""
+        self.config_manager.set('appearance.show_chat_timestamps', switch_row.get_active())
+        self.refresh_chat_list_style()
+    
+    def on_compact_chat_list_changed(self, switch_row, pspec):
+        """Handle compact mode toggle change."""
+        self.config_manager.set('appearance.compact_chat_list', switch_row.get_active())
+        self.refresh_chat_list_style()
+    
+    def refresh_chat_list_style(self):
+        """Show the new chat list appearance right away."""
+        for window in self.application.get_windows():
+            if hasattr(window, 'refresh_chat_list_style'):
+                window.refresh_chat_list_style()
+    
+    def on_clock_format_changed(self, combo_row, pspec):
+        """Handle clock format change."""
+        self.config_manager.set('appearance.clock_format', CLOCK_FORMATS[combo_row.get_selected()])
+        self.refresh_timestamps()
+    
+    def on_date_format_changed(self, combo_row, pspec):
+        """Handle date format change."""
+        self.config_manager.set('appearance.date_format', list(DATE_FORMATS)[combo_row.get_selected()])
+        self.refresh_timestamps()
+    
+    def on_relative_timestamps_changed(self, switch_row, pspec):
+        """Handle relative timestamps toggle change."""
+        self.config_manager.set('appearance.relative_timestamps', switch_row.get_active())
+        self.refresh_timestamps()
+    
+    def refresh_timestamps(self):
+        """Show the new timestamp format right away."""
+        for window in self.application.get_windows():
+            if hasattr(window, 'refresh_timestamps'):
+                window.refresh_timestamps()
+    
+    def on_background_setting_changed(self, switch_row, pspec):
+        """Ask the background portal for permission to match the toggles."""
+        run_in_background = self.run_in_background_row.get_active()
+        autostart = self.autostart_row.get_active()
+        
+        # Starting at login only makes sense if the app may stay in the background;
+        # the follow-up toggle change makes the request
+        if switch_row is self.autostart_row and autostart and not run_in_background:
+            self.run_in_background_row.set_active(True)
+            return
+        if switch_row is self.run_in_background_row and autostart and not run_in_background:
+            self.autostart_row.set_active(False)
+            return
+        
+        if (run_in_background == self.config_manager.get_run_in_background()
+                and autostart == self.config_manager.get_autostart()):
+            return  # Loading preferences or reverting a denied request
+        
+        def on_response(background_allowed, autostart_enabled):
+            if run_in_background and not background_allowed:
+                self.add_toast(Adw.Toast.new("Running in the background was not allowed"))
+                self.run_in_background_row.set_active(self.config_manager.get_run_in_background())
+                self.autostart_row.set_active(self.config_manager.get_autostart())
+                return
+            
+            self.config_manager.set('app.run_in_background', run_in_background)
+            self.config_manager.set('app.autostart', autostart_enabled)
+            if autostart and not autostart_enabled:
+                self.add_toast(Adw.Toast.new("Starting at login was not allowed"))
+                self.autostart_row.set_active(False)
+        
+        if not request_background(autostart, "Receive message notifications while the window is closed",
+                                  on_response):
+            # Without the portal, rely on the desktop's own session handling
+            self.config_manager.set('app.run_in_background', run_in_background)
+            self.config_manager.set('app.autostart', False)
+            if autostart:
+                self.add_toast(Adw.Toast.new("Starting at login needs the background portal"))
+                self.autostart_row.set_active(False)
+    
+    def on_notification_preview_changed(self, combo_row, pspec):
+        """Handle notification preview change."""
+        self.config_manager.set_notification_preview(NOTIFICATION_PREVIEWS[combo_row.get_selected()])
+    
+    def on_message_sounds_changed(self, switch_row, pspec):
+        """Handle message sounds toggle change."""
+        self.config_manager.set('notifications.sounds', switch_row.get_active())
+    
+    def on_auto_copy_codes_changed(self, switch_row, pspec):
+        """Handle copy verification codes toggle change."""
+        self.config_manager.set('notifications.auto_copy_codes', switch_row.get_active())
+    
+    def on_typing_indicators_changed(self, switch_row, pspec):
+        """Handle typing indicators toggle change."""
+        self.config_manager.set('privacy.send_typing_indicators', switch_row.get_active())
+    
+    def on_read_receipts_changed(self, switch_row, pspec):
+        """Handle mark read on server toggle change."""
+        self.config_manager.set('privacy.send_read_receipts', switch_row.get_active())
+    
+    def setup_label_rows(self):
+        """Add a notification choice for each label, replacing any shown before."""
+        for row in self.label_rows:
+            self.labels_group.remove(row)
+        self.label_rows = []
+        
+        labels = self.application.get_chat_service().get_labels()
+        self.labels_group.set_visible(bool(labels))
+        for name, policy in labels:
+            row = Adw.ComboRow()
+            row.set_title(name)
+            row.set_model(Gtk.StringList.new(LABEL_NOTIFICATION_NAMES))
+            if policy in LABEL_NOTIFICATIONS:
+                row.set_selected(LABEL_NOTIFICATIONS.index(policy))
+            row.connect("notify::selected", self.on_label_notifications_changed, name)
+            
+            delete_button = Gtk.Button.new_from_icon_name("user-trash-symbolic")
+            delete_button.set_tooltip_text("Delete Label")
+            delete_button.set_valign(Gtk.Align.CENTER)
+            delete_button.add_css_class("flat")
+            delete_button.connect("clicked", self.on_delete_label_clicked, name)
+            row.add_suffix(delete_button)
+            
+            self.labels_group.add(row)
+            self.label_rows.append(row)
+    
+    def on_label_notifications_changed(self, combo_row, pspec, name: str):
+        """Save how chats with a label notify."""
+        self.application.get_chat_service().set_label_notifications(
+            name, LABEL_NOTIFICATIONS[combo_row.get_selected()]
+        )
+    
+    def on_delete_label_clicked(self, button, name: str):
+        """Delete a label after confirming, removing it from every chat."""
+        dialog = Adw.AlertDialog.new(f"Delete “{name}”?", "Chats with this label keep their messages.")
+        dialog.add_response("cancel", "Cancel")
+        dialog.add_response("delete", "Delete")
+        dialog.set_response_appearance("delete", Adw.ResponseAppearance.DESTRUCTIVE)
+        
+        def on_response(dialog, response):
+            if response != "delete":
+                return
+            if self.application.main_window:
+                self.application.main_window.delete_label(name)
+            else:
+                self.application.get_chat_service().delete_label(name)
+            self.setup_label_rows()
+        
+        dialog.connect("response", on_response)
+        dialog.present(self)
+    
+    def on_power_profile_changed(self, combo_row, pspec):
+        """Handle power profile change."""
+        self.config_manager.set_power_profile(POWER_PROFILE_IDS[combo_row.get_selected()])
+        self.application.apply_power_profile()
+    
+    def on_auto_battery_saver_changed(self, switch_row, pspec):
+        """Handle automatic battery saver toggle change."""
+        self.config_manager.set('power.auto_battery_saver', switch_row.get_active())
+        self.application.apply_power_profile()
+    
+    def on_auto_low_bandwidth_changed(self, switch_row, pspec):
+        """Handle metered connection toggle change."""
+        self.config_manager.set('power.auto_low_bandwidth', switch_row.get_active())
+        self.application.apply_power_profile()
+    
+    def on_check_interval_changed(self, spin_row, pspec):
+        """Handle message check interval change."""
+        self.config_manager.set('app.message_check_interval', int(spin_row.get_value()))
+        self.application.apply_power_profile()
+    
+    def on_request_timeout_changed(self, spin_row, pspec):
+        """Handle quick request timeout change."""
+        self.config_manager.set('advanced.request_timeout', int(spin_row.get_value()))
+        self.application.apply_request_timeouts()
+    
+    def on_long_request_timeout_changed(self, spin_row, pspec):
+        """Handle long request timeout change."""
+        self.config_manager.set('advanced.long_request_timeout', int(spin_row.get_value()))
+        self.application.apply_request_timeouts()
+    
     def on_api_method_changed(self, switch_row, pspec):
         """Handle API method toggle change."""
         use_private = switch_row.get_active()
@@ -244,6 +754,99 @@ We are not responsible for any damage or issues caused by enabling this feature.
             # User cancelled, revert the switch
             switch_row.set_active(False)
     
+    def on_activity_log_activated(self, row):
+        """Show the activity log."""
+        self.push_subpage(ActivityLogPage(self.application))
+    
+    def on_debug_bundle_activated(self, row):
+        """Pick where to save the debug bundle."""
+        file_dialog = Gtk.FileDialog()
+        file_dialog.set_title("Save Debug Bundle")
+        file_dialog.set_initial_name(f"bluebubbles-debug-{datetime.now():%Y%m%d-%H%M%S}.zip")
+        
+        def on_file_selected(dialog, result):
+            try:
+                file = dialog.save_finish(result)
+            except Exception:
+                return  # Selection cancelled
+            
+            if file and file.get_path():
+                self.create_debug_bundle_async(file.get_path())
+        
+        file_dialog.save(self.get_root(), None, on_file_selected)
+    
+    def create_debug_bundle_async(self, file_path: str):
+        """Gather diagnostics and write the debug bundle in the background."""
+        chat_service = self.application.get_chat_service()
+        config = self.config_manager.get_server_config()
+        
+        def run_async():
+            try:
+                server_info = None
+                if config['url'] and config['password']:
+                    loop = asyncio.new_event_loop()
+                    asyncio.set_event_loop(loop)
+                    server_info = loop.run_until_complete(
+                        chat_service.get_server_info(config['url'], config['password'])
+                    )
+                    loop.close()
+                
+                build_debug_bundle(
+                    file_path, __version__, self.config_manager.get_all(),
+                    chat_service.get_activity_log(limit=500),
+                    chat_service.get_schema_info(), server_info,
+                    describe_features({
+                        'media': GST_AVAILABLE, 'pdf': POPPLER_AVAILABLE, 'encryption': ENCRYPTION_AVAILABLE,
+                    })
+                )
+                GLib.idle_add(self.add_toast, Adw.Toast.new("Debug bundle saved"))
+            except Exception as e:
+                GLib.idle_add(self.add_toast, Adw.Toast.new(f"Failed to create debug bundle: {e}"))
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def load_imessage_account(self):
+        """Show the server's iMessage account, fetching it if it hasn't been checked yet."""
+        chat_service = self.application.get_chat_service()
+        if chat_service.imessage_account is not None:
+            self.show_imessage_account(chat_service.imessage_account)
+            return
+        
+        config = self.config_manager.get_server_config()
+        
+        def run_async():
+            loop = asyncio.new_event_loop()
+            asyncio.set_event_loop(loop)
+            account = loop.run_until_complete(
+                chat_service.check_imessage_account(config['url'], config['password'])
+            )
+            loop.close()
+            GLib.idle_add(self.show_imessage_account, account)
+        
+        threading.Thread(target=run_async, daemon=True).start()
+    
+    def show_imessage_account(self, account):
+        """Fill in the account row, listing aliases underneath."""
+        if account is None:
+            self.account_row.set_subtitle("Unavailable")
+            return False
+        
+        self.account_row.set_subtitle(describe_account(account))
+        if is_signed_out(account):
+            self.account_row.add_css_class("error")
+        
+        aliases = get_aliases(account)
+        for address, active in aliases:
+            alias_row = Adw.ActionRow()
+            alias_row.set_use_markup(False)
+            alias_row.set_title(address)
+            alias_row.set_subtitle("Active" if active else "Not active")
+            if active:
+                alias_row.add_suffix(Gtk.Image.new_from_icon_name("emblem-ok-symbolic"))
+            self.account_row.add_row(alias_row)
+        self.account_row.set_enable_expansion(bool(aliases))
+        return False
+    
     def on_forget_server_clicked(self, button):
         """Handle forget server button click."""
         # Create confirmation dialog
@@ -269,6 +872,8 @@ We are not responsible for any damage or issues caused by enabling this feature.
     def on_forget_server_response(self, dialog, response):
         """Handle the response from the forget server confirmation dialog."""
         if response == "forget":
+            self.application.get_chat_service().log_activity('logout', "Forgot the server configuration")
+            
             # Clear the server configuration
             self.config_manager.clear_server_config()
             
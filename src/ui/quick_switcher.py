@@ -0,0 +1,184 @@
+"""
+Quick Switcher
+Ctrl+K dialog for jumping to a conversation by name, or sending a quick
+message with "name: message text"
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw, Gdk
+from typing import List, Optional, Tuple
+from ..db.models import ChatRecord
+
+# Maximum number of matches listed below the search field
+MAX_RESULTS = 10
+
+
+def parse_quick_send(text: str) -> Optional[Tuple[str, str]]:
+    """Split "name: message" into (name, message), or return None if it isn't one."""
+    name, separator, message = text.partition(':')
+    name = name.strip()
+    message = message.strip()
+    if not separator or not name or not message:
+        return None
+    return name, message
+
+
+def match_chats(chats: List[ChatRecord], query: str) -> List[ChatRecord]:
+    """Return chats whose title matches the query, keeping the list's recency order."""
+    query = query.strip().lower()
+    if not query:
+        return list(chats)
+
+    # Titles starting with the query rank above titles merely containing it
+    prefix_matches = []
+    other_matches = []
+    for chat in chats:
+        title = chat.display_title.lower()
+        if title.startswith(query):
+            prefix_matches.append(chat)
+        elif query in title:
+            other_matches.append(chat)
+    return prefix_matches + other_matches
+
+
+class QuickSwitcherDialog(Adw.Dialog):
+    """Type-ahead chat switcher."""
+
+    def __init__(self, parent_window):
+        super().__init__()
+
+        self.parent_window = parent_window
+
+        self.set_title("Go to Chat")
+        self.set_content_width(420)
+        self.set_content_height(360)
+
+        self.setup_ui()
+        self.update_results()
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        toolbar_view = Adw.ToolbarView()
+
+        header_bar = Adw.HeaderBar()
+        toolbar_view.add_top_bar(header_bar)
+
+        content_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=6)
+        content_box.set_margin_top(6)
+        content_box.set_margin_bottom(12)
+        content_box.set_margin_start(12)
+        content_box.set_margin_end(12)
+
+        self.search_entry = Gtk.SearchEntry()
+        self.search_entry.set_placeholder_text("Chat name, or \"name: message\" to send")
+        self.search_entry.connect("search-changed", self.on_search_changed)
+        self.search_entry.connect("activate", self.on_activate)
+        self.search_entry.connect("stop-search", lambda e: self.close())
+        content_box.append(self.search_entry)
+
+        self.hint_label = Gtk.Label()
+        self.hint_label.set_halign(Gtk.Align.START)
+        self.hint_label.set_ellipsize(3)  # ELLIPSIZE_END
+        self.hint_label.add_css_class("dim-label")
+        self.hint_label.add_css_class("caption")
+        content_box.append(self.hint_label)
+
+        scrolled = Gtk.ScrolledWindow()
+        scrolled.set_vexpand(True)
+        scrolled.set_policy(Gtk.PolicyType.NEVER, Gtk.PolicyType.AUTOMATIC)
+
+        self.results_list = Gtk.ListBox()
+        self.results_list.set_selection_mode(Gtk.SelectionMode.BROWSE)
+        self.results_list.add_css_class("navigation-sidebar")
+        self.results_list.connect("row-activated", self.on_row_activated)
+        scrolled.set_child(self.results_list)
+        content_box.append(scrolled)
+
+        toolbar_view.set_content(content_box)
+        self.set_child(toolbar_view)
+
+        # Let arrow keys move through results while typing
+        key_controller = Gtk.EventControllerKey()
+        key_controller.connect("key-pressed", self.on_key_pressed)
+        self.search_entry.add_controller(key_controller)
+
+        self.set_focus(self.search_entry)
+
+    def get_query(self) -> Tuple[str, Optional[str]]:
+        """Return the chat query and, in quick send mode, the message text."""
+        text = self.search_entry.get_text()
+        quick_send = parse_quick_send(text)
+        if quick_send:
+            return quick_send
+        return text, None
+
+    def update_results(self):
+        """Rebuild the result list for the current query."""
+        while True:
+            row = self.results_list.get_first_child()
+            if row is None:
+                break
+            self.results_list.remove(row)
+
+        query, message = self.get_query()
+        matches = match_chats(self.parent_window.chats, query)[:MAX_RESULTS]
+
+        for chat in matches:
+            row = Adw.ActionRow()
+            row.set_title(chat.display_title)
+            if chat.last_message_text:
+                row.set_subtitle(chat.last_message_text)
+                row.set_subtitle_lines(1)
+            row.set_activatable(True)
+            row.chat = chat
+            self.results_list.append(row)
+
+        first_row = self.results_list.get_row_at_index(0)
+        if first_row:
+            self.results_list.select_row(first_row)
+
+        if message and matches:
+            self.hint_label.set_text("Enter sends to the selected chat without opening it")
+        elif message:
+            self.hint_label.set_text("No matching chat")
+        else:
+            self.hint_label.set_text("Enter opens the selected chat")
+
+    def on_key_pressed(self, controller, keyval, keycode, state):
+        """Move the result selection with the arrow keys."""
+        if keyval not in (Gdk.KEY_Up, Gdk.KEY_Down):
+            return False
+
+        row = self.results_list.get_selected_row()
+        index = row.get_index() if row else -1
+        index += -1 if keyval == Gdk.KEY_Up else 1
+
+        next_row = self.results_list.get_row_at_index(max(index, 0))
+        if next_row:
+            self.results_list.select_row(next_row)
+        return True
+
+    def on_search_changed(self, entry):
+        """Handle search text changes."""
+        self.update_results()
+
+    def on_activate(self, entry):
+        """Open the selected chat, or send the quick message."""
+        row = self.results_list.get_selected_row()
+        if row is None:
+            return
+
+        query, message = self.get_query()
+        if message:
+            self.parent_window.send_quick_message(row.chat, message)
+        else:
+            self.parent_window.select_chat(row.chat.guid)
+        self.close()
+
+    def on_row_activated(self, list_box, row):
+        """Handle clicking a result."""
+        self.results_list.select_row(row)
+        self.on_activate(self.search_entry)
@@ -0,0 +1,132 @@
+"""
+Share Contact Dialog
+Picks one of the cached contacts to send as a contact card
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw, Gdk
+from typing import Callable
+from ..utils.participants import Participant, match_contacts
+from ..utils.phone_format import format_phone_number
+
+# Maximum number of contacts listed below the search field
+MAX_RESULTS = 50
+
+
+class ShareContactDialog(Adw.Dialog):
+    """Searchable list of contacts; choosing one hands it to on_selected."""
+
+    def __init__(self, contacts: list, on_selected: Callable[[Participant], None]):
+        super().__init__()
+
+        self.contacts = contacts
+        self.on_selected = on_selected
+
+        self.set_title("Share Contact")
+        self.set_content_width(420)
+        self.set_content_height(420)
+
+        self.setup_ui()
+        self.update_results()
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        toolbar_view = Adw.ToolbarView()
+        toolbar_view.add_top_bar(Adw.HeaderBar())
+
+        content_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=6)
+        content_box.set_margin_top(6)
+        content_box.set_margin_bottom(12)
+        content_box.set_margin_start(12)
+        content_box.set_margin_end(12)
+
+        self.search_entry = Gtk.SearchEntry()
+        self.search_entry.set_placeholder_text("Search contacts")
+        self.search_entry.connect("search-changed", lambda entry: self.update_results())
+        self.search_entry.connect("activate", self.on_activate)
+        self.search_entry.connect("stop-search", lambda entry: self.close())
+        content_box.append(self.search_entry)
+
+        self.results_stack = Gtk.Stack()
+        self.results_stack.set_vexpand(True)
+
+        scrolled = Gtk.ScrolledWindow()
+        scrolled.set_policy(Gtk.PolicyType.NEVER, Gtk.PolicyType.AUTOMATIC)
+        self.results_list = Gtk.ListBox()
+        self.results_list.set_selection_mode(Gtk.SelectionMode.BROWSE)
+        self.results_list.add_css_class("navigation-sidebar")
+        self.results_list.connect("row-activated", self.on_row_activated)
+        scrolled.set_child(self.results_list)
+        self.results_stack.add_named(scrolled, "list")
+
+        empty_page = Adw.StatusPage()
+        empty_page.set_icon_name("avatar-default-symbolic")
+        empty_page.set_title("No Contacts")
+        empty_page.set_description("Contacts appear here once they're synced from the server")
+        empty_page.add_css_class("compact")
+        self.results_stack.add_named(empty_page, "empty")
+
+        content_box.append(self.results_stack)
+        toolbar_view.set_content(content_box)
+        self.set_child(toolbar_view)
+
+        # Let arrow keys move through results while typing
+        key_controller = Gtk.EventControllerKey()
+        key_controller.connect("key-pressed", self.on_key_pressed)
+        self.search_entry.add_controller(key_controller)
+
+        self.set_focus(self.search_entry)
+
+    def update_results(self):
+        """Rebuild the contact list for the current search."""
+        while True:
+            row = self.results_list.get_first_child()
+            if row is None:
+                break
+            self.results_list.remove(row)
+
+        matches = match_contacts(self.contacts, self.search_entry.get_text())[:MAX_RESULTS]
+        for contact in matches:
+            row = Adw.ActionRow()
+            row.set_title(contact.name)
+            row.set_subtitle(", ".join(format_phone_number(handle.address) for handle in contact.handles))
+            row.set_subtitle_lines(1)
+            row.add_prefix(Adw.Avatar.new(32, contact.name, True))
+            row.set_activatable(True)
+            row.contact = contact
+            self.results_list.append(row)
+
+        self.results_stack.set_visible_child_name("list" if matches or self.contacts else "empty")
+        first_row = self.results_list.get_row_at_index(0)
+        if first_row:
+            self.results_list.select_row(first_row)
+
+    def on_key_pressed(self, controller, keyval, keycode, state):
+        """Move the selection with the arrow keys."""
+        if keyval not in (Gdk.KEY_Up, Gdk.KEY_Down):
+            return False
+
+        row = self.results_list.get_selected_row()
+        index = row.get_index() if row else -1
+        index += -1 if keyval == Gdk.KEY_Up else 1
+
+        next_row = self.results_list.get_row_at_index(max(index, 0))
+        if next_row:
+            self.results_list.select_row(next_row)
+        return True
+
+    def on_activate(self, entry):
+        """Share the selected contact."""
+        row = self.results_list.get_selected_row()
+        if row is None:
+            return
+        self.close()
+        self.on_selected(row.contact)
+
+    def on_row_activated(self, list_box, row):
+        """Handle clicking a contact."""
+        self.results_list.select_row(row)
+        self.on_activate(self.search_entry)
@@ -0,0 +1,153 @@
+"""
+Start Page
+Shown in the content pane when no conversation is selected
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw
+from typing import List
+from ..db.models import ChatRecord
+
+# Maximum number of recent chats shown as tiles
+MAX_RECENT_TILES = 8
+
+
+class StartPage(Gtk.ScrolledWindow):
+    """Start page listing pinned and recent chats as large tiles."""
+
+    def __init__(self, window):
+        super().__init__()
+
+        # The main window provides avatar loading and chat opening
+        self.window = window
+
+        self.set_vexpand(True)
+        self.set_policy(Gtk.PolicyType.NEVER, Gtk.PolicyType.AUTOMATIC)
+
+        self.setup_ui()
+
+    def setup_ui(self):
+        """Set up the start page UI."""
+        clamp = Adw.Clamp()
+        clamp.set_maximum_size(720)
+
+        content_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=18)
+        content_box.set_margin_top(36)
+        content_box.set_margin_bottom(36)
+        content_box.set_margin_start(24)
+        content_box.set_margin_end(24)
+
+        # Header
+        title_label = Gtk.Label()
+        title_label.set_text("Welcome to BlueBubbles")
+        title_label.add_css_class("title-1")
+        content_box.append(title_label)
+
+        subtitle_label = Gtk.Label()
+        subtitle_label.set_text("Pick up a conversation or start a new one")
+        subtitle_label.add_css_class("dim-label")
+        content_box.append(subtitle_label)
+
+        new_chat_button = Gtk.Button()
+        new_chat_button.set_label("New Chat")
+        new_chat_button.set_halign(Gtk.Align.CENTER)
+        new_chat_button.add_css_class("pill")
+        new_chat_button.add_css_class("suggested-action")
+        new_chat_button.connect("clicked", self.window.on_new_chat_clicked)
+        content_box.append(new_chat_button)
+
+        # Pinned chats section
+        self.pinned_label = self.create_section_label("Pinned")
+        content_box.append(self.pinned_label)
+        self.pinned_flow = self.create_flow_box()
+        content_box.append(self.pinned_flow)
+
+        # Recent chats section
+        self.recent_label = self.create_section_label("Recent")
+        content_box.append(self.recent_label)
+        self.recent_flow = self.create_flow_box()
+        content_box.append(self.recent_flow)
+
+        clamp.set_child(content_box)
+        self.set_child(clamp)
+
+        self.set_chats([], [])
+
+    def create_section_label(self, text: str) -> Gtk.Label:
+        """Create a section heading."""
+        label = Gtk.Label()
+        label.set_text(text)
+        label.set_halign(Gtk.Align.START)
+        label.set_margin_top(12)
+        label.add_css_class("heading")
+        return label
+
+    def create_flow_box(self) -> Gtk.FlowBox:
+        """Create a flow box for chat tiles."""
+        flow_box = Gtk.FlowBox()
+        flow_box.set_selection_mode(Gtk.SelectionMode.NONE)
+        flow_box.set_homogeneous(True)
+        flow_box.set_min_children_per_line(2)
+        flow_box.set_max_children_per_line(6)
+        flow_box.set_column_spacing(12)
+        flow_box.set_row_spacing(12)
+        return flow_box
+
+    def set_chats(self, pinned: List[ChatRecord], recent: List[ChatRecord]):
+        """Rebuild the tiles for the given pinned and recent chats."""
+        self.fill_flow_box(self.pinned_flow, pinned)
+        self.pinned_label.set_visible(bool(pinned))
+        self.pinned_flow.set_visible(bool(pinned))
+
+        recent = recent[:MAX_RECENT_TILES]
+        self.fill_flow_box(self.recent_flow, recent)
+        self.recent_label.set_visible(bool(recent))
+        self.recent_flow.set_visible(bool(recent))
+
+    def fill_flow_box(self, flow_box: Gtk.FlowBox, chats: List[ChatRecord]):
+        """Replace the tiles in a flow box."""
+        while True:
+            child = flow_box.get_first_child()
+            if child is None:
+                break
+            flow_box.remove(child)
+
+        for chat in chats:
+            flow_box.append(self.create_chat_tile(chat))
+
+    def create_chat_tile(self, chat: ChatRecord) -> Gtk.Widget:
+        """Create a large clickable tile for a chat."""
+        button = Gtk.Button()
+        button.add_css_class("flat")
+        button.add_css_class("chat-tile")
+        button.set_tooltip_text(chat.display_title)
+
+        tile_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=6)
+        tile_box.set_margin_top(12)
+        tile_box.set_margin_bottom(12)
+
+        avatar = Gtk.Image()
+        if chat.is_group_chat:
+            avatar.set_from_icon_name("group-symbolic")
+        else:
+            avatar.set_from_icon_name("person-symbolic")
+        avatar.add_css_class("circular")
+        avatar.set_pixel_size(64)
+        tile_box.append(avatar)
+
+        name_label = Gtk.Label()
+        name_label.set_text(chat.display_title)
+        name_label.set_ellipsize(3)  # ELLIPSIZE_END
+        name_label.set_max_width_chars(14)
+        tile_box.append(name_label)
+
+        button.set_child(tile_box)
+        button.connect("clicked", lambda b: self.window.select_chat(chat.guid))
+
+        # Load avatar asynchronously
+        self.window.load_chat_avatar_async(avatar, chat)
+
+        return button
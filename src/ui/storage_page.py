@@ -0,0 +1,214 @@
+"""
+Storage Page
+Preferences page showing how much disk space the local cache takes, with ways to free it
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw, GLib
+import threading
+from typing import Any, Dict
+
+from ..db.models import ChatRecord
+from .undo_toast import create_undo_toast
+
+# Conversations listed in the breakdown, largest first
+CHAT_LIST_LIMIT = 50
+
+
+class StoragePage(Adw.PreferencesPage):
+    """Cache size overview with per-chat breakdown and clear/compact actions."""
+
+    def __init__(self, dialog, application):
+        super().__init__()
+        self.dialog = dialog
+        self.application = application
+        self.chat_service = application.get_chat_service()
+        self.attachment_cache = self.chat_service.attachment_cache
+        # Rows of the per-chat breakdown, removed on refresh
+        self.chat_rows = []
+
+        self.set_title("Storage")
+        self.set_icon_name("drive-harddisk-symbolic")
+
+        self.setup_ui()
+        self.refresh()
+
+    def setup_ui(self):
+        """Set up the page UI."""
+        usage_group = Adw.PreferencesGroup()
+        usage_group.set_title("Offline Cache")
+        usage_group.set_description("Messages and attachments kept on this computer. Everything can be fetched again from your server.")
+
+        # Database Row
+        self.database_row = Adw.ActionRow()
+        self.database_row.set_title("Message Database")
+        self.database_row.set_subtitle("Measuring…")
+
+        self.vacuum_button = Gtk.Button()
+        self.vacuum_button.set_label("Compact")
+        self.vacuum_button.set_tooltip_text("Give space freed by deleted messages back to the disk")
+        self.vacuum_button.set_valign(Gtk.Align.CENTER)
+        self.vacuum_button.connect("clicked", self.on_vacuum_clicked)
+        self.database_row.add_suffix(self.vacuum_button)
+        usage_group.add(self.database_row)
+
+        # Attachments Row
+        self.attachments_row = Adw.ActionRow()
+        self.attachments_row.set_title("Attachments")
+        self.attachments_row.set_subtitle("Measuring…")
+
+        self.clear_attachments_button = Gtk.Button()
+        self.clear_attachments_button.set_label("Clear")
+        self.clear_attachments_button.add_css_class("destructive-action")
+        self.clear_attachments_button.set_valign(Gtk.Align.CENTER)
+        self.clear_attachments_button.connect("clicked", self.on_clear_attachments_clicked)
+        self.attachments_row.add_suffix(self.clear_attachments_button)
+        usage_group.add(self.attachments_row)
+
+        # Attachment Cache Limit
+        self.cache_limit_row = Adw.SpinRow.new_with_range(100, 1024 * 1024, 100)
+        self.cache_limit_row.set_title("Attachment Cache Limit")
+        self.cache_limit_row.set_subtitle("Megabytes; the least recently viewed attachments are removed beyond it")
+        self.cache_limit_row.set_value(self.application.config_manager.get_attachment_cache_limit())
+        self.cache_limit_row.connect("notify::value", self.on_cache_limit_changed)
+        usage_group.add(self.cache_limit_row)
+
+        self.add(usage_group)
+
+        # Per-chat Breakdown
+        self.chats_group = Adw.PreferencesGroup()
+        self.chats_group.set_title("Conversations")
+        self.chats_group.set_description("Space taken by each conversation's messages and attachments")
+        self.add(self.chats_group)
+
+    def format_size(self, size: int) -> str:
+        """Format a size in bytes for display."""
+        return self.attachment_cache.get_file_size_string(size)
+
+    def refresh(self):
+        """Measure the cache in the background and show the results."""
+        def run():
+            try:
+                usage = self.chat_service.get_storage_usage()
+            except Exception as e:
+                GLib.idle_add(self.dialog.add_toast, Adw.Toast.new(f"Failed to measure storage: {e}"))
+                return
+            GLib.idle_add(self.show_usage, usage)
+
+        threading.Thread(target=run, daemon=True).start()
+
+    def show_usage(self, usage: Dict[str, Any]):
+        """Fill in the sizes and the per-chat breakdown."""
+        self.database_row.set_subtitle(self.format_size(usage['database']))
+        self.attachments_row.set_subtitle(self.format_size(usage['attachments']))
+        self.clear_attachments_button.set_sensitive(usage['attachments'] > 0)
+
+        for row in self.chat_rows:
+            self.chats_group.remove(row)
+        self.chat_rows = []
+
+        for chat, messages, size in usage['chats'][:CHAT_LIST_LIMIT]:
+            row = Adw.ActionRow()
+            row.set_use_markup(False)
+            row.set_title(chat.display_title)
+            row.set_subtitle(f"{self.format_size(size)} · {messages} messages")
+
+            clear_button = Gtk.Button.new_from_icon_name("user-trash-symbolic")
+            clear_button.set_tooltip_text("Clear Cached Messages")
+            clear_button.add_css_class("flat")
+            clear_button.set_valign(Gtk.Align.CENTER)
+            clear_button.connect("clicked", self.on_clear_chat_clicked, chat)
+            row.add_suffix(clear_button)
+
+            self.chats_group.add(row)
+            self.chat_rows.append(row)
+
+        if not self.chat_rows:
+            row = Adw.ActionRow()
+            row.set_title("No cached conversations")
+            self.chats_group.add(row)
+            self.chat_rows.append(row)
+        return False
+
+    def confirm(self, heading: str, body: str, label: str, on_confirmed):
+        """Ask before deleting cached data."""
+        dialog = Adw.AlertDialog()
+        dialog.set_heading(heading)
+        dialog.set_body(body)
+        dialog.add_response("cancel", "Cancel")
+        dialog.add_response("confirm", label)
+        dialog.set_response_appearance("confirm", Adw.ResponseAppearance.DESTRUCTIVE)
+        dialog.set_default_response("cancel")
+        dialog.set_close_response("cancel")
+        dialog.connect("response", lambda d, response: on_confirmed() if response == "confirm" else None)
+        dialog.present(self.dialog)
+
+    def on_clear_attachments_clicked(self, button):
+        """Delete every cached attachment."""
+        def clear():
+            self.chat_service.clear_attachment_cache()
+            self.dialog.add_toast(Adw.Toast.new("Attachment cache cleared"))
+            self.refresh()
+
+        self.confirm(
+            "Clear Attachments?",
+            "Cached photos, videos and files are deleted. They're downloaded again when you view them.",
+            "Clear", clear
+        )
+
+    def on_cache_limit_changed(self, row, pspec):
+        """Save the new limit and trim the cache to it in the background."""
+        megabytes = int(row.get_value())
+
+        def run():
+            freed = self.chat_service.set_attachment_cache_limit(megabytes)
+            if freed:
+                GLib.idle_add(self.refresh)
+
+        threading.Thread(target=run, daemon=True).start()
+
+    def on_clear_chat_clicked(self, button, chat: ChatRecord):
+        """Delete one conversation's cached messages and attachments once the undo toast times out."""
+        def clear():
+            self.chat_service.clear_chat_cache(chat)
+
+            main_window = self.application.main_window
+            if main_window and main_window.current_chat and main_window.current_chat.guid == chat.guid:
+                main_window.refresh_current_chat_messages()
+            self.refresh()
+
+        def revert():
+            button.set_sensitive(True)
+
+        # Greyed out until the cache is cleared or the clear is undone
+        button.set_sensitive(False)
+        pending_actions = self.application.pending_actions
+        action_id = pending_actions.add(f"Clear the cache of {chat.display_title}", clear, revert)
+        self.dialog.add_toast(
+            create_undo_toast(f"Clearing the cache of {chat.display_title}", pending_actions, action_id)
+        )
+
+    def on_vacuum_clicked(self, button):
+        """Compact the database in the background."""
+        button.set_sensitive(False)
+        self.database_row.set_subtitle("Compacting…")
+
+        def run():
+            try:
+                self.chat_service.vacuum_database()
+                message = "Database compacted"
+            except Exception as e:
+                message = f"Failed to compact database: {e}"
+            GLib.idle_add(self.on_vacuum_finished, message)
+
+        threading.Thread(target=run, daemon=True).start()
+
+    def on_vacuum_finished(self, message: str):
+        """Show the compacted size."""
+        self.vacuum_button.set_sensitive(True)
+        self.dialog.add_toast(Adw.Toast.new(message))
+        self.refresh()
+        return False
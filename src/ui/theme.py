@@ -0,0 +1,66 @@
+"""
+Theme Styles
+Loads the app stylesheet with its dark and high contrast variants and the accent override
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw
+from pathlib import Path
+from ..utils.accent_colors import build_accent_css
+
+STYLES_DIR = Path(__file__).parent
+
+
+class ThemeStyles:
+    """
+    Keeps the app's CSS in step with the desktop style.
+
+    styles.css defines named colors for bubbles, links and badges; the dark and
+    high contrast stylesheets redefine them and are swapped in whenever the
+    style manager changes, so no color is fixed to one appearance.
+    """
+
+    def __init__(self, display, config_manager):
+        self.config_manager = config_manager
+        self.style_manager = Adw.StyleManager.get_for_display(display)
+
+        self.base_provider = Gtk.CssProvider()
+        self.base_provider.load_from_path(str(STYLES_DIR / 'styles.css'))
+        self.variant_provider = Gtk.CssProvider()
+        self.accent_provider = Gtk.CssProvider()
+        self.effects_provider = Gtk.CssProvider()
+
+        # Added in order, so variants override the base, the accent overrides both,
+        # and reduced effects override everything
+        for provider in (self.base_provider, self.variant_provider, self.accent_provider, self.effects_provider):
+            Gtk.StyleContext.add_provider_for_display(display, provider, Gtk.STYLE_PROVIDER_PRIORITY_APPLICATION)
+
+        self.style_manager.connect("notify::dark", lambda manager, pspec: self.update())
+        self.style_manager.connect("notify::high-contrast", lambda manager, pspec: self.update())
+        self.update()
+
+    def update(self):
+        """Load the stylesheets matching the current appearance, accent and effects preference."""
+        dark = self.style_manager.get_dark()
+        variants = []
+        if dark:
+            variants.append('styles-dark.css')
+        if self.style_manager.get_high_contrast():
+            variants.append('styles-hc.css')
+        self.variant_provider.load_from_string(
+            "".join((STYLES_DIR / name).read_text(encoding='utf-8') for name in variants)
+        )
+
+        # CSS variables arrived with libadwaita 1.6; older versions only know named colors
+        css_variables = Adw.get_minor_version() >= 6
+        self.accent_provider.load_from_string(
+            build_accent_css(self.config_manager.get_accent_color(), dark, css_variables)
+        )
+
+        reduced = self.config_manager.get_reduced_effects()
+        self.effects_provider.load_from_string(
+            (STYLES_DIR / 'styles-reduced.css').read_text(encoding='utf-8') if reduced else ""
+        )
@@ -0,0 +1,20 @@
+"""
+Undo Toast
+Toast offering to undo an action held in the pending action queue
+"""
+
+import gi
+gi.require_version('Adw', '1')
+
+from gi.repository import Adw
+
+from ..utils.pending_actions import UNDO_SECONDS, PendingActionQueue
+
+
+def create_undo_toast(title: str, pending_actions: PendingActionQueue, action_id: int) -> Adw.Toast:
+    """Create a toast whose Undo button cancels a pending action."""
+    toast = Adw.Toast.new(title)
+    toast.set_button_label("Undo")
+    toast.set_timeout(UNDO_SECONDS)
+    toast.connect("button-clicked", lambda toast: pending_actions.undo(action_id))
+    return toast
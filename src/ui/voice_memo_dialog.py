@@ -0,0 +1,233 @@
+"""
+Voice Memo Dialog
+Records a voice memo from a chosen input device, with a live level meter and
+a trim step before sending
+"""
+
+import gi
+gi.require_version('Gtk', '4.0')
+gi.require_version('Adw', '1')
+
+from gi.repository import Gtk, Adw, GLib
+import os
+import tempfile
+import time
+from typing import Callable, Optional
+from ..services.voice_recorder import (
+    VoiceRecorder, list_input_devices, get_wav_duration, trim_wav
+)
+
+
+class VoiceMemoDialog(Adw.Dialog):
+    """Record, trim and send a voice memo."""
+
+    def __init__(self, config_manager,
+                 on_send: Callable[[str, tempfile.TemporaryDirectory], None]):
+        super().__init__()
+
+        self.config_manager = config_manager
+        # Called with the file path of the trimmed recording and the directory
+        # holding it, which the sender removes once the upload finishes
+        self.on_send = on_send
+        self.devices = list_input_devices()
+        self.recorder = None
+        # Recordings are written here; removed when the dialog closes unless sent
+        self.temp_dir: Optional[tempfile.TemporaryDirectory] = None
+        self.recording_path = None
+        self.duration = 0.0
+        self.started_at = None
+        self.timer_id = None
+
+        self.set_title("Voice Memo")
+        self.set_content_width(420)
+
+        self.setup_ui()
+        self.connect("closed", self.on_closed)
+
+    def setup_ui(self):
+        """Set up the dialog UI."""
+        toolbar_view = Adw.ToolbarView()
+
+        header_bar = Adw.HeaderBar()
+        header_bar.set_show_end_title_buttons(False)
+        header_bar.set_show_start_title_buttons(False)
+
+        cancel_button = Gtk.Button()
+        cancel_button.set_label("Cancel")
+        cancel_button.connect("clicked", lambda b: self.close())
+        header_bar.pack_start(cancel_button)
+
+        self.send_button = Gtk.Button()
+        self.send_button.set_label("Send")
+        self.send_button.add_css_class("suggested-action")
+        self.send_button.set_sensitive(False)
+        self.send_button.connect("clicked", self.on_send_clicked)
+        header_bar.pack_end(self.send_button)
+
+        toolbar_view.add_top_bar(header_bar)
+
+        content_box = Gtk.Box(orientation=Gtk.Orientation.VERTICAL, spacing=12)
+        content_box.set_margin_top(12)
+        content_box.set_margin_bottom(24)
+        content_box.set_margin_start(12)
+        content_box.set_margin_end(12)
+
+        # Input device
+        device_group = Adw.PreferencesGroup()
+        self.device_row = Adw.ComboRow()
+        self.device_row.set_title("Microphone")
+        self.device_row.set_model(Gtk.StringList.new(
+            ["Default"] + [name for name, device in self.devices]
+        ))
+        saved_device = self.config_manager.get('audio.input_device')
+        for index, (name, device) in enumerate(self.devices):
+            if name == saved_device:
+                self.device_row.set_selected(index + 1)
+        self.device_row.connect("notify::selected", self.on_device_changed)
+        device_group.add(self.device_row)
+        content_box.append(device_group)
+
+        # Level meter and elapsed time
+        self.level_bar = Gtk.LevelBar()
+        self.level_bar.set_min_value(0.0)
+        self.level_bar.set_max_value(1.0)
+        content_box.append(self.level_bar)
+
+        self.time_label = Gtk.Label()
+        self.time_label.set_text("0:00")
+        self.time_label.add_css_class("title-2")
+        self.time_label.add_css_class("numeric")
+        content_box.append(self.time_label)
+
+        self.record_button = Gtk.Button()
+        self.record_button.set_icon_name("media-record-symbolic")
+        self.record_button.set_tooltip_text("Start recording")
+        self.record_button.set_halign(Gtk.Align.CENTER)
+        self.record_button.add_css_class("circular")
+        self.record_button.add_css_class("destructive-action")
+        self.record_button.connect("clicked", self.on_record_clicked)
+        content_box.append(self.record_button)
+
+        # Trim controls, shown once a recording exists
+        self.trim_group = Adw.PreferencesGroup()
+        self.trim_group.set_title("Trim")
+        self.trim_group.set_visible(False)
+
+        self.trim_start_row = Adw.SpinRow.new_with_range(0, 1, 0.1)
+        self.trim_start_row.set_title("Start")
+        self.trim_start_row.set_digits(1)
+        self.trim_start_row.connect("notify::value", self.on_trim_changed)
+        self.trim_group.add(self.trim_start_row)
+
+        self.trim_end_row = Adw.SpinRow.new_with_range(0, 1, 0.1)
+        self.trim_end_row.set_title("End")
+        self.trim_end_row.set_digits(1)
+        self.trim_end_row.connect("notify::value", self.on_trim_changed)
+        self.trim_group.add(self.trim_end_row)
+
+        content_box.append(self.trim_group)
+
+        toolbar_view.set_content(content_box)
+        self.set_child(toolbar_view)
+
+    def get_selected_device(self):
+        """Get the selected Gst.Device, or None for the default input."""
+        index = self.device_row.get_selected()
+        return self.devices[index - 1][1] if index > 0 else None
+
+    def on_device_changed(self, row, pspec):
+        """Remember the chosen input device."""
+        index = row.get_selected()
+        self.config_manager.set('audio.input_device', self.devices[index - 1][0] if index > 0 else '')
+
+    def on_record_clicked(self, button):
+        """Start or stop recording."""
+        if self.recorder:
+            self.recorder.stop()
+            self.record_button.set_sensitive(False)
+            return
+
+        if not self.temp_dir:
+            self.temp_dir = tempfile.TemporaryDirectory(prefix="bluebubbles-")
+        self.recording_path = os.path.join(self.temp_dir.name, "Voice Memo.wav")
+        self.recorder = VoiceRecorder(
+            self.get_selected_device(),
+            on_level=self.level_bar.set_value,
+            on_finished=self.on_recording_finished
+        )
+        self.recorder.start(self.recording_path)
+
+        self.started_at = time.monotonic()
+        self.timer_id = GLib.timeout_add(250, self.update_elapsed)
+
+        self.device_row.set_sensitive(False)
+        self.trim_group.set_visible(False)
+        self.send_button.set_sensitive(False)
+        self.record_button.set_icon_name("media-playback-stop-symbolic")
+        self.record_button.set_tooltip_text("Stop recording")
+
+    def update_elapsed(self):
+        """Update the elapsed recording time."""
+        elapsed = int(time.monotonic() - self.started_at)
+        self.time_label.set_text(f"{elapsed // 60}:{elapsed % 60:02d}")
+        return True
+
+    def stop_timer(self):
+        """Stop the elapsed time updates."""
+        if self.timer_id:
+            GLib.source_remove(self.timer_id)
+            self.timer_id = None
+
+    def on_recording_finished(self, file_path):
+        """Switch to the trim step once the recording is written."""
+        self.recorder = None
+        self.stop_timer()
+        self.level_bar.set_value(0.0)
+
+        self.device_row.set_sensitive(True)
+        self.record_button.set_sensitive(True)
+        self.record_button.set_icon_name("media-record-symbolic")
+        self.record_button.set_tooltip_text("Record again")
+
+        if not file_path:
+            self.time_label.set_text("Recording failed")
+            return
+
+        self.duration = get_wav_duration(file_path)
+        for row in (self.trim_start_row, self.trim_end_row):
+            row.get_adjustment().set_upper(self.duration)
+        self.trim_start_row.set_value(0)
+        self.trim_end_row.set_value(self.duration)
+
+        self.trim_group.set_visible(True)
+        self.send_button.set_sensitive(True)
+
+    def on_trim_changed(self, row, pspec):
+        """Keep the trim range valid."""
+        start = self.trim_start_row.get_value()
+        end = self.trim_end_row.get_value()
+        self.send_button.set_sensitive(self.recording_path is not None and end > start)
+
+    def on_send_clicked(self, button):
+        """Trim the recording if needed and send it."""
+        start = self.trim_start_row.get_value()
+        end = self.trim_end_row.get_value()
+
+        file_path = self.recording_path
+        if start > 0 or end < self.duration:
+            file_path = os.path.join(os.path.dirname(self.recording_path), "Voice Memo (trimmed).wav")
+            trim_wav(self.recording_path, file_path, start, end)
+
+        self.on_send(file_path, self.temp_dir)
+        self.temp_dir = None
+        self.close()
+
+    def on_closed(self, dialog):
+        """Stop any recording still in progress and remove unsent recordings."""
+        self.stop_timer()
+        if self.recorder:
+            self.recorder.cancel()
+            self.recorder = None
+        if self.temp_dir:
+            self.temp_dir.cleanup()
+            self.temp_dir = None
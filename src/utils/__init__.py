@@ -0,0 +1,4 @@
+"""
+Utilities Module
+Pure helpers shared by the services and the UI
+"""
@@ -0,0 +1,50 @@
+"""Accent color choices, and the CSS that applies one over the desktop's accent."""
+
+# Accent IDs in the order shown in preferences -> background color, from the GNOME palette
+ACCENT_COLORS = {
+    'blue': '#3584e4',
+    'teal': '#2190a4',
+    'green': '#3a944a',
+    'yellow': '#c88800',
+    'orange': '#ed5b00',
+    'red': '#e62d42',
+    'pink': '#d56199',
+    'purple': '#9141ac',
+    'slate': '#6f8396',
+}
+
+# 'system' keeps the desktop's accent
+ACCENT_COLOR_IDS = ['system'] + list(ACCENT_COLORS)
+
+# Text on accent backgrounds; white doesn't read on yellow
+ACCENT_FOREGROUNDS = {'yellow': 'rgba(0, 0, 0, 0.8)'}
+
+
+def build_accent_css(accent: str, dark: bool, css_variables: bool = False) -> str:
+    """
+    Get CSS overriding the accent colors, or an empty string to keep the desktop's.
+
+    Accent text is lightened on dark backgrounds and darkened on light ones so
+    it stays readable. With css_variables, the libadwaita 1.6+ variables are set
+    as well as the older named colors.
+    """
+    background = ACCENT_COLORS.get(accent)
+    if background is None:
+        return ""
+
+    foreground = ACCENT_FOREGROUNDS.get(accent, 'white')
+    text = f"mix({background}, white, 0.3)" if dark else f"mix({background}, black, 0.1)"
+    css = (
+        f"@define-color accent_bg_color {background};\n"
+        f"@define-color accent_fg_color {foreground};\n"
+        f"@define-color accent_color {text};\n"
+    )
+    if css_variables:
+        css += (
+            ":root {\n"
+            f"    --accent-bg-color: {background};\n"
+            f"    --accent-fg-color: {foreground};\n"
+            f"    --accent-color: {text};\n"
+            "}\n"
+        )
+    return css
@@ -0,0 +1,46 @@
+"""Checks of addresses typed into the New Chat dialog before a chat is created."""
+
+import re
+from typing import List, Optional
+
+EMAIL_PATTERN = re.compile(r'^[^@\s]+@[^@\s]+\.[^@\s]+$')
+
+# Phone numbers may be written with spacing and punctuation, but need enough digits to dial
+PHONE_PATTERN = re.compile(r'^\+?[\d\s().\-]+$')
+MIN_PHONE_DIGITS = 3
+
+# How an address will be reached: 'imessage', 'sms', 'invalid', or 'unknown' when the server couldn't say
+REACHABILITY_LABELS = {
+    'imessage': "iMessage",
+    'sms': "SMS",
+    'invalid': "Can't be reached",
+    'unknown': "Couldn't check",
+}
+
+
+def parse_addresses(text: str) -> List[str]:
+    """Split the comma-separated addresses of a new chat."""
+    return [part.strip() for part in text.split(',') if part.strip()]
+
+
+def is_valid_address(address: str) -> bool:
+    """Check whether an address looks like an email address or a phone number."""
+    if '@' in address:
+        return bool(EMAIL_PATTERN.match(address))
+    digits = re.sub(r'\D', '', address)
+    return bool(PHONE_PATTERN.match(address)) and len(digits) >= MIN_PHONE_DIGITS
+
+
+def get_reachability(address: str, imessage_available: Optional[bool]) -> str:
+    """
+    Get how a new chat would reach an address, given the server's iMessage availability check.
+
+    Phone numbers without iMessage fall back to SMS; email addresses can't.
+    """
+    if not is_valid_address(address):
+        return 'invalid'
+    if imessage_available is None:
+        return 'unknown'
+    if imessage_available:
+        return 'imessage'
+    return 'invalid' if '@' in address else 'sms'
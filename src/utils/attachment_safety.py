@@ -0,0 +1,92 @@
+"""Which received attachments need a warning before they're opened."""
+
+import os
+
+# Extensions of programs, scripts and installers -> what to call them
+RISKY_EXTENSIONS = {
+    'exe': "Windows program",
+    'msi': "Windows installer",
+    'bat': "Windows batch script",
+    'cmd': "Windows batch script",
+    'com': "Windows program",
+    'scr': "Windows screensaver program",
+    'ps1': "PowerShell script",
+    'vbs': "VBScript",
+    'js': "JavaScript file",
+    'jar': "Java program",
+    'sh': "shell script",
+    'bash': "shell script",
+    'py': "Python script",
+    'pl': "Perl script",
+    'run': "Linux installer",
+    'bin': "program",
+    'appimage': "Linux program",
+    'desktop': "application launcher",
+    'deb': "Debian package",
+    'rpm': "RPM package",
+    'flatpakref': "Flatpak app reference",
+    'apk': "Android app",
+    'app': "Mac application",
+    'command': "Mac shell script",
+    'dmg': "Mac disk image",
+    'pkg': "Mac installer",
+    'iso': "disk image",
+    'lnk': "Windows shortcut",
+    'reg': "Windows registry file",
+    'docm': "Word document with macros",
+    'xlsm': "Excel workbook with macros",
+    'pptm': "PowerPoint presentation with macros",
+}
+
+# MIME types of programs and scripts, whatever the file is called
+RISKY_MIME_TYPES = {
+    'application/x-executable', 'application/x-sharedlib', 'application/x-msdownload',
+    'application/x-msi', 'application/x-dosexec', 'application/x-shellscript', 'application/x-sh',
+    'application/x-desktop', 'application/java-archive', 'application/vnd.android.package-archive',
+    'application/x-apple-diskimage', 'application/x-deb', 'application/x-rpm',
+    'application/javascript', 'text/javascript', 'text/x-python', 'application/x-python-code',
+}
+
+# Everyday file types opened without asking; anything else is unusual
+SAFE_MIME_PREFIXES = ('image/', 'video/', 'audio/')
+SAFE_MIME_TYPES = {
+    'text/plain', 'text/vcard', 'text/x-vcard', 'text/calendar', 'application/pdf',
+    'application/msword', 'application/vnd.ms-excel', 'application/vnd.ms-powerpoint',
+    'application/vnd.openxmlformats-officedocument.wordprocessingml.document',
+    'application/vnd.openxmlformats-officedocument.spreadsheetml.sheet',
+    'application/vnd.openxmlformats-officedocument.presentationml.presentation',
+    'application/vnd.oasis.opendocument.text', 'application/vnd.oasis.opendocument.spreadsheet',
+    'application/vnd.oasis.opendocument.presentation', 'application/rtf',
+    'application/x-iwork-keynote-sffkey', 'application/x-iwork-pages-sffpages',
+    'application/x-iwork-numbers-sffnumbers', 'application/zip',
+}
+
+
+def get_extension(file_name: str) -> str:
+    """Get a file's extension, lowercased and without the dot."""
+    return os.path.splitext(file_name)[1].lstrip('.').lower()
+
+
+def is_risky(file_name: str, mime_type: str) -> bool:
+    """Check whether an attachment is a program, script or installer, even one posing as a document."""
+    if get_extension(file_name) in RISKY_EXTENSIONS:
+        return True
+    return (mime_type or '').lower() in RISKY_MIME_TYPES
+
+
+def needs_open_confirmation(file_name: str, mime_type: str) -> bool:
+    """Check whether opening an attachment should be confirmed: risky or just unusual types."""
+    if is_risky(file_name, mime_type):
+        return True
+    mime_type = (mime_type or '').lower()
+    return not (mime_type.startswith(SAFE_MIME_PREFIXES) or mime_type in SAFE_MIME_TYPES)
+
+
+def describe_file_type(file_name: str, mime_type: str) -> str:
+    """Describe an attachment's type for a warning, e.g. "Windows program (.exe)"."""
+    extension = get_extension(file_name)
+    if extension in RISKY_EXTENSIONS:
+        return f"{RISKY_EXTENSIONS[extension]} (.{extension})"
+    if extension:
+        return f"{extension.upper()} file"
+    return f"file of type {mime_type}" if mime_type else "file of unknown type"
@@ -0,0 +1,59 @@
+"""Descriptions for iMessage app ("balloon") messages the client can't render natively."""
+
+from typing import Optional, Tuple
+
+# Bundle ID prefixes of balloons that are rendered normally (link previews)
+RENDERED_BALLOONS = (
+    'com.apple.messages.URLBalloonProvider',
+)
+
+# Known message extensions, matched against the end of the bundle ID:
+# extension ID -> (icon name, description)
+KNOWN_EXTENSIONS = {
+    'com.apple.PassbookUIService.PeerPaymentMessagesExtension':
+        ("wallet-symbolic", "Apple Pay payment"),
+    'com.gamerdelights.gamepigeon.ext':
+        ("applications-games-symbolic", "GamePigeon game"),
+    'com.apple.icloud.apps.messages.business.extension':
+        ("mail-message-new-symbolic", "Business Chat message"),
+    'com.apple.findmy.FindMyMessagesApp':
+        ("find-location-symbolic", "Find My location"),
+    'com.apple.Jellyfish.Animoji':
+        ("face-smile-symbolic", "Memoji sticker"),
+    'com.apple.siri.parsec.HashtagImagesApp':
+        ("image-x-generic-symbolic", "#images GIF"),
+}
+
+# Native balloon providers handled elsewhere or described generically
+KNOWN_PROVIDERS = {
+    'com.apple.DigitalTouchBalloonProvider':
+        ("input-tablet-symbolic", "Digital Touch message"),
+    'com.apple.Handwriting.HandwritingProvider':
+        ("document-edit-symbolic", "Handwritten message"),
+}
+
+
+def is_drawing_balloon(bundle_id: Optional[str]) -> bool:
+    """Check whether a balloon is a handwriting or Digital Touch drawing."""
+    return bool(bundle_id) and bundle_id.startswith(tuple(KNOWN_PROVIDERS))
+
+
+def describe_balloon(bundle_id: Optional[str]) -> Optional[Tuple[str, str]]:
+    """
+    Get an (icon name, description) placeholder for a balloon bundle ID.
+
+    Returns None for messages without a balloon or with one we render normally.
+    """
+    if not bundle_id or bundle_id.startswith(RENDERED_BALLOONS):
+        return None
+
+    for provider, description in KNOWN_PROVIDERS.items():
+        if bundle_id.startswith(provider):
+            return description
+
+    # Extension balloons look like "<plugin>:<team id>:<extension bundle id>"
+    extension_id = bundle_id.split(':')[-1]
+    if extension_id in KNOWN_EXTENSIONS:
+        return KNOWN_EXTENSIONS[extension_id]
+
+    return ("application-x-addon-symbolic", "iMessage app message")
@@ -0,0 +1,83 @@
+"""The first-run checklist of what the server can do, with a hint for each missing piece."""
+
+from dataclasses import dataclass
+from typing import Any, Dict, List, Optional
+
+# Check IDs in the order shown -> title
+CHECK_TITLES = {
+    'reachable': "Server reachable",
+    'auth': "Password accepted",
+    'socket': "Realtime connection",
+    'private_api': "Private API",
+    'contacts': "Contacts",
+}
+
+# Check ID -> how to fix it when it fails
+FIX_HINTS = {
+    'reachable': "Check the server URL and that the BlueBubbles server app is running on your Mac. "
+                 "If you connect through a proxy or tunnel, make sure it is up.",
+    'auth': "The password doesn't match the server's. Copy it from the server app's settings.",
+    'socket': "New messages will only arrive every few seconds instead of instantly. "
+              "If you use a proxy, make sure it forwards WebSocket connections.",
+    'private_api': "Reactions, typing indicators, edits and FaceTime links need the Private API. "
+                   "Enable it in the server app; it needs the helper bundle installed on the Mac.",
+    'contacts': "Names can't be shown for phone numbers and emails. Give the server app "
+                "Contacts access in the Mac's System Settings, under Privacy & Security.",
+}
+
+# HTTP statuses that mean the server turned down the password
+AUTH_FAILED_STATUSES = (401, 403)
+
+
+@dataclass
+class CapabilityCheck:
+    """The outcome of one checklist item."""
+    id: str
+    # None when it couldn't be checked because an earlier item failed
+    ok: Optional[bool]
+    detail: str = ""
+
+    @property
+    def title(self) -> str:
+        """Get the item's name."""
+        return CHECK_TITLES[self.id]
+
+    @property
+    def hint(self) -> Optional[str]:
+        """Get how to fix a failed item."""
+        return FIX_HINTS[self.id] if self.ok is False else None
+
+
+def check_server_info(server_info: Optional[Dict[str, Any]], error: Optional[str] = None,
+                      status: Optional[int] = None) -> List[CapabilityCheck]:
+    """
+    Judge the reachable, auth and Private API items from the server info request.
+
+    Pass the server info, or the error and HTTP status it failed with; a status
+    of None means the server couldn't be reached at all.
+    """
+    if server_info is None:
+        if status is None:
+            return [CapabilityCheck('reachable', False, error or ""),
+                    CapabilityCheck('auth', None), CapabilityCheck('private_api', None)]
+        reachable = CapabilityCheck('reachable', True)
+        if status in AUTH_FAILED_STATUSES:
+            return [reachable, CapabilityCheck('auth', False, error or ""), CapabilityCheck('private_api', None)]
+        return [reachable, CapabilityCheck('auth', None, error or ""), CapabilityCheck('private_api', None)]
+
+    version = server_info.get('server_version')
+    checks = [CapabilityCheck('reachable', True, f"Server {version}" if version else ""),
+              CapabilityCheck('auth', True)]
+    if not server_info.get('private_api'):
+        checks.append(CapabilityCheck('private_api', False, "Turned off"))
+    elif not server_info.get('helper_connected'):
+        checks.append(CapabilityCheck('private_api', False, "Turned on, but the helper isn't connected"))
+    else:
+        checks.append(CapabilityCheck('private_api', True, "Enabled"))
+    return checks
+
+
+def order_checks(checks: List[CapabilityCheck]) -> List[CapabilityCheck]:
+    """Sort checks into checklist order."""
+    order = list(CHECK_TITLES)
+    return sorted(checks, key=lambda check: order.index(check.id))
@@ -0,0 +1,37 @@
+"""Sidebar appearance preferences: avatar size, preview lines, timestamps and density."""
+
+from dataclasses import dataclass
+
+# Avatar sizes allowed in preferences, in pixels
+MIN_AVATAR_SIZE = 24
+MAX_AVATAR_SIZE = 64
+
+# Lines of the last message a row can show
+PREVIEW_LINE_CHOICES = [1, 2]
+
+# Characters of the last message shown per preview line
+PREVIEW_LINE_LENGTH = 60
+
+
+@dataclass
+class ChatListStyle:
+    """User chat list preferences."""
+    avatar_size: int = 40
+    preview_lines: int = 1
+    show_timestamps: bool = True
+    compact: bool = False
+
+    @property
+    def row_margin(self) -> int:
+        """Get the vertical padding of a chat row."""
+        return 3 if self.compact else 8
+
+    @property
+    def row_spacing(self) -> int:
+        """Get the space between a row's title, preview and captions."""
+        return 0 if self.compact else 4
+
+    @property
+    def preview_length(self) -> int:
+        """Get how much of the last message to show before cutting it off."""
+        return PREVIEW_LINE_LENGTH * self.preview_lines
@@ -0,0 +1,104 @@
+"""Merging of chats the server reports once per service (iMessage;-;X and SMS;-;X)."""
+
+import dataclasses
+import re
+from typing import Dict, FrozenSet, List, Optional
+
+# Preferred service for the chat that represents a merged conversation.
+# Keeping the choice stable means the sidebar row keeps its GUID.
+SERVICE_ORDER = ['iMessage', 'any', 'RCS', 'SMS']
+
+
+def normalize_address(address: str) -> str:
+    """Normalize a handle address so phone numbers and emails compare equal across services."""
+    address = address.strip().lower()
+    if '@' in address:
+        return address
+    # Phone numbers: drop spacing and punctuation, keep a leading +
+    digits = re.sub(r'\D', '', address)
+    return f"+{digits}" if address.startswith('+') else digits
+
+
+def participant_key(chat) -> FrozenSet[str]:
+    """Get the normalized participants of a chat, falling back to its identifier."""
+    if chat.participants:
+        return frozenset(normalize_address(p.address) for p in chat.participants)
+    return frozenset([normalize_address(chat.chat_identifier)])
+
+
+def service_rank(chat) -> int:
+    """Rank a chat's service for picking the primary chat of a merged conversation."""
+    try:
+        return SERVICE_ORDER.index(chat.service)
+    except ValueError:
+        return len(SERVICE_ORDER)
+
+
+def service_order(chats: List) -> List:
+    """Sort chats of one conversation so the primary chat comes first."""
+    return sorted(chats, key=lambda c: (service_rank(c), c.guid))
+
+
+def pick_send_chat(chat, service: Optional[str]) -> str:
+    """
+    Get the GUID of the chat to send on for a merged conversation.
+
+    With no preferred service, or none of that service in the conversation,
+    messages go to the primary chat, which is on the best available service.
+    """
+    if service:
+        for member in [chat] + chat.linked_chats:
+            if member.service == service:
+                return member.guid
+    return chat.guid
+
+
+def merge_chat_family(family: List) -> object:
+    """
+    Merge chats for the same conversation into one record.
+
+    The primary chat keeps its GUID and title; the last message and unread
+    count reflect the whole conversation, and the others become linked chats.
+    """
+    family = service_order(family)
+    primary, others = family[0], family[1:]
+    if not others:
+        return dataclasses.replace(primary, linked_chats=[])
+
+    latest = max(family, key=lambda c: c.last_message_date or 0)
+    return dataclasses.replace(
+        primary,
+        linked_chats=[dataclasses.replace(c, linked_chats=[]) for c in others],
+        last_message_text=latest.last_message_text,
+        last_message_date=latest.last_message_date,
+        last_message_from_me=latest.last_message_from_me,
+        last_message_address=latest.last_message_address,
+        unread_count=sum(c.unread_count for c in family),
+        nickname=primary.nickname or next((c.nickname for c in others if c.nickname), None)
+    )
+
+
+def merge_duplicate_chats(chats: List) -> List:
+    """
+    Collapse chats with the same participants on different services.
+
+    Two chats on the same service are never merged, since they are separate
+    conversations (e.g. two group chats with the same members). The result
+    keeps the order of each conversation's first chat in the input.
+    """
+    families: Dict[FrozenSet[str], List[List]] = {}
+    order = []
+
+    for chat in chats:
+        # Flatten records that were merged before, so merging is repeatable
+        for member in [chat] + chat.linked_chats:
+            groups = families.setdefault(participant_key(member), [])
+            family = next((f for f in groups
+                           if all(c.service != member.service for c in f)), None)
+            if family is None:
+                family = []
+                groups.append(family)
+                order.append(family)
+            family.append(member)
+
+    return [merge_chat_family(family) for family in order]
@@ -0,0 +1,195 @@
+"""Crash reports: written when the app dies, offered to the user on the next launch."""
+
+import faulthandler
+import os
+import platform
+import sys
+import threading
+import traceback
+from datetime import datetime
+from pathlib import Path
+from typing import Callable, List, Optional
+
+# Activity log lines included at the end of a report
+LOG_TAIL_LINES = 50
+
+# Reports kept in the crash directory; older ones are removed
+MAX_CRASH_REPORTS = 10
+
+# Where faulthandler writes the stack of a native crash (segfault, abort), turned into a
+# report on the next launch
+NATIVE_CRASH_FILE = 'native-crash.log'
+
+# Name of the report not yet offered to the user
+PENDING_FILE = 'pending'
+
+
+def get_crash_dir() -> Path:
+    """Get the directory crash reports are written to."""
+    data_home = os.environ.get('XDG_DATA_HOME') or os.path.expanduser('~/.local/share')
+    return Path(data_home) / 'bluebubbles-gtk' / 'crashes'
+
+
+def format_crash_report(version: str, details: str, log_lines: List[str],
+                        when: Optional[datetime] = None) -> str:
+    """Format a report: versions, what went wrong, then the last activity log lines."""
+    when = when or datetime.now()
+    lines = [
+        f"BlueBubbles Client {version} crash report",
+        f"Time: {when.isoformat(timespec='seconds')}",
+        f"Python: {platform.python_version()}",
+        f"Platform: {platform.platform()}",
+        "",
+        details.rstrip(),
+        "",
+        f"Last {len(log_lines[-LOG_TAIL_LINES:])} activity log lines:",
+    ]
+    lines.extend(log_lines[-LOG_TAIL_LINES:])
+    return "\n".join(lines) + "\n"
+
+
+def write_crash_report(crash_dir: Path, report: str, when: Optional[datetime] = None,
+                       pending: bool = True) -> Path:
+    """Save a report, marked to be offered on the next launch if pending; returns its path."""
+    when = when or datetime.now()
+    crash_dir.mkdir(parents=True, exist_ok=True)
+    path = crash_dir / f"crash-{when.strftime('%Y%m%d-%H%M%S-%f')}.txt"
+    path.write_text(report)
+    if pending:
+        mark_pending_report(path)
+
+    reports = sorted(crash_dir.glob('crash-*.txt'))
+    for old in reports[:-MAX_CRASH_REPORTS]:
+        old.unlink()
+    return path
+
+
+def mark_pending_report(path: Path):
+    """Offer a saved report on the next launch."""
+    (path.parent / PENDING_FILE).write_text(path.name)
+
+
+def take_pending_report(crash_dir: Path) -> Optional[Path]:
+    """Get the report written since the last launch, if any, so it's only offered once."""
+    pending = crash_dir / PENDING_FILE
+    try:
+        name = pending.read_text().strip()
+        pending.unlink()
+    except OSError:
+        return None
+    path = crash_dir / name
+    return path if name and path.is_file() else None
+
+
+def collect_native_crash(crash_dir: Path, version: str, log_lines: List[str]) -> Optional[Path]:
+    """Turn the stack faulthandler left from a native crash into a report."""
+    native_file = crash_dir / NATIVE_CRASH_FILE
+    try:
+        stack = native_file.read_text(errors='replace')
+    except OSError:
+        return None
+    if not stack.strip():
+        return None
+    native_file.write_text('')
+    details = "The app stopped on a fatal error (faulthandler stack):\n\n" + stack
+    return write_crash_report(crash_dir, format_crash_report(version, details, log_lines))
+
+
+class CrashReporter:
+    """
+    Write a crash report for the first unhandled exception of a run.
+
+    Covers the main thread, GTK callbacks and worker threads, but only offers the
+    report on the next launch if the exception ended the app. PyGObject passes
+    exceptions raised in GTK callbacks to sys.excepthook and the main loop carries
+    on, as does the app after a worker thread dies; those are passed to on_error
+    instead. Native crashes, including GLib errors that abort, are caught by
+    faulthandler and reported on the next launch.
+    """
+
+    def __init__(self, version: str, get_log_lines: Callable[[], List[str]], crash_dir: Path = None,
+                 in_main_loop: Callable[[], bool] = lambda: False,
+                 on_error: Callable[[str], None] = lambda summary: None):
+        self.version = version
+        # Gets the recent activity log lines, credentials removed
+        self.get_log_lines = get_log_lines
+        self.crash_dir = crash_dir or get_crash_dir()
+        # Whether the main loop is running, so an exception in the main thread isn't fatal
+        self.in_main_loop = in_main_loop
+        # Called with a one-line summary of an exception the app survived
+        self.on_error = on_error
+        # Path of the report written this run; later exceptions are usually fallout from the first
+        self.report_path: Optional[Path] = None
+        # Whether the report is offered on the next launch
+        self._report_pending = False
+        self._lock = threading.Lock()
+        self._native_file = None
+
+    def install(self) -> Optional[Path]:
+        """Install the hooks; returns a report from the last run not yet offered to the user."""
+        log_lines = self._read_log_lines()
+        collect_native_crash(self.crash_dir, self.version, log_lines)
+        pending = take_pending_report(self.crash_dir)
+
+        self.crash_dir.mkdir(parents=True, exist_ok=True)
+        self._native_file = open(self.crash_dir / NATIVE_CRASH_FILE, 'w')
+        faulthandler.enable(file=self._native_file, all_threads=True)
+
+        previous_hook = sys.excepthook
+        previous_thread_hook = threading.excepthook
+
+        def excepthook(exc_type, exc, tb):
+            self.report(exc_type, exc, tb, fatal=not self.in_main_loop())
+            previous_hook(exc_type, exc, tb)
+
+        def thread_excepthook(args):
+            self.report(args.exc_type, args.exc_value, args.exc_traceback,
+                        args.thread.name if args.thread else None, fatal=False)
+            previous_thread_hook(args)
+
+        sys.excepthook = excepthook
+        threading.excepthook = thread_excepthook
+        return pending
+
+    def report(self, exc_type, exc, tb, thread_name: Optional[str] = None, fatal: bool = True):
+        """
+        Write a report for an unhandled exception, unless this run already has one.
+
+        Only fatal exceptions, which ended the app, are offered on the next launch;
+        if an earlier one was survived, its report is offered as the likely cause.
+        """
+        if issubclass(exc_type, KeyboardInterrupt):
+            return
+        if not fatal:
+            self._notify_error(exc_type, exc, thread_name)
+        with self._lock:
+            try:
+                if not self.report_path:
+                    details = "".join(traceback.format_exception(exc_type, exc, tb))
+                    if thread_name:
+                        details = f"Unhandled exception in thread {thread_name}:\n\n{details}"
+                    report = format_crash_report(self.version, details, self._read_log_lines())
+                    self.report_path = write_crash_report(self.crash_dir, report, pending=fatal)
+                    self._report_pending = fatal
+                elif fatal and not self._report_pending:
+                    mark_pending_report(self.report_path)
+                    self._report_pending = True
+            except OSError:
+                pass  # Nowhere to write it; the default hook still prints it
+
+    def _notify_error(self, exc_type, exc, thread_name: Optional[str]):
+        """Pass a summary of an exception the app survived to on_error."""
+        summary = "".join(traceback.format_exception_only(exc_type, exc)).strip()
+        if thread_name:
+            summary = f"{summary} (thread {thread_name})"
+        try:
+            self.on_error(summary)
+        except Exception:
+            pass  # Reporting must never raise from the hook
+
+    def _read_log_lines(self) -> List[str]:
+        """Get the recent log lines, or none if the log can't be read, e.g. mid-crash."""
+        try:
+            return self.get_log_lines()
+        except Exception:
+            return []
@@ -0,0 +1,75 @@
+"""
+Passphrase encryption for chat exports.
+
+An encrypted export is ENCRYPTED_MAGIC, a 16-byte scrypt salt (N=2^16, r=8,
+p=1), a 12-byte nonce and the AES-256-GCM ciphertext of the zip archive, with
+ENCRYPTED_MAGIC as associated data. `bluebubbles --decrypt-export` turns one
+back into the zip.
+"""
+
+import hashlib
+import os
+
+from .features import get_missing_reason, require_feature
+
+try:
+    require_feature('encryption')
+    from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+    ENCRYPTION_AVAILABLE = True
+except ImportError:
+    AESGCM = None
+    ENCRYPTION_AVAILABLE = False
+
+# Header of encrypted exports, followed by the scrypt salt, the AES-GCM nonce
+# and the ciphertext of the zip archive
+ENCRYPTED_MAGIC = b"BBEXPORT1"
+SALT_SIZE = 16
+NONCE_SIZE = 12
+
+# scrypt cost parameters (about 64 MiB of memory per key derivation)
+SCRYPT_N = 2 ** 16
+SCRYPT_R = 8
+SCRYPT_P = 1
+
+
+def derive_key(passphrase: str, salt: bytes) -> bytes:
+    """Derive a 256-bit key from a passphrase."""
+    return hashlib.scrypt(
+        passphrase.encode('utf-8'), salt=salt,
+        n=SCRYPT_N, r=SCRYPT_R, p=SCRYPT_P, maxmem=128 * 1024 * 1024, dklen=32
+    )
+
+
+def encrypt_archive(data: bytes, passphrase: str) -> bytes:
+    """Encrypt an archive with AES-256-GCM using a passphrase-derived key."""
+    salt = os.urandom(SALT_SIZE)
+    nonce = os.urandom(NONCE_SIZE)
+    ciphertext = AESGCM(derive_key(passphrase, salt)).encrypt(nonce, data, ENCRYPTED_MAGIC)
+    return ENCRYPTED_MAGIC + salt + nonce + ciphertext
+
+
+def decrypt_archive(data: bytes, passphrase: str) -> bytes:
+    """Decrypt an archive written by encrypt_archive. Raises ValueError on failure."""
+    if not ENCRYPTION_AVAILABLE:
+        raise ValueError(f"Exports can't be decrypted: {get_missing_reason('encryption')}")
+    if not data.startswith(ENCRYPTED_MAGIC):
+        raise ValueError("Not an encrypted BlueBubbles export")
+
+    offset = len(ENCRYPTED_MAGIC)
+    salt = data[offset:offset + SALT_SIZE]
+    nonce = data[offset + SALT_SIZE:offset + SALT_SIZE + NONCE_SIZE]
+    ciphertext = data[offset + SALT_SIZE + NONCE_SIZE:]
+
+    try:
+        return AESGCM(derive_key(passphrase, salt)).decrypt(nonce, ciphertext, ENCRYPTED_MAGIC)
+    except Exception:
+        raise ValueError("Wrong passphrase or damaged export")
+
+
+def decrypt_export_file(file_path: str, output_path: str, passphrase: str):
+    """Decrypt an encrypted export file into a zip archive."""
+    with open(file_path, 'rb') as f:
+        data = decrypt_archive(f.read(), passphrase)
+
+    with open(output_path, 'wb') as f:
+        f.write(data)
@@ -0,0 +1,59 @@
+"""Optional subsystems a build can leave out, and checks for which ones it has."""
+
+import os
+from typing import Dict, Mapping, Set
+
+# Feature -> (what it needs, what happens without it)
+OPTIONAL_FEATURES = {
+    'media': ("GStreamer", "Voice memos can't be recorded and videos don't play"),
+    'pdf': ("poppler-glib", "PDFs aren't previewed; they open in another app"),
+    'encryption': ("the cryptography package", "Chat exports can't be encrypted"),
+}
+
+# Features left out of this build even when their libraries are installed. Packagers of a
+# minimal client can list them here, or at run time in BLUEBUBBLES_DISABLE_FEATURES
+# (comma-separated)
+DISABLED_FEATURES: Set[str] = set()
+
+DISABLE_FEATURES_ENV = 'BLUEBUBBLES_DISABLE_FEATURES'
+
+
+def get_disabled_features(environ: Mapping[str, str] = os.environ) -> Set[str]:
+    """Get the features turned off by the build or the environment."""
+    from_env = {name.strip() for name in environ.get(DISABLE_FEATURES_ENV, '').split(',') if name.strip()}
+    return DISABLED_FEATURES | from_env
+
+
+def is_feature_enabled(name: str, environ: Mapping[str, str] = os.environ) -> bool:
+    """Check whether a feature may be used, before checking its libraries are installed."""
+    return name not in get_disabled_features(environ)
+
+
+def require_feature(name: str):
+    """
+    Raise ImportError if a feature is turned off, so the optional import that
+    follows is skipped the same way as when its library is missing.
+    """
+    if not is_feature_enabled(name):
+        raise ImportError(f"The {name} feature is disabled in this build")
+
+
+def get_missing_reason(name: str) -> str:
+    """Say why an unavailable feature is missing, e.g. "GStreamer isn't installed"."""
+    if not is_feature_enabled(name):
+        return "it isn't included in this build"
+    return f"{OPTIONAL_FEATURES[name][0]} isn't installed"
+
+
+def describe_features(available: Dict[str, bool]) -> Dict[str, str]:
+    """Describe each optional feature for a report: "enabled", "disabled", or what's missing."""
+    disabled = get_disabled_features()
+    descriptions = {}
+    for name, (requirement, _) in OPTIONAL_FEATURES.items():
+        if name in disabled:
+            descriptions[name] = "disabled"
+        elif available.get(name):
+            descriptions[name] = "enabled"
+        else:
+            descriptions[name] = f"missing {requirement}"
+    return descriptions
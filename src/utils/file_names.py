@@ -0,0 +1,7 @@
+"""Turning chat and contact names into file names."""
+
+
+def safe_file_name(name: str, extension: str, fallback: str = "Untitled") -> str:
+    """Make a file name from a display name, which may contain path separators."""
+    stem = name.replace('/', '-').replace('\0', '').strip().lstrip('.')
+    return f"{stem or fallback}.{extension}"
@@ -0,0 +1,35 @@
+"""Interpretation of the server's iMessage account info (GET /api/v1/icloud/account)."""
+
+from typing import Any, Dict, List, Tuple
+
+# Alias status macOS reports for an address that can send and receive
+ALIAS_STATUS_ACTIVE = 3
+
+# Login status messages meaning the account can't send, lowercased
+SIGNED_OUT_STATUSES = ('signed out', 'not signed in', 'logged out', 'disconnected', 'not connected')
+
+
+def is_signed_out(account: Dict[str, Any]) -> bool:
+    """Check whether the server's iMessage account is signed out, so sends would fail."""
+    if not account.get('apple_id'):
+        return True
+    status = (account.get('login_status_message') or '').lower()
+    return any(signed_out in status for signed_out in SIGNED_OUT_STATUSES)
+
+
+def get_aliases(account: Dict[str, Any]) -> List[Tuple[str, bool]]:
+    """Get the account's addresses as (address, active) pairs, active ones first."""
+    aliases = [
+        (alias.get('Alias'), alias.get('Status') == ALIAS_STATUS_ACTIVE)
+        for alias in account.get('vetted_aliases') or []
+        if alias.get('Alias')
+    ]
+    return sorted(aliases, key=lambda alias: not alias[1])
+
+
+def describe_account(account: Dict[str, Any]) -> str:
+    """Describe the account in one line, e.g. "me@icloud.com · Connected"."""
+    if not account.get('apple_id'):
+        return "Signed out"
+    status = account.get('login_status_message')
+    return f"{account['apple_id']} · {status}" if status else account['apple_id']
@@ -0,0 +1,23 @@
+"""User-defined chat labels and their notification policies."""
+
+from typing import Dict, Iterable
+
+# Notification policies in the order shown in preferences, least to most restrictive:
+# notify as usual, notify without sound or banner priority, or don't notify
+LABEL_NOTIFICATIONS = ['all', 'quiet', 'none']
+
+
+def get_notification_policy(labels: Iterable[str], policies: Dict[str, str]) -> str:
+    """
+    Get the notification policy of a chat from its labels.
+
+    The most restrictive label wins, so a chat labelled both "Family" and
+    a muted "Work" doesn't notify.
+    """
+    policy = 'all'
+    for label in labels:
+        label_policy = policies.get(label, 'all')
+        if (label_policy in LABEL_NOTIFICATIONS
+                and LABEL_NOTIFICATIONS.index(label_policy) > LABEL_NOTIFICATIONS.index(policy)):
+            policy = label_policy
+    return policy
@@ -0,0 +1,111 @@
+"""Parsing of links that open conversations, e.g. bluebubbles://chat/<guid> or sms:<number>."""
+
+import re
+from dataclasses import dataclass, field
+from html import escape
+from typing import List, Optional
+from urllib.parse import parse_qs, unquote, urlsplit
+
+LINK_SCHEME = 'bluebubbles'
+
+# Standard message URI schemes -> service to send on when the chat has several
+MESSAGE_SCHEMES = {
+    'sms': 'SMS',
+    'smsto': 'SMS',
+    'mms': 'SMS',
+    'mmsto': 'SMS',
+    'imessage': 'iMessage',
+}
+
+# Web addresses in message text, leaving off trailing punctuation
+URL_PATTERN = re.compile(r'\b(?:https?://|www\.)[^\s<>"]*[^\s<>".,;:!?)\]\'’]', re.IGNORECASE)
+
+
+@dataclass
+class MessageLink:
+    """A conversation to open, by chat GUID or by addresses, with an optional draft."""
+    chat_guid: Optional[str] = None
+    addresses: List[str] = field(default_factory=list)
+    body: str = ''
+    # Preferred service, from sms: or imessage: links
+    service: Optional[str] = None
+
+
+def parse_message_link(uri: str) -> Optional[MessageLink]:
+    """
+    Parse a deep link into the conversation it points at.
+
+    Supported forms:
+        bluebubbles://chat/<guid>
+        bluebubbles://send?address=<address>[&address=...]&body=<text>
+        sms:<address>[,<address>...][?body=<text>]   (also smsto:, mms:, imessage:)
+
+    Returns None for anything else.
+    """
+    parts = urlsplit(uri)
+    scheme = parts.scheme.lower()
+    if scheme in MESSAGE_SCHEMES:
+        return _parse_message_uri(parts, MESSAGE_SCHEMES[scheme])
+    if scheme != LINK_SCHEME:
+        return None
+
+    # A literal + is far more likely a phone number prefix than an encoded space
+    query = parse_qs(parts.query.replace('+', '%2B'))
+    body = query.get('body', [''])[0]
+
+    if parts.netloc == 'chat':
+        chat_guid = unquote(parts.path.lstrip('/'))
+        return MessageLink(chat_guid=chat_guid, body=body) if chat_guid else None
+
+    if parts.netloc == 'send':
+        addresses = [
+            address.strip()
+            for value in query.get('address', [])
+            for address in value.split(',')
+            if address.strip()
+        ]
+        return MessageLink(addresses=addresses, body=body) if addresses else None
+
+    return None
+
+
+def _split_addresses(value: str) -> List[str]:
+    """Split a comma-separated address list."""
+    return [address.strip() for address in unquote(value).split(',') if address.strip()]
+
+
+def _parse_message_uri(parts, service: str) -> Optional[MessageLink]:
+    """Parse an sms:-style URI (RFC 5724, plus the forms phones produce)."""
+    path = parts.netloc or parts.path
+    query = parts.query
+    # sms:<number>&body=<text>, as written by iOS
+    if not query and '&' in path:
+        path, query = path.split('&', 1)
+
+    params = parse_qs(query.replace('+', '%2B'))
+    body = params.get('body', [''])[0]
+
+    if path == '/open':
+        # sms:/open?addresses=<a>,<b>&body=<text>
+        addresses = _split_addresses(params.get('addresses', [''])[0])
+    else:
+        addresses = _split_addresses(path.split(';', 1)[0])
+
+    if not addresses:
+        return None
+    return MessageLink(addresses=addresses, body=body, service=service)
+
+
+
+def linkify(text: str) -> str:
+    """Turn web addresses in message text into Pango markup links, escaping the rest."""
+    parts = []
+    last = 0
+    for match in URL_PATTERN.finditer(text):
+        url = match.group(0)
+        href = url if '://' in url else f"https://{url}"
+        parts.append(escape(text[last:match.start()]))
+        parts.append(f'<a href="{escape(href)}">{escape(url)}</a>')
+        last = match.end()
+    parts.append(escape(text[last:]))
+    return ''.join(parts)
@@ -0,0 +1,68 @@
+"""A least-recently-used cache of byte strings, bounded by their total size."""
+
+import threading
+from collections import OrderedDict
+from typing import Optional
+
+
+class ByteLimitedCache:
+    """
+    Keeps byte strings by key until their total size passes max_bytes, then
+    drops the least recently used. Safe to share between threads.
+    """
+
+    def __init__(self, max_bytes: int):
+        self.max_bytes = max_bytes
+        self.total_bytes = 0
+        self._items: "OrderedDict[str, bytes]" = OrderedDict()
+        self._lock = threading.Lock()
+
+    def __contains__(self, key: str) -> bool:
+        with self._lock:
+            return key in self._items
+
+    def __getitem__(self, key: str) -> bytes:
+        with self._lock:
+            self._items.move_to_end(key)
+            return self._items[key]
+
+    def __setitem__(self, key: str, data: bytes):
+        with self._lock:
+            if key in self._items:
+                self.total_bytes -= len(self._items.pop(key))
+            self._items[key] = data
+            self.total_bytes += len(data)
+            self._trim()
+
+    def get(self, key: str) -> Optional[bytes]:
+        """Get an item, marking it as just used, or None if it isn't cached."""
+        with self._lock:
+            if key not in self._items:
+                return None
+            self._items.move_to_end(key)
+            return self._items[key]
+
+    def pop(self, key: str, default: Optional[bytes] = None) -> Optional[bytes]:
+        with self._lock:
+            data = self._items.pop(key, None)
+            if data is None:
+                return default
+            self.total_bytes -= len(data)
+            return data
+
+    def clear(self):
+        with self._lock:
+            self._items.clear()
+            self.total_bytes = 0
+
+    def set_limit(self, max_bytes: int):
+        """Change the size limit, dropping what no longer fits."""
+        with self._lock:
+            self.max_bytes = max_bytes
+            self._trim()
+
+    def _trim(self):
+        """Drop the least recently used items until the rest fit; the newest is always kept."""
+        while self.total_bytes > self.max_bytes and len(self._items) > 1:
+            _, data = self._items.popitem(last=False)
+            self.total_bytes -= len(data)
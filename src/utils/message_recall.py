@@ -0,0 +1,24 @@
+"""Recall of recently sent messages into the composer with the Up key."""
+
+from typing import List
+
+# Sent messages Up cycles through, newest first
+RECALL_LIMIT = 20
+
+
+def get_recallable_messages(messages: List, limit: int = RECALL_LIMIT) -> List:
+    """
+    Get the user's own text messages that can be recalled from a newest-first list.
+
+    Reactions, group events, attachments and unsent messages are skipped,
+    since there's no text of ours to bring back on its own.
+    """
+    recallable = [
+        message for message in messages
+        if message.is_from_me and message.text and message.text.strip()
+        and not message.attachments
+        and not message.associated_message_guid
+        and not message.item_type
+        and not message.date_retracted
+    ]
+    return recallable[:limit]
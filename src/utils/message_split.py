@@ -0,0 +1,91 @@
+"""Splitting text too long for a service into several messages."""
+
+from typing import Callable, List, Optional
+
+from .sms_segments import GSM_MULTI, UCS2_MULTI, count_segments, is_gsm
+
+# Most concatenated SMS segments carriers reliably deliver; they drop or mangle more
+SMS_MAX_SEGMENTS = 10
+
+# Longest message each service reliably delivers in one piece, in characters.
+# SMS is counted in GSM septets: ten concatenated segments of 153.
+MESSAGE_LENGTH_LIMITS = {
+    'SMS': SMS_MAX_SEGMENTS * GSM_MULTI,
+    'RCS': 8000,
+    'iMessage': 20000,
+}
+
+# SMS limit for text that needs Unicode (UCS-2), in UTF-16 units: ten segments of 67
+SMS_UNICODE_LIMIT = SMS_MAX_SEGMENTS * UCS2_MULTI
+
+# Places to split at, best first; a split is only taken in the second half of a part
+SPLIT_SEPARATORS = ['\n\n', '\n', '. ', '! ', '? ', '; ', ', ', ' ']
+
+# Characters of each part shown on either side of the split in the preview
+PREVIEW_CONTEXT = 30
+
+
+def get_length_limit(service: str, text: str = '') -> Optional[int]:
+    """
+    Get the longest message a service takes, or None if it's unknown.
+
+    A single character outside the GSM alphabet, like an emoji, sends a whole SMS
+    as Unicode, which fits far less; the limit is then in UTF-16 units.
+    """
+    if service == 'SMS' and not is_gsm(text):
+        return SMS_UNICODE_LIMIT
+    return MESSAGE_LENGTH_LIMITS.get(service)
+
+
+def get_length(service: str, text: str) -> int:
+    """Measure text the way get_length_limit counts it for a service."""
+    if service == 'SMS':
+        return count_segments(text).length
+    return len(text)
+
+
+def split_message(text: str, limit: int, measure: Callable[[str], int] = len) -> List[str]:
+    """
+    Split text into parts of at most limit, at paragraph, sentence or word breaks.
+
+    Parts are measured with measure, e.g. in UTF-16 units for Unicode SMS.
+    """
+    parts = []
+    rest = text.strip()
+    while measure(rest) > limit:
+        size = fitting_prefix(rest, limit, measure)
+        window = rest[:size]
+        cut = size
+        for separator in SPLIT_SEPARATORS:
+            index = window.rfind(separator)
+            if index >= size // 2:
+                cut = index + len(separator)
+                break
+        part = rest[:cut].rstrip()
+        if part:
+            parts.append(part)
+        rest = rest[cut:].lstrip()
+    if rest:
+        parts.append(rest)
+    return parts
+
+
+def fitting_prefix(text: str, limit: int, measure: Callable[[str], int]) -> int:
+    """Get how many characters from the start of text fit in limit, at least one."""
+    low, high = 1, min(len(text), limit)
+    while low < high:
+        middle = (low + high + 1) // 2
+        if measure(text[:middle]) <= limit:
+            low = middle
+        else:
+            high = middle - 1
+    return low
+
+
+def preview_part(part: str) -> str:
+    """Show where a part starts and ends, e.g. "Hey, about tomorrow … see you then"."""
+    if len(part) <= PREVIEW_CONTEXT * 2:
+        return part.replace('\n', ' ')
+    head = part[:PREVIEW_CONTEXT].rstrip().replace('\n', ' ')
+    tail = part[-PREVIEW_CONTEXT:].lstrip().replace('\n', ' ')
+    return f"{head} … {tail}"
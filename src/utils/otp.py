@@ -0,0 +1,56 @@
+"""Finding one-time verification codes in incoming text messages."""
+
+import re
+from typing import Iterable, Optional
+
+# Words that show a message carries a code, rather than an order number or price
+CODE_KEYWORDS = re.compile(
+    r'\b(code|codes|passcode|password|pin|otp|verification|verify|one[- ]time|2fa|'
+    r'authentication|login|log in|sign in|security|confirm|confirmation|token)\b|码|コード',
+    re.IGNORECASE
+)
+
+# 4-8 digits, optionally split in two by a space or dash ("123 456", "123-456") or
+# prefixed like Google's "G-123456". Not part of a longer number, amount, time or date.
+CODE_PATTERN = re.compile(
+    r'(?<![\d$€£.,:/+-])(?:[A-Z]-)?(\d{3,4}[ -]\d{3,4}|\d{4,8})(?![\d.,:/-]\d)(?!\d)'
+)
+
+# Services whose messages are checked; codes arrive as SMS
+CODE_SERVICES = ('SMS', 'RCS')
+
+
+def find_code(text: Optional[str]) -> Optional[str]:
+    """Get the one-time code in a message, without separators, or None if it has none."""
+    keywords = [match.start() for match in CODE_KEYWORDS.finditer(text or '')]
+    if not keywords:
+        return None
+
+    # (distance to the nearest keyword, code) for each candidate
+    candidates = []
+    for match in CODE_PATTERN.finditer(text):
+        code = re.sub(r'[ -]', '', match.group(1))
+        if 4 <= len(code) <= 8:
+            distance = min(abs(match.start() - keyword) for keyword in keywords)
+            candidates.append((distance, code))
+    if not candidates:
+        return None
+
+    # The number closest to "code" is the code; a tie is too ambiguous to guess
+    candidates.sort()
+    if len(candidates) > 1 and candidates[0][0] == candidates[1][0] and candidates[0][1] != candidates[1][1]:
+        return None
+    return candidates[0][1]
+
+
+def find_incoming_code(text: Optional[str], services: Iterable[str], from_me: bool) -> Optional[str]:
+    """Get the one-time code in a received message if it came over SMS, None for anything else."""
+    if from_me or not any(service in CODE_SERVICES for service in services):
+        return None
+    return find_code(text)
+
+
+def find_chat_code(chat) -> Optional[str]:
+    """Get the one-time code in a chat's latest message, for its notification."""
+    services = [c.service for c in [chat] + chat.linked_chats]
+    return find_incoming_code(chat.last_message_text, services, bool(chat.last_message_from_me))
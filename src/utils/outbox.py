@@ -0,0 +1,94 @@
+"""Sending messages in the order they were composed: one at a time per chat, chats in parallel."""
+
+import threading
+import time
+from collections import deque
+from typing import Any, Callable, Dict, Optional
+
+# Seconds to wait before each retry of a send that failed for a passing reason
+RETRY_DELAYS = [2, 5, 15]
+
+
+def send_with_retries(send: Callable[[], Any], sleep: Callable[[float], None] = time.sleep) -> Any:
+    """
+    Call send until it succeeds, fails for good, or runs out of retries.
+
+    send returns a result that is falsy on failure, with a retryable attribute
+    telling whether trying again could help; the last result is returned.
+    """
+    result = send()
+    for delay in RETRY_DELAYS:
+        if result or not getattr(result, 'retryable', False):
+            break
+        sleep(delay)
+        result = send()
+    return result
+
+
+class Outbox:
+    """
+    Queue of message sends, worked through in order for each chat.
+
+    A send waits for the ones queued before it to the same chat, retries
+    included, so messages arrive in the order they were composed. Each chat
+    gets its own worker, so a slow chat doesn't hold up the others.
+    """
+
+    def __init__(self, start: Callable[[Callable[[], None]], None] = None):
+        # Runs a chat's worker; a daemon thread unless replaced, e.g. in tests
+        self._start = start or (lambda target: threading.Thread(target=target, daemon=True).start())
+        self._lock = threading.Lock()
+        # Notified whenever a chat's queue empties
+        self._idle = threading.Condition(self._lock)
+        # Chat GUID -> its queued (job, on_done) pairs, the one being sent first
+        self._queues: Dict[str, deque] = {}
+
+    def submit(self, chat_guid: str, job: Callable[[], Any],
+               on_done: Callable[[Any, Optional[Exception]], None] = None):
+        """
+        Queue job for a chat.
+
+        on_done is called on the worker thread with the job's result and None, or
+        None and what it raised.
+        """
+        with self._lock:
+            queue = self._queues.get(chat_guid)
+            idle = queue is None
+            if idle:
+                queue = self._queues[chat_guid] = deque()
+            queue.append((job, on_done))
+        if idle:
+            self._start(lambda: self._work(chat_guid))
+
+    def pending(self, chat_guid: str) -> int:
+        """Count a chat's sends not finished yet, including the one in progress."""
+        with self._lock:
+            return len(self._queues.get(chat_guid, ()))
+
+    def wait_until_empty(self, timeout: float) -> bool:
+        """Wait up to timeout seconds for every queued send to finish; returns whether they did."""
+        with self._idle:
+            return self._idle.wait_for(lambda: not self._queues, timeout)
+    
+    def _work(self, chat_guid: str):
+        """Send a chat's queued jobs one at a time until its queue is empty."""
+        while True:
+            with self._lock:
+                queue = self._queues[chat_guid]
+                job, on_done = queue[0]
+
+            try:
+                result, error = job(), None
+            except Exception as e:
+                result, error = None, e
+
+            with self._lock:
+                queue.popleft()
+                finished = not queue
+                if finished:
+                    del self._queues[chat_guid]
+                    self._idle.notify_all()
+            if on_done:
+                on_done(result, error)
+            if finished:
+                return
@@ -0,0 +1,24 @@
+"""Stable per-participant colors for telling senders apart in group chats."""
+
+import hashlib
+
+from .chat_merge import normalize_address
+
+# Number of participant-color-N classes defined in styles.css
+PARTICIPANT_COLOR_COUNT = 8
+
+
+def participant_color_index(address: str) -> int:
+    """Get a color index for a handle that stays the same across runs and services."""
+    digest = hashlib.md5(normalize_address(address).encode()).hexdigest()
+    return int(digest[:8], 16) % PARTICIPANT_COLOR_COUNT
+
+
+def participant_css_class(address: str) -> str:
+    """Get the CSS class coloring a participant's name label and avatar ring."""
+    return f"participant-color-{participant_color_index(address)}"
+
+
+def is_group_chat_guid(chat_guid: str) -> bool:
+    """Check whether a chat GUID belongs to a group chat (e.g. iMessage;+;chat123)."""
+    return ';+;' in chat_guid
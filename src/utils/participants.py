@@ -0,0 +1,97 @@
+"""Participants of a conversation, grouped by person across handles and services."""
+
+from dataclasses import dataclass, field
+from typing import Callable, Dict, List, Optional
+
+from .chat_merge import normalize_address
+
+
+@dataclass
+class ParticipantHandle:
+    """One address a participant is reached at."""
+    address: str
+    country: Optional[str] = None
+    # Services the conversation reaches this address on, e.g. ['iMessage', 'SMS']
+    services: List[str] = field(default_factory=list)
+
+
+@dataclass
+class Participant:
+    """A person in a conversation, with every handle of theirs it includes."""
+    # Contact name, None for unknown handles
+    name: Optional[str]
+    handles: List[ParticipantHandle] = field(default_factory=list)
+
+
+def group_participants(chat, get_contact_name: Callable[[str], Optional[str]]) -> List[Participant]:
+    """
+    Get the participants of a chat and the chats merged into it.
+
+    Handles are matched across services by normalized address, and handles
+    with the same contact name are shown as one person.
+    """
+    handles = {}
+    for service_chat in [chat] + chat.linked_chats:
+        addresses = ([(p.address, p.country) for p in service_chat.participants]
+                     or [(service_chat.chat_identifier, None)])
+        for address, country in addresses:
+            handle = handles.setdefault(normalize_address(address), ParticipantHandle(address, country))
+            if service_chat.service not in handle.services:
+                handle.services.append(service_chat.service)
+
+    participants = {}
+    for key, handle in handles.items():
+        name = get_contact_name(handle.address)
+        participant = participants.setdefault(name or key, Participant(name))
+        participant.handles.append(handle)
+    return list(participants.values())
+
+
+def summarize_chat(chat, get_contact_name: Callable[[str], Optional[str]]) -> str:
+    """Describe a conversation under its name: a group's size, or the services a one-on-one chat is on."""
+    if chat.is_group_chat:
+        people = len(group_participants(chat, get_contact_name))
+        return f"{people} people"
+
+    services = []
+    for service_chat in [chat] + chat.linked_chats:
+        if service_chat.service not in services:
+            services.append(service_chat.service)
+    return " · ".join(services)
+
+
+def group_contacts(contact_names: Dict[str, str]) -> List[Participant]:
+    """Turn the cached address -> name lookup into one entry per contact, sorted by name."""
+    contacts: Dict[str, Participant] = {}
+    for address, name in sorted(contact_names.items()):
+        contacts.setdefault(name, Participant(name)).handles.append(ParticipantHandle(address))
+    return sorted(contacts.values(), key=lambda contact: contact.name.lower())
+
+
+def match_contacts(contacts: List[Participant], query: str) -> List[Participant]:
+    """Get the contacts whose name or any address contains the query."""
+    query = query.strip().lower()
+    if not query:
+        return list(contacts)
+    return [
+        contact for contact in contacts
+        if query in contact.name.lower() or any(query in handle.address for handle in contact.handles)
+    ]
+
+
+def escape_vcard(value: str) -> str:
+    """Escape a vCard property value."""
+    return value.replace('\\', '\\\\').replace(',', '\\,').replace(';', '\\;').replace('\n', '\\n')
+
+
+def build_contact_card(participant: Participant) -> str:
+    """Build a vCard 3.0 for a participant, for opening in or importing into a contacts app."""
+    name = participant.name or participant.handles[0].address
+    lines = ['BEGIN:VCARD', 'VERSION:3.0', f"FN:{escape_vcard(name)}", f"N:{escape_vcard(name)};;;;"]
+    for handle in participant.handles:
+        if '@' in handle.address:
+            lines.append(f"EMAIL:{escape_vcard(handle.address)}")
+        else:
+            lines.append(f"TEL:{escape_vcard(handle.address)}")
+    lines.append('END:VCARD')
+    return '\r\n'.join(lines) + '\r\n'
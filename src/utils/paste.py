@@ -0,0 +1,27 @@
+"""Deciding when pasted text is better sent as a text file than as a bubble."""
+
+import re
+
+# Pastes longer than either of these are offered as a .txt attachment
+LONG_PASTE_CHARS = 2000
+LONG_PASTE_LINES = 30
+
+# Longest file name made from the start of a paste, before ".txt"
+FILE_NAME_LENGTH = 40
+
+
+def is_long_paste(text: str) -> bool:
+    """Check whether pasted text would make an unreadable or over-long bubble."""
+    return len(text) > LONG_PASTE_CHARS or text.count('\n') + 1 > LONG_PASTE_LINES
+
+
+def paste_file_name(text: str) -> str:
+    """Name a text attachment after the first words of the paste, e.g. "Traceback most recent call.txt"."""
+    first_line = next((line for line in text.splitlines() if line.strip()), '')
+    words = re.sub(r'[^\w\s-]', ' ', first_line).split()
+    name = ''
+    for word in words:
+        if len(name) + len(word) + 1 > FILE_NAME_LENGTH:
+            break
+        name = f"{name} {word}" if name else word
+    return f"{name or 'Pasted Text'}.txt"
@@ -0,0 +1,59 @@
+"""
+Lenient reading of server payload fields.
+
+Server versions disagree on field types (0/1 vs booleans, numeric vs named
+reaction types) and some send null or omit fields entirely. These helpers
+turn whatever arrived into the type the cache expects, or a fallback.
+"""
+
+from typing import Any, Dict, List, Optional
+
+TRUE_STRINGS = ('1', 'true', 'yes')
+
+
+def as_bool(value: Any) -> bool:
+    """Read a flag, treating anything unrecognised as False."""
+    if isinstance(value, bool):
+        return value
+    if isinstance(value, (int, float)):
+        return value != 0
+    if isinstance(value, str):
+        return value.strip().lower() in TRUE_STRINGS
+    return False
+
+
+def as_int(value: Any, default: Optional[int] = 0) -> Optional[int]:
+    """Read an integer, falling back to default for anything that isn't one."""
+    if isinstance(value, bool):
+        return int(value)
+    if isinstance(value, int):
+        return value
+    if isinstance(value, float):
+        return int(value)
+    if isinstance(value, str):
+        try:
+            return int(value.strip())
+        except ValueError:
+            return default
+    return default
+
+
+def as_text(value: Any) -> Optional[str]:
+    """Read a string field. Numbers become strings; empty and nested values become None."""
+    if isinstance(value, str):
+        return value or None
+    if isinstance(value, (int, float)) and not isinstance(value, bool):
+        return str(value)
+    return None
+
+
+def as_dict(value: Any) -> Optional[Dict[str, Any]]:
+    """Read a nested object, or None if it's missing, empty or not an object."""
+    return value if isinstance(value, dict) and value else None
+
+
+def as_list(value: Any) -> List[Dict[str, Any]]:
+    """Read a list of nested objects, dropping entries that aren't objects."""
+    if not isinstance(value, list):
+        return []
+    return [item for item in value if isinstance(item, dict)]
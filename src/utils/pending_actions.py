@@ -0,0 +1,71 @@
+"""Destructive actions held back for a few seconds so they can be undone."""
+
+from dataclasses import dataclass
+from typing import Any, Callable, Dict, Optional
+
+# Seconds an action waits, with its undo toast shown, before it's carried out
+UNDO_SECONDS = 5
+
+
+@dataclass
+class PendingAction:
+    """An action waiting to be carried out."""
+    description: str
+    # Carries out the deletion in storage or on the server
+    commit: Callable[[], None]
+    # Puts back what the UI already removed, if anything
+    revert: Optional[Callable[[], None]] = None
+    timer: Any = None
+
+
+class PendingActionQueue:
+    """
+    Actions that run once their undo window has passed.
+
+    schedule(seconds, callback) starts a timer and returns an ID for cancel(timer_id),
+    e.g. GLib.timeout_add_seconds and GLib.source_remove.
+    """
+
+    def __init__(self, schedule: Callable[[int, Callable[[], Any]], Any], cancel: Callable[[Any], Any]):
+        self.schedule = schedule
+        self.cancel = cancel
+        self._actions: Dict[int, PendingAction] = {}
+        self._next_id = 1
+
+    def __len__(self) -> int:
+        return len(self._actions)
+
+    def add(self, description: str, commit: Callable[[], None],
+            revert: Optional[Callable[[], None]] = None, delay: int = UNDO_SECONDS) -> int:
+        """Hold an action back, returning its ID for undo()."""
+        action_id = self._next_id
+        self._next_id += 1
+        action = PendingAction(description, commit, revert)
+        self._actions[action_id] = action
+        action.timer = self.schedule(delay, lambda: self._on_timeout(action_id))
+        return action_id
+
+    def _on_timeout(self, action_id: int) -> bool:
+        """Carry out an action whose undo window passed."""
+        action = self._actions.pop(action_id, None)
+        if action:
+            action.commit()
+        return False  # One-shot timer
+
+    def undo(self, action_id: int) -> bool:
+        """Drop an action before it runs. Returns False if it already ran."""
+        action = self._actions.pop(action_id, None)
+        if action is None:
+            return False
+        self.cancel(action.timer)
+        if action.revert:
+            action.revert()
+        return True
+
+    def flush(self):
+        """Carry out every waiting action now, e.g. before quitting."""
+        while self._actions:
+            action_id = next(iter(self._actions))
+            action = self._actions.pop(action_id)
+            self.cancel(action.timer)
+            action.commit()
@@ -0,0 +1,68 @@
+"""Display formatting of phone number handles, grouped the way their country writes them."""
+
+import re
+from typing import Optional
+
+# Calling codes that are one or two digits long; every other code has three
+ONE_DIGIT_CODES = {'1', '7'}
+TWO_DIGIT_CODES = {
+    '20', '27', '30', '31', '32', '33', '34', '36', '39', '40', '41', '43', '44', '45', '46', '47',
+    '48', '49', '51', '52', '53', '54', '55', '56', '57', '58', '60', '61', '62', '63', '64', '65',
+    '66', '81', '82', '84', '86', '90', '91', '92', '93', '94', '95', '98',
+}
+
+# Handle countries (as the server reports them) that use North American numbering
+NANP_COUNTRIES = {'us', 'ca'}
+
+
+def split_calling_code(digits: str) -> tuple:
+    """Split international digits into (calling code, national number)."""
+    if digits[:1] in ONE_DIGIT_CODES:
+        return digits[:1], digits[1:]
+    if digits[:2] in TWO_DIGIT_CODES:
+        return digits[:2], digits[2:]
+    return digits[:3], digits[3:]
+
+
+def group_digits(digits: str) -> str:
+    """Group a national number in threes, ending on a group of four, e.g. "612 345 6789"."""
+    if len(digits) <= 4:
+        return digits
+    if len(digits) == 8:
+        return f"{digits[:4]} {digits[4:]}"
+    groups = []
+    rest = digits
+    while len(rest) > 4:
+        # Leave four digits for the last group, or three when only six or seven remain
+        size = 3 if len(rest) != 5 else 2
+        groups.append(rest[:size])
+        rest = rest[size:]
+    groups.append(rest)
+    return ' '.join(groups)
+
+
+def format_phone_number(address: str, country: Optional[str] = None) -> str:
+    """
+    Format a phone number handle for display, e.g. "+1 (555) 123-4567" or "+44 207 946 0958".
+
+    Email addresses, short codes and anything that isn't a phone number are returned unchanged.
+    """
+    address = address.strip()
+    if '@' in address or re.search(r'[^\d\s+().\-]', address):
+        return address
+
+    digits = re.sub(r'\D', '', address)
+    if len(digits) < 7:
+        return address
+
+    if not address.startswith('+'):
+        # Without a calling code only North American numbers can be placed
+        if len(digits) == 10 and (country or 'us').lower() in NANP_COUNTRIES:
+            digits = '1' + digits
+        elif not (len(digits) == 11 and digits.startswith('1')):
+            return address
+
+    code, national = split_calling_code(digits)
+    if code == '1' and len(national) == 10:
+        return f"+1 ({national[:3]}) {national[3:6]}-{national[6:]}"
+    return f"+{code} {group_digits(national)}"
@@ -0,0 +1,30 @@
+"""Per-chat overrides of privacy settings, such as sending typing indicators."""
+
+from typing import List, Optional
+
+# Override choices in the order shown in chat details: follow the global setting, always, never
+OVERRIDE_CHOICES = [None, True, False]
+
+
+def parse_override(value: Optional[str]) -> Optional[bool]:
+    """Read a stored chat override, None meaning the chat follows the global setting."""
+    if value is None:
+        return None
+    return value == '1'
+
+
+def store_override(override: Optional[bool]) -> Optional[str]:
+    """Turn an override into its chat setting value, None removing it."""
+    if override is None:
+        return None
+    return '1' if override else '0'
+
+
+def apply_override(default: bool, override: Optional[bool]) -> bool:
+    """Get the effective setting for a chat."""
+    return default if override is None else override
+
+
+def override_labels(default: bool) -> List[str]:
+    """Get the labels of OVERRIDE_CHOICES, naming what the global setting currently is."""
+    return [f"Default ({'On' if default else 'Off'})", "On", "Off"]
@@ -0,0 +1,253 @@
+"""
+Search filters typed into search fields (from:, in:, has:, before: and
+after:), and ranking and snippets of the messages found
+"""
+
+import re
+from dataclasses import dataclass
+from datetime import date, datetime, time
+from typing import List, Optional, Tuple
+
+# Filter keys, in the order their chips are shown
+FILTER_KEYS = ['from', 'in', 'has', 'before', 'after']
+
+# A filter token: key, colon, then a word or a quoted phrase, e.g. from:"Jane Doe"
+FILTER_PATTERN = re.compile(r'(?<!\S)(from|in|has|before|after):("[^"]*"?|\S*)', re.IGNORECASE)
+
+# from: value meaning messages sent from here
+SENDER_ME = 'me'
+
+# has: values: any attachment, or one kind picked by MIME type prefix
+ATTACHMENT_KINDS = {
+    'attachment': (),
+    'image': ('image/',),
+    'video': ('video/',),
+    'audio': ('audio/',),
+    'pdf': ('application/pdf',),
+}
+
+
+@dataclass
+class SearchQuery:
+    """A search split into its free text and filters."""
+    text: str = ''
+    # Part of a sender's name or address, or SENDER_ME
+    sender: Optional[str] = None
+    # Part of a conversation's name
+    chat: Optional[str] = None
+    # An ATTACHMENT_KINDS key
+    attachment_kind: Optional[str] = None
+    # Messages sent before the start of this day
+    before: Optional[date] = None
+    # Messages sent on or after this day
+    after: Optional[date] = None
+
+    @property
+    def has_attachment(self) -> bool:
+        return self.attachment_kind is not None
+
+    @property
+    def attachment_mime_prefixes(self) -> Tuple[str, ...]:
+        """Get the MIME type prefixes attachments have to match, none for any attachment."""
+        return ATTACHMENT_KINDS.get(self.attachment_kind, ())
+
+    @property
+    def filters_messages(self) -> bool:
+        """Check whether the query narrows down messages, not just conversation names."""
+        return bool(self.sender or self.has_attachment or self.before or self.after)
+
+    @property
+    def before_ms(self) -> Optional[int]:
+        return day_start_ms(self.before) if self.before else None
+
+    @property
+    def after_ms(self) -> Optional[int]:
+        return day_start_ms(self.after) if self.after else None
+
+    def __bool__(self) -> bool:
+        return bool(self.text or self.chat or self.filters_messages)
+
+
+def day_start_ms(day: date) -> int:
+    """Get the local midnight starting a day, in Unix milliseconds."""
+    return int(datetime.combine(day, time.min).timestamp() * 1000)
+
+
+def parse_date(value: str) -> Optional[date]:
+    """Parse a filter date: YYYY-MM-DD, today or yesterday."""
+    value = value.lower()
+    if value == 'today':
+        return date.today()
+    if value == 'yesterday':
+        return date.fromordinal(date.today().toordinal() - 1)
+    try:
+        return date.fromisoformat(value)
+    except ValueError:
+        return None
+
+
+def parse_search_query(text: str) -> SearchQuery:
+    """
+    Split a search into free text and filters.
+
+    Filters with no value yet, e.g. just picked from a chip, are left out.
+    Ones that don't parse, like an unfinished date, stay in the text, so
+    they match nothing rather than quietly widening the search.
+    """
+    query = SearchQuery()
+    words = []
+    position = 0
+    for match in FILTER_PATTERN.finditer(text):
+        words.append(text[position:match.start()])
+        position = match.end()
+        key = match.group(1).lower()
+        value = match.group(2).strip('"').strip()
+        if not value:
+            continue
+
+        if key == 'from':
+            query.sender = value.lower()
+        elif key == 'in':
+            query.chat = value.lower()
+        elif key == 'has' and value.lower() in ATTACHMENT_KINDS:
+            query.attachment_kind = value.lower()
+        elif key in ('before', 'after') and parse_date(value):
+            setattr(query, key, parse_date(value))
+        else:
+            words.append(match.group(0))
+    words.append(text[position:])
+
+    query.text = ' '.join(' '.join(words).split())
+    return query
+
+
+def has_filter(text: str, key: str) -> bool:
+    """Check whether a search contains a filter, with or without a value."""
+    return any(match.group(1).lower() == key for match in FILTER_PATTERN.finditer(text))
+
+
+def remove_filter(text: str, key: str) -> str:
+    """Take a filter out of a search, keeping the rest as typed."""
+    text = FILTER_PATTERN.sub(lambda match: '' if match.group(1).lower() == key else match.group(0), text)
+    return ' '.join(text.split())
+
+
+
+# Characters of message text shown around the match in a search result
+SNIPPET_WIDTH = 80
+
+# Days over which a result's recency score halves, and characters of extra
+# distance between the search words over which its proximity score halves
+RECENCY_HALF_LIFE_DAYS = 30
+PROXIMITY_HALF_DISTANCE = 20
+
+
+@dataclass
+class SearchHit:
+    """A cached message found by a search."""
+    chat_guid: str
+    guid: str
+    date_created: int
+    text: Optional[str]
+
+
+@dataclass
+class AttachmentHit:
+    """An attachment of a message found by a search."""
+    chat_guid: str
+    message_guid: str
+    guid: str
+    file_name: Optional[str]
+    mime_type: Optional[str]
+    date_created: int
+
+
+def search_words(text: str) -> List[str]:
+    """Get the lowercase words of a search's free text; a message has to contain each of them."""
+    return list(dict.fromkeys(text.lower().split()))
+
+
+def find_words(text: str, word: str) -> List[Tuple[int, int]]:
+    """Find the (start, end) of every occurrence of a word in text, ignoring case."""
+    return [match.span() for match in re.finditer(re.escape(word), text, re.IGNORECASE)]
+
+
+def find_word_window(text: str, words: List[str]) -> Optional[Tuple[int, int]]:
+    """Find the shortest (start, end) stretch of text containing every word, or None if one is missing."""
+    occurrences = []
+    for index, word in enumerate(words):
+        spans = find_words(text, word)
+        if not spans:
+            return None
+        occurrences.extend((start, end, index) for start, end in spans)
+    occurrences.sort()
+
+    # Slide over the occurrences, shrinking the window from the left while it holds every word
+    best = None
+    counts = [0] * len(words)
+    missing = len(words)
+    left = 0
+    for right, (_, _, index) in enumerate(occurrences):
+        counts[index] += 1
+        if counts[index] == 1:
+            missing -= 1
+        while not missing:
+            start = occurrences[left][0]
+            end = max(occurrence[1] for occurrence in occurrences[left:right + 1])
+            if best is None or end - start < best[1] - best[0]:
+                best = (start, end)
+            counts[occurrences[left][2]] -= 1
+            if not counts[occurrences[left][2]]:
+                missing += 1
+            left += 1
+    return best
+
+
+def score_hit(hit: SearchHit, words: List[str], now_ms: int) -> float:
+    """Score a result: recent messages, and ones with the words close together, score higher."""
+    age_days = max(now_ms - hit.date_created, 0) / 86400000
+    score = 0.5 ** (age_days / RECENCY_HALF_LIFE_DAYS)
+    window = find_word_window(hit.text or '', words) if words else None
+    if window:
+        # Distance beyond the words written next to each other
+        extra = (window[1] - window[0]) - sum(len(word) for word in words) - (len(words) - 1)
+        score += 0.5 ** (max(extra, 0) / PROXIMITY_HALF_DISTANCE)
+    return score
+
+
+def rank_hits(hits: List[SearchHit], words: List[str], now_ms: int) -> List[SearchHit]:
+    """Order results best first, newest first among equals."""
+    return sorted(hits, key=lambda hit: (score_hit(hit, words, now_ms), hit.date_created), reverse=True)
+
+
+def make_snippet(text: str, words: List[str], width: int = SNIPPET_WIDTH) -> List[Tuple[str, bool]]:
+    """
+    Cut a message down to about width characters around the search words.
+
+    Returns the snippet as (part, is a match) pairs, for highlighting.
+    """
+    text = ' '.join(text.split())
+    window = find_word_window(text, words) if words else None
+    start = 0
+    if window and len(text) > width:
+        # Center the words, without running past the end
+        start = max(0, min(window[0] - (width - (window[1] - window[0])) // 2, len(text) - width))
+    end = start + width
+    snippet = text[start:end]
+
+    marked = [False] * len(snippet)
+    for word in words:
+        for match_start, match_end in find_words(snippet, word):
+            marked[match_start:match_end] = [True] * (match_end - match_start)
+
+    parts = []
+    for char, is_match in zip(snippet, marked):
+        if parts and parts[-1][1] == is_match:
+            parts[-1][0] += char
+        else:
+            parts.append([char, is_match])
+    if start > 0:
+        parts.insert(0, ['…', False])
+    if end < len(text):
+        parts.append(['…', False])
+    return [(part, is_match) for part, is_match in parts]
@@ -0,0 +1,59 @@
+"""Counting the SMS segments a message takes, for the composer's counter."""
+
+from dataclasses import dataclass
+
+# GSM 03.38 default alphabet: one 7-bit character each
+GSM_BASIC = set(
+    "@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ !\"#¤%&'()*+,-./0123456789:;<=>?"
+    "¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà"
+)
+# Extension table characters, sent as an escape plus the character
+GSM_EXTENDED = set("^{}\\[~]|€\f")
+
+# Characters per segment: alone, and as part of a concatenated message
+GSM_SINGLE, GSM_MULTI = 160, 153
+UCS2_SINGLE, UCS2_MULTI = 70, 67
+
+
+@dataclass
+class SegmentCount:
+    """How long a message is as SMS."""
+    # Length in the encoding's units (GSM septets or UTF-16 code units)
+    length: int
+    segments: int
+    # Units per segment at the current number of segments
+    per_segment: int
+    unicode: bool
+
+    @property
+    def capacity(self) -> int:
+        """Get how long the message can get before it takes another segment."""
+        return self.per_segment * max(self.segments, 1)
+
+
+def is_gsm(text: str) -> bool:
+    """Check whether text fits the GSM alphabet, so it isn't sent as Unicode."""
+    return all(char in GSM_BASIC or char in GSM_EXTENDED for char in text)
+
+
+def count_segments(text: str) -> SegmentCount:
+    """Count the SMS segments a message takes."""
+    if is_gsm(text):
+        length = sum(2 if char in GSM_EXTENDED else 1 for char in text)
+        single, multi, unicode = GSM_SINGLE, GSM_MULTI, False
+    else:
+        # Characters outside the Basic Multilingual Plane (most emoji) take two units
+        length = len(text.encode('utf-16-le')) // 2
+        single, multi, unicode = UCS2_SINGLE, UCS2_MULTI, True
+
+    if length <= single:
+        return SegmentCount(length, 1 if length else 0, single, unicode)
+    return SegmentCount(length, -(-length // multi), multi, unicode)
+
+
+def describe_count(count: SegmentCount) -> str:
+    """Describe a count for the composer, e.g. "42/160" or "200/306 · 2 texts"."""
+    text = f"{count.length}/{count.capacity}"
+    if count.segments > 1:
+        text += f" · {count.segments} texts"
+    return text
@@ -0,0 +1,70 @@
+"""Times a conversation can be snoozed until, also offered for message reminders."""
+
+from datetime import datetime, timedelta
+from typing import Dict, List, Optional, Tuple
+
+from .time_format import TimeFormat, format_clock, format_date
+
+# Hour of the day snoozes to "Tomorrow", "This Weekend" and "Next Week" end at
+MORNING_HOUR = 8
+# Hour "This Evening" ends at
+EVENING_HOUR = 18
+# Hours "Later Today" lasts
+LATER_HOURS = 3
+
+
+def at_hour(day: datetime, hour: int) -> datetime:
+    """Get a day's date at the start of an hour."""
+    return day.replace(hour=hour, minute=0, second=0, microsecond=0)
+
+
+def get_snooze_options(now: datetime) -> List[Tuple[str, datetime]]:
+    """
+    Get the (label, end time) choices offered when snoozing a conversation.
+
+    Choices that would end at the same time as an earlier one, or too soon
+    to be worth it, are left out: "This Evening" in the evening, "This
+    Weekend" at the weekend.
+    """
+    options = [("Later Today", now + timedelta(hours=LATER_HOURS))]
+
+    evening = at_hour(now, EVENING_HOUR)
+    if evening - now > timedelta(hours=1):
+        options.append(("This Evening", evening))
+
+    tomorrow = at_hour(now + timedelta(days=1), MORNING_HOUR)
+    options.append(("Tomorrow", tomorrow))
+
+    # Monday is 0, Saturday 5
+    if now.weekday() < 4:
+        options.append(("This Weekend", at_hour(now + timedelta(days=5 - now.weekday()), MORNING_HOUR)))
+
+    next_week = at_hour(now + timedelta(days=7 - now.weekday()), MORNING_HOUR)
+    if next_week != tomorrow:
+        options.append(("Next Week", next_week))
+
+    return options
+
+
+def get_due_snoozes(snoozes: Dict[str, int], now_ms: int) -> List[str]:
+    """Get the chat GUIDs whose snooze (an end time in Unix milliseconds) is over."""
+    return [chat_guid for chat_guid, until in snoozes.items() if until <= now_ms]
+
+
+def describe_time_ahead(when: datetime, time_format: TimeFormat, now: Optional[datetime] = None) -> str:
+    """Describe a time in the coming days, e.g. "8:00 PM", "tomorrow 8:00 AM" or "Saturday 8:00 AM"."""
+    now = now or datetime.now()
+    clock = format_clock(when, time_format)
+    days = (when.date() - now.date()).days
+    if days <= 0:
+        return clock
+    if days == 1:
+        return f"tomorrow {clock}"
+    if days < 7:
+        return f"{when.strftime('%A')} {clock}"
+    return f"{format_date(when, time_format)} {clock}"
+
+
+def describe_snooze(until: datetime, time_format: TimeFormat, now: Optional[datetime] = None) -> str:
+    """Describe when a snooze ends, e.g. "Snoozed until tomorrow 8:00 AM"."""
+    return f"Snoozed until {describe_time_ahead(until, time_format, now)}"
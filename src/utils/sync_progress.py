@@ -0,0 +1,31 @@
+"""Progress reports for long-running syncs, shown as a progress bar under the header."""
+
+from dataclasses import dataclass
+from typing import Optional
+
+
+@dataclass
+class SyncProgress:
+    """How far one stage of a sync has got."""
+    # 'chats' while fetching the chat list, 'messages' while fetching one chat's messages
+    stage: str
+    done: int
+    # None when the server doesn't say how many there are
+    total: Optional[int] = None
+    # The chat whose messages are being fetched, for the 'messages' stage
+    chat_title: Optional[str] = None
+
+    @property
+    def fraction(self) -> Optional[float]:
+        """Get the completed fraction, or None if the total is unknown."""
+        if not self.total:
+            return None
+        return min(self.done / self.total, 1.0)
+
+    @property
+    def label(self) -> str:
+        """Describe the progress, e.g. "Syncing chats 120/600"."""
+        count = f"{self.done}/{self.total}" if self.total else str(self.done)
+        if self.stage == 'messages' and self.chat_title:
+            return f"Syncing messages for {self.chat_title} {count}"
+        return f"Syncing {self.stage} {count}"
@@ -0,0 +1,103 @@
+"""Central timestamp formatting for the sidebar, message bubbles and exports."""
+
+import locale
+from dataclasses import dataclass
+from datetime import date, datetime
+from typing import Optional
+
+from .timestamps import CLOCK_SKEW_THRESHOLD_MS
+
+# Date format IDs in the order shown in preferences -> strftime pattern
+DATE_FORMATS = {
+    'locale': "%x",
+    'mdy': "%m/%d/%y",
+    'dmy': "%d/%m/%y",
+    'iso': "%Y-%m-%d",
+}
+
+# Clock format IDs in the order shown in preferences
+CLOCK_FORMATS = ['locale', '12h', '24h']
+
+
+@dataclass
+class TimeFormat:
+    """User timestamp preferences."""
+    clock: str = 'locale'
+    relative: bool = True
+    date_format: str = 'locale'
+
+    @property
+    def uses_24_hour_clock(self) -> bool:
+        """Check whether times are shown with a 24-hour clock."""
+        if self.clock != 'locale':
+            return self.clock == '24h'
+        try:
+            time_pattern = locale.nl_langinfo(locale.T_FMT)
+        except (AttributeError, ValueError):
+            return True  # nl_langinfo is unavailable on some platforms
+        return '%p' not in time_pattern and '%r' not in time_pattern
+
+
+def format_clock(dt: datetime, time_format: TimeFormat) -> str:
+    """Format the time of day."""
+    if time_format.uses_24_hour_clock:
+        return dt.strftime("%H:%M")
+    # Strip the leading zero of the hour ("09:30 PM" -> "9:30 PM")
+    return dt.strftime("%I:%M %p").lstrip('0')
+
+
+def format_date(dt: datetime, time_format: TimeFormat) -> str:
+    """Format the calendar date."""
+    return dt.strftime(DATE_FORMATS.get(time_format.date_format, "%x"))
+
+
+def format_time(dt: Optional[datetime], time_format: TimeFormat, full: bool = False,
+                now: Optional[datetime] = None) -> str:
+    """
+    Format a timestamp according to the user's preferences.
+
+    Relative timestamps read "Now", "5 min", "Yesterday" or a weekday for recent
+    times; absolute ones always show the clock time, with the date for other days.
+    full always shows the date and time, for exports.
+    """
+    if not dt:
+        return ""
+
+    if full:
+        return f"{format_date(dt, time_format)} {format_clock(dt, time_format)}"
+
+    now = now or datetime.now()
+    days = (now.date() - dt.date()).days
+
+    if time_format.relative:
+        seconds = (now - dt).total_seconds()
+        minutes = int(seconds // 60)
+        # Clock differences too small to correct can put new messages slightly in the future
+        if -CLOCK_SKEW_THRESHOLD_MS / 1000 <= seconds < 60:
+            return "Now"
+        elif 0 <= minutes < 60:
+            return f"{minutes} min"
+        elif days == 0:
+            return format_clock(dt, time_format)
+        elif days == 1:
+            return "Yesterday"
+        elif 1 < days < 7:
+            return dt.strftime("%A")
+        return format_date(dt, time_format)
+
+    if days == 0:
+        return format_clock(dt, time_format)
+    return f"{format_date(dt, time_format)} {format_clock(dt, time_format)}"
+
+
+def format_day(day: date, time_format: TimeFormat, today: Optional[date] = None) -> str:
+    """Format a day for the separators between days in a conversation, e.g. "Yesterday"."""
+    today = today or date.today()
+    days = (today - day).days
+    if days == 0:
+        return "Today"
+    if days == 1:
+        return "Yesterday"
+    if 1 < days < 7:
+        return day.strftime("%A")
+    return f"{day.strftime('%A')}, {format_date(datetime(day.year, day.month, day.day), time_format)}"
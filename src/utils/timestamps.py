@@ -0,0 +1,107 @@
+"""
+Conversion of server timestamps to Unix milliseconds and local datetimes.
+
+The server normally sends Unix milliseconds, but values read straight from
+chat.db use Apple's epoch (2001-01-01) in seconds or nanoseconds, and some
+endpoints send seconds or ISO 8601 strings. Everything is stored as Unix ms.
+"""
+
+from datetime import datetime, timezone
+from typing import Any, Optional
+
+# Seconds between the Unix epoch and Apple's epoch (2001-01-01 00:00 UTC)
+APPLE_EPOCH_OFFSET = 978307200
+
+# Difference between the server's clock and ours below which we ignore it.
+# The HTTP Date header only has one-second resolution, so small skews are noise.
+CLOCK_SKEW_THRESHOLD_MS = 30 * 1000
+
+# Server clock minus local clock, in milliseconds, once it's been measured
+_clock_skew_ms = 0
+
+
+def to_unix_ms(value: Any) -> Optional[int]:
+    """
+    Convert a server timestamp to Unix milliseconds.
+
+    Numbers are told apart by magnitude, which is unambiguous for dates
+    between 2004 and 2032:
+        >= 1e17  Apple nanoseconds (chat.db on macOS 10.13 and later)
+        >= 1e14  Unix microseconds
+        >= 1e12  Unix milliseconds
+        >= 1e11  Apple milliseconds
+        >= 1e9   Unix seconds
+        else     Apple seconds (older chat.db)
+
+    Returns None for missing, zero or unparseable values.
+    """
+    if value is None or isinstance(value, bool):
+        return None
+
+    if isinstance(value, str):
+        value = value.strip()
+        try:
+            value = float(value)
+        except ValueError:
+            return _iso_to_unix_ms(value)
+
+    if not isinstance(value, (int, float)) or value <= 0:
+        return None
+
+    if value >= 1e17:
+        return int(value / 1e6) + APPLE_EPOCH_OFFSET * 1000
+    if value >= 1e14:
+        return int(value / 1e3)
+    if value >= 1e12:
+        return int(value)
+    if value >= 1e11:
+        return int(value) + APPLE_EPOCH_OFFSET * 1000
+    if value >= 1e9:
+        return int(value * 1000)
+    return int((value + APPLE_EPOCH_OFFSET) * 1000)
+
+
+def _iso_to_unix_ms(value: str) -> Optional[int]:
+    """Convert an ISO 8601 string to Unix milliseconds. Naive times are taken as UTC."""
+    # fromisoformat only accepts a Z suffix from Python 3.11
+    if value.endswith(('Z', 'z')):
+        value = value[:-1] + '+00:00'
+    try:
+        dt = datetime.fromisoformat(value)
+    except ValueError:
+        return None
+    if dt.tzinfo is None:
+        dt = dt.replace(tzinfo=timezone.utc)
+    return int(dt.timestamp() * 1000)
+
+
+def to_datetime(value: Any) -> Optional[datetime]:
+    """Convert a server timestamp to a local datetime, corrected for clock skew."""
+    unix_ms = to_unix_ms(value)
+    if unix_ms is None:
+        return None
+    return datetime.fromtimestamp((unix_ms - _clock_skew_ms) / 1000)
+
+
+def measure_clock_skew(server_time: datetime, sent_at: float, received_at: float) -> int:
+    """
+    Measure how far the server's clock is ahead of ours, in milliseconds.
+
+    server_time is the server's Date header; sent_at and received_at are local
+    Unix times around the request, so the server read its clock about halfway.
+    Returns 0 when the difference is below CLOCK_SKEW_THRESHOLD_MS.
+    """
+    local_ms = (sent_at + received_at) / 2 * 1000
+    skew_ms = int(server_time.timestamp() * 1000 - local_ms)
+    return skew_ms if abs(skew_ms) >= CLOCK_SKEW_THRESHOLD_MS else 0
+
+
+def set_clock_skew(skew_ms: int):
+    """Set the measured server clock skew used by to_datetime."""
+    global _clock_skew_ms
+    _clock_skew_ms = skew_ms
+
+
+def get_clock_skew() -> int:
+    """Get the measured server clock skew in milliseconds (0 if insignificant)."""
+    return _clock_skew_ms
@@ -0,0 +1 @@
+"""Unit tests for the pure helpers (run with: python3 -m unittest discover -s tests -t .)."""
@@ -0,0 +1,23 @@
+"""Base test case for tests that need a cache database."""
+
+import os
+import shutil
+import tempfile
+import unittest
+
+from src.db.manager import DatabaseManager
+
+
+class DatabaseTestCase(unittest.TestCase):
+    """
+    Gives each test a fresh cache database in its own temporary directory.
+
+    The database runs in WAL mode, so it leaves -wal and -shm files next to
+    itself; removing the directory takes them too.
+    """
+
+    def setUp(self):
+        self.directory = tempfile.mkdtemp()
+        self.addCleanup(shutil.rmtree, self.directory)
+        self.db_path = os.path.join(self.directory, 'cache.db')
+        self.db = DatabaseManager(self.db_path)
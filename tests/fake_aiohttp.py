@@ -0,0 +1,36 @@
+"""Importing the API client in tests without aiohttp installed."""
+
+import enum
+import importlib
+import sys
+import types
+import unittest
+from unittest import mock
+
+
+class WSMsgType(enum.Enum):
+    TEXT = 1
+    CLOSED = 8
+
+
+def fake_aiohttp_module():
+    """Build an aiohttp module with what the client uses at import time and in except clauses."""
+    aiohttp = types.ModuleType('aiohttp')
+    aiohttp.ClientError = type('ClientError', (Exception,), {})
+    aiohttp.ClientSession = mock.MagicMock(name='ClientSession')
+    aiohttp.ClientTimeout = mock.MagicMock(name='ClientTimeout')
+    aiohttp.WSMsgType = WSMsgType
+    return aiohttp
+
+
+class ClientTestCase(unittest.TestCase):
+    """Imports src.api.client as self.client_module, against the real aiohttp if it's installed."""
+
+    def setUp(self):
+        if importlib.util.find_spec('aiohttp') is None:
+            patcher = mock.patch.dict(sys.modules, {'aiohttp': fake_aiohttp_module()})
+            patcher.start()
+            self.addCleanup(patcher.stop)
+            sys.modules.pop('src.api.client', None)
+            self.addCleanup(sys.modules.pop, 'src.api.client', None)
+        self.client_module = importlib.import_module('src.api.client')
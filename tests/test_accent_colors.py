@@ -0,0 +1,27 @@
+"""Tests for the accent color override."""
+
+import unittest
+
+from src.utils.accent_colors import build_accent_css
+
+
+class AccentColorTests(unittest.TestCase):
+
+    def test_system_accent_is_left_alone(self):
+        self.assertEqual(build_accent_css('system', dark=False), "")
+
+    def test_accent_text_follows_the_appearance(self):
+        self.assertIn("@define-color accent_bg_color #3584e4;", build_accent_css('blue', dark=False))
+        self.assertIn("mix(#3584e4, white, 0.3)", build_accent_css('blue', dark=True))
+        self.assertIn("mix(#3584e4, black, 0.1)", build_accent_css('blue', dark=False))
+
+    def test_css_variables_only_when_asked(self):
+        self.assertNotIn("--accent-bg-color", build_accent_css('green', dark=False))
+        self.assertIn("--accent-bg-color: #3a944a;", build_accent_css('green', dark=False, css_variables=True))
+
+    def test_yellow_gets_dark_text(self):
+        self.assertIn("@define-color accent_fg_color rgba(0, 0, 0, 0.8);", build_accent_css('yellow', dark=True))
+
+
+if __name__ == '__main__':
+    unittest.main()
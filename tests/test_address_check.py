@@ -0,0 +1,31 @@
+"""Tests for checking New Chat addresses before creating the chat."""
+
+import unittest
+
+from src.utils.address_check import get_reachability, is_valid_address, parse_addresses
+
+
+class AddressCheckTests(unittest.TestCase):
+
+    def test_parse_addresses(self):
+        self.assertEqual(parse_addresses(" +15551234567, ann@icloud.com ,, "),
+                         ['+15551234567', 'ann@icloud.com'])
+
+    def test_valid_addresses(self):
+        for address in ('+1 (555) 123-4567', '262966', 'ann@icloud.com', '+44 20 7946 0958'):
+            self.assertTrue(is_valid_address(address), address)
+
+    def test_invalid_addresses(self):
+        for address in ('ann@', 'ann@icloud', 'call me', '12', '+1 555 CALL NOW'):
+            self.assertFalse(is_valid_address(address), address)
+
+    def test_reachability(self):
+        self.assertEqual(get_reachability('+15551234567', True), 'imessage')
+        self.assertEqual(get_reachability('+15551234567', False), 'sms')
+        self.assertEqual(get_reachability('ann@icloud.com', False), 'invalid')
+        self.assertEqual(get_reachability('+15551234567', None), 'unknown')
+        self.assertEqual(get_reachability('ann@', True), 'invalid')
+
+
+if __name__ == '__main__':
+    unittest.main()
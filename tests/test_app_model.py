@@ -0,0 +1,69 @@
+"""Tests for the shared in-memory app model."""
+
+import unittest
+
+from src.db.models import ChatRecord
+from src.models.app_model import CHATS, CONNECTION, MESSAGES, AppModel
+
+
+def make_chat(guid, unread=0, hide_badge=False):
+    return ChatRecord(original_rowid=1, guid=guid, chat_identifier=guid, style=45, unread_count=unread,
+                      hide_badge=hide_badge)
+
+
+class AppModelTests(unittest.TestCase):
+
+    def setUp(self):
+        self.model = AppModel()
+        self.events = []
+        for topic in (CHATS, MESSAGES, CONNECTION):
+            self.model.connect(topic, lambda *args, topic=topic: self.events.append((topic,) + args))
+
+    def test_unread_total_skips_hidden_badges(self):
+        self.model.set_chats([make_chat('a', 2), make_chat('b', 3, hide_badge=True), make_chat('c', 1)])
+        self.assertEqual(self.model.unread_total, 3)
+
+    def test_update_chat_keeps_position(self):
+        self.model.set_chats([make_chat('a'), make_chat('b'), make_chat('c')])
+        self.model.update_chat(make_chat('b', 4))
+        self.assertEqual([chat.guid for chat in self.model.chats], ['a', 'b', 'c'])
+        self.assertEqual(self.model.get_chat('b').unread_count, 4)
+        self.assertEqual(self.events[-1], (CHATS, 'b'))
+
+    def test_update_chat_moves_to_index(self):
+        self.model.set_chats([make_chat('a'), make_chat('b'), make_chat('c')])
+        self.model.update_chat(make_chat('c'), 0)
+        self.model.update_chat(make_chat('d'), 1)
+        self.assertEqual([chat.guid for chat in self.model.chats], ['c', 'd', 'a', 'b'])
+
+    def test_chats_copy_cannot_change_model(self):
+        self.model.set_chats([make_chat('a')])
+        self.model.chats.append(make_chat('b'))
+        self.assertEqual(len(self.model.chats), 1)
+
+    def test_messages_changed_forgets_loaded_messages(self):
+        self.model.set_chats([make_chat('a')])
+        self.model.set_messages('a', ['m1'])
+        self.assertEqual(self.events, [(CHATS, None)])
+        self.model.messages_changed('a')
+        self.assertEqual(self.model.get_messages('a'), [])
+        self.assertEqual(self.events[-1], (MESSAGES, 'a'))
+
+    def test_connection_changes_are_announced_once(self):
+        self.model.set_connected(False)
+        self.model.set_connected(False)
+        self.model.set_connected(True)
+        self.assertEqual(self.events, [(CONNECTION, False), (CONNECTION, True)])
+
+    def test_broken_listener_does_not_stop_others(self):
+        def broken(chat_guid):
+            raise RuntimeError
+        model = AppModel()
+        model.connect(CHATS, broken)
+        model.connect(CHATS, lambda chat_guid: self.events.append(('after', chat_guid)))
+        model.set_chats([])
+        self.assertEqual(self.events, [('after', None)])
+
+
+if __name__ == '__main__':
+    unittest.main()
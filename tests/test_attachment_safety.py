@@ -0,0 +1,36 @@
+"""Tests for deciding which attachments need a warning before opening."""
+
+import unittest
+
+from src.utils.attachment_safety import describe_file_type, is_risky, needs_open_confirmation
+
+
+class AttachmentSafetyTests(unittest.TestCase):
+
+    def test_everyday_files_open_without_asking(self):
+        self.assertFalse(needs_open_confirmation("IMG_0001.HEIC", "image/heic"))
+        self.assertFalse(needs_open_confirmation("clip.mov", "video/quicktime"))
+        self.assertFalse(needs_open_confirmation("notes.pdf", "application/pdf"))
+
+    def test_programs_are_risky(self):
+        self.assertTrue(is_risky("setup.exe", "application/octet-stream"))
+        self.assertTrue(is_risky("install.SH", ""))
+        self.assertTrue(needs_open_confirmation("tool.AppImage", "application/octet-stream"))
+
+    def test_program_posing_as_document(self):
+        self.assertTrue(is_risky("invoice.pdf.exe", "application/pdf"))
+        self.assertTrue(is_risky("photo.jpg", "application/x-executable"))
+
+    def test_unusual_types_ask_without_being_risky(self):
+        self.assertFalse(is_risky("data.xyz", "application/octet-stream"))
+        self.assertTrue(needs_open_confirmation("data.xyz", "application/octet-stream"))
+        self.assertTrue(needs_open_confirmation("unknown", ""))
+
+    def test_describe_file_type(self):
+        self.assertEqual(describe_file_type("setup.exe", ""), "Windows program (.exe)")
+        self.assertEqual(describe_file_type("data.xyz", "application/octet-stream"), "XYZ file")
+        self.assertEqual(describe_file_type("unknown", ""), "file of unknown type")
+
+
+if __name__ == '__main__':
+    unittest.main()
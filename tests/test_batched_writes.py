@@ -0,0 +1,47 @@
+"""Tests for saving synced messages in batches."""
+
+import unittest
+
+from tests.database_case import DatabaseTestCase
+
+
+CHAT_GUID = 'iMessage;+;chat123'
+
+
+def message(rowid: int, **fields):
+    data = {'originalROWID': rowid, 'guid': f'msg-{rowid}', 'text': f'text {rowid}',
+            'dateCreated': 1709294400000 + rowid}
+    data.update(fields)
+    return data
+
+
+class SaveMessagesTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        self.db.save_chat({'originalROWID': 1, 'guid': CHAT_GUID, 'chatIdentifier': 'chat123'})
+
+    def test_saves_every_message_and_handle(self):
+        handle = {'originalROWID': 5, 'address': '+15551234567'}
+        self.db.save_messages([message(1, handle=handle), message(2), message(3)], CHAT_GUID)
+
+        messages = self.db.get_chat_messages(CHAT_GUID)
+        self.assertEqual([m.guid for m in messages], ['msg-1', 'msg-2', 'msg-3'])
+        self.assertEqual(messages[0].handle_id, 5)
+
+    def test_missing_date_borrows_from_earlier_message_in_batch(self):
+        self.db.save_messages([message(1), message(2, dateCreated=None)], CHAT_GUID)
+        dates = [m.date_created for m in self.db.get_chat_messages(CHAT_GUID)]
+        self.assertEqual(dates[0], dates[1])
+
+    def test_failed_batch_saves_nothing(self):
+        with self.assertRaises(Exception):
+            self.db.save_messages([message(1), message(2, guid=None)], CHAT_GUID)
+        self.assertEqual(self.db.get_chat_messages(CHAT_GUID), [])
+
+        self.db.save_messages([message(3)], CHAT_GUID)
+        self.assertEqual(len(self.db.get_chat_messages(CHAT_GUID)), 1)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,41 @@
+"""Tests for judging the first-run checklist from the server's answers."""
+
+import unittest
+
+from src.utils.capabilities import CapabilityCheck, check_server_info, order_checks
+
+
+def outcomes(checks):
+    return {check.id: check.ok for check in checks}
+
+
+class CapabilityTests(unittest.TestCase):
+
+    def test_unreachable_server_skips_the_rest(self):
+        checks = check_server_info(None, "Connection refused", None)
+        self.assertEqual(outcomes(checks), {'reachable': False, 'auth': None, 'private_api': None})
+        self.assertIn("server URL", checks[0].hint)
+
+    def test_wrong_password(self):
+        self.assertEqual(outcomes(check_server_info(None, "Unauthorized", 401)),
+                         {'reachable': True, 'auth': False, 'private_api': None})
+
+    def test_private_api_needs_the_helper(self):
+        checks = check_server_info({'server_version': '1.9.0', 'private_api': True, 'helper_connected': False})
+        private_api = checks[-1]
+        self.assertFalse(private_api.ok)
+        self.assertIn("helper", private_api.detail)
+        self.assertIsNotNone(private_api.hint)
+
+    def test_working_server(self):
+        checks = check_server_info({'server_version': '1.9.0', 'private_api': True, 'helper_connected': True})
+        self.assertTrue(all(check.ok for check in checks))
+        self.assertTrue(all(check.hint is None for check in checks))
+
+    def test_checklist_order(self):
+        checks = [CapabilityCheck('contacts', True), CapabilityCheck('reachable', True), CapabilityCheck('socket', True)]
+        self.assertEqual([check.id for check in order_checks(checks)], ['reachable', 'socket', 'contacts'])
+
+
+if __name__ == '__main__':
+    unittest.main()
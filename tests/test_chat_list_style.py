@@ -0,0 +1,26 @@
+"""Tests for chat list appearance preferences."""
+
+import unittest
+
+from src.utils.chat_list_style import ChatListStyle
+
+
+class ChatListStyleTests(unittest.TestCase):
+
+    def test_defaults_match_original_layout(self):
+        style = ChatListStyle()
+        self.assertEqual(style.avatar_size, 40)
+        self.assertEqual(style.row_margin, 8)
+        self.assertEqual(style.preview_length, 60)
+
+    def test_compact_tightens_rows(self):
+        style = ChatListStyle(compact=True)
+        self.assertLess(style.row_margin, ChatListStyle().row_margin)
+        self.assertLess(style.row_spacing, ChatListStyle().row_spacing)
+
+    def test_two_lines_double_preview(self):
+        self.assertEqual(ChatListStyle(preview_lines=2).preview_length, 120)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,60 @@
+"""Tests for the cache queries behind the chat list views and conversation search."""
+
+import unittest
+
+from src.utils.search_query import parse_search_query
+from tests.database_case import DatabaseTestCase
+
+
+class ChatViewTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        self.db.save_chat({'originalROWID': 1, 'guid': 'chat-a', 'chatIdentifier': 'a'})
+        self.db.save_chat({'originalROWID': 2, 'guid': 'chat-b', 'chatIdentifier': 'b', 'isArchived': True})
+
+    def test_archived_chats_are_listed_apart(self):
+        self.assertEqual([chat.guid for chat in self.db.get_chats()], ['chat-a'])
+        self.assertEqual([chat.guid for chat in self.db.get_chats(archived=True)], ['chat-b'])
+
+    def test_starred_chats_are_found(self):
+        self.db.set_chat_setting('chat-b', 'starred', '1')
+        self.assertEqual(list(self.db.get_chats_with_setting('starred')), ['chat-b'])
+
+    def test_deleting_a_chat_keeps_its_star(self):
+        self.db.set_chat_setting('chat-a', 'starred', '1')
+        self.db.delete_chat('chat-a')
+        self.assertEqual(list(self.db.get_chats_with_setting('starred')), ['chat-a'])
+
+    def test_search_finds_newest_first(self):
+        for n, text in enumerate(["Lunch at noon?", "50% off lunch", "see you", "LUNCH_TIME"]):
+            self.db.save_message({'guid': f'm{n}', 'text': text, 'dateCreated': 1709294400000 + n * 60000}, 'chat-a')
+        self.assertEqual([guid for guid, date in self.db.search_chat_messages('chat-a', 'lunch')], ['m3', 'm1', 'm0'])
+        self.assertEqual([guid for guid, date in self.db.search_chat_messages('chat-a', '50%')], ['m1'])
+        self.assertEqual([guid for guid, date in self.db.search_chat_messages('chat-a', 'lunch_')], ['m3'])
+
+    def test_search_filters(self):
+        day = 1709294400000  # 2024-03-01 12:00 UTC
+        jane = {'originalROWID': 7, 'address': '+15551234567'}
+        self.db.save_message({'guid': 'm0', 'text': 'lunch?', 'dateCreated': day, 'handle': jane}, 'chat-a')
+        self.db.save_message({'guid': 'm1', 'text': 'lunch!', 'dateCreated': day + 86400000 * 2,
+                              'isFromMe': True, 'attachments': [{'guid': 'a1'}]}, 'chat-b')
+
+        def search(query, handle_ids=None):
+            return [guid for chat_guid, guid, date in self.db.search_messages(parse_search_query(query),
+                                                                              handle_ids=handle_ids)]
+
+        self.assertEqual(search('lunch'), ['m1', 'm0'])
+        self.assertEqual(search('LUNCH? ?'), ['m0'])
+        self.assertEqual(search('from:me'), ['m1'])
+        self.assertEqual(search('from:jane', [7]), ['m0'])
+        self.assertEqual(search('from:nobody', []), [])
+        self.assertEqual(search('has:attachment'), ['m1'])
+        self.assertEqual(search('after:2024-03-03'), ['m1'])
+        self.assertEqual(search('before:2024-03-03'), ['m0'])
+        self.assertEqual(self.db.search_message_chats(parse_search_query('lunch from:me')), {'chat-b'})
+        self.assertEqual(self.db.get_handle_addresses(), {7: '+15551234567'})
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,166 @@
+"""Tests for resuming large attachment uploads after a failure or a restart."""
+
+import asyncio
+import importlib
+import os
+import sys
+import types
+from unittest import mock
+
+from tests.database_case import DatabaseTestCase
+from tests.fake_aiohttp import fake_aiohttp_module
+
+CHUNK_SIZE = 4
+FILE_SIZE = 10
+
+
+class FakeClient:
+    """
+    Takes chunked uploads CHUNK_SIZE bytes at a time, failing with each error
+    in fail_with before the chunk at the matching index of fail_at_chunk.
+    """
+
+    def __init__(self, errors_module, fail_at_chunk=(), fail_with=(), limit=None):
+        self.errors = errors_module
+        self.fail_at_chunk = list(fail_at_chunk)
+        self.fail_with = list(fail_with)
+        self.limit = limit
+        # (upload GUID, start) of each chunked upload attempt
+        self.attempts = []
+        self.whole_uploads = 0
+
+    async def send_attachment_chunked(self, chat_guid, file_path, upload_guid, start=0, message="",
+                                      is_audio_message=False, on_progress=None):
+        self.attempts.append((upload_guid, start))
+        size = os.path.getsize(file_path)
+        offset = start
+        chunk = 0
+        while offset < size:
+            if self.fail_at_chunk and chunk == self.fail_at_chunk[0]:
+                self.fail_at_chunk.pop(0)
+                raise self.fail_with.pop(0)
+            offset = min(offset + CHUNK_SIZE, size)
+            chunk += 1
+            if on_progress:
+                on_progress(offset, size)
+        return {}
+
+    async def send_attachment(self, *args, **kwargs):
+        self.whole_uploads += 1
+        return {}
+
+    async def get_server_info(self):
+        return {'max_upload_size': self.limit}
+
+
+class ChunkedUploadTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        modules = {}
+        if importlib.util.find_spec('aiohttp') is None:
+            modules['aiohttp'] = fake_aiohttp_module()
+        if importlib.util.find_spec('toml') is None:
+            modules['toml'] = types.ModuleType('toml')
+            modules['toml'].TomlDecodeError = type('TomlDecodeError', (ValueError,), {})
+        # Restores sys.modules afterwards, dropping anything imported against the fakes
+        patcher = mock.patch.dict(sys.modules, modules)
+        patcher.start()
+        self.addCleanup(patcher.stop)
+        for name in [name for name in sys.modules if name.startswith(('src.services', 'src.api', 'src.config'))]:
+            del sys.modules[name]
+
+        patcher = mock.patch.dict(os.environ, {'XDG_CACHE_HOME': os.path.join(self.directory, 'cache')})
+        patcher.start()
+        self.addCleanup(patcher.stop)
+
+        self.chat_service_module = importlib.import_module('src.services.chat_service')
+        self.client_module = importlib.import_module('src.api.client')
+        self.file_path = os.path.join(self.directory, 'video.mov')
+        with open(self.file_path, 'wb') as f:
+            f.write(b'x' * FILE_SIZE)
+        self.service = self.make_service()
+
+    def make_service(self):
+        return self.chat_service_module.ChatService(self.db, mock.MagicMock())
+
+    def client(self, **kwargs):
+        return FakeClient(self.client_module, **kwargs)
+
+    def network_error(self):
+        return self.client_module.BlueBubblesAPIError("Network error: connection reset")
+
+    def upload(self, client, service=None):
+        service = service or self.service
+        asyncio.run(service._send_attachment_in_chunks(client, 'iMessage;-;chat', self.file_path, '', False, None))
+
+    def test_retry_resumes_from_the_saved_offset(self):
+        client = self.client(fail_at_chunk=[2], fail_with=[self.network_error()])
+        with self.assertRaises(self.client_module.BlueBubblesAPIError):
+            self.upload(client)
+        [(upload_guid, sent)] = self.service._partial_uploads.values()
+        self.assertEqual(sent, 8)
+
+        self.upload(client)
+        self.assertEqual(client.attempts, [(upload_guid, 0), (upload_guid, 8)])
+        self.assertEqual(self.service._partial_uploads, {})
+
+    def test_upload_resumes_after_a_restart(self):
+        client = self.client(fail_at_chunk=[1], fail_with=[self.network_error()])
+        with self.assertRaises(self.client_module.BlueBubblesAPIError):
+            self.upload(client)
+        self.service._save_partial_uploads()
+
+        restarted = self.make_service()
+        self.upload(client, restarted)
+        self.assertEqual(client.attempts[1], (client.attempts[0][0], 4))
+        self.assertEqual(restarted._partial_uploads, {})
+
+    def test_changed_file_starts_over(self):
+        client = self.client(fail_at_chunk=[1], fail_with=[self.network_error()])
+        with self.assertRaises(self.client_module.BlueBubblesAPIError):
+            self.upload(client)
+        os.utime(self.file_path, (0, 0))
+
+        self.upload(client)
+        self.assertNotEqual(client.attempts[1][0], client.attempts[0][0])
+        self.assertEqual(client.attempts[1][1], 0)
+
+    def test_rejected_upload_starts_over(self):
+        client = self.client(fail_at_chunk=[1], fail_with=[self.client_module.BlueBubblesAPIError("HTTP 400", 400)])
+        with self.assertRaises(self.client_module.BlueBubblesAPIError):
+            self.upload(client)
+        self.assertEqual(self.service._partial_uploads, {})
+
+        self.upload(client)
+        self.assertEqual(client.attempts[1][1], 0)
+
+    def test_rate_limited_upload_resumes(self):
+        error = self.client_module.BlueBubblesRateLimitError("HTTP 429", 30)
+        client = self.client(fail_at_chunk=[1], fail_with=[error])
+        with self.assertRaises(self.client_module.BlueBubblesRateLimitError):
+            self.upload(client)
+
+        self.upload(client)
+        self.assertEqual(client.attempts[1][1], 4)
+
+    def test_too_large_upload_names_the_limit(self):
+        client = self.client(fail_at_chunk=[0], fail_with=[self.client_module.BlueBubblesAPIError("HTTP 413", 413)],
+                             limit=8)
+        with self.assertRaises(self.client_module.BlueBubblesUploadTooLargeError) as caught:
+            self.upload(client)
+        self.assertEqual((caught.exception.size, caught.exception.limit), (FILE_SIZE, 8))
+        self.assertIn("larger than the server's 8 B upload limit", str(caught.exception))
+
+    def test_server_without_chunks_gets_the_whole_file(self):
+        client = self.client(fail_at_chunk=[0], fail_with=[self.client_module.BlueBubblesAPIError("HTTP 404", 404)],
+                             limit=FILE_SIZE)
+        self.upload(client)
+        self.assertEqual(client.whole_uploads, 1)
+
+    def test_server_without_chunks_checks_its_limit_first(self):
+        client = self.client(fail_at_chunk=[0], fail_with=[self.client_module.BlueBubblesAPIError("HTTP 404", 404)],
+                             limit=8)
+        with self.assertRaises(self.client_module.BlueBubblesUploadTooLargeError):
+            self.upload(client)
+        self.assertEqual(client.whole_uploads, 0)
@@ -0,0 +1,129 @@
+"""Tests for validating bb.toml and keeping what can't be used."""
+
+import os
+import shutil
+import tempfile
+import unittest
+from pathlib import Path
+from unittest import mock
+
+from src.config.schema import validate_config
+
+try:
+    import toml
+except ImportError:
+    toml = None
+
+
+class ValidateConfigTests(unittest.TestCase):
+
+    def test_valid_values_are_kept(self):
+        data = {'appearance': {'dark_mode': True, 'text_width': 90}}
+        cleaned, errors, rejected = validate_config(data)
+        self.assertEqual(cleaned, data)
+        self.assertEqual(errors, [])
+        self.assertEqual(rejected, {})
+
+    def test_invalid_values_are_set_aside(self):
+        cleaned, errors, rejected = validate_config({
+            'appearance': {'dark_mode': 'yes', 'text_width': 500, 'clock_format': '24h'},
+            'power': {'profile': 'turbo'},
+        })
+        self.assertEqual(cleaned, {'appearance': {'clock_format': '24h'}, 'power': {}})
+        self.assertEqual(rejected, {'appearance': {'dark_mode': 'yes', 'text_width': 500},
+                                    'power': {'profile': 'turbo'}})
+        self.assertEqual(len(errors), 3)
+        self.assertIn("appearance.text_width: must be at most 150", errors[1])
+
+    def test_bool_is_not_an_integer(self):
+        _, errors, _ = validate_config({'app': {'message_check_interval': True}})
+        self.assertEqual(errors, ["app.message_check_interval: expected an integer, got True"])
+
+    def test_section_that_isnt_a_table(self):
+        cleaned, errors, rejected = validate_config({'server': 'http://localhost'})
+        self.assertEqual(cleaned, {})
+        self.assertEqual(rejected, {'server': 'http://localhost'})
+        self.assertEqual(len(errors), 1)
+
+    def test_unknown_keys_are_kept(self):
+        data = {'plugins': {'enabled': ['x']}, 'appearance': {'future_option': 1}}
+        cleaned, errors, _ = validate_config(data)
+        self.assertEqual(cleaned, data)
+        self.assertEqual(errors, [])
+
+
+@unittest.skipUnless(toml, "the toml package isn't installed")
+class ConfigManagerTests(unittest.TestCase):
+
+    def setUp(self):
+        self.directory = tempfile.mkdtemp()
+        self.addCleanup(shutil.rmtree, self.directory)
+        patcher = mock.patch.dict(os.environ, {'XDG_CONFIG_HOME': self.directory})
+        patcher.start()
+        self.addCleanup(patcher.stop)
+        self.config_file = Path(self.directory) / 'bluebubbles' / 'bb.toml'
+        self.config_file.parent.mkdir()
+
+    def load(self, text: str):
+        from src.config.manager import ConfigManager
+        self.config_file.write_text(text)
+        return ConfigManager()
+
+    def test_invalid_values_survive_a_save(self):
+        config = self.load('[appearance]\ntext_width = 500\n\n[custom]\nkey = "value"\n')
+        self.assertEqual(config.get_text_width(), 80)
+        self.assertEqual(len(config.errors), 1)
+
+        config.set('appearance.dark_mode', True)
+        saved = toml.loads(self.config_file.read_text())
+        self.assertEqual(saved['appearance'], {'dark_mode': True, 'text_width': 500})
+        self.assertEqual(saved['custom'], {'key': 'value'})
+
+    def test_setting_a_value_replaces_the_invalid_one(self):
+        config = self.load('[appearance]\ntext_width = 500\n')
+        config.set('appearance.text_width', 100)
+        config.set('appearance.dark_mode', False)
+        self.assertEqual(toml.loads(self.config_file.read_text())['appearance']['text_width'], 100)
+
+    def test_invalid_toml_is_backed_up(self):
+        config = self.load('[server\n')
+        self.assertEqual(config.get_all(), {})
+        self.assertIn('bb.toml.invalid', config.errors[0])
+        self.assertEqual((self.config_file.parent / 'bb.toml.invalid').read_text(), '[server\n')
+
+    def test_backup_failure_doesnt_stop_startup(self):
+        with mock.patch('src.config.manager.shutil.copyfile', side_effect=PermissionError("read-only")):
+            config = self.load('[server\n')
+        self.assertEqual(config.get_all(), {})
+        self.assertIn("couldn't be saved", config.errors[0])
+
+    def test_environment_overrides_the_file(self):
+        config = self.load('[server]\nurl = "http://file"\n')
+        with mock.patch.dict(os.environ, {'BB_SERVER_URL': 'http://env'}):
+            self.assertEqual(config.get('server.url'), 'http://env')
+        self.assertEqual(config.get('server.url'), 'http://file')
+
+    def test_reload_returns_the_changed_keys(self):
+        config = self.load('[appearance]\ndark_mode = true\ntext_width = 90\n\n[server]\nurl = "http://a"\n')
+        self.config_file.write_text('[appearance]\ndark_mode = false\ntext_width = 90\n\n[power]\nprofile = "battery_saver"\n')
+        self.assertEqual(config.reload(), ['appearance.dark_mode', 'power.profile', 'server.url'])
+        self.assertFalse(config.get('appearance.dark_mode'))
+        self.assertIsNone(config.get('server.url'))
+
+    def test_reload_skips_our_own_save(self):
+        config = self.load('[appearance]\ntext_width = 90\n')
+        config.set('appearance.dark_mode', True)
+        self.assertEqual(config.reload(), [])
+        self.assertTrue(config.get('appearance.dark_mode'))
+
+    def test_reload_keeps_settings_when_the_file_is_invalid(self):
+        config = self.load('[appearance]\ntext_width = 90\n')
+        self.config_file.write_text('[appearance\n')
+        self.assertEqual(config.reload(), [])
+        self.assertEqual(config.get_text_width(), 90)
+        self.assertIn("previous settings stay in use", config.errors[0])
+        self.assertFalse((self.config_file.parent / 'bb.toml.invalid').exists())
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,97 @@
+"""Tests for writing crash reports and offering them on the next launch."""
+
+import shutil
+import tempfile
+import unittest
+from datetime import datetime, timedelta
+from pathlib import Path
+
+from src.utils.crash_reports import (
+    LOG_TAIL_LINES, MAX_CRASH_REPORTS, NATIVE_CRASH_FILE, CrashReporter, collect_native_crash,
+    format_crash_report, take_pending_report, write_crash_report
+)
+
+
+class CrashReportTests(unittest.TestCase):
+
+    def setUp(self):
+        self.crash_dir = Path(tempfile.mkdtemp()) / 'crashes'
+
+    def tearDown(self):
+        shutil.rmtree(self.crash_dir.parent)
+
+    def test_report_has_version_details_and_log_tail(self):
+        log_lines = [f"line {n}" for n in range(LOG_TAIL_LINES + 10)]
+        report = format_crash_report('1.2.3', 'Traceback: boom', log_lines)
+        self.assertIn('BlueBubbles Client 1.2.3', report)
+        self.assertIn('Traceback: boom', report)
+        self.assertTrue(report.endswith(f"line {LOG_TAIL_LINES + 9}\n"))
+        self.assertNotIn('line 9\n', report)
+
+    def test_pending_report_is_offered_once(self):
+        path = write_crash_report(self.crash_dir, 'report')
+        self.assertEqual(take_pending_report(self.crash_dir), path)
+        self.assertIsNone(take_pending_report(self.crash_dir))
+        self.assertTrue(path.is_file())  # Kept for bug reports
+
+    def test_old_reports_are_removed(self):
+        start = datetime(2026, 1, 1)
+        for n in range(MAX_CRASH_REPORTS + 3):
+            write_crash_report(self.crash_dir, f'report {n}', start + timedelta(seconds=n))
+        reports = sorted(self.crash_dir.glob('crash-*.txt'))
+        self.assertEqual(len(reports), MAX_CRASH_REPORTS)
+        self.assertEqual(reports[-1].read_text(), f'report {MAX_CRASH_REPORTS + 2}')
+
+    def test_native_crash_becomes_a_report(self):
+        self.crash_dir.mkdir(parents=True)
+        (self.crash_dir / NATIVE_CRASH_FILE).write_text('Fatal Python error: Segmentation fault\n')
+        path = collect_native_crash(self.crash_dir, '1.2.3', ['last line'])
+        self.assertIn('Segmentation fault', path.read_text())
+        self.assertEqual((self.crash_dir / NATIVE_CRASH_FILE).read_text(), '')
+        self.assertIsNone(collect_native_crash(self.crash_dir, '1.2.3', []))
+
+    def test_only_the_first_exception_is_reported(self):
+        reporter = CrashReporter('1.2.3', lambda: ['connected'], self.crash_dir)
+        for message in ('first', 'second'):
+            try:
+                raise ValueError(message)
+            except ValueError as e:
+                reporter.report(type(e), e, e.__traceback__)
+        report = reporter.report_path.read_text()
+        self.assertIn('ValueError: first', report)
+        self.assertIn('connected', report)
+        self.assertEqual(len(list(self.crash_dir.glob('crash-*.txt'))), 1)
+
+    def test_survived_exception_is_logged_but_not_offered(self):
+        errors = []
+        reporter = CrashReporter('1.2.3', lambda: [], self.crash_dir, on_error=errors.append)
+        try:
+            raise ValueError('callback failed')
+        except ValueError as e:
+            reporter.report(type(e), e, e.__traceback__, fatal=False)
+        self.assertEqual(errors, ['ValueError: callback failed'])
+        self.assertTrue(reporter.report_path.is_file())
+        self.assertIsNone(take_pending_report(self.crash_dir))
+
+    def test_fatal_exception_offers_the_first_report(self):
+        reporter = CrashReporter('1.2.3', lambda: [], self.crash_dir)
+        for message, fatal in (('first', False), ('second', True)):
+            try:
+                raise ValueError(message)
+            except ValueError as e:
+                reporter.report(type(e), e, e.__traceback__, fatal=fatal)
+        pending = take_pending_report(self.crash_dir)
+        self.assertEqual(pending, reporter.report_path)
+        self.assertIn('ValueError: first', pending.read_text())
+
+    def test_unreadable_log_still_writes_a_report(self):
+        reporter = CrashReporter('1.2.3', lambda: 1 / 0, self.crash_dir)
+        try:
+            raise RuntimeError('boom')
+        except RuntimeError as e:
+            reporter.report(type(e), e, e.__traceback__, 'worker')
+        self.assertIn('thread worker', reporter.report_path.read_text())
+
+
+if __name__ == '__main__':
+    unittest.main()
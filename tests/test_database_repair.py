@@ -0,0 +1,90 @@
+"""Tests for rebuilding a damaged cache database on startup."""
+
+import glob
+import os
+import shutil
+import sqlite3
+import tempfile
+import unittest
+from unittest import mock
+
+from src.db.manager import DatabaseManager
+
+
+class DatabaseRepairTests(unittest.TestCase):
+
+    def setUp(self):
+        self.directory = tempfile.mkdtemp()
+        self.db_path = os.path.join(self.directory, 'cache.db')
+
+    def tearDown(self):
+        shutil.rmtree(self.directory)
+
+    def test_healthy_database_is_left_alone(self):
+        DatabaseManager(self.db_path).pin_chat('chat-a')
+        db = DatabaseManager(self.db_path)
+        self.assertIsNone(db.repaired_backup)
+        self.assertEqual(db.get_pinned_chat_guids(), ['chat-a'])
+
+    def test_garbage_file_is_moved_aside(self):
+        with open(self.db_path, 'wb') as f:
+            f.write(b'this is not a database' * 100)
+
+        db = DatabaseManager(self.db_path)
+        self.assertIsNotNone(db.repaired_backup)
+        self.assertTrue(os.path.exists(db.repaired_backup))
+        self.assertEqual(db.check_integrity(), 'ok')
+        self.assertEqual(db.get_chats(), [])
+
+    def damage_pages(self):
+        db = DatabaseManager(self.db_path)
+        db.pin_chat('chat-a')
+        db.set_chat_setting('chat-a', 'nickname', 'Family')
+        db._close_connection()
+
+        # Damage a page past the header, leaving the file recognisable as SQLite
+        size = os.path.getsize(self.db_path)
+        with open(self.db_path, 'r+b') as f:
+            for offset in range(4096, size, 4096):
+                if offset // 4096 % 2:
+                    f.seek(offset)
+                    f.write(b'\xff' * 4096)
+
+    def test_damaged_pages_are_rebuilt(self):
+        self.damage_pages()
+        db = DatabaseManager(self.db_path)
+        self.assertIsNotNone(db.repaired_backup)
+        self.assertEqual(db.check_integrity(), 'ok')
+        self.assertEqual(len(glob.glob(self.db_path + '.corrupt-*')), 1)
+
+    def test_damage_is_only_reported_without_repair(self):
+        self.damage_pages()
+        with self.assertRaises(sqlite3.DatabaseError):
+            DatabaseManager(self.db_path, repair=False)
+        self.assertTrue(os.path.exists(self.db_path))
+        self.assertEqual(glob.glob(self.db_path + '.corrupt-*'), [])
+
+    def test_locked_database_is_not_moved(self):
+        db = DatabaseManager(self.db_path)
+        db.pin_chat('chat-a')
+        db._close_connection()
+        # A rollback journal lets another process's exclusive lock shut out readers
+        conn = sqlite3.connect(self.db_path)
+        conn.execute("PRAGMA journal_mode = DELETE")
+        conn.close()
+
+        other = sqlite3.connect(self.db_path, isolation_level=None)
+        other.execute("BEGIN EXCLUSIVE")
+        with mock.patch('src.db.manager.BUSY_TIMEOUT', 0.1):
+            with self.assertRaises(sqlite3.OperationalError):
+                DatabaseManager(self.db_path)
+        other.close()
+
+        self.assertEqual(glob.glob(self.db_path + '.corrupt-*'), [])
+        db = DatabaseManager(self.db_path)
+        self.assertIsNone(db.repaired_backup)
+        self.assertEqual(db.get_pinned_chat_guids(), ['chat-a'])
+
+
+if __name__ == '__main__':
+    unittest.main()
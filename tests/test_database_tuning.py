@@ -0,0 +1,52 @@
+"""Tests for the cache database's journal and locking settings."""
+
+import os
+import threading
+import unittest
+
+from src.db.manager import BUSY_TIMEOUT
+from tests.database_case import DatabaseTestCase
+
+CHAT_GUID = 'iMessage;+;chat123'
+
+
+class DatabaseTuningTests(DatabaseTestCase):
+
+    def test_connection_settings(self):
+        conn = self.db._get_connection()
+        self.assertEqual(conn.execute("PRAGMA journal_mode").fetchone()[0], 'wal')
+        self.assertEqual(conn.execute("PRAGMA synchronous").fetchone()[0], 1)  # NORMAL
+        self.assertEqual(conn.execute("PRAGMA busy_timeout").fetchone()[0], BUSY_TIMEOUT * 1000)
+
+    def test_checkpoint_empties_log(self):
+        self.db.save_chat({'originalROWID': 1, 'guid': CHAT_GUID, 'chatIdentifier': 'chat123'})
+        self.assertTrue(self.db.checkpoint(truncate=True))
+        self.assertEqual(os.path.getsize(self.db.db_path + '-wal'), 0)
+
+    def test_reader_does_not_block_writer(self):
+        self.db.save_chat({'originalROWID': 1, 'guid': CHAT_GUID, 'chatIdentifier': 'chat123'})
+        reader = self.db._get_connection()
+        reader.execute("BEGIN")
+        reader.execute("SELECT COUNT(*) FROM chats").fetchone()
+
+        errors = []
+
+        def write():
+            try:
+                self.db.save_message({
+                    'originalROWID': 10, 'guid': 'msg-10', 'text': 'hi', 'dateCreated': 1709294400000,
+                }, CHAT_GUID)
+            except Exception as e:
+                errors.append(e)
+
+        writer = threading.Thread(target=write)
+        writer.start()
+        writer.join(timeout=BUSY_TIMEOUT)
+        reader.rollback()
+
+        self.assertEqual(errors, [])
+        self.assertEqual(len(self.db.get_chat_messages(CHAT_GUID)), 1)
+
+
+if __name__ == '__main__':
+    unittest.main()
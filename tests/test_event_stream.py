@@ -0,0 +1,98 @@
+"""Tests for staying connected to the server's live event socket."""
+
+import asyncio
+import types
+import unittest
+from collections import deque
+from unittest import mock
+
+from tests.fake_aiohttp import ClientTestCase
+
+OPEN_FRAME = '0{"sid":"abc","upgrades":[],"pingInterval":2000,"pingTimeout":1000}'
+
+
+class FakeClock:
+
+    def __init__(self):
+        self.now = 0.0
+
+    def monotonic(self):
+        return self.now
+
+
+class FakeSocket:
+    """
+    Plays back frames; None stands for a second with nothing received.
+    Once the frames run out the socket reports itself closed.
+    """
+
+    def __init__(self, frames, clock: FakeClock, message_types):
+        self.frames = deque(frames)
+        self.clock = clock
+        self.message_types = message_types
+        self.sent = []
+
+    async def __aenter__(self):
+        return self
+
+    async def __aexit__(self, *exc_info):
+        return False
+
+    async def receive(self, timeout: float):
+        if not self.frames:
+            return types.SimpleNamespace(type=self.message_types.CLOSED, data=None)
+        frame = self.frames.popleft()
+        if frame is None:
+            self.clock.now += timeout
+            raise asyncio.TimeoutError()
+        return types.SimpleNamespace(type=self.message_types.TEXT, data=frame)
+
+    async def send_str(self, data: str):
+        self.sent.append(data)
+
+
+class ListenEventsTests(ClientTestCase):
+
+    def listen(self, frames, clock: FakeClock = None):
+        clock = clock or FakeClock()
+        socket = FakeSocket(frames, clock, self.client_module.aiohttp.WSMsgType)
+        client = self.client_module.BlueBubblesClient('http://localhost:1234', 'secret')
+        client.session = mock.MagicMock()
+        client.session.ws_connect.return_value = socket
+        connected = []
+
+        with mock.patch.object(self.client_module, 'time', clock):
+            asyncio.run(client.listen_events(lambda event: None, lambda: False, lambda: connected.append(True)))
+        return socket, clock, connected
+
+    def test_silent_socket_times_out(self):
+        with self.assertRaisesRegex(self.client_module.BlueBubblesAPIError, "no ping"):
+            self.listen([OPEN_FRAME, '40'] + [None] * 10)
+
+    def test_each_ping_moves_the_deadline(self):
+        # Pinged at 2s, so the next ping is due by 2 + 2 + 1 seconds
+        clock = FakeClock()
+        with self.assertRaises(self.client_module.BlueBubblesAPIError):
+            self.listen([OPEN_FRAME, '40', None, None, '2'] + [None] * 10, clock)
+        self.assertEqual(clock.now, 6)
+
+    def test_pings_keep_the_socket_open(self):
+        frames = [OPEN_FRAME, '40']
+        for _ in range(5):
+            frames += [None, None, '2']
+        socket, clock, connected = self.listen(frames)
+
+        self.assertEqual(connected, [True])
+        self.assertEqual(socket.sent, ['40'] + ['3'] * 5)
+        self.assertEqual(clock.now, 10)
+
+    def test_missing_heartbeat_settings_use_defaults(self):
+        # 25s interval + 20s timeout by default: 44 quiet seconds are fine
+        socket, clock, _ = self.listen(['0{"sid":"abc"}', '40'] + [None] * 44)
+        self.assertEqual(clock.now, 44)
+        with self.assertRaises(self.client_module.BlueBubblesAPIError):
+            self.listen(['0{"sid":"abc"}', '40'] + [None] * 46)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,53 @@
+"""Tests for encrypting and decrypting chat exports."""
+
+import os
+import shutil
+import tempfile
+import unittest
+
+from src.utils.export_crypto import (
+    ENCRYPTED_MAGIC, ENCRYPTION_AVAILABLE, decrypt_archive, decrypt_export_file, encrypt_archive
+)
+
+ARCHIVE = b"PK\x03\x04 not really a zip, but any bytes will do"
+
+
+@unittest.skipUnless(ENCRYPTION_AVAILABLE, "the cryptography package isn't installed")
+class ExportCryptoTests(unittest.TestCase):
+
+    def test_round_trip(self):
+        encrypted = encrypt_archive(ARCHIVE, "correct horse")
+        self.assertTrue(encrypted.startswith(ENCRYPTED_MAGIC))
+        self.assertNotIn(ARCHIVE, encrypted)
+        self.assertEqual(decrypt_archive(encrypted, "correct horse"), ARCHIVE)
+
+    def test_wrong_passphrase_fails(self):
+        encrypted = encrypt_archive(ARCHIVE, "correct horse")
+        with self.assertRaisesRegex(ValueError, "Wrong passphrase"):
+            decrypt_archive(encrypted, "battery staple")
+
+    def test_tampered_export_fails(self):
+        encrypted = bytearray(encrypt_archive(ARCHIVE, "correct horse"))
+        encrypted[-1] ^= 1
+        with self.assertRaises(ValueError):
+            decrypt_archive(bytes(encrypted), "correct horse")
+
+    def test_plain_archive_is_rejected(self):
+        with self.assertRaisesRegex(ValueError, "Not an encrypted"):
+            decrypt_archive(ARCHIVE, "correct horse")
+
+    def test_decrypt_file(self):
+        directory = tempfile.mkdtemp()
+        self.addCleanup(shutil.rmtree, directory)
+        export_path = os.path.join(directory, 'Family.bbexport')
+        with open(export_path, 'wb') as f:
+            f.write(encrypt_archive(ARCHIVE, "correct horse"))
+
+        output_path = os.path.join(directory, 'Family.zip')
+        decrypt_export_file(export_path, output_path, "correct horse")
+        with open(output_path, 'rb') as f:
+            self.assertEqual(f.read(), ARCHIVE)
+
+
+if __name__ == '__main__':
+    unittest.main()
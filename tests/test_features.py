@@ -0,0 +1,39 @@
+"""Tests for turning optional features off in a build."""
+
+import unittest
+from unittest import mock
+
+from src.utils import features
+from src.utils.features import (
+    DISABLE_FEATURES_ENV, describe_features, get_missing_reason, is_feature_enabled, require_feature
+)
+
+
+class FeatureTests(unittest.TestCase):
+
+    def test_everything_is_enabled_by_default(self):
+        for name in features.OPTIONAL_FEATURES:
+            self.assertTrue(is_feature_enabled(name, environ={}))
+
+    def test_environment_turns_features_off(self):
+        environ = {DISABLE_FEATURES_ENV: 'media, pdf'}
+        self.assertFalse(is_feature_enabled('media', environ))
+        self.assertFalse(is_feature_enabled('pdf', environ))
+        self.assertTrue(is_feature_enabled('encryption', environ))
+
+    def test_build_turns_features_off(self):
+        with mock.patch.object(features, 'DISABLED_FEATURES', {'media'}):
+            with self.assertRaises(ImportError):
+                require_feature('media')
+            self.assertEqual(get_missing_reason('media'), "it isn't included in this build")
+            self.assertEqual(describe_features({'media': True, 'pdf': True}), {
+                'media': "disabled", 'pdf': "enabled", 'encryption': "missing the cryptography package",
+            })
+
+    def test_missing_library(self):
+        with mock.patch.dict('os.environ', {}, clear=True):
+            self.assertEqual(get_missing_reason('media'), "GStreamer isn't installed")
+
+
+if __name__ == '__main__':
+    unittest.main()
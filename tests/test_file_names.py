@@ -0,0 +1,23 @@
+"""Tests for making file names from chat titles."""
+
+import unittest
+
+from src.utils.file_names import safe_file_name
+
+
+class SafeFileNameTests(unittest.TestCase):
+
+    def test_plain_name(self):
+        self.assertEqual(safe_file_name("Family", "zip"), "Family.zip")
+
+    def test_path_separators_are_replaced(self):
+        self.assertEqual(safe_file_name("Mom/Dad", "zip"), "Mom-Dad.zip")
+        self.assertEqual(safe_file_name("../../.bashrc", "zip"), "-..-.bashrc.zip")
+
+    def test_empty_name_uses_fallback(self):
+        self.assertEqual(safe_file_name("  ", "bbexport", "Chat"), "Chat.bbexport")
+        self.assertEqual(safe_file_name("...", "zip"), "Untitled.zip")
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,37 @@
+"""Tests for reading the server's iMessage account status."""
+
+import unittest
+
+from src.utils.imessage_account import describe_account, get_aliases, is_signed_out
+
+ACCOUNT = {
+    'apple_id': 'me@icloud.com',
+    'login_status_message': 'Connected',
+    'vetted_aliases': [
+        {'Alias': 'old@example.com', 'Status': 0},
+        {'Alias': 'me@icloud.com', 'Status': 3},
+    ],
+}
+
+
+class IMessageAccountTests(unittest.TestCase):
+
+    def test_connected_account(self):
+        self.assertFalse(is_signed_out(ACCOUNT))
+        self.assertEqual(describe_account(ACCOUNT), "me@icloud.com · Connected")
+
+    def test_signed_out_status(self):
+        self.assertTrue(is_signed_out(dict(ACCOUNT, login_status_message='Signed Out')))
+        self.assertTrue(is_signed_out(dict(ACCOUNT, login_status_message='Not Connected')))
+
+    def test_missing_apple_id(self):
+        account = {'apple_id': None, 'login_status_message': 'Unknown'}
+        self.assertTrue(is_signed_out(account))
+        self.assertEqual(describe_account(account), "Signed out")
+
+    def test_active_aliases_first(self):
+        self.assertEqual(get_aliases(ACCOUNT), [('me@icloud.com', True), ('old@example.com', False)])
+
+
+if __name__ == '__main__':
+    unittest.main()
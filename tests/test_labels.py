@@ -0,0 +1,48 @@
+"""Tests for chat labels and their notification policies."""
+
+import unittest
+
+from src.utils.labels import get_notification_policy
+from tests.database_case import DatabaseTestCase
+
+
+class NotificationPolicyTests(unittest.TestCase):
+
+    def test_unlabelled_chat_notifies(self):
+        self.assertEqual(get_notification_policy([], {'Work': 'none'}), 'all')
+
+    def test_most_restrictive_label_wins(self):
+        policies = {'Family': 'all', 'Work': 'none', 'Alerts': 'quiet'}
+        self.assertEqual(get_notification_policy(['Family', 'Alerts'], policies), 'quiet')
+        self.assertEqual(get_notification_policy(['Alerts', 'Work', 'Family'], policies), 'none')
+
+    def test_unknown_policy_is_ignored(self):
+        self.assertEqual(get_notification_policy(['Work'], {'Work': 'sometimes'}), 'all')
+
+
+class LabelStorageTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        for name in ('Family', 'Work'):
+            self.db.create_label(name)
+
+    def test_chat_labels_follow_label_order(self):
+        self.db.set_chat_labels('chat-1', ['Work', 'Family'])
+        self.assertEqual(self.db.get_chat_labels(), {'chat-1': ['Family', 'Work']})
+
+    def test_deleting_a_label_removes_it_from_chats(self):
+        self.db.set_chat_labels('chat-1', ['Work', 'Family'])
+        self.db.set_label_notifications('Work', 'none')
+        self.db.delete_label('Work')
+        self.assertEqual(self.db.get_labels(), [('Family', 'all')])
+        self.assertEqual(self.db.get_chat_labels(), {'chat-1': ['Family']})
+
+    def test_labels_survive_clearing_the_cache(self):
+        self.db.set_chat_labels('chat-1', ['Family'])
+        self.db.clear_cache()
+        self.assertEqual(self.db.get_chat_labels(), {'chat-1': ['Family']})
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,110 @@
+"""Tests for turning web addresses in message text into links, and for parsing message links."""
+
+import unittest
+
+from src.utils.links import MessageLink, linkify, parse_message_link
+
+
+class LinkifyTests(unittest.TestCase):
+
+    def test_plain_text_is_escaped(self):
+        self.assertEqual(linkify("a < b & c"), "a &lt; b &amp; c")
+
+    def test_web_addresses_become_links(self):
+        self.assertEqual(
+            linkify("see https://example.com/a?b=1&c=2."),
+            'see <a href="https://example.com/a?b=1&amp;c=2">https://example.com/a?b=1&amp;c=2</a>.'
+        )
+
+    def test_bare_www_addresses_get_a_scheme(self):
+        self.assertEqual(linkify("(www.example.com)"), '(<a href="https://www.example.com">www.example.com</a>)')
+
+
+class BlueBubblesLinkTests(unittest.TestCase):
+
+    def test_chat_link_opens_the_chat(self):
+        self.assertEqual(
+            parse_message_link("bluebubbles://chat/iMessage;-;+15551234567"),
+            MessageLink(chat_guid="iMessage;-;+15551234567")
+        )
+
+    def test_chat_guid_and_body_are_percent_decoded(self):
+        self.assertEqual(
+            parse_message_link("bluebubbles://chat/iMessage%3B%2B%3Bchat123?body=hi%20there"),
+            MessageLink(chat_guid="iMessage;+;chat123", body="hi there")
+        )
+
+    def test_send_link_reads_addresses_and_body(self):
+        self.assertEqual(
+            parse_message_link("bluebubbles://send?address=%2B15551234567&body=Hello%20%26%20bye"),
+            MessageLink(addresses=["+15551234567"], body="Hello & bye")
+        )
+
+    def test_literal_plus_is_kept_as_a_phone_prefix(self):
+        link = parse_message_link("bluebubbles://send?address=+15551234567")
+        self.assertEqual(link.addresses, ["+15551234567"])
+
+    def test_addresses_can_be_comma_separated_or_repeated(self):
+        link = parse_message_link("bluebubbles://send?address=a@b.com,+1555&address=%2B1666")
+        self.assertEqual(link.addresses, ["a@b.com", "+1555", "+1666"])
+
+    def test_missing_parameters_give_no_link(self):
+        for uri in ("bluebubbles://chat/", "bluebubbles://send", "bluebubbles://send?body=hi"):
+            with self.subTest(uri=uri):
+                self.assertIsNone(parse_message_link(uri))
+
+    def test_malformed_or_unknown_links_are_ignored(self):
+        for uri in ("", "not a uri", "bluebubbles:", "bluebubbles://other/x", "http://example.com"):
+            with self.subTest(uri=uri):
+                self.assertIsNone(parse_message_link(uri))
+
+
+class MessageUriTests(unittest.TestCase):
+
+    def test_multiple_recipients(self):
+        self.assertEqual(
+            parse_message_link("sms:+15551234567,+15556667777"),
+            MessageLink(addresses=["+15551234567", "+15556667777"], service="SMS")
+        )
+
+    def test_query_body(self):
+        self.assertEqual(
+            parse_message_link("sms:+1555?body=Hi%20there"),
+            MessageLink(addresses=["+1555"], body="Hi there", service="SMS")
+        )
+
+    def test_ios_ampersand_body(self):
+        self.assertEqual(
+            parse_message_link("sms:+1555&body=Hi"),
+            MessageLink(addresses=["+1555"], body="Hi", service="SMS")
+        )
+
+    def test_double_slash_form(self):
+        self.assertEqual(
+            parse_message_link("sms://+1555?body=hey"),
+            MessageLink(addresses=["+1555"], body="hey", service="SMS")
+        )
+
+    def test_open_addresses_form(self):
+        link = parse_message_link("sms:/open?addresses=+1555,+1666&body=yo")
+        self.assertEqual(link.addresses, ["+1555", "+1666"])
+        self.assertEqual(link.body, "yo")
+
+    def test_parameters_after_the_recipient_are_dropped(self):
+        link = parse_message_link("sms:+1555;via=+1777?body=x")
+        self.assertEqual(link.addresses, ["+1555"])
+
+    def test_schemes_map_to_services(self):
+        for uri, service in (("smsto:+1555", "SMS"), ("mms:+1555", "SMS"), ("SMS:+1555", "SMS"),
+                             ("imessage:a@b.com", "iMessage")):
+            with self.subTest(uri=uri):
+                self.assertEqual(parse_message_link(uri).service, service)
+
+    def test_no_recipient_gives_no_link(self):
+        for uri in ("sms:", "sms:?body=x", "imessage:"):
+            with self.subTest(uri=uri):
+                self.assertIsNone(parse_message_link(uri))
+
+
+if __name__ == '__main__':
+    unittest.main()
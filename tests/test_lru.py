@@ -0,0 +1,45 @@
+"""Tests for the size-bounded in-memory attachment cache."""
+
+import unittest
+
+from src.utils.lru import ByteLimitedCache
+
+
+class ByteLimitedCacheTests(unittest.TestCase):
+
+    def test_least_recently_used_is_dropped(self):
+        cache = ByteLimitedCache(10)
+        cache['a'] = b'1234'
+        cache['b'] = b'1234'
+        self.assertEqual(cache['a'], b'1234')  # a is now the most recent
+        cache['c'] = b'1234'
+        self.assertIn('a', cache)
+        self.assertNotIn('b', cache)
+        self.assertEqual(cache.total_bytes, 8)
+
+    def test_newest_item_is_kept_even_if_too_big(self):
+        cache = ByteLimitedCache(10)
+        cache['a'] = b'1234'
+        cache['big'] = b'x' * 20
+        self.assertNotIn('a', cache)
+        self.assertIn('big', cache)
+
+    def test_lowering_the_limit_trims(self):
+        cache = ByteLimitedCache(100)
+        for key in 'abcd':
+            cache[key] = b'x' * 10
+        cache.set_limit(25)
+        self.assertEqual([key for key in 'abcd' if key in cache], ['c', 'd'])
+
+    def test_replacing_and_removing_keep_the_total(self):
+        cache = ByteLimitedCache(100)
+        cache['a'] = b'x' * 10
+        cache['a'] = b'x' * 4
+        self.assertEqual(cache.total_bytes, 4)
+        self.assertEqual(cache.pop('a'), b'x' * 4)
+        self.assertIsNone(cache.pop('a'))
+        self.assertEqual(cache.total_bytes, 0)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,72 @@
+"""Tests for listing a chat's images and videos for the media viewer, and searching attachments."""
+
+import unittest
+
+from src.db.manager import DatabaseManager
+from src.utils.search_query import parse_search_query
+from tests.database_case import DatabaseTestCase
+
+CHAT_GUID = 'iMessage;-;+15551234567'
+
+
+def attachment(guid: str, mime_type: str):
+    return {'guid': guid, 'mimeType': mime_type, 'transferName': f'{guid}.bin'}
+
+
+class MediaIndexTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        self.db.save_chat({'guid': CHAT_GUID, 'originalROWID': 1, 'chatIdentifier': '+15551234567', 'style': 45})
+        self.db.save_messages([
+            {'guid': 'msg-2', 'originalROWID': 2, 'dateCreated': 1709294460000, 'isFromMe': True,
+             'attachments': [attachment('att-video', 'video/quicktime'), attachment('att-pdf', 'application/pdf')]},
+            {'guid': 'msg-1', 'originalROWID': 1, 'dateCreated': 1709294400000,
+             'handle': {'originalROWID': 7, 'address': '+15551234567'},
+             'attachments': [attachment('att-photo', 'image/jpeg')]},
+            {'guid': 'msg-3', 'originalROWID': 3, 'text': 'No attachments', 'dateCreated': 1709294520000},
+        ], CHAT_GUID)
+
+    def test_images_and_videos_oldest_first(self):
+        media = self.db.get_chat_media(CHAT_GUID)
+        self.assertEqual([item.guid for item in media], ['att-photo', 'att-video'])
+        self.assertEqual(media[0].handle_address, '+15551234567')
+        self.assertTrue(media[1].is_from_me)
+
+    def test_hidden_messages_are_left_out(self):
+        self.db.hide_message('msg-1')
+        self.assertEqual([item.guid for item in self.db.get_chat_media(CHAT_GUID)], ['att-video'])
+
+    def test_unreadable_attachment_data_is_skipped(self):
+        conn = self.db._get_connection()
+        conn.execute("UPDATE messages SET attachments_json = ? WHERE guid = 'msg-2'", ('{broken',))
+        conn.commit()
+        self.assertEqual([item.guid for item in self.db.get_chat_media(CHAT_GUID)], ['att-photo'])
+
+
+    def search_attachments(self, query: str):
+        return sorted(hit.guid for hit in self.db.search_attachments(parse_search_query(query)))
+
+    def test_attachments_are_found_by_kind_and_name(self):
+        self.assertEqual(self.search_attachments('has:pdf'), ['att-pdf'])
+        self.assertEqual(self.search_attachments('has:attachment'), ['att-pdf', 'att-photo', 'att-video'])
+        self.assertEqual(self.search_attachments('from:me has:video'), ['att-video'])
+        # Messages match on their attachments' file names too
+        matches = self.db.search_messages(parse_search_query('att-photo.bin'))
+        self.assertEqual([guid for chat_guid, guid, date in matches], ['msg-1'])
+
+    def test_caches_from_before_the_index_are_indexed(self):
+        conn = self.db._get_connection()
+        conn.execute("DELETE FROM message_attachments")
+        conn.commit()
+        self.db = DatabaseManager(self.db_path)
+        self.assertEqual(self.search_attachments('has:image'), ['att-photo'])
+
+    def test_deleted_messages_leave_the_index(self):
+        self.db.delete_chat_messages(CHAT_GUID)
+        count = self.db._get_connection().execute("SELECT COUNT(*) FROM message_attachments").fetchone()[0]
+        self.assertEqual(count, 0)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,63 @@
+"""Tests for ordering cached messages when timestamps collide or are missing."""
+
+import unittest
+
+from tests.database_case import DatabaseTestCase
+
+
+CHAT_GUID = 'iMessage;-;+15551234567'
+
+
+def message(rowid: int, date_created=None, text: str = ''):
+    """Build a message as the server sends it."""
+    data = {'originalROWID': rowid, 'guid': f'msg-{rowid}', 'text': text or f'#{rowid}'}
+    if date_created is not None:
+        data['dateCreated'] = date_created
+    return data
+
+
+class MessageOrderTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        self.db.save_chat({'originalROWID': 1, 'guid': CHAT_GUID, 'chatIdentifier': '+15551234567'})
+
+    def texts(self):
+        """Get cached message texts, oldest first."""
+        return [m.text for m in self.db.get_chat_messages(CHAT_GUID)]
+
+    def test_equal_timestamps_ordered_by_rowid(self):
+        # Saved out of order, as a fast exchange might arrive
+        for rowid in (12, 10, 11):
+            self.db.save_message(message(rowid, 1709294400000), CHAT_GUID)
+        self.assertEqual(self.texts(), ['#10', '#11', '#12'])
+
+    def test_missing_timestamp_follows_previous_rowid(self):
+        self.db.save_message(message(10, 1709294400000), CHAT_GUID)
+        self.db.save_message(message(11, 1709294460000), CHAT_GUID)
+        self.db.save_message(message(13, 1709294520000), CHAT_GUID)
+        self.db.save_message(message(12), CHAT_GUID)
+        self.assertEqual(self.texts(), ['#10', '#11', '#12', '#13'])
+
+    def test_missing_timestamp_keeps_cached_date(self):
+        self.db.save_message(message(10, 1709294400000), CHAT_GUID)
+        self.db.save_message(message(11, 1709294460000), CHAT_GUID)
+        self.db.save_message(message(10, text='edited'), CHAT_GUID)
+        self.assertEqual(self.texts(), ['edited', '#11'])
+
+    def test_sort_key_breaks_ties(self):
+        for rowid in (21, 20):
+            self.db.save_message(message(rowid, 1709294400000), CHAT_GUID)
+        records = self.db.get_chat_messages(CHAT_GUID)
+        self.assertEqual([m.original_rowid for m in sorted(records, key=lambda m: m.sort_key)],
+                         [20, 21])
+
+    def test_messages_before_a_time(self):
+        for rowid in (10, 11, 12, 13):
+            self.db.save_message(message(rowid, 1709294400000 + rowid * 60000), CHAT_GUID)
+        records = self.db.get_chat_messages(CHAT_GUID, limit=2, before=1709294400000 + 12 * 60000)
+        self.assertEqual([m.text for m in records], ['#10', '#11'])
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,40 @@
+"""Tests for recalling sent messages into the composer."""
+
+import unittest
+
+from src.db.models import MessageRecord
+from src.utils.message_recall import get_recallable_messages
+
+
+def message(rowid: int, **fields):
+    data = dict(original_rowid=rowid, guid=f'msg-{rowid}', text=f'text {rowid}', handle_id=None,
+                handle_address=None, chat_guid='iMessage;-;+15551234567',
+                date_created=1709294400000 + rowid, is_from_me=True)
+    data.update(fields)
+    return MessageRecord(**data)
+
+
+class RecallableMessagesTests(unittest.TestCase):
+
+    def test_newest_sent_message_first(self):
+        messages = [message(3), message(2, is_from_me=False), message(1)]
+        self.assertEqual([m.guid for m in get_recallable_messages(messages)], ['msg-3', 'msg-1'])
+
+    def test_skips_messages_without_own_text(self):
+        messages = [
+            message(1),
+            message(2, associated_message_guid='p:0/msg-1', associated_message_type='love'),
+            message(3, text=None, item_type=2, group_title='Trip'),
+            message(4, date_retracted=1709294500000),
+            message(5, text='\n'),
+            message(6, text='\ufffc', attachments=[{'guid': 'att-6'}]),
+        ]
+        self.assertEqual([m.guid for m in get_recallable_messages(messages)], ['msg-1'])
+
+    def test_limit(self):
+        messages = [message(rowid) for rowid in range(1, 6)]
+        self.assertEqual(len(get_recallable_messages(messages, limit=2)), 2)
+
+
+if __name__ == '__main__':
+    unittest.main()
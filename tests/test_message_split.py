@@ -0,0 +1,59 @@
+"""Tests for splitting over-long messages."""
+
+import unittest
+
+from src.utils.message_split import (
+    SMS_UNICODE_LIMIT, get_length, get_length_limit, preview_part, split_message
+)
+
+
+class SplitMessageTests(unittest.TestCase):
+
+    def test_short_text_is_one_part(self):
+        self.assertEqual(split_message("  hello  ", 100), ["hello"])
+
+    def test_parts_fit_the_limit_and_keep_every_word(self):
+        text = " ".join(f"word{i}" for i in range(500))
+        parts = split_message(text, 160)
+        self.assertTrue(all(len(part) <= 160 for part in parts))
+        self.assertEqual(" ".join(parts).split(), text.split())
+
+    def test_prefers_sentence_breaks(self):
+        text = "First sentence is here. " + "x" * 20 + " more words follow"
+        parts = split_message(text, 40)
+        self.assertEqual(parts[0], "First sentence is here.")
+
+    def test_prefers_paragraph_breaks(self):
+        text = "Para one, with words.\n\nPara two. Still para two here"
+        self.assertEqual(split_message(text, 40)[0], "Para one, with words.")
+
+    def test_unbroken_text_is_cut_at_limit(self):
+        self.assertEqual(split_message("a" * 25, 10), ["a" * 10, "a" * 10, "a" * 5])
+
+    def test_limits(self):
+        self.assertEqual(get_length_limit('SMS'), 1530)
+        self.assertIsNone(get_length_limit('Unknown'))
+
+    def test_unicode_sms_is_limited_in_utf16_units(self):
+        text = "word " * 300 + "🎉"
+        self.assertEqual(get_length_limit('SMS', text), SMS_UNICODE_LIMIT)
+        self.assertEqual(get_length('SMS', "🎉é"), 3)
+        self.assertEqual(get_length('iMessage', "🎉é"), 2)
+
+        parts = split_message(text, SMS_UNICODE_LIMIT, lambda part: get_length('SMS', part))
+        self.assertGreater(len(parts), 1)
+        self.assertTrue(all(get_length('SMS', part) <= SMS_UNICODE_LIMIT for part in parts))
+        self.assertEqual(" ".join(parts).split(), text.split())
+
+    def test_emoji_are_not_cut_in_half(self):
+        parts = split_message("🎉" * 5, 4, lambda part: len(part.encode('utf-16-le')) // 2)
+        self.assertEqual(parts, ["🎉🎉", "🎉🎉", "🎉"])
+
+    def test_preview_shows_both_ends(self):
+        preview = preview_part("start " + "x" * 100 + " the end")
+        self.assertTrue(preview.startswith("start"))
+        self.assertTrue(preview.endswith("the end"))
+
+
+if __name__ == '__main__':
+    unittest.main()
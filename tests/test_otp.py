@@ -0,0 +1,34 @@
+"""Tests for finding one-time codes in incoming messages."""
+
+import unittest
+
+from src.utils.otp import find_code, find_incoming_code
+
+
+class FindCodeTests(unittest.TestCase):
+
+    def test_common_formats(self):
+        self.assertEqual(find_code("Your verification code is 482913."), '482913')
+        self.assertEqual(find_code("G-123456 is your Google verification code."), '123456')
+        self.assertEqual(find_code("Your code: 123 456"), '123456')
+        self.assertEqual(find_code("Use 4821 as your login PIN"), '4821')
+        self.assertEqual(find_code("验证码：654321"), '654321')
+
+    def test_picks_number_nearest_keyword(self):
+        self.assertEqual(find_code("Your Uber code: 1234. Reply STOP to 12345"), '1234')
+
+    def test_ignores_messages_without_codes(self):
+        self.assertIsNone(find_code("Call me at 555-1234"))
+        self.assertIsNone(find_code("Meet at 10:30, code word is banana"))
+        self.assertIsNone(find_code("Security review on 2024-05-01"))
+        self.assertIsNone(find_code(None))
+
+    def test_only_received_sms(self):
+        text = "Your code is 482913"
+        self.assertEqual(find_incoming_code(text, ['SMS'], False), '482913')
+        self.assertIsNone(find_incoming_code(text, ['SMS'], True))
+        self.assertIsNone(find_incoming_code(text, ['iMessage'], False))
+
+
+if __name__ == '__main__':
+    unittest.main()
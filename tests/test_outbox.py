@@ -0,0 +1,93 @@
+"""Tests for sending queued messages in order per chat."""
+
+import threading
+import unittest
+
+from src.utils.outbox import RETRY_DELAYS, Outbox, send_with_retries
+
+
+class Result:
+    def __init__(self, ok: bool, retryable: bool = False):
+        self.ok = ok
+        self.retryable = retryable
+
+    def __bool__(self):
+        return self.ok
+
+
+class SendWithRetriesTests(unittest.TestCase):
+
+    def test_retries_passing_failures_until_sent(self):
+        results = [Result(False, retryable=True), Result(False, retryable=True), Result(True)]
+        delays = []
+        result = send_with_retries(lambda: results.pop(0), delays.append)
+        self.assertTrue(result)
+        self.assertEqual(delays, RETRY_DELAYS[:2])
+
+    def test_lasting_failure_is_not_retried(self):
+        calls = []
+        result = send_with_retries(lambda: calls.append(1) or Result(False), lambda delay: None)
+        self.assertFalse(result)
+        self.assertEqual(len(calls), 1)
+
+    def test_gives_up_after_the_last_retry(self):
+        calls = []
+        send_with_retries(lambda: calls.append(1) or Result(False, retryable=True), lambda delay: None)
+        self.assertEqual(len(calls), len(RETRY_DELAYS) + 1)
+
+
+class OutboxTests(unittest.TestCase):
+
+    def test_same_chat_waits_for_earlier_sends(self):
+        workers = []
+        outbox = Outbox(start=workers.append)
+        sent = []
+        for text in ('first', 'second', 'third'):
+            outbox.submit('chat-a', lambda text=text: sent.append(text) or text)
+        self.assertEqual(len(workers), 1)  # One worker per chat
+        self.assertEqual(outbox.pending('chat-a'), 3)
+
+        workers[0]()
+        self.assertEqual(sent, ['first', 'second', 'third'])
+        self.assertEqual(outbox.pending('chat-a'), 0)
+
+    def test_chats_send_independently(self):
+        # chat-a's send blocks until chat-b's has gone
+        chat_b_sent = threading.Event()
+        done = threading.Event()
+        outbox = Outbox()
+        outbox.submit('chat-a', lambda: chat_b_sent.wait(5), lambda result, error: done.set())
+        outbox.submit('chat-b', chat_b_sent.set)
+        self.assertTrue(done.wait(5))
+
+    def test_on_done_gets_the_result(self):
+        results = []
+        outbox = Outbox(start=lambda target: target())
+        outbox.submit('chat-a', lambda: 'guid-1', lambda *outcome: results.append(outcome))
+        outbox.submit('chat-a', lambda: 'guid-2', lambda *outcome: results.append(outcome))
+        self.assertEqual(results, [('guid-1', None), ('guid-2', None)])
+
+    def test_failing_job_doesnt_stop_the_queue(self):
+        workers = []
+        outbox = Outbox(start=workers.append)
+        results = []
+        outbox.submit('chat-a', lambda: 1 / 0, lambda *outcome: results.append(outcome))
+        outbox.submit('chat-a', lambda: 'sent', lambda *outcome: results.append(outcome))
+        workers[0]()
+        result, error = results[0]
+        self.assertIsNone(result)
+        self.assertIsInstance(error, ZeroDivisionError)
+        self.assertEqual(results[1], ('sent', None))
+
+    def test_wait_until_empty(self):
+        release = threading.Event()
+        outbox = Outbox()
+        self.assertTrue(outbox.wait_until_empty(0))
+        outbox.submit('chat-a', lambda: release.wait(5))
+        self.assertFalse(outbox.wait_until_empty(0.05))  # Gives up rather than hang
+        release.set()
+        self.assertTrue(outbox.wait_until_empty(5))
+
+
+if __name__ == '__main__':
+    unittest.main()
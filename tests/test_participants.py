@@ -0,0 +1,84 @@
+"""Tests for the participants list in chat details."""
+
+import unittest
+
+from src.db.models import ChatRecord, HandleRecord
+from src.utils.participants import (
+    build_contact_card, group_contacts, group_participants, match_contacts, summarize_chat
+)
+from src.utils.phone_format import format_phone_number
+
+
+class PhoneFormatTests(unittest.TestCase):
+
+    def test_north_american_numbers(self):
+        self.assertEqual(format_phone_number('+15551234567'), "+1 (555) 123-4567")
+        self.assertEqual(format_phone_number('5551234567', 'us'), "+1 (555) 123-4567")
+
+    def test_international_numbers(self):
+        self.assertEqual(format_phone_number('+442079460958'), "+44 207 946 0958")
+        self.assertEqual(format_phone_number('+393471234567'), "+39 347 123 4567")
+        self.assertEqual(format_phone_number('+35312345678'), "+353 1234 5678")
+
+    def test_other_handles_are_unchanged(self):
+        self.assertEqual(format_phone_number('someone@icloud.com'), 'someone@icloud.com')
+        self.assertEqual(format_phone_number('262966'), '262966')
+        self.assertEqual(format_phone_number('0612345678', 'nl'), '0612345678')
+
+
+class GroupParticipantsTests(unittest.TestCase):
+
+    def chat(self, guid, *addresses):
+        participants = [HandleRecord(i, address) for i, address in enumerate(addresses)]
+        return ChatRecord(1, guid, addresses[0], 43, participants=participants)
+
+    def test_handles_merge_across_services(self):
+        chat = self.chat('iMessage;-;+15551234567', '+15551234567')
+        chat.linked_chats = [self.chat('SMS;-;+15551234567', '+1 555 123 4567')]
+        participants = group_participants(chat, lambda address: None)
+        self.assertEqual(len(participants), 1)
+        self.assertEqual(participants[0].handles[0].services, ['iMessage', 'SMS'])
+
+    def test_handles_of_one_contact_are_one_person(self):
+        chat = self.chat('iMessage;+;chat1', '+15551234567', 'ann@icloud.com', '+15557654321')
+        names = {'+15551234567': "Ann", 'ann@icloud.com': "Ann"}
+        participants = group_participants(chat, names.get)
+        self.assertEqual([(p.name, len(p.handles)) for p in participants], [("Ann", 2), (None, 1)])
+
+    def test_contact_card(self):
+        chat = self.chat('iMessage;+;chat1', '+15551234567', 'ann@icloud.com')
+        participant = group_participants(chat, lambda address: "Ann; Jr")[0]
+        card = build_contact_card(participant)
+        self.assertIn("FN:Ann\\; Jr\r\n", card)
+        self.assertIn("TEL:+15551234567\r\n", card)
+        self.assertIn("EMAIL:ann@icloud.com\r\n", card)
+
+    def test_summary(self):
+        group = self.chat('iMessage;+;chat1', '+15551234567', 'ann@icloud.com', '+15557654321')
+        names = {'+15551234567': "Ann", 'ann@icloud.com': "Ann"}
+        self.assertEqual(summarize_chat(group, names.get), "2 people")
+
+        chat = self.chat('iMessage;-;+15551234567', '+15551234567')
+        chat.linked_chats = [self.chat('SMS;-;+15551234567', '+15551234567')]
+        self.assertEqual(summarize_chat(chat, names.get), "iMessage · SMS")
+
+
+class ShareContactTests(unittest.TestCase):
+
+    def setUp(self):
+        self.contacts = group_contacts({
+            '+15551234567': "Bob", 'ann@icloud.com': "ann", '+15557654321': "ann",
+        })
+
+    def test_contacts_group_addresses_by_name(self):
+        self.assertEqual([(c.name, [h.address for h in c.handles]) for c in self.contacts],
+                         [("ann", ['+15557654321', 'ann@icloud.com']), ("Bob", ['+15551234567'])])
+
+    def test_match_by_name_or_address(self):
+        self.assertEqual([c.name for c in match_contacts(self.contacts, "BO")], ["Bob"])
+        self.assertEqual([c.name for c in match_contacts(self.contacts, "icloud")], ["ann"])
+        self.assertEqual(len(match_contacts(self.contacts, " ")), 2)
+
+
+if __name__ == '__main__':
+    unittest.main()
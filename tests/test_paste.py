@@ -0,0 +1,32 @@
+"""Tests for offering long pastes as text attachments."""
+
+import unittest
+
+from src.utils.paste import LONG_PASTE_CHARS, LONG_PASTE_LINES, is_long_paste, paste_file_name
+
+
+class LongPasteTests(unittest.TestCase):
+
+    def test_short_text_is_pasted(self):
+        self.assertFalse(is_long_paste("See you at 6"))
+        self.assertFalse(is_long_paste("x" * LONG_PASTE_CHARS))
+
+    def test_long_or_tall_text_is_offered_as_file(self):
+        self.assertTrue(is_long_paste("x" * (LONG_PASTE_CHARS + 1)))
+        self.assertTrue(is_long_paste("line\n" * LONG_PASTE_LINES))
+
+    def test_file_name_from_first_line(self):
+        text = "\n  Traceback (most recent call last):\n  File \"main.py\""
+        self.assertEqual(paste_file_name(text), "Traceback most recent call last.txt")
+
+    def test_file_name_is_shortened(self):
+        name = paste_file_name("word " * 50)
+        self.assertLessEqual(len(name), 40 + len(".txt"))
+        self.assertTrue(name.startswith("word word"))
+
+    def test_file_name_fallback(self):
+        self.assertEqual(paste_file_name("!!!\n???"), "Pasted Text.txt")
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,100 @@
+"""Tests that server payloads of every known shape deserialize into usable records."""
+
+import copy
+import json
+import random
+import unittest
+from pathlib import Path
+
+from tests.database_case import DatabaseTestCase
+
+
+# Real payload variants: server versions, SMS vs iMessage, group events
+FIXTURES = json.loads((Path(__file__).parent / 'fixtures' / 'server_payloads.json').read_text(encoding='utf-8'))
+
+CHAT_GUID = 'iMessage;+;chat123'
+
+# Values of the wrong type a server or proxy might send in place of any field
+JUNK_VALUES = [None, '', 0, -1, '1', 'true', [], {}, 1.5, 'not a date']
+
+
+def variants(payload):
+    """Yield (description, payload) with each field missing, null or of a junk type."""
+    rng = random.Random(payload['guid'])
+    for key in payload:
+        if key == 'guid':
+            continue
+        dropped = copy.deepcopy(payload)
+        del dropped[key]
+        yield f"without {key}", dropped
+
+        for value in [None] + rng.sample(JUNK_VALUES[1:], 3):
+            changed = copy.deepcopy(payload)
+            changed[key] = value
+            yield f"{key}={value!r}", changed
+
+
+class PayloadToleranceTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        self.db.save_chat({'originalROWID': 1, 'guid': CHAT_GUID, 'chatIdentifier': 'chat123'})
+
+    def assert_usable_chat(self, guid):
+        chat = self.db.get_chat_by_guid(guid)
+        self.assertIsNotNone(chat)
+        self.assertIsInstance(chat.display_title, str)
+        self.assertIsInstance(chat.style, int)
+        self.assertIn(chat.is_archived, (True, False))
+        for participant in chat.participants:
+            self.assertIsInstance(participant.address, str)
+        if not chat.is_archived:
+            self.assertIn(guid, [c.guid for c in self.db.get_chats()])
+
+    def assert_usable_message(self, guid):
+        messages = {m.guid: m for m in self.db.get_chat_messages(CHAT_GUID, limit=100)}
+        self.assertIn(guid, messages)
+        message = messages[guid]
+        self.assertIsInstance(message.date_created, int)
+        # SQLite hands flags back as 0 or 1
+        self.assertIn(message.is_from_me, (True, False))
+        self.assertIsInstance(message.item_type, int)
+        self.assertIsInstance(message.attachments, list)
+        self.assertTrue(message.text is None or isinstance(message.text, str))
+
+    def test_chat_fixtures(self):
+        for name, payload in FIXTURES['chats'].items():
+            with self.subTest(name):
+                self.db.save_chat(payload)
+                self.assert_usable_chat(payload['guid'])
+
+    def test_message_fixtures(self):
+        for name, payload in FIXTURES['messages'].items():
+            with self.subTest(name):
+                self.db.save_message(payload, CHAT_GUID)
+                self.assert_usable_message(payload['guid'])
+
+    def test_chat_field_variants(self):
+        for name, payload in FIXTURES['chats'].items():
+            for description, variant in variants(payload):
+                with self.subTest(f"{name} {description}"):
+                    self.db.save_chat(variant)
+                    self.assert_usable_chat(variant['guid'])
+
+    def test_message_field_variants(self):
+        for name, payload in FIXTURES['messages'].items():
+            for description, variant in variants(payload):
+                with self.subTest(f"{name} {description}"):
+                    self.db.save_messages([variant], CHAT_GUID)
+                    self.assert_usable_message(variant['guid'])
+
+    def test_resaving_keeps_placeholder_rowid(self):
+        payload = FIXTURES['messages']['minimal']
+        self.db.save_message(payload, CHAT_GUID)
+        self.db.save_message(payload, CHAT_GUID)
+        guids = [m.guid for m in self.db.get_chat_messages(CHAT_GUID)]
+        self.assertEqual(guids.count(payload['guid']), 1)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,68 @@
+"""Tests for the pending action queue behind undo toasts."""
+
+import unittest
+
+from src.utils.pending_actions import PendingActionQueue
+
+
+class FakeTimers:
+    """Timers fired by hand instead of by a main loop."""
+
+    def __init__(self):
+        self.timers = {}
+        self.next_id = 1
+
+    def schedule(self, seconds, callback):
+        timer_id = self.next_id
+        self.next_id += 1
+        self.timers[timer_id] = callback
+        return timer_id
+
+    def cancel(self, timer_id):
+        del self.timers[timer_id]
+
+    def fire_all(self):
+        for timer_id in list(self.timers):
+            self.timers.pop(timer_id)()
+
+
+class PendingActionQueueTests(unittest.TestCase):
+
+    def setUp(self):
+        self.timers = FakeTimers()
+        self.queue = PendingActionQueue(self.timers.schedule, self.timers.cancel)
+        self.log = []
+
+    def add(self, name):
+        return self.queue.add(name, lambda: self.log.append(('commit', name)),
+                              lambda: self.log.append(('revert', name)))
+
+    def test_action_runs_after_timeout(self):
+        self.add('a')
+        self.assertEqual(self.log, [])
+        self.timers.fire_all()
+        self.assertEqual(self.log, [('commit', 'a')])
+        self.assertEqual(len(self.queue), 0)
+
+    def test_undo_reverts_without_committing(self):
+        action_id = self.add('a')
+        self.assertTrue(self.queue.undo(action_id))
+        self.timers.fire_all()
+        self.assertEqual(self.log, [('revert', 'a')])
+
+    def test_undo_after_commit_does_nothing(self):
+        action_id = self.add('a')
+        self.timers.fire_all()
+        self.assertFalse(self.queue.undo(action_id))
+        self.assertEqual(self.log, [('commit', 'a')])
+
+    def test_flush_commits_in_order(self):
+        self.add('a')
+        self.add('b')
+        self.queue.flush()
+        self.assertEqual(self.log, [('commit', 'a'), ('commit', 'b')])
+        self.assertEqual(self.timers.timers, {})
+
+
+if __name__ == '__main__':
+    unittest.main()
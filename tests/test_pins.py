@@ -0,0 +1,34 @@
+"""Tests for pinned chat ordering."""
+
+import unittest
+
+from tests.database_case import DatabaseTestCase
+
+
+class PinOrderTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        for chat_guid in ('chat-a', 'chat-b', 'chat-c'):
+            self.db.pin_chat(chat_guid)
+
+    def test_move_up(self):
+        self.db.move_pin('chat-c', 0)
+        self.assertEqual(self.db.get_pinned_chat_guids(), ['chat-c', 'chat-a', 'chat-b'])
+
+    def test_move_down(self):
+        self.db.move_pin('chat-a', 2)
+        self.assertEqual(self.db.get_pinned_chat_guids(), ['chat-b', 'chat-c', 'chat-a'])
+
+    def test_new_pins_go_last_after_reordering(self):
+        self.db.move_pin('chat-c', 0)
+        self.db.pin_chat('chat-d')
+        self.assertEqual(self.db.get_pinned_chat_guids()[-1], 'chat-d')
+
+    def test_unpinned_chat_is_ignored(self):
+        self.db.move_pin('chat-z', 0)
+        self.assertEqual(self.db.get_pinned_chat_guids(), ['chat-a', 'chat-b', 'chat-c'])
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,29 @@
+"""Tests for per-chat privacy overrides."""
+
+import unittest
+
+from src.utils.privacy import OVERRIDE_CHOICES, apply_override, override_labels, parse_override, store_override
+
+
+class PrivacyOverrideTests(unittest.TestCase):
+
+    def test_no_override_follows_default(self):
+        self.assertTrue(apply_override(True, None))
+        self.assertFalse(apply_override(False, None))
+
+    def test_override_wins(self):
+        self.assertFalse(apply_override(True, False))
+        self.assertTrue(apply_override(False, True))
+
+    def test_stored_values_round_trip(self):
+        for override in OVERRIDE_CHOICES:
+            self.assertEqual(parse_override(store_override(override)), override)
+
+    def test_labels_match_choices(self):
+        labels = override_labels(False)
+        self.assertEqual(len(labels), len(OVERRIDE_CHOICES))
+        self.assertEqual(labels[0], "Default (Off)")
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,37 @@
+"""Tests for applying reads made on other devices to the cache."""
+
+import unittest
+
+from tests.database_case import DatabaseTestCase
+
+
+CHAT_GUID = 'iMessage;-;+15551234567'
+
+
+class MarkChatReadUntilTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        self.db.save_chat({'originalROWID': 1, 'guid': CHAT_GUID, 'chatIdentifier': '+15551234567'})
+        self.db.save_messages([
+            {'originalROWID': rowid, 'guid': f'msg-{rowid}', 'text': 'hi', 'dateCreated': 1709294400000 + rowid,
+             'isFromMe': rowid == 2}
+            for rowid in range(1, 5)
+        ], CHAT_GUID)
+
+    def unread_count(self) -> int:
+        return self.db.get_chat_by_guid(CHAT_GUID).unread_count
+
+    def test_marks_incoming_messages_up_to_the_read_one(self):
+        self.assertEqual(self.unread_count(), 3)
+        self.assertEqual(self.db.mark_chat_read_until(CHAT_GUID, 1709294400003, 1709294500000), 2)
+        self.assertEqual(self.unread_count(), 1)
+
+    def test_already_read_messages_are_not_counted(self):
+        self.db.mark_chat_read_until(CHAT_GUID, 1709294400004, 1709294500000)
+        self.assertEqual(self.db.mark_chat_read_until(CHAT_GUID, 1709294400004, 1709294600000), 0)
+        self.assertEqual(self.unread_count(), 0)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,25 @@
+"""Tests for message reminder storage."""
+
+import unittest
+
+from tests.database_case import DatabaseTestCase
+
+
+class ReminderStorageTests(DatabaseTestCase):
+
+    def test_reminders_come_soonest_first(self):
+        later = self.db.add_reminder('chat-1', 'message-1', "Call back", 2000)
+        sooner = self.db.add_reminder('chat-2', 'message-2', None, 1000)
+        self.assertEqual([r['id'] for r in self.db.get_reminders()], [sooner, later])
+        self.assertEqual(self.db.get_reminders()[1]['message_text'], "Call back")
+
+    def test_reminders_survive_clearing_the_cache(self):
+        reminder_id = self.db.add_reminder('chat-1', 'message-1', "Call back", 1000)
+        self.db.clear_cache()
+        self.assertEqual(len(self.db.get_reminders()), 1)
+        self.db.delete_reminder(reminder_id)
+        self.assertEqual(self.db.get_reminders(), [])
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,64 @@
+"""Tests for reflecting messages and chats deleted on the server in the cache."""
+
+import unittest
+
+from tests.database_case import DatabaseTestCase
+
+
+CHAT_GUID = 'iMessage;-;+15551234567'
+
+
+def message(rowid: int, **fields):
+    data = {'originalROWID': rowid, 'guid': f'msg-{rowid}', 'text': f'text {rowid}',
+            'dateCreated': 1709294400000 + rowid}
+    data.update(fields)
+    return data
+
+
+class RemoteDeletionTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        self.db.save_chat({'originalROWID': 1, 'guid': CHAT_GUID, 'chatIdentifier': '+15551234567'})
+        self.db.save_messages([
+            message(1), message(2, attachments=[{'guid': 'att-2'}]), message(3), message(4)
+        ], CHAT_GUID)
+
+    def cached_guids(self):
+        return [m.guid for m in self.db.get_chat_messages(CHAT_GUID)]
+
+    def test_unsent_message_becomes_tombstone(self):
+        self.db.save_message(message(3, text=None, dateRetracted=1709294500000), CHAT_GUID)
+
+        unsent = self.db.get_chat_messages(CHAT_GUID)[2]
+        self.assertEqual(unsent.date_retracted, 1709294500000)
+        self.assertIsNone(unsent.text)
+        self.assertEqual(self.db.get_retracted_message_guids(['msg-3', 'msg-4']), {'msg-3'})
+
+    def test_messages_missing_from_fetched_range_are_deleted(self):
+        deleted, attachment_guids = self.db.delete_missing_messages(CHAT_GUID, [message(4), message(1)])
+        self.assertEqual(deleted, 2)
+        self.assertEqual(attachment_guids, ['att-2'])
+        self.assertEqual(self.cached_guids(), ['msg-1', 'msg-4'])
+
+    def test_messages_outside_fetched_range_are_kept(self):
+        deleted, _ = self.db.delete_missing_messages(CHAT_GUID, [message(4), message(3)])
+        self.assertEqual(deleted, 0)
+        self.assertEqual(len(self.cached_guids()), 4)
+
+    def test_delete_chat(self):
+        self.assertEqual(self.db.delete_chat(CHAT_GUID), ['att-2'])
+        self.assertIsNone(self.db.get_chat_by_guid(CHAT_GUID))
+        self.assertEqual(self.db.get_chat_guids(), [])
+        self.assertEqual(self.cached_guids(), [])
+
+    def test_delete_chat_keeps_user_settings(self):
+        self.db.pin_chat(CHAT_GUID)
+        self.db.set_chat_setting(CHAT_GUID, 'starred', '1')
+        self.db.delete_chat(CHAT_GUID)
+        self.assertEqual(self.db.get_pinned_chat_guids(), [CHAT_GUID])
+        self.assertEqual(list(self.db.get_chats_with_setting('starred')), [CHAT_GUID])
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,57 @@
+"""Tests for caching inline replies and the threads they form."""
+
+import unittest
+
+from tests.database_case import DatabaseTestCase
+
+
+CHAT_GUID = 'iMessage;-;+15551234567'
+
+
+class ReplyQuoteTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        self.db.save_chat({'guid': CHAT_GUID, 'originalROWID': 1, 'chatIdentifier': '+15551234567', 'style': 45})
+        self.db.save_messages([
+            {'guid': 'msg-1', 'originalROWID': 1, 'text': 'Dinner at 7?', 'dateCreated': 1709294400000},
+            {'guid': 'msg-2', 'originalROWID': 2, 'text': 'Sounds good', 'dateCreated': 1709294460000,
+             'isFromMe': True, 'threadOriginatorGuid': 'msg-1'},
+        ], CHAT_GUID)
+
+    def test_reply_keeps_its_original(self):
+        messages = {m.guid: m for m in self.db.get_chat_messages(CHAT_GUID)}
+        self.assertEqual(messages['msg-2'].thread_originator_guid, 'msg-1')
+        self.assertIsNone(messages['msg-1'].thread_originator_guid)
+
+    def test_get_message(self):
+        original = self.db.get_message('msg-1')
+        self.assertEqual(original.text, 'Dinner at 7?')
+        self.assertEqual(original.date_created, 1709294400000)
+        self.assertIsNone(self.db.get_message('msg-9'))
+
+    def test_hidden_message_is_not_found(self):
+        self.db.hide_message('msg-1')
+        self.assertIsNone(self.db.get_message('msg-1'))
+
+    def test_thread_has_originator_and_replies(self):
+        self.db.save_messages([
+            {'guid': 'msg-3', 'originalROWID': 3, 'text': 'Unrelated', 'dateCreated': 1709294520000},
+            {'guid': 'msg-4', 'originalROWID': 4, 'text': 'Make it 8', 'dateCreated': 1709294580000,
+             'threadOriginatorGuid': 'msg-1'},
+            {'guid': 'msg-5', 'originalROWID': 5, 'text': 'Loved "Make it 8"', 'dateCreated': 1709294640000,
+             'associatedMessageGuid': 'p:0/msg-4', 'associatedMessageType': 'love',
+             'threadOriginatorGuid': 'msg-1'},
+        ], CHAT_GUID)
+        thread = self.db.get_thread_messages('msg-1')
+        self.assertEqual([m.guid for m in thread], ['msg-1', 'msg-2', 'msg-4'])
+
+    def test_clearing_a_chat_forgets_its_replies(self):
+        self.db.delete_chat_messages(CHAT_GUID)
+        self.db.save_messages([{'guid': 'msg-2', 'originalROWID': 2, 'text': 'Sounds good',
+                                'dateCreated': 1709294460000}], CHAT_GUID)
+        self.assertIsNone(self.db.get_message('msg-2').thread_originator_guid)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,44 @@
+"""Tests for reading how long the server asks us to back off after a 429."""
+
+import unittest
+from datetime import datetime, timedelta, timezone
+from email.utils import format_datetime
+
+from tests.fake_aiohttp import ClientTestCase
+
+
+class ParseRetryAfterTests(ClientTestCase):
+
+    def parse(self, value):
+        return self.client_module.parse_retry_after(value)
+
+    def test_seconds(self):
+        self.assertEqual(self.parse('120'), 120)
+        self.assertEqual(self.parse('1.5'), 1.5)
+
+    def test_http_date(self):
+        retry_at = datetime.now(timezone.utc) + timedelta(minutes=2)
+        self.assertAlmostEqual(self.parse(format_datetime(retry_at, usegmt=True)), 120, delta=2)
+
+    def test_past_dates_and_negative_seconds_mean_now(self):
+        self.assertEqual(self.parse('Wed, 21 Oct 2015 07:28:00 GMT'), 0)
+        self.assertEqual(self.parse('-5'), 0)
+
+    def test_missing_or_garbage_uses_the_default(self):
+        for value in (None, '', 'soon', '12 parsecs'):
+            with self.subTest(value=value):
+                self.assertEqual(self.parse(value), self.client_module.DEFAULT_RETRY_AFTER)
+
+    def test_non_finite_values_use_the_default(self):
+        for value in ('inf', '-inf', 'nan', 'Infinity'):
+            with self.subTest(value=value):
+                self.assertEqual(self.parse(value), self.client_module.DEFAULT_RETRY_AFTER)
+
+    def test_long_delays_are_capped(self):
+        self.assertEqual(self.parse('1e12'), self.client_module.MAX_RETRY_AFTER)
+        retry_at = datetime.now(timezone.utc) + timedelta(days=30)
+        self.assertEqual(self.parse(format_datetime(retry_at, usegmt=True)), self.client_module.MAX_RETRY_AFTER)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,88 @@
+"""Tests for the filters typed into search fields, and the ranking of what they find."""
+
+import unittest
+from datetime import date
+
+from src.utils.search_query import (
+    SENDER_ME, SearchHit, find_word_window, has_filter, make_snippet, parse_search_query, rank_hits,
+    remove_filter, search_words
+)
+
+
+class SearchQueryTests(unittest.TestCase):
+
+    def test_filters_are_split_from_the_text(self):
+        query = parse_search_query('lunch from:me in:"Book Club" has:attachment friday')
+        self.assertEqual(query.text, 'lunch friday')
+        self.assertEqual(query.sender, SENDER_ME)
+        self.assertEqual(query.chat, 'book club')
+        self.assertTrue(query.has_attachment)
+        self.assertTrue(query.filters_messages)
+
+    def test_attachment_kinds(self):
+        query = parse_search_query('has:PDF')
+        self.assertEqual((query.attachment_kind, query.attachment_mime_prefixes), ('pdf', ('application/pdf',)))
+        self.assertEqual(parse_search_query('has:attachment').attachment_mime_prefixes, ())
+        self.assertEqual(parse_search_query('has:links').text, 'has:links')
+
+    def test_dates_bound_the_search(self):
+        query = parse_search_query('after:2024-03-01 before:2024-04-01')
+        self.assertEqual((query.after, query.before), (date(2024, 3, 1), date(2024, 4, 1)))
+        self.assertEqual(query.text, '')
+        self.assertLess(query.after_ms, query.before_ms)
+
+    def test_unfinished_filters(self):
+        # Just picked from a chip: no value yet, so nothing to filter on
+        query = parse_search_query('lunch from:')
+        self.assertEqual((query.text, query.sender), ('lunch', None))
+        # A date being typed matches nothing rather than everything
+        query = parse_search_query('before:2024-0')
+        self.assertEqual((query.text, query.before), ('before:2024-0', None))
+
+    def test_in_alone_only_names_conversations(self):
+        query = parse_search_query('IN:Family')
+        self.assertEqual(query.chat, 'family')
+        self.assertFalse(query.filters_messages)
+        self.assertTrue(query)
+        self.assertFalse(parse_search_query('   '))
+
+    def test_filters_are_found_and_removed(self):
+        text = 'lunch in:"Book Club" from:'
+        self.assertTrue(has_filter(text, 'from'))
+        self.assertFalse(has_filter(text, 'has'))
+        self.assertEqual(remove_filter(text, 'in'), 'lunch from:')
+        self.assertEqual(remove_filter('email:me@example.com', 'from'), 'email:me@example.com')
+
+
+class SearchRankingTests(unittest.TestCase):
+
+    NOW = 1709294400000
+    DAY = 86400000
+
+    def test_word_window_is_the_closest_pair(self):
+        text = "Lunch on Monday? No, lunch friday"
+        self.assertEqual(find_word_window(text, ['lunch', 'friday']), (21, 33))
+        self.assertIsNone(find_word_window(text, ['lunch', 'dinner']))
+
+    def test_close_words_outrank_scattered_ones(self):
+        scattered = SearchHit('chat-a', 'm0', self.NOW, "Friday works, and lunch is on me")
+        together = SearchHit('chat-b', 'm1', self.NOW - self.DAY, "Lunch friday?")
+        words = search_words("lunch Friday")
+        self.assertEqual([hit.guid for hit in rank_hits([scattered, together], words, self.NOW)], ['m1', 'm0'])
+
+    def test_recent_messages_outrank_old_ones(self):
+        old = SearchHit('chat-a', 'm0', self.NOW - 365 * self.DAY, "lunch")
+        new = SearchHit('chat-a', 'm1', self.NOW - self.DAY, "lunch")
+        self.assertEqual([hit.guid for hit in rank_hits([old, new], ['lunch'], self.NOW)], ['m1', 'm0'])
+
+    def test_snippet_centers_and_marks_the_words(self):
+        text = "a" * 100 + " Lunch at noon " + "b" * 100
+        parts = make_snippet(text, ['lunch'], width=40)
+        self.assertEqual(parts[0], ('…', False))
+        self.assertEqual(parts[-1], ('…', False))
+        self.assertIn(('Lunch', True), parts)
+        self.assertEqual(make_snippet("short\ntext", []), [("short text", False)])
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,53 @@
+"""Tests for keeping chats out of the on-disk cache."""
+
+import threading
+import unittest
+
+from src.db.manager import DatabaseManager
+from tests.database_case import DatabaseTestCase
+
+CHAT_GUID = 'iMessage;+;chat123'
+
+
+def save_chat_with_attachment(db: DatabaseManager):
+    """Cache a chat with one message carrying an attachment."""
+    db.save_chat({'originalROWID': 1, 'guid': CHAT_GUID, 'chatIdentifier': 'chat123'})
+    db.save_message({
+        'originalROWID': 10, 'guid': 'msg-10', 'text': 'photo', 'dateCreated': 1709294400000,
+        'attachments': [{'guid': 'att-1', 'mimeType': 'image/jpeg'}],
+    }, CHAT_GUID)
+
+
+class DeleteChatMessagesTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        save_chat_with_attachment(self.db)
+
+    def test_returns_attachment_guids(self):
+        self.assertTrue(self.db.has_attachment('att-1'))
+        self.assertEqual(self.db.delete_chat_messages(CHAT_GUID), ['att-1'])
+        self.assertEqual(self.db.get_chat_messages(CHAT_GUID), [])
+        self.assertFalse(self.db.has_attachment('att-1'))
+
+    def test_forgets_import_progress(self):
+        self.db.save_history_import_state(CHAT_GUID, 200)
+        self.db.delete_chat_messages(CHAT_GUID)
+        self.assertEqual(self.db.get_history_import_state(CHAT_GUID), (0, False))
+
+
+class SessionDatabaseTests(unittest.TestCase):
+
+    def test_shared_between_threads(self):
+        db = DatabaseManager('file:test-session?mode=memory&cache=shared')
+        save_chat_with_attachment(db)
+
+        found = []
+        thread = threading.Thread(target=lambda: found.append(db.has_attachment('att-1')))
+        thread.start()
+        thread.join()
+        self.assertEqual(found, [True])
+
+
+if __name__ == '__main__':
+    unittest.main()
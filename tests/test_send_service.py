@@ -0,0 +1,31 @@
+"""Tests for picking the service a merged conversation sends on."""
+
+import unittest
+
+from src.db.models import ChatRecord
+from src.utils.chat_merge import merge_duplicate_chats, pick_send_chat
+
+
+def chat(guid: str) -> ChatRecord:
+    return ChatRecord(original_rowid=1, guid=guid, chat_identifier=guid.split(';')[-1], style=45)
+
+
+class PickSendChatTests(unittest.TestCase):
+
+    def setUp(self):
+        self.conversation = merge_duplicate_chats([chat('SMS;-;+15551234567'), chat('iMessage;-;+15551234567')])[0]
+
+    def test_auto_sends_on_primary_chat(self):
+        self.assertEqual(pick_send_chat(self.conversation, None), 'iMessage;-;+15551234567')
+
+    def test_forced_service(self):
+        self.assertEqual(pick_send_chat(self.conversation, 'SMS'), 'SMS;-;+15551234567')
+        self.assertEqual(pick_send_chat(self.conversation, 'iMessage'), 'iMessage;-;+15551234567')
+
+    def test_unavailable_service_falls_back_to_primary(self):
+        single = chat('iMessage;-;+15559876543')
+        self.assertEqual(pick_send_chat(single, 'SMS'), single.guid)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,77 @@
+"""Tests for decoding live events pushed by the server."""
+
+import unittest
+
+from src.api.events import (
+    RECONNECT_BASE_DELAY, RECONNECT_JITTER, RECONNECT_MAX_DELAY, ChatRead, MessageUpdated, NewMessage,
+    TypingIndicator, UnknownEvent, decode_event_frame, parse_open_packet, parse_server_event, reconnect_delay
+)
+
+
+class EventFrameTests(unittest.TestCase):
+
+    def test_event_frame(self):
+        frame = '42["new-message",{"guid":"msg-1","chats":[{"guid":"iMessage;-;+15551234567"}]}]'
+        name, data = decode_event_frame(frame)
+        self.assertEqual(name, 'new-message')
+        self.assertEqual(data['guid'], 'msg-1')
+
+    def test_event_with_acknowledgement_id(self):
+        self.assertEqual(decode_event_frame('4212["typing-indicator",{}]'), ('typing-indicator', {}))
+
+    def test_other_frames_are_not_events(self):
+        for frame in ('2', '3', '0{"sid":"abc"}', '40', '42not json', '42[]', '42[1,2]'):
+            self.assertIsNone(decode_event_frame(frame), frame)
+
+
+class OpenPacketTests(unittest.TestCase):
+
+    def test_heartbeat_in_seconds(self):
+        frame = '0{"sid":"abc","upgrades":[],"pingInterval":25000,"pingTimeout":60000,"maxPayload":1000000}'
+        self.assertEqual(parse_open_packet(frame), (25, 60))
+
+    def test_missing_or_bad_values_use_defaults(self):
+        for frame in ('0', '0{"sid":"abc"}', '0not json', '0[]', '0{"pingInterval":"fast","pingTimeout":-1}'):
+            self.assertEqual(parse_open_packet(frame), (25, 20), frame)
+
+
+class ServerEventTests(unittest.TestCase):
+
+    def test_new_message_knows_its_chat(self):
+        event = parse_server_event('new-message', {'guid': 'msg-1', 'chats': [{'guid': 'SMS;-;+15551234567'}]})
+        self.assertIsInstance(event, NewMessage)
+        self.assertEqual(event.chat_guid, 'SMS;-;+15551234567')
+
+    def test_group_changes_are_new_messages(self):
+        self.assertIsInstance(parse_server_event('group-name-change', {'guid': 'msg-2'}), NewMessage)
+        self.assertIsNone(parse_server_event('participant-left', {'guid': 'msg-3'}).chat_guid)
+
+    def test_typed_payloads(self):
+        self.assertEqual(parse_server_event('typing-indicator', {'guid': 'chat-a', 'display': True}),
+                         TypingIndicator('chat-a', True))
+        self.assertEqual(parse_server_event('chat-read-status-changed', {'chatGuid': 'chat-a', 'read': False}),
+                         ChatRead('chat-a', False))
+        self.assertIsInstance(parse_server_event('updated-message', {'guid': 'msg-1'}), MessageUpdated)
+
+    def test_unknown_or_malformed_events(self):
+        self.assertIsInstance(parse_server_event('incoming-facetime', {'caller': 'x'}), UnknownEvent)
+        self.assertIsInstance(parse_server_event('typing-indicator', {'display': True}), UnknownEvent)
+        self.assertIsInstance(parse_server_event('new-message', 'not a message'), UnknownEvent)
+
+
+class ReconnectDelayTests(unittest.TestCase):
+
+    def test_doubles_up_to_the_maximum(self):
+        delays = [reconnect_delay(attempt, rand=lambda: 0) for attempt in range(10)]
+        self.assertEqual(delays[:3], [RECONNECT_BASE_DELAY, RECONNECT_BASE_DELAY * 2, RECONNECT_BASE_DELAY * 4])
+        self.assertEqual(delays[-1], RECONNECT_MAX_DELAY)
+        self.assertEqual(delays, sorted(delays))
+
+    def test_jitter_only_shortens_the_delay(self):
+        self.assertEqual(reconnect_delay(3, rand=lambda: 1), RECONNECT_BASE_DELAY * 8 * (1 - RECONNECT_JITTER))
+        for _ in range(100):
+            self.assertLessEqual(reconnect_delay(50), RECONNECT_MAX_DELAY)
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,36 @@
+"""Tests for counting SMS segments in the composer."""
+
+import unittest
+
+from src.utils.sms_segments import count_segments, describe_count
+
+
+class SegmentCountTests(unittest.TestCase):
+
+    def test_gsm_text_fits_160_per_text(self):
+        count = count_segments("a" * 160)
+        self.assertEqual((count.segments, count.unicode), (1, False))
+        self.assertEqual(describe_count(count), "160/160")
+
+    def test_long_gsm_text_splits_at_153(self):
+        self.assertEqual(count_segments("a" * 161).segments, 2)
+        self.assertEqual(count_segments("a" * 306).segments, 2)
+        self.assertEqual(describe_count(count_segments("a" * 307)), "307/459 · 3 texts")
+
+    def test_extended_characters_count_twice(self):
+        self.assertEqual(count_segments("€[]").length, 6)
+        self.assertFalse(count_segments("€").unicode)
+
+    def test_emoji_switch_to_unicode(self):
+        count = count_segments("hi 👋")
+        self.assertTrue(count.unicode)
+        self.assertEqual(count.length, 5)
+        self.assertEqual(describe_count(count), "5/70")
+        self.assertEqual(count_segments("é" * 70 + "ł").segments, 2)
+
+    def test_empty_text(self):
+        self.assertEqual(count_segments("").segments, 0)
+
+
+if __name__ == '__main__':
+    unittest.main()
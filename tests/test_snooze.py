@@ -0,0 +1,65 @@
+"""Tests for snooze choices and wake-up times."""
+
+import unittest
+from datetime import datetime
+
+from src.utils.snooze import describe_snooze, get_due_snoozes, get_snooze_options
+from src.utils.time_format import TimeFormat
+from tests.database_case import DatabaseTestCase
+
+
+class SnoozeOptionTests(unittest.TestCase):
+
+    def test_weekday_morning(self):
+        # A Wednesday
+        options = dict(get_snooze_options(datetime(2026, 10, 14, 9, 30)))
+        self.assertEqual(options["Later Today"], datetime(2026, 10, 14, 12, 30))
+        self.assertEqual(options["This Evening"], datetime(2026, 10, 14, 18, 0))
+        self.assertEqual(options["Tomorrow"], datetime(2026, 10, 15, 8, 0))
+        self.assertEqual(options["This Weekend"], datetime(2026, 10, 17, 8, 0))
+        self.assertEqual(options["Next Week"], datetime(2026, 10, 19, 8, 0))
+
+    def test_evening_has_no_evening_choice(self):
+        options = dict(get_snooze_options(datetime(2026, 10, 14, 17, 30)))
+        self.assertNotIn("This Evening", options)
+
+    def test_sunday_skips_next_week_when_it_is_tomorrow(self):
+        options = dict(get_snooze_options(datetime(2026, 10, 18, 10, 0)))
+        self.assertNotIn("This Weekend", options)
+        self.assertNotIn("Next Week", options)
+        self.assertEqual(options["Tomorrow"], datetime(2026, 10, 19, 8, 0))
+
+    def test_choices_are_in_the_future(self):
+        now = datetime(2026, 10, 16, 23, 59)
+        for label, until in get_snooze_options(now):
+            self.assertGreater(until, now, label)
+
+
+class SnoozeWakeTests(unittest.TestCase):
+
+    def test_due_snoozes(self):
+        snoozes = {'past': 1000, 'now': 2000, 'future': 3000}
+        self.assertEqual(sorted(get_due_snoozes(snoozes, 2000)), ['now', 'past'])
+
+    def test_description(self):
+        time_format = TimeFormat(clock='24h')
+        now = datetime(2026, 10, 14, 9, 30)
+        self.assertEqual(describe_snooze(datetime(2026, 10, 14, 18, 0), time_format, now),
+                         "Snoozed until 18:00")
+        self.assertEqual(describe_snooze(datetime(2026, 10, 15, 8, 0), time_format, now),
+                         "Snoozed until tomorrow 08:00")
+
+
+class SnoozeStorageTests(DatabaseTestCase):
+
+    def test_snoozes_survive_clearing_the_cache(self):
+        self.db.snooze_chat('chat-1', 1000)
+        self.db.snooze_chat('chat-1', 2000)
+        self.db.clear_cache()
+        self.assertEqual(self.db.get_snoozes(), {'chat-1': 2000})
+        self.db.unsnooze_chat('chat-1')
+        self.assertEqual(self.db.get_snoozes(), {})
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,88 @@
+"""Tests for measuring and shrinking the cache database."""
+
+import unittest
+from unittest import mock
+
+from src.db.manager import MESSAGE_ROW_OVERHEAD
+from tests.database_case import DatabaseTestCase
+
+CHAT_GUID = 'iMessage;+;chat123'
+
+
+class ChatStorageTests(DatabaseTestCase):
+
+    def setUp(self):
+        super().setUp()
+        self.db.save_chat({'originalROWID': 1, 'guid': CHAT_GUID, 'chatIdentifier': 'chat123'})
+
+    def save_messages(self, count: int, text: str = 'hello'):
+        for rowid in range(1, count + 1):
+            self.db.save_message({
+                'originalROWID': rowid, 'guid': f'msg-{rowid}', 'text': text,
+                'dateCreated': 1709294400000 + rowid,
+            }, CHAT_GUID)
+
+    def test_breakdown_per_chat(self):
+        self.save_messages(2)
+        self.db.save_message({
+            'originalROWID': 3, 'guid': 'msg-3', 'dateCreated': 1709294400003,
+            'attachments': [{'guid': 'att-1'}, {'guid': 'att-2'}],
+        }, CHAT_GUID)
+
+        usage = self.db.get_chat_storage()[CHAT_GUID]
+        self.assertEqual(usage['messages'], 3)
+        self.assertEqual(usage['attachment_guids'], ['att-1', 'att-2'])
+        self.assertGreater(usage['bytes'], 3 * MESSAGE_ROW_OVERHEAD)
+
+    def test_empty_cache_has_no_chats(self):
+        self.assertEqual(self.db.get_chat_storage(), {})
+
+    def test_vacuum_gives_space_back(self):
+        self.save_messages(300, text='x' * 1000)
+        self.db.delete_chat_messages(CHAT_GUID)
+        size_before = self.db.get_file_size()
+
+        self.db.vacuum()
+        self.assertLess(self.db.get_file_size(), size_before)
+        self.assertEqual(self.db.check_integrity(), 'ok')
+
+
+class AttachmentStorageTests(DatabaseTestCase):
+
+    def store(self, guid: str, size: int, used_at: int):
+        with mock.patch('src.db.manager.time.time', return_value=used_at):
+            self.db.record_stored_attachment(guid, f'{guid}.jpg', size, 'image/jpeg')
+
+    def test_under_the_limit_nothing_is_evicted(self):
+        self.store('att-1', 100, 1)
+        self.assertEqual(self.db.get_storage_evictions(100), [])
+
+    def test_least_recently_used_go_first(self):
+        self.store('att-1', 100, 1)
+        self.store('att-2', 100, 2)
+        self.store('att-3', 100, 3)
+        with mock.patch('src.db.manager.time.time', return_value=4):
+            self.db.touch_stored_attachment('att-1')
+
+        evictions = self.db.get_storage_evictions(150)
+        self.assertEqual([entry['guid'] for entry in evictions], ['att-2', 'att-3'])
+        self.assertEqual(evictions[0]['file_name'], 'att-2.jpg')
+
+    def test_kept_attachment_is_never_evicted(self):
+        self.store('att-1', 500, 1)
+        self.store('att-2', 50, 2)
+        evictions = self.db.get_storage_evictions(100, keep_guid='att-1')
+        self.assertEqual([entry['guid'] for entry in evictions], ['att-2'])
+
+    def test_removed_attachments_stop_counting(self):
+        self.store('att-1', 100, 1)
+        self.store('att-2', 100, 2)
+        self.db.remove_stored_attachment('att-1')
+        self.assertIsNone(self.db.get_stored_attachment('att-1'))
+        self.assertEqual(self.db.get_storage_evictions(100), [])
+        self.db.clear_storage()
+        self.assertIsNone(self.db.get_stored_attachment('att-2'))
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,56 @@
+"""Tests for building the Storage preferences page without a display."""
+
+import importlib
+import sys
+import types
+import unittest
+from unittest import mock
+
+
+class FakeWidget:
+    """Stands in for a GTK base class; every method call is recorded and ignored."""
+
+    def __init__(self, *args, **kwargs):
+        pass
+
+    def __getattr__(self, name):
+        return mock.MagicMock(name=name)
+
+
+def fake_gi_modules():
+    """Build gi modules that satisfy the page's imports."""
+    gi = types.ModuleType('gi')
+    gi.require_version = lambda namespace, version: None
+    repository = types.ModuleType('gi.repository')
+    adw = mock.MagicMock(name='Adw')
+    adw.PreferencesPage = FakeWidget
+    repository.Adw = adw
+    repository.Gtk = mock.MagicMock(name='Gtk')
+    repository.GLib = mock.MagicMock(name='GLib')
+    gi.repository = repository
+    return {'gi': gi, 'gi.repository': repository}
+
+
+class StoragePageTests(unittest.TestCase):
+
+    def setUp(self):
+        patcher = mock.patch.dict(sys.modules, fake_gi_modules())
+        patcher.start()
+        self.addCleanup(patcher.stop)
+        sys.modules.pop('src.ui.storage_page', None)
+        sys.modules.pop('src.ui.undo_toast', None)
+        self.storage_page = importlib.import_module('src.ui.storage_page')
+
+    def test_setup_reads_cache_limit(self):
+        application = mock.MagicMock()
+        application.config_manager.get_attachment_cache_limit.return_value = 512
+
+        with mock.patch.object(self.storage_page.StoragePage, 'refresh'):
+            page = self.storage_page.StoragePage(mock.MagicMock(), application)
+
+        application.config_manager.get_attachment_cache_limit.assert_called_once_with()
+        page.cache_limit_row.set_value.assert_called_once_with(512)
+
+
+if __name__ == '__main__':
+    unittest.main()
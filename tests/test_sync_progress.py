@@ -0,0 +1,30 @@
+"""Tests for describing sync progress."""
+
+import unittest
+
+from src.utils.sync_progress import SyncProgress
+
+
+class SyncProgressTests(unittest.TestCase):
+
+    def test_chat_progress(self):
+        progress = SyncProgress('chats', 120, 600)
+        self.assertEqual(progress.label, "Syncing chats 120/600")
+        self.assertAlmostEqual(progress.fraction, 0.2)
+
+    def test_message_progress_names_the_chat(self):
+        progress = SyncProgress('messages', 300, 1500, "Family")
+        self.assertEqual(progress.label, "Syncing messages for Family 300/1500")
+
+    def test_unknown_total(self):
+        progress = SyncProgress('messages', 200, None, "Family")
+        self.assertIsNone(progress.fraction)
+        self.assertEqual(progress.label, "Syncing messages for Family 200")
+
+    def test_fraction_never_passes_one(self):
+        # Messages arriving during a sync can push the count past the total
+        self.assertEqual(SyncProgress('messages', 210, 200).fraction, 1.0)
+
+
+if __name__ == '__main__':
+    unittest.main()
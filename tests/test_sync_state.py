@@ -0,0 +1,27 @@
+"""Tests for recording per-chat sync failures."""
+
+import unittest
+
+from tests.database_case import DatabaseTestCase
+
+
+class SyncStateTests(DatabaseTestCase):
+
+    def test_failures_are_recorded_per_chat(self):
+        self.db.record_chat_sync('chat-a')
+        self.db.record_chat_sync('chat-b', "HTTP 500: Unknown error")
+        self.assertEqual(self.db.get_chat_sync_errors(), {'chat-b': "HTTP 500: Unknown error"})
+
+    def test_success_clears_failure(self):
+        self.db.record_chat_sync('chat-a', "Network error: timeout")
+        self.db.record_chat_sync('chat-a')
+        self.assertEqual(self.db.get_chat_sync_errors(), {})
+
+    def test_clear_cache_forgets_failures(self):
+        self.db.record_chat_sync('chat-a', "Network error: timeout")
+        self.db.clear_cache()
+        self.assertEqual(self.db.get_chat_sync_errors(), {})
+
+
+if __name__ == '__main__':
+    unittest.main()
@@ -0,0 +1,132 @@
+"""Tests for server timestamp conversion and clock skew handling."""
+
+import unittest
+from datetime import datetime, timedelta, timezone
+
+from src.utils import timestamps
+from src.utils.time_format import TimeFormat, format_day, format_time
+from src.utils.timestamps import (
+    CLOCK_SKEW_THRESHOLD_MS, measure_clock_skew, set_clock_skew, to_datetime, to_unix_ms
+)
+
+# 2024-03-01 12:00:00 UTC in every format the server might send
+UNIX_MS = 1709294400000
+UNIX_SECONDS = UNIX_MS // 1000
+APPLE_SECONDS = UNIX_SECONDS - 978307200
+
+
+class ToUnixMsTests(unittest.TestCase):
+
+    def test_unix_milliseconds_pass_through(self):
+        self.assertEqual(to_unix_ms(UNIX_MS), UNIX_MS)
+
+    def test_unix_seconds(self):
+        self.assertEqual(to_unix_ms(UNIX_SECONDS), UNIX_MS)
+        self.assertEqual(to_unix_ms(float(UNIX_SECONDS)), UNIX_MS)
+
+    def test_unix_microseconds(self):
+        self.assertEqual(to_unix_ms(UNIX_MS * 1000), UNIX_MS)
+
+    def test_apple_seconds(self):
+        self.assertEqual(to_unix_ms(APPLE_SECONDS), UNIX_MS)
+
+    def test_apple_milliseconds(self):
+        self.assertEqual(to_unix_ms(APPLE_SECONDS * 1000), UNIX_MS)
+
+    def test_apple_nanoseconds(self):
+        self.assertEqual(to_unix_ms(APPLE_SECONDS * 10**9), UNIX_MS)
+        # Sub-millisecond precision is dropped, not rounded into the next ms
+        self.assertEqual(to_unix_ms(APPLE_SECONDS * 10**9 + 999999), UNIX_MS)
+
+    def test_numeric_strings(self):
+        self.assertEqual(to_unix_ms(str(UNIX_MS)), UNIX_MS)
+        self.assertEqual(to_unix_ms(f" {APPLE_SECONDS * 10**9} "), UNIX_MS)
+
+    def test_iso_strings(self):
+        self.assertEqual(to_unix_ms("2024-03-01T12:00:00Z"), UNIX_MS)
+        self.assertEqual(to_unix_ms("2024-03-01T12:00:00.000+00:00"), UNIX_MS)
+        self.assertEqual(to_unix_ms("2024-03-01T13:00:00+01:00"), UNIX_MS)
+        # Naive times are UTC
+        self.assertEqual(to_unix_ms("2024-03-01T12:00:00"), UNIX_MS)
+
+    def test_missing_values(self):
+        for value in (None, 0, -5, "", "not a date", True, [], {}):
+            with self.subTest(value=value):
+                self.assertIsNone(to_unix_ms(value))
+
+
+class ClockSkewTests(unittest.TestCase):
+
+    def tearDown(self):
+        set_clock_skew(0)
+
+    def test_small_skew_is_ignored(self):
+        server_time = datetime.fromtimestamp(UNIX_SECONDS + 5, timezone.utc)
+        self.assertEqual(measure_clock_skew(server_time, UNIX_SECONDS - 1, UNIX_SECONDS + 1), 0)
+
+    def test_server_ahead(self):
+        server_time = datetime.fromtimestamp(UNIX_SECONDS + 120, timezone.utc)
+        self.assertEqual(measure_clock_skew(server_time, UNIX_SECONDS - 1, UNIX_SECONDS + 1), 120000)
+
+    def test_server_behind(self):
+        server_time = datetime.fromtimestamp(UNIX_SECONDS - 3600, timezone.utc)
+        self.assertEqual(measure_clock_skew(server_time, UNIX_SECONDS, UNIX_SECONDS), -3600000)
+
+    def test_threshold_is_inclusive(self):
+        server_time = datetime.fromtimestamp(UNIX_SECONDS + CLOCK_SKEW_THRESHOLD_MS / 1000,
+                                             timezone.utc)
+        self.assertEqual(measure_clock_skew(server_time, UNIX_SECONDS, UNIX_SECONDS),
+                         CLOCK_SKEW_THRESHOLD_MS)
+
+    def test_to_datetime_corrects_for_skew(self):
+        self.assertEqual(to_datetime(UNIX_MS), datetime.fromtimestamp(UNIX_SECONDS))
+        set_clock_skew(120000)
+        self.assertEqual(timestamps.get_clock_skew(), 120000)
+        self.assertEqual(to_datetime(UNIX_MS), datetime.fromtimestamp(UNIX_SECONDS - 120))
+
+    def test_to_datetime_missing(self):
+        self.assertIsNone(to_datetime(None))
+
+
+class JustNowTests(unittest.TestCase):
+
+    def setUp(self):
+        self.now = datetime(2024, 3, 1, 12, 0, 0)
+        self.time_format = TimeFormat(clock='24h')
+
+    def test_recent_message_is_now(self):
+        self.assertEqual(format_time(self.now - timedelta(seconds=30), self.time_format,
+                                     now=self.now), "Now")
+
+    def test_slightly_future_message_is_now(self):
+        self.assertEqual(format_time(self.now + timedelta(seconds=10), self.time_format,
+                                     now=self.now), "Now")
+
+    def test_far_future_message_shows_time(self):
+        self.assertEqual(format_time(self.now + timedelta(minutes=5), self.time_format,
+                                     now=self.now), "12:05")
+
+    def test_minutes_ago(self):
+        self.assertEqual(format_time(self.now - timedelta(minutes=5), self.time_format,
+                                     now=self.now), "5 min")
+
+
+class DaySeparatorTests(unittest.TestCase):
+
+    def setUp(self):
+        self.time_format = TimeFormat(date_format='iso')
+        # A Friday
+        self.today = datetime(2026, 10, 16).date()
+
+    def test_recent_days(self):
+        self.assertEqual(format_day(self.today, self.time_format, self.today), "Today")
+        self.assertEqual(format_day(self.today - timedelta(days=1), self.time_format, self.today), "Yesterday")
+        self.assertEqual(format_day(self.today - timedelta(days=3), self.time_format, self.today), "Tuesday")
+
+    def test_older_days_show_the_date(self):
+        self.assertEqual(format_day(self.today - timedelta(days=7), self.time_format, self.today),
+                         "Friday, 2026-10-09")
+
+
+if __name__ == '__main__':
+    unittest.main()